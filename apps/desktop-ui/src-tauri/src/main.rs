@@ -1,62 +1,553 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::State;
-use tokio::sync::Mutex;
+use mcp_server::api::{InitStatus, SiloHandle};
+use mcp_server::cleanup::{CleanupArgs, CleanupReport};
+use mcp_server::disk::{DiskReport, DiskReportArgs};
+use mcp_server::quick_search::{QuickSearch, QuickSearchArgs};
+use mcp_server::timeline::{Granularity, Timeline, TimelineArgs, TimelineField};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
 
-struct AppCtx {
-    app: Mutex<Option<mcp_server::api::SiloApp>>,
-}
+/// Emitted with the `InitStatus` when background initialization finishes or fails.
+const INIT_EVENT: &str = "silo://init";
 
-impl AppCtx {
-    async fn get_or_init(&self) -> Result<mcp_server::api::SiloApp, String> {
-        let mut guard = self.app.lock().await;
-        if let Some(app) = guard.as_ref() {
-            // Clone by reusing the shared state (cheap)
-            return Ok(mcp_server::api::SiloApp {
-                state: app.state.clone(),
-            });
-        }
-        let app = mcp_server::api::SiloApp::new().await?;
-        let clone = mcp_server::api::SiloApp {
-            state: app.state.clone(),
-        };
-        *guard = Some(app);
-        Ok(clone)
-    }
+/// Whether the backend is ready (the UI shows "initializing database…" until it is).
+#[tauri::command]
+fn init_status(handle: State<'_, SiloHandle>) -> InitStatus {
+    handle.status()
 }
 
 #[tauri::command]
-async fn get_config(state: State<'_, AppCtx>) -> Result<serde_json::Value, String> {
-    let app = state.get_or_init().await?;
+async fn get_config(state: State<'_, SiloHandle>) -> Result<serde_json::Value, String> {
+    let app = state.get().await?;
     Ok(app.get_config().await)
 }
 
 #[tauri::command]
 async fn index_home(
-    state: State<'_, AppCtx>,
+    state: State<'_, SiloHandle>,
     max_files: Option<u64>,
     concurrency: Option<usize>,
 ) -> Result<mcp_server::indexer::IndexSummary, String> {
-    let app = state.get_or_init().await?;
+    let app = state.get().await?;
     app.index_home(max_files, concurrency).await
 }
 
+/// Dry run before indexing: new / changed / deleted files versus the catalog.
+#[tauri::command]
+async fn index_diff(
+    state: State<'_, SiloHandle>,
+    max_samples: Option<usize>,
+) -> Result<mcp_server::index_diff::IndexDiff, String> {
+    let app = state.get().await?;
+    app.index_diff(max_samples).await
+}
+
+/// Per-root coverage: eligible vs indexed / failed / evicted / pending files, with percentages.
+#[tauri::command]
+async fn index_coverage(
+    state: State<'_, SiloHandle>,
+    max_samples: Option<usize>,
+) -> Result<mcp_server::coverage::Coverage, String> {
+    let app = state.get().await?;
+    app.index_coverage(max_samples).await
+}
+
+#[tauri::command]
+async fn index_messages(
+    state: State<'_, SiloHandle>,
+    full: Option<bool>,
+) -> Result<mcp_server::messages::MessagesIndexSummary, String> {
+    let app = state.get().await?;
+    app.index_messages(full.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn index_screenshots(
+    state: State<'_, SiloHandle>,
+) -> Result<mcp_server::screenshots::ScreenshotPass, String> {
+    let app = state.get().await?;
+    app.index_screenshots().await
+}
+
+#[tauri::command]
+async fn import_chat_export(
+    state: State<'_, SiloHandle>,
+    path: Option<String>,
+    format: Option<mcp_server::chat_export::ChatExportFormat>,
+    name: Option<String>,
+) -> Result<mcp_server::chat_export::ChatImportSummary, String> {
+    let app = state.get().await?;
+    app.import_chat_export(mcp_server::chat_export::ImportChatExportArgs { path, format, name })
+        .await
+}
+
+/// Index size vs `storage.max_index_bytes`, evicting over the cap (same as `silo_index_budget`).
+#[tauri::command]
+async fn index_budget(
+    state: State<'_, SiloHandle>,
+    dry_run: Option<bool>,
+) -> Result<mcp_server::budget::EvictionReport, String> {
+    let app = state.get().await?;
+    app.index_budget(dry_run.unwrap_or(true)).await
+}
+
+/// Collections and their lock state (same as `silo_collections`).
+#[tauri::command]
+async fn collections(state: State<'_, SiloHandle>) -> Result<Vec<mcp_server::access::CollectionStatus>, String> {
+    let app = state.get().await?;
+    Ok(app.collections().await)
+}
+
+/// Unlock a locked collection for a while (the UI is the user, so this is allowed here).
+#[tauri::command]
+async fn unlock_collection(
+    state: State<'_, SiloHandle>,
+    name: String,
+    secs: Option<u64>,
+) -> Result<mcp_server::access::CollectionStatus, String> {
+    let app = state.get().await?;
+    app.unlock_collection(name, secs).await
+}
+
+#[tauri::command]
+async fn lock_collection(state: State<'_, SiloHandle>, name: Option<String>) -> Result<Vec<String>, String> {
+    let app = state.get().await?;
+    app.lock_collection(name).await
+}
+
+/// MCP tool calls on sensitive paths waiting for approval.
+#[tauri::command]
+async fn consent_pending(
+    state: State<'_, SiloHandle>,
+) -> Result<Vec<mcp_server::consent::ConsentRequest>, String> {
+    let app = state.get().await?;
+    Ok(app.consent_pending())
+}
+
+/// Approve or deny one; only the UI can do this (there is no MCP tool for it).
+#[tauri::command]
+async fn consent_respond(
+    state: State<'_, SiloHandle>,
+    consent_id: String,
+    approve: bool,
+) -> Result<mcp_server::consent::ConsentRequest, String> {
+    let app = state.get().await?;
+    app.consent_respond(&consent_id, approve)
+}
+
+/// Export config and tags to an archive (same as `silo_backup_settings`).
+#[tauri::command]
+async fn backup_settings(
+    state: State<'_, SiloHandle>,
+    path: Option<String>,
+) -> Result<mcp_server::backup::BackupSummary, String> {
+    let app = state.get().await?;
+    app.backup_settings(path).await
+}
+
+/// Compact the knowledge base and prune old versions (same as `silo_maintenance`).
+#[tauri::command]
+async fn maintenance(
+    state: State<'_, SiloHandle>,
+    keep_versions_hours: Option<u64>,
+) -> Result<mcp_server::maintenance::MaintenanceReport, String> {
+    let app = state.get().await?;
+    app.maintenance(keep_versions_hours).await
+}
+
+/// Check a sample of indexed files for drift; `repair` re-ingests them (same as `silo_verify`).
+#[tauri::command]
+async fn verify(
+    state: State<'_, SiloHandle>,
+    sample: Option<usize>,
+    paths: Option<Vec<String>>,
+    repair: Option<bool>,
+) -> Result<mcp_server::verify::VerifyReport, String> {
+    let app = state.get().await?;
+    app.verify(mcp_server::verify::VerifyArgs {
+        sample,
+        paths: paths.unwrap_or_default(),
+        repair: repair.unwrap_or(false),
+    })
+    .await
+}
+
+/// Embedding model state and downloads (same data as `silo_models_list`).
+#[tauri::command]
+async fn models_list(state: State<'_, SiloHandle>) -> Result<mcp_server::models::ModelList, String> {
+    let app = state.get().await?;
+    Ok(app.models_list().await)
+}
+
+#[tauri::command]
+async fn models_pull(
+    state: State<'_, SiloHandle>,
+    model: Option<String>,
+) -> Result<mcp_server::models::PullResult, String> {
+    let app = state.get().await?;
+    app.models_pull(model).await
+}
+
+#[tauri::command]
+async fn models_remove(
+    state: State<'_, SiloHandle>,
+    model: Option<String>,
+) -> Result<mcp_server::models::RemoveResult, String> {
+    let app = state.get().await?;
+    app.models_remove(model).await
+}
+
+/// Open a search hit with its default application.
+#[tauri::command]
+async fn open_path(state: State<'_, SiloHandle>, path: String) -> Result<(), String> {
+    let app = state.get().await?;
+    app.open_path(path).await
+}
+
+/// Show a search hit in Finder (Explorer / the file manager elsewhere).
+#[tauri::command]
+async fn reveal_in_finder(state: State<'_, SiloHandle>, path: String) -> Result<(), String> {
+    let app = state.get().await?;
+    app.reveal_path(path).await
+}
+
+/// Small preview of a search hit (`page` selects the PDF page).
+#[tauri::command]
+async fn preview(
+    state: State<'_, SiloHandle>,
+    path: String,
+    page: Option<i64>,
+) -> Result<mcp_server::preview::Preview, String> {
+    let app = state.get().await?;
+    app.preview(path, page).await
+}
+
+/// Hotkey popup search: name matches plus whatever semantic hits arrive within `budget_ms`.
+#[tauri::command]
+async fn quick_search(
+    state: State<'_, SiloHandle>,
+    query: String,
+    limit: Option<usize>,
+    budget_ms: Option<u64>,
+) -> Result<QuickSearch, String> {
+    let app = state.get().await?;
+    app.quick_search(QuickSearchArgs {
+        query,
+        limit,
+        budget_ms,
+    })
+    .await
+}
+
+#[tauri::command]
+fn hide_quick_search(app: AppHandle) {
+    if let Some(w) = app.get_webview_window(QUICK_SEARCH_WINDOW) {
+        let _ = w.hide();
+    }
+}
+
+const QUICK_SEARCH_WINDOW: &str = "quick-search";
+/// Default popup shortcut; override with `SILO_QUICK_SEARCH_SHORTCUT` (e.g. "CmdOrCtrl+Shift+Space").
+const DEFAULT_QUICK_SEARCH_SHORTCUT: &str = "Alt+Space";
+
+/// Show the popup (created on first use) or hide it when it is already focused.
+fn toggle_quick_search(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window(QUICK_SEARCH_WINDOW) {
+        if w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false) {
+            let _ = w.hide();
+        } else {
+            let _ = w.show();
+            let _ = w.set_focus();
+        }
+        return;
+    }
+    let built = WebviewWindowBuilder::new(app, QUICK_SEARCH_WINDOW, WebviewUrl::App("quick.html".into()))
+        .title("Silo")
+        .inner_size(640.0, 380.0)
+        .center()
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build();
+    if let Err(e) = built {
+        eprintln!("failed to open quick search window: {e}");
+    }
+}
+
+#[cfg(desktop)]
+fn register_quick_search_shortcut(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+    let spec = std::env::var("SILO_QUICK_SEARCH_SHORTCUT")
+        .unwrap_or_else(|_| DEFAULT_QUICK_SEARCH_SHORTCUT.to_string());
+    let shortcut: Shortcut = spec
+        .parse()
+        .map_err(|e| format!("invalid quick search shortcut {spec:?}: {e}"))?;
+    app.handle().plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(move |app, pressed, event| {
+                if pressed == &shortcut && event.state() == ShortcutState::Pressed {
+                    toggle_quick_search(app);
+                }
+            })
+            .build(),
+    )?;
+    // Another app may own the shortcut; the main window still works without it.
+    if let Err(e) = app.global_shortcut().register(shortcut) {
+        eprintln!("failed to register quick search shortcut {spec}: {e}");
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn search(
-    state: State<'_, AppCtx>,
+    state: State<'_, SiloHandle>,
     query: String,
     top_k: Option<usize>,
+    min_score: Option<f32>,
+    entity: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<serde_json::Value, String> {
+    let app = state.get().await?;
+    app.search(query, top_k.unwrap_or(5), min_score, entity, tags.unwrap_or_default())
+        .await
+}
+
+/// Event channel for streaming `ask` output. Payload: `{ request_id, event }` where `event` is an
+/// `AskEvent` (`citations` first, then `token`s, then `done` or `error`).
+const ASK_EVENT: &str = "silo://ask";
+
+#[derive(Clone, serde::Serialize)]
+struct AskEventPayload {
+    request_id: String,
+    event: mcp_server::ask::AskEvent,
+}
+
+/// Chat with your files: retrieval + local LLM, streamed to the frontend as `silo://ask` events.
+/// The final answer (with citations) is also returned when generation completes.
+#[tauri::command]
+async fn ask(
+    app_handle: AppHandle,
+    state: State<'_, SiloHandle>,
+    request_id: String,
+    question: String,
+    top_k: Option<usize>,
+) -> Result<mcp_server::ask::AskAnswer, String> {
+    let app = state.get().await?;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let emitter = app_handle.clone();
+    let forward = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let _ = emitter.emit(
+                ASK_EVENT,
+                AskEventPayload {
+                    request_id: request_id.clone(),
+                    event,
+                },
+            );
+        }
+    });
+
+    let res = app.ask_stream(question, top_k, tx).await;
+    let _ = forward.await;
+    res
+}
+
+#[tauri::command]
+async fn chat_send(
+    state: State<'_, SiloHandle>,
+    session_id: Option<String>,
+    message: String,
+    top_k: Option<usize>,
+) -> Result<mcp_server::chat::ChatReply, String> {
+    let app = state.get().await?;
+    app.chat(session_id, message, top_k).await
+}
+
+#[tauri::command]
+async fn chat_history(
+    state: State<'_, SiloHandle>,
+    session_id: String,
+) -> Result<mcp_server::chat::ChatHistory, String> {
+    let app = state.get().await?;
+    app.chat_history(&session_id)
+        .await
+        .ok_or_else(|| format!("Unknown chat session: {session_id}"))
+}
+
+#[tauri::command]
+async fn chat_reset(state: State<'_, SiloHandle>, session_id: String) -> Result<bool, String> {
+    let app = state.get().await?;
+    Ok(app.chat_reset(&session_id))
+}
+
+/// Search-as-you-type: file name/path keywords only (no embeddings), cheap enough per keystroke.
+#[tauri::command]
+async fn search_names(
+    state: State<'_, SiloHandle>,
+    query: String,
+    limit: Option<usize>,
 ) -> Result<serde_json::Value, String> {
-    let app = state.get_or_init().await?;
-    app.search(query, top_k.unwrap_or(5)).await
+    let app = state.get().await?;
+    Ok(app.search_names(query, limit.unwrap_or(10)).await)
+}
+
+/// Storage insights page: largest folders/files, per-extension sizes, growth since last report.
+#[tauri::command]
+async fn disk_report(
+    state: State<'_, SiloHandle>,
+    max_depth: Option<usize>,
+    top_n: Option<usize>,
+    include_excluded: Option<bool>,
+) -> Result<DiskReport, String> {
+    let app = state.get().await?;
+    app.disk_report(DiskReportArgs {
+        max_depth,
+        top_n,
+        include_excluded: include_excluded.unwrap_or(false),
+        max_files: None,
+    })
+    .await
+}
+
+/// Cleanup candidates: stale large files, duplicates, cache directories (read-only).
+#[tauri::command]
+async fn cleanup_suggestions(
+    state: State<'_, SiloHandle>,
+    min_size_mb: Option<u64>,
+    untouched_months: Option<u32>,
+    min_cache_mb: Option<u64>,
+) -> Result<CleanupReport, String> {
+    let app = state.get().await?;
+    app.cleanup_suggestions(CleanupArgs {
+        min_size_mb,
+        untouched_months,
+        min_cache_mb,
+        include_excluded: None,
+        top_n: None,
+    })
+    .await
+}
+
+/// Start background tagging of untagged documents; progress is reported by `list_tags`.
+#[tauri::command]
+async fn tag_documents(state: State<'_, SiloHandle>, retag: Option<bool>) -> Result<bool, String> {
+    let app = state.get().await?;
+    app.tag_documents(retag.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn list_tags(
+    state: State<'_, SiloHandle>,
+    prefix: Option<String>,
+    limit: Option<usize>,
+) -> Result<mcp_server::tagging::TagList, String> {
+    let app = state.get().await?;
+    Ok(app.list_tags(prefix, limit.unwrap_or(100)).await)
+}
+
+/// Activity timeline: indexed files bucketed by modified/created/indexed date.
+#[tauri::command]
+async fn timeline(
+    state: State<'_, SiloHandle>,
+    granularity: Option<Granularity>,
+    field: Option<TimelineField>,
+    from: Option<serde_json::Value>,
+    to: Option<serde_json::Value>,
+    top_n: Option<usize>,
+) -> Result<Timeline, String> {
+    let app = state.get().await?;
+    app.timeline(TimelineArgs {
+        granularity: granularity.unwrap_or_default(),
+        field: field.unwrap_or_default(),
+        from,
+        to,
+        top_n,
+        max_buckets: None,
+    })
+    .await
+}
+
+/// Show core notifications (index runs finished, sources failing) as system notifications.
+async fn show_notifications(
+    app: AppHandle,
+    mut rx: tokio::sync::broadcast::Receiver<mcp_server::notify::Notification>,
+) {
+    use tauri_plugin_notification::NotificationExt;
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        match rx.recv().await {
+            Ok(n) => {
+                if let Err(e) = app.notification().builder().title(&n.title).body(&n.message).show() {
+                    eprintln!("failed to show notification: {e}");
+                }
+            }
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
 }
 
 fn main() {
+    tauri::async_runtime::block_on(mcp_server::logging::init_default());
     tauri::Builder::default()
-        .manage(AppCtx {
-            app: Mutex::new(None),
+        .plugin(tauri_plugin_notification::init())
+        .manage(SiloHandle::new())
+        .setup(|app| {
+            // Open the database in the background so the first command does not pay for it.
+            let handle = app.state::<SiloHandle>().inner().clone();
+            let events = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let app = handle.get().await;
+                let _ = events.emit(INIT_EVENT, handle.status());
+                if let Ok(app) = app {
+                    show_notifications(events, app.notifications()).await;
+                }
+            });
+            #[cfg(desktop)]
+            register_quick_search_shortcut(app)?;
+            Ok(())
         })
-        .invoke_handler(tauri::generate_handler![get_config, index_home, search])
+        .invoke_handler(tauri::generate_handler![
+            init_status,
+            get_config,
+            index_home,
+            index_diff,
+            index_coverage,
+            index_messages,
+            import_chat_export,
+            index_screenshots,
+            search,
+            search_names,
+            quick_search,
+            hide_quick_search,
+            index_budget,
+            maintenance,
+            verify,
+            backup_settings,
+            consent_pending,
+            consent_respond,
+            collections,
+            unlock_collection,
+            lock_collection,
+            models_list,
+            models_pull,
+            models_remove,
+            open_path,
+            reveal_in_finder,
+            preview,
+            ask,
+            chat_send,
+            chat_history,
+            chat_reset,
+            timeline,
+            tag_documents,
+            list_tags,
+            disk_report,
+            cleanup_suggestions
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
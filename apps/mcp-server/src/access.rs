@@ -0,0 +1,235 @@
+//! Collection access policies (`collections` in the config).
+//!
+//! A collection is a named set of folders (or files). `open` collections behave like everything
+//! else; `locked` ones are hidden from search, answers (so their text never reaches an LLM), file
+//! reads and any tool output listing their paths, until `silo_unlock_collection` opens them for
+//! `unlock_secs`. Unlocks live in memory, so a restart locks everything again.
+//!
+//! The built-in agent cannot unlock: the unlock tool is dispatched by `tools::call_tool` only,
+//! never by the agent's dispatcher.
+
+use crate::config::CollectionAccess;
+use crate::errors::ToolError;
+use crate::predicate::Predicate;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Deserialize)]
+pub struct UnlockArgs {
+    pub name: String,
+    /// Unlock for this long instead of the collection's `unlock_secs` (never longer).
+    #[serde(default)]
+    pub secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LockArgs {
+    /// Collection to lock again (default: all).
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionStatus {
+    pub name: String,
+    pub access: CollectionAccess,
+    pub paths: Vec<String>,
+    /// Readable right now (always for open collections).
+    pub readable: bool,
+    /// Seconds until an unlocked collection locks again.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlocked_for_secs: Option<u64>,
+}
+
+/// Paths of the collections that are locked right now.
+#[derive(Debug, Clone, Default)]
+pub struct LockedPaths {
+    /// (collection name, path prefix)
+    prefixes: Vec<(String, PathBuf)>,
+}
+
+impl LockedPaths {
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    /// Locked collection containing `path`, if any.
+    pub fn collection_of(&self, path: &str) -> Option<&str> {
+        let path = Path::new(path);
+        self.prefixes
+            .iter()
+            .find(|(_, prefix)| path.starts_with(prefix))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn check(&self, path: &str) -> Result<(), ToolError> {
+        match self.collection_of(path) {
+            Some(name) => Err(ToolError::locked_collection(path, name)),
+            None => Ok(()),
+        }
+    }
+
+    /// DB filter excluding locked paths (`None` when nothing is locked).
+    pub fn search_filter(&self) -> Option<Predicate> {
+        if self.is_empty() {
+            return None;
+        }
+        let locked = Predicate::any(self.prefixes.iter().map(|(_, prefix)| path_prefix_filter(prefix)));
+        Some(locked.not())
+    }
+
+    /// Distinguishes cached results computed under different lock states.
+    pub fn cache_key(&self) -> String {
+        let mut names: Vec<&str> = self.prefixes.iter().map(|(n, _)| n.as_str()).collect();
+        names.dedup();
+        names.join(",")
+    }
+
+    /// Drop array entries that are (or have a `path` field) under a locked collection, and note
+    /// how many were hidden in a top-level `hidden_by_lock` field.
+    pub fn scrub(&self, value: &mut Value) {
+        if self.is_empty() {
+            return;
+        }
+        let hidden = self.scrub_inner(value);
+        if let Some(obj) = value.as_object_mut().filter(|_| hidden > 0) {
+            obj.insert("hidden_by_lock".to_string(), json!(hidden));
+        }
+    }
+
+    fn scrub_inner(&self, value: &mut Value) -> usize {
+        match value {
+            Value::Array(items) => {
+                let before = items.len();
+                items.retain(|item| {
+                    let path = match item {
+                        Value::String(s) => Some(s.as_str()),
+                        Value::Object(o) => o.get("path").and_then(Value::as_str),
+                        _ => None,
+                    };
+                    path.is_none_or(|p| self.collection_of(p).is_none())
+                });
+                let removed = before - items.len();
+                removed + items.iter_mut().map(|v| self.scrub_inner(v)).sum::<usize>()
+            }
+            Value::Object(obj) => obj.values_mut().map(|v| self.scrub_inner(v)).sum(),
+            _ => 0,
+        }
+    }
+}
+
+/// Chunks of the file `prefix` or of files under the folder `prefix`.
+pub fn path_prefix_filter(prefix: &Path) -> Predicate {
+    let prefix = prefix.to_string_lossy();
+    let dir = format!("{}{MAIN_SEPARATOR}", prefix.trim_end_matches(MAIN_SEPARATOR));
+    Predicate::eq("path", prefix.as_ref()).or(Predicate::starts_with("path", &dir))
+}
+
+/// MCP tool entrypoint (`silo_collections`).
+pub async fn collections_tool(state: &SharedState, _args: Value) -> Result<Value, ToolError> {
+    let list = collections(state).await;
+    serde_json::to_value(list).map_err(|e| format!("failed to serialize collections: {e}").into())
+}
+
+/// MCP tool entrypoint (`silo_unlock_collection`).
+pub async fn unlock_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: UnlockArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let status = unlock(state, &args.name, args.secs).await?;
+    serde_json::to_value(status).map_err(|e| format!("failed to serialize collection: {e}").into())
+}
+
+/// MCP tool entrypoint (`silo_lock_collection`).
+pub async fn lock_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: LockArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let locked = lock(state, args.name.as_deref()).await?;
+    Ok(json!({ "locked": locked }))
+}
+
+/// Locked collections that are not currently unlocked.
+pub async fn locked_paths(state: &SharedState) -> LockedPaths {
+    let cfg = state.config.read().await;
+    let now = Instant::now();
+    let mut unlocks = state.unlocks.lock().unwrap_or_else(|e| e.into_inner());
+    unlocks.retain(|_, until| *until > now);
+    let prefixes = cfg
+        .collections
+        .iter()
+        .filter(|c| c.access == CollectionAccess::Locked && !unlocks.contains_key(&c.name))
+        .flat_map(|c| {
+            c.paths
+                .iter()
+                .map(|p| (c.name.clone(), expand_tilde(&p.to_string_lossy())))
+        })
+        .collect();
+    LockedPaths { prefixes }
+}
+
+pub async fn collections(state: &SharedState) -> Vec<CollectionStatus> {
+    let cfg = state.config.read().await;
+    let now = Instant::now();
+    let unlocks = state.unlocks.lock().unwrap_or_else(|e| e.into_inner());
+    cfg.collections
+        .iter()
+        .map(|c| {
+            let unlocked_for = unlocks
+                .get(&c.name)
+                .filter(|until| **until > now)
+                .map(|until| until.duration_since(now).as_secs());
+            CollectionStatus {
+                name: c.name.clone(),
+                access: c.access,
+                paths: c.paths.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+                readable: c.access == CollectionAccess::Open || unlocked_for.is_some(),
+                unlocked_for_secs: unlocked_for.filter(|_| c.access == CollectionAccess::Locked),
+            }
+        })
+        .collect()
+}
+
+/// Open a locked collection for `secs` (at most its `unlock_secs`).
+pub async fn unlock(state: &SharedState, name: &str, secs: Option<u64>) -> Result<CollectionStatus, String> {
+    let (access, max_secs) = {
+        let cfg = state.config.read().await;
+        let Some(c) = cfg.collections.iter().find(|c| c.name == name) else {
+            return Err(format!("Unknown collection \"{name}\". See `silo_collections`"));
+        };
+        (c.access, c.unlock_secs)
+    };
+    if access == CollectionAccess::Locked {
+        let secs = secs.unwrap_or(max_secs).min(max_secs);
+        let until = Instant::now() + Duration::from_secs(secs);
+        state
+            .unlocks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), until);
+        tracing::info!("Collection \"{name}\" unlocked for {secs}s");
+    }
+    collections(state)
+        .await
+        .into_iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| format!("Unknown collection \"{name}\""))
+}
+
+/// Lock `name` (or every collection) again before its unlock expires. Returns the names locked.
+pub async fn lock(state: &SharedState, name: Option<&str>) -> Result<Vec<String>, String> {
+    let known = match name {
+        Some(name) => state.config.read().await.collections.iter().any(|c| c.name == name),
+        None => true,
+    };
+    if !known {
+        return Err(format!("Unknown collection \"{}\". See `silo_collections`", name.unwrap_or_default()));
+    }
+    let mut unlocks = state.unlocks.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(match name {
+        Some(name) => {
+            unlocks.remove(name);
+            vec![name.to_string()]
+        }
+        None => unlocks.drain().map(|(name, _)| name).collect(),
+    })
+}
@@ -1,5 +1,6 @@
+use crate::errors::ToolError;
 use crate::state::SharedState;
-use crate::tools::{call_tool_no_agent, tool_definitions, ToolCallParams};
+use crate::tools::{available_tool_definitions, call_tool_no_agent, ToolCallParams};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
@@ -17,33 +18,22 @@ struct AgentPlan {
     arguments: Value,
 }
 
-pub async fn agent_tool(state: &SharedState, args: Value) -> Result<Value, String> {
-    let args: AgentArgs = serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {e}"))?;
+pub async fn agent_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: AgentArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
 
     // Expose *all* tools except the agent itself (avoid recursion).
-    let tools = tool_definitions()
+    let tools = available_tool_definitions(state)
         .into_iter()
         .filter(|t| t.name != "silo_agent")
         .collect::<Vec<_>>();
 
-    let prompt = format!(
-        r#"You are a local-first desktop assistant for the Silo app.
-
-You MUST respond with a SINGLE LINE of JSON only (no markdown, no explanation).
-The JSON must have this exact shape:
-{{"tool":"<tool_name>","arguments":{{...}}}}
-
-Pick the ONE best tool to accomplish the user task. If the task cannot be done with the tools,
-return: {{"tool":"none","arguments":{{"reason":"..."}}}}
-
-Available tools (name + description + JSON schema):
-{}
-
-User task: {}
-"#,
-        serde_json::to_string_pretty(&tools).unwrap_or_else(|_| "[]".to_string()),
-        args.task
-    );
+    let tools_json = serde_json::to_string_pretty(&tools).unwrap_or_else(|_| "[]".to_string());
+    let prompt = state
+        .prompts
+        .read()
+        .await
+        .agent
+        .render(&[("tools", &tools_json), ("task", &args.task)]);
 
     let raw = state.llm.generate(prompt).await?;
     let raw = raw.trim();
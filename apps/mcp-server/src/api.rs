@@ -1,23 +1,128 @@
+use crate::coverage::{Coverage, CoverageArgs};
+use crate::index_diff::{IndexDiff, IndexDiffArgs};
 use crate::indexer::{index_roots, IndexOptions, IndexSummary};
+use crate::library::LibraryPass;
+use crate::newsletters::NewsletterPass;
+use crate::screenshots::ScreenshotPass;
 use crate::state::AppState;
-use crate::{database::Database, state::SharedState};
+use crate::access::CollectionStatus;
+use crate::ask::{AskAnswer, AskEvent};
+use crate::backup::BackupSummary;
+use crate::budget::EvictionReport;
+use crate::chat::{ChatHistory, ChatReply};
+use crate::consent::ConsentRequest;
+use crate::maintenance::MaintenanceReport;
+use crate::chat_export::{ChatImportSummary, ImportChatExportArgs};
+use crate::cleanup::{CleanupArgs, CleanupReport};
+use crate::config::default_data_dir;
+use crate::database::Database;
+use crate::disk::{DiskReport, DiskReportArgs};
+use crate::entities::{EntityKind, EntitySummary};
+use crate::messages::{IndexMessagesArgs, MessagesIndexSummary};
+use crate::models::{ModelList, PullResult, RemoveResult};
+use crate::quick_search::{QuickSearch, QuickSearchArgs};
+use crate::state::SharedState;
+use crate::tagging::TagList;
+use crate::timeline::{Timeline, TimelineArgs};
+use crate::verify::{VerifyArgs, VerifyReport};
 use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 /// High-level API used by the desktop UI (Tauri) without going through MCP stdio.
+///
+/// Cloning is cheap (shared state); use `SiloHandle` to initialize it once across windows.
+#[derive(Clone)]
 pub struct SiloApp {
     pub state: SharedState,
 }
 
+/// Where `SiloHandle` initialization is, for an "initializing database…" indicator.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum InitStatus {
+    NotStarted,
+    Initializing,
+    Ready,
+    /// The last attempt failed; the next `get` retries.
+    Failed { error: String },
+}
+
+/// Cheaply cloneable, lazily initialized `SiloApp` shared by every command and window.
+///
+/// Concurrent callers during first init wait for the same `SiloApp::new` instead of racing to
+/// create their own.
+#[derive(Clone)]
+pub struct SiloHandle {
+    inner: Arc<HandleInner>,
+}
+
+struct HandleInner {
+    app: tokio::sync::OnceCell<SiloApp>,
+    status: std::sync::Mutex<InitStatus>,
+}
+
+impl Default for SiloHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SiloHandle {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(HandleInner {
+                app: tokio::sync::OnceCell::new(),
+                status: std::sync::Mutex::new(InitStatus::NotStarted),
+            }),
+        }
+    }
+
+    pub fn status(&self) -> InitStatus {
+        self.inner.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// The app, initializing it on first use.
+    pub async fn get(&self) -> Result<SiloApp, String> {
+        self.inner
+            .app
+            .get_or_try_init(|| async {
+                self.set_status(InitStatus::Initializing);
+                match SiloApp::new().await {
+                    Ok(app) => {
+                        self.set_status(InitStatus::Ready);
+                        Ok(app)
+                    }
+                    Err(error) => {
+                        self.set_status(InitStatus::Failed { error: error.clone() });
+                        Err(error)
+                    }
+                }
+            })
+            .await
+            .cloned()
+    }
+
+    fn set_status(&self, status: InitStatus) {
+        *self.inner.status.lock().unwrap_or_else(|e| e.into_inner()) = status;
+    }
+}
+
 impl SiloApp {
     pub async fn new() -> Result<Self, String> {
-        let db = Database::new("./data")
+        let db = Database::open_configured(default_data_dir())
             .await
             .map_err(|e| format!("db init failed: {e}"))?;
         let state = AppState::new(Arc::new(db))
             .await
             .map_err(|e| format!("state init failed: {e}"))?;
+        crate::screenshots::spawn_watcher(&state);
+        crate::newsletters::spawn_watcher(&state);
+        crate::maintenance::spawn_scheduler(&state);
+        // MCP clients started while the app runs proxy through it instead of opening the DB.
+        if state.db.is_enabled() {
+            crate::daemon::spawn_listener(&state);
+        }
         Ok(Self { state })
     }
 
@@ -25,6 +130,11 @@ impl SiloApp {
         self.state.get_config_json().await
     }
 
+    /// Index and source failure notifications (`crate::notify`), e.g. for system notifications.
+    pub fn notifications(&self) -> tokio::sync::broadcast::Receiver<crate::notify::Notification> {
+        self.state.notifier.subscribe()
+    }
+
     pub async fn index_home(&self, max_files: Option<u64>, concurrency: Option<usize>) -> Result<IndexSummary, String> {
         let Some(policy) = self.state.filesystem_policy().await else {
             return Err("No filesystem policy configured".to_string());
@@ -34,32 +144,233 @@ impl SiloApp {
             max_files,
             concurrency: concurrency.unwrap_or(2),
             max_sample_errors: 20,
+            progress: Some(self.state.index_progress.clone()),
+            metrics: Some(self.state.metrics.clone()),
+            boilerplate: Some(self.state.boilerplate.clone()),
         };
-        Ok(index_roots(
+        let started = crate::catalog::now_epoch_secs();
+        let mut summary = index_roots(
             roots,
             Arc::new(policy),
             self.state.db.clone(),
             self.state.embedder.clone(),
+            self.state.entities.clone(),
+            self.state.catalog.clone(),
             opts,
         )
-        .await)
+        .await;
+        self.state.index_changed();
+        self.state.save_catalog().await;
+        summary.eviction = crate::budget::after_index(&self.state).await;
+        crate::tagging::after_index(&self.state).await;
+        crate::notify::index_finished(&self.state, &summary, started).await;
+        Ok(summary)
     }
 
-    pub async fn search(&self, query: String, top_k: usize) -> Result<serde_json::Value, String> {
-        let qvec = self
-            .state
-            .embedder
-            .embed_query(query)
-            .await
-            .map_err(|e| format!("Embedding failed: {e}"))?;
-        let hits = self
-            .state
-            .db
-            .search_chunks_by_vector(&qvec, top_k.clamp(1, 50))
-            .await
-            .map_err(|e| format!("DB search failed: {e}"))?;
-        Ok(serde_json::json!({ "hits": hits }))
+    /// Retrieval + local LLM answer, streaming `AskEvent`s (citations first, then tokens).
+    pub async fn ask_stream(
+        &self,
+        question: String,
+        top_k: Option<usize>,
+        events: tokio::sync::mpsc::UnboundedSender<AskEvent>,
+    ) -> Result<AskAnswer, String> {
+        crate::ask::ask_streaming(&self.state, question, top_k, None, events).await
+    }
+
+    /// Send a chat message (new session when `session_id` is None).
+    pub async fn chat(
+        &self,
+        session_id: Option<String>,
+        message: String,
+        top_k: Option<usize>,
+    ) -> Result<ChatReply, String> {
+        Ok(crate::chat::chat(&self.state, session_id, message, top_k).await?)
+    }
+
+    pub async fn chat_history(&self, session_id: &str) -> Option<ChatHistory> {
+        let locked = crate::access::locked_paths(&self.state).await;
+        self.state
+            .chats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .history(session_id, &locked)
+    }
+
+    pub fn chat_reset(&self, session_id: &str) -> bool {
+        self.state
+            .chats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .reset(session_id)
+    }
+
+    /// Keystroke-speed file name search (catalog + trigram index, no embeddings).
+    pub async fn search_names(&self, query: String, limit: usize) -> serde_json::Value {
+        crate::tools::silo_search_files(&self.state, &query, Some(limit)).await
+    }
+
+    /// Low-latency search for the hotkey popup (name matches plus semantic hits within a budget).
+    pub async fn quick_search(&self, args: QuickSearchArgs) -> Result<QuickSearch, String> {
+        crate::quick_search::quick_search(&self.state, &args).await
+    }
+
+    pub async fn search(
+        &self,
+        query: String,
+        top_k: usize,
+        min_score: Option<f32>,
+        entity: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<serde_json::Value, String> {
+        let args = crate::tools::SearchKnowledgeBaseArgs {
+            query,
+            top_k: Some(top_k),
+            min_score,
+            entity,
+            tags,
+            ..Default::default()
+        };
+        Ok(crate::tools::silo_search(&self.state, args).await?)
+    }
+
+    pub async fn search_entities(
+        &self,
+        query: String,
+        kind: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<EntitySummary>, String> {
+        let kind = kind.as_deref().map(EntityKind::parse);
+        Ok(crate::entities::search_entities(&self.state, &query, kind, limit).await?)
+    }
+
+    /// What an incremental index would do (new / changed / deleted files).
+    pub async fn index_diff(&self, max_samples: Option<usize>) -> Result<IndexDiff, String> {
+        Ok(crate::index_diff::index_diff(&self.state, &IndexDiffArgs { max_samples }).await?)
+    }
+
+    /// Eligible / indexed / failed / pending files per filesystem root.
+    pub async fn index_coverage(&self, max_samples: Option<usize>) -> Result<Coverage, String> {
+        Ok(crate::coverage::coverage(&self.state, &CoverageArgs { max_samples }).await?)
+    }
+
+    /// Index new Messages history (`full` re-reads everything).
+    pub async fn index_messages(&self, full: bool) -> Result<MessagesIndexSummary, String> {
+        Ok(crate::messages::index_messages(&self.state, &IndexMessagesArgs { full }).await?)
+    }
+
+    /// Check the index size cap and evict documents over it (`dry_run` only reports).
+    pub async fn index_budget(&self, dry_run: bool) -> Result<EvictionReport, String> {
+        crate::budget::enforce_budget(&self.state, dry_run).await
+    }
+
+    /// Configured collections and whether each is readable right now.
+    pub async fn collections(&self) -> Vec<CollectionStatus> {
+        crate::access::collections(&self.state).await
+    }
+
+    /// Open a locked collection for `secs` (capped at its `unlock_secs`).
+    pub async fn unlock_collection(&self, name: String, secs: Option<u64>) -> Result<CollectionStatus, String> {
+        crate::access::unlock(&self.state, &name, secs).await
+    }
+
+    /// Lock a collection (or all of them) again.
+    pub async fn lock_collection(&self, name: Option<String>) -> Result<Vec<String>, String> {
+        crate::access::lock(&self.state, name.as_deref()).await
+    }
+
+    /// Tool calls waiting for the user's approval (sensitive paths, see `crate::consent`).
+    pub fn consent_pending(&self) -> Vec<ConsentRequest> {
+        crate::consent::pending(&self.state)
+    }
+
+    /// Approve or deny a pending tool call.
+    pub fn consent_respond(&self, consent_id: &str, approve: bool) -> Result<ConsentRequest, String> {
+        crate::consent::respond(&self.state, consent_id, approve)
+    }
+
+    /// Export config and generated tags to a `.tar.gz` (default location next to the config).
+    pub async fn backup_settings(&self, path: Option<String>) -> Result<BackupSummary, String> {
+        crate::backup::backup_settings(&self.state, path.as_deref()).await
+    }
+
+    /// Compact the tables and prune old versions (`keep_versions_hours` defaults to the config).
+    pub async fn maintenance(&self, keep_versions_hours: Option<u64>) -> Result<MaintenanceReport, String> {
+        Ok(crate::maintenance::run_maintenance(&self.state, keep_versions_hours, false).await?)
+    }
+
+    /// Sample indexed files and report (or with `repair`, fix) drift (same as `silo_verify`).
+    pub async fn verify(&self, args: VerifyArgs) -> Result<VerifyReport, String> {
+        crate::verify::verify(&self.state, args).await
+    }
+
+    /// Embedding models: active model state, downloadable and downloaded models.
+    pub async fn models_list(&self) -> ModelList {
+        crate::models::list_models(&self.state).await
+    }
+
+    /// Download a model (default: the active one, which is also loaded).
+    pub async fn models_pull(&self, model: Option<String>) -> Result<PullResult, String> {
+        crate::models::pull_model(&self.state, model.as_deref()).await
+    }
+
+    /// Delete a downloaded model.
+    pub async fn models_remove(&self, model: Option<String>) -> Result<RemoveResult, String> {
+        crate::models::remove_model(&self.state, model.as_deref()).await
     }
-}
 
+    /// Open a search hit in its default application.
+    pub async fn open_path(&self, path: String) -> Result<(), String> {
+        crate::launch::open_path(&path).await
+    }
+
+    /// Show a search hit in Finder / Explorer / the file manager.
+    pub async fn reveal_path(&self, path: String) -> Result<(), String> {
+        crate::launch::reveal_path(&path).await
+    }
+
+    /// Rendered preview of a search hit (PDF page, image thumbnail, or first lines of text).
+    pub async fn preview(&self, path: String, page: Option<i64>) -> Result<crate::preview::Preview, String> {
+        crate::preview::preview(&self.state, &path, page).await
+    }
+
+    /// OCR new screenshots now instead of waiting for the watcher.
+    pub async fn index_screenshots(&self) -> Result<ScreenshotPass, String> {
+        Ok(crate::screenshots::index_screenshots(&self.state).await?)
+    }
+
+    /// Ingest new newsletter items now instead of waiting for the watcher.
+    pub async fn index_newsletters(&self) -> Result<NewsletterPass, String> {
+        Ok(crate::newsletters::index_newsletters(&self.state).await?)
+    }
 
+    /// Read the Zotero/BibTeX library and ingest new or changed attachments.
+    pub async fn index_library(&self) -> Result<LibraryPass, String> {
+        Ok(crate::library::index_library(&self.state).await?)
+    }
+
+    /// Import a WhatsApp export or Signal Desktop history.
+    pub async fn import_chat_export(&self, args: ImportChatExportArgs) -> Result<ChatImportSummary, String> {
+        Ok(crate::chat_export::import_chat_export(&self.state, &args).await?)
+    }
+
+    pub async fn disk_report(&self, args: DiskReportArgs) -> Result<DiskReport, String> {
+        Ok(crate::disk::disk_report(&self.state, &args).await?)
+    }
+
+    pub async fn cleanup_suggestions(&self, args: CleanupArgs) -> Result<CleanupReport, String> {
+        Ok(crate::cleanup::cleanup_suggestions(&self.state, &args).await?)
+    }
+
+    /// Start the background tagging job (`false` if one is already running).
+    pub fn tag_documents(&self, retag: bool) -> Result<bool, String> {
+        Ok(crate::tagging::start_tagging(&self.state, retag, None)?)
+    }
+
+    pub async fn list_tags(&self, prefix: Option<String>, limit: usize) -> TagList {
+        crate::tagging::list_tags(&self.state, prefix.as_deref(), limit).await
+    }
+
+    pub async fn timeline(&self, args: TimelineArgs) -> Result<Timeline, String> {
+        crate::timeline::timeline(&self.state, &args).await
+    }
+}
@@ -0,0 +1,376 @@
+//! Retrieval-augmented question answering over the local index.
+//!
+//! Flow: embed question -> vector search -> build prompt with numbered sources -> local LLM.
+//! Citations are known before generation starts, so streaming callers get them up front.
+
+use crate::catalog::CatalogEntry;
+use crate::database::{SearchHit, SearchOptions};
+use crate::errors::ToolError;
+use crate::llm::{estimate_llm_tokens, truncate_to_llm_tokens};
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+#[derive(Debug, Deserialize)]
+struct AskArgs {
+    question: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+    #[serde(default)]
+    min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    /// 1-based source number as referenced in the answer (`[1]`, `[2]`, ...).
+    pub index: usize,
+    pub path: String,
+    /// Chunk row id, for `silo_get_chunk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_preview: Option<String>,
+    /// Bibliographic entry when the source is a library attachment (`crate::library`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<crate::library::Reference>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AskAnswer {
+    pub question: String,
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    pub context: ContextUsage,
+    /// Served from the answer cache (no LLM call).
+    pub cached: bool,
+}
+
+/// A cached `silo_ask` answer plus a fingerprint of every cited file at answer time.
+/// It is only reused while all of those files are unchanged.
+#[derive(Debug, Clone)]
+pub struct CachedAnswer {
+    answer: AskAnswer,
+    files: Vec<(String, String)>,
+}
+
+/// How retrieved chunks were fitted into the model's context window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextUsage {
+    pub window_tokens: usize,
+    pub prompt_budget_tokens: usize,
+    pub window_source: &'static str,
+    pub prompt_tokens_estimate: usize,
+    pub chunks_included: usize,
+    /// Included, but cut short to fit.
+    pub chunks_trimmed: usize,
+    /// Retrieved, but left out entirely.
+    pub chunks_dropped: usize,
+}
+
+/// Don't bother including a trimmed chunk smaller than this.
+const MIN_TRIMMED_CHUNK_TOKENS: usize = 48;
+
+/// Events emitted by `ask_streaming`, in order: `citations`, `token`*, then `done` or `error`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AskEvent {
+    Citations { citations: Vec<Citation> },
+    Token { text: String },
+    Done { answer: String },
+    Error { message: String },
+}
+
+struct Prepared {
+    prompt: String,
+    citations: Vec<Citation>,
+    context: ContextUsage,
+    cache_key: String,
+}
+
+/// MCP tool entrypoint (`silo_ask`): non-streaming.
+pub async fn ask_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: AskArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let answer = ask(state, args.question, args.top_k, args.min_score).await?;
+    serde_json::to_value(answer).map_err(|e| format!("failed to serialize answer: {e}").into())
+}
+
+pub async fn ask(
+    state: &SharedState,
+    question: String,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<AskAnswer, ToolError> {
+    let prepared = prepare(state, &question, top_k, min_score).await?;
+    if let Some(hit) = cached_answer(state, &prepared.cache_key).await {
+        return Ok(hit);
+    }
+    let answer = state.llm.generate(prepared.prompt).await?;
+    let answer = AskAnswer {
+        question,
+        answer,
+        citations: prepared.citations,
+        context: prepared.context,
+        cached: false,
+    };
+    remember_answer(state, prepared.cache_key, &answer).await;
+    Ok(answer)
+}
+
+/// Streaming variant for UIs: sends `AskEvent`s to `events` while generating.
+pub async fn ask_streaming(
+    state: &SharedState,
+    question: String,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+    events: UnboundedSender<AskEvent>,
+) -> Result<AskAnswer, String> {
+    let prepared = match prepare(state, &question, top_k, min_score).await {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = events.send(AskEvent::Error {
+                message: e.message.clone(),
+            });
+            return Err(e.into());
+        }
+    };
+    let _ = events.send(AskEvent::Citations {
+        citations: prepared.citations.clone(),
+    });
+    if let Some(hit) = cached_answer(state, &prepared.cache_key).await {
+        let _ = events.send(AskEvent::Token {
+            text: hit.answer.clone(),
+        });
+        let _ = events.send(AskEvent::Done {
+            answer: hit.answer.clone(),
+        });
+        return Ok(hit);
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let forward_events = events.clone();
+    let forward = tokio::spawn(async move {
+        while let Some(text) = rx.recv().await {
+            let _ = forward_events.send(AskEvent::Token { text });
+        }
+    });
+
+    let res = state.llm.generate_stream(prepared.prompt, tx).await;
+    // The sender was moved into generate_stream and is dropped by now, so this finishes.
+    let _ = forward.await;
+
+    match res {
+        Ok(answer) => {
+            let _ = events.send(AskEvent::Done {
+                answer: answer.clone(),
+            });
+            let answer = AskAnswer {
+                question,
+                answer,
+                citations: prepared.citations,
+                context: prepared.context,
+                cached: false,
+            };
+            remember_answer(state, prepared.cache_key, &answer).await;
+            Ok(answer)
+        }
+        Err(e) => {
+            let _ = events.send(AskEvent::Error { message: e.clone() });
+            Err(e)
+        }
+    }
+}
+
+async fn prepare(
+    state: &SharedState,
+    question: &str,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<Prepared, ToolError> {
+    if question.trim().is_empty() {
+        return Err("question must not be empty".into());
+    }
+    let hits = retrieve(state, question, top_k.unwrap_or(5).clamp(1, 20), min_score).await?;
+
+    let budget = state.context_budget().await;
+    let template = state.prompts.read().await.ask.clone();
+    let prompt_budget = budget.prompt_tokens();
+    let fixed = template.literal_tokens() + estimate_llm_tokens(question);
+    let mut available = prompt_budget.saturating_sub(fixed);
+
+    // Rank order: include whole chunks while they fit, trim the first one that doesn't,
+    // drop whatever is left. Small local models fail badly on overflowing prompts.
+    let mut context = String::new();
+    let mut citations = vec![];
+    let (mut trimmed, mut dropped) = (0usize, 0usize);
+    for h in &hits {
+        let text = h
+            .content
+            .as_deref()
+            .or(h.content_preview.as_deref())
+            .unwrap_or("")
+            .trim();
+        let reference = crate::library::reference_of(state, &h.path).await;
+        // Library attachments are named by their citation, so answers can cite the work.
+        let source = match &reference {
+            Some(r) => format!("{} ({})", r.citation, h.path),
+            None => h.path.clone(),
+        };
+        let header = match h.page {
+            Some(page) => format!("[{}] {source} (page {page})", citations.len() + 1),
+            None => format!("[{}] {source}", citations.len() + 1),
+        };
+        let header_cost = estimate_llm_tokens(&header) + 2;
+        let cost = header_cost + estimate_llm_tokens(text);
+
+        let body = if cost <= available {
+            available -= cost;
+            text.to_string()
+        } else if available >= header_cost + MIN_TRIMMED_CHUNK_TOKENS {
+            let body = truncate_to_llm_tokens(text, available - header_cost);
+            available -= header_cost + estimate_llm_tokens(&body);
+            trimmed += 1;
+            body
+        } else {
+            dropped += 1;
+            continue;
+        };
+
+        context.push_str(&format!("{header}\n{body}\n\n"));
+        citations.push(Citation {
+            index: citations.len() + 1,
+            path: h.path.clone(),
+            id: h.id.clone(),
+            chunk_index: h.chunk_index,
+            page: h.page,
+            score: h.score,
+            content_preview: h.content_preview.clone(),
+            reference,
+        });
+    }
+    if context.is_empty() {
+        context.push_str("(no matching documents)\n");
+    }
+
+    let cache_key = answer_cache_key(&state.llm.model_id(), question, &citations);
+    let prompt = template.render(&[("context", &context), ("question", question)]);
+    let context = ContextUsage {
+        window_tokens: budget.window_tokens,
+        prompt_budget_tokens: prompt_budget,
+        window_source: budget.source,
+        prompt_tokens_estimate: estimate_llm_tokens(&prompt),
+        chunks_included: citations.len(),
+        chunks_trimmed: trimmed,
+        chunks_dropped: dropped,
+    };
+    Ok(Prepared {
+        prompt,
+        citations,
+        context,
+        cache_key,
+    })
+}
+
+/// Key: model + normalized question + the retrieved chunk ids, in prompt order.
+/// Changed file contents are caught separately by `cached_answer`.
+fn answer_cache_key(model: &str, question: &str, citations: &[Citation]) -> String {
+    let question = question.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let mut h = blake3::Hasher::new();
+    h.update(model.as_bytes());
+    h.update(b"\0");
+    h.update(question.as_bytes());
+    for c in citations {
+        h.update(b"\0");
+        h.update(c.path.as_bytes());
+        h.update(b"#");
+        h.update(c.chunk_index.unwrap_or(-1).to_string().as_bytes());
+    }
+    h.finalize().to_hex().to_string()
+}
+
+/// Cached answer for `key`, unless one of its cited files was re-indexed since.
+async fn cached_answer(state: &SharedState, key: &str) -> Option<AskAnswer> {
+    let cached = state.cached_answer(key)?;
+    let catalog = state.catalog.read().await;
+    let unchanged = cached
+        .files
+        .iter()
+        .all(|(path, fp)| catalog.get(path).map(file_fingerprint).as_ref() == Some(fp));
+    drop(catalog);
+    if !unchanged {
+        state.forget_answer(key);
+        return None;
+    }
+    let mut answer = cached.answer;
+    answer.cached = true;
+    Some(answer)
+}
+
+async fn remember_answer(state: &SharedState, key: String, answer: &AskAnswer) {
+    let catalog = state.catalog.read().await;
+    let mut files: Vec<(String, String)> = vec![];
+    for c in &answer.citations {
+        if files.iter().any(|(p, _)| p == &c.path) {
+            continue;
+        }
+        let Some(entry) = catalog.get(&c.path) else {
+            // Not in the catalog: nothing to validate against later, so don't cache.
+            return;
+        };
+        files.push((c.path.clone(), file_fingerprint(entry)));
+    }
+    drop(catalog);
+    state.cache_answer(
+        key,
+        CachedAnswer {
+            answer: answer.clone(),
+            files,
+        },
+    );
+}
+
+/// Content hash when known; otherwise any re-index counts as a change.
+fn file_fingerprint(entry: &CatalogEntry) -> String {
+    entry
+        .file_hash
+        .clone()
+        .unwrap_or_else(|| format!("indexed@{}", entry.indexed_at_epoch_secs))
+}
+
+pub(crate) async fn retrieve(
+    state: &SharedState,
+    question: &str,
+    top_k: usize,
+    min_score: Option<f32>,
+) -> Result<Vec<SearchHit>, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let qvec = state
+        .embed_query_cached(question)
+        .await
+        .map_err(ToolError::embedding_failed)?;
+    // Locked collections never reach the LLM (see `crate::access`).
+    let filter = crate::access::locked_paths(state).await.search_filter();
+    let hits = state
+        .db
+        .search_chunks_by_vector(
+            &qvec,
+            &SearchOptions {
+                top_k,
+                min_score,
+                filter,
+            },
+        )
+        .await
+        .map_err(ToolError::db_search_failed)?;
+    state.catalog.write().await.record_access(hits.iter().map(|h| h.path.as_str()));
+    Ok(hits)
+}
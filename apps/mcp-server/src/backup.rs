@@ -0,0 +1,198 @@
+//! Backups: keep the data dir out of them, export what cannot be rebuilt (`silo_backup_settings`).
+//!
+//! Everything in the data dir (vectors, catalog, caches, models) is reproducible by re-indexing, so
+//! on startup it is marked as a cache: a `CACHEDIR.TAG` (honored by restic, borg, tar
+//! `--exclude-caches`, ...) and, on macOS, the Time Machine exclusion attribute `tmutil addexclusion`
+//! sets. `storage.include_in_backups` turns this off (and removes the markers).
+//!
+//! The settings export is a small `.tar.gz` with the config file and the generated tags from the
+//! catalog (which took LLM calls to produce); Finder/xdg tags live on the files themselves.
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const CACHEDIR_TAG: &str = "CACHEDIR.TAG";
+/// Fixed signature from the Cache Directory Tagging Specification.
+const CACHEDIR_SIGNATURE: &str = "Signature: 8a477f597d28d172789f06886806bc55";
+#[cfg(target_os = "macos")]
+const TIME_MACHINE_EXCLUDE: &str = "com.apple.metadata:com_apple_backup_excludeItem";
+
+const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BackupSettingsArgs {
+    /// Archive to write (default: `backups/silo-settings-<time>.tar.gz` next to the config file).
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaggedFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tagged_at_epoch_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsManifest {
+    pub version: u32,
+    pub created_epoch_secs: i64,
+    pub config_path: String,
+    pub data_dir: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub path: String,
+    pub bytes: u64,
+    pub tagged_files: usize,
+    pub files: Vec<String>,
+}
+
+/// MCP tool entrypoint (`silo_backup_settings`).
+pub async fn backup_settings_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: BackupSettingsArgs =
+        serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let summary = backup_settings(state, args.path.as_deref()).await?;
+    serde_json::to_value(summary).map_err(|e| format!("failed to serialize backup summary: {e}").into())
+}
+
+/// Write the settings archive and return where it went.
+pub async fn backup_settings(state: &SharedState, path: Option<&str>) -> Result<BackupSummary, String> {
+    let now = now_epoch_secs();
+    let out = match path.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) => expand_tilde(p),
+        None => state
+            .config_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("backups")
+            .join(format!("silo-settings-{now}.tar.gz")),
+    };
+
+    let config = serde_json::to_vec_pretty(&*state.config.read().await)
+        .map_err(|e| format!("failed to serialize config: {e}"))?;
+    let tags: BTreeMap<String, TaggedFile> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| !e.tags.is_empty())
+        .map(|e| {
+            (
+                e.path.clone(),
+                TaggedFile {
+                    tags: e.tags.clone(),
+                    tagged_at_epoch_secs: e.tagged_at_epoch_secs,
+                },
+            )
+        })
+        .collect();
+    let tagged_files = tags.len();
+    let tags = serde_json::to_vec_pretty(&tags).map_err(|e| format!("failed to serialize tags: {e}"))?;
+
+    let mut entries: Vec<(&str, Vec<u8>)> = vec![("config.json", config), ("tags.json", tags)];
+    let manifest = SettingsManifest {
+        version: ARCHIVE_VERSION,
+        created_epoch_secs: now,
+        config_path: state.config_path.to_string_lossy().to_string(),
+        data_dir: state.data_dir.to_string_lossy().to_string(),
+        files: entries.iter().map(|(name, _)| name.to_string()).collect(),
+    };
+    let files = manifest.files.clone();
+    let manifest =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| format!("failed to serialize manifest: {e}"))?;
+    entries.insert(0, ("manifest.json", manifest));
+
+    let archive = tokio::task::spawn_blocking({
+        let entries: Vec<(String, Vec<u8>)> = entries.into_iter().map(|(n, b)| (n.to_string(), b)).collect();
+        move || build_archive(&entries, now)
+    })
+    .await
+    .map_err(|e| format!("archive task failed: {e}"))??;
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    tokio::fs::write(&out, &archive)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", out.display()))?;
+    Ok(BackupSummary {
+        path: out.to_string_lossy().to_string(),
+        bytes: archive.len() as u64,
+        tagged_files,
+        files,
+    })
+}
+
+fn build_archive(entries: &[(String, Vec<u8>)], mtime: i64) -> Result<Vec<u8>, String> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(gz);
+    for (name, bytes) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime.max(0) as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, name, bytes.as_slice())
+            .map_err(|e| format!("Failed to add {name} to archive: {e}"))?;
+    }
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to finish archive: {e}"))
+}
+
+/// Mark `data_dir` as excluded from backups, or clear the markers when `include` is set.
+/// Best effort: failures are logged.
+pub async fn apply_backup_exclusion(data_dir: &Path, include: bool) {
+    if let Err(e) = tokio::fs::create_dir_all(data_dir).await {
+        tracing::warn!("Failed to create {}: {e}", data_dir.display());
+        return;
+    }
+    let tag = data_dir.join(CACHEDIR_TAG);
+    if include {
+        // Only remove a tag we wrote.
+        if tokio::fs::read_to_string(&tag)
+            .await
+            .is_ok_and(|s| s.starts_with(CACHEDIR_SIGNATURE))
+        {
+            let _ = tokio::fs::remove_file(&tag).await;
+        }
+    } else if !tokio::fs::try_exists(&tag).await.unwrap_or(false) {
+        let body = format!(
+            "{CACHEDIR_SIGNATURE}\n# This file is a cache directory tag created by Silo.\n# The index here is rebuilt by re-indexing; export settings with `silo_backup_settings`.\n"
+        );
+        if let Err(e) = tokio::fs::write(&tag, body).await {
+            tracing::warn!("Failed to write {}: {e}", tag.display());
+        }
+    }
+    set_time_machine_exclusion(data_dir, !include);
+}
+
+#[cfg(target_os = "macos")]
+fn set_time_machine_exclusion(dir: &Path, exclude: bool) {
+    if !exclude {
+        // Absent unless we set it earlier.
+        let _ = xattr::remove(dir, TIME_MACHINE_EXCLUDE);
+        return;
+    }
+    let mut value = vec![];
+    let result = plist::to_writer_binary(&mut value, &"com.apple.backupd")
+        .map_err(|e| e.to_string())
+        .and_then(|_| xattr::set(dir, TIME_MACHINE_EXCLUDE, &value).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!("Failed to set the Time Machine exclusion on {}: {e}", dir.display());
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_time_machine_exclusion(_dir: &Path, _exclude: bool) {}
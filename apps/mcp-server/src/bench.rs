@@ -0,0 +1,349 @@
+//! Ingest benchmark (`silo_bench_ingest`): time extract, chunk, embed and store separately over
+//! N files, so throughput changes between releases (and the effect of chunk or model settings)
+//! show up as numbers.
+//!
+//! Files are either synthetic (generated text files in a temp folder in the data dir, the same
+//! words for the same `files` / `file_bytes`, so runs are comparable) or sampled from the
+//! catalog (spread evenly over the indexed files, locked collections skipped). The model is
+//! loaded before timing starts and its load time is reported separately.
+//!
+//! The store stage writes to the live store by default (to measure the real backend) under
+//! paths in the temp folder, which are deleted again afterwards; `store: "memory"` writes to a
+//! throwaway in-memory store instead. Sampled files are never re-stored under their own path,
+//! and the catalog is not touched.
+
+use crate::chunk::{cap_chunks, chunk_text, ChunkRules};
+use crate::database::{Database, DatabaseHandle};
+use crate::errors::ToolError;
+use crate::extract::extract_text;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const BENCH_DIR: &str = "bench-tmp";
+const DEFAULT_FILES: usize = 20;
+const MAX_FILES: usize = 500;
+const DEFAULT_FILE_BYTES: u64 = 16 * 1024;
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchSource {
+    #[default]
+    Synthetic,
+    Sample,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchStore {
+    /// The live store, under temporary paths removed afterwards.
+    #[default]
+    Index,
+    Memory,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BenchIngestArgs {
+    /// Files to run (default 20, max 500).
+    #[serde(default)]
+    pub files: Option<usize>,
+    #[serde(default)]
+    pub source: BenchSource,
+    /// Size of each synthetic file (default 16 KiB, max 1 MiB).
+    #[serde(default)]
+    pub file_bytes: Option<u64>,
+    #[serde(default)]
+    pub store: BenchStore,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageReport {
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    /// Share of the summed stage time.
+    pub share: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub files: usize,
+    pub bytes: u64,
+    pub chars: usize,
+    pub chunks: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub model_load_ms: f64,
+    pub chunk_tokens: usize,
+    pub chunk_overlap_tokens: usize,
+    pub store: BenchStore,
+    pub extract: StageReport,
+    pub chunk: StageReport,
+    pub embed: StageReport,
+    pub store_write: StageReport,
+    pub total_ms: f64,
+    pub files_per_sec: f64,
+    pub chunks_per_sec: f64,
+    pub mb_per_sec: f64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Per-file durations of one stage.
+#[derive(Default)]
+struct Stage(Vec<Duration>);
+
+impl Stage {
+    fn total(&self) -> Duration {
+        self.0.iter().sum()
+    }
+
+    fn report(&mut self, all: Duration) -> StageReport {
+        self.0.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let pct = |p: usize| self.0.get((self.0.len().saturating_sub(1)) * p / 100).copied().unwrap_or_default();
+        let total = self.total();
+        StageReport {
+            total_ms: ms(total),
+            mean_ms: if self.0.is_empty() { 0.0 } else { ms(total) / self.0.len() as f64 },
+            p50_ms: ms(pct(50)),
+            p95_ms: ms(pct(95)),
+            share: if all.is_zero() { 0.0 } else { (total.as_secs_f64() / all.as_secs_f64()) as f32 },
+        }
+    }
+}
+
+/// MCP tool entrypoint (`silo_bench_ingest`).
+pub async fn bench_ingest_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: BenchIngestArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = bench_ingest(state, args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize bench report: {e}").into())
+}
+
+pub async fn bench_ingest(state: &SharedState, args: BenchIngestArgs) -> Result<BenchReport, String> {
+    let n = args.files.unwrap_or(DEFAULT_FILES).clamp(1, MAX_FILES);
+    let fs_cfg = state.filesystem_config().await.unwrap_or_default();
+    let chunking = fs_cfg.chunking();
+    let db: DatabaseHandle = match args.store {
+        BenchStore::Index if state.db.is_enabled() => state.db.clone(),
+        BenchStore::Index => return Err(state.db.disabled_reason().unwrap_or("DB disabled").to_string()),
+        BenchStore::Memory => Arc::new(Database::memory()),
+    };
+    let load_started = Instant::now();
+    state.embedder.load().await?;
+    let model_load = load_started.elapsed();
+
+    let dir = state.data_dir.join(BENCH_DIR);
+    // Leftovers of an interrupted run.
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let files = match args.source {
+        BenchSource::Synthetic => {
+            let size = args.file_bytes.unwrap_or(DEFAULT_FILE_BYTES).clamp(1, MAX_FILE_BYTES);
+            synthesize(&dir, n, size).await
+        }
+        BenchSource::Sample => Ok(sample(state, n).await),
+    };
+    let files = match files {
+        Ok(files) if !files.is_empty() => files,
+        Ok(_) => {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            return Err("no indexed files to sample".to_string());
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            return Err(e);
+        }
+    };
+
+    let mut run = run(state, &db, &dir, &files, fs_cfg.max_text_bytes, &chunking).await;
+    if args.store == BenchStore::Index {
+        state.index_changed();
+    }
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+
+    let wall = run.wall;
+    let summed = run.extract.total() + run.chunk.total() + run.embed.total() + run.store.total();
+    let secs = wall.as_secs_f64().max(f64::EPSILON);
+    Ok(BenchReport {
+        files: run.files,
+        bytes: run.bytes,
+        chars: run.chars,
+        chunks: run.chunks,
+        model: state.embedder.model_status().map(|s| s.model),
+        model_load_ms: model_load.as_secs_f64() * 1000.0,
+        chunk_tokens: chunking.default.tokens,
+        chunk_overlap_tokens: chunking.default.overlap_tokens,
+        store: args.store,
+        extract: run.extract.report(summed),
+        chunk: run.chunk.report(summed),
+        embed: run.embed.report(summed),
+        store_write: run.store.report(summed),
+        total_ms: wall.as_secs_f64() * 1000.0,
+        files_per_sec: run.files as f64 / secs,
+        chunks_per_sec: run.chunks as f64 / secs,
+        mb_per_sec: run.bytes as f64 / (1024.0 * 1024.0) / secs,
+        errors: run.errors,
+    })
+}
+
+#[derive(Default)]
+struct Run {
+    files: usize,
+    bytes: u64,
+    chars: usize,
+    chunks: usize,
+    extract: Stage,
+    chunk: Stage,
+    embed: Stage,
+    store: Stage,
+    wall: Duration,
+    errors: Vec<String>,
+}
+
+/// The ingest pipeline of `crate::ingest::process_file` with a timer around each stage. Rows are
+/// stored under a path in `dir` and removed again at the end.
+async fn run(
+    state: &SharedState,
+    db: &DatabaseHandle,
+    dir: &Path,
+    files: &[PathBuf],
+    max_text_bytes: u64,
+    chunking: &ChunkRules,
+) -> Run {
+    let mut run = Run::default();
+    let mut stored: Vec<String> = vec![];
+    let started = Instant::now();
+    for (i, path) in files.iter().enumerate() {
+        let t = Instant::now();
+        let extracted = match extract_text(path, max_text_bytes).await {
+            Ok(x) => x,
+            Err(e) => {
+                run.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        run.extract.0.push(t.elapsed());
+
+        let t = Instant::now();
+        let mut chunks = chunk_text(&extracted.text, &chunking.for_path(path));
+        if let Some(cap) = chunking.cap {
+            chunks = cap_chunks(chunks, cap);
+        }
+        run.chunk.0.push(t.elapsed());
+
+        let t = Instant::now();
+        let embeddings = if chunks.is_empty() {
+            Ok(vec![])
+        } else {
+            state
+                .embedder
+                .embed_texts(chunks.iter().map(|c| c.text.clone()).collect())
+                .await
+        };
+        run.embed.0.push(t.elapsed());
+        let embeddings = match embeddings {
+            Ok(v) => v,
+            Err(e) => {
+                // The next file would fail the same way.
+                run.errors.push(format!("{}: embedding failed: {e}", path.display()));
+                break;
+            }
+        };
+
+        let key = dir.join(format!("stored-{i}")).to_string_lossy().to_string();
+        let rows = chunks
+            .iter()
+            .zip(embeddings)
+            .map(|(ch, emb)| {
+                let page = extracted.page_of(ch.start_token);
+                (ch.index, ch.start_token, ch.end_token, page, None, ch.text.clone(), emb)
+            })
+            .collect::<Vec<_>>();
+        let t = Instant::now();
+        stored.push(key.clone());
+        let res = db.replace_file_chunks(&key, None, None, None, &[], rows).await;
+        run.store.0.push(t.elapsed());
+        if let Err(e) = res {
+            run.errors.push(format!("{}: DB write failed: {e}", path.display()));
+            continue;
+        }
+
+        run.files += 1;
+        run.bytes += tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+        run.chars += extracted.text.chars().count();
+        run.chunks += chunks.len();
+    }
+    run.wall = started.elapsed();
+
+    for key in stored {
+        if let Err(e) = db.delete_file(&key).await {
+            tracing::warn!("silo_bench_ingest: failed to remove {key}: {e}");
+        }
+    }
+    run
+}
+
+/// `n` text files of about `size` bytes each: paragraphs of pseudo-words from a fixed seed.
+async fn synthesize(dir: &Path, n: usize, size: u64) -> Result<Vec<PathBuf>, String> {
+    const SYLLABLES: &[&str] = &[
+        "ka", "lo", "mi", "ren", "to", "sa", "vel", "di", "nor", "pe", "qua", "stri", "bo", "an", "te", "ul",
+    ];
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        // xorshift64
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let mut out = vec![];
+    for i in 0..n {
+        let mut text = String::with_capacity(size as usize + 16);
+        let mut words = 0;
+        while (text.len() as u64) < size {
+            let r = next();
+            for s in 0..(1 + r % 3) {
+                text.push_str(SYLLABLES[((r >> (8 * s + 8)) % SYLLABLES.len() as u64) as usize]);
+            }
+            words += 1;
+            text.push_str(if words % 80 == 0 { "\n\n" } else { " " });
+        }
+        let path = dir.join(format!("synthetic-{i}.txt"));
+        tokio::fs::write(&path, text)
+            .await
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        out.push(path);
+    }
+    Ok(out)
+}
+
+/// Up to `n` indexed files that still exist, spread evenly over the catalog in path order.
+async fn sample(state: &SharedState, n: usize) -> Vec<PathBuf> {
+    let locked = crate::access::locked_paths(state).await;
+    let mut paths: Vec<String> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| !e.is_evicted() && e.chunks > 0)
+        .map(|e| e.path.clone())
+        .filter(|p| locked.collection_of(p).is_none())
+        .collect();
+    paths.sort();
+    let step = (paths.len() / n).max(1);
+    paths
+        .into_iter()
+        .step_by(step)
+        .map(PathBuf::from)
+        .filter(|p| p.is_file())
+        .take(n)
+        .collect()
+}
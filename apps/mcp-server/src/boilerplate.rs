@@ -0,0 +1,183 @@
+//! Boilerplate chunk filtering (`filesystem.drop_boilerplate`): license headers, repeated footers
+//! and navigation text show up in many files and crowd real matches out of search results.
+//!
+//! Chunks are compared by shingles (runs of `SHINGLE_WORDS` normalized words). A sample of them
+//! (by hash, so every file samples the same shingles) is counted across the corpus: for each
+//! sampled shingle, the distinct files it was seen in, up to `MIN_FILES`. A chunk whose sampled
+//! shingles are mostly (`BOILERPLATE_SHARE`) in `MIN_FILES` files or more is dropped at ingest.
+//!
+//! The table grows as files are ingested and is persisted in `boilerplate.json` under `data_dir`,
+//! so a header is only recognized once it has been seen often enough: files indexed before that
+//! keep their copy until they are re-ingested. A file never loses all of its chunks.
+
+use crate::chunk::TextChunk;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SHINGLE_WORDS: usize = 5;
+/// Shingles whose hash has these low bits clear are counted (one in 8).
+const SAMPLE_MASK: u64 = 0x7;
+/// Distinct files a shingle must appear in to count as boilerplate.
+const MIN_FILES: usize = 20;
+/// Share of a chunk's sampled shingles that must be boilerplate to drop it.
+const BOILERPLATE_SHARE: f32 = 0.8;
+/// Chunks with fewer sampled shingles are never judged (too short to tell).
+const MIN_SAMPLED: usize = 4;
+/// Shingles tracked at most; once full, only shingles already tracked are counted.
+const MAX_TRACKED: usize = 400_000;
+
+const FILE_NAME: &str = "boilerplate.json";
+const FILE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct BoilerplateFile {
+    version: u32,
+    /// Shingle hash -> hashes of the files it was seen in (at most `MIN_FILES`).
+    shingles: Vec<(u64, Vec<u32>)>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    shingles: HashMap<u64, Vec<u32>>,
+    dirty: bool,
+}
+
+/// Corpus-wide shingle counts (`AppState::boilerplate`).
+#[derive(Debug)]
+pub struct Boilerplate {
+    file: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+impl Boilerplate {
+    /// An empty table saved to `boilerplate.json` in `data_dir` (what was there is not read).
+    pub fn empty(data_dir: &Path) -> Self {
+        Self {
+            file: data_dir.join(FILE_NAME),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Load `boilerplate.json` from `data_dir` (missing or unreadable file -> empty table).
+    pub async fn load(data_dir: &Path) -> Self {
+        let table = Self::empty(data_dir);
+        let shingles = match tokio::fs::read_to_string(&table.file).await {
+            Ok(s) => match serde_json::from_str::<BoilerplateFile>(&s) {
+                Ok(f) if f.version == FILE_VERSION => f.shingles.into_iter().collect(),
+                Ok(_) => HashMap::new(),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid boilerplate table {}: {e}", table.file.display());
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+        table.lock().shingles = shingles;
+        table
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Write the table if it changed since the last save.
+    pub async fn save(&self) -> Result<(), String> {
+        let out = {
+            let mut inner = self.lock();
+            if !inner.dirty {
+                return Ok(());
+            }
+            inner.dirty = false;
+            BoilerplateFile {
+                version: FILE_VERSION,
+                shingles: inner.shingles.iter().map(|(h, f)| (*h, f.clone())).collect(),
+            }
+        };
+        if let Some(parent) = self.file.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create data dir {}: {e}", parent.display()))?;
+        }
+        let s = serde_json::to_string(&out).map_err(|e| format!("Failed to serialize boilerplate table: {e}"))?;
+        tokio::fs::write(&self.file, s)
+            .await
+            .map_err(|e| format!("Failed to write boilerplate table {}: {e}", self.file.display()))
+    }
+
+    /// Count `chunks` as seen in `path`, then drop the ones that are mostly boilerplate.
+    /// Returns how many were dropped. Kept chunks keep their `index`.
+    pub fn filter(&self, path: &str, chunks: &mut Vec<TextChunk>) -> usize {
+        let file = path_hash(path);
+        let sampled: Vec<Vec<u64>> = chunks.iter().map(|c| sampled_shingles(&c.text)).collect();
+
+        let mut guard = self.lock();
+        let inner = &mut *guard;
+        let mut seen_here = HashSet::new();
+        for h in sampled.iter().flatten() {
+            if !seen_here.insert(*h) {
+                continue;
+            }
+            if inner.shingles.len() >= MAX_TRACKED && !inner.shingles.contains_key(h) {
+                continue;
+            }
+            let files = inner.shingles.entry(*h).or_default();
+            if files.len() < MIN_FILES && !files.contains(&file) {
+                files.push(file);
+                inner.dirty = true;
+            }
+        }
+
+        let boilerplate: Vec<bool> = sampled
+            .iter()
+            .map(|hashes| {
+                if hashes.len() < MIN_SAMPLED {
+                    return false;
+                }
+                let common = hashes
+                    .iter()
+                    .filter(|h| inner.shingles.get(*h).is_some_and(|f| f.len() >= MIN_FILES))
+                    .count();
+                common as f32 >= hashes.len() as f32 * BOILERPLATE_SHARE
+            })
+            .collect();
+        drop(guard);
+
+        let dropped = boilerplate.iter().filter(|b| **b).count();
+        if dropped == 0 || dropped == chunks.len() {
+            return 0;
+        }
+        let mut keep = boilerplate.into_iter().map(|b| !b);
+        chunks.retain(|_| keep.next().unwrap_or(true));
+        dropped
+    }
+}
+
+/// Hashes of the sampled shingles of `text` (lowercased alphanumeric words).
+fn sampled_shingles(text: &str) -> Vec<u64> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| w.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect::<String>())
+        .filter(|w| !w.is_empty())
+        .collect();
+    words
+        .windows(SHINGLE_WORDS)
+        .map(|w| fnv1a(w.join(" ").as_bytes()))
+        .filter(|h| h & SAMPLE_MASK == 0)
+        .collect()
+}
+
+fn path_hash(path: &str) -> u32 {
+    let h = fnv1a(path.as_bytes());
+    (h ^ (h >> 32)) as u32
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
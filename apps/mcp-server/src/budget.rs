@@ -0,0 +1,204 @@
+//! Index size budget (`storage.max_index_bytes`, `silo_index_budget`).
+//!
+//! The index size is estimated from the catalog (stored text plus one vector and some row
+//! overhead per chunk) rather than measured on disk: LanceDB only returns space after compaction,
+//! so a disk measurement would keep reporting "over budget" right after an eviction. The on-disk
+//! size of the tables is reported alongside.
+//!
+//! When over the cap, whole documents are evicted in `storage.evict` order until the estimate
+//! fits. Evicted files stay in the catalog (`evicted_at_epoch_secs`) so indexing skips them until
+//! they change. The last eviction report is kept in `eviction_report.json` in the data dir.
+
+use crate::catalog::{now_epoch_secs, CatalogEntry};
+use crate::config::{EvictionOrder, VectorType};
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const REPORT_FILE: &str = "eviction_report.json";
+/// Ids, path, offsets and other per-row columns, roughly.
+const ROW_OVERHEAD_BYTES: u64 = 256;
+const MAX_REPORTED_EVICTIONS: usize = 500;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexBudgetArgs {
+    /// Report what would be evicted without changing the index.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvictedDocument {
+    pub path: String,
+    pub estimated_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_accessed_epoch_secs: Option<i64>,
+    pub access_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_epoch_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvictionReport {
+    pub at_epoch_secs: i64,
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_index_bytes: Option<u64>,
+    pub order: EvictionOrder,
+    /// Estimated index size before and after eviction.
+    pub estimated_bytes_before: u64,
+    pub estimated_bytes_after: u64,
+    /// LanceDB tables on disk (shrinks only after compaction).
+    pub disk_bytes: u64,
+    pub documents: usize,
+    /// Evicted (or, on a dry run, evictable) documents, in eviction order.
+    pub evicted: Vec<EvictedDocument>,
+    /// More documents were evicted than listed in `evicted`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub evicted_unlisted: usize,
+    /// Still over the cap because everything left is protected.
+    pub over_budget: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// MCP tool entrypoint (`silo_index_budget`).
+pub async fn index_budget_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: IndexBudgetArgs =
+        serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = enforce_budget(state, args.dry_run).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize eviction report: {e}").into())
+}
+
+/// Run after bulk indexing: evicts when a cap is configured and exceeded (`None` otherwise).
+pub async fn after_index(state: &SharedState) -> Option<EvictionReport> {
+    state.config.read().await.storage.max_index_bytes?;
+    match enforce_budget(state, false).await {
+        Ok(report) if !report.evicted.is_empty() => {
+            tracing::info!(
+                "Index over budget: evicted {} documents ({} -> {} estimated bytes)",
+                report.evicted.len() + report.evicted_unlisted,
+                report.estimated_bytes_before,
+                report.estimated_bytes_after
+            );
+            Some(report)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("Index budget check failed: {e}");
+            None
+        }
+    }
+}
+
+/// Measure the index and evict documents until it fits `storage.max_index_bytes`.
+pub async fn enforce_budget(state: &SharedState, dry_run: bool) -> Result<EvictionReport, String> {
+    let storage = state.config.read().await.storage.clone();
+    let vector_bytes = crate::embed::EMBEDDING_DIM as u64
+        * match storage.vector_type {
+            VectorType::F32 => 4,
+            VectorType::F16 => 2,
+        };
+    let protect: Vec<String> = storage
+        .protect_tags
+        .iter()
+        .map(|t| crate::tagging::normalize_tag(t))
+        .collect();
+
+    // (estimated bytes, entry) for every document in the index.
+    let mut docs: Vec<(u64, CatalogEntry)> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| !e.is_evicted() && e.chunks > 0)
+        .map(|e| (estimated_bytes(e, vector_bytes), e.clone()))
+        .collect();
+    let total: u64 = docs.iter().map(|(b, _)| b).sum();
+
+    let mut report = EvictionReport {
+        at_epoch_secs: now_epoch_secs(),
+        dry_run,
+        max_index_bytes: storage.max_index_bytes,
+        order: storage.evict,
+        estimated_bytes_before: total,
+        estimated_bytes_after: total,
+        disk_bytes: crate::maintenance::tables_disk_bytes(&state.data_dir).await,
+        documents: docs.len(),
+        evicted: vec![],
+        evicted_unlisted: 0,
+        over_budget: false,
+        errors: vec![],
+    };
+    let Some(cap) = storage.max_index_bytes else {
+        return Ok(report);
+    };
+    if total <= cap {
+        return Ok(report);
+    }
+
+    docs.retain(|(_, e)| !e.all_tags().iter().any(|t| protect.contains(t)));
+    match storage.evict {
+        EvictionOrder::LeastAccessed => docs.sort_by_key(|(_, e)| {
+            (e.last_accessed_epoch_secs.unwrap_or(i64::MIN), e.access_count, e.indexed_at_epoch_secs)
+        }),
+        EvictionOrder::Oldest => docs.sort_by_key(|(_, e)| e.modified_epoch_secs.unwrap_or(i64::MIN)),
+        EvictionOrder::Largest => docs.sort_by_key(|(bytes, _)| std::cmp::Reverse(*bytes)),
+    }
+
+    let mut remaining = total;
+    let mut evicted = 0usize;
+    for (bytes, entry) in docs {
+        if remaining <= cap {
+            break;
+        }
+        if !dry_run {
+            if let Err(e) = state.db.delete_file(&entry.path).await {
+                report.errors.push(format!("{}: {e}", entry.path));
+                continue;
+            }
+            state.catalog.write().await.mark_evicted(&entry.path);
+        }
+        remaining = remaining.saturating_sub(bytes);
+        evicted += 1;
+        if report.evicted.len() < MAX_REPORTED_EVICTIONS {
+            report.evicted.push(EvictedDocument {
+                path: entry.path,
+                estimated_bytes: bytes,
+                last_accessed_epoch_secs: entry.last_accessed_epoch_secs,
+                access_count: entry.access_count,
+                modified_epoch_secs: entry.modified_epoch_secs,
+            });
+        }
+    }
+    report.evicted_unlisted = evicted - report.evicted.len();
+    report.estimated_bytes_after = remaining;
+    report.over_budget = remaining > cap;
+
+    if !dry_run && evicted > 0 {
+        state.index_changed();
+        state.save_catalog().await;
+        if let Err(e) = state.db.optimize().await {
+            report.errors.push(format!("compaction failed: {e}"));
+        }
+        let path = state.data_dir.join(REPORT_FILE);
+        match serde_json::to_string_pretty(&report) {
+            Ok(s) => {
+                if let Err(e) = tokio::fs::write(&path, s).await {
+                    tracing::warn!("Failed to write {}: {e}", path.display());
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize eviction report: {e}"),
+        }
+    }
+    Ok(report)
+}
+
+fn estimated_bytes(e: &CatalogEntry, vector_bytes: u64) -> u64 {
+    e.extracted_chars as u64 + e.chunks as u64 * (vector_bytes + ROW_OVERHEAD_BYTES)
+}
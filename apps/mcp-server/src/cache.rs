@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Small in-memory LRU with a per-entry TTL.
+///
+/// Intended for tiny hot sets (query embeddings, repeated UI searches, answers), so eviction is a
+/// linear scan over entries rather than a linked list. Not thread-safe on its own:
+/// wrap it in a `std::sync::Mutex` and never hold the lock across an `.await`.
+pub struct TtlLru<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    tick: u64,
+    entries: HashMap<K, Entry<V>>,
+}
+
+struct Entry<V> {
+    value: V,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlLru<K, V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns a clone of the cached value if present and not expired.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let expired = match self.entries.get_mut(key) {
+            Some(e) if e.inserted_at.elapsed() <= self.ttl => {
+                e.last_used = self.tick;
+                return Some(e.value.clone());
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            self.entries.remove(key);
+        }
+        None
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_one();
+        }
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                inserted_at: Instant::now(),
+                last_used: self.tick,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.entries.remove(key).map(|e| e.value)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop expired entries first; otherwise the least recently used one.
+    fn evict_one(&mut self) {
+        let ttl = self.ttl;
+        self.entries.retain(|_, e| e.inserted_at.elapsed() <= ttl);
+        if self.entries.len() < self.capacity {
+            return;
+        }
+        if let Some(k) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, e)| e.last_used)
+            .map(|(k, _)| k.clone())
+        {
+            self.entries.remove(&k);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use crate::ingest::IngestStats;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub type CatalogHandle = Arc<RwLock<FileCatalog>>;
+
+/// One indexed file. The catalog is the cheap, file-level view of the knowledge base
+/// (chunks/vectors live in the DB).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_epoch_secs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_epoch_secs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hash: Option<String>,
+    /// Inode when indexed (Unix); with size and mtime it identifies a renamed file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inode: Option<u64>,
+    pub extracted_kind: String,
+    pub chunks: usize,
+    pub indexed_at_epoch_secs: i64,
+    /// Tags assigned by the tagging job (`crate::tagging`); cleared on re-index.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tagged_at_epoch_secs: Option<i64>,
+    /// User tags read from extended attributes at index time (`crate::xattrs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_comment: Option<String>,
+    /// Characters of extracted text (for the index size estimate, see `crate::budget`).
+    #[serde(default)]
+    pub extracted_chars: usize,
+    /// Times the file came up in search results / answers, and when it last did.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub access_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_accessed_epoch_secs: Option<i64>,
+    /// Removed from the index by the size budget; not re-indexed until the file changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evicted_at_epoch_secs: Option<i64>,
+    /// Bibliographic entry of a library attachment (`crate::library`); cleared on re-index, so
+    /// the next library pass stores the file again with its reference header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<crate::library::Reference>,
+    /// Definitions in a source file (`crate::symbols`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<crate::symbols::Symbol>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl CatalogEntry {
+    /// Entries from non-file sources use `<source>://` paths (e.g. `messages://`, see
+    /// `crate::messages`) and have no file on disk.
+    pub fn is_file(&self) -> bool {
+        !self.path.contains("://")
+    }
+
+    /// Evicted by the index size budget (kept in the catalog, no chunks in the index).
+    pub fn is_evicted(&self) -> bool {
+        self.evicted_at_epoch_secs.is_some()
+    }
+
+    /// File tags followed by generated tags, deduplicated.
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut out = self.file_tags.clone();
+        for t in &self.tags {
+            if !out.contains(t) {
+                out.push(t.clone());
+            }
+        }
+        out
+    }
+}
+
+/// A file whose last ingest failed (extraction error, unreadable, ...). Cleared when the file
+/// is indexed or leaves the catalog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestFailure {
+    pub path: String,
+    pub error: String,
+    pub failed_at_epoch_secs: i64,
+    /// Failed ingests in a row.
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NameMatch {
+    pub path: String,
+    pub name: String,
+    pub score: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_epoch_secs: Option<i64>,
+}
+
+/// File catalog persisted as JSON under the data dir, with an in-memory trigram index over
+/// lowercased paths for search-as-you-type (no embedding per keystroke).
+#[derive(Default)]
+pub struct FileCatalog {
+    file: PathBuf,
+    entries: BTreeMap<String, CatalogEntry>,
+    failures: BTreeMap<String, IngestFailure>,
+    trigrams: Option<TrigramIndex>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CatalogFile {
+    version: u32,
+    entries: Vec<CatalogEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<IngestFailure>,
+}
+
+const CATALOG_VERSION: u32 = 1;
+
+impl FileCatalog {
+    /// An empty catalog saved to `catalog.json` in `data_dir` (what was there is not read).
+    pub fn empty(data_dir: &Path) -> Self {
+        Self {
+            file: data_dir.join("catalog.json"),
+            entries: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            trigrams: None,
+        }
+    }
+
+    /// Load `catalog.json` from `data_dir` (missing or unreadable file -> empty catalog).
+    pub async fn load(data_dir: &Path) -> Self {
+        let file = data_dir.join("catalog.json");
+        let (entries, failures) = match tokio::fs::read_to_string(&file).await {
+            Ok(s) => match serde_json::from_str::<CatalogFile>(&s) {
+                Ok(c) => (
+                    c.entries.into_iter().map(|e| (e.path.clone(), e)).collect(),
+                    c.failures.into_iter().map(|f| (f.path.clone(), f)).collect(),
+                ),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid catalog {}: {e}", file.display());
+                    Default::default()
+                }
+            },
+            Err(_) => Default::default(),
+        };
+        Self {
+            file,
+            entries,
+            failures,
+            trigrams: None,
+        }
+    }
+
+    pub async fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.file.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create data dir {}: {e}", parent.display()))?;
+        }
+        let out = CatalogFile {
+            version: CATALOG_VERSION,
+            entries: self.entries.values().cloned().collect(),
+            failures: self.failures.values().cloned().collect(),
+        };
+        let s = serde_json::to_string(&out).map_err(|e| format!("Failed to serialize catalog: {e}"))?;
+        tokio::fs::write(&self.file, s)
+            .await
+            .map_err(|e| format!("Failed to write catalog {}: {e}", self.file.display()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&CatalogEntry> {
+        self.entries.get(path)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.values()
+    }
+
+    /// Failure of the last ingest of `path`, if it failed.
+    pub fn failure(&self, path: &str) -> Option<&IngestFailure> {
+        self.failures.get(path)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &IngestFailure> {
+        self.failures.values()
+    }
+
+    /// Note a failed ingest of `path` (counting failures in a row).
+    pub fn record_failure(&mut self, path: &str, error: &str) {
+        let attempts = self.failures.get(path).map_or(0, |f| f.attempts) + 1;
+        self.failures.insert(
+            path.to_string(),
+            IngestFailure {
+                path: path.to_string(),
+                error: error.to_string(),
+                failed_at_epoch_secs: now_epoch_secs(),
+                attempts,
+            },
+        );
+    }
+
+    pub fn record_ingest(&mut self, stats: &IngestStats) {
+        self.failures.remove(&stats.path);
+        // Access history survives re-indexing (it drives eviction).
+        let (access_count, last_accessed_epoch_secs) = self
+            .entries
+            .get(&stats.path)
+            .map(|e| (e.access_count, e.last_accessed_epoch_secs))
+            .unwrap_or_default();
+        self.upsert(CatalogEntry {
+            path: stats.path.clone(),
+            size_bytes: stats.file_size_bytes,
+            modified_epoch_secs: stats.file_mtime_epoch_secs,
+            created_epoch_secs: stats.file_created_epoch_secs,
+            file_hash: stats.file_hash.clone(),
+            inode: stats.file_inode,
+            extracted_kind: stats.extracted_kind.clone(),
+            chunks: stats.chunks,
+            indexed_at_epoch_secs: now_epoch_secs(),
+            tags: vec![],
+            tagged_at_epoch_secs: None,
+            file_tags: stats.file_tags.clone(),
+            file_comment: stats.file_comment.clone(),
+            extracted_chars: stats.extracted_chars,
+            access_count,
+            last_accessed_epoch_secs,
+            evicted_at_epoch_secs: None,
+            reference: None,
+            symbols: stats.symbols.clone(),
+        });
+    }
+
+    /// Count one access for each distinct path in `paths` (search hits, citations; unknown paths
+    /// are ignored).
+    pub fn record_access<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let now = now_epoch_secs();
+        let mut seen = std::collections::HashSet::new();
+        for path in paths {
+            if !seen.insert(path) {
+                continue;
+            }
+            if let Some(e) = self.entries.get_mut(path) {
+                e.access_count += 1;
+                e.last_accessed_epoch_secs = Some(now);
+            }
+        }
+    }
+
+    /// Mark `path` evicted: it stays listed but has no chunks until the file changes.
+    pub fn mark_evicted(&mut self, path: &str) {
+        if let Some(e) = self.entries.get_mut(path) {
+            e.chunks = 0;
+            e.evicted_at_epoch_secs = Some(now_epoch_secs());
+        }
+    }
+
+    /// Record tags for an indexed file (no-op for unknown paths).
+    pub fn set_tags(&mut self, path: &str, tags: Vec<String>) {
+        if let Some(e) = self.entries.get_mut(path) {
+            e.tags = tags;
+            e.tagged_at_epoch_secs = Some(now_epoch_secs());
+        }
+    }
+
+    /// Set the library references (`crate::library`): those in `refs` by path, none for every
+    /// other entry.
+    pub fn set_references(&mut self, mut refs: HashMap<String, crate::library::Reference>) {
+        for e in self.entries.values_mut() {
+            e.reference = refs.remove(&e.path);
+        }
+    }
+
+    /// Move the entry of `from` to `to`, keeping tags, access history and index time (the
+    /// file was renamed, not changed). `false` for an unknown `from`.
+    pub fn rename(&mut self, from: &str, to: &str, inode: Option<u64>) -> bool {
+        let Some(mut entry) = self.entries.remove(from) else {
+            return false;
+        };
+        entry.path = to.to_string();
+        entry.inode = inode.or(entry.inode);
+        self.upsert(entry);
+        true
+    }
+
+    pub fn upsert(&mut self, entry: CatalogEntry) {
+        self.entries.insert(entry.path.clone(), entry);
+        self.trigrams = None;
+    }
+
+    pub fn remove(&mut self, path: &str) -> Option<CatalogEntry> {
+        self.failures.remove(path);
+        let removed = self.entries.remove(path);
+        if removed.is_some() {
+            self.trigrams = None;
+        }
+        removed
+    }
+
+    /// Keyword search over file names/paths.
+    ///
+    /// Every whitespace-separated term must appear (case-insensitive) somewhere in the path.
+    /// Matches in the file name rank above matches in parent directories; a file name that
+    /// starts with a term ranks highest (the usual "prefix while typing" case).
+    pub fn search_names(&mut self, query: &str, limit: usize) -> Vec<NameMatch> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if terms.is_empty() || limit == 0 {
+            return vec![];
+        }
+
+        if self.trigrams.is_none() {
+            self.trigrams = Some(TrigramIndex::build(self.entries.keys()));
+        }
+        let Some(index) = self.trigrams.as_ref() else {
+            return vec![];
+        };
+
+        let candidates = index.candidates(&terms);
+        let mut out: Vec<NameMatch> = vec![];
+        for id in candidates {
+            let path = &index.paths[id];
+            let lower = &index.lower[id];
+            let name_start = lower.rfind(['/', '\\']).map(|i| i + 1).unwrap_or(0);
+            let name = &lower[name_start..];
+
+            let mut score = 0u32;
+            let mut all = true;
+            for t in &terms {
+                if name.starts_with(t.as_str()) {
+                    score += 4;
+                } else if name.contains(t.as_str()) {
+                    score += 2;
+                } else if lower.contains(t.as_str()) {
+                    score += 1;
+                } else {
+                    all = false;
+                    break;
+                }
+            }
+            if !all {
+                continue;
+            }
+
+            let entry = self.entries.get(path);
+            out.push(NameMatch {
+                path: path.clone(),
+                name: Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone()),
+                score,
+                modified_epoch_secs: entry.and_then(|e| e.modified_epoch_secs),
+            });
+        }
+
+        out.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.len().cmp(&b.path.len()))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        out.truncate(limit);
+        out
+    }
+}
+
+/// Trigram postings over lowercased paths. Rebuilt lazily after catalog changes.
+struct TrigramIndex {
+    paths: Vec<String>,
+    lower: Vec<String>,
+    postings: HashMap<[char; 3], Vec<usize>>,
+}
+
+impl TrigramIndex {
+    fn build<'a>(paths: impl Iterator<Item = &'a String>) -> Self {
+        let paths: Vec<String> = paths.cloned().collect();
+        let lower: Vec<String> = paths.iter().map(|p| p.to_lowercase()).collect();
+        let mut postings: HashMap<[char; 3], Vec<usize>> = HashMap::new();
+        for (id, p) in lower.iter().enumerate() {
+            let mut grams = trigrams(p);
+            grams.sort_unstable();
+            grams.dedup();
+            for g in grams {
+                postings.entry(g).or_default().push(id);
+            }
+        }
+        Self {
+            paths,
+            lower,
+            postings,
+        }
+    }
+
+    /// Ids whose paths contain every trigram of every term (>= 3 chars).
+    /// Terms shorter than 3 chars can't narrow the set and are checked by the caller.
+    fn candidates(&self, terms: &[String]) -> Vec<usize> {
+        let mut acc: Option<Vec<usize>> = None;
+        for t in terms {
+            for g in trigrams(t) {
+                let Some(list) = self.postings.get(&g) else {
+                    return vec![];
+                };
+                acc = Some(match acc {
+                    None => list.clone(),
+                    Some(prev) => intersect_sorted(&prev, list),
+                });
+                if acc.as_ref().is_some_and(|a| a.is_empty()) {
+                    return vec![];
+                }
+            }
+        }
+        acc.unwrap_or_else(|| (0..self.paths.len()).collect())
+    }
+}
+
+fn trigrams(s: &str) -> Vec<[char; 3]> {
+    let chars: Vec<char> = s.chars().collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+fn intersect_sorted(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = vec![];
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+pub fn now_epoch_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
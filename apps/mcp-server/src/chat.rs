@@ -0,0 +1,394 @@
+//! Multi-turn chat over the local index.
+//!
+//! Each session keeps its message history plus every source retrieved so far. Sources keep a
+//! stable number for the whole session, so follow-ups like "what about the second one?" can be
+//! answered against `[2]` from an earlier turn.
+
+use crate::access::LockedPaths;
+use crate::ask::{retrieve, Citation};
+use crate::errors::ToolError;
+use crate::llm::{estimate_llm_tokens, truncate_to_llm_tokens};
+use crate::prompt::PromptTemplate;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Max sessions kept in memory; the least recently used one is dropped beyond this.
+const MAX_SESSIONS: usize = 64;
+/// Sources beyond this many are forgotten (oldest first).
+const MAX_SOURCES_PER_SESSION: usize = 40;
+/// Don't bother including a trimmed source smaller than this.
+const MIN_TRIMMED_SOURCE_TOKENS: usize = 48;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    pub at_epoch_secs: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ChatSource {
+    citation: Citation,
+    content: String,
+    /// Turn number that last retrieved this source (recent sources win the budget).
+    last_turn: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatSession {
+    pub id: String,
+    pub created_at_epoch_secs: i64,
+    pub messages: Vec<ChatMessage>,
+    sources: Vec<ChatSource>,
+    /// Sources ever added, so numbers of dropped sources are never handed out again.
+    sources_added: usize,
+    turns: usize,
+    last_used: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatReply {
+    pub session_id: String,
+    pub answer: String,
+    /// Sources included in this turn's prompt (numbers are stable for the session).
+    pub citations: Vec<Citation>,
+    pub prompt_tokens_estimate: usize,
+    /// History messages that did not fit the token budget.
+    pub history_messages_dropped: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatHistory {
+    pub session_id: String,
+    pub messages: Vec<ChatMessage>,
+    pub sources: Vec<Citation>,
+}
+
+/// In-memory session store (kept in `AppState`).
+#[derive(Default)]
+pub struct ChatStore {
+    sessions: HashMap<String, ChatSession>,
+    /// One lock per session, held for a whole turn: a turn works on a copy of the session
+    /// (`checkout`) and writes it back (`store`), so overlapping turns would lose one another.
+    turn_locks: HashMap<String, Arc<tokio::sync::Mutex<()>>>,
+    tick: u64,
+    next_id: u64,
+}
+
+impl ChatStore {
+    /// The session id to use (a new one when `id` is None) and its turn lock.
+    fn turn_lock(&mut self, id: Option<&str>) -> (String, Arc<tokio::sync::Mutex<()>>) {
+        let id = id.map(str::to_string).unwrap_or_else(|| {
+            self.next_id += 1;
+            let seed = format!("{}-{}", crate::catalog::now_epoch_secs(), self.next_id);
+            blake3::hash(seed.as_bytes()).to_hex()[..16].to_string()
+        });
+        let lock = self.turn_locks.entry(id.clone()).or_default().clone();
+        (id, lock)
+    }
+
+    /// A copy of session `id` (a new, empty one when unknown).
+    fn checkout(&mut self, id: &str) -> ChatSession {
+        self.tick += 1;
+        if let Some(s) = self.sessions.get(id) {
+            return s.clone();
+        }
+        ChatSession {
+            id: id.to_string(),
+            created_at_epoch_secs: crate::catalog::now_epoch_secs(),
+            messages: vec![],
+            sources: vec![],
+            sources_added: 0,
+            turns: 0,
+            last_used: self.tick,
+        }
+    }
+
+    fn store(&mut self, mut session: ChatSession) {
+        self.tick += 1;
+        session.last_used = self.tick;
+        self.sessions.insert(session.id.clone(), session);
+        while self.sessions.len() > MAX_SESSIONS {
+            let Some(oldest) = self
+                .sessions
+                .values()
+                .min_by_key(|s| s.last_used)
+                .map(|s| s.id.clone())
+            else {
+                break;
+            };
+            self.sessions.remove(&oldest);
+        }
+        // Locks of dropped sessions, unless a turn is waiting on them.
+        let sessions = &self.sessions;
+        self.turn_locks
+            .retain(|id, lock| sessions.contains_key(id) || Arc::strong_count(lock) > 1);
+    }
+
+    /// Session `id`'s messages and its sources outside `locked` collections.
+    pub fn history(&self, id: &str, locked: &LockedPaths) -> Option<ChatHistory> {
+        self.sessions.get(id).map(|s| ChatHistory {
+            session_id: s.id.clone(),
+            messages: s.messages.clone(),
+            sources: s
+                .sources
+                .iter()
+                .filter(|src| locked.collection_of(&src.citation.path).is_none())
+                .map(|src| src.citation.clone())
+                .collect(),
+        })
+    }
+
+    pub fn reset(&mut self, id: &str) -> bool {
+        self.sessions.remove(id).is_some()
+    }
+
+    pub fn session_ids(&self) -> Vec<String> {
+        let mut ids: Vec<_> = self.sessions.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatArgs {
+    #[serde(default)]
+    session_id: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    top_k: Option<usize>,
+    /// Drop the session (history + sources) instead of sending a message.
+    #[serde(default)]
+    reset: bool,
+    /// Return the session history instead of sending a message.
+    #[serde(default)]
+    history: bool,
+}
+
+/// MCP tool entrypoint (`silo_chat`).
+pub async fn chat_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ChatArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+
+    if args.reset || args.history {
+        let id = args
+            .session_id
+            .ok_or_else(|| "session_id is required for reset/history".to_string())?;
+        let locked = crate::access::locked_paths(state).await;
+        let mut store = state.chats.lock().unwrap_or_else(|e| e.into_inner());
+        if args.reset {
+            return Ok(serde_json::json!({ "session_id": id, "reset": store.reset(&id) }));
+        }
+        let history = store
+            .history(&id, &locked)
+            .ok_or_else(|| format!("Unknown chat session: {id}"))?;
+        return serde_json::to_value(history).map_err(|e| format!("failed to serialize history: {e}").into());
+    }
+
+    let message = args
+        .message
+        .ok_or_else(|| "message is required".to_string())?;
+    let reply = chat(state, args.session_id, message, args.top_k).await?;
+    serde_json::to_value(reply).map_err(|e| format!("failed to serialize reply: {e}").into())
+}
+
+/// Send one user message in a session (created when `session_id` is None or unknown).
+pub async fn chat(
+    state: &SharedState,
+    session_id: Option<String>,
+    message: String,
+    top_k: Option<usize>,
+) -> Result<ChatReply, ToolError> {
+    if message.trim().is_empty() {
+        return Err("message must not be empty".into());
+    }
+
+    let (id, turn_lock) = state
+        .chats
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .turn_lock(session_id.as_deref());
+    // Turns of one session run one at a time; other sessions are not held up.
+    let _turn = turn_lock.lock().await;
+    let mut session = state.chats.lock().unwrap_or_else(|e| e.into_inner()).checkout(&id);
+    session.turns += 1;
+    let turn = session.turns;
+
+    // Retrieve with the new message plus the previous user turn, so short follow-ups
+    // ("and the budget?") still land near the topic being discussed.
+    let retrieval_query = match session.messages.iter().rev().find(|m| m.role == ChatRole::User) {
+        Some(prev) => format!("{}\n{}", prev.content, message),
+        None => message.clone(),
+    };
+    let hits = retrieve(state, &retrieval_query, top_k.unwrap_or(5).clamp(1, 20), None).await?;
+    for h in hits {
+        let existing = session
+            .sources
+            .iter_mut()
+            .find(|s| s.citation.path == h.path && s.citation.chunk_index == h.chunk_index);
+        if let Some(src) = existing {
+            src.last_turn = turn;
+            continue;
+        }
+        session.sources_added += 1;
+        let index = session.sources_added;
+        session.sources.push(ChatSource {
+            citation: Citation {
+                index,
+                path: h.path.clone(),
+                id: h.id.clone(),
+                chunk_index: h.chunk_index,
+                page: h.page,
+                score: h.score,
+                content_preview: h.content_preview.clone(),
+                reference: crate::library::reference_of(state, &h.path).await,
+            },
+            content: h.content.or(h.content_preview).unwrap_or_default(),
+            last_turn: turn,
+        });
+    }
+    // Retrieval only filters new hits; sources from earlier turns may be in a collection that
+    // has been locked since.
+    let locked = crate::access::locked_paths(state).await;
+    session
+        .sources
+        .retain(|s| locked.collection_of(&s.citation.path).is_none());
+    if session.sources.len() > MAX_SOURCES_PER_SESSION {
+        session.sources.sort_by_key(|s| std::cmp::Reverse(s.last_turn));
+        session.sources.truncate(MAX_SOURCES_PER_SESSION);
+        session.sources.sort_by_key(|s| s.citation.index);
+    }
+
+    // Sources and history share the model's prompt budget (context window minus answer reserve).
+    let budget = state.context_budget().await.prompt_tokens();
+    let built = build_prompt(&state.prompts.read().await.chat, &session, &message, budget);
+    let answer = state.llm.generate(built.prompt).await?;
+
+    let now = crate::catalog::now_epoch_secs();
+    session.messages.push(ChatMessage {
+        role: ChatRole::User,
+        content: message,
+        at_epoch_secs: now,
+    });
+    session.messages.push(ChatMessage {
+        role: ChatRole::Assistant,
+        content: answer.clone(),
+        at_epoch_secs: now,
+    });
+
+    let reply = ChatReply {
+        session_id: session.id.clone(),
+        answer,
+        citations: built.citations,
+        prompt_tokens_estimate: built.tokens,
+        history_messages_dropped: built.history_dropped,
+    };
+    state
+        .chats
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .store(session);
+    Ok(reply)
+}
+
+struct BuiltPrompt {
+    prompt: String,
+    citations: Vec<Citation>,
+    tokens: usize,
+    history_dropped: usize,
+}
+
+/// Budgeting: sources retrieved most recently go first (the last one that doesn't fit is
+/// trimmed), then the newest history turns, until the prompt budget is used up.
+/// The question itself is always included.
+fn build_prompt(
+    template: &PromptTemplate,
+    session: &ChatSession,
+    message: &str,
+    budget: usize,
+) -> BuiltPrompt {
+    let mut used = template.literal_tokens() + estimate_llm_tokens(message);
+
+    let mut by_recency: Vec<&ChatSource> = session.sources.iter().collect();
+    by_recency.sort_by_key(|s| (std::cmp::Reverse(s.last_turn), s.citation.index));
+    let mut included: Vec<&ChatSource> = vec![];
+    let mut trimmed: HashMap<usize, String> = HashMap::new();
+    for src in by_recency {
+        let header_cost = estimate_llm_tokens(&src.citation.path) + 4;
+        let cost = header_cost + estimate_llm_tokens(&src.content);
+        if used + cost <= budget {
+            used += cost;
+            included.push(src);
+        } else if budget.saturating_sub(used) >= header_cost + MIN_TRIMMED_SOURCE_TOKENS {
+            let body = truncate_to_llm_tokens(&src.content, budget - used - header_cost);
+            used += header_cost + estimate_llm_tokens(&body);
+            trimmed.insert(src.citation.index, body);
+            included.push(src);
+        }
+    }
+    included.sort_by_key(|s| s.citation.index);
+
+    let mut history: Vec<&ChatMessage> = vec![];
+    for m in session.messages.iter().rev() {
+        let cost = estimate_llm_tokens(&m.content) + 2;
+        if used + cost > budget {
+            break;
+        }
+        used += cost;
+        history.push(m);
+    }
+    history.reverse();
+    let history_dropped = session.messages.len() - history.len();
+
+    let mut context = String::new();
+    if included.is_empty() {
+        context.push_str("(no matching documents)\n");
+    }
+    for src in &included {
+        let body = trimmed
+            .get(&src.citation.index)
+            .map(String::as_str)
+            .unwrap_or(&src.content);
+        context.push_str(&format!(
+            "[{}] {}\n{}\n\n",
+            src.citation.index,
+            src.citation.path,
+            body.trim()
+        ));
+    }
+
+    let mut history_text = String::new();
+    if !history.is_empty() {
+        history_text.push_str("Conversation so far:\n");
+        for m in &history {
+            let who = match m.role {
+                ChatRole::User => "User",
+                ChatRole::Assistant => "Assistant",
+            };
+            history_text.push_str(&format!("{who}: {}\n", m.content.trim()));
+        }
+    }
+
+    let prompt = template.render(&[
+        ("context", &context),
+        ("history", &history_text),
+        ("question", message),
+    ]);
+
+    BuiltPrompt {
+        prompt,
+        citations: included.iter().map(|s| s.citation.clone()).collect(),
+        tokens: used,
+        history_dropped,
+    }
+}
@@ -0,0 +1,415 @@
+//! Chat export import (`silo_import_chat_export`), using the Messages conversation-chunk model
+//! (`crate::messages`).
+//!
+//! - WhatsApp: "Export chat" `.txt` files, or the `.zip` with media (the text is read with
+//!   `unzip`). Both the iOS (`[31/12/23, 21:41:05] Alex: hi`) and Android
+//!   (`31/12/23, 21:41 - Alex: hi`) layouts are understood. Day/month order is inferred from the
+//!   file. Documents: `whatsapp://<chat>/<n>`.
+//! - Signal Desktop: its database is SQLCipher-encrypted, so this needs the `sqlcipher` CLI and a
+//!   plain `key` in Signal's `config.json`. Keys sealed in the OS keychain (`encryptedKey`) are not
+//!   accessible. Documents: `signal://<conversation id>/<first sent_at>`.
+//!
+//! Each import replaces what the previous import of the same chat (or Signal profile) stored.
+
+use crate::errors::ToolError;
+use crate::messages::{
+    conversation_windows, remove_chunks, snapshot_db, sqlite_query, store_conversation_chunks, ConversationChunk,
+    ConversationMessage,
+};
+use crate::state::{expand_tilde, SharedState};
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+pub const WHATSAPP_PREFIX: &str = "whatsapp://";
+pub const SIGNAL_PREFIX: &str = "signal://";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatExportFormat {
+    Whatsapp,
+    Signal,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportChatExportArgs {
+    /// WhatsApp `.txt`/`.zip` export, or the Signal Desktop data directory (default location
+    /// when omitted with `format: "signal"`).
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Inferred from `path` when omitted (directories are Signal, files WhatsApp).
+    #[serde(default)]
+    pub format: Option<ChatExportFormat>,
+    /// WhatsApp chat name (default: from the file name).
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatImportSummary {
+    pub format: ChatExportFormat,
+    pub source: String,
+    pub conversations: usize,
+    pub messages: usize,
+    pub chunks: usize,
+    /// WhatsApp lines that looked like a message header but had an unreadable date.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub unparsed_lines: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// MCP tool entrypoint (`silo_import_chat_export`).
+pub async fn import_chat_export_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ImportChatExportArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let summary = import_chat_export(state, &args).await?;
+    serde_json::to_value(summary).map_err(|e| format!("failed to serialize import summary: {e}").into())
+}
+
+pub async fn import_chat_export(state: &SharedState, args: &ImportChatExportArgs) -> Result<ChatImportSummary, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let path = args.path.as_deref().map(expand_tilde);
+    let format = match (args.format, &path) {
+        (Some(f), _) => f,
+        (None, Some(p)) if tokio::fs::metadata(p).await.is_ok_and(|m| m.is_dir()) => ChatExportFormat::Signal,
+        (None, Some(_)) => ChatExportFormat::Whatsapp,
+        (None, None) => return Err("`path` or `format: \"signal\"` is required".into()),
+    };
+    let summary = match format {
+        ChatExportFormat::Whatsapp => {
+            let Some(path) = path else {
+                return Err("`path` to a WhatsApp .txt or .zip export is required".into());
+            };
+            import_whatsapp(state, &path, args.name.as_deref()).await?
+        }
+        ChatExportFormat::Signal => {
+            let dir = path.unwrap_or_else(default_signal_dir);
+            let snapshot_dir = state.data_dir.join("signal_snapshot");
+            let res = import_signal(state, &dir, &snapshot_dir).await;
+            let _ = tokio::fs::remove_dir_all(&snapshot_dir).await;
+            res?
+        }
+    };
+    state.index_changed();
+    state.save_catalog().await;
+    Ok(summary)
+}
+
+/// Window settings of the Messages source (defaults when it is not configured).
+async fn window_settings(state: &SharedState) -> (i64, usize) {
+    let cfg = state.messages_config().await.unwrap_or_default();
+    ((cfg.window_minutes.max(1) * 60) as i64, cfg.max_messages_per_chunk)
+}
+
+async fn import_whatsapp(state: &SharedState, path: &Path, name: Option<&str>) -> Result<ChatImportSummary, ToolError> {
+    let text = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip")) {
+        let out = tokio::process::Command::new("unzip")
+            .arg("-p")
+            .arg(path)
+            .arg("*.txt")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run unzip (is it installed?): {e}"))?;
+        if !out.status.success() {
+            return Err(format!("No chat text found in {}", path.display()).into());
+        }
+        String::from_utf8_lossy(&out.stdout).to_string()
+    } else {
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| ToolError::read_failed(path.display(), e))?
+    };
+    let (messages, unparsed_lines) = parse_whatsapp(&text);
+    if messages.is_empty() {
+        return Err(format!("{} does not look like a WhatsApp chat export", path.display()).into());
+    }
+
+    let title = name.map(str::to_string).unwrap_or_else(|| whatsapp_chat_name(path));
+    let key = blake3::hash(title.as_bytes()).to_hex()[..16].to_string();
+    let prefix = format!("{WHATSAPP_PREFIX}{key}/");
+    remove_chunks(state, &prefix).await?;
+
+    let mut participants: Vec<String> = messages.iter().map(|m| m.sender.clone()).collect();
+    participants.sort();
+    participants.dedup();
+    let (gap_secs, max_messages) = window_settings(state).await;
+    let chunks: Vec<ConversationChunk> = conversation_windows(&messages, gap_secs, max_messages)
+        .into_iter()
+        .map(|w| ConversationChunk {
+            path: format!("{prefix}{}", w[0].id),
+            title: title.clone(),
+            participants: participants.clone(),
+            messages: w.to_vec(),
+        })
+        .collect();
+    let n_chunks = chunks.len();
+    let errors = store_conversation_chunks(state, "whatsapp", chunks).await;
+    Ok(ChatImportSummary {
+        format: ChatExportFormat::Whatsapp,
+        source: path.to_string_lossy().to_string(),
+        conversations: 1,
+        messages: messages.len(),
+        chunks: n_chunks,
+        unparsed_lines,
+        errors,
+    })
+}
+
+/// `WhatsApp Chat with Alex.txt` / `WhatsApp Chat - Alex.zip` -> `Alex`.
+fn whatsapp_chat_name(path: &Path) -> String {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let name = stem
+        .strip_prefix("WhatsApp Chat with ")
+        .or_else(|| stem.strip_prefix("WhatsApp Chat - "))
+        .unwrap_or(&stem)
+        .trim();
+    if name.is_empty() || name == "_chat" {
+        "WhatsApp chat".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Date and time fields of a message header, before day/month order is known.
+struct RawStamp {
+    a: u32,
+    b: u32,
+    year: i32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+/// Messages (ids are line order) and the number of header lines whose date was invalid.
+/// System lines without a sender ("Messages are end-to-end encrypted") are dropped.
+fn parse_whatsapp(text: &str) -> (Vec<ConversationMessage>, usize) {
+    let mut parsed: Vec<(RawStamp, String, String)> = vec![];
+    let mut in_system_line = false;
+    for line in text.lines() {
+        let line = line.trim_start_matches(['\u{feff}', '\u{200e}']);
+        match parse_header(line) {
+            Some((stamp, rest)) => match rest.split_once(": ") {
+                Some((sender, body)) => {
+                    in_system_line = false;
+                    parsed.push((stamp, sender.trim().to_string(), body.to_string()));
+                }
+                None => in_system_line = true,
+            },
+            None if !in_system_line => {
+                if let Some((_, _, body)) = parsed.last_mut() {
+                    body.push('\n');
+                    body.push_str(line);
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Day-first unless some second field is above 12 (and no first field is).
+    let day_first = parsed.iter().any(|(s, _, _)| s.a > 12) || !parsed.iter().any(|(s, _, _)| s.b > 12);
+    let mut unparsed = 0;
+    let mut out = vec![];
+    for (i, (s, sender, body)) in parsed.into_iter().enumerate() {
+        let (day, month) = if day_first { (s.a, s.b) } else { (s.b, s.a) };
+        let ts = NaiveDate::from_ymd_opt(s.year, month, day)
+            .and_then(|d| d.and_hms_opt(s.hour, s.minute, s.second))
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest());
+        let Some(ts) = ts else {
+            unparsed += 1;
+            continue;
+        };
+        let body = body.trim_start_matches('\u{200e}').trim().to_string();
+        if body.is_empty() {
+            continue;
+        }
+        out.push(ConversationMessage {
+            id: i as i64,
+            sent_at_epoch_secs: ts.timestamp(),
+            sender,
+            text: body,
+        });
+    }
+    (out, unparsed)
+}
+
+/// `[date, time] rest` (iOS) or `date, time - rest` (Android).
+fn parse_header(line: &str) -> Option<(RawStamp, &str)> {
+    let (stamp, rest) = if let Some(inner) = line.strip_prefix('[') {
+        let (stamp, rest) = inner.split_once(']')?;
+        (stamp, rest.trim_start())
+    } else {
+        line.split_once(" - ")?
+    };
+    Some((parse_stamp(stamp)?, rest))
+}
+
+fn parse_stamp(stamp: &str) -> Option<RawStamp> {
+    let (date, time) = stamp.split_once(',').or_else(|| stamp.split_once(' '))?;
+    let mut d = date.trim().split(['/', '.', '-']).map(|p| p.parse::<u32>().ok());
+    let (a, b, y) = (d.next()??, d.next()??, d.next()??);
+    if d.next().is_some() || a == 0 || b == 0 {
+        return None;
+    }
+    let year = if y < 100 { 2000 + y as i32 } else { y as i32 };
+
+    let time = time.replace(['\u{202f}', '\u{a0}'], " ").to_lowercase().replace('.', "");
+    let pm = time.contains("pm");
+    let am = time.contains("am");
+    let clock = time.trim().trim_end_matches("pm").trim_end_matches("am").trim();
+    let mut t = clock.split(':').map(|p| p.parse::<u32>().ok());
+    let (mut hour, minute) = (t.next()??, t.next()??);
+    let second = t.next().flatten().unwrap_or(0);
+    if am || pm {
+        if hour == 0 || hour > 12 {
+            return None;
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    (hour < 24 && minute < 60 && second < 60).then_some(RawStamp {
+        a,
+        b,
+        year,
+        hour,
+        minute,
+        second,
+    })
+}
+
+fn default_signal_dir() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        expand_tilde("~/Library/Application Support/Signal")
+    } else {
+        expand_tilde("~/.config/Signal")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SignalConversationRow {
+    id: String,
+    json: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignalMessageRow {
+    conversation_id: String,
+    kind: String,
+    body: String,
+    sent_at: i64,
+    source: Option<String>,
+}
+
+async fn import_signal(state: &SharedState, dir: &Path, snapshot_dir: &Path) -> Result<ChatImportSummary, String> {
+    let config: Value = tokio::fs::read_to_string(dir.join("config.json"))
+        .await
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .ok_or_else(|| format!("No Signal Desktop config.json in {}", dir.display()))?;
+    let Some(key) = config.get("key").and_then(Value::as_str) else {
+        return Err(if config.get("encryptedKey").is_some() {
+            "Signal Desktop's database key is sealed in the OS keychain and cannot be read".to_string()
+        } else {
+            "Signal Desktop config.json has no database key".to_string()
+        });
+    };
+    if !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Unexpected Signal Desktop database key format".to_string());
+    }
+    let db = snapshot_db(&dir.join("sql").join("db.sqlite"), snapshot_dir, "db.sqlite").await?;
+    let pragma = format!("PRAGMA key = \"x'{key}'\";");
+
+    let conversations: Vec<SignalConversationRow> =
+        sqlite_query("sqlcipher", &db, Some(&pragma), "SELECT id, json FROM conversations").await?;
+    // Conversation id -> title; sender id (service id / uuid / phone) -> name.
+    let mut titles: HashMap<String, String> = HashMap::new();
+    let mut senders: HashMap<String, String> = HashMap::new();
+    for c in conversations {
+        let Ok(j) = serde_json::from_str::<Value>(&c.json) else {
+            continue;
+        };
+        let field = |k: &str| j.get(k).and_then(Value::as_str).filter(|s| !s.trim().is_empty());
+        let Some(name) = field("name")
+            .or_else(|| field("profileFullName"))
+            .or_else(|| field("profileName"))
+            .or_else(|| field("e164"))
+        else {
+            continue;
+        };
+        for id in ["serviceId", "uuid", "e164"].into_iter().filter_map(field) {
+            senders.insert(id.to_string(), name.to_string());
+        }
+        titles.insert(c.id, name.to_string());
+    }
+
+    let rows: Vec<SignalMessageRow> = sqlite_query(
+        "sqlcipher",
+        &db,
+        Some(&pragma),
+        "SELECT conversationId AS conversation_id, type AS kind, body, sent_at, \
+         COALESCE(json_extract(json, '$.sourceServiceId'), json_extract(json, '$.sourceUuid'), json_extract(json, '$.source')) AS source \
+         FROM messages WHERE type IN ('incoming', 'outgoing') AND body IS NOT NULL AND trim(body) != ''",
+    )
+    .await?;
+
+    remove_chunks(state, SIGNAL_PREFIX).await?;
+    let mut by_conversation: BTreeMap<String, Vec<ConversationMessage>> = BTreeMap::new();
+    for r in rows {
+        let sender = if r.kind == "outgoing" {
+            "Me".to_string()
+        } else {
+            r.source
+                .as_deref()
+                .map(|s| senders.get(s).cloned().unwrap_or_else(|| s.to_string()))
+                .unwrap_or_else(|| "Unknown".to_string())
+        };
+        by_conversation.entry(r.conversation_id).or_default().push(ConversationMessage {
+            id: r.sent_at,
+            sent_at_epoch_secs: r.sent_at / 1000,
+            sender,
+            text: r.body,
+        });
+    }
+
+    let (gap_secs, max_messages) = window_settings(state).await;
+    let mut summary = ChatImportSummary {
+        format: ChatExportFormat::Signal,
+        source: dir.to_string_lossy().to_string(),
+        conversations: 0,
+        messages: 0,
+        chunks: 0,
+        unparsed_lines: 0,
+        errors: vec![],
+    };
+    for (conversation_id, mut msgs) in by_conversation {
+        msgs.sort_by_key(|m| m.id);
+        let mut participants: Vec<String> = msgs.iter().map(|m| m.sender.clone()).collect();
+        participants.sort();
+        participants.dedup();
+        let title = titles
+            .get(&conversation_id)
+            .cloned()
+            .unwrap_or_else(|| participants.join(", "));
+        let chunks: Vec<ConversationChunk> = conversation_windows(&msgs, gap_secs, max_messages)
+            .into_iter()
+            .map(|w| ConversationChunk {
+                path: format!("{SIGNAL_PREFIX}{conversation_id}/{}", w[0].id),
+                title: title.clone(),
+                participants: participants.clone(),
+                messages: w.to_vec(),
+            })
+            .collect();
+        summary.conversations += 1;
+        summary.messages += msgs.len();
+        summary.chunks += chunks.len();
+        summary.errors.extend(store_conversation_chunks(state, "signal", chunks).await);
+    }
+    Ok(summary)
+}
@@ -1,4 +1,11 @@
+use crate::config::{ChunkBoundaries, ChunkCapSelection};
 use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Low bits of the boundary hash that decide a cut; bit k depends on the last k+1 tokens only,
+/// so a content-defined boundary is a function of the 32 tokens before it.
+const BOUNDARY_HASH_MASK: u64 = 0xFFFF_FFFF;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct TextChunk {
@@ -8,6 +15,104 @@ pub struct TextChunk {
     pub end_token: usize,
 }
 
+/// How ingestion splits text (`filesystem` config `chunk_tokens`, `chunk_overlap_tokens`,
+/// `chunk_boundaries`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChunkOptions {
+    pub tokens: usize,
+    pub overlap_tokens: usize,
+    pub boundaries: ChunkBoundaries,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            tokens: 500,
+            overlap_tokens: 50,
+            boundaries: ChunkBoundaries::Fixed,
+        }
+    }
+}
+
+/// `ChunkOptions` per file: the source-wide options, or those of the file's extension
+/// (`filesystem.extension_overrides`), plus the per-file chunk cap and the time window of log
+/// chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkRules {
+    pub default: ChunkOptions,
+    /// Keyed by lowercased extension without the dot.
+    pub by_extension: HashMap<String, ChunkOptions>,
+    pub cap: Option<ChunkCap>,
+    /// `filesystem.log_window_minutes` (`crate::logs`).
+    pub log_window_minutes: u64,
+}
+
+/// `filesystem.max_chunks_per_file` and `chunk_cap_selection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkCap {
+    pub max_chunks: usize,
+    pub selection: ChunkCapSelection,
+}
+
+impl ChunkRules {
+    pub fn for_path(&self, path: &Path) -> ChunkOptions {
+        crate::logs::effective_extension(path)
+            .and_then(|e| self.by_extension.get(&e))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl From<ChunkOptions> for ChunkRules {
+    /// The same options for every file.
+    fn from(default: ChunkOptions) -> Self {
+        Self {
+            default,
+            by_extension: HashMap::new(),
+            cap: None,
+            log_window_minutes: crate::logs::DEFAULT_WINDOW_MINUTES,
+        }
+    }
+}
+
+/// Keep at most `cap.max_chunks` of `chunks`, in order. Kept chunks keep their `index` (their
+/// position in the whole file), so indexes of a capped file have gaps.
+pub fn cap_chunks(chunks: Vec<TextChunk>, cap: ChunkCap) -> Vec<TextChunk> {
+    let n = chunks.len();
+    let max = cap.max_chunks.max(1);
+    if n <= max {
+        return chunks;
+    }
+    let keep: Vec<bool> = match cap.selection {
+        ChunkCapSelection::HeadTail => {
+            let head = max.div_ceil(2);
+            let tail = max - head;
+            (0..n).map(|i| i < head || i >= n - tail).collect()
+        }
+        ChunkCapSelection::Sampled => {
+            let mut keep = vec![false; n];
+            if max == 1 {
+                keep[0] = true;
+            } else {
+                for k in 0..max {
+                    keep[k * (n - 1) / (max - 1)] = true;
+                }
+            }
+            keep
+        }
+    };
+    chunks.into_iter().zip(keep).filter(|(_, k)| *k).map(|(c, _)| c).collect()
+}
+
+pub fn chunk_text(text: &str, opts: &ChunkOptions) -> Vec<TextChunk> {
+    match opts.boundaries {
+        ChunkBoundaries::Fixed => chunk_by_whitespace_tokens(text, opts.tokens, opts.overlap_tokens),
+        ChunkBoundaries::Content => chunk_by_content(text, opts.tokens, opts.overlap_tokens),
+        ChunkBoundaries::Headings => chunk_by_units(text, heading_starts(text), opts.tokens, opts.overlap_tokens),
+        ChunkBoundaries::Rows => chunk_by_units(text, row_starts(text), opts.tokens, opts.overlap_tokens),
+    }
+}
+
 /// MVP tokenization: whitespace tokens.
 ///
 /// This matches the "500-token chunks with overlap" requirement approximately.
@@ -45,5 +150,173 @@ pub fn chunk_by_whitespace_tokens(text: &str, chunk_tokens: usize, overlap_token
     chunks
 }
 
+/// Content-defined chunks over whitespace tokens: a chunk ends where a rolling (gear) hash of
+/// the preceding tokens hits a target value, once it is at least half of `chunk_tokens` long,
+/// and at twice `chunk_tokens` at the latest. Chunks average about `chunk_tokens`.
+///
+/// Boundaries depend on nearby text only, so inserting a paragraph near the start of a file
+/// changes the chunks around the edit and leaves the later ones (and their hashes) as they were.
+/// Each chunk after the first starts `overlap_tokens` before the previous boundary.
+pub fn chunk_by_content(text: &str, chunk_tokens: usize, overlap_tokens: usize) -> Vec<TextChunk> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if tokens.is_empty() || chunk_tokens == 0 {
+        return vec![];
+    }
+
+    let min_len = (chunk_tokens / 2).max(1);
+    let max_len = chunk_tokens.saturating_mul(2).max(min_len);
+    // Past `min_len`, a cut is hit with probability 1/divisor per token.
+    let divisor = (chunk_tokens - min_len).max(1) as u64;
+    let overlap = overlap_tokens.min(min_len.saturating_sub(1));
+
+    let mut ends = vec![];
+    let mut hash = 0u64;
+    let mut last_end = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        hash = (hash << 1).wrapping_add(token_hash(token));
+        let len = i + 1 - last_end;
+        if len >= max_len || (len >= min_len && (hash & BOUNDARY_HASH_MASK).is_multiple_of(divisor)) {
+            ends.push(i + 1);
+            last_end = i + 1;
+        }
+    }
+    if last_end < tokens.len() {
+        ends.push(tokens.len());
+    }
+
+    let mut chunks = Vec::with_capacity(ends.len());
+    let mut boundary = 0usize;
+    for (idx, end) in ends.into_iter().enumerate() {
+        let start = boundary.saturating_sub(overlap);
+        chunks.push(TextChunk {
+            index: idx,
+            text: tokens[start..end].join(" "),
+            start_token: start,
+            end_token: end,
+        });
+        boundary = end;
+    }
+    chunks
+}
+
+/// Chunks that start and end on unit boundaries (`starts`: the token index where each unit
+/// begins, ascending). Consecutive units are packed while they fit in `chunk_tokens`; a unit
+/// longer than that is split into fixed windows with `overlap_tokens` of overlap. Packed units
+/// do not overlap.
+fn chunk_by_units(text: &str, starts: Vec<usize>, chunk_tokens: usize, overlap_tokens: usize) -> Vec<TextChunk> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if tokens.is_empty() || chunk_tokens == 0 {
+        return vec![];
+    }
+    let overlap = overlap_tokens.min(chunk_tokens.saturating_sub(1));
+    let mut bounds: Vec<usize> = starts.into_iter().filter(|&s| s > 0 && s < tokens.len()).collect();
+    bounds.dedup();
+    bounds.push(tokens.len());
+
+    let mut chunks = vec![];
+    let mut push = |start: usize, end: usize| {
+        chunks.push(TextChunk {
+            index: chunks.len(),
+            text: tokens[start..end].join(" "),
+            start_token: start,
+            end_token: end,
+        });
+    };
+    let mut chunk_start = 0usize;
+    let mut unit_start = 0usize;
+    for end in bounds {
+        if end - chunk_start <= chunk_tokens {
+            unit_start = end;
+            continue;
+        }
+        // Adding this unit overflows: close the chunk packed so far, then place the unit.
+        if unit_start > chunk_start {
+            push(chunk_start, unit_start);
+            chunk_start = unit_start;
+        }
+        if end - chunk_start > chunk_tokens {
+            let mut start = chunk_start;
+            loop {
+                let window_end = (start + chunk_tokens).min(end);
+                if window_end == end {
+                    // The tail starts the next chunk, so units after it can join.
+                    chunk_start = start;
+                    break;
+                }
+                push(start, window_end);
+                start = window_end - overlap;
+            }
+        }
+        unit_start = end;
+    }
+    if chunk_start < tokens.len() {
+        push(chunk_start, tokens.len());
+    }
+    chunks
+}
+
+/// Chunks of blank-line separated records (flattened JSON, see `crate::json_flatten`), packed
+/// like `Headings` sections.
+pub fn chunk_records(text: &str, opts: &ChunkOptions) -> Vec<TextChunk> {
+    chunk_by_units(text, record_starts(text), opts.tokens, opts.overlap_tokens)
+}
+
+/// Token index of each Markdown ATX heading line (`#` to `######` followed by a space).
+fn heading_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut token = 0usize;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let level = trimmed.bytes().take_while(|&b| b == b'#').count();
+        let is_heading = (1..=6).contains(&level) && trimmed[level..].starts_with([' ', '\t']);
+        if is_heading {
+            starts.push(token);
+        }
+        token += line.split_whitespace().count();
+    }
+    starts
+}
+
+/// Token index of each non-empty line (CSV / TSV rows, log lines).
+fn row_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut token = 0usize;
+    for line in text.lines() {
+        let n = line.split_whitespace().count();
+        if n > 0 {
+            starts.push(token);
+        }
+        token += n;
+    }
+    starts
+}
 
+/// Token index of the first line after each blank line.
+fn record_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut token = 0usize;
+    let mut after_blank = false;
+    for line in text.lines() {
+        let n = line.split_whitespace().count();
+        if n == 0 {
+            after_blank = true;
+            continue;
+        }
+        if after_blank {
+            starts.push(token);
+            after_blank = false;
+        }
+        token += n;
+    }
+    starts
+}
 
+/// FNV-1a: a cheap, stable per-token hash.
+fn token_hash(token: &str) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in token.bytes() {
+        h ^= u64::from(b);
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
@@ -0,0 +1,136 @@
+//! `silo_get_chunk`: one stored chunk with its metadata and neighboring chunks, for drilling
+//! down into a search hit without re-reading and re-chunking the file.
+//!
+//! A chunk is addressed by its row id (`chunk_row_id`) or by `path` + `chunk_index` as returned in
+//! search hits. Chunks in locked collections are refused like file reads.
+
+use crate::database::ChunkRow;
+use crate::errors::ToolError;
+use crate::predicate::Predicate;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const DEFAULT_NEIGHBORS: usize = 1;
+const MAX_NEIGHBORS: usize = 5;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GetChunkArgs {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub chunk_index: Option<usize>,
+    /// Chunks to return on each side (default 1, at most `MAX_NEIGHBORS`).
+    #[serde(default)]
+    pub neighbors: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkView {
+    pub id: String,
+    pub path: String,
+    pub chunk_index: usize,
+    pub start_token: usize,
+    pub end_token: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub content: String,
+}
+
+impl From<ChunkRow> for ChunkView {
+    fn from(r: ChunkRow) -> Self {
+        Self {
+            id: r.id,
+            path: r.path,
+            chunk_index: r.chunk_index,
+            start_token: r.start_token,
+            end_token: r.end_token,
+            page: r.page,
+            source: r.source,
+            content: r.content,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkDetail {
+    #[serde(flatten)]
+    pub chunk: ChunkView,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_mtime_epoch_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_hash: Option<String>,
+    /// Up to `neighbors` chunks before the chunk, in order.
+    pub before: Vec<ChunkView>,
+    /// Up to `neighbors` chunks after the chunk, in order.
+    pub after: Vec<ChunkView>,
+}
+
+/// MCP tool entrypoint (`silo_get_chunk`).
+pub async fn get_chunk_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: GetChunkArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let detail = get_chunk(state, &args).await?;
+    serde_json::to_value(detail).map_err(|e| format!("failed to serialize chunk: {e}").into())
+}
+
+pub async fn get_chunk(state: &SharedState, args: &GetChunkArgs) -> Result<ChunkDetail, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let filter = match (&args.id, &args.path, args.chunk_index) {
+        (Some(id), _, _) => Predicate::eq("id", id.as_str()),
+        (None, Some(path), Some(index)) => {
+            let path = crate::state::expand_tilde(path).to_string_lossy().to_string();
+            Predicate::eq("path", path).and(Predicate::eq("chunk_index", index as i64))
+        }
+        _ => return Err("Pass `id`, or `path` and `chunk_index` (both are in search hits)".into()),
+    };
+    let row = state
+        .db
+        .find_chunks(&filter, 1)
+        .await
+        .map_err(ToolError::db_query_failed)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No such chunk (the file may have been re-indexed since the search)".to_string())?;
+    crate::access::locked_paths(state).await.check(&row.path)?;
+
+    let n = args.neighbors.unwrap_or(DEFAULT_NEIGHBORS).min(MAX_NEIGHBORS);
+    let index = row.chunk_index as i64;
+    let nearby = Predicate::eq("path", row.path.as_str())
+        .and(Predicate::ge("chunk_index", index - n as i64))
+        .and(Predicate::le("chunk_index", index + n as i64))
+        .and(Predicate::ne("chunk_index", index));
+    let mut siblings = state
+        .db
+        .find_chunks(&nearby, 2 * n)
+        .await
+        .map_err(ToolError::db_query_failed)?;
+    siblings.sort_by_key(|r| r.chunk_index);
+    let (before, after): (Vec<ChunkRow>, Vec<ChunkRow>) =
+        siblings.into_iter().partition(|s| (s.chunk_index as i64) < index);
+
+    let tags = row
+        .tags
+        .as_deref()
+        .map(|t| t.split(',').filter(|t| !t.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    Ok(ChunkDetail {
+        tags,
+        file_mtime_epoch_secs: row.file_mtime_epoch_secs,
+        file_size_bytes: row.file_size_bytes,
+        file_hash: row.file_hash.clone(),
+        chunk: ChunkView::from(row),
+        before: before.into_iter().map(ChunkView::from).collect(),
+        after: after.into_iter().map(ChunkView::from).collect(),
+    })
+}
@@ -0,0 +1,219 @@
+//! Cleanup suggestions (`silo_cleanup_suggestions`). Read-only: nothing is deleted.
+//!
+//! - large files untouched for a long time (one metadata walk over the roots)
+//! - duplicate groups, from the catalog's content hashes (indexed files only)
+//! - big cache directories (`node_modules`, `.cache`, `__pycache__`, ...) found by the walk
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::filesystem::{modified_epoch_secs, walk_files};
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const MB: u64 = 1024 * 1024;
+const SECS_PER_MONTH: i64 = 30 * 24 * 60 * 60;
+const DEFAULT_MAX_FILES: u64 = 2_000_000;
+
+/// Directory names (lowercase) whose contents can be regenerated.
+const CACHE_DIR_NAMES: &[&str] = &[
+    "node_modules",
+    ".cache",
+    "cache",
+    "caches",
+    "__pycache__",
+    ".pytest_cache",
+    ".mypy_cache",
+    ".gradle",
+    ".npm",
+    "deriveddata",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct CleanupArgs {
+    /// Minimum size for stale-file suggestions.
+    #[serde(default)]
+    pub min_size_mb: Option<u64>,
+    /// Files not modified for at least this many months count as stale.
+    #[serde(default)]
+    pub untouched_months: Option<u32>,
+    /// Minimum size for cache directory suggestions.
+    #[serde(default)]
+    pub min_cache_mb: Option<u64>,
+    /// Cache directories are usually excluded from indexing, so they are walked by default.
+    #[serde(default)]
+    pub include_excluded: Option<bool>,
+    #[serde(default)]
+    pub top_n: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleFile {
+    pub path: String,
+    pub bytes: u64,
+    pub modified_epoch_secs: i64,
+    pub months_untouched: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub bytes_each: u64,
+    /// Bytes freed by keeping one copy.
+    pub wasted_bytes: u64,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheDir {
+    pub path: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    pub stale_large_files: Vec<StaleFile>,
+    pub duplicate_groups: Vec<DuplicateGroup>,
+    pub cache_dirs: Vec<CacheDir>,
+    /// Upper bound if every suggestion were acted on (listed entries only).
+    pub reclaimable_bytes: u64,
+    pub files_scanned: u64,
+    /// The walk stopped early; stale files and caches may be incomplete.
+    pub truncated: bool,
+}
+
+/// MCP tool entrypoint (`silo_cleanup_suggestions`).
+pub async fn cleanup_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: CleanupArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = cleanup_suggestions(state, &args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize cleanup report: {e}").into())
+}
+
+pub async fn cleanup_suggestions(state: &SharedState, args: &CleanupArgs) -> Result<CleanupReport, ToolError> {
+    let Some(policy) = state.filesystem_policy().await else {
+        return Err(ToolError::no_filesystem_policy());
+    };
+    let roots = state.filesystem_roots().await;
+    let min_size = args.min_size_mb.unwrap_or(100) * MB;
+    let min_cache = args.min_cache_mb.unwrap_or(500) * MB;
+    let months = args.untouched_months.unwrap_or(12).max(1) as i64;
+    let top_n = args.top_n.unwrap_or(20).clamp(1, 500);
+    let now = now_epoch_secs();
+    let stale_before = now - months * SECS_PER_MONTH;
+
+    let mut stale: Vec<StaleFile> = vec![];
+    let mut caches: HashMap<PathBuf, (u64, u64)> = HashMap::new();
+    let walk = walk_files(
+        &roots,
+        &policy,
+        args.include_excluded.unwrap_or(true),
+        DEFAULT_MAX_FILES,
+        |root, path, meta| {
+            let bytes = meta.len();
+            if let Some(dir) = cache_ancestor(root, path) {
+                let e = caches.entry(dir).or_default();
+                e.0 += bytes;
+                e.1 += 1;
+            }
+            if bytes < min_size {
+                return;
+            }
+            if let Some(modified) = modified_epoch_secs(meta).filter(|m| *m < stale_before) {
+                stale.push(StaleFile {
+                    path: path.to_string_lossy().to_string(),
+                    bytes,
+                    modified_epoch_secs: modified,
+                    months_untouched: (now - modified) / SECS_PER_MONTH,
+                });
+            }
+        },
+    )
+    .await;
+
+    stale.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    stale.truncate(top_n);
+
+    let mut cache_dirs: Vec<CacheDir> = caches
+        .into_iter()
+        .filter(|(_, (bytes, _))| *bytes >= min_cache)
+        .map(|(p, (bytes, files))| CacheDir {
+            path: p.to_string_lossy().to_string(),
+            bytes,
+            files,
+        })
+        .collect();
+    cache_dirs.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    cache_dirs.truncate(top_n);
+
+    let mut duplicate_groups = duplicate_groups(state).await;
+    duplicate_groups.truncate(top_n);
+
+    let reclaimable_bytes = stale.iter().map(|f| f.bytes).sum::<u64>()
+        + duplicate_groups.iter().map(|g| g.wasted_bytes).sum::<u64>()
+        + cache_dirs.iter().map(|c| c.bytes).sum::<u64>();
+
+    Ok(CleanupReport {
+        stale_large_files: stale,
+        duplicate_groups,
+        cache_dirs,
+        reclaimable_bytes,
+        files_scanned: walk.files,
+        truncated: walk.truncated,
+    })
+}
+
+/// Outermost cache-named directory containing `path` (below `root`), if any.
+fn cache_ancestor(root: &Path, path: &Path) -> Option<PathBuf> {
+    let rel = path.parent()?.strip_prefix(root).ok()?;
+    let mut dir = root.to_path_buf();
+    for part in rel.components() {
+        dir.push(part);
+        let name = part.as_os_str().to_string_lossy().to_lowercase();
+        if CACHE_DIR_NAMES.contains(&name.as_str()) {
+            return Some(dir);
+        }
+    }
+    None
+}
+
+/// Indexed files with the same content hash and size, largest waste first. Catalog entries
+/// whose file no longer exists are left out.
+async fn duplicate_groups(state: &SharedState) -> Vec<DuplicateGroup> {
+    let candidates: Vec<(u64, Vec<String>)> = {
+        let catalog = state.catalog.read().await;
+        let mut groups: HashMap<(&str, i64), Vec<String>> = HashMap::new();
+        for e in catalog.entries().filter(|e| e.is_file()) {
+            if let (Some(hash), Some(size)) = (e.file_hash.as_deref(), e.size_bytes) {
+                groups.entry((hash, size)).or_default().push(e.path.clone());
+            }
+        }
+        groups
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|((_, size), paths)| (size.max(0) as u64, paths))
+            .collect()
+    };
+
+    let mut out = vec![];
+    for (bytes_each, paths) in candidates {
+        let mut existing = vec![];
+        for p in paths {
+            if tokio::fs::metadata(&p).await.is_ok() {
+                existing.push(p);
+            }
+        }
+        if existing.len() < 2 {
+            continue;
+        }
+        existing.sort();
+        out.push(DuplicateGroup {
+            bytes_each,
+            wasted_bytes: bytes_each * (existing.len() as u64 - 1),
+            paths: existing,
+        });
+    }
+    out.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then_with(|| a.paths.cmp(&b.paths)));
+    out
+}
@@ -1,5 +1,12 @@
+use crate::chunk::{ChunkCap, ChunkOptions, ChunkRules};
+use crate::entities::EntityKind;
+use crate::extract::ExtractLimits;
+use crate::filesystem::MAX_TRAVERSAL_DEPTH;
+use crate::render::ResultFormat;
+use crate::rewrite::RewriteMode;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Top-level configuration. Keep this extensible: new sources (messages/apps) will become new entries.
@@ -7,24 +14,951 @@ use std::path::{Path, PathBuf};
 pub struct SiloConfig {
     #[serde(default)]
     pub sources: Vec<SourceConfig>,
+
+    /// Prompt templates for ask/chat/agent (defaults are built in).
+    #[serde(default)]
+    pub prompts: PromptConfig,
+
+    /// Local LLM limits used when assembling prompts.
+    #[serde(default)]
+    pub llm: LlmConfig,
+
+    /// Optional entity extraction stage at ingest (for `silo_search_entities`).
+    #[serde(default)]
+    pub entities: EntityConfig,
+
+    /// Background document tagging (`silo_tag_documents`).
+    #[serde(default)]
+    pub tagging: TaggingConfig,
+
+    /// Embedding model storage and residency (`silo_models_*`).
+    #[serde(default)]
+    pub models: ModelsConfig,
+
+    /// How chunk vectors are stored in the knowledge base.
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// Named groups of paths with an access policy (see `crate::access`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<CollectionConfig>,
+
+    /// Tool calls on sensitive paths wait for approval in the desktop app (see `crate::consent`).
+    #[serde(default)]
+    pub consent: ConsentConfig,
+
+    /// Confine MCP tool calls to the filesystem roots plus roots granted per session (see `crate::sandbox`).
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+
+    /// Only offer read/search tools over MCP (also `SILO_READ_ONLY=1`). Read at startup.
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Per-session limits on expensive tools (see `crate::ratelimit`).
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+
+    /// Size cap on tool results, with continuation cursors for the rest (see `crate::responses`).
+    #[serde(default)]
+    pub response_limits: ResponseLimitsConfig,
+
+    /// How searches rerank hits: by recency and access with `prefer_recent`, and by file-name
+    /// matches (see `crate::tools`, `crate::fields`).
+    #[serde(default)]
+    pub ranking: RankingConfig,
+
+    /// Typo correction and synonym expansion of search queries (see `crate::rewrite`).
+    #[serde(default)]
+    pub query_rewrite: QueryRewriteConfig,
+
+    /// Federated search over collections and chats, with per-partition weights (see
+    /// `crate::federation`).
+    #[serde(default)]
+    pub federation: FederationConfig,
+
+    /// Preview length and full chunk text in `silo_search` hits, with server-side caps.
+    #[serde(default)]
+    pub hits: HitsConfig,
+
+    /// Desktop and MCP notifications per event type (see `crate::notify`).
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Language of tool descriptions, tool errors and notifications (see `crate::i18n`), e.g.
+    /// `de` or `pt-BR`; `system` uses the environment's locale.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+
+    /// How `silo_search`, `silo_ask` and `silo_chat` results are returned when a call does not pass
+    /// `format`: `json` (default), `markdown` or `plain` text (see `crate::render`).
+    #[serde(default)]
+    pub result_format: ResultFormat,
+
+    /// Debugging aids (traffic log, see `crate::traffic`). Read at startup.
+    #[serde(default)]
+    pub debug: DebugConfig,
+
+    /// Log format, file and levels (see `crate::logging`). Read at startup.
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// File writes by tools (`silo_write_file`, see `crate::writes`). Off by default; read at startup.
+    #[serde(default)]
+    pub writes: WritesConfig,
+
+    /// Notes folder for `silo_append_note` (see `crate::notes`). Off unless `root` is set; read at startup.
+    #[serde(default)]
+    pub notes: NotesConfig,
+
+    /// Web page ingestion (`silo_ingest_url`, see `crate::web`). Off by default; read at startup.
+    #[serde(default)]
+    pub web: WebConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritesConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Folders writes may go into (subfolders included). Empty: the filesystem roots.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<PathBuf>,
+    /// Largest content one call may write.
+    #[serde(default = "default_max_write_bytes")]
+    pub max_bytes: u64,
+}
+
+impl Default for WritesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            roots: vec![],
+            max_bytes: default_max_write_bytes(),
+        }
+    }
+}
+
+fn default_max_write_bytes() -> u64 {
+    1024 * 1024
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotesConfig {
+    /// Folder notes are appended in (created if missing). `None` disables `silo_append_note`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+    /// Note file (relative to `root`) used when a call names none. `None`: today's daily note.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_file: Option<String>,
+    /// strftime pattern for daily note file names, relative to `root`.
+    #[serde(default = "default_daily_note_format")]
+    pub daily_format: String,
+    /// Largest entry one call may append.
+    #[serde(default = "default_max_note_bytes")]
+    pub max_entry_bytes: u64,
+}
+
+impl Default for NotesConfig {
+    fn default() -> Self {
+        Self {
+            root: None,
+            default_file: None,
+            daily_format: default_daily_note_format(),
+            max_entry_bytes: default_max_note_bytes(),
+        }
+    }
+}
+
+fn default_daily_note_format() -> String {
+    "%Y-%m-%d.md".to_string()
+}
+
+fn default_max_note_bytes() -> u64 {
+    16 * 1024
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Largest download (HTML page or PDF).
+    #[serde(default = "default_max_web_bytes")]
+    pub max_bytes: u64,
+    #[serde(default = "default_web_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Also fetch `localhost` and private-network addresses (routers, intranet pages).
+    #[serde(default)]
+    pub allow_private_hosts: bool,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: default_max_web_bytes(),
+            timeout_secs: default_web_timeout_secs(),
+            allow_private_hosts: false,
+        }
+    }
+}
+
+fn default_max_web_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_web_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Level for everything without an entry in `levels` (`error`..`trace`).
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    /// Per-module levels, e.g. `{"mcp_server::indexer": "debug", "lance": "warn"}`.
+    /// `RUST_LOG` is applied on top of these.
+    #[serde(default)]
+    pub levels: BTreeMap<String, String>,
+    /// Also write the log to `logs/silo.log` in the data dir.
+    #[serde(default)]
+    pub to_file: bool,
+    /// Log file instead of `logs/silo.log` (implies `to_file`).
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Rotate the log file at this size.
+    #[serde(default = "default_max_log_bytes")]
+    pub max_file_bytes: u64,
+    /// Rotated files to keep.
+    #[serde(default = "default_keep_log_files")]
+    pub keep_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            level: default_log_level(),
+            levels: BTreeMap::new(),
+            to_file: false,
+            file: None,
+            max_file_bytes: default_max_log_bytes(),
+            keep_files: default_keep_log_files(),
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugConfig {
+    /// Log every JSON-RPC request/response to `logs/traffic.jsonl` (also `SILO_LOG_TRAFFIC=1`).
+    #[serde(default)]
+    pub log_traffic: bool,
+    /// Replace queries, paths and other content with length + hash in that log.
+    #[serde(default = "default_true")]
+    pub redact: bool,
+    /// Rotate the log at this size.
+    #[serde(default = "default_max_log_bytes")]
+    pub max_log_bytes: u64,
+    /// Rotated files to keep.
+    #[serde(default = "default_keep_log_files")]
+    pub keep_files: usize,
+}
+
+impl Default for DebugConfig {
+    fn default() -> Self {
+        Self {
+            log_traffic: false,
+            redact: true,
+            max_log_bytes: default_max_log_bytes(),
+            keep_files: default_keep_log_files(),
+        }
+    }
+}
+
+fn default_max_log_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_keep_log_files() -> usize {
+    3
+}
+
+/// Calls per minute per client session; 0 = unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// `silo_search` (and aliases), `silo_search_entities`, `silo_search_paths`, `silo_grep`,
+    /// `silo_find_symbol`, `silo_session_index`.
+    #[serde(default = "default_search_per_minute")]
+    pub search_per_minute: u32,
+    /// `silo_ask`, `silo_chat`, `silo_agent`, `silo_extract_structured`.
+    #[serde(default = "default_ask_per_minute")]
+    pub ask_per_minute: u32,
+    /// `silo_ingest_file`, `silo_index_*`, `silo_import_chat_export`, `silo_tag_documents`,
+    /// `silo_eval_run`, `silo_bench_ingest`.
+    #[serde(default = "default_ingest_per_minute")]
+    pub ingest_per_minute: u32,
+    /// `silo_verify`, `silo_disk_report`, `silo_cleanup_suggestions`, `silo_index_diff`,
+    /// `silo_maintenance`, `silo_models_pull`, `silo_graph_rebuild`.
+    #[serde(default = "default_heavy_per_minute")]
+    pub heavy_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            search_per_minute: default_search_per_minute(),
+            ask_per_minute: default_ask_per_minute(),
+            ingest_per_minute: default_ingest_per_minute(),
+            heavy_per_minute: default_heavy_per_minute(),
+        }
+    }
+}
+
+/// Largest tool result, in bytes of JSON. Larger results are cut to fit and marked
+/// `truncated: true`, with a `next_cursor` for `silo_continue`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseLimitsConfig {
+    #[serde(default = "default_max_response_bytes")]
+    pub max_bytes: usize,
+    /// Per-tool caps (tool name -> bytes), overriding `max_bytes`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, usize>,
+}
+
+impl Default for ResponseLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_max_response_bytes(),
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+impl ResponseLimitsConfig {
+    pub fn max_bytes_for(&self, tool: &str) -> usize {
+        self.tools.get(tool).copied().unwrap_or(self.max_bytes)
+    }
+}
+
+fn default_max_response_bytes() -> usize {
+    512 * 1024
+}
+
+/// Reranking of search hits: cosine similarity plus a recency bonus (halving every
+/// `recency_half_life_days`) plus an access bonus (log of the file's search-result count,
+/// full weight at 100), for searches with `prefer_recent`; plus a file-name bonus
+/// (`title_weight`, see `crate::fields`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingConfig {
+    /// Rerank every search, not only calls passing `prefer_recent: true` (a call's own
+    /// `prefer_recent` still wins).
+    #[serde(default)]
+    pub prefer_recent: bool,
+    /// Bonus for a file modified just now.
+    #[serde(default = "default_recency_weight")]
+    pub recency_weight: f32,
+    #[serde(default = "default_recency_half_life_days")]
+    pub recency_half_life_days: f32,
+    /// Bonus for files that often come up in results (0 = off). Hits count as accesses, so this
+    /// favors what was found before.
+    #[serde(default)]
+    pub access_weight: f32,
+    /// Bonus for a file whose name contains every query term (scaled by the share of terms it
+    /// contains; 0 = off). Applies to every search matching `title`, not only `prefer_recent`.
+    #[serde(default)]
+    pub title_weight: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            prefer_recent: false,
+            recency_weight: default_recency_weight(),
+            recency_half_life_days: default_recency_half_life_days(),
+            access_weight: 0.0,
+            title_weight: 0.0,
+        }
+    }
+}
+
+/// Query preprocessing for `silo_search` (and quick search). A call's `correct_typos`,
+/// `expand_synonyms` and `rewrite` override these.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRewriteConfig {
+    /// Replace words missing from the corpus vocabulary with a close, frequent word.
+    #[serde(default)]
+    pub correct_typos: bool,
+    /// Append synonyms suggested by the local LLM to the embedded query (one LLM call per new query).
+    #[serde(default)]
+    pub expand_synonyms: bool,
+    #[serde(default = "default_max_synonyms")]
+    pub max_synonyms: usize,
+    /// `hyde`: embed an LLM-drafted answer passage instead of the query (one LLM call per new query).
+    #[serde(default)]
+    pub mode: RewriteMode,
+}
+
+impl Default for QueryRewriteConfig {
+    fn default() -> Self {
+        Self {
+            correct_typos: false,
+            expand_synonyms: false,
+            max_synonyms: default_max_synonyms(),
+            mode: RewriteMode::None,
+        }
+    }
+}
+
+fn default_max_synonyms() -> usize {
+    3
+}
+
+/// Federated search: one search per collection (plus `messages` and `files`), scores normalized
+/// per partition and weighted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// Federate every search (a call's `federated` still wins).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Partition (collection name, `messages` or `files`) -> weight; 1 when missing.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub weights: BTreeMap<String, f32>,
+}
+
+/// How much chunk text `silo_search` hits carry. A call's `preview_chars` is capped at
+/// `max_preview_chars`; with `include_full_content`, hits carry their chunk text until
+/// `max_content_chars` (over all hits of the call) are used up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitsConfig {
+    #[serde(default = "default_preview_chars")]
+    pub preview_chars: usize,
+    #[serde(default = "default_max_preview_chars")]
+    pub max_preview_chars: usize,
+    #[serde(default = "default_max_content_chars")]
+    pub max_content_chars: usize,
+}
+
+impl Default for HitsConfig {
+    fn default() -> Self {
+        Self {
+            preview_chars: default_preview_chars(),
+            max_preview_chars: default_max_preview_chars(),
+            max_content_chars: default_max_content_chars(),
+        }
+    }
+}
+
+/// Which events are notified (desktop notification and MCP `notifications/message`).
+/// `source_failing` fires once a file's ingest or a background source pass has failed
+/// `failure_threshold` times in a row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    #[serde(default = "default_true")]
+    pub index_finished: bool,
+    #[serde(default = "default_true")]
+    pub source_failing: bool,
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            index_finished: true,
+            source_failing: true,
+            failure_threshold: default_failure_threshold(),
+        }
+    }
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+fn default_preview_chars() -> usize {
+    240
+}
+
+fn default_max_preview_chars() -> usize {
+    2_000
+}
+
+fn default_max_content_chars() -> usize {
+    24_000
+}
+
+fn default_recency_weight() -> f32 {
+    0.05
+}
+
+fn default_recency_half_life_days() -> f32 {
+    30.0
+}
+
+fn default_search_per_minute() -> u32 {
+    120
+}
+
+fn default_ask_per_minute() -> u32 {
+    20
+}
+
+fn default_ingest_per_minute() -> u32 {
+    60
+}
+
+fn default_heavy_per_minute() -> u32 {
+    6
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentConfig {
+    /// Globs (matched against full paths, like `exclude_globs`), e.g. `**/.ssh/**`. Empty = off.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_globs: Vec<String>,
+
+    /// Seconds after the first call until the request (and an approval of it) expires.
+    #[serde(default = "default_consent_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+impl Default for ConsentConfig {
+    fn default() -> Self {
+        Self {
+            sensitive_globs: vec![],
+            ttl_secs: default_consent_ttl_secs(),
+        }
+    }
+}
+
+fn default_consent_ttl_secs() -> u64 {
+    5 * 60
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Refuse path arguments outside the filesystem roots and the session's temporary roots.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// `silo_grant_temp_root` grants for this long unless the call asks for less.
+    #[serde(default = "default_grant_secs")]
+    pub grant_secs: u64,
+
+    /// Longest grant a call may ask for.
+    #[serde(default = "default_max_grant_secs")]
+    pub max_grant_secs: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grant_secs: default_grant_secs(),
+            max_grant_secs: default_max_grant_secs(),
+        }
+    }
+}
+
+fn default_grant_secs() -> u64 {
+    30 * 60
+}
+
+fn default_max_grant_secs() -> u64 {
+    4 * 60 * 60
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionConfig {
+    pub name: String,
+    /// Files under these folders (or these files) belong to the collection.
+    pub paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub access: CollectionAccess,
+    /// How long `silo_unlock_collection` opens a locked collection for.
+    #[serde(default = "default_unlock_secs")]
+    pub unlock_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionAccess {
+    /// Readable by every tool (and the agent).
+    #[default]
+    Open,
+    /// Hidden from search, answers and file reads until unlocked.
+    Locked,
+}
+
+fn default_unlock_secs() -> u64 {
+    15 * 60
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Where the index lives. Read when the database opens (restart to apply); backends do not
+    /// share data, so switching needs a re-index.
+    #[serde(default)]
+    pub backend: StorageBackend,
+
+    /// Element type of stored embeddings. Read when the database opens (restart to apply);
+    /// each type has its own table, so switching needs a re-index.
+    #[serde(default)]
+    pub vector_type: VectorType,
+
+    /// Cap on the estimated index size; documents are evicted after indexing when it is exceeded
+    /// (see `crate::budget`). No cap by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_index_bytes: Option<u64>,
+
+    /// Which documents go first when over the cap.
+    #[serde(default)]
+    pub evict: EvictionOrder,
+
+    /// Documents with any of these tags (file or generated) are never evicted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protect_tags: Vec<String>,
+
+    /// Run `silo_maintenance` in the background every this many hours (off by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_interval_hours: Option<u64>,
+
+    /// Table versions younger than this are kept by maintenance (default 1 hour).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_versions_hours: Option<u64>,
+
+    /// Let backup tools copy the data dir. Off by default: it is marked as a cache
+    /// (`CACHEDIR.TAG`, Time Machine exclusion) since re-indexing rebuilds it.
+    #[serde(default)]
+    pub include_in_backups: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionOrder {
+    /// Never or longest ago returned by search/ask, then oldest indexed.
+    #[default]
+    LeastAccessed,
+    /// Oldest file modification time first.
+    Oldest,
+    /// Largest index footprint first.
+    Largest,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// LanceDB tables in the data dir (`--features lancedb`).
+    #[default]
+    Lancedb,
+    /// One SQLite file with the sqlite-vec extension (`--features sqlite`), for targets where
+    /// the Arrow toolchain does not build. Vectors are always stored as f32.
+    Sqlite,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorType {
+    /// 4 bytes per dimension.
+    #[default]
+    F32,
+    /// Half precision: half the vector storage, searched natively by LanceDB.
+    F16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelsConfig {
+    /// Where model files are downloaded. Defaults to `models/` in the data dir.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Unload the embedding model after this many seconds without use (0 = keep it loaded).
+    #[serde(default = "default_idle_unload_secs")]
+    pub idle_unload_secs: u64,
+}
+
+impl Default for ModelsConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            idle_unload_secs: default_idle_unload_secs(),
+        }
+    }
+}
+
+fn default_idle_unload_secs() -> u64 {
+    600
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaggingConfig {
+    /// Start the tagging job after each bulk index. Off by default (one LLM call per document).
+    #[serde(default)]
+    pub auto: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityConfig {
+    /// Extract people/orgs/projects per chunk while ingesting. Off by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Ask the local LLM instead of using name heuristics (one call per chunk; slow).
+    #[serde(default)]
+    pub use_llm: bool,
+
+    /// Names that should always be recognized (e.g. internal project code names).
+    #[serde(default)]
+    pub known: Vec<KnownEntity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownEntity {
+    pub name: String,
+    #[serde(default)]
+    pub kind: EntityKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// Model context window in tokens. When unset, ask the backend (the model's `num_ctx` from
+    /// `ollama show`), then fall back to a conservative default. Set this to your Ollama `num_ctx`
+    /// if you raised it for the server (`OLLAMA_CONTEXT_LENGTH`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window_tokens: Option<usize>,
+
+    /// Tokens kept free for the answer when fitting retrieved chunks into the prompt.
+    #[serde(default = "default_answer_reserve_tokens")]
+    pub answer_reserve_tokens: usize,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            context_window_tokens: None,
+            answer_reserve_tokens: default_answer_reserve_tokens(),
+        }
+    }
+}
+
+fn default_answer_reserve_tokens() -> usize {
+    512
 }
 
 impl Default for SiloConfig {
     fn default() -> Self {
         Self {
             sources: vec![SourceConfig::FileSystem(FileSystemSourceConfig::default())],
+            prompts: PromptConfig::default(),
+            llm: LlmConfig::default(),
+            entities: EntityConfig::default(),
+            tagging: TaggingConfig::default(),
+            models: ModelsConfig::default(),
+            storage: StorageConfig::default(),
+            collections: vec![],
+            consent: ConsentConfig::default(),
+            sandbox: SandboxConfig::default(),
+            read_only: false,
+            rate_limits: RateLimitConfig::default(),
+            response_limits: ResponseLimitsConfig::default(),
+            ranking: RankingConfig::default(),
+            query_rewrite: QueryRewriteConfig::default(),
+            federation: FederationConfig::default(),
+            hits: HitsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            locale: default_locale(),
+            result_format: ResultFormat::default(),
+            debug: DebugConfig::default(),
+            logging: LoggingConfig::default(),
+            writes: WritesConfig::default(),
+            notes: NotesConfig::default(),
+            web: WebConfig::default(),
         }
     }
 }
 
+/// Custom prompt templates. Each template can be given inline or as a file path (not both).
+///
+/// Placeholders (`{{`/`}}` for literal braces):
+/// - ask: `{context}`, `{question}` (both required)
+/// - chat: `{context}`, `{history}`, `{question}` (`{context}` and `{question}` required)
+/// - agent: `{tools}`, `{task}` (both required)
+/// - extract: `{schema}`, `{document}`, `{instructions}` (`{schema}` and `{document}` required)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ask_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ask_template_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_template_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_template_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract_template_path: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SourceConfig {
     /// Local filesystem indexing (Phase 2 MVP: everything under `~` with safe exclusions).
     FileSystem(FileSystemSourceConfig),
 
-    // Placeholder for future sources (messages, apps, calendars, etc).
-    // Keep as an enum variant later (e.g. `Messages(MessagesSourceConfig)`).
+    /// macOS Messages history (`chat.db`), indexed by `silo_index_messages`. Opt-in.
+    Messages(MessagesSourceConfig),
+
+    /// Screenshots folder, watched and OCR'd in the background (`crate::screenshots`). Opt-in.
+    Screenshots(ScreenshotsSourceConfig),
+
+    /// Folder of saved newsletters / RSS items, watched in the background (`crate::newsletters`). Opt-in.
+    Newsletters(NewslettersSourceConfig),
+
+    /// Zotero library or BibTeX file with its attachments, indexed by `silo_index_library`
+    /// (`crate::library`). Opt-in.
+    Library(LibrarySourceConfig),
+    // Placeholder for future sources (apps, calendars, etc).
+}
+
+/// Replace the filesystem source (or add it when there is none).
+pub fn set_filesystem_source(cfg: &mut SiloConfig, fs: FileSystemSourceConfig) {
+    match cfg.sources.iter_mut().find(|s| matches!(s, SourceConfig::FileSystem(_))) {
+        Some(src) => *src = SourceConfig::FileSystem(fs),
+        None => cfg.sources.push(SourceConfig::FileSystem(fs)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagesSourceConfig {
+    /// Messages database. Default: `~/Library/Messages/chat.db` (needs Full Disk Access).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<PathBuf>,
+
+    /// A pause longer than this starts a new conversation chunk.
+    #[serde(default = "default_window_minutes")]
+    pub window_minutes: u64,
+
+    /// Messages per conversation chunk at most.
+    #[serde(default = "default_max_messages_per_chunk")]
+    pub max_messages_per_chunk: usize,
+
+    /// Show contact names instead of phone numbers/emails (reads the macOS address book).
+    #[serde(default = "default_true")]
+    pub resolve_contacts: bool,
+
+    /// Chats to leave out, by chat identifier (phone/email/group id) or display name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_chats: Vec<String>,
+}
+
+impl Default for MessagesSourceConfig {
+    fn default() -> Self {
+        Self {
+            db_path: None,
+            window_minutes: default_window_minutes(),
+            max_messages_per_chunk: default_max_messages_per_chunk(),
+            resolve_contacts: true,
+            exclude_chats: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotsSourceConfig {
+    /// Folder to watch. Default: the macOS screenshot location (or `~/Desktop`), else
+    /// `~/Pictures/Screenshots`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<PathBuf>,
+
+    /// Only images whose file name starts with one of these (case-insensitive). Empty: every
+    /// image in the folder.
+    #[serde(default = "default_screenshot_prefixes")]
+    pub name_prefixes: Vec<String>,
+
+    /// Seconds between folder checks.
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+}
+
+impl Default for ScreenshotsSourceConfig {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            name_prefixes: default_screenshot_prefixes(),
+            poll_secs: default_poll_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewslettersSourceConfig {
+    /// Folder of saved items (`.html`/`.htm` pages and `.eml` mails); one subfolder per feed.
+    pub folder: PathBuf,
+
+    /// Seconds between folder checks.
+    #[serde(default = "default_newsletter_poll_secs")]
+    pub poll_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibrarySourceConfig {
+    /// `zotero.sqlite`, or a `.bib` file. Default: `~/Zotero/zotero.sqlite`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Base folder of Zotero linked attachments stored relative to it (`attachments:` paths;
+    /// Zotero's "Linked Attachment Base Directory").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments_dir: Option<PathBuf>,
+}
+
+fn default_screenshot_prefixes() -> Vec<String> {
+    ["Screenshot", "Screen Shot"].into_iter().map(String::from).collect()
+}
+
+fn default_poll_secs() -> u64 {
+    60
+}
+
+fn default_newsletter_poll_secs() -> u64 {
+    15 * 60
+}
+
+fn default_log_window_minutes() -> u64 {
+    crate::logs::DEFAULT_WINDOW_MINUTES
+}
+
+fn default_window_minutes() -> u64 {
+    60
+}
+
+fn default_max_messages_per_chunk() -> usize {
+    40
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +984,14 @@ pub struct FileSystemSourceConfig {
     #[serde(default = "default_max_text_bytes")]
     pub max_text_bytes: u64,
 
+    /// Kill a PDF/OCR extractor (`pdftotext`, `tesseract`) that runs longer than this.
+    #[serde(default = "default_extract_timeout_secs")]
+    pub extract_timeout_secs: u64,
+
+    /// Address-space cap for those extractors in MB (0 = none). Enforced on Linux.
+    #[serde(default = "default_extract_max_memory_mb")]
+    pub extract_max_memory_mb: u64,
+
     /// Whether to follow symlinks (generally false for safety).
     #[serde(default)]
     pub follow_symlinks: bool,
@@ -61,6 +1003,119 @@ pub struct FileSystemSourceConfig {
     /// Chunk overlap in (approx) tokens for ingestion (Phase 2.3).
     #[serde(default = "default_chunk_overlap_tokens")]
     pub chunk_overlap_tokens: usize,
+
+    /// Where chunks end: every `chunk_tokens` tokens, or at content-defined points so edits
+    /// early in a file leave later chunks (and their vectors) unchanged. Re-index after changing.
+    #[serde(default)]
+    pub chunk_boundaries: ChunkBoundaries,
+
+    /// Settings per file extension (without dot), e.g. `"pdf": { "chunk_tokens": 800 }`,
+    /// `"md": { "chunk_boundaries": "headings" }`, `"log": { "skip": true }`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extension_overrides: BTreeMap<String, ExtensionOverride>,
+
+    /// Chunks stored per file at most (unlimited when unset); beyond it `chunk_cap_selection`
+    /// decides which are kept. Keeps one huge log or dump from flooding the index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chunks_per_file: Option<usize>,
+
+    /// Which chunks a capped file keeps.
+    #[serde(default)]
+    pub chunk_cap_selection: ChunkCapSelection,
+
+    /// Log files (`.log`, rotated `app.log.1`) are chunked along their timestamps: a chunk never
+    /// spans two windows of this length (see `crate::logs`). Re-index after changing.
+    #[serde(default = "default_log_window_minutes")]
+    pub log_window_minutes: u64,
+
+    /// Which files a bulk index run ingests first.
+    #[serde(default)]
+    pub index_order: IndexOrder,
+
+    /// Skip directories that contain a `CACHEDIR.TAG` or `.nobackup` marker.
+    #[serde(default = "default_true")]
+    pub honor_skip_markers: bool,
+
+    /// Skip iCloud/OneDrive/Dropbox "online-only" placeholders, so indexing never triggers
+    /// cloud downloads.
+    #[serde(default = "default_true")]
+    pub skip_cloud_placeholders: bool,
+
+    /// Skip lockfiles, minified bundles and generated code (by name, a generated-code header, or
+    /// long lines with little whitespace), even with an allow-listed extension.
+    #[serde(default = "default_true")]
+    pub skip_generated: bool,
+
+    /// Drop chunks that are mostly text repeated across many files (license headers, footers,
+    /// navigation), by shingle frequency over the corpus (see `crate::boilerplate`).
+    #[serde(default = "default_true")]
+    pub drop_boilerplate: bool,
+
+    /// Directories where placeholders are read anyway (downloading them).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hydrate_roots: Vec<PathBuf>,
+
+    /// Directory levels below each root to descend into (unlimited when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+
+    /// Files considered per directory (by name); the rest are skipped. Subdirectories are
+    /// always walked. Guards against mail spools and dependency trees that escaped the globs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files_per_directory: Option<usize>,
+
+    /// List candidates with Spotlight (`mdfind`) / `plocate` instead of walking each root, falling
+    /// back to the walker when unavailable. Much faster initial scans; see `crate::sysindex`.
+    #[serde(default)]
+    pub use_system_index: bool,
+
+    /// How to treat roots/subdirectories on NFS/SMB/other network mounts.
+    #[serde(default)]
+    pub network_mounts: NetworkMountConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkMountMode {
+    /// Index with `concurrency` and `max_file_size_bytes` below.
+    #[default]
+    Throttle,
+    /// Don't descend into network mounts (reported as skipped).
+    Skip,
+    /// Treat like local disks.
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkMountConfig {
+    #[serde(default)]
+    pub mode: NetworkMountMode,
+
+    /// Concurrent ingests for files on network mounts (shared across all mounts).
+    #[serde(default = "default_network_concurrency")]
+    pub concurrency: usize,
+
+    /// Max file size on network mounts (bytes).
+    #[serde(default = "default_network_max_file_size_bytes")]
+    pub max_file_size_bytes: u64,
+}
+
+impl Default for NetworkMountConfig {
+    fn default() -> Self {
+        Self {
+            mode: NetworkMountMode::default(),
+            concurrency: default_network_concurrency(),
+            max_file_size_bytes: default_network_max_file_size_bytes(),
+        }
+    }
+}
+
+fn default_network_concurrency() -> usize {
+    1
+}
+
+fn default_network_max_file_size_bytes() -> u64 {
+    2 * 1024 * 1024 // 2MB
 }
 
 impl Default for FileSystemSourceConfig {
@@ -72,13 +1127,131 @@ impl Default for FileSystemSourceConfig {
             allow_extensions: default_allow_extensions(),
             max_file_size_bytes: default_max_file_size_bytes(),
             max_text_bytes: default_max_text_bytes(),
+            extract_timeout_secs: default_extract_timeout_secs(),
+            extract_max_memory_mb: default_extract_max_memory_mb(),
             follow_symlinks: false,
             chunk_tokens: default_chunk_tokens(),
             chunk_overlap_tokens: default_chunk_overlap_tokens(),
+            chunk_boundaries: ChunkBoundaries::default(),
+            extension_overrides: BTreeMap::new(),
+            max_chunks_per_file: None,
+            chunk_cap_selection: ChunkCapSelection::default(),
+            log_window_minutes: default_log_window_minutes(),
+            index_order: IndexOrder::default(),
+            honor_skip_markers: true,
+            skip_cloud_placeholders: true,
+            skip_generated: true,
+            drop_boilerplate: true,
+            hydrate_roots: vec![],
+            max_depth: None,
+            max_files_per_directory: None,
+            use_system_index: false,
+            network_mounts: NetworkMountConfig::default(),
         }
     }
 }
 
+impl FileSystemSourceConfig {
+    pub fn chunking(&self) -> ChunkRules {
+        let default = ChunkOptions {
+            tokens: self.chunk_tokens,
+            overlap_tokens: self.chunk_overlap_tokens,
+            boundaries: self.chunk_boundaries,
+        };
+        let by_extension = self
+            .extension_overrides
+            .iter()
+            .filter(|(_, o)| o.changes_chunking())
+            .map(|(ext, o)| {
+                let opts = ChunkOptions {
+                    tokens: o.chunk_tokens.unwrap_or(default.tokens),
+                    overlap_tokens: o.chunk_overlap_tokens.unwrap_or(default.overlap_tokens),
+                    boundaries: o.chunk_boundaries.unwrap_or(default.boundaries),
+                };
+                (normalize_extension(ext), opts)
+            })
+            .collect();
+        let cap = self.max_chunks_per_file.map(|max_chunks| ChunkCap {
+            max_chunks,
+            selection: self.chunk_cap_selection,
+        });
+        ChunkRules {
+            default,
+            by_extension,
+            cap,
+            log_window_minutes: self.log_window_minutes,
+        }
+    }
+}
+
+/// Settings for one file extension (`filesystem.extension_overrides`). Unset fields keep the
+/// source-wide value. An override does not add its extension to `allow_extensions`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionOverride {
+    /// Never index files with this extension.
+    #[serde(default)]
+    pub skip: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_tokens: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_overlap_tokens: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_boundaries: Option<ChunkBoundaries>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_text_bytes: Option<u64>,
+}
+
+impl ExtensionOverride {
+    fn changes_chunking(&self) -> bool {
+        self.chunk_tokens.is_some() || self.chunk_overlap_tokens.is_some() || self.chunk_boundaries.is_some()
+    }
+}
+
+/// `".PDF"` -> `"pdf"`.
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkBoundaries {
+    /// Fixed windows of `chunk_tokens` tokens.
+    #[default]
+    Fixed,
+    /// Cut where a rolling hash of the last few tokens hits a target, between half and twice
+    /// `chunk_tokens` (see `crate::chunk`).
+    Content,
+    /// Markdown: cut at `#` headings; short sections are packed together up to `chunk_tokens`,
+    /// longer ones split into windows.
+    Headings,
+    /// CSV / TSV / logs: cut between lines, packing whole rows up to `chunk_tokens`.
+    Rows,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkCapSelection {
+    /// The first and last chunks, half each (headers and the latest log lines).
+    #[default]
+    HeadTail,
+    /// Chunks evenly spaced through the file, first and last included.
+    Sampled,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexOrder {
+    /// Most recently modified first (by day), smaller files first within a day.
+    #[default]
+    Recent,
+    /// Smallest files first.
+    Small,
+    /// In directory walk order (no ranking; `max_files` stops the walk early).
+    Walk,
+}
+
 fn default_max_file_size_bytes() -> u64 {
     10 * 1024 * 1024 // 10MB
 }
@@ -87,6 +1260,14 @@ fn default_max_text_bytes() -> u64 {
     2 * 1024 * 1024 // 2MB extracted text cap
 }
 
+fn default_extract_timeout_secs() -> u64 {
+    crate::extract::DEFAULT_TIMEOUT_SECS
+}
+
+fn default_extract_max_memory_mb() -> u64 {
+    crate::extract::DEFAULT_MAX_MEMORY_MB
+}
+
 fn default_chunk_tokens() -> usize {
     500
 }
@@ -95,6 +1276,10 @@ fn default_chunk_overlap_tokens() -> usize {
     50
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_exclude_globs() -> Vec<String> {
     vec![
         // VCS + build + deps
@@ -118,6 +1303,12 @@ fn default_exclude_globs() -> Vec<String> {
         // App bundles & photo libraries (avoid huge/noisy + macOS privacy prompts)
         "**/*.app/**".into(),
         "**/*.photoslibrary/**".into(),
+        // OS trash (deleted files should not be searchable)
+        "**/.Trash/**".into(),
+        "**/.Trashes/**".into(),
+        "**/.Trash-*/**".into(),
+        "**/.local/share/Trash/**".into(),
+        "**/$RECYCLE.BIN/**".into(),
     ]
 }
 
@@ -139,10 +1330,27 @@ fn default_allow_extensions() -> Vec<String> {
 #[derive(Clone)]
 pub struct CompiledFileSystemPolicy {
     pub exclude: GlobSet,
+    /// `exclude_globs` in `exclude` order, for naming the glob that matched.
+    pub exclude_globs: Vec<String>,
     pub allow_extensions: Vec<String>,
     pub max_file_size_bytes: u64,
     pub max_text_bytes: u64,
+    pub extract_limits: ExtractLimits,
     pub follow_symlinks: bool,
+    pub honor_skip_markers: bool,
+    pub skip_cloud_placeholders: bool,
+    pub skip_generated: bool,
+    pub drop_boilerplate: bool,
+    pub hydrate_roots: Vec<PathBuf>,
+    /// Effective depth limit (config `max_depth`, capped by the walk's hard limit).
+    pub max_depth: usize,
+    pub max_files_per_directory: Option<usize>,
+    pub use_system_index: bool,
+    pub network_mounts: NetworkMountConfig,
+    pub chunking: ChunkRules,
+    pub index_order: IndexOrder,
+    /// `extension_overrides`, keyed by normalized extension.
+    pub extension_overrides: HashMap<String, ExtensionOverride>,
 }
 
 impl CompiledFileSystemPolicy {
@@ -150,12 +1358,44 @@ impl CompiledFileSystemPolicy {
         self.exclude.is_match(path)
     }
 
+    /// The exclude globs matching `path`.
+    pub fn matching_excludes(&self, path: &Path) -> Vec<&str> {
+        self.exclude
+            .matches(path)
+            .into_iter()
+            .filter_map(|i| self.exclude_globs.get(i).map(String::as_str))
+            .collect()
+    }
+
+    /// Rotated logs (`app.log.1`) count as `log` files.
     pub fn extension_allowed(&self, path: &Path) -> bool {
-        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        let Some(ext) = crate::logs::effective_extension(path) else {
             return false;
         };
-        let ext = ext.to_ascii_lowercase();
-        self.allow_extensions.iter().any(|e| e == &ext)
+        self.allow_extensions.iter().any(|e| e == &ext) && !self.extension_overrides.get(&ext).is_some_and(|o| o.skip)
+    }
+
+    fn extension_override(&self, path: &Path) -> Option<&ExtensionOverride> {
+        self.extension_overrides.get(&crate::logs::effective_extension(path)?)
+    }
+
+    /// `max_file_size_bytes`, or the override for the file's extension.
+    pub fn max_file_size_for(&self, path: &Path) -> u64 {
+        self.extension_override(path)
+            .and_then(|o| o.max_file_size_bytes)
+            .unwrap_or(self.max_file_size_bytes)
+    }
+
+    /// `max_text_bytes`, or the override for the file's extension.
+    pub fn max_text_bytes_for(&self, path: &Path) -> u64 {
+        self.extension_override(path)
+            .and_then(|o| o.max_text_bytes)
+            .unwrap_or(self.max_text_bytes)
+    }
+
+    /// Placeholder under `path` should be skipped (not inside a `hydrate_roots` entry).
+    pub fn skip_placeholder(&self, path: &Path) -> bool {
+        self.skip_cloud_placeholders && !self.hydrate_roots.iter().any(|r| path.starts_with(r))
     }
 }
 
@@ -166,6 +1406,14 @@ pub fn compile_filesystem_policy(cfg: &FileSystemSourceConfig) -> Result<Compile
         builder.add(glob);
     }
     let exclude = builder.build().map_err(|e| format!("Failed to build globset: {e}"))?;
+    if cfg.max_chunks_per_file == Some(0) {
+        return Err("max_chunks_per_file must be at least 1".to_string());
+    }
+    for (ext, o) in &cfg.extension_overrides {
+        if o.chunk_tokens == Some(0) {
+            return Err(format!("extension_overrides.{ext}: chunk_tokens must be at least 1"));
+        }
+    }
 
     let allow_extensions = if cfg.allow_extensions.is_empty() {
         default_allow_extensions()
@@ -179,10 +1427,37 @@ pub fn compile_filesystem_policy(cfg: &FileSystemSourceConfig) -> Result<Compile
 
     Ok(CompiledFileSystemPolicy {
         exclude,
+        exclude_globs: cfg.exclude_globs.clone(),
         allow_extensions,
         max_file_size_bytes: cfg.max_file_size_bytes,
         max_text_bytes: cfg.max_text_bytes,
+        extract_limits: ExtractLimits {
+            timeout_secs: cfg.extract_timeout_secs,
+            max_memory_mb: cfg.extract_max_memory_mb,
+        },
         follow_symlinks: cfg.follow_symlinks,
+        honor_skip_markers: cfg.honor_skip_markers,
+        skip_cloud_placeholders: cfg.skip_cloud_placeholders,
+        skip_generated: cfg.skip_generated,
+        drop_boilerplate: cfg.drop_boilerplate,
+        hydrate_roots: cfg
+            .hydrate_roots
+            .iter()
+            .map(|r| crate::state::expand_tilde(&r.to_string_lossy()))
+            .collect(),
+        max_depth: cfg
+            .max_depth
+            .map_or(MAX_TRAVERSAL_DEPTH, |d| d.min(MAX_TRAVERSAL_DEPTH)),
+        max_files_per_directory: cfg.max_files_per_directory,
+        use_system_index: cfg.use_system_index,
+        network_mounts: cfg.network_mounts.clone(),
+        chunking: cfg.chunking(),
+        extension_overrides: cfg
+            .extension_overrides
+            .iter()
+            .map(|(ext, o)| (normalize_extension(ext), o.clone()))
+            .collect(),
+        index_order: cfg.index_order,
     })
 }
 
@@ -197,6 +1472,16 @@ pub fn default_config_path() -> PathBuf {
     home.join(".config").join("silo").join("config.json")
 }
 
+/// Location for local data (LanceDB tables, file catalog, caches):
+/// - `SILO_DATA_DIR` overrides
+/// - default: `./data` (relative to the working directory)
+pub fn default_data_dir() -> PathBuf {
+    if let Some(p) = std::env::var_os("SILO_DATA_DIR") {
+        return PathBuf::from(p);
+    }
+    PathBuf::from("./data")
+}
+
 pub async fn load_or_init_config(path: &Path) -> Result<SiloConfig, String> {
     match tokio::fs::read_to_string(path).await {
         Ok(s) => serde_json::from_str::<SiloConfig>(&s).map_err(|e| format!("Invalid config JSON: {e}")),
@@ -209,16 +1494,52 @@ pub async fn load_or_init_config(path: &Path) -> Result<SiloConfig, String> {
     }
 }
 
-pub async fn save_config(path: &Path, cfg: &SiloConfig) -> Result<(), String> {
+/// Revision (etag) of config file contents. Any change to the file, including a manual edit,
+/// gives a new revision; a missing file has the empty revision.
+pub fn config_revision(contents: &str) -> String {
+    if contents.is_empty() {
+        return String::new();
+    }
+    blake3::hash(contents.as_bytes()).to_hex()[..16].to_string()
+}
+
+/// The config file parsed, with its revision; `None` when there is no file yet.
+pub async fn read_config(path: &Path) -> Result<Option<(SiloConfig, String)>, String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(s) => {
+            let cfg = serde_json::from_str::<SiloConfig>(&s)
+                .map_err(|e| format!("Invalid config JSON in {}: {e}", path.display()))?;
+            Ok(Some((cfg, config_revision(&s))))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read config {}: {e}", path.display())),
+    }
+}
+
+/// Write the config and return its new revision. The file is replaced atomically (written to a
+/// temp file next to it, then renamed), so readers never see a torn config.
+pub async fn save_config(path: &Path, cfg: &SiloConfig) -> Result<String, String> {
+    use tokio::io::AsyncWriteExt;
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent)
             .await
             .map_err(|e| format!("Failed to create config dir {}: {e}", parent.display()))?;
     }
     let s = serde_json::to_string_pretty(cfg).map_err(|e| format!("Failed to serialize config: {e}"))?;
-    tokio::fs::write(path, s)
-        .await
-        .map_err(|e| format!("Failed to write config {}: {e}", path.display()))
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+    let written = async {
+        let mut f = tokio::fs::File::create(&tmp).await?;
+        f.write_all(s.as_bytes()).await?;
+        f.sync_all().await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+    .await;
+    if let Err(e) = written {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(format!("Failed to write config {}: {e}", path.display()));
+    }
+    Ok(config_revision(&s))
 }
 
 
@@ -0,0 +1,220 @@
+//! `silo_update_config`: change settings with a JSON merge patch (RFC 7386) instead of editing
+//! `config.json` by hand.
+//!
+//! The patch applies to a view of the settings that are safe for a client to change and take
+//! effect without a restart: `filesystem` (the filesystem source: excludes, size limits,
+//! chunking, ...) and the `PATCHABLE_SECTIONS`. The roots are not patchable: they are what sandbox
+//! mode confines clients to, so they change only through `silo_set_index_roots`, whose `roots`
+//! argument goes through the sandbox, consent and collection lock checks like any other path. Access and privacy settings (collections,
+//! consent, read-only mode, writes, notes) and settings read at startup stay file-only. Objects are
+//! merged, `null` removes a key (back to its default), anything else replaces the value. The
+//! result must deserialize into the config, name only known settings and compile into a
+//! filesystem policy; `dry_run` reports the outcome without saving.
+
+use crate::config::{compile_filesystem_policy, read_config, FileSystemSourceConfig, SiloConfig, SourceConfig};
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// Top-level config sections a patch may change, besides `filesystem`.
+const PATCHABLE_SECTIONS: &[&str] = &["ranking", "query_rewrite", "federation", "hits", "tagging", "llm", "notifications", "locale", "result_format", "response_limits"];
+
+#[derive(Debug, Deserialize)]
+struct UpdateConfigArgs {
+    patch: Value,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    expected_revision: Option<String>,
+}
+
+/// MCP tool entrypoint (`silo_update_config`).
+pub async fn update_config_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: UpdateConfigArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let (current, revision) = match read_config(&state.config_path).await? {
+        Some(found) => found,
+        None => (state.config.read().await.clone(), String::new()),
+    };
+    let (next, changed) = apply_patch(&current, &args.patch)?;
+
+    if args.dry_run {
+        return Ok(json!({
+            "dry_run": true,
+            "revision": revision,
+            "changed": changed,
+            "settings": patchable_view(&next)?,
+        }));
+    }
+    if changed.is_empty() {
+        return Ok(json!({ "dry_run": false, "revision": revision, "changed": changed }));
+    }
+
+    // Re-applied to the file as it is when saving, in case it changed since the read above.
+    let patch = args.patch;
+    let mut saved_changes = vec![];
+    let revision = state
+        .update_config(args.expected_revision.as_deref(), |cfg| {
+            let (next, changed) = apply_patch(cfg, &patch)?;
+            *cfg = next;
+            saved_changes = changed;
+            Ok(())
+        })
+        .await?;
+    let settings = patchable_view(&*state.config.read().await)?;
+    Ok(json!({
+        "dry_run": false,
+        "revision": revision,
+        "changed": saved_changes,
+        "settings": settings,
+    }))
+}
+
+/// `cfg` with `patch` applied, and the dotted paths of the settings that changed.
+pub fn apply_patch(cfg: &SiloConfig, patch: &Value) -> Result<(SiloConfig, Vec<String>), ToolError> {
+    let Some(sections) = patch.as_object() else {
+        return Err("`patch` must be a JSON object".into());
+    };
+    if let Some(key) = sections
+        .keys()
+        .find(|k| *k != "filesystem" && !PATCHABLE_SECTIONS.contains(&k.as_str()))
+    {
+        return Err(format!(
+            "`{key}` cannot be changed with silo_update_config (allowed: filesystem, {}); edit the config file instead",
+            PATCHABLE_SECTIONS.join(", ")
+        )
+        .into());
+    }
+
+    let before = patchable_view(cfg)?;
+    let mut merged = before.clone();
+    merge_patch(&mut merged, patch);
+    let mut next = cfg.clone();
+    for (name, value) in merged.as_object().into_iter().flatten() {
+        if !sections.contains_key(name) {
+            continue;
+        }
+        match name.as_str() {
+            "filesystem" => crate::config::set_filesystem_source(&mut next, section(name, value)?),
+            "ranking" => next.ranking = section(name, value)?,
+            "query_rewrite" => next.query_rewrite = section(name, value)?,
+            "federation" => next.federation = section(name, value)?,
+            "hits" => next.hits = section(name, value)?,
+            "tagging" => next.tagging = section(name, value)?,
+            "llm" => next.llm = section(name, value)?,
+            "notifications" => next.notifications = section(name, value)?,
+            "locale" => next.locale = section(name, value)?,
+            "result_format" => next.result_format = section(name, value)?,
+            "response_limits" => next.response_limits = section(name, value)?,
+            _ => {}
+        }
+    }
+    if let Some(fs) = filesystem_source(&next) {
+        compile_filesystem_policy(&fs)?;
+    }
+
+    let after = patchable_view(&next)?;
+    if before["filesystem"]["roots"] != after["filesystem"]["roots"] {
+        return Err(
+            "`filesystem.roots` cannot be changed with silo_update_config; use silo_set_index_roots".into(),
+        );
+    }
+    let mut unknown = vec![];
+    unknown_keys(patch, &after, "", &mut unknown);
+    if !unknown.is_empty() {
+        return Err(ToolError::unknown_settings(unknown.join(", ")));
+    }
+    let mut changed = vec![];
+    changed_paths(&before, &after, "", &mut changed);
+    Ok((next, changed))
+}
+
+/// The patchable settings of `cfg`, with defaults filled in.
+fn patchable_view(cfg: &SiloConfig) -> Result<Value, String> {
+    let to_value = |v: Result<Value, serde_json::Error>| v.map_err(|e| format!("Failed to serialize config: {e}"));
+    let full = to_value(serde_json::to_value(cfg))?;
+    let mut view = Map::new();
+    view.insert(
+        "filesystem".to_string(),
+        to_value(serde_json::to_value(filesystem_source(cfg).unwrap_or_default()))?,
+    );
+    for name in PATCHABLE_SECTIONS {
+        view.insert(name.to_string(), full.get(*name).cloned().unwrap_or_else(|| json!({})));
+    }
+    Ok(Value::Object(view))
+}
+
+fn filesystem_source(cfg: &SiloConfig) -> Option<FileSystemSourceConfig> {
+    cfg.sources.iter().find_map(|s| match s {
+        SourceConfig::FileSystem(fs) => Some(fs.clone()),
+        _ => None,
+    })
+}
+
+fn section<T: DeserializeOwned>(name: &str, value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("Invalid `{name}`: {e}"))
+}
+
+/// RFC 7386 JSON merge patch.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Keys set by `patch` that the resulting settings do not have (misspelled or unknown settings
+/// are dropped when deserializing). Removals and empty values are not reported, since empty
+/// settings may be left out of the serialized config.
+fn unknown_keys(patch: &Value, result: &Value, prefix: &str, out: &mut Vec<String>) {
+    let (Value::Object(patch), Value::Object(result)) = (patch, result) else {
+        return;
+    };
+    for (key, value) in patch {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+        match result.get(key) {
+            Some(r) => unknown_keys(value, r, &path, out),
+            None if !is_empty(value) => out.push(path),
+            None => {}
+        }
+    }
+}
+
+fn is_empty(v: &Value) -> bool {
+    match v {
+        Value::Null => true,
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+/// Dotted paths where `before` and `after` differ (objects compared key by key).
+fn changed_paths(before: &Value, after: &Value, prefix: &str, out: &mut Vec<String>) {
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            let mut keys: Vec<&String> = b.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                changed_paths(b.get(key).unwrap_or(&Value::Null), a.get(key).unwrap_or(&Value::Null), &path, out);
+            }
+        }
+        (b, a) if b != a => out.push(prefix.to_string()),
+        _ => {}
+    }
+}
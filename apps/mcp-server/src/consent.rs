@@ -0,0 +1,226 @@
+//! Consent prompts for tool calls on sensitive paths (`consent.sensitive_globs`).
+//!
+//! A tool call whose `path`/`directory`/`paths`/`roots` argument matches a sensitive glob is not
+//! run. Instead the result is a `pending_consent` object with a `consent_id`, and the desktop app
+//! shows an approve/deny dialog. Once approved, `silo_consent_status` hands out a single-use
+//! `consent_token` bound to that exact tool call; repeating the call with `consent_token` runs it.
+//!
+//! Approval only happens in the desktop UI (`respond`), never through an MCP tool, so a model
+//! cannot approve its own requests. A request, and the token approving it, expire `consent.ttl_secs`
+//! after the call was first made.
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use crate::tools::ToolCallParams;
+use globset::{Glob, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Argument keys holding the paths a tool acts on.
+const PATH_ARGS: &[&str] = &["path", "directory", "paths", "roots"];
+pub const TOKEN_ARG: &str = "consent_token";
+
+#[derive(Debug, Deserialize)]
+pub struct ConsentStatusArgs {
+    pub consent_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentState {
+    Pending,
+    Approved,
+    Denied,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentRequest {
+    pub consent_id: String,
+    pub tool: String,
+    pub paths: Vec<String>,
+    pub requested_epoch_secs: i64,
+    pub state: ConsentState,
+    /// Set once approved; spent by the retried call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consent_token: Option<String>,
+    #[serde(skip)]
+    key: String,
+    #[serde(skip)]
+    expires: Instant,
+}
+
+/// Pending and answered requests, in memory.
+#[derive(Debug, Default)]
+pub struct ConsentStore {
+    requests: HashMap<String, ConsentRequest>,
+}
+
+impl ConsentStore {
+    fn expire(&mut self) {
+        let now = Instant::now();
+        self.requests.retain(|_, r| r.expires > now);
+    }
+}
+
+/// MCP tool entrypoint (`silo_consent_status`).
+pub async fn consent_status_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ConsentStatusArgs =
+        serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let mut store = state.consents.lock().unwrap_or_else(|e| e.into_inner());
+    store.expire();
+    Ok(match store.requests.get(&args.consent_id) {
+        Some(r) => serde_json::to_value(r).map_err(|e| format!("failed to serialize consent: {e}"))?,
+        None => json!({ "consent_id": args.consent_id, "state": ConsentState::Expired }),
+    })
+}
+
+/// Check `call` against the sensitive globs. `Ok(None)`: run it (the consent token, if any, is
+/// spent and removed from the arguments). `Ok(Some(..))`: return this pending-consent object.
+pub async fn gate(state: &SharedState, call: &mut ToolCallParams) -> Result<Option<Value>, String> {
+    let token = call
+        .arguments
+        .as_object_mut()
+        .and_then(|o| o.remove(TOKEN_ARG))
+        .and_then(|v| v.as_str().map(str::to_string));
+    let (globs, ttl_secs) = {
+        let cfg = state.config.read().await;
+        (cfg.consent.sensitive_globs.clone(), cfg.consent.ttl_secs)
+    };
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pat in &globs {
+        builder.add(Glob::new(pat).map_err(|e| format!("Invalid consent glob `{pat}`: {e}"))?);
+    }
+    let sensitive = builder.build().map_err(|e| format!("Failed to build globset: {e}"))?;
+
+    let paths: Vec<String> = target_paths(&call.arguments)
+        .into_iter()
+        .map(|p| expand_tilde(&p).to_string_lossy().to_string())
+        .filter(|p| sensitive.is_match(p))
+        .collect();
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let key = call_key(call);
+    let mut store = state.consents.lock().unwrap_or_else(|e| e.into_inner());
+    store.expire();
+    if let Some(token) = token {
+        let granted = store
+            .requests
+            .iter()
+            .find(|(_, r)| r.state == ConsentState::Approved && r.consent_token.as_deref() == Some(token.as_str()))
+            .map(|(id, r)| (id.clone(), r.key == key));
+        return match granted {
+            Some((id, true)) => {
+                store.requests.remove(&id);
+                Ok(None)
+            }
+            Some(_) => Err("consent_token was approved for a different tool call".to_string()),
+            None => Err("Invalid or expired consent_token".to_string()),
+        };
+    }
+
+    let request = match store.requests.values().find(|r| r.key == key && r.state == ConsentState::Pending) {
+        Some(r) => r.clone(),
+        None => {
+            let request = ConsentRequest {
+                consent_id: random_id(&key),
+                tool: call.name.clone(),
+                paths,
+                requested_epoch_secs: now_epoch_secs(),
+                state: ConsentState::Pending,
+                consent_token: None,
+                key,
+                expires: Instant::now() + Duration::from_secs(ttl_secs),
+            };
+            store.requests.insert(request.consent_id.clone(), request.clone());
+            tracing::info!("Consent requested for {} on {:?}", request.tool, request.paths);
+            request
+        }
+    };
+    Ok(Some(json!({
+        "status": "pending_consent",
+        "consent_id": request.consent_id,
+        "tool": request.tool,
+        "paths": request.paths,
+        "message": "This touches a sensitive path. Ask the user to approve it in the Silo desktop app, then get a consent_token from silo_consent_status and repeat the same call with `consent_token`.",
+    })))
+}
+
+/// Requests waiting for the user (for the desktop dialog).
+pub fn pending(state: &SharedState) -> Vec<ConsentRequest> {
+    let mut store = state.consents.lock().unwrap_or_else(|e| e.into_inner());
+    store.expire();
+    let mut out: Vec<ConsentRequest> = store
+        .requests
+        .values()
+        .filter(|r| r.state == ConsentState::Pending)
+        .cloned()
+        .collect();
+    out.sort_by_key(|r| r.requested_epoch_secs);
+    out
+}
+
+/// Approve or deny a pending request (desktop UI only).
+pub fn respond(state: &SharedState, consent_id: &str, approve: bool) -> Result<ConsentRequest, String> {
+    let mut store = state.consents.lock().unwrap_or_else(|e| e.into_inner());
+    store.expire();
+    let Some(request) = store.requests.get_mut(consent_id) else {
+        return Err(format!("Consent request {consent_id} not found (expired?)"));
+    };
+    if request.state != ConsentState::Pending {
+        return Err(format!("Consent request {consent_id} was already answered"));
+    }
+    if approve {
+        request.state = ConsentState::Approved;
+        request.consent_token = Some(random_id(&request.key));
+    } else {
+        request.state = ConsentState::Denied;
+    }
+    Ok(request.clone())
+}
+
+/// Values of the path arguments (`PATH_ARGS`) of a call.
+pub(crate) fn target_paths(args: &Value) -> Vec<String> {
+    let mut out = vec![];
+    for key in PATH_ARGS {
+        match args.get(*key) {
+            Some(Value::String(s)) => out.push(s.clone()),
+            Some(Value::Array(items)) => out.extend(items.iter().filter_map(Value::as_str).map(str::to_string)),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Identifies one exact tool call (object keys serialize sorted, so this is stable).
+fn call_key(call: &ToolCallParams) -> String {
+    blake3::hash(format!("{}\n{}", call.name, call.arguments).as_bytes())
+        .to_hex()
+        .to_string()
+}
+
+/// Hard-to-guess id: the call and the time, hashed with std's randomly keyed hasher.
+pub(crate) fn random_id(key: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let mut seed = key.to_string();
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write(key.as_bytes());
+        hasher.write_u128(nanos);
+        seed.push_str(&format!("\n{:016x}", hasher.finish()));
+    }
+    blake3::hash(seed.as_bytes()).to_hex()[..24].to_string()
+}
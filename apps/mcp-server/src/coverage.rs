@@ -0,0 +1,127 @@
+//! Index coverage per filesystem root (`silo_index_coverage`).
+//!
+//! Scans each root with the indexing policy (metadata only, like `silo_index_diff`) and sorts its
+//! eligible files into indexed (in the catalog and unchanged), failed (last ingest failed, see
+//! `FileCatalog::record_failure`), evicted (dropped by the size budget) and pending (new or
+//! changed since indexing). Percentages are of the eligible files, for progress bars.
+
+use crate::errors::ToolError;
+use crate::filesystem::{modified_epoch_secs, scan_candidates, ScanOptions};
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CoverageArgs {
+    /// Failed and pending paths listed per root.
+    #[serde(default)]
+    pub max_samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedFile {
+    pub path: String,
+    pub error: String,
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RootCoverage {
+    pub root: String,
+    /// Files the policy would index.
+    pub eligible: u64,
+    pub eligible_bytes: u64,
+    pub indexed: u64,
+    pub failed: u64,
+    pub evicted: u64,
+    pub pending: u64,
+    /// Shares of `eligible`, in percent (one decimal).
+    pub indexed_pct: f64,
+    pub failed_pct: f64,
+    pub evicted_pct: f64,
+    pub pending_pct: f64,
+    pub sample_failed: Vec<FailedFile>,
+    pub sample_pending: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Coverage {
+    pub roots: Vec<RootCoverage>,
+}
+
+/// MCP tool entrypoint (`silo_index_coverage`).
+pub async fn coverage_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: CoverageArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let coverage = coverage(state, &args).await?;
+    serde_json::to_value(coverage).map_err(|e| format!("failed to serialize coverage: {e}").into())
+}
+
+pub async fn coverage(state: &SharedState, args: &CoverageArgs) -> Result<Coverage, ToolError> {
+    let Some(policy) = state.filesystem_policy().await else {
+        return Err(ToolError::no_filesystem_policy());
+    };
+    let max_samples = args.max_samples.unwrap_or(20).min(1000);
+    let scan_opts = || ScanOptions {
+        max_sample_candidates: 0,
+        max_sample_skipped: 0,
+    };
+
+    let mut out = Coverage::default();
+    for root in state.filesystem_roots().await {
+        let root = expand_tilde(&root.to_string_lossy());
+        let mut files: Vec<(String, u64, Option<i64>)> = vec![];
+        scan_candidates(vec![root.clone()], &policy, scan_opts(), |path, meta| {
+            files.push((path.to_string_lossy().to_string(), meta.len(), modified_epoch_secs(meta)));
+        })
+        .await;
+
+        let mut cov = RootCoverage {
+            root: root.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let catalog = state.catalog.read().await;
+        for (path, size, mtime) in files {
+            cov.eligible += 1;
+            cov.eligible_bytes += size;
+            let unchanged = catalog
+                .get(&path)
+                .filter(|e| e.size_bytes == Some(size as i64) && e.modified_epoch_secs == mtime);
+            match (unchanged, catalog.failure(&path)) {
+                (Some(e), _) if e.is_evicted() => cov.evicted += 1,
+                (Some(_), _) => cov.indexed += 1,
+                (None, Some(f)) => {
+                    cov.failed += 1;
+                    cov.sample_failed.push(FailedFile {
+                        path,
+                        error: f.error.clone(),
+                        attempts: f.attempts,
+                    });
+                }
+                (None, None) => {
+                    cov.pending += 1;
+                    cov.sample_pending.push(path);
+                }
+            }
+        }
+        drop(catalog);
+
+        cov.indexed_pct = pct(cov.indexed, cov.eligible);
+        cov.failed_pct = pct(cov.failed, cov.eligible);
+        cov.evicted_pct = pct(cov.evicted, cov.eligible);
+        cov.pending_pct = pct(cov.pending, cov.eligible);
+        // Deterministic samples, independent of traversal order.
+        cov.sample_failed.sort_by(|a, b| a.path.cmp(&b.path));
+        cov.sample_failed.truncate(max_samples);
+        cov.sample_pending.sort();
+        cov.sample_pending.truncate(max_samples);
+        out.roots.push(cov);
+    }
+    Ok(out)
+}
+
+fn pct(n: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (n as f64 * 1000.0 / total as f64).round() / 10.0
+}
@@ -0,0 +1,137 @@
+//! Shared daemon mode: one core process (state, DB, embedder, watchers) serves every client.
+//!
+//! The core listens on a local socket next to the data (`<data_dir>/silo.sock`; a named pipe
+//! derived from the data dir on Windows) and speaks the same line-delimited JSON-RPC as the
+//! stdio server, one session per connection. The core is either `mcp-server --daemon` or the
+//! desktop app (`SiloApp::new` starts the listener). A stdio `mcp-server` that finds a core
+//! becomes a thin proxy (`proxy_stdio`), so no second model load, watcher, or DB open happens.
+//! A proxy started with `SILO_READ_ONLY` opens its session with `silo/readOnly`, so that client
+//! gets read-only mode even when the core does not run in it.
+//!
+//! Only the process holding the data-dir lock (`crate::datalock`) can listen, so a socket left
+//! by a crashed core is stale and replaced.
+
+use crate::state::SharedState;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+pub const SOCKET_FILE: &str = "silo.sock";
+/// JSON-RPC notification making the rest of a connection read-only.
+pub const READ_ONLY_METHOD: &str = "silo/readOnly";
+
+/// Socket (Unix) or pipe name (Windows) for `data_dir`.
+pub fn endpoint(data_dir: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let dir = std::fs::canonicalize(data_dir).unwrap_or_else(|_| data_dir.to_path_buf());
+        let key = blake3::hash(dir.to_string_lossy().as_bytes()).to_hex()[..16].to_string();
+        PathBuf::from(format!(r"\\.\pipe\silo-{key}"))
+    }
+    #[cfg(not(windows))]
+    {
+        data_dir.join(SOCKET_FILE)
+    }
+}
+
+/// Start serving `state` on the data dir's endpoint (in the background).
+///
+/// Call only after the DB opened, i.e. while holding the data-dir lock.
+pub fn spawn_listener(state: &SharedState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = listen(state).await {
+            tracing::warn!("Daemon listener stopped: {e}");
+        }
+    });
+}
+
+/// Serve `state` on the data dir's endpoint until the process exits.
+pub async fn listen(state: SharedState) -> Result<(), String> {
+    let endpoint = endpoint(&state.data_dir);
+    tracing::info!("Serving Silo clients on {}", endpoint.display());
+    imp::listen(state, &endpoint).await
+}
+
+/// Forward stdio to a running core. `Ok(false)` when there is none (serve in-process instead).
+/// With `read_only`, the session is made read-only before the client's first message.
+pub async fn proxy_stdio(data_dir: &Path, read_only: bool) -> Result<bool, String> {
+    let Some(mut conn) = imp::connect(&endpoint(data_dir)).await else {
+        return Ok(false);
+    };
+    if read_only {
+        let line = format!("{}\n", serde_json::json!({ "jsonrpc": "2.0", "method": READ_ONLY_METHOD }));
+        conn.write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Connection to the Silo daemon failed: {e}"))?;
+    }
+    let mut stdio = tokio::io::join(tokio::io::stdin(), tokio::io::stdout());
+    tokio::io::copy_bidirectional(&mut stdio, &mut conn)
+        .await
+        .map_err(|e| format!("Connection to the Silo daemon failed: {e}"))?;
+    Ok(true)
+}
+
+async fn serve_connection<S>(state: SharedState, stream: S)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (read, write) = tokio::io::split(stream);
+    if let Err(e) = crate::server::serve(tokio::io::BufReader::new(read), write, &state).await {
+        tracing::debug!("Client session ended: {e}");
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::serve_connection;
+    use crate::state::SharedState;
+    use std::path::Path;
+    use tokio::net::{UnixListener, UnixStream};
+
+    pub async fn listen(state: SharedState, path: &Path) -> Result<(), String> {
+        // We hold the data-dir lock, so an existing socket belongs to a core that is gone.
+        let _ = tokio::fs::remove_file(path).await;
+        let listener =
+            UnixListener::bind(path).map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+        loop {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .map_err(|e| format!("Accept failed on {}: {e}", path.display()))?;
+            tokio::spawn(serve_connection(state.clone(), stream));
+        }
+    }
+
+    pub async fn connect(path: &Path) -> Option<UnixStream> {
+        UnixStream::connect(path).await.ok()
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::serve_connection;
+    use crate::state::SharedState;
+    use std::path::Path;
+    use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient, ServerOptions};
+
+    pub async fn listen(state: SharedState, name: &Path) -> Result<(), String> {
+        let mut server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(name)
+            .map_err(|e| format!("Failed to create pipe {}: {e}", name.display()))?;
+        loop {
+            server
+                .connect()
+                .await
+                .map_err(|e| format!("Pipe connect failed on {}: {e}", name.display()))?;
+            let next = ServerOptions::new()
+                .create(name)
+                .map_err(|e| format!("Failed to create pipe {}: {e}", name.display()))?;
+            tokio::spawn(serve_connection(state.clone(), std::mem::replace(&mut server, next)));
+        }
+    }
+
+    pub async fn connect(name: &Path) -> Option<NamedPipeClient> {
+        ClientOptions::new().open(name).ok()
+    }
+}
@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
 
+#[cfg(feature = "lancedb")]
+use crate::config::VectorType;
 #[cfg(feature = "lancedb")]
 use std::path::PathBuf;
 
-#[cfg(feature = "lancedb")]
-use std::sync::Arc;
+use crate::config::{StorageBackend, StorageConfig};
+use crate::predicate::Predicate;
+use crate::store::{StoreBackend, StoreStats, VectorStore};
 
 pub type DatabaseHandle = std::sync::Arc<Database>;
 
@@ -13,34 +17,210 @@ const EMBEDDING_DIM: usize = crate::embed::EMBEDDING_DIM;
 
 #[derive(Clone)]
 pub enum Database {
-    #[cfg(feature = "lancedb")]
-    Enabled(EnabledDatabase),
+    /// A working store: LanceDB or SQLite on disk, or in memory (see `crate::store`).
+    Enabled(Arc<dyn VectorStore>),
     Disabled { reason: String },
 }
 
+/// The LanceDB backend.
 #[cfg(feature = "lancedb")]
-#[derive(Clone)]
-pub struct EnabledDatabase {
+pub struct LanceStore {
     #[allow(dead_code)]
     data_dir: PathBuf,
     // We keep the table behind a mutex to avoid relying on Table's thread-safety guarantees.
     table: std::sync::Arc<tokio::sync::Mutex<lancedb::Table>>,
+    /// Entity mentions per chunk (see `crate::entities`).
+    entities: std::sync::Arc<tokio::sync::Mutex<lancedb::Table>>,
+    vector_type: VectorType,
+    /// Held while any clone is alive (see `crate::datalock`).
+    _lock: std::sync::Arc<crate::datalock::DataDirLock>,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum DbError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    /// Another process holds the data dir (see `crate::datalock`).
+    #[error("{0}")]
+    Locked(String),
     #[cfg(feature = "lancedb")]
     #[error("lancedb error: {0}")]
     LanceDb(#[from] lancedb::Error),
     #[cfg(feature = "lancedb")]
     #[error("arrow error: {0}")]
     Arrow(#[from] arrow_schema::ArrowError),
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// The configured backend is not compiled in.
+    #[error("{0}")]
+    Unavailable(String),
+}
+
+/// What `Database::compact_and_prune` did, summed over the tables.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompactionStats {
+    pub fragments_removed: usize,
+    pub fragments_added: usize,
+    pub files_removed: usize,
+    pub files_added: usize,
+    pub versions_removed: u64,
+    pub bytes_removed: u64,
+}
+
+/// Distance metric used for vector search. Scores are reported as similarity (higher is better).
+pub const SEARCH_METRIC: &str = "cosine";
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub top_k: usize,
+    /// Drop hits whose cosine similarity is below this value (range -1.0..=1.0).
+    pub min_score: Option<f32>,
+    /// Only consider chunk rows matching this predicate.
+    pub filter: Option<Predicate>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            top_k: 10,
+            min_score: None,
+            filter: None,
+        }
+    }
+}
+
+/// One entity mention in one chunk (entities table row).
+#[derive(Debug, Clone)]
+pub struct EntityRow {
+    /// `id` of the chunk row (see `chunk_row_id`).
+    pub chunk_id: String,
+    pub path: String,
+    pub chunk_index: usize,
+    /// Display name as found in the text.
+    pub name: String,
+    /// Normalized name used for lookups (`crate::entities::entity_key`).
+    pub key: String,
+    pub kind: String,
+}
+
+/// One chunk row (documents table).
+#[derive(Debug, Clone)]
+pub struct ChunkRow {
+    /// See `chunk_row_id`.
+    pub id: String,
+    pub path: String,
+    pub chunk_index: usize,
+    pub start_token: usize,
+    pub end_token: usize,
+    pub file_mtime_epoch_secs: Option<i64>,
+    pub file_size_bytes: Option<i64>,
+    pub file_hash: Option<String>,
+    /// 1-based page where the chunk starts (paged documents only).
+    pub page: Option<i64>,
+    /// Document tags in `tags_column` form (extended-attribute tags plus generated tags).
+    pub tags: Option<String>,
+    /// `ChunkSource` name (`None` for rows stored before the column existed).
+    pub source: Option<String>,
+    /// Earliest and latest timestamp of the chunk's entries (log lines, chat messages).
+    pub time_start_epoch_secs: Option<i64>,
+    pub time_end_epoch_secs: Option<i64>,
+    pub content: String,
+    /// f32 whatever the table's vector type.
+    pub embedding: Vec<f32>,
+}
+
+/// Tags are stored as one string column, `,tag-a,tag-b,`, so a tag filter is a plain
+/// substring match (`tags_filter`). Tags themselves never contain commas.
+pub fn tags_column(tags: &[String]) -> String {
+    format!(",{},", tags.join(","))
+}
+
+/// Rows tagged with every tag in `tags`.
+pub fn tags_filter(tags: &[String]) -> Predicate {
+    Predicate::all(tags.iter().map(|t| Predicate::contains("tags", &format!(",{t},"))))
+}
+
+/// Connector a chunk came from (`source` column). Chat connectors, uploads and web pages store
+/// their documents under their own URL schemes (`crate::messages`, `crate::chat_export`,
+/// `crate::upload`, `crate::web`); everything else is a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkSource {
+    Filesystem,
+    Messages,
+    Whatsapp,
+    Signal,
+    Upload,
+    Web,
+}
+
+impl ChunkSource {
+    pub const ALL: [ChunkSource; 6] = [
+        ChunkSource::Filesystem,
+        ChunkSource::Messages,
+        ChunkSource::Whatsapp,
+        ChunkSource::Signal,
+        ChunkSource::Upload,
+        ChunkSource::Web,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChunkSource::Filesystem => "filesystem",
+            ChunkSource::Messages => "messages",
+            ChunkSource::Whatsapp => "whatsapp",
+            ChunkSource::Signal => "signal",
+            ChunkSource::Upload => "upload",
+            ChunkSource::Web => "web",
+        }
+    }
+
+    /// Path prefixes of the connector's documents (none for files).
+    pub fn path_prefixes(&self) -> &'static [&'static str] {
+        match self {
+            ChunkSource::Filesystem => &[],
+            ChunkSource::Messages => &[crate::messages::PATH_PREFIX],
+            ChunkSource::Whatsapp => &[crate::chat_export::WHATSAPP_PREFIX],
+            ChunkSource::Signal => &[crate::chat_export::SIGNAL_PREFIX],
+            ChunkSource::Upload => &[crate::upload::PATH_PREFIX],
+            ChunkSource::Web => crate::web::PATH_PREFIXES,
+        }
+    }
+
+    /// The connector that stores documents at `path`.
+    pub fn of_path(path: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|s| s.path_prefixes().iter().any(|p| path.starts_with(p)))
+            .unwrap_or(ChunkSource::Filesystem)
+    }
+}
+
+/// Rows from any of `sources`.
+pub fn sources_filter(sources: &[ChunkSource]) -> Predicate {
+    Predicate::in_list("source", sources.iter().map(|s| s.as_str().to_string()))
+}
+
+/// A chunk to store with `replace_file_chunks`: (chunk_index, start_token, end_token, page,
+/// time span, content, embedding).
+pub type ChunkInput = (usize, usize, usize, Option<i64>, Option<(i64, i64)>, String, Vec<f32>);
+
+/// Stable id of a chunk row: path + chunk index + content hash.
+pub fn chunk_row_id(path: &str, chunk_index: usize, content: &str) -> String {
+    blake3::hash(
+        format!("{path}\n{chunk_index}\n{}", blake3::hash(content.as_bytes()).to_hex()).as_bytes(),
+    )
+    .to_hex()
+    .to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchHit {
+    /// Row id of the chunk (see `chunk_row_id`); stable while the chunk's text is unchanged, and
+    /// accepted by `silo_get_chunk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chunk_index: Option<i64>,
@@ -48,95 +228,146 @@ pub struct SearchHit {
     pub start_token: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_token: Option<i64>,
+    /// 1-based page where the chunk starts (PDFs), for opening the document at the hit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    /// Cosine similarity (higher is better).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score: Option<f32>,
+    /// Raw cosine distance as returned by LanceDB (`1 - score`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_preview: Option<String>,
+    /// Full chunk text (used for RAG prompts; not included in tool output).
+    #[serde(skip_serializing, default)]
+    pub content: Option<String>,
+    /// Other files holding the same chunk text, collapsed into this hit (`silo_search`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_paths: Vec<String>,
+    /// `ChunkSource` name of the chunk (missing for rows stored before the column existed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Time span of the chunk's log lines or chat messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_start_epoch_secs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_end_epoch_secs: Option<i64>,
+    /// Partition the hit came from in a federated search (`crate::federation`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
 }
 
 impl Database {
     /// Create or open the local DB.
     ///
     /// - With feature `lancedb`: opens/creates a local LanceDB at `data_dir`.
-    /// - Without: returns a disabled DB (so Milestone 1 handshake/tools still work).
+    /// - Without: an in-memory store, so indexing and search still work within one run.
     pub async fn new(data_dir: impl AsRef<Path>) -> Result<Self, DbError> {
-        #[cfg(feature = "lancedb")]
-        {
-            const TABLE_NAME: &str = "silo_chunks_v1";
-            let data_dir = data_dir.as_ref().to_path_buf();
-            tokio::fs::create_dir_all(&data_dir).await?;
-            // lancedb 0.4.x: connect(...) returns a builder; call execute().await to connect.
-            let conn = lancedb::connect(data_dir.to_string_lossy().as_ref())
-                .execute()
-                .await?;
-            let table = open_or_create_table(&conn, TABLE_NAME).await?;
-            return Ok(Database::Enabled(EnabledDatabase {
-                data_dir,
-                table: std::sync::Arc::new(tokio::sync::Mutex::new(table)),
-            }));
-        }
+        Self::open(data_dir, &StorageConfig::default()).await
+    }
 
-        #[cfg(not(feature = "lancedb"))]
-        {
-            let _ = data_dir;
-            Ok(Database::Disabled {
-                reason: "LanceDB is not enabled. Rebuild with `--features lancedb`.".to_string(),
-            })
+    /// `new` with the storage configured in the config file (`storage.backend`, `storage.vector_type`).
+    pub async fn open_configured(data_dir: impl AsRef<Path>) -> Result<Self, DbError> {
+        let storage = crate::config::load_or_init_config(&crate::config::default_config_path())
+            .await
+            .map(|cfg| cfg.storage)
+            .unwrap_or_default();
+        Self::open(data_dir, &storage).await
+    }
+
+    pub async fn open(data_dir: impl AsRef<Path>, storage: &StorageConfig) -> Result<Self, DbError> {
+        let data_dir = data_dir.as_ref();
+        let vector_type = storage.vector_type;
+        match storage.backend {
+            StorageBackend::Lancedb => {
+                #[cfg(feature = "lancedb")]
+                {
+                    let store = LanceStore::open(data_dir, vector_type).await?;
+                    Ok(Database::Enabled(Arc::new(store)))
+                }
+
+                #[cfg(not(feature = "lancedb"))]
+                {
+                    let _ = (data_dir, vector_type);
+                    tracing::warn!("Built without LanceDB (`--features lancedb`): the index is kept in memory only");
+                    Ok(Self::memory())
+                }
+            }
+            StorageBackend::Sqlite => {
+                #[cfg(feature = "sqlite")]
+                {
+                    let store = crate::sqlitestore::SqliteStore::open(data_dir, vector_type).await?;
+                    Ok(Database::Enabled(Arc::new(store)))
+                }
+
+                #[cfg(not(feature = "sqlite"))]
+                {
+                    let _ = (data_dir, vector_type);
+                    Err(DbError::Unavailable(
+                        "storage.backend = \"sqlite\" needs a build with `--features sqlite`".to_string(),
+                    ))
+                }
+            }
         }
     }
 
+    /// An empty in-memory store (nothing survives the process).
+    pub fn memory() -> Self {
+        Database::Enabled(Arc::new(crate::memstore::MemoryStore::default()))
+    }
+
     /// A safe fallback mode where DB-backed tools return a clear error string instead of crashing.
     pub fn disabled(reason: String) -> Self {
         Database::Disabled { reason }
     }
 
     pub fn is_enabled(&self) -> bool {
-        #[cfg(feature = "lancedb")]
-        {
-            matches!(self, Database::Enabled(_))
-        }
-        #[cfg(not(feature = "lancedb"))]
-        {
-            false
-        }
+        matches!(self, Database::Enabled(_))
     }
 
     pub fn disabled_reason(&self) -> Option<&str> {
         match self {
             Database::Disabled { reason } => Some(reason.as_str()),
-            #[cfg(feature = "lancedb")]
             Database::Enabled(_) => None,
         }
     }
 
-    /// Stores a document (placeholder embedding).
-    pub async fn add_document(&self, path: &str, content: &str) -> Result<(), DbError> {
-        let _ = (path, content);
-        #[cfg(feature = "lancedb")]
-        {
-            let Database::Enabled(db) = self else {
-                return Ok(());
-            };
+    pub fn backend(&self) -> Option<StoreBackend> {
+        self.store().map(|s| s.backend())
+    }
 
-            let mut table = db.table.lock().await;
-            add_row(
-                &mut table,
-                Row {
-                    id: blake3::hash(format!("{path}\n0").as_bytes()).to_hex().to_string(),
-                    path: path.to_string(),
-                    chunk_index: 0,
-                    start_token: 0,
-                    end_token: 0,
-                    file_mtime_epoch_secs: None,
-                    file_size_bytes: None,
-                    file_hash: None,
-                    content: content.to_string(),
-                    embedding: zero_embedding(),
-                },
-            )
-            .await?;
+    fn store(&self) -> Option<&Arc<dyn VectorStore>> {
+        match self {
+            Database::Enabled(store) => Some(store),
+            Database::Disabled { .. } => None,
         }
-        Ok(())
+    }
+
+    /// Stores a document (placeholder embedding).
+    pub async fn add_document(&self, path: &str, content: &str) -> Result<(), DbError> {
+        let Some(store) = self.store() else {
+            return Ok(());
+        };
+        store
+            .add_chunks(vec![ChunkRow {
+                id: blake3::hash(format!("{path}\n0").as_bytes()).to_hex().to_string(),
+                path: path.to_string(),
+                chunk_index: 0,
+                start_token: 0,
+                end_token: 0,
+                file_mtime_epoch_secs: None,
+                file_size_bytes: None,
+                file_hash: None,
+                page: None,
+                tags: None,
+                source: Some(ChunkSource::of_path(path).as_str().to_string()),
+                time_start_epoch_secs: None,
+                time_end_epoch_secs: None,
+                content: content.to_string(),
+                embedding: zero_embedding(),
+            }])
+            .await
     }
 
     /// Stores a chunk row (placeholder embedding).
@@ -152,125 +383,394 @@ impl Database {
         content: &str,
         embedding: &[f32],
     ) -> Result<(), DbError> {
-        let _ = (id, path, chunk_index, start_token, end_token, content, embedding);
-        #[cfg(feature = "lancedb")]
-        {
-            let Database::Enabled(db) = self else {
-                return Ok(());
-            };
-
-            let mut table = db.table.lock().await;
-            add_row(
-                &mut table,
-                Row {
-                    id: id.to_string(),
-                    path: path.to_string(),
-                    chunk_index,
-                    start_token,
-                    end_token,
-                    file_mtime_epoch_secs: None,
-                    file_size_bytes: None,
-                    file_hash: None,
-                    content: content.to_string(),
-                    embedding: embedding.to_vec(),
-                },
-            )
-            .await?;
-        }
-        Ok(())
+        let Some(store) = self.store() else {
+            return Ok(());
+        };
+        store
+            .add_chunks(vec![ChunkRow {
+                id: id.to_string(),
+                path: path.to_string(),
+                chunk_index,
+                start_token,
+                end_token,
+                file_mtime_epoch_secs: None,
+                file_size_bytes: None,
+                file_hash: None,
+                page: None,
+                tags: None,
+                source: Some(ChunkSource::of_path(path).as_str().to_string()),
+                time_start_epoch_secs: None,
+                time_end_epoch_secs: None,
+                content: content.to_string(),
+                embedding: embedding.to_vec(),
+            }])
+            .await
     }
 
-    /// Replace all chunks for a given file path:
-    /// 1) delete existing rows for that path
-    /// 2) batch-insert new rows
+    /// Replace all chunks for a given file path (and drop its entity mentions, which point at
+    /// the old chunk ids; the caller re-adds them). Interrupted, the file keeps its old rows.
     pub async fn replace_file_chunks(
         &self,
         path: &str,
         file_mtime_epoch_secs: Option<i64>,
         file_size_bytes: Option<i64>,
         file_hash: Option<String>,
-        rows: Vec<(usize, usize, usize, String, Vec<f32>)>, // (chunk_index, start_token, end_token, content, embedding)
+        tags: &[String],
+        rows: Vec<ChunkInput>,
     ) -> Result<(), DbError> {
-        #[cfg(not(feature = "lancedb"))]
-        {
-            let _ = (
-                path,
+        let Some(store) = self.store() else {
+            return Ok(());
+        };
+        let tags = (!tags.is_empty()).then(|| tags_column(tags));
+        let source = ChunkSource::of_path(path).as_str();
+        let rows = rows
+            .into_iter()
+            .map(|(chunk_index, start_token, end_token, page, time, content, embedding)| ChunkRow {
+                id: chunk_row_id(path, chunk_index, &content),
+                path: path.to_string(),
+                chunk_index,
+                start_token,
+                end_token,
                 file_mtime_epoch_secs,
                 file_size_bytes,
-                &file_hash,
-                &rows,
-            );
-            return Ok(());
+                file_hash: file_hash.clone(),
+                page,
+                tags: tags.clone(),
+                source: Some(source.to_string()),
+                time_start_epoch_secs: time.map(|(start, _)| start),
+                time_end_epoch_secs: time.map(|(_, end)| end),
+                content,
+                embedding,
+            })
+            .collect();
+        store.replace_file_chunks(path, rows).await
+    }
+
+    /// Remove every chunk (and entity mention) of `path`.
+    pub async fn delete_file(&self, path: &str) -> Result<(), DbError> {
+        match self.store() {
+            Some(store) => store.delete_file(path).await,
+            None => Ok(()),
         }
-        #[cfg(feature = "lancedb")]
-        {
-            let Database::Enabled(db) = self else {
-                return Ok(());
-            };
+    }
 
-            let mut table = db.table.lock().await;
-            delete_by_path(&mut table, path).await?;
-
-            let mut out_rows: Vec<Row> = Vec::with_capacity(rows.len());
-            for (chunk_index, start_token, end_token, content, embedding) in rows {
-                let id = blake3::hash(
-                    format!("{path}\n{chunk_index}\n{}", blake3::hash(content.as_bytes()).to_hex())
-                        .as_bytes(),
-                )
-                .to_hex()
-                .to_string();
-
-                out_rows.push(Row {
-                    id,
-                    path: path.to_string(),
-                    chunk_index,
-                    start_token,
-                    end_token,
-                    file_mtime_epoch_secs,
-                    file_size_bytes,
-                    file_hash: file_hash.clone(),
-                    content,
-                    embedding,
-                });
-            }
+    /// Move the chunks and entity mentions of `from` to `to` without re-embedding (a rename).
+    /// Returns the chunks moved (0 when `from` has none).
+    pub async fn rename_file(&self, from: &str, to: &str) -> Result<usize, DbError> {
+        match self.store() {
+            Some(store) => store.rename_file(from, to).await,
+            None => Ok(0),
+        }
+    }
 
-            add_rows(&mut table, out_rows).await?;
+    /// Compact the chunk table and drop old versions, so deleted rows free disk space.
+    pub async fn optimize(&self) -> Result<(), DbError> {
+        match self.store() {
+            Some(store) => store.optimize().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Compact both tables (merging small fragments and dropping deleted rows) and delete table
+    /// versions older than `prune_older_than`, returning what was reclaimed.
+    pub async fn compact_and_prune(&self, prune_older_than: std::time::Duration) -> Result<CompactionStats, DbError> {
+        match self.store() {
+            Some(store) => store.compact_and_prune(prune_older_than).await,
+            None => Ok(CompactionStats::default()),
         }
-        Ok(())
     }
 
-    /// Searches documents (placeholder query embedding).
     /// Vector search against stored chunks. Query embedding must match the DB schema dimension.
+    ///
+    /// Uses cosine distance and reports `score = 1 - distance` (cosine similarity), so callers
+    /// never have to reason about "lower is better".
     pub async fn search_chunks_by_vector(
         &self,
         query_embedding: &[f32],
-        top_k: usize,
+        opts: &SearchOptions,
     ) -> Result<Vec<SearchHit>, DbError> {
-        #[cfg(feature = "lancedb")]
-        {
-            use futures::TryStreamExt;
-            use lancedb::query::{ExecutableQuery, QueryBase};
-            let Database::Enabled(db) = self else {
-                return Ok(vec![]);
-            };
+        match self.store() {
+            Some(store) => store.search(query_embedding, opts).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Set the tags of every chunk of `path`.
+    pub async fn set_file_tags(&self, path: &str, tags: &[String]) -> Result<(), DbError> {
+        match self.store() {
+            Some(store) => store.set_file_tags(path, &tags_column(tags)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Append entity mention rows (call after `replace_file_chunks` for the same file).
+    pub async fn add_entities(&self, rows: Vec<EntityRow>) -> Result<(), DbError> {
+        match self.store() {
+            Some(store) => store.add_entities(rows).await,
+            None => Ok(()),
+        }
+    }
 
-            let table = db.table.lock().await;
-            let stream: lancedb::arrow::SendableRecordBatchStream = table
-                .vector_search(query_embedding)?
-                .column("embedding")
-                .limit(top_k)
+    /// Entity mention rows matching `filter` (at most `limit`).
+    pub async fn find_entities(&self, filter: &Predicate, limit: usize) -> Result<Vec<EntityRow>, DbError> {
+        match self.store() {
+            Some(store) => store.find_entities(filter, limit).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Every chunk row of `paths`, vectors included.
+    pub async fn chunks_for_paths(&self, paths: &[String]) -> Result<Vec<ChunkRow>, DbError> {
+        match self.store() {
+            Some(store) if !paths.is_empty() => store.chunks_for_paths(paths).await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Chunk rows matching `filter` (at most `limit`), vectors included.
+    pub async fn find_chunks(&self, filter: &Predicate, limit: usize) -> Result<Vec<ChunkRow>, DbError> {
+        match self.store() {
+            Some(store) => store.find_chunks(filter, limit).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Backend and row counts (`None` when disabled).
+    pub async fn stats(&self) -> Result<Option<StoreStats>, DbError> {
+        match self.store() {
+            Some(store) => store.stats().await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "lancedb")]
+impl LanceStore {
+    async fn open(data_dir: &Path, vector_type: VectorType) -> Result<Self, DbError> {
+        // v2: added `tags`. v3: added `page`. v4: added `source`. v5: added `time_start_epoch_secs`,
+        // `time_end_epoch_secs`. Tables of older versions are dropped on open (`drop_old_tables`).
+        const TABLE_VERSION: u32 = 5;
+        const TABLE_NAME: &str = "silo_chunks_v5";
+        const ENTITIES_TABLE_NAME: &str = "silo_entities_v1";
+        let table_name = match vector_type {
+            VectorType::F32 => TABLE_NAME.to_string(),
+            VectorType::F16 => format!("{TABLE_NAME}_f16"),
+        };
+        let data_dir = data_dir.to_path_buf();
+        tokio::fs::create_dir_all(&data_dir).await?;
+        let lock = crate::datalock::acquire(&data_dir).map_err(DbError::Locked)?;
+        // lancedb 0.4.x: connect(...) returns a builder; call execute().await to connect.
+        let conn = lancedb::connect(data_dir.to_string_lossy().as_ref())
+            .execute()
+            .await?;
+        drop_old_tables(&conn, TABLE_VERSION).await?;
+        let table = open_or_create_table(&conn, &table_name, documents_schema(vector_type)).await?;
+        let entities = open_or_create_table(&conn, ENTITIES_TABLE_NAME, entities_schema()).await?;
+        Ok(LanceStore {
+            data_dir,
+            table: std::sync::Arc::new(tokio::sync::Mutex::new(table)),
+            entities: std::sync::Arc::new(tokio::sync::Mutex::new(entities)),
+            vector_type,
+            _lock: std::sync::Arc::new(lock),
+        })
+    }
+}
+
+#[cfg(feature = "lancedb")]
+#[async_trait::async_trait]
+impl VectorStore for LanceStore {
+    fn backend(&self) -> StoreBackend {
+        StoreBackend::Lancedb
+    }
+
+    async fn add_chunks(&self, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        add_rows(&mut *self.table.lock().await, self.vector_type, rows).await
+    }
+
+    /// Never leaves the file without rows:
+    /// 1) batch-insert the new rows that are not stored yet (ids are content-addressed, see
+    ///    `chunk_row_id`, so an unchanged chunk keeps its row, unless it moved: same text at a
+    ///    different token range, page or time span is stored again with its new position)
+    /// 2) delete the path's rows that are not among the new ones, and the old rows of moved chunks
+    /// 3) refresh the file metadata of the rows kept from before
+    ///
+    /// If the process dies after 1), old and new chunks are both stored until the file is
+    /// ingested again; existing data is never lost.
+    async fn replace_file_chunks(&self, path: &str, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        let mut table = self.table.lock().await;
+        let existing = positions_for_path(&table, path).await?;
+        let new_ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+        let meta = rows.first().map(|r| {
+            (
+                r.file_mtime_epoch_secs,
+                r.file_size_bytes,
+                r.file_hash.clone(),
+                r.tags.clone(),
+            )
+        });
+        let stored = |r: &ChunkRow| existing.iter().any(|old| old.id == r.id && same_position(old, r));
+        // Old rows of chunks whose text is unchanged but whose position is not.
+        let moved: Vec<Predicate> = existing
+            .iter()
+            .filter(|old| rows.iter().any(|r| r.id == old.id && !same_position(old, r)))
+            .map(position_filter)
+            .collect();
+        let out_rows: Vec<ChunkRow> = rows.into_iter().filter(|r| !stored(r)).collect();
+        let kept = out_rows.len() < new_ids.len();
+
+        add_rows(&mut table, self.vector_type, out_rows).await?;
+        // Only now that the new rows are stored, drop the ones they replace.
+        let stale = Predicate::eq("path", path)
+            .and(Predicate::in_list("id", &new_ids).not().or(Predicate::any(moved)));
+        table.delete(stale.as_sql()).await?;
+        if let Some((mtime, size, hash, tags)) = meta.filter(|_| kept) {
+            table
+                .update()
+                .only_if(Predicate::eq("path", path).as_sql())
+                .column("file_mtime_epoch_secs", sql_opt_int(mtime))
+                .column("file_size_bytes", sql_opt_int(size))
+                .column("file_hash", sql_opt_str(hash.as_deref()))
+                .column("tags", sql_opt_str(tags.as_deref()))
                 .execute()
                 .await?;
+        }
+        // Mentions point at the old chunk ids; the caller re-adds them for the new chunks.
+        delete_by_path(&mut *self.entities.lock().await, path).await
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), DbError> {
+        delete_by_path(&mut *self.table.lock().await, path).await?;
+        delete_by_path(&mut *self.entities.lock().await, path).await
+    }
 
-            let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
-            Ok(batches_to_hits(batches))
+    async fn search(&self, query: &[f32], opts: &SearchOptions) -> Result<Vec<SearchHit>, DbError> {
+        use futures::TryStreamExt;
+        use lancedb::query::{ExecutableQuery, QueryBase};
+
+        let table = self.table.lock().await;
+        let mut query = table
+            .vector_search(query)?
+            .column("embedding")
+            .distance_type(lancedb::DistanceType::Cosine)
+            .limit(opts.top_k);
+        if let Some(filter) = &opts.filter {
+            query = query.only_if(filter.as_sql());
+        }
+        let stream: lancedb::arrow::SendableRecordBatchStream = query.execute().await?;
+
+        let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
+        let mut hits = batches_to_hits(batches);
+        if let Some(min) = opts.min_score {
+            hits.retain(|h| h.score.is_some_and(|s| s >= min));
         }
+        Ok(hits)
+    }
+
+    async fn chunks_for_paths(&self, paths: &[String]) -> Result<Vec<ChunkRow>, DbError> {
+        use futures::TryStreamExt;
+        use lancedb::query::{ExecutableQuery, QueryBase};
+
+        let table = self.table.lock().await;
+        let stream = table
+            .query()
+            .only_if(Predicate::in_list("path", paths).as_sql())
+            .limit(u32::MAX as usize)
+            .execute()
+            .await?;
+        let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
+        Ok(batches_to_chunk_rows(batches))
+    }
+
+    async fn find_chunks(&self, filter: &Predicate, limit: usize) -> Result<Vec<ChunkRow>, DbError> {
+        use futures::TryStreamExt;
+        use lancedb::query::{ExecutableQuery, QueryBase};
+
+        let table = self.table.lock().await;
+        let stream = table.query().only_if(filter.as_sql()).limit(limit).execute().await?;
+        let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
+        Ok(batches_to_chunk_rows(batches))
+    }
+
+    async fn set_file_tags(&self, path: &str, tags_column: &str) -> Result<(), DbError> {
+        let table = self.table.lock().await;
+        table
+            .update()
+            .only_if(Predicate::eq("path", path).as_sql())
+            .column("tags", crate::predicate::quote_str(tags_column))
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    async fn add_entities(&self, rows: Vec<EntityRow>) -> Result<(), DbError> {
+        add_entity_rows(&mut *self.entities.lock().await, rows).await
+    }
+
+    async fn find_entities(&self, filter: &Predicate, limit: usize) -> Result<Vec<EntityRow>, DbError> {
+        use futures::TryStreamExt;
+        use lancedb::query::{ExecutableQuery, QueryBase};
+
+        let table = self.entities.lock().await;
+        let stream = table
+            .query()
+            .only_if(filter.as_sql())
+            .limit(limit)
+            .execute()
+            .await?;
+        let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
+        Ok(batches_to_entity_rows(batches))
+    }
+
+    async fn stats(&self) -> Result<StoreStats, DbError> {
+        let chunks = self.table.lock().await.count_rows(None).await?;
+        let entity_mentions = self.entities.lock().await.count_rows(None).await?;
+        Ok(StoreStats {
+            backend: StoreBackend::Lancedb,
+            persistent: true,
+            chunks: chunks as u64,
+            entity_mentions: entity_mentions as u64,
+        })
+    }
+
+    async fn optimize(&self) -> Result<(), DbError> {
+        self.table
+            .lock()
+            .await
+            .optimize(lancedb::table::OptimizeAction::All)
+            .await?;
+        Ok(())
+    }
 
-        #[cfg(not(feature = "lancedb"))]
-        {
-            let _ = (query_embedding, top_k);
-            Ok(vec![])
+    async fn compact_and_prune(&self, prune_older_than: std::time::Duration) -> Result<CompactionStats, DbError> {
+        use lancedb::table::{CompactionOptions, OptimizeAction};
+        let mut stats = CompactionStats::default();
+        let older_than = chrono::Duration::seconds(prune_older_than.as_secs().min(i64::MAX as u64) as i64);
+        for table in [&self.table, &self.entities] {
+            let table = table.lock().await;
+            let compacted = table
+                .optimize(OptimizeAction::Compact {
+                    options: CompactionOptions::default(),
+                    remap_options: None,
+                })
+                .await?;
+            if let Some(m) = compacted.compaction {
+                stats.fragments_removed += m.fragments_removed;
+                stats.fragments_added += m.fragments_added;
+                stats.files_removed += m.files_removed;
+                stats.files_added += m.files_added;
+            }
+            let pruned = table
+                .optimize(OptimizeAction::Prune {
+                    older_than,
+                    delete_unverified: None,
+                })
+                .await?;
+            if let Some(r) = pruned.prune {
+                stats.versions_removed += r.old_versions;
+                stats.bytes_removed += r.bytes_removed;
+            }
         }
+        Ok(stats)
     }
 }
 
@@ -278,7 +778,18 @@ fn zero_embedding() -> Vec<f32> {
     vec![0.0; EMBEDDING_DIM]
 }
 
-fn preview(s: &str, max_chars: usize) -> String {
+/// Cosine distance (`1 - cos`) -> similarity. Degenerate vectors (e.g. the noop embedder's
+/// all-zero output) produce NaN distances; report those as 0.0 (unrelated).
+#[cfg(any(feature = "lancedb", feature = "sqlite"))]
+pub(crate) fn cosine_similarity_from_distance(distance: f32) -> f32 {
+    if distance.is_finite() {
+        (1.0 - distance).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+pub(crate) fn preview(s: &str, max_chars: usize) -> String {
     let mut out = s.chars().take(max_chars).collect::<String>();
     if s.chars().count() > max_chars {
         out.push_str("…");
@@ -289,23 +800,12 @@ fn preview(s: &str, max_chars: usize) -> String {
 // --- LanceDB integration (feature-gated) ---
 
 #[cfg(feature = "lancedb")]
-#[derive(Debug, Clone)]
-struct Row {
-    id: String,
-    path: String,
-    chunk_index: usize,
-    start_token: usize,
-    end_token: usize,
-    file_mtime_epoch_secs: Option<i64>,
-    file_size_bytes: Option<i64>,
-    file_hash: Option<String>,
-    content: String,
-    embedding: Vec<f32>,
-}
-
-#[cfg(feature = "lancedb")]
-fn documents_schema() -> arrow_schema::SchemaRef {
+fn documents_schema(vector_type: VectorType) -> arrow_schema::SchemaRef {
     use arrow_schema::{DataType, Field, Schema};
+    let element = match vector_type {
+        VectorType::F32 => DataType::Float32,
+        VectorType::F16 => DataType::Float16,
+    };
     Arc::new(Schema::new(vec![
         Field::new("id", DataType::Utf8, false),
         Field::new("path", DataType::Utf8, false),
@@ -315,11 +815,16 @@ fn documents_schema() -> arrow_schema::SchemaRef {
         Field::new("file_mtime_epoch_secs", DataType::Int64, true),
         Field::new("file_size_bytes", DataType::Int64, true),
         Field::new("file_hash", DataType::Utf8, true),
+        Field::new("page", DataType::Int64, true),
+        Field::new("tags", DataType::Utf8, true),
+        Field::new("source", DataType::Utf8, true),
+        Field::new("time_start_epoch_secs", DataType::Int64, true),
+        Field::new("time_end_epoch_secs", DataType::Int64, true),
         Field::new("content", DataType::Utf8, false),
         Field::new(
             "embedding",
             DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, true)),
+                Arc::new(Field::new("item", element, true)),
                 EMBEDDING_DIM as i32,
             ),
             true,
@@ -327,76 +832,89 @@ fn documents_schema() -> arrow_schema::SchemaRef {
     ]))
 }
 
+/// The `embedding` column for `vectors`, converted to the table's element type.
+#[cfg(feature = "lancedb")]
+fn embedding_array(
+    vector_type: VectorType,
+    vectors: impl Iterator<Item = Vec<f32>>,
+) -> arrow_array::ArrayRef {
+    use arrow_array::types::{Float16Type, Float32Type};
+    use arrow_array::FixedSizeListArray;
+
+    let dim = EMBEDDING_DIM as i32;
+    match vector_type {
+        VectorType::F32 => Arc::new(FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            vectors.map(|v| Some(v.into_iter().map(Some).collect::<Vec<_>>())),
+            dim,
+        )),
+        VectorType::F16 => Arc::new(FixedSizeListArray::from_iter_primitive::<Float16Type, _, _>(
+            vectors.map(|v| Some(v.into_iter().map(|x| Some(half::f16::from_f32(x))).collect::<Vec<_>>())),
+            dim,
+        )),
+    }
+}
+
 #[cfg(feature = "lancedb")]
-async fn open_or_create_table(conn: &lancedb::Connection, name: &str) -> Result<lancedb::Table, DbError> {
+fn entities_schema() -> arrow_schema::SchemaRef {
+    use arrow_schema::{DataType, Field, Schema};
+    Arc::new(Schema::new(vec![
+        Field::new("chunk_id", DataType::Utf8, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("chunk_index", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("key", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+    ]))
+}
+
+#[cfg(feature = "lancedb")]
+async fn open_or_create_table(
+    conn: &lancedb::Connection,
+    name: &str,
+    schema: arrow_schema::SchemaRef,
+) -> Result<lancedb::Table, DbError> {
     match conn.open_table(name).execute().await {
         Ok(t) => Ok(t),
         Err(lancedb::Error::TableNotFound { .. }) => {
-            let schema = documents_schema();
             Ok(conn.create_empty_table(name, schema).execute().await?)
         }
         Err(e) => Err(DbError::LanceDb(e)),
     }
 }
 
+/// Drop chunk tables of schema versions before `current` (both vector types). Their chunks are
+/// not migrated: files are ingested again into the current table on the next index run.
 #[cfg(feature = "lancedb")]
-async fn add_row(table: &mut lancedb::Table, row: Row) -> Result<(), DbError> {
-    use arrow_array::{
-        types::Float32Type, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator,
-        StringArray,
-    };
-
-    let schema = documents_schema();
-
-    let id_arr = Arc::new(StringArray::from(vec![row.id]));
-    let path_arr = Arc::new(StringArray::from(vec![row.path]));
-    let chunk_index_arr = Arc::new(Int64Array::from(vec![row.chunk_index as i64]));
-    let start_token_arr = Arc::new(Int64Array::from(vec![row.start_token as i64]));
-    let end_token_arr = Arc::new(Int64Array::from(vec![row.end_token as i64]));
-    let file_mtime_arr = Arc::new(Int64Array::from(vec![row.file_mtime_epoch_secs]));
-    let file_size_arr = Arc::new(Int64Array::from(vec![row.file_size_bytes]));
-    let file_hash_arr = Arc::new(StringArray::from(vec![row.file_hash]));
-    let content_arr = Arc::new(StringArray::from(vec![row.content]));
-
-    let emb_list = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
-        std::iter::once(Some(row.embedding.into_iter().map(Some).collect::<Vec<_>>())),
-        EMBEDDING_DIM as i32,
-    );
-    let emb_arr = Arc::new(emb_list);
-
-    let batch = RecordBatch::try_new(
-        schema.clone(),
-        vec![
-            id_arr,
-            path_arr,
-            chunk_index_arr,
-            start_token_arr,
-            end_token_arr,
-            file_mtime_arr,
-            file_size_arr,
-            file_hash_arr,
-            content_arr,
-            emb_arr,
-        ],
-    )?;
-
-    let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
-    table.add(Box::new(batches)).execute().await?;
+async fn drop_old_tables(conn: &lancedb::Connection, current: u32) -> Result<(), DbError> {
+    for name in conn.table_names().execute().await? {
+        if !old_chunk_table(&name, current) {
+            continue;
+        }
+        conn.drop_table(&name).await?;
+        tracing::warn!("Dropped chunk table {name} from an older version; re-index (silo_index_home) to search those files again");
+    }
     Ok(())
 }
 
+/// `silo_chunks_v<N>` or `silo_chunks_v<N>_f16` with `N < current`.
 #[cfg(feature = "lancedb")]
-async fn add_rows(table: &mut lancedb::Table, rows: Vec<Row>) -> Result<(), DbError> {
-    use arrow_array::{
-        types::Float32Type, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator,
-        StringArray,
+fn old_chunk_table(name: &str, current: u32) -> bool {
+    let Some(rest) = name.strip_prefix("silo_chunks_v") else {
+        return false;
     };
+    let version = rest.strip_suffix("_f16").unwrap_or(rest);
+    version.parse::<u32>().is_ok_and(|v| v < current)
+}
+
+#[cfg(feature = "lancedb")]
+async fn add_rows(table: &mut lancedb::Table, vector_type: VectorType, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+    use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
 
     if rows.is_empty() {
         return Ok(());
     }
 
-    let schema = documents_schema();
+    let schema = documents_schema(vector_type);
 
     let id_arr = Arc::new(StringArray::from(rows.iter().map(|r| r.id.as_str()).collect::<Vec<_>>()));
     let path_arr =
@@ -415,17 +933,20 @@ async fn add_rows(table: &mut lancedb::Table, rows: Vec<Row>) -> Result<(), DbEr
     let file_hash_arr = Arc::new(StringArray::from(
         rows.iter().map(|r| r.file_hash.as_deref()).collect::<Vec<_>>(),
     ));
+    let page_arr = Arc::new(Int64Array::from(rows.iter().map(|r| r.page).collect::<Vec<_>>()));
+    let tags_arr = Arc::new(StringArray::from(
+        rows.iter().map(|r| r.tags.as_deref()).collect::<Vec<_>>(),
+    ));
+    let source_arr = Arc::new(StringArray::from(
+        rows.iter().map(|r| r.source.as_deref()).collect::<Vec<_>>(),
+    ));
+    let time_start_arr = Arc::new(Int64Array::from(rows.iter().map(|r| r.time_start_epoch_secs).collect::<Vec<_>>()));
+    let time_end_arr = Arc::new(Int64Array::from(rows.iter().map(|r| r.time_end_epoch_secs).collect::<Vec<_>>()));
 
     let content_arr =
         Arc::new(StringArray::from(rows.iter().map(|r| r.content.as_str()).collect::<Vec<_>>()));
 
-    let emb_list = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
-        rows.into_iter().map(|r| {
-            Some(r.embedding.into_iter().map(Some).collect::<Vec<_>>())
-        }),
-        EMBEDDING_DIM as i32,
-    );
-    let emb_arr = Arc::new(emb_list);
+    let emb_arr = embedding_array(vector_type, rows.into_iter().map(|r| r.embedding));
 
     let batch = RecordBatch::try_new(
         schema.clone(),
@@ -438,6 +959,11 @@ async fn add_rows(table: &mut lancedb::Table, rows: Vec<Row>) -> Result<(), DbEr
             file_mtime_arr,
             file_size_arr,
             file_hash_arr,
+            page_arr,
+            tags_arr,
+            source_arr,
+            time_start_arr,
+            time_end_arr,
             content_arr,
             emb_arr,
         ],
@@ -448,45 +974,256 @@ async fn add_rows(table: &mut lancedb::Table, rows: Vec<Row>) -> Result<(), DbEr
     Ok(())
 }
 
+#[cfg(feature = "lancedb")]
+async fn add_entity_rows(table: &mut lancedb::Table, rows: Vec<EntityRow>) -> Result<(), DbError> {
+    use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let schema = entities_schema();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(rows.iter().map(|r| r.chunk_id.as_str()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(rows.iter().map(|r| r.path.as_str()).collect::<Vec<_>>())),
+            Arc::new(Int64Array::from(rows.iter().map(|r| r.chunk_index as i64).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(rows.iter().map(|r| r.name.as_str()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(rows.iter().map(|r| r.key.as_str()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(rows.iter().map(|r| r.kind.as_str()).collect::<Vec<_>>())),
+        ],
+    )?;
+
+    let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
+    table.add(Box::new(batches)).execute().await?;
+    Ok(())
+}
+
+#[cfg(feature = "lancedb")]
+fn batches_to_entity_rows(batches: Vec<arrow_array::RecordBatch>) -> Vec<EntityRow> {
+    use arrow_array::cast::AsArray;
+    let mut out = vec![];
+    for b in batches {
+        let (Some(chunk_id), Some(path), Some(chunk_index), Some(name), Some(key), Some(kind)) = (
+            b.column_by_name("chunk_id"),
+            b.column_by_name("path"),
+            b.column_by_name("chunk_index"),
+            b.column_by_name("name"),
+            b.column_by_name("key"),
+            b.column_by_name("kind"),
+        ) else {
+            continue;
+        };
+        let chunk_id = chunk_id.as_string::<i32>();
+        let path = path.as_string::<i32>();
+        let chunk_index = chunk_index.as_primitive::<arrow_array::types::Int64Type>();
+        let name = name.as_string::<i32>();
+        let key = key.as_string::<i32>();
+        let kind = kind.as_string::<i32>();
+        for i in 0..b.num_rows() {
+            out.push(EntityRow {
+                chunk_id: chunk_id.value(i).to_string(),
+                path: path.value(i).to_string(),
+                chunk_index: chunk_index.value(i).max(0) as usize,
+                name: name.value(i).to_string(),
+                key: key.value(i).to_string(),
+                kind: kind.value(i).to_string(),
+            });
+        }
+    }
+    out
+}
+
+#[cfg(feature = "lancedb")]
+fn batches_to_chunk_rows(batches: Vec<arrow_array::RecordBatch>) -> Vec<ChunkRow> {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float16Type, Float32Type, Int64Type};
+    use arrow_array::Array;
+    let mut out = vec![];
+    for b in batches {
+        let (Some(id), Some(path), Some(chunk_index), Some(start_token), Some(end_token), Some(content)) = (
+            b.column_by_name("id"),
+            b.column_by_name("path"),
+            b.column_by_name("chunk_index"),
+            b.column_by_name("start_token"),
+            b.column_by_name("end_token"),
+            b.column_by_name("content"),
+        ) else {
+            continue;
+        };
+        let id = id.as_string::<i32>();
+        let path = path.as_string::<i32>();
+        let chunk_index = chunk_index.as_primitive::<Int64Type>();
+        let start_token = start_token.as_primitive::<Int64Type>();
+        let end_token = end_token.as_primitive::<Int64Type>();
+        let content = content.as_string::<i32>();
+        let opt_int = |name: &str| b.column_by_name(name).map(|c| c.as_primitive::<Int64Type>());
+        let opt_str = |name: &str| b.column_by_name(name).map(|c| c.as_string::<i32>());
+        let (mtime, size, page) = (opt_int("file_mtime_epoch_secs"), opt_int("file_size_bytes"), opt_int("page"));
+        let (file_hash, tags, source) = (opt_str("file_hash"), opt_str("tags"), opt_str("source"));
+        let (time_start, time_end) = (opt_int("time_start_epoch_secs"), opt_int("time_end_epoch_secs"));
+        let embedding = b.column_by_name("embedding").and_then(|c| c.as_fixed_size_list_opt());
+        for i in 0..b.num_rows() {
+            let int_at = |c: Option<&arrow_array::Int64Array>| c.filter(|c| c.is_valid(i)).map(|c| c.value(i));
+            let str_at = |c: Option<&arrow_array::StringArray>| {
+                c.filter(|c| c.is_valid(i)).map(|c| c.value(i).to_string())
+            };
+            let vector = embedding.filter(|e| e.is_valid(i)).map(|e| e.value(i));
+            let embedding = match vector {
+                Some(v) => match (v.as_primitive_opt::<Float32Type>(), v.as_primitive_opt::<Float16Type>()) {
+                    (Some(f32s), _) => f32s.values().to_vec(),
+                    (None, Some(f16s)) => f16s.values().iter().map(|x| x.to_f32()).collect(),
+                    (None, None) => vec![],
+                },
+                None => vec![],
+            };
+            out.push(ChunkRow {
+                id: id.value(i).to_string(),
+                path: path.value(i).to_string(),
+                chunk_index: chunk_index.value(i).max(0) as usize,
+                start_token: start_token.value(i).max(0) as usize,
+                end_token: end_token.value(i).max(0) as usize,
+                file_mtime_epoch_secs: int_at(mtime),
+                file_size_bytes: int_at(size),
+                file_hash: str_at(file_hash),
+                page: int_at(page),
+                tags: str_at(tags),
+                source: str_at(source),
+                time_start_epoch_secs: int_at(time_start),
+                time_end_epoch_secs: int_at(time_end),
+                content: content.value(i).to_string(),
+                embedding,
+            });
+        }
+    }
+    out
+}
+
 #[cfg(feature = "lancedb")]
 async fn delete_by_path(table: &mut lancedb::Table, path: &str) -> Result<(), DbError> {
-    // NOTE: LanceDB expects SQL predicate strings.
-    let escaped = path.replace('\'', "''");
-    let predicate = format!("path = '{escaped}'");
-    table.delete(&predicate).await?;
+    // NOTE: LanceDB expects SQL predicate strings; always build them via `Predicate`.
+    let predicate = Predicate::eq("path", path);
+    table.delete(predicate.as_sql()).await?;
     Ok(())
 }
 
+/// Rows stored for `path`, without their embeddings.
+#[cfg(feature = "lancedb")]
+async fn positions_for_path(table: &lancedb::Table, path: &str) -> Result<Vec<ChunkRow>, DbError> {
+    use futures::TryStreamExt;
+    use lancedb::query::{ExecutableQuery, QueryBase, Select};
+    let columns = [
+        "id",
+        "path",
+        "chunk_index",
+        "start_token",
+        "end_token",
+        "page",
+        "source",
+        "time_start_epoch_secs",
+        "time_end_epoch_secs",
+        "content",
+    ];
+    let stream = table
+        .query()
+        .only_if(Predicate::eq("path", path).as_sql())
+        .select(Select::Columns(columns.iter().map(|c| c.to_string()).collect()))
+        // Every row of the file, whatever the default query limit is.
+        .limit(u32::MAX as usize)
+        .execute()
+        .await?;
+    let batches = stream.try_collect::<Vec<arrow_array::RecordBatch>>().await?;
+    Ok(batches_to_chunk_rows(batches))
+}
+
+/// Same place in the file: token range, page, source and time span (the id covers the rest).
+#[cfg(feature = "lancedb")]
+fn same_position(a: &ChunkRow, b: &ChunkRow) -> bool {
+    (a.start_token, a.end_token, a.page, &a.source) == (b.start_token, b.end_token, b.page, &b.source)
+        && (a.time_start_epoch_secs, a.time_end_epoch_secs) == (b.time_start_epoch_secs, b.time_end_epoch_secs)
+}
+
+/// Matches the stored row `row` (by id and position).
+#[cfg(feature = "lancedb")]
+fn position_filter(row: &ChunkRow) -> Predicate {
+    let opt_int = |column, v: Option<i64>| v.map_or_else(|| Predicate::is_null(column), |v| Predicate::eq(column, v));
+    let source = row
+        .source
+        .as_deref()
+        .map_or_else(|| Predicate::is_null("source"), |s| Predicate::eq("source", s));
+    Predicate::all([
+        Predicate::eq("id", row.id.as_str()),
+        Predicate::eq("start_token", row.start_token as i64),
+        Predicate::eq("end_token", row.end_token as i64),
+        opt_int("page", row.page),
+        source,
+        opt_int("time_start_epoch_secs", row.time_start_epoch_secs),
+        opt_int("time_end_epoch_secs", row.time_end_epoch_secs),
+    ])
+}
+
+/// SQL value for `update().column(..)`.
+#[cfg(feature = "lancedb")]
+fn sql_opt_int(v: Option<i64>) -> String {
+    v.map_or_else(|| "NULL".to_string(), |v| v.to_string())
+}
+
+#[cfg(feature = "lancedb")]
+fn sql_opt_str(v: Option<&str>) -> String {
+    v.map_or_else(|| "NULL".to_string(), crate::predicate::quote_str)
+}
+
 #[cfg(feature = "lancedb")]
 fn batches_to_hits(batches: Vec<arrow_array::RecordBatch>) -> Vec<SearchHit> {
     use arrow_array::cast::AsArray;
+    use arrow_array::Array;
     let mut hits = vec![];
     for b in batches {
         let Some(path_col) = b.column_by_name("path") else { continue };
         let paths = path_col.as_string::<i32>();
+        let id_opt = b.column_by_name("id").map(|c| c.as_string::<i32>());
 
         let content_opt = b.column_by_name("content").map(|c| c.as_string::<i32>());
         let distance_opt = b.column_by_name("_distance").map(|c| c.as_primitive::<arrow_array::types::Float32Type>());
         let chunk_index_opt = b.column_by_name("chunk_index").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
         let start_token_opt = b.column_by_name("start_token").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
         let end_token_opt = b.column_by_name("end_token").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
+        let page_opt = b.column_by_name("page").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
+        let source_opt = b.column_by_name("source").map(|c| c.as_string::<i32>());
+        let time_start_opt = b.column_by_name("time_start_epoch_secs").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
+        let time_end_opt = b.column_by_name("time_end_epoch_secs").map(|c| c.as_primitive::<arrow_array::types::Int64Type>());
 
         for i in 0..b.num_rows() {
             let path = paths.value(i).to_string();
-            let content_preview = content_opt
-                .as_ref()
-                .map(|c| preview(c.value(i), 240));
-            let score = distance_opt.as_ref().map(|d| d.value(i));
+            let id = id_opt.as_ref().map(|c| c.value(i).to_string());
+            let content = content_opt.as_ref().map(|c| c.value(i).to_string());
+            let content_preview = content.as_deref().map(|c| preview(c, 240));
+            let distance = distance_opt.as_ref().map(|d| d.value(i));
+            let score = distance.map(cosine_similarity_from_distance);
             let chunk_index = chunk_index_opt.as_ref().map(|c| c.value(i));
             let start_token = start_token_opt.as_ref().map(|c| c.value(i));
             let end_token = end_token_opt.as_ref().map(|c| c.value(i));
+            let page = page_opt.as_ref().filter(|c| c.is_valid(i)).map(|c| c.value(i));
+            let source = source_opt.as_ref().filter(|c| c.is_valid(i)).map(|c| c.value(i).to_string());
+            let time_start_epoch_secs = time_start_opt.as_ref().filter(|c| c.is_valid(i)).map(|c| c.value(i));
+            let time_end_epoch_secs = time_end_opt.as_ref().filter(|c| c.is_valid(i)).map(|c| c.value(i));
             hits.push(SearchHit {
+                id,
                 path,
                 chunk_index,
                 start_token,
                 end_token,
+                page,
                 score,
+                distance,
                 content_preview,
+                content,
+                source,
+                time_start_epoch_secs,
+                time_end_epoch_secs,
+                duplicate_paths: vec![],
+                collection: None,
             });
         }
     }
@@ -0,0 +1,75 @@
+//! Data-dir lock: only one process (MCP server or desktop app) opens the knowledge base.
+//!
+//! LanceDB does not coordinate writers across processes, so `Database::new` takes an OS advisory
+//! lock on `<data_dir>/silo.lock` and keeps it for the life of the process (the OS releases it on
+//! exit, including crashes). The holder's pid and program are written into the file so the second
+//! process can say who has it.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+pub const LOCK_FILE: &str = "silo.lock";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub program: String,
+    #[serde(default)]
+    pub started_epoch_secs: Option<i64>,
+}
+
+/// Held lock; dropping it (or exiting) releases the data dir.
+#[derive(Debug)]
+pub struct DataDirLock {
+    _file: File,
+    pub path: PathBuf,
+}
+
+/// Lock `data_dir` for this process, or explain which process already holds it.
+pub fn acquire(data_dir: &Path) -> Result<DataDirLock, String> {
+    let path = data_dir.join(LOCK_FILE);
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| format!("Failed to open lock file {}: {e}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let mut raw = String::new();
+            let _ = file.read_to_string(&mut raw);
+            let holder = serde_json::from_str::<LockHolder>(&raw)
+                .map(|h| format!("{} (pid {})", h.program, h.pid))
+                .unwrap_or_else(|_| "another Silo process".to_string());
+            return Err(format!(
+                "The knowledge base in {} is already in use by {holder}. Quit it first (the MCP server and the desktop app cannot share a data dir), or set SILO_DATA_DIR to a different directory.",
+                data_dir.display()
+            ));
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(format!("Failed to lock {}: {e}", path.display()));
+        }
+    }
+
+    let holder = LockHolder {
+        pid: std::process::id(),
+        program: std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "silo".to_string()),
+        started_epoch_secs: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs() as i64),
+    };
+    // Informational only; the lock itself is what matters.
+    let _ = file.set_len(0).and_then(|_| file.rewind()).and_then(|_| {
+        file.write_all(serde_json::to_string(&holder).unwrap_or_default().as_bytes())
+    });
+    Ok(DataDirLock { _file: file, path })
+}
@@ -0,0 +1,279 @@
+//! Storage insights over the configured roots (`silo_disk_report`).
+//!
+//! One metadata-only walk (no extraction) aggregates sizes per folder (down to `max_depth`
+//! below each root), per extension, and keeps the largest files. Folder totals are saved to
+//! `disk_report.json` in the data dir so the next report can show growth since this scan.
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::filesystem::{modified_epoch_secs, walk_files};
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_FILE: &str = "disk_report.json";
+const SNAPSHOT_VERSION: u32 = 1;
+const DEFAULT_MAX_FILES: u64 = 2_000_000;
+
+#[derive(Debug, Deserialize)]
+pub struct DiskReportArgs {
+    /// Folder levels below each root to aggregate (1 = the root's direct children).
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    /// Entries per list (folders, files, extensions, growth).
+    #[serde(default)]
+    pub top_n: Option<usize>,
+    /// Also count paths matched by the exclude globs (e.g. `node_modules/`, caches).
+    #[serde(default)]
+    pub include_excluded: bool,
+    #[serde(default)]
+    pub max_files: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderUsage {
+    pub path: String,
+    pub bytes: u64,
+    pub files: u64,
+    /// Change since the previous scan (new folders count from zero; absent on the first scan).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub growth_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct FileUsage {
+    pub bytes: u64,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_epoch_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtensionUsage {
+    /// Lowercased, without the dot; empty for files without an extension.
+    pub extension: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Growth {
+    pub previous_scan_epoch_secs: i64,
+    pub total_bytes_delta: i64,
+    /// Folders with the largest size change (either direction).
+    pub changed_folders: Vec<FolderUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskReport {
+    pub roots: Vec<String>,
+    pub scanned_at_epoch_secs: i64,
+    pub total_bytes: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub skipped: u64,
+    /// The walk stopped at `max_files`; totals are partial and no snapshot was saved.
+    pub truncated: bool,
+    pub largest_folders: Vec<FolderUsage>,
+    pub largest_files: Vec<FileUsage>,
+    pub extensions: Vec<ExtensionUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub growth: Option<Growth>,
+}
+
+/// Folder totals from the previous complete scan (compared only with the same roots and depth).
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    scanned_at_epoch_secs: i64,
+    roots: Vec<String>,
+    max_depth: usize,
+    total_bytes: u64,
+    folders: BTreeMap<String, u64>,
+}
+
+/// Per-folder and per-extension totals from one walk.
+#[derive(Default)]
+pub(crate) struct Usage {
+    /// Folder path -> (bytes, files), cumulative, down to `max_depth` below each root.
+    pub folders: HashMap<PathBuf, (u64, u64)>,
+    pub extensions: HashMap<String, (u64, u64)>,
+    pub total_bytes: u64,
+}
+
+impl Usage {
+    pub(crate) fn add(&mut self, root: &Path, path: &Path, bytes: u64, max_depth: usize) {
+        self.total_bytes += bytes;
+        if let Ok(rel) = path.parent().unwrap_or(root).strip_prefix(root) {
+            let mut dir = root.to_path_buf();
+            for part in rel.components().take(max_depth) {
+                dir.push(part);
+                let e = self.folders.entry(dir.clone()).or_default();
+                e.0 += bytes;
+                e.1 += 1;
+            }
+        }
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let e = self.extensions.entry(ext).or_default();
+        e.0 += bytes;
+        e.1 += 1;
+    }
+}
+
+/// MCP tool entrypoint (`silo_disk_report`).
+pub async fn disk_report_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: DiskReportArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = disk_report(state, &args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize disk report: {e}").into())
+}
+
+pub async fn disk_report(state: &SharedState, args: &DiskReportArgs) -> Result<DiskReport, ToolError> {
+    let Some(policy) = state.filesystem_policy().await else {
+        return Err(ToolError::no_filesystem_policy());
+    };
+    let roots = state.filesystem_roots().await;
+    let max_depth = args.max_depth.unwrap_or(3).clamp(1, 10);
+    let top_n = args.top_n.unwrap_or(20).clamp(1, 500);
+
+    let mut usage = Usage::default();
+    let mut largest: BinaryHeap<Reverse<FileUsage>> = BinaryHeap::new();
+    let walk = walk_files(
+        &roots,
+        &policy,
+        args.include_excluded,
+        args.max_files.unwrap_or(DEFAULT_MAX_FILES),
+        |root, path, meta| {
+            let bytes = meta.len();
+            usage.add(root, path, bytes, max_depth);
+            if largest.len() < top_n || largest.peek().is_some_and(|Reverse(min)| bytes > min.bytes) {
+                largest.push(Reverse(FileUsage {
+                    bytes,
+                    path: path.to_string_lossy().to_string(),
+                    modified_epoch_secs: modified_epoch_secs(meta),
+                }));
+                if largest.len() > top_n {
+                    largest.pop();
+                }
+            }
+        },
+    )
+    .await;
+
+    let roots: Vec<String> = roots.iter().map(|r| r.to_string_lossy().to_string()).collect();
+    let folders: BTreeMap<String, u64> = usage
+        .folders
+        .iter()
+        .map(|(p, (bytes, _))| (p.to_string_lossy().to_string(), *bytes))
+        .collect();
+    let snapshot_path = state.data_dir.join(SNAPSHOT_FILE);
+    let previous = load_snapshot(&snapshot_path)
+        .await
+        .filter(|s| s.version == SNAPSHOT_VERSION && s.roots == roots && s.max_depth == max_depth);
+
+    let folder_usage = |path: &str, bytes: u64, files: u64| FolderUsage {
+        path: path.to_string(),
+        bytes,
+        files,
+        growth_bytes: previous
+            .as_ref()
+            .map(|s| bytes as i64 - s.folders.get(path).copied().unwrap_or_default() as i64),
+    };
+
+    let mut largest_folders: Vec<FolderUsage> = usage
+        .folders
+        .iter()
+        .map(|(p, (bytes, files))| folder_usage(&p.to_string_lossy(), *bytes, *files))
+        .collect();
+    largest_folders.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+
+    let growth = previous.as_ref().map(|prev| {
+        let mut changed: Vec<FolderUsage> = largest_folders
+            .iter()
+            .filter(|f| f.growth_bytes.is_some_and(|g| g != 0))
+            .cloned()
+            .collect();
+        // Folders that disappeared since the last scan.
+        changed.extend(
+            prev.folders
+                .iter()
+                .filter(|(p, _)| !folders.contains_key(*p))
+                .map(|(p, bytes)| FolderUsage {
+                    path: p.clone(),
+                    bytes: 0,
+                    files: 0,
+                    growth_bytes: Some(-(*bytes as i64)),
+                }),
+        );
+        changed.sort_by_key(|f| Reverse(f.growth_bytes.unwrap_or_default().unsigned_abs()));
+        changed.truncate(top_n);
+        Growth {
+            previous_scan_epoch_secs: prev.scanned_at_epoch_secs,
+            total_bytes_delta: usage.total_bytes as i64 - prev.total_bytes as i64,
+            changed_folders: changed,
+        }
+    });
+    largest_folders.truncate(top_n);
+
+    let mut extensions: Vec<ExtensionUsage> = usage
+        .extensions
+        .into_iter()
+        .map(|(extension, (bytes, files))| ExtensionUsage { extension, bytes, files })
+        .collect();
+    extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.extension.cmp(&b.extension)));
+    extensions.truncate(top_n);
+
+    let mut largest_files: Vec<FileUsage> = largest.into_iter().map(|Reverse(f)| f).collect();
+    largest_files.sort_by(|a, b| b.cmp(a));
+
+    let scanned_at = now_epoch_secs();
+    if !walk.truncated {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            scanned_at_epoch_secs: scanned_at,
+            roots: roots.clone(),
+            max_depth,
+            total_bytes: usage.total_bytes,
+            folders,
+        };
+        if let Err(e) = save_snapshot(&snapshot_path, &snapshot).await {
+            tracing::warn!("{e}");
+        }
+    }
+
+    Ok(DiskReport {
+        roots,
+        scanned_at_epoch_secs: scanned_at,
+        total_bytes: usage.total_bytes,
+        files: walk.files,
+        dirs: walk.dirs,
+        skipped: walk.skipped,
+        truncated: walk.truncated,
+        largest_folders,
+        largest_files,
+        extensions,
+        growth,
+    })
+}
+
+async fn load_snapshot(path: &Path) -> Option<Snapshot> {
+    let s = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&s).ok()
+}
+
+async fn save_snapshot(path: &Path, snapshot: &Snapshot) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create data dir {}: {e}", parent.display()))?;
+    }
+    let s = serde_json::to_string(snapshot).map_err(|e| format!("Failed to serialize disk snapshot: {e}"))?;
+    tokio::fs::write(path, s)
+        .await
+        .map_err(|e| format!("Failed to write disk snapshot {}: {e}", path.display()))
+}
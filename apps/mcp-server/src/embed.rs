@@ -1,5 +1,11 @@
+use serde::Serialize;
 use std::sync::Arc;
 
+#[cfg(feature = "embeddings")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "embeddings")]
+use std::time::{Duration, Instant};
+
 pub const EMBEDDING_DIM: usize = 384;
 
 pub type EmbedderHandle = Arc<dyn Embedder + Send + Sync>;
@@ -24,6 +30,41 @@ pub trait Embedder {
         let mut out = self.embed_texts(vec![query]).await?;
         out.pop().ok_or_else(|| "embedder returned no vectors".to_string())
     }
+
+    /// Model name and load state (`None` for embedders without a model).
+    fn model_status(&self) -> Option<ModelStatus> {
+        None
+    }
+
+    /// Download (if needed) and load the model now instead of on first use.
+    async fn load(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Drop the loaded model (it is loaded again on next use). False when nothing was loaded.
+    fn unload(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelStatus {
+    pub model: String,
+    #[serde(flatten)]
+    pub state: ModelState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_unload_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ModelState {
+    Unloaded,
+    /// Downloading and/or loading (the first use after start or after an idle unload).
+    Loading { elapsed_ms: u64 },
+    Loaded { idle_secs: u64 },
+    /// The last load failed; the next use retries.
+    Failed { error: String },
 }
 
 pub struct NoopEmbedder;
@@ -39,21 +80,104 @@ impl Embedder for NoopEmbedder {
     }
 }
 
+#[cfg(feature = "embeddings")]
+pub const DEFAULT_MODEL: fastembed::EmbeddingModel = fastembed::EmbeddingModel::BGESmallENV15;
+
+/// fastembed model, downloaded and loaded on first use and dropped again after `idle_unload`.
 #[cfg(feature = "embeddings")]
 pub struct FastEmbedder {
-    model: Arc<fastembed::TextEmbedding>,
+    cache_dir: PathBuf,
+    idle_unload: Option<Duration>,
+    model: std::sync::Mutex<Option<Arc<fastembed::TextEmbedding>>>,
+    /// Serializes loads so concurrent first uses share one download.
+    loading: tokio::sync::Mutex<()>,
+    info: std::sync::Mutex<LoadInfo>,
+}
+
+#[cfg(feature = "embeddings")]
+#[derive(Default)]
+struct LoadInfo {
+    last_used: Option<Instant>,
+    loading_since: Option<Instant>,
+    error: Option<String>,
 }
 
 #[cfg(feature = "embeddings")]
 impl FastEmbedder {
-    pub fn try_new_default() -> Result<Self, String> {
-        // NOTE: fastembed API may differ slightly by version. If cargo reports a mismatch,
-        // we will adjust the initialization accordingly.
-        let opts = fastembed::InitOptions::new(fastembed::EmbeddingModel::BGESmallENV15);
-        let model = fastembed::TextEmbedding::try_new(opts).map_err(|e| format!("{e}"))?;
-        Ok(Self {
-            model: Arc::new(model),
-        })
+    /// Does not load anything yet; starts the idle-unload task when `idle_unload` is set.
+    pub fn new(cache_dir: PathBuf, idle_unload: Option<Duration>) -> Arc<Self> {
+        let embedder = Arc::new(Self {
+            cache_dir,
+            idle_unload,
+            model: std::sync::Mutex::new(None),
+            loading: tokio::sync::Mutex::new(()),
+            info: std::sync::Mutex::new(LoadInfo::default()),
+        });
+        if let Some(idle) = idle_unload {
+            let weak = Arc::downgrade(&embedder);
+            tokio::spawn(async move {
+                let tick = (idle / 4).clamp(Duration::from_secs(5), Duration::from_secs(60));
+                loop {
+                    tokio::time::sleep(tick).await;
+                    let Some(embedder) = weak.upgrade() else {
+                        break;
+                    };
+                    let idle_for = lock(&embedder.info).last_used.map(|t| t.elapsed());
+                    if idle_for.is_some_and(|d| d >= idle) && embedder.unload() {
+                        tracing::info!("Unloaded idle embedding model");
+                    }
+                }
+            });
+        }
+        embedder
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    async fn loaded_model(&self) -> Result<Arc<fastembed::TextEmbedding>, String> {
+        if let Some(m) = self.current() {
+            return Ok(m);
+        }
+        let _loading = self.loading.lock().await;
+        if let Some(m) = self.current() {
+            return Ok(m);
+        }
+
+        tracing::info!("Loading embedding model ({})", model_code(&DEFAULT_MODEL));
+        lock(&self.info).loading_since = Some(Instant::now());
+        let opts = fastembed::InitOptions::new(DEFAULT_MODEL)
+            .with_cache_dir(self.cache_dir.clone())
+            .with_show_download_progress(false);
+        let loaded = tokio::task::spawn_blocking(move || fastembed::TextEmbedding::try_new(opts))
+            .await
+            .map_err(|e| format!("model load task failed: {e}"))
+            .and_then(|r| r.map_err(|e| format!("Failed to load embedding model: {e}")));
+
+        let mut info = lock(&self.info);
+        info.loading_since = None;
+        match loaded {
+            Ok(model) => {
+                let model = Arc::new(model);
+                info.error = None;
+                info.last_used = Some(Instant::now());
+                *lock(&self.model) = Some(model.clone());
+                Ok(model)
+            }
+            Err(e) => {
+                info.error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    fn current(&self) -> Option<Arc<fastembed::TextEmbedding>> {
+        let model = lock(&self.model).clone();
+        if model.is_some() {
+            lock(&self.info).last_used = Some(Instant::now());
+        }
+        model
     }
 }
 
@@ -65,8 +189,8 @@ impl Embedder for FastEmbedder {
     }
 
     async fn embed_texts(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        let model = self.loaded_model().await?;
         // fastembed is CPU-bound; run in blocking pool.
-        let model = self.model.clone();
         tokio::task::spawn_blocking(move || {
             model
                 .embed(texts, None)
@@ -75,6 +199,49 @@ impl Embedder for FastEmbedder {
         .await
         .map_err(|e| format!("embed task failed: {e}"))?
     }
+
+    fn model_status(&self) -> Option<ModelStatus> {
+        let info = lock(&self.info);
+        let state = if let Some(since) = info.loading_since {
+            ModelState::Loading {
+                elapsed_ms: since.elapsed().as_millis() as u64,
+            }
+        } else if lock(&self.model).is_some() {
+            ModelState::Loaded {
+                idle_secs: info.last_used.map(|t| t.elapsed().as_secs()).unwrap_or(0),
+            }
+        } else if let Some(error) = &info.error {
+            ModelState::Failed { error: error.clone() }
+        } else {
+            ModelState::Unloaded
+        };
+        Some(ModelStatus {
+            model: model_code(&DEFAULT_MODEL),
+            state,
+            idle_unload_secs: self.idle_unload.map(|d| d.as_secs()),
+        })
+    }
+
+    async fn load(&self) -> Result<(), String> {
+        self.loaded_model().await.map(|_| ())
+    }
+
+    fn unload(&self) -> bool {
+        lock(&self.model).take().is_some()
+    }
 }
 
+/// Hugging Face repo of a fastembed model (e.g. "Xenova/bge-small-en-v1.5").
+#[cfg(feature = "embeddings")]
+pub fn model_code(model: &fastembed::EmbeddingModel) -> String {
+    fastembed::TextEmbedding::list_supported_models()
+        .into_iter()
+        .find(|m| &m.model == model)
+        .map(|m| m.model_code)
+        .unwrap_or_else(|| format!("{model:?}"))
+}
 
+#[cfg(feature = "embeddings")]
+fn lock<T>(m: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,86 @@
+//! Encrypted document detection: password-protected PDFs and Office files cannot be extracted, so
+//! the index walk skips them with a reason (`SkipReason::Encrypted`) instead of failing on them
+//! every run. A PDF with a stored password (`crate::passwords`) is indexed as usual.
+//!
+//! - PDFs: an `/Encrypt` entry in a trailer, looked for in the first and last `SCAN_BYTES`, then
+//!   `pdfinfo` to tell PDFs that need a password to open from ones that only restrict printing or
+//!   copying (those open without one and are not skipped).
+//! - Office Open XML (`docx`, `xlsx`, `pptx`, ...): normally a ZIP archive; an encrypted one is an
+//!   OLE compound file holding the encrypted package instead.
+//!
+//! Legacy binary Office files (`doc`, `xls`, `ppt`) are not checked.
+
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Bytes read at each end of a PDF for the trailer check.
+const SCAN_BYTES: u64 = 16 * 1024;
+/// Office Open XML extensions (lowercased).
+const OOXML_EXTENSIONS: &[&str] = &["docx", "docm", "xlsx", "xlsm", "xlsb", "pptx", "pptm"];
+/// Signature of OLE compound files.
+const CFB_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// What kind of encrypted document a file is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encrypted {
+    Pdf,
+    Office,
+}
+
+impl Encrypted {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Encrypted::Pdf => "encrypted PDF: needs a password (store one with silo_document_password_set)",
+            Encrypted::Office => "encrypted Office document (Office files are not decrypted)",
+        }
+    }
+}
+
+/// `path`'s encryption, by extension and then content.
+pub async fn detect(path: &Path) -> Option<Encrypted> {
+    let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    if ext == "pdf" {
+        return (has_encrypt_entry(path).await && crate::extract::pdf_needs_password(path).await)
+            .then_some(Encrypted::Pdf);
+    }
+    if OOXML_EXTENSIONS.contains(&ext.as_str()) {
+        let mut magic = [0u8; 8];
+        let mut file = tokio::fs::File::open(path).await.ok()?;
+        file.read_exact(&mut magic).await.ok()?;
+        return (magic == CFB_MAGIC).then_some(Encrypted::Office);
+    }
+    None
+}
+
+/// `detect`, except PDFs with a stored password: what the walk skips.
+pub async fn detect_locked(path: &Path) -> Option<Encrypted> {
+    let is_pdf = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    if is_pdf && crate::passwords::covering(path).is_some() {
+        return None;
+    }
+    detect(path).await
+}
+
+/// An `/Encrypt` key (not `/EncryptMetadata`) near either end of the file.
+async fn has_encrypt_entry(path: &Path) -> bool {
+    const KEY: &[u8] = b"/Encrypt";
+    let Ok(mut file) = tokio::fs::File::open(path).await else {
+        return false;
+    };
+    let Ok(len) = file.metadata().await.map(|m| m.len()) else {
+        return false;
+    };
+    let mut buf = Vec::with_capacity(2 * SCAN_BYTES as usize);
+    if (&mut file).take(SCAN_BYTES).read_to_end(&mut buf).await.is_err() {
+        return false;
+    }
+    if len > SCAN_BYTES {
+        let tail_start = len.saturating_sub(SCAN_BYTES).max(SCAN_BYTES);
+        if file.seek(SeekFrom::Start(tail_start)).await.is_err() || file.read_to_end(&mut buf).await.is_err() {
+            return false;
+        }
+    }
+    buf.windows(KEY.len() + 1)
+        .any(|w| w.starts_with(KEY) && !w[KEY.len()].is_ascii_alphanumeric())
+}
@@ -0,0 +1,450 @@
+//! Entity extraction (people, organizations, projects) for entity-centric search.
+//!
+//! Optional ingest stage, enabled with `entities.enabled` in the config. Mentions are stored per
+//! chunk in the entities table, so `silo_search_entities` can list everything involving
+//! "Acme Corp" and `silo_search` can be restricted to chunks that mention an entity.
+//!
+//! Heuristics (no model required):
+//! - organizations: capitalized names ending in a company suffix (`Acme Corp`, `Initech LLC`)
+//! - people: honorific + name (`Dr. Jane Smith`), or two/three capitalized words (`Jane Smith`)
+//! - projects: `Project <Name>`
+//!
+//! `entities.known` names (with aliases) are always matched, case-insensitively. With
+//! `entities.use_llm`, the local LLM replaces the name heuristics (slow: one call per chunk).
+
+use crate::config::EntityConfig;
+use crate::database::EntityRow;
+use crate::errors::ToolError;
+use crate::llm::LlmHandle;
+use crate::predicate::Predicate;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Person,
+    Org,
+    Project,
+    #[default]
+    Other,
+}
+
+impl EntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Person => "person",
+            EntityKind::Org => "org",
+            EntityKind::Project => "project",
+            EntityKind::Other => "other",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "person" | "people" => EntityKind::Person,
+            "org" | "organization" | "organisation" | "company" => EntityKind::Org,
+            "project" => EntityKind::Project,
+            _ => EntityKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityMention {
+    pub name: String,
+    pub kind: EntityKind,
+}
+
+/// Normalized lookup key: lowercase, single spaces, no surrounding punctuation.
+pub fn entity_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+pub struct EntityExtractor {
+    /// (lowercased needle, canonical name, kind)
+    known: Vec<(String, String, EntityKind)>,
+    llm: Option<LlmHandle>,
+}
+
+impl EntityExtractor {
+    /// `None` when the stage is disabled.
+    pub fn from_config(cfg: &EntityConfig, llm: &LlmHandle) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        let mut known = vec![];
+        for k in &cfg.known {
+            for needle in std::iter::once(&k.name).chain(k.aliases.iter()) {
+                let needle = entity_key(needle);
+                if !needle.is_empty() {
+                    known.push((needle, k.name.clone(), k.kind));
+                }
+            }
+        }
+        Some(Self {
+            known,
+            llm: cfg.use_llm.then(|| llm.clone()),
+        })
+    }
+
+    /// Entities mentioned in one chunk (deduplicated).
+    pub async fn extract(&self, text: &str) -> Vec<EntityMention> {
+        let mut out = self.match_known(text);
+        let found = match &self.llm {
+            Some(llm) => match extract_with_llm(llm, text).await {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::debug!("LLM entity extraction failed, using heuristics: {e}");
+                    extract_heuristic(text)
+                }
+            },
+            None => extract_heuristic(text),
+        };
+        out.extend(found);
+
+        let mut seen = HashSet::new();
+        out.retain(|m| seen.insert((entity_key(&m.name), m.kind)));
+        out
+    }
+
+    fn match_known(&self, text: &str) -> Vec<EntityMention> {
+        if self.known.is_empty() {
+            return vec![];
+        }
+        let haystack = format!(" {} ", normalize_for_match(text));
+        self.known
+            .iter()
+            .filter(|(needle, _, _)| haystack.contains(&format!(" {needle} ")))
+            .map(|(_, name, kind)| EntityMention {
+                name: name.clone(),
+                kind: *kind,
+            })
+            .collect()
+    }
+}
+
+/// Lowercase, punctuation -> spaces, single spaces (so known names match on word boundaries).
+fn normalize_for_match(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c == '&' { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+const ORG_SUFFIXES: &[&str] = &[
+    "inc", "corp", "corporation", "llc", "ltd", "limited", "gmbh", "ag", "sa", "plc", "co",
+    "company", "group", "labs", "foundation", "university", "institute", "bank",
+];
+
+const HONORIFICS: &[&str] = &["mr", "mrs", "ms", "dr", "prof", "sir"];
+
+/// Capitalized words that usually start a sentence or a date rather than a name.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "this", "that", "these", "those", "and", "or", "but", "if", "when", "while",
+    "in", "on", "at", "for", "from", "to", "of", "by", "with", "as", "we", "i", "you", "he",
+    "she", "it", "they", "our", "my", "your", "dear", "hi", "hello", "thanks", "regards", "re",
+    "fw", "fwd", "subject", "note", "please", "yes", "no", "monday", "tuesday", "wednesday",
+    "thursday", "friday", "saturday", "sunday", "january", "february", "march", "april", "may",
+    "june", "july", "august", "september", "october", "november", "december", "today",
+    "tomorrow", "yesterday",
+];
+
+fn extract_heuristic(text: &str) -> Vec<EntityMention> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < words.len() {
+        let w = clean_word(words[i]);
+        let lower = w.to_lowercase();
+
+        if lower == "project" && !ends_run(words[i]) {
+            let (run, next) = capitalized_run(&words, i + 1, 3);
+            if !run.is_empty() {
+                out.push(EntityMention {
+                    name: run.join(" "),
+                    kind: EntityKind::Project,
+                });
+                i = next;
+                continue;
+            }
+        }
+
+        if is_capitalized(w) && !STOPWORDS.contains(&lower.as_str()) {
+            let (run, next) = capitalized_run(&words, i, 5);
+            classify_run(&run, &mut out);
+            i = next.max(i + 1);
+            continue;
+        }
+        i += 1;
+    }
+    out
+}
+
+fn classify_run(run: &[&str], out: &mut Vec<EntityMention>) {
+    if run.len() < 2 {
+        return;
+    }
+    let last = run[run.len() - 1].trim_end_matches('.').to_lowercase();
+    let first = run[0].trim_end_matches('.').to_lowercase();
+    if ORG_SUFFIXES.contains(&last.as_str()) {
+        out.push(EntityMention {
+            name: run.join(" "),
+            kind: EntityKind::Org,
+        });
+    } else if HONORIFICS.contains(&first.as_str()) {
+        out.push(EntityMention {
+            name: run[1..].join(" "),
+            kind: EntityKind::Person,
+        });
+    } else if run.len() <= 3 && run.iter().all(|w| looks_like_name(w)) {
+        out.push(EntityMention {
+            name: run.join(" "),
+            kind: EntityKind::Person,
+        });
+    }
+}
+
+/// Consecutive capitalized words starting at `start` (at most `max`), stopping after a word
+/// that ends a phrase (`Smith,`). Returns the cleaned words and the next index to scan.
+fn capitalized_run<'a>(words: &[&'a str], start: usize, max: usize) -> (Vec<&'a str>, usize) {
+    let mut run = vec![];
+    let mut i = start;
+    while i < words.len() && run.len() < max {
+        let w = clean_word(words[i]);
+        if !is_capitalized(w) {
+            break;
+        }
+        run.push(w);
+        i += 1;
+        let raw = words[i - 1];
+        let abbreviation = HONORIFICS.contains(&w.trim_end_matches('.').to_lowercase().as_str());
+        if ends_run(raw) && !abbreviation {
+            break;
+        }
+    }
+    (run, i)
+}
+
+/// Strip surrounding quotes/brackets/punctuation, keeping a trailing `.` on abbreviations.
+fn clean_word(w: &str) -> &str {
+    let w = w.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let trimmed = w.trim_end_matches(|c: char| !c.is_alphanumeric());
+    let lower = trimmed.to_lowercase();
+    if w.len() > trimmed.len()
+        && w[trimmed.len()..].starts_with('.')
+        && (HONORIFICS.contains(&lower.as_str()) || ORG_SUFFIXES.contains(&lower.as_str()))
+    {
+        return &w[..trimmed.len() + 1];
+    }
+    trimmed
+}
+
+fn ends_run(raw: &str) -> bool {
+    raw.ends_with(['.', ',', ';', ':', '!', '?', ')', '"', '\''])
+}
+
+fn is_capitalized(w: &str) -> bool {
+    let mut chars = w.chars();
+    matches!(chars.next(), Some(c) if c.is_uppercase()) && w.chars().count() >= 2
+}
+
+/// `Jane`, `O'Brien`, `Smith-Jones`: capitalized, mostly letters, not all caps.
+fn looks_like_name(w: &str) -> bool {
+    is_capitalized(w)
+        && w.chars().all(|c| c.is_alphabetic() || c == '\'' || c == '-')
+        && w.chars().skip(1).any(|c| c.is_lowercase())
+        && !STOPWORDS.contains(&w.to_lowercase().as_str())
+}
+
+const LLM_ENTITY_PROMPT: &str = r#"List the named people, organizations and projects mentioned in the text below.
+Respond with a JSON array only, e.g. [{"name":"Acme Corp","kind":"org"}]. Allowed kinds: person, org, project.
+Return [] if there are none.
+
+Text:
+"#;
+
+async fn extract_with_llm(llm: &LlmHandle, text: &str) -> Result<Vec<EntityMention>, String> {
+    let raw = llm.generate(format!("{LLM_ENTITY_PROMPT}{text}\n\nJSON:")).await?;
+    let (Some(start), Some(end)) = (raw.find('['), raw.rfind(']')) else {
+        return Err("LLM did not return a JSON array".to_string());
+    };
+    if end < start {
+        return Err("LLM did not return a JSON array".to_string());
+    }
+    let items: Vec<Value> =
+        serde_json::from_str(&raw[start..=end]).map_err(|e| format!("LLM returned invalid JSON: {e}"))?;
+    Ok(items
+        .iter()
+        .filter_map(|v| {
+            let name = v.get("name")?.as_str()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some(EntityMention {
+                name: name.to_string(),
+                kind: EntityKind::parse(v.get("kind").and_then(Value::as_str).unwrap_or("")),
+            })
+        })
+        .collect())
+}
+
+// --- Search ---
+
+/// Upper bound on mention rows scanned per entity query.
+const MAX_ENTITY_ROWS: usize = 5000;
+/// Chunks passed to the vector search filter for `silo_search --entity`.
+const MAX_ENTITY_FILTER_CHUNKS: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+struct SearchEntitiesArgs {
+    query: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityFile {
+    pub path: String,
+    pub chunk_indexes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntitySummary {
+    pub name: String,
+    pub kind: EntityKind,
+    pub mentions: usize,
+    pub file_count: usize,
+    /// Files mentioning the entity, most mentions first (capped).
+    pub files: Vec<EntityFile>,
+}
+
+/// MCP tool entrypoint (`silo_search_entities`).
+pub async fn search_entities_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: SearchEntitiesArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let kind = args.kind.as_deref().map(EntityKind::parse);
+    let found = search_entities(state, &args.query, kind, args.limit.unwrap_or(20).clamp(1, 200)).await?;
+    Ok(serde_json::json!({ "entities": found }))
+}
+
+/// Mentions of one entity, grouped by (key, kind): display name counts, and chunk indexes per path.
+type EntityGroup = (BTreeMap<String, usize>, BTreeMap<String, Vec<usize>>);
+
+/// Entities whose name contains `query` (case-insensitive), exact matches first, then by mentions.
+pub async fn search_entities(
+    state: &SharedState,
+    query: &str,
+    kind: Option<EntityKind>,
+    limit: usize,
+) -> Result<Vec<EntitySummary>, ToolError> {
+    ensure_db(state)?;
+    let key = entity_key(query);
+    if key.is_empty() {
+        return Err("query must not be empty".into());
+    }
+    let mut filter = Predicate::contains("key", &key);
+    if let Some(kind) = kind {
+        filter = filter.and(Predicate::eq("kind", kind.as_str()));
+    }
+    let rows = state
+        .db
+        .find_entities(&filter, MAX_ENTITY_ROWS)
+        .await
+        .map_err(ToolError::db_query_failed)?;
+
+    let mut groups: BTreeMap<(String, String), EntityGroup> = BTreeMap::new();
+    for r in rows {
+        let g = groups.entry((r.key.clone(), r.kind.clone())).or_default();
+        *g.0.entry(r.name).or_default() += 1;
+        g.1.entry(r.path).or_default().push(r.chunk_index);
+    }
+
+    let mut out: Vec<(bool, EntitySummary)> = groups
+        .into_iter()
+        .map(|((k, kind), (names, files))| {
+            let name = names
+                .into_iter()
+                .max_by_key(|(_, n)| *n)
+                .map(|(name, _)| name)
+                .unwrap_or_else(|| k.clone());
+            let mentions = files.values().map(Vec::len).sum();
+            let file_count = files.len();
+            let mut files: Vec<EntityFile> = files
+                .into_iter()
+                .map(|(path, mut chunk_indexes)| {
+                    chunk_indexes.sort_unstable();
+                    chunk_indexes.dedup();
+                    EntityFile { path, chunk_indexes }
+                })
+                .collect();
+            files.sort_by_key(|f| std::cmp::Reverse(f.chunk_indexes.len()));
+            files.truncate(20);
+            (
+                k == key,
+                EntitySummary {
+                    name,
+                    kind: EntityKind::parse(&kind),
+                    mentions,
+                    file_count,
+                    files,
+                },
+            )
+        })
+        .collect();
+    out.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.mentions.cmp(&a.1.mentions)));
+    out.truncate(limit);
+    Ok(out.into_iter().map(|(_, s)| s).collect())
+}
+
+/// Search filter restricting hits to chunks that mention `entity` (exact normalized name).
+/// Returns `None` when no chunk mentions it.
+pub async fn entity_search_filter(state: &SharedState, entity: &str) -> Result<Option<Predicate>, ToolError> {
+    let rows = state
+        .db
+        .find_entities(&Predicate::eq("key", entity_key(entity)), MAX_ENTITY_ROWS)
+        .await
+        .map_err(ToolError::db_query_failed)?;
+    let mut ids: Vec<String> = rows.into_iter().map(|r| r.chunk_id).collect();
+    ids.sort();
+    ids.dedup();
+    ids.truncate(MAX_ENTITY_FILTER_CHUNKS);
+    if ids.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Predicate::in_list("id", ids)))
+}
+
+/// Build mention rows for one chunk (used by ingest).
+pub fn entity_rows(chunk_id: &str, path: &str, chunk_index: usize, mentions: Vec<EntityMention>) -> Vec<EntityRow> {
+    mentions
+        .into_iter()
+        .map(|m| EntityRow {
+            chunk_id: chunk_id.to_string(),
+            path: path.to_string(),
+            chunk_index,
+            key: entity_key(&m.name),
+            name: m.name,
+            kind: m.kind.as_str().to_string(),
+        })
+        .filter(|r| !r.key.is_empty())
+        .collect()
+}
+
+fn ensure_db(state: &SharedState) -> Result<(), ToolError> {
+    if state.db.is_enabled() {
+        return Ok(());
+    }
+    let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+    Err(ToolError::kb_disabled(reason))
+}
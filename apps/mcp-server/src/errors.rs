@@ -0,0 +1,246 @@
+//! Tool errors with a machine-readable code.
+//!
+//! Most failures are plain English text (`String`, converted with `From`). The ones a client may
+//! want to act on are raised with an `ErrorCode` and the values of their message, so the server
+//! reports the code (`_meta["silo/errorCode"]`) and translates the message from it
+//! (`crate::i18n`, key `error.<code>`) without looking at the text. Codes are the same in every
+//! locale.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidArguments,
+    KbDisabled,
+    NoFilesystemPolicy,
+    DbQueryFailed,
+    DbSearchFailed,
+    EmbeddingFailed,
+    UnknownTool,
+    ReadOnly,
+    LockedCollection,
+    ConfigConflict,
+    OutsideSandbox,
+    EmptyPath,
+    ParentDirPath,
+    FiltersOnlyQuery,
+    UnknownSettings,
+    ReadFailed,
+}
+
+impl ErrorCode {
+    pub const ALL: &[ErrorCode] = &[
+        Self::InvalidArguments,
+        Self::KbDisabled,
+        Self::NoFilesystemPolicy,
+        Self::DbQueryFailed,
+        Self::DbSearchFailed,
+        Self::EmbeddingFailed,
+        Self::UnknownTool,
+        Self::ReadOnly,
+        Self::LockedCollection,
+        Self::ConfigConflict,
+        Self::OutsideSandbox,
+        Self::EmptyPath,
+        Self::ParentDirPath,
+        Self::FiltersOnlyQuery,
+        Self::UnknownSettings,
+        Self::ReadFailed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidArguments => "invalid_arguments",
+            Self::KbDisabled => "kb_disabled",
+            Self::NoFilesystemPolicy => "no_filesystem_policy",
+            Self::DbQueryFailed => "db_query_failed",
+            Self::DbSearchFailed => "db_search_failed",
+            Self::EmbeddingFailed => "embedding_failed",
+            Self::UnknownTool => "unknown_tool",
+            Self::ReadOnly => "read_only",
+            Self::LockedCollection => "locked_collection",
+            Self::ConfigConflict => "config_conflict",
+            Self::OutsideSandbox => "outside_sandbox",
+            Self::EmptyPath => "empty_path",
+            Self::ParentDirPath => "parent_dir_path",
+            Self::FiltersOnlyQuery => "filters_only_query",
+            Self::UnknownSettings => "unknown_settings",
+            Self::ReadFailed => "read_failed",
+        }
+    }
+
+    /// English message, with `{name}` placeholders for the values.
+    pub fn template(self) -> &'static str {
+        match self {
+            Self::InvalidArguments => "Invalid arguments: {detail}",
+            Self::KbDisabled => "Knowledge base is disabled: {reason}",
+            Self::NoFilesystemPolicy => "No filesystem policy configured",
+            Self::DbQueryFailed => "DB query failed: {detail}",
+            Self::DbSearchFailed => "DB search failed: {detail}",
+            Self::EmbeddingFailed => "Embedding failed: {detail}",
+            Self::UnknownTool => "Unknown tool: {tool}",
+            Self::ReadOnly => "{tool} is disabled: this Silo server is read-only",
+            Self::LockedCollection => {
+                "{path} is in the locked collection \"{collection}\". Ask the user to unlock it (silo_unlock_collection) first."
+            }
+            Self::ConfigConflict => {
+                "Config changed since revision {expected} (now {revision}); re-read it with silo_get_config and retry"
+            }
+            Self::OutsideSandbox => {
+                "{path} is outside the folders this session may access. Grant its folder to the session (silo_grant_temp_root) first."
+            }
+            Self::EmptyPath => "Path must not be empty",
+            Self::ParentDirPath => "Path must not contain '..'",
+            Self::FiltersOnlyQuery => "The query has only filters or excluded terms; add words to search for",
+            Self::UnknownSettings => "Unknown settings: {settings}",
+            Self::ReadFailed => "Failed to read {path}: {detail}",
+        }
+    }
+}
+
+/// A failed tool call: the English message, and its code and values when it has one.
+#[derive(Debug, Clone)]
+pub struct ToolError {
+    pub code: Option<ErrorCode>,
+    pub args: Vec<(&'static str, String)>,
+    pub message: String,
+}
+
+impl ToolError {
+    pub fn new(code: ErrorCode, args: Vec<(&'static str, String)>) -> Self {
+        let values: Vec<(&str, &str)> = args.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        Self {
+            code: Some(code),
+            message: crate::i18n::fill(code.template(), &values),
+            args,
+        }
+    }
+
+    pub fn invalid_arguments(detail: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::InvalidArguments, vec![("detail", detail.to_string())])
+    }
+
+    pub fn kb_disabled(reason: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::KbDisabled, vec![("reason", reason.to_string())])
+    }
+
+    pub fn no_filesystem_policy() -> Self {
+        Self::new(ErrorCode::NoFilesystemPolicy, vec![])
+    }
+
+    pub fn db_query_failed(detail: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::DbQueryFailed, vec![("detail", detail.to_string())])
+    }
+
+    pub fn db_search_failed(detail: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::DbSearchFailed, vec![("detail", detail.to_string())])
+    }
+
+    pub fn embedding_failed(detail: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::EmbeddingFailed, vec![("detail", detail.to_string())])
+    }
+
+    pub fn unknown_tool(tool: &str) -> Self {
+        Self::new(ErrorCode::UnknownTool, vec![("tool", tool.to_string())])
+    }
+
+    pub fn read_only(tool: &str) -> Self {
+        Self::new(ErrorCode::ReadOnly, vec![("tool", tool.to_string())])
+    }
+
+    pub fn locked_collection(path: &str, collection: &str) -> Self {
+        Self::new(
+            ErrorCode::LockedCollection,
+            vec![("path", path.to_string()), ("collection", collection.to_string())],
+        )
+    }
+
+    pub fn config_conflict(expected: &str, revision: &str) -> Self {
+        Self::new(
+            ErrorCode::ConfigConflict,
+            vec![("expected", expected.to_string()), ("revision", revision.to_string())],
+        )
+    }
+
+    pub fn outside_sandbox(path: &str) -> Self {
+        Self::new(ErrorCode::OutsideSandbox, vec![("path", path.to_string())])
+    }
+
+    pub fn empty_path() -> Self {
+        Self::new(ErrorCode::EmptyPath, vec![])
+    }
+
+    pub fn parent_dir_path() -> Self {
+        Self::new(ErrorCode::ParentDirPath, vec![])
+    }
+
+    pub fn filters_only_query() -> Self {
+        Self::new(ErrorCode::FiltersOnlyQuery, vec![])
+    }
+
+    pub fn unknown_settings(settings: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::UnknownSettings, vec![("settings", settings.to_string())])
+    }
+
+    pub fn read_failed(path: impl fmt::Display, detail: impl fmt::Display) -> Self {
+        Self::new(
+            ErrorCode::ReadFailed,
+            vec![("path", path.to_string()), ("detail", detail.to_string())],
+        )
+    }
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for ToolError {
+    fn from(message: String) -> Self {
+        Self {
+            code: None,
+            args: vec![],
+            message,
+        }
+    }
+}
+
+impl From<&str> for ToolError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<ToolError> for String {
+    fn from(e: ToolError) -> Self {
+        e.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_is_the_filled_template() {
+        let e = ToolError::locked_collection("/a/b.txt", "work");
+        assert_eq!(e.code, Some(ErrorCode::LockedCollection));
+        assert_eq!(
+            e.message,
+            "/a/b.txt is in the locked collection \"work\". Ask the user to unlock it (silo_unlock_collection) first."
+        );
+        assert_eq!(ToolError::from("oops".to_string()).code, None);
+    }
+
+    #[test]
+    fn built_in_catalogs_translate_every_code() {
+        for lang in ["de", "es", "fr"] {
+            let catalog = crate::i18n::Catalog::load(lang, std::path::Path::new("/nonexistent"));
+            for code in ErrorCode::ALL {
+                let e = ToolError::new(*code, vec![]);
+                assert_ne!(catalog.localize_error(&e), e.message, "{lang} lacks error.{}", code.as_str());
+            }
+        }
+    }
+}
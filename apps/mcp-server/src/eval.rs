@@ -0,0 +1,380 @@
+//! Retrieval evals (`silo_eval_run`): measure search quality on a suite of known queries instead
+//! of guessing whether a pipeline change helped.
+//!
+//! A suite is a YAML file of cases (a query and the files a good search should find) and
+//! variants (search settings to compare):
+//!
+//! ```yaml
+//! k: [1, 5, 10]
+//! corpus: ["~/notes"]          # files re-chunked by variants with chunk settings
+//! cases:
+//!   - query: "quarterly tax estimate"
+//!     expected: ["~/finance/2024-q3-taxes.pdf"]
+//! variants:
+//!   - name: index              # the live index, as built
+//!   - name: recent
+//!     prefer_recent: true
+//!   - name: small-chunks
+//!     chunk_tokens: 250
+//!     chunk_overlap_tokens: 25
+//! ```
+//!
+//! A variant without chunk settings searches the live index. A variant with any of them
+//! re-extracts, re-chunks and re-embeds the corpus (default: every expected file) into a
+//! throwaway in-memory store, the way `silo_search_paths` does, so chunking can be compared
+//! without re-indexing. Silo runs one embedding model and pure vector ranking, so there is no
+//! model or hybrid-weight setting; unknown variant fields are rejected rather than ignored.
+//!
+//! For each variant the report has recall@k (share of a case's expected files in the top k
+//! distinct files, averaged over cases) for each k, MRR (mean of 1 / rank of the first expected
+//! file, 0 when none is found) and each case's ranks. Nothing is recorded as access, cached, or
+//! written to the index. Locked collections are excluded as in every search.
+
+use crate::chunk::{ChunkOptions, ChunkRules};
+use crate::config::ChunkBoundaries;
+use crate::database::{Database, DatabaseHandle, SearchHit, SearchOptions};
+use crate::errors::ToolError;
+use crate::search_paths::{SkippedPath, MAX_FILES};
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const DEFAULT_K: &[usize] = &[1, 5, 10];
+const MAX_K: usize = 50;
+const MAX_CASES: usize = 500;
+const MAX_VARIANTS: usize = 10;
+/// Chunks fetched per query; enough distinct files for recall at `MAX_K` in most corpora.
+const SEARCH_DEPTH: usize = 150;
+const MIN_CHUNK_TOKENS: usize = 16;
+
+#[derive(Debug, Deserialize)]
+pub struct EvalRunArgs {
+    /// Path to a suite YAML file.
+    #[serde(default)]
+    pub suite: Option<String>,
+    /// The suite itself, inline.
+    #[serde(default)]
+    pub yaml: Option<String>,
+    /// Only run these variants (by name).
+    #[serde(default)]
+    pub variants: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalSuite {
+    #[serde(default)]
+    pub k: Vec<usize>,
+    /// Files, folders or globs re-chunked by variants with chunk settings.
+    #[serde(default)]
+    pub corpus: Vec<String>,
+    pub cases: Vec<EvalCase>,
+    /// Default: one variant over the live index.
+    #[serde(default)]
+    pub variants: Vec<EvalVariant>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCase {
+    pub query: String,
+    pub expected: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalVariant {
+    pub name: String,
+    #[serde(default)]
+    pub chunk_tokens: Option<usize>,
+    #[serde(default)]
+    pub chunk_overlap_tokens: Option<usize>,
+    #[serde(default)]
+    pub chunk_boundaries: Option<ChunkBoundaries>,
+    #[serde(default)]
+    pub prefer_recent: bool,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+}
+
+impl EvalVariant {
+    fn rechunks(&self) -> bool {
+        self.chunk_tokens.is_some() || self.chunk_overlap_tokens.is_some() || self.chunk_boundaries.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub query: String,
+    /// 1-based rank of the first expected file among distinct result files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_rank: Option<usize>,
+    /// Rank of each expected file (`null` when not in the results).
+    pub ranks: BTreeMap<String, Option<usize>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantResult {
+    pub name: String,
+    /// `index` or `corpus`.
+    pub source: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking: Option<ChunkOptions>,
+    pub prefer_recent: bool,
+    /// recall@k per k.
+    pub recall: BTreeMap<usize, f32>,
+    pub mrr: f32,
+    pub cases: Vec<CaseResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corpus_files: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corpus_chunks: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedPath>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub cases: usize,
+    pub k: Vec<usize>,
+    pub variants: Vec<VariantResult>,
+}
+
+/// MCP tool entrypoint (`silo_eval_run`).
+pub async fn eval_run_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: EvalRunArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = eval_run(state, args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize eval report: {e}").into())
+}
+
+pub async fn eval_run(state: &SharedState, args: EvalRunArgs) -> Result<EvalReport, ToolError> {
+    let yaml = match (args.suite, args.yaml) {
+        (Some(path), None) => {
+            crate::sandbox::check_path(state, &path).await?;
+            let path = expand_tilde(&path);
+            crate::tools::validate_safe_path(&path)?;
+            tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|e| ToolError::read_failed(path.display(), e))?
+        }
+        (None, Some(yaml)) => yaml,
+        _ => return Err("pass exactly one of `suite` (a file) or `yaml` (inline)".into()),
+    };
+    let mut suite: EvalSuite = serde_yaml_ng::from_str(&yaml).map_err(|e| format!("Invalid eval suite: {e}"))?;
+    validate(&mut suite)?;
+    if !args.variants.is_empty() {
+        suite.variants.retain(|v| args.variants.contains(&v.name));
+        if suite.variants.is_empty() {
+            return Err("none of the requested `variants` are in the suite".into());
+        }
+    }
+
+    let mut queries = Vec::with_capacity(suite.cases.len());
+    for case in &suite.cases {
+        let qvec = state
+            .embed_query_cached(&case.query)
+            .await
+            .map_err(ToolError::embedding_failed)?;
+        queries.push(qvec);
+    }
+
+    let mut variants = vec![];
+    for variant in &suite.variants {
+        variants.push(run_variant(state, &suite, variant, &queries).await?);
+    }
+    Ok(EvalReport {
+        cases: suite.cases.len(),
+        k: suite.k,
+        variants,
+    })
+}
+
+/// Check limits, fill defaults and normalize expected paths.
+fn validate(suite: &mut EvalSuite) -> Result<(), String> {
+    if suite.cases.is_empty() {
+        return Err("the suite has no `cases`".to_string());
+    }
+    if suite.cases.len() > MAX_CASES {
+        return Err(format!("the suite has {} cases (max {MAX_CASES})", suite.cases.len()));
+    }
+    for case in &mut suite.cases {
+        if case.query.trim().is_empty() {
+            return Err("every case needs a non-empty `query`".to_string());
+        }
+        if case.expected.is_empty() {
+            return Err(format!("case `{}` has no `expected` paths", case.query));
+        }
+        for path in &mut case.expected {
+            *path = expand_tilde(path).to_string_lossy().to_string();
+        }
+    }
+    if suite.k.is_empty() {
+        suite.k = DEFAULT_K.to_vec();
+    }
+    if suite.k.iter().any(|&k| k == 0 || k > MAX_K) {
+        return Err(format!("`k` values must be between 1 and {MAX_K}"));
+    }
+    suite.k.sort_unstable();
+    suite.k.dedup();
+
+    if suite.variants.is_empty() {
+        suite.variants.push(EvalVariant {
+            name: "index".to_string(),
+            ..EvalVariant::default()
+        });
+    }
+    if suite.variants.len() > MAX_VARIANTS {
+        return Err(format!("the suite has {} variants (max {MAX_VARIANTS})", suite.variants.len()));
+    }
+    for (i, v) in suite.variants.iter().enumerate() {
+        if suite.variants[..i].iter().any(|other| other.name == v.name) {
+            return Err(format!("variant name `{}` is used twice", v.name));
+        }
+    }
+    Ok(())
+}
+
+async fn run_variant(
+    state: &SharedState,
+    suite: &EvalSuite,
+    variant: &EvalVariant,
+    queries: &[Vec<f32>],
+) -> Result<VariantResult, ToolError> {
+    let started = std::time::Instant::now();
+    let mut result = VariantResult {
+        name: variant.name.clone(),
+        source: "index",
+        chunking: None,
+        prefer_recent: variant.prefer_recent,
+        recall: BTreeMap::new(),
+        mrr: 0.0,
+        cases: vec![],
+        corpus_files: None,
+        corpus_chunks: None,
+        skipped: vec![],
+        elapsed_ms: 0,
+    };
+
+    let db = if variant.rechunks() {
+        let (db, chunking) = build_corpus(state, suite, variant, &mut result).await?;
+        result.source = "corpus";
+        result.chunking = Some(chunking);
+        db
+    } else {
+        if !state.db.is_enabled() {
+            return Err(state.db.disabled_reason().unwrap_or("DB disabled").to_string().into());
+        }
+        state.db.clone()
+    };
+    let filter = crate::access::locked_paths(state).await.search_filter();
+
+    let mut recall_sums: BTreeMap<usize, f32> = suite.k.iter().map(|&k| (k, 0.0)).collect();
+    let mut rr_sum = 0.0f32;
+    for (case, qvec) in suite.cases.iter().zip(queries) {
+        let opts = SearchOptions {
+            top_k: SEARCH_DEPTH,
+            min_score: variant.min_score,
+            filter: filter.clone(),
+        };
+        let mut hits = db
+            .search_chunks_by_vector(qvec, &opts)
+            .await
+            .map_err(|e| format!("Search failed: {e}"))?;
+        // As `silo_search` with all fields.
+        let terms = crate::fields::query_terms(&case.query);
+        crate::tools::rerank_hits(state, &mut hits, &terms, variant.prefer_recent).await;
+        let files = distinct_paths(&hits);
+
+        let ranks: BTreeMap<String, Option<usize>> = case
+            .expected
+            .iter()
+            .map(|p| (p.clone(), files.iter().position(|f| f == p).map(|i| i + 1)))
+            .collect();
+        let first_rank = ranks.values().flatten().min().copied();
+        rr_sum += first_rank.map_or(0.0, |r| 1.0 / r as f32);
+        for (&k, sum) in recall_sums.iter_mut() {
+            let found = ranks.values().flatten().filter(|&&r| r <= k).count();
+            *sum += found as f32 / ranks.len() as f32;
+        }
+        result.cases.push(CaseResult {
+            query: case.query.clone(),
+            first_rank,
+            ranks,
+        });
+    }
+
+    let n = suite.cases.len() as f32;
+    result.recall = recall_sums.into_iter().map(|(k, sum)| (k, sum / n)).collect();
+    result.mrr = rr_sum / n;
+    result.elapsed_ms = started.elapsed().as_millis() as u64;
+    Ok(result)
+}
+
+/// Embed the corpus into a throwaway store with the variant's chunking (unset fields come from
+/// the filesystem config).
+async fn build_corpus(
+    state: &SharedState,
+    suite: &EvalSuite,
+    variant: &EvalVariant,
+    result: &mut VariantResult,
+) -> Result<(DatabaseHandle, ChunkOptions), ToolError> {
+    let policy = state
+        .filesystem_policy()
+        .await
+        .ok_or_else(ToolError::no_filesystem_policy)?;
+    let rules = state
+        .filesystem_config()
+        .await
+        .ok_or_else(|| "No filesystem source configured".to_string())?
+        .chunking();
+    let base = rules.default;
+    let tokens = variant.chunk_tokens.unwrap_or(base.tokens);
+    if tokens < MIN_CHUNK_TOKENS {
+        return Err(format!("variant `{}`: chunk_tokens must be at least {MIN_CHUNK_TOKENS}", variant.name).into());
+    }
+    let chunking = ChunkOptions {
+        tokens,
+        overlap_tokens: variant.chunk_overlap_tokens.unwrap_or(base.overlap_tokens).min(tokens / 2),
+        boundaries: variant.chunk_boundaries.unwrap_or(base.boundaries),
+    };
+
+    let corpus: Vec<String> = if suite.corpus.is_empty() {
+        let mut paths: Vec<String> = suite.cases.iter().flat_map(|c| c.expected.iter().cloned()).collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    } else {
+        suite.corpus.clone()
+    };
+    let (files, truncated): (Vec<PathBuf>, bool) =
+        crate::search_paths::expand(&corpus, &policy, MAX_FILES, &mut result.skipped).await;
+    if truncated {
+        tracing::warn!("silo_eval_run: corpus cut at {MAX_FILES} files");
+    }
+    let db: DatabaseHandle = Arc::new(Database::memory());
+    // The variant's chunking applies to every file, whatever `extension_overrides` says; the
+    // per-file chunk cap still holds.
+    let rules = ChunkRules {
+        cap: rules.cap,
+        ..ChunkRules::from(chunking)
+    };
+    let tally = crate::search_paths::ingest_files(state, &db, &files, &policy, &rules, &mut result.skipped).await;
+    result.corpus_files = Some(tally.files);
+    result.corpus_chunks = Some(tally.chunks);
+    Ok((db, chunking))
+}
+
+/// Result files in rank order, each once.
+fn distinct_paths(hits: &[SearchHit]) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    for hit in hits {
+        if !out.contains(&hit.path) {
+            out.push(hit.path.clone());
+        }
+    }
+    out
+}
@@ -1,10 +1,66 @@
+//! Text extraction: plain text is read in-process (bounded by `max_text_bytes`); PDFs and images
+//! go through `pdftotext` / `tesseract` child processes, so a malformed document can only take
+//! down its own extractor. Those run under `ExtractLimits`: killed after `timeout_secs`, and with
+//! their address space capped at `max_memory_mb` (via `ulimit -v`; enforced on Linux).
+
+use std::ffi::OsStr;
 use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
 
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+pub const DEFAULT_MAX_MEMORY_MB: u64 = 2048;
+
+/// Limits for extractor processes (`extract_timeout_secs` / `extract_max_memory_mb` in the
+/// filesystem source config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    pub timeout_secs: u64,
+    /// 0 = no cap.
+    pub max_memory_mb: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        DEFAULT_LIMITS
+    }
+}
+
+const DEFAULT_LIMITS: ExtractLimits = ExtractLimits {
+    timeout_secs: DEFAULT_TIMEOUT_SECS,
+    max_memory_mb: DEFAULT_MAX_MEMORY_MB,
+};
+
+/// Process-wide, so every caller of `extract_text` gets them without threading config through.
+static LIMITS: RwLock<ExtractLimits> = RwLock::new(DEFAULT_LIMITS);
+
+/// Apply new limits (on startup and whenever the config is reloaded).
+pub fn set_limits(limits: ExtractLimits) {
+    *LIMITS.write().unwrap_or_else(|e| e.into_inner()) = limits;
+}
+
+fn limits() -> ExtractLimits {
+    *LIMITS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Extensions extracted with OCR.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp"];
+
 #[derive(Debug, Clone)]
 pub enum ExtractKind {
     Text,
     Pdf,
+    /// Images are OCR'd (screenshots, scans).
+    Image,
+    /// Web pages (`crate::web`), reduced to their text by `html_to_text`. HTML files on disk are
+    /// indexed as text: they are as often templates as documents.
+    Html,
+    /// Log files (`crate::logs`): plain text, chunked along the lines' timestamps.
+    Log,
+    /// JSON / JSONL flattened into `key.path: value` lines (`crate::json_flatten`).
+    Json,
     Unknown,
 }
 
@@ -13,13 +69,31 @@ pub struct ExtractResult {
     pub kind: ExtractKind,
     pub text: String,
     pub truncated: bool,
+    /// Whitespace-token index where each page starts (PDFs only; empty otherwise).
+    pub page_starts: Vec<usize>,
+}
+
+impl ExtractResult {
+    /// 1-based page containing whitespace token `token` (`None` without page info).
+    pub fn page_of(&self, token: usize) -> Option<i64> {
+        if self.page_starts.is_empty() {
+            return None;
+        }
+        Some(self.page_starts.partition_point(|&s| s <= token).max(1) as i64)
+    }
 }
 
 pub async fn extract_text(path: &Path, max_text_bytes: u64) -> Result<ExtractResult, String> {
     let kind = detect_kind(path);
     match kind {
         ExtractKind::Pdf => extract_pdf_pdftotext(path, max_text_bytes).await,
+        ExtractKind::Image => extract_image_tesseract(path, max_text_bytes).await,
         ExtractKind::Text => extract_plain_text(path, max_text_bytes).await,
+        ExtractKind::Html => extract_html(path, max_text_bytes).await.map(|(_, r)| r),
+        ExtractKind::Log | ExtractKind::Json => {
+            let raw = extract_plain_text(path, max_text_bytes).await?;
+            Ok(text_as(path, raw.text, raw.truncated))
+        }
         ExtractKind::Unknown => {
             // Still try as plain text; caller can choose to gate by extension.
             extract_plain_text(path, max_text_bytes).await
@@ -27,21 +101,31 @@ pub async fn extract_text(path: &Path, max_text_bytes: u64) -> Result<ExtractRes
     }
 }
 
-fn detect_kind(path: &Path) -> ExtractKind {
+pub(crate) fn detect_kind(path: &Path) -> ExtractKind {
+    if crate::logs::is_log_path(path) {
+        return ExtractKind::Log;
+    }
     let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
         return ExtractKind::Unknown;
     };
     match ext.to_ascii_lowercase().as_str() {
         "pdf" => ExtractKind::Pdf,
+        e if crate::json_flatten::EXTENSIONS.contains(&e) => ExtractKind::Json,
+        e if IMAGE_EXTENSIONS.contains(&e) => ExtractKind::Image,
         _ => ExtractKind::Text,
     }
 }
 
 async fn extract_plain_text(path: &Path, max_text_bytes: u64) -> Result<ExtractResult, String> {
-    // Read bytes so we can truncate safely without UTF-8 errors.
-    let bytes = tokio::fs::read(path)
+    // Read bytes so we can truncate safely without UTF-8 errors; never more than the cap (+1 to
+    // tell whether it was hit), so a huge file cannot exhaust memory.
+    let read_err = |e: std::io::Error| format!("Failed to read file {}: {e}", path.display());
+    let file = tokio::fs::File::open(path).await.map_err(read_err)?;
+    let mut bytes = vec![];
+    file.take(max_text_bytes.saturating_add(1))
+        .read_to_end(&mut bytes)
         .await
-        .map_err(|e| format!("Failed to read file {}: {e}", path.display()))?;
+        .map_err(read_err)?;
 
     let (bytes, truncated) = truncate_bytes(bytes, max_text_bytes);
     let text = String::from_utf8_lossy(&bytes).to_string();
@@ -50,43 +134,338 @@ async fn extract_plain_text(path: &Path, max_text_bytes: u64) -> Result<ExtractR
         kind: ExtractKind::Text,
         text,
         truncated,
+        page_starts: vec![],
     })
 }
 
+/// Text of a text-based file at `path` (read from disk, or sent inline to `crate::upload`):
+/// logs are marked as such, JSON is flattened (kept as plain text when it does not parse).
+pub fn text_as(path: &Path, text: String, truncated: bool) -> ExtractResult {
+    let (kind, text) = match detect_kind(path) {
+        ExtractKind::Log => (ExtractKind::Log, text),
+        ExtractKind::Json => match crate::json_flatten::flatten_document(path, &text) {
+            Some(flat) => (ExtractKind::Json, flat),
+            None => (ExtractKind::Text, text),
+        },
+        _ => (ExtractKind::Text, text),
+    };
+    ExtractResult {
+        kind,
+        text,
+        truncated,
+        page_starts: vec![],
+    }
+}
+
+/// The page's title and text; `max_text_bytes` bounds the markup read.
+pub async fn extract_html(path: &Path, max_text_bytes: u64) -> Result<(Option<String>, ExtractResult), String> {
+    let raw = extract_plain_text(path, max_text_bytes).await?;
+    let (title, text) = html_to_text(&raw.text);
+    let result = ExtractResult {
+        kind: ExtractKind::Html,
+        text,
+        truncated: raw.truncated,
+        page_starts: vec![],
+    };
+    Ok((title, result))
+}
+
+/// Elements whose content is never text to index.
+const HTML_SKIPPED: &[&str] = &["script", "style", "noscript", "template", "svg", "nav", "footer", "aside", "form"];
+/// Elements that start a new line.
+const HTML_BLOCKS: &[&str] = &[
+    "p", "div", "br", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6", "section", "article", "main", "header",
+    "blockquote", "pre", "ul", "ol", "table", "hr", "dt", "dd", "figcaption",
+];
+
+/// The `<title>` and the readable text of an HTML page: scripts, styles, navigation and forms are
+/// dropped, block elements become lines, entities are decoded and runs of whitespace collapsed.
+/// A simple scanner rather than a parser; it only needs to get the words and lines right.
+pub fn html_to_text(html: &str) -> (Option<String>, String) {
+    let mut title = None;
+    let mut out = String::with_capacity(html.len() / 2);
+    let lower = html.to_ascii_lowercase();
+    let mut i = 0;
+    while i < html.len() {
+        let Some(lt) = html[i..].find('<').map(|p| i + p) else {
+            push_text(&mut out, &html[i..]);
+            break;
+        };
+        push_text(&mut out, &html[i..lt]);
+        if lower[lt..].starts_with("<!--") {
+            i = lower[lt..].find("-->").map_or(html.len(), |p| lt + p + 3);
+            continue;
+        }
+        let Some(gt) = html[lt..].find('>').map(|p| lt + p) else {
+            break;
+        };
+        let tag = &lower[lt + 1..gt];
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        i = gt + 1;
+        if closing {
+            // List items are lines of their own, not paragraphs.
+            if HTML_BLOCKS.contains(&name.as_str()) && name != "li" {
+                out.push('\n');
+            }
+            continue;
+        }
+        if name == "title" || HTML_SKIPPED.contains(&name.as_str()) {
+            if tag.ends_with('/') {
+                continue;
+            }
+            let end = lower[i..].find(&format!("</{name}")).map_or(html.len(), |p| i + p);
+            if name == "title" && title.is_none() {
+                let mut t = String::new();
+                push_text(&mut t, &html[i..end]);
+                title = Some(t.trim().to_string()).filter(|t| !t.is_empty());
+            }
+            i = lower[end..].find('>').map_or(html.len(), |p| end + p + 1);
+            continue;
+        }
+        if HTML_BLOCKS.contains(&name.as_str()) {
+            out.push('\n');
+            if name == "li" {
+                out.push_str("- ");
+            }
+        }
+    }
+
+    let mut text = String::with_capacity(out.len());
+    let mut blank = true;
+    for line in out.lines().map(str::trim) {
+        if line.is_empty() || line == "-" {
+            if !blank {
+                text.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+        blank = false;
+    }
+    (title, text.trim_end().to_string())
+}
+
+/// Append the text between tags: entities decoded, whitespace collapsed to single spaces.
+fn push_text(out: &mut String, raw: &str) {
+    let decoded = decode_entities(raw);
+    for c in decoded.chars() {
+        if c.is_whitespace() {
+            if !out.ends_with([' ', '\n']) && !out.is_empty() {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+}
+
+/// `raw` with HTML character references decoded (unknown ones are kept as written).
+pub(crate) fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&semi| semi <= 10)
+            .and_then(|semi| entity(&rest[1..semi + 1]).map(|c| (c, semi + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character for entity `name` (between `&` and `;`); the common named ones and numeric.
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+/// What poppler's tools print for an encrypted PDF opened without (or with a wrong) password.
+const POPPLER_WRONG_PASSWORD: &str = "Incorrect password";
+const POPPLER_HINT: &str = "Try `brew install poppler`.";
+
+/// Whether the PDF at `path` needs a password to open, as `pdfinfo` reports it. `false` when
+/// `pdfinfo` is missing or fails for another reason; extraction reports those.
+pub(crate) async fn pdf_needs_password(path: &Path) -> bool {
+    match run_extractor("pdfinfo", &[path.as_os_str()], path, POPPLER_HINT).await {
+        Ok(_) => false,
+        Err(e) => e.contains(POPPLER_WRONG_PASSWORD),
+    }
+}
+
 async fn extract_pdf_pdftotext(path: &Path, max_text_bytes: u64) -> Result<ExtractResult, String> {
     // Requires poppler's `pdftotext` to be installed (brew install poppler).
-    // `pdftotext <in.pdf> -` writes the extracted text to stdout.
-    let output = Command::new("pdftotext")
-        .arg("-layout")
-        .arg("-nopgbrk")
-        .arg(path)
-        .arg("-")
-        .output()
+    // `pdftotext <in.pdf> -` writes the extracted text to stdout, pages separated by form feeds.
+    let password = crate::passwords::password_for(path).await?;
+    let mut args = vec![OsStr::new("-layout")];
+    if let Some(password) = &password {
+        args.extend([OsStr::new("-upw"), OsStr::new(password.as_str())]);
+    }
+    args.extend([path.as_os_str(), OsStr::new("-")]);
+    let stdout = run_extractor("pdftotext", &args, path, POPPLER_HINT)
         .await
-        .map_err(|e| {
-            format!(
-                "Failed to run pdftotext (is poppler installed?). Try `brew install poppler`. Details: {e}"
-            )
+        .map_err(|e| match (e.contains(POPPLER_WRONG_PASSWORD), &password) {
+            (false, _) => e,
+            (true, None) => crate::encrypted::Encrypted::Pdf.describe().to_string(),
+            (true, Some(_)) => "encrypted PDF: the stored password is wrong (silo_document_password_set)".to_string(),
         })?;
 
+    let (bytes, truncated) = truncate_bytes(stdout, max_text_bytes);
+    let raw = String::from_utf8_lossy(&bytes);
+
+    let mut text = String::with_capacity(raw.len());
+    let mut page_starts = vec![];
+    let mut tokens = 0;
+    for page in raw.split('\u{c}') {
+        page_starts.push(tokens);
+        tokens += page.split_whitespace().count();
+        text.push_str(page);
+        text.push('\n');
+    }
+    // A trailing form feed leaves an empty last "page".
+    while page_starts.len() > 1 && page_starts.last() == Some(&tokens) {
+        page_starts.pop();
+    }
+
+    Ok(ExtractResult {
+        kind: ExtractKind::Pdf,
+        text,
+        truncated,
+        page_starts,
+    })
+}
+
+async fn extract_image_tesseract(path: &Path, max_text_bytes: u64) -> Result<ExtractResult, String> {
+    // Requires `tesseract` (brew install tesseract / apt install tesseract-ocr).
+    // `tesseract <image> stdout` writes the recognized text to stdout.
+    let stdout = run_extractor(
+        "tesseract",
+        &[path.as_os_str(), OsStr::new("stdout")],
+        path,
+        "Try `brew install tesseract`.",
+    )
+    .await?;
+
+    let (bytes, truncated) = truncate_bytes(stdout, max_text_bytes);
+    let text = String::from_utf8_lossy(&bytes).to_string();
+
+    Ok(ExtractResult {
+        kind: ExtractKind::Image,
+        text,
+        truncated,
+        page_starts: vec![],
+    })
+}
+
+/// Run `program args..` under the current `ExtractLimits` and return its stdout.
+async fn run_extractor(program: &str, args: &[&OsStr], path: &Path, install_hint: &str) -> Result<Vec<u8>, String> {
+    let limits = limits();
+    let mut cmd = limited_command(program, limits.max_memory_mb);
+    cmd.args(args).kill_on_drop(true);
+    let not_installed = |details: String| format!("Failed to run {program} (is it installed?). {install_hint} Details: {details}");
+
+    let run = cmd.output();
+    let output = match tokio::time::timeout(Duration::from_secs(limits.timeout_secs.max(1)), run).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(not_installed(e.to_string())),
+        // Dropping the future kills the child (`kill_on_drop`).
+        Err(_) => {
+            return Err(format!(
+                "{program} timed out after {}s for {} (extract_timeout_secs)",
+                limits.timeout_secs,
+                path.display()
+            ))
+        }
+    };
+    if output.status.code() == Some(127) && limited_via_shell(limits.max_memory_mb) {
+        return Err(not_installed("command not found".to_string()));
+    }
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!(
-            "pdftotext failed for {} (exit={}): {}",
+            "{program} failed for {} (exit={}): {}",
             path.display(),
             output.status,
             stderr.trim()
         ));
     }
+    Ok(output.stdout)
+}
 
-    let (bytes, truncated) = truncate_bytes(output.stdout, max_text_bytes);
-    let text = String::from_utf8_lossy(&bytes).to_string();
+fn limited_via_shell(max_memory_mb: u64) -> bool {
+    cfg!(unix) && max_memory_mb > 0
+}
 
-    Ok(ExtractResult {
-        kind: ExtractKind::Pdf,
-        text,
-        truncated,
-    })
+/// `program`, or on Unix with a memory cap `sh -c 'ulimit -v ..; exec program "$@"'`, so the
+/// cap applies to the extractor only. Where `ulimit -v` is unsupported the cap is skipped.
+fn limited_command(program: &str, max_memory_mb: u64) -> Command {
+    if !limited_via_shell(max_memory_mb) {
+        return Command::new(program);
+    }
+    let mut cmd = Command::new("/bin/sh");
+    cmd.arg("-c")
+        .arg(format!(
+            "ulimit -v {} 2>/dev/null; exec \"$0\" \"$@\"",
+            max_memory_mb.saturating_mul(1024)
+        ))
+        .arg(program);
+    cmd
+}
+
+/// Cut `text` to at most `max_bytes` (at a character boundary); whether it was cut.
+pub(crate) fn truncate_text(text: &mut String, max_bytes: u64) -> bool {
+    if text.len() as u64 <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes as usize;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    true
 }
 
 fn truncate_bytes(mut bytes: Vec<u8>, max_bytes: u64) -> (Vec<u8>, bool) {
@@ -97,6 +476,3 @@ fn truncate_bytes(mut bytes: Vec<u8>, max_bytes: u64) -> (Vec<u8>, bool) {
     bytes.truncate(max);
     (bytes, true)
 }
-
-
-
@@ -0,0 +1,162 @@
+//! Federated search (`silo_search` `federated`, `federation` config): one vector search per
+//! partition of the index, run concurrently and merged.
+//!
+//! Partitions are the configured collections, `messages` (Messages, WhatsApp and Signal
+//! conversations), `web` (pages from `silo_ingest_url`) and `files` (everything else). Cosine
+//! scores are not comparable across very different content (short chat windows vs long
+//! documents), so each partition's scores are min-max normalized over its own candidates (its best
+//! hit gets 1), multiplied by the partition's weight (`federation.weights`, default 1), and hits
+//! are merged by that value. Every partition thus surfaces its best matches; weights and
+//! `min_score` tune how much. `score` stays the cosine similarity, and each hit carries its
+//! partition in `collection`.
+
+use crate::database::{SearchHit, SearchOptions};
+use crate::predicate::Predicate;
+use crate::state::{expand_tilde, SharedState};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Partition of chat conversations (`crate::messages`, `crate::chat_export`).
+pub const MESSAGES: &str = "messages";
+/// Partition of web pages (`crate::web`).
+pub const WEB: &str = "web";
+/// Partition of everything outside the collections, chats and web pages.
+pub const FILES: &str = "files";
+
+const MESSAGE_PREFIXES: &[&str] = &[
+    crate::messages::PATH_PREFIX,
+    crate::chat_export::WHATSAPP_PREFIX,
+    crate::chat_export::SIGNAL_PREFIX,
+];
+
+pub struct Partition {
+    pub name: String,
+    pub weight: f32,
+    filter: Predicate,
+}
+
+/// What each partition contributed, reported with federated results.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionSummary {
+    pub name: String,
+    pub weight: f32,
+    /// Candidates the partition returned (before merging and truncation).
+    pub candidates: usize,
+    /// Hits from the partition among the returned results (see `count_hits`).
+    pub hits: usize,
+}
+
+/// Fill in `hits` of each summary from the final (truncated) results.
+pub fn count_hits(summaries: &mut [PartitionSummary], hits: &[SearchHit]) {
+    for s in summaries {
+        s.hits = hits.iter().filter(|h| h.collection.as_deref() == Some(s.name.as_str())).count();
+    }
+}
+
+/// Collections (in config order), then `messages`, `web` and `files`. A file in several collections
+/// belongs to each of them.
+pub async fn partitions(state: &SharedState) -> Vec<Partition> {
+    let cfg = state.config.read().await;
+    let weight = |name: &str| cfg.federation.weights.get(name).copied().unwrap_or(1.0).max(0.0);
+    let mut out: Vec<Partition> = cfg
+        .collections
+        .iter()
+        .filter(|c| !c.paths.is_empty())
+        .map(|c| Partition {
+            name: c.name.clone(),
+            weight: weight(&c.name),
+            filter: Predicate::any(
+                c.paths
+                    .iter()
+                    .map(|p| crate::access::path_prefix_filter(&expand_tilde(&p.to_string_lossy()))),
+            ),
+        })
+        .collect();
+    let messages = Predicate::any(MESSAGE_PREFIXES.iter().map(|p| Predicate::starts_with("path", p)));
+    let web = Predicate::any(crate::web::PATH_PREFIXES.iter().map(|p| Predicate::starts_with("path", p)));
+    let elsewhere = Predicate::any(out.iter().map(|p| p.filter.clone()).chain([messages.clone(), web.clone()]));
+    out.push(Partition {
+        name: MESSAGES.to_string(),
+        weight: weight(MESSAGES),
+        filter: messages,
+    });
+    out.push(Partition {
+        name: WEB.to_string(),
+        weight: weight(WEB),
+        filter: web,
+    });
+    out.push(Partition {
+        name: FILES.to_string(),
+        weight: weight(FILES),
+        filter: elsewhere.not(),
+    });
+    out
+}
+
+/// Search every partition for `qvec` (each with `opts` plus its own filter, `opts.top_k`
+/// candidates) and merge the hits by weighted normalized score, best first. A chunk found in
+/// several partitions is kept once, labelled with the partition where it ranked best.
+pub async fn search(
+    state: &SharedState,
+    qvec: &[f32],
+    opts: &SearchOptions,
+    partitions: Vec<Partition>,
+) -> Result<(Vec<SearchHit>, Vec<PartitionSummary>), String> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for (i, p) in partitions.iter().enumerate() {
+        let db = state.db.clone();
+        let qvec = qvec.to_vec();
+        let filter = match &opts.filter {
+            Some(f) => f.clone().and(p.filter.clone()),
+            None => p.filter.clone(),
+        };
+        let opts = SearchOptions {
+            top_k: opts.top_k,
+            min_score: opts.min_score,
+            filter: Some(filter),
+        };
+        tasks.spawn(async move { (i, db.search_chunks_by_vector(&qvec, &opts).await) });
+    }
+
+    let mut results: Vec<Vec<SearchHit>> = vec![vec![]; partitions.len()];
+    while let Some(joined) = tasks.join_next().await {
+        let (i, hits) = joined.map_err(|e| format!("Federated search task failed: {e}"))?;
+        results[i] = hits.map_err(|e| format!("DB search failed ({}): {e}", partitions[i].name))?;
+    }
+
+    let summaries: Vec<PartitionSummary> = partitions
+        .iter()
+        .zip(&results)
+        .map(|(p, hits)| PartitionSummary {
+            name: p.name.clone(),
+            weight: p.weight,
+            candidates: hits.len(),
+            hits: 0,
+        })
+        .collect();
+
+    let mut scored: Vec<(f32, SearchHit)> = vec![];
+    for (i, hits) in results.iter_mut().enumerate() {
+        let scores: Vec<f32> = hits.iter().filter_map(|h| h.score).collect();
+        let max = scores.iter().copied().fold(f32::MIN, f32::max);
+        let min = scores.iter().copied().fold(f32::MAX, f32::min);
+        for mut hit in hits.drain(..) {
+            let norm = match hit.score {
+                Some(s) if max > min => (s - min) / (max - min),
+                Some(_) => 1.0,
+                None => 0.0,
+            };
+            hit.collection = Some(partitions[i].name.clone());
+            scored.push((partitions[i].weight * norm, hit));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut seen: HashSet<(String, Option<i64>)> = HashSet::new();
+    let mut merged = Vec::with_capacity(scored.len());
+    for (_, hit) in scored {
+        if seen.insert((hit.path.clone(), hit.chunk_index)) {
+            merged.push(hit);
+        }
+    }
+    Ok((merged, summaries))
+}
@@ -0,0 +1,61 @@
+//! Search fields (`silo_search` `fields`): a hit matches in its file's `title` (the file name)
+//! or in its `body` (the chunk text, by vector similarity).
+//!
+//! Title matches are lexical: the share of query terms found in the file name. They add
+//! `ranking.title_weight` times that share to a hit's rank, and `fields: ["title"]` restricts the
+//! search to files whose name contains a query term. Chunks carry no heading metadata (their
+//! text is whitespace-joined), so there is no heading field.
+
+use crate::catalog::FileCatalog;
+use crate::predicate::Predicate;
+use serde::{Deserialize, Serialize};
+
+/// Files a title-only search is restricted to at most (best name matches first).
+const MAX_TITLE_FILES: usize = 1000;
+/// Shorter query terms are not matched against names.
+const MIN_TERM_CHARS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    /// The file name.
+    Title,
+    /// The chunk text.
+    Body,
+}
+
+/// Lowercased alphanumeric words of `query` worth matching against names.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= MIN_TERM_CHARS)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Share of `terms` contained in the file name of `path` (0 when there are no terms).
+pub fn title_match(path: &str, terms: &[String]) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+    terms.iter().filter(|t| name.contains(t.as_str())).count() as f32 / terms.len() as f32
+}
+
+/// Chunks of catalog files whose name contains at least one of `terms`, preferring files that
+/// match more of them; `Predicate::never` when none do.
+pub fn title_filter(catalog: &FileCatalog, terms: &[String]) -> Predicate {
+    let mut matches: Vec<(f32, &str)> = catalog
+        .entries()
+        .map(|e| (title_match(&e.path, terms), e.path.as_str()))
+        .filter(|(share, _)| *share > 0.0)
+        .collect();
+    if matches.len() > MAX_TITLE_FILES {
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        matches.truncate(MAX_TITLE_FILES);
+    }
+    Predicate::in_list("path", matches.into_iter().map(|(_, p)| p.to_string()))
+}
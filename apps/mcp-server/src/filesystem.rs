@@ -1,5 +1,8 @@
-use crate::config::CompiledFileSystemPolicy;
+use crate::config::{CompiledFileSystemPolicy, NetworkMountMode};
+use crate::mounts::NetworkMounts;
+use crate::sysindex::initial_stack;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize)]
@@ -12,21 +15,63 @@ pub struct FileCandidate {
     pub inode: Option<u64>,
 }
 
+/// Why `preview_index` skipped an entry (keys of `ScanSummary::skipped_by_reason`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    Excluded,
+    Extension,
+    TooLarge,
+    Symlink,
+    Error,
+    NotRegular,
+    Placeholder,
+    Generated,
+    Encrypted,
+    Marker,
+    NetworkMount,
+    Cycle,
+    Depth,
+    Truncated,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SkippedEntry {
     pub path: String,
+    pub kind: SkipReason,
     pub reason: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtensionTotal {
+    /// Lowercased, without the dot.
+    pub extension: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ScanSummary {
     pub roots: Vec<String>,
     pub files_seen: u64,
     pub dirs_seen: u64,
     pub candidates: u64,
+    /// Total size of the candidates.
+    pub candidate_bytes: u64,
+    /// Candidate files and bytes per extension, largest first.
+    pub candidates_by_extension: Vec<ExtensionTotal>,
     pub skipped: u64,
+    pub skipped_by_reason: BTreeMap<SkipReason, u64>,
+    /// Directories cut short by `max_depth` or `max_files_per_directory`.
+    pub truncated_dirs: u64,
     pub sample_candidates: Vec<FileCandidate>,
     pub sample_skipped: Vec<SkippedEntry>,
+    /// Network mounts met during the scan, with how indexing would handle them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub network_mounts: Vec<String>,
+    /// Roots whose files were listed by the OS index instead of walked (`use_system_index`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system_index_roots: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -53,122 +98,154 @@ pub async fn preview_index(
     roots: Vec<PathBuf>,
     policy: &CompiledFileSystemPolicy,
     options: ScanOptions,
+) -> ScanSummary {
+    scan_candidates(roots, policy, options, |_, _| {}).await
+}
+
+/// `preview_index`, also calling `on_candidate(path, metadata)` for every file that would be
+/// indexed (not just the sampled ones).
+pub async fn scan_candidates(
+    roots: Vec<PathBuf>,
+    policy: &CompiledFileSystemPolicy,
+    options: ScanOptions,
+    mut on_candidate: impl FnMut(&Path, &std::fs::Metadata),
 ) -> ScanSummary {
     let mut files_seen = 0u64;
     let mut dirs_seen = 0u64;
     let mut candidates = 0u64;
-    let mut skipped = 0u64;
+    let mut candidate_bytes = 0u64;
+    let mut by_extension: HashMap<String, ExtensionTotal> = HashMap::new();
+    let mut truncated_dirs = 0u64;
 
     let mut sample_candidates: Vec<FileCandidate> = vec![];
-    let mut sample_skipped: Vec<SkippedEntry> = vec![];
+    let mut skips = Skips::new(options.max_sample_skipped);
 
-    let mut stack: Vec<PathBuf> = vec![];
-    for r in &roots {
-        stack.push(r.clone());
-    }
+    let net = &policy.network_mounts;
+    let mounts = NetworkMounts::for_mode(net.mode).await;
+    let mut network_mounts: Vec<String> = vec![];
 
-    while let Some(current) = stack.pop() {
+    let mut visited = VisitedDirs::default();
+    let (mut stack, system_index_roots) = initial_stack(&roots, policy).await;
+
+    while let Some((current, depth)) = stack.pop() {
         // Exclude matches apply to both files and directories.
         if policy.matches_exclude(&current) {
-            skipped += 1;
-            push_skipped(
-                &mut sample_skipped,
-                options.max_sample_skipped,
-                current,
-                "excluded by glob".to_string(),
-            );
+            skips.add(SkipReason::Excluded, current, "excluded by glob".to_string());
             continue;
         }
 
-        let meta = match tokio::fs::symlink_metadata(&current).await {
-            Ok(m) => m,
-            Err(e) => {
-                skipped += 1;
-                push_skipped(
-                    &mut sample_skipped,
-                    options.max_sample_skipped,
-                    current,
-                    format!("metadata error: {e}"),
-                );
+        let meta = match entry_metadata(&current, policy.follow_symlinks).await {
+            Ok(Some(m)) => m,
+            Ok(None) => {
+                skips.add(SkipReason::Symlink, current, "symlink (skipped)".to_string());
+                continue;
+            }
+            Err(reason) => {
+                skips.add(SkipReason::Error, current, reason);
                 continue;
             }
         };
 
         let ft = meta.file_type();
-        if ft.is_symlink() && !policy.follow_symlinks {
-            skipped += 1;
-            push_skipped(
-                &mut sample_skipped,
-                options.max_sample_skipped,
-                current,
-                "symlink (skipped)".to_string(),
-            );
-            continue;
-        }
-
         if ft.is_dir() {
+            if !visited.first_visit(&meta) {
+                skips.add(SkipReason::Cycle, current, "directory cycle (already visited)".to_string());
+                continue;
+            }
+            if depth > policy.max_depth {
+                truncated_dirs += 1;
+                skips.add(SkipReason::Depth, current, format!("max depth {} exceeded", policy.max_depth));
+                continue;
+            }
             dirs_seen += 1;
 
-            let mut rd = match tokio::fs::read_dir(&current).await {
+            if let Some(m) = mounts.find(&current) {
+                m.note(net.mode, &mut network_mounts);
+                if net.mode == NetworkMountMode::Skip {
+                    skips.add(SkipReason::NetworkMount, current, "network mount (skipped)".to_string());
+                    continue;
+                }
+            }
+
+            if policy.honor_skip_markers && has_skip_marker(&current).await {
+                skips.add(SkipReason::Marker, current, "skip marker (CACHEDIR.TAG/.nobackup)".to_string());
+                continue;
+            }
+
+            let (children, dropped) = match read_dir_limited(&current, policy.max_files_per_directory).await {
                 Ok(r) => r,
                 Err(e) => {
-                    skipped += 1;
-                    push_skipped(
-                        &mut sample_skipped,
-                        options.max_sample_skipped,
-                        current,
-                        format!("read_dir error: {e}"),
-                    );
+                    skips.add(SkipReason::Error, current, format!("read_dir error: {e}"));
                     continue;
                 }
             };
+            stack.extend(children.into_iter().map(|c| (c, depth + 1)));
 
-            while let Ok(Some(entry)) = rd.next_entry().await {
-                stack.push(entry.path());
+            if dropped > 0 {
+                truncated_dirs += 1;
+                skips.add_n(
+                    SkipReason::Truncated,
+                    dropped as u64,
+                    current,
+                    format!("directory truncated: {dropped} files over max_files_per_directory"),
+                );
             }
-
-            // If next_entry itself errors, record it once (best-effort).
-            // (Tokio does not expose the error directly in the loop above.)
             continue;
         }
 
         if !ft.is_file() {
-            skipped += 1;
-            push_skipped(
-                &mut sample_skipped,
-                options.max_sample_skipped,
-                current,
-                "not a regular file".to_string(),
-            );
+            skips.add(SkipReason::NotRegular, current, "not a regular file".to_string());
             continue;
         }
 
         files_seen += 1;
 
         if !policy.extension_allowed(&current) {
-            skipped += 1;
-            push_skipped(
-                &mut sample_skipped,
-                options.max_sample_skipped,
-                current,
-                "extension not allowlisted".to_string(),
-            );
+            skips.add(SkipReason::Extension, current, "extension not allowlisted".to_string());
             continue;
         }
 
         let size = meta.len();
-        if size > policy.max_file_size_bytes {
-            skipped += 1;
-            push_skipped(
-                &mut sample_skipped,
-                options.max_sample_skipped,
-                current,
-                format!("file too large: {size} bytes"),
-            );
+        if size > policy.max_file_size_for(&current) {
+            skips.add(SkipReason::TooLarge, current, format!("file too large: {size} bytes"));
+            continue;
+        }
+        if size > net.max_file_size_bytes && mounts.find(&current).is_some() {
+            skips.add(SkipReason::TooLarge, current, format!("file too large for a network mount: {size} bytes"));
+            continue;
+        }
+
+        if policy.skip_placeholder(&current) && is_cloud_placeholder(&current, &meta) {
+            skips.add(SkipReason::Placeholder, current, "cloud placeholder (online-only)".to_string());
+            continue;
+        }
+
+        let generated = if policy.skip_generated {
+            crate::generated::detect(&current).await
+        } else {
+            None
+        };
+        if let Some(found) = generated {
+            skips.add(SkipReason::Generated, current, found.describe().to_string());
+            continue;
+        }
+
+        if let Some(found) = crate::encrypted::detect_locked(&current).await {
+            skips.add(SkipReason::Encrypted, current, found.describe().to_string());
             continue;
         }
 
         candidates += 1;
+        candidate_bytes += size;
+        let ext = crate::logs::effective_extension(&current).unwrap_or_default();
+        let total = by_extension.entry(ext.clone()).or_insert_with(|| ExtensionTotal {
+            extension: ext,
+            files: 0,
+            bytes: 0,
+        });
+        total.files += 1;
+        total.bytes += size;
+        on_candidate(&current, &meta);
         push_candidate(
             &mut sample_candidates,
             options.max_sample_candidates,
@@ -179,32 +256,125 @@ pub async fn preview_index(
 
     // Deterministic order for samples (independent of filesystem traversal order)
     sample_candidates.sort_by(|a, b| a.path.cmp(&b.path));
-    sample_skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    skips.samples.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut candidates_by_extension: Vec<ExtensionTotal> = by_extension.into_values().collect();
+    candidates_by_extension.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.extension.cmp(&b.extension)));
 
     ScanSummary {
         roots: roots.iter().map(|p| p.to_string_lossy().to_string()).collect(),
         files_seen,
         dirs_seen,
         candidates,
-        skipped,
+        candidate_bytes,
+        candidates_by_extension,
+        skipped: skips.total,
+        skipped_by_reason: skips.by_reason,
+        truncated_dirs,
         sample_candidates,
-        sample_skipped,
+        sample_skipped: skips.samples,
+        network_mounts,
+        system_index_roots,
     }
 }
 
-fn push_skipped(
-    out: &mut Vec<SkippedEntry>,
-    max: usize,
-    path: PathBuf,
-    reason: String,
-) {
-    if out.len() >= max {
-        return;
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WalkStats {
+    pub files: u64,
+    pub dirs: u64,
+    /// Unreadable entries, skipped symlinks and (unless included) excluded paths.
+    pub skipped: u64,
+    /// Stopped early at `max_files`.
+    pub truncated: bool,
+}
+
+/// Walks every regular file under `roots` (no extension or size filters, unlike
+/// `preview_index`), calling `visit(root, path, metadata)`. Symlinks are never followed (no
+/// loops, no double counting); exclude globs are honored unless `include_excluded` is set.
+pub async fn walk_files(
+    roots: &[PathBuf],
+    policy: &CompiledFileSystemPolicy,
+    include_excluded: bool,
+    max_files: u64,
+    mut visit: impl FnMut(&Path, &Path, &std::fs::Metadata),
+) -> WalkStats {
+    let mut stats = WalkStats::default();
+    let mut visited = VisitedDirs::default();
+    for root in roots {
+        let mut stack = vec![(root.clone(), 0)];
+        while let Some((current, depth)) = stack.pop() {
+            if !include_excluded && policy.matches_exclude(&current) {
+                stats.skipped += 1;
+                continue;
+            }
+            let Ok(meta) = tokio::fs::symlink_metadata(&current).await else {
+                stats.skipped += 1;
+                continue;
+            };
+            let ft = meta.file_type();
+            if ft.is_dir() {
+                if !visited.first_visit(&meta) || depth >= MAX_TRAVERSAL_DEPTH {
+                    stats.skipped += 1;
+                    continue;
+                }
+                stats.dirs += 1;
+                let Ok(mut rd) = tokio::fs::read_dir(&current).await else {
+                    stats.skipped += 1;
+                    continue;
+                };
+                while let Ok(Some(entry)) = rd.next_entry().await {
+                    stack.push((entry.path(), depth + 1));
+                }
+                continue;
+            }
+            if !ft.is_file() {
+                stats.skipped += 1;
+                continue;
+            }
+            if stats.files >= max_files {
+                stats.truncated = true;
+                return stats;
+            }
+            stats.files += 1;
+            visit(root, &current, &meta);
+        }
+    }
+    stats
+}
+
+/// Skip counters and samples for `preview_index`.
+struct Skips {
+    total: u64,
+    by_reason: BTreeMap<SkipReason, u64>,
+    samples: Vec<SkippedEntry>,
+    max_samples: usize,
+}
+
+impl Skips {
+    fn new(max_samples: usize) -> Self {
+        Self {
+            total: 0,
+            by_reason: BTreeMap::new(),
+            samples: vec![],
+            max_samples,
+        }
+    }
+
+    fn add(&mut self, kind: SkipReason, path: PathBuf, reason: String) {
+        self.add_n(kind, 1, path, reason);
+    }
+
+    /// `n` skipped entries recorded under one sample (e.g. a truncated directory).
+    fn add_n(&mut self, kind: SkipReason, n: u64, path: PathBuf, reason: String) {
+        self.total += n;
+        *self.by_reason.entry(kind).or_default() += n;
+        if self.samples.len() < self.max_samples {
+            self.samples.push(SkippedEntry {
+                path: path.to_string_lossy().to_string(),
+                kind,
+                reason,
+            });
+        }
     }
-    out.push(SkippedEntry {
-        path: path.to_string_lossy().to_string(),
-        reason,
-    });
 }
 
 fn push_candidate(out: &mut Vec<FileCandidate>, max: usize, path: &Path, meta: &std::fs::Metadata) {
@@ -219,13 +389,142 @@ fn push_candidate(out: &mut Vec<FileCandidate>, max: usize, path: &Path, meta: &
     });
 }
 
-fn modified_epoch_secs(meta: &std::fs::Metadata) -> Option<i64> {
+/// Hard limit on directory nesting below a root. Real trees are far shallower; this only stops
+/// runaway traversal through cycles the visited set cannot see.
+pub const MAX_TRAVERSAL_DEPTH: usize = 64;
+
+/// Directories already entered during one walk, by (device, inode). Catches cycles through
+/// followed symlinks and bind mounts.
+#[derive(Default)]
+pub struct VisitedDirs(HashSet<(u64, u64)>);
+
+impl VisitedDirs {
+    /// `false` if this directory was entered before.
+    pub fn first_visit(&mut self, meta: &std::fs::Metadata) -> bool {
+        match dir_id(meta) {
+            Some(id) => self.0.insert(id),
+            None => true,
+        }
+    }
+}
+
+fn dir_id(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((meta.dev(), meta.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
+    }
+}
+
+/// Entries of `dir` to walk: every subdirectory, plus at most `max_files` other entries
+/// (lowest names first, so repeated scans keep the same ones). Also returns how many entries
+/// were dropped.
+pub async fn read_dir_limited(dir: &Path, max_files: Option<usize>) -> std::io::Result<(Vec<PathBuf>, usize)> {
+    let mut rd = tokio::fs::read_dir(dir).await?;
+    let mut dirs = vec![];
+    let mut files = vec![];
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let is_dir = entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir {
+            dirs.push(entry.path());
+        } else {
+            files.push(entry.path());
+        }
+    }
+    let mut dropped = 0;
+    if let Some(max) = max_files.filter(|m| files.len() > *m) {
+        files.sort();
+        dropped = files.len() - max;
+        files.truncate(max);
+    }
+    dirs.extend(files);
+    Ok((dirs, dropped))
+}
+
+/// Metadata of a walk entry: `Ok(None)` for a symlink that is not followed, `Err` with a
+/// skip reason for unreadable entries and broken links.
+pub async fn entry_metadata(path: &Path, follow_symlinks: bool) -> Result<Option<std::fs::Metadata>, String> {
+    let meta = tokio::fs::symlink_metadata(path)
+        .await
+        .map_err(|e| format!("metadata error: {e}"))?;
+    if !meta.file_type().is_symlink() {
+        return Ok(Some(meta));
+    }
+    if !follow_symlinks {
+        return Ok(None);
+    }
+    tokio::fs::metadata(path)
+        .await
+        .map(Some)
+        .map_err(|e| format!("broken symlink: {e}"))
+}
+
+/// Marker files that opt a directory out of indexing (cachedir spec, `.nobackup` convention).
+const SKIP_MARKERS: &[&str] = &["CACHEDIR.TAG", ".nobackup"];
+
+/// The directory contains a skip marker (`CACHEDIR.TAG` / `.nobackup`).
+pub async fn has_skip_marker(dir: &Path) -> bool {
+    for marker in SKIP_MARKERS {
+        if tokio::fs::symlink_metadata(dir.join(marker)).await.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+/// "Online-only" cloud file whose content is not on disk; reading it starts a download.
+///
+/// - iCloud evicted stubs are named `.<name>.icloud`
+/// - macOS File Provider (iCloud Drive, OneDrive, Dropbox) marks dataless files with `SF_DATALESS`
+/// - Windows cloud files carry the recall-on-access / offline attributes
+/// - elsewhere, a non-empty file with no allocated blocks (FUSE-backed sync clients)
+pub fn is_cloud_placeholder(path: &Path, meta: &std::fs::Metadata) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    if name.starts_with('.') && name.ends_with(".icloud") {
+        return true;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::macos::fs::MetadataExt;
+        const SF_DATALESS: u32 = 0x4000_0000;
+        if meta.st_flags() & SF_DATALESS != 0 {
+            return true;
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // Small files may live inline in metadata; only trust this for larger ones.
+        meta.blocks() == 0 && meta.len() > 4096
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+        const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x4_0000;
+        const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x40_0000;
+        let attrs = meta.file_attributes();
+        attrs & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS) != 0
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = meta;
+        false
+    }
+}
+
+pub(crate) fn modified_epoch_secs(meta: &std::fs::Metadata) -> Option<i64> {
     let t = meta.modified().ok()?;
     let d = t.duration_since(std::time::UNIX_EPOCH).ok()?;
     Some(d.as_secs() as i64)
 }
 
-fn inode(meta: &std::fs::Metadata) -> Option<u64> {
+pub(crate) fn inode(meta: &std::fs::Metadata) -> Option<u64> {
     #[cfg(unix)]
     {
         use std::os::unix::fs::MetadataExt;
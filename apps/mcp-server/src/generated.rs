@@ -0,0 +1,166 @@
+//! Generated-file detection (`filesystem.skip_generated`): lockfiles, minified bundles and
+//! autogenerated code are noise in search results even when their extension is allow-listed.
+//!
+//! Three checks, cheapest first:
+//! - the file name: lockfiles (`package-lock.json`, `Cargo.lock`, ...) and generated-name
+//!   patterns (`*.min.js`, `*_pb2.py`, `*.g.dart`, ...)
+//! - a "generated" marker in a comment near the top (`@generated`, `Code generated ... DO NOT
+//!   EDIT`, `auto-generated`, `This file was generated ...`)
+//! - minified text: long lines on average with almost no whitespace
+//!
+//! Content checks read the first `HEAD_BYTES` of text files only (not PDFs or images); notes and
+//! other prose formats only get the minified check.
+
+use crate::extract::{detect_kind, ExtractKind};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Bytes read for the content checks.
+const HEAD_BYTES: usize = 16 * 1024;
+/// Lines at the top searched for a generated marker.
+const MARKER_LINES: usize = 10;
+/// Minified: average line length and whitespace share over the head.
+const MINIFIED_MIN_AVG_LINE: usize = 500;
+const MINIFIED_MAX_WHITESPACE: f32 = 0.08;
+/// Heads shorter than this are not judged as minified.
+const MINIFIED_MIN_BYTES: usize = 1024;
+
+const LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    "cargo.lock",
+    "gemfile.lock",
+    "podfile.lock",
+    "composer.lock",
+    "poetry.lock",
+    "pipfile.lock",
+    "uv.lock",
+    "pdm.lock",
+    "go.sum",
+    "mix.lock",
+    "flake.lock",
+    "packages.lock.json",
+    "pubspec.lock",
+];
+
+/// File name suffixes of generated or bundled code (lowercased).
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js",
+    ".min.mjs",
+    ".min.css",
+    ".bundle.js",
+    ".chunk.js",
+    ".pb.go",
+    ".pb.cc",
+    ".pb.h",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+    ".g.cs",
+    ".generated.ts",
+    ".generated.js",
+];
+
+/// Text formats where the marker check is skipped (see `detect_by_content`).
+const PROSE_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "org", "adoc"];
+
+/// Comment markers that say a file is generated (matched lowercased).
+const MARKERS: &[&str] = &[
+    "@generated",
+    "do not edit",
+    "auto-generated",
+    "autogenerated",
+    "automatically generated",
+    "code generated by",
+    "file was generated",
+    "file is generated",
+];
+
+/// Why a file counts as generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generated {
+    Lockfile,
+    GeneratedName,
+    Marker,
+    Minified,
+}
+
+impl Generated {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Generated::Lockfile => "lockfile",
+            Generated::GeneratedName => "generated file name",
+            Generated::Marker => "generated-code header",
+            Generated::Minified => "minified (long lines, little whitespace)",
+        }
+    }
+}
+
+/// Name checks, then (for text files) content checks on the head of the file.
+pub async fn detect(path: &Path) -> Option<Generated> {
+    if let Some(found) = detect_by_name(path) {
+        return Some(found);
+    }
+    if !matches!(detect_kind(path), ExtractKind::Text | ExtractKind::Json) {
+        return None;
+    }
+    let head = read_head(path).await?;
+    let ext = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    detect_by_content(&head, PROSE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+pub fn detect_by_name(path: &Path) -> Option<Generated> {
+    let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+    if LOCKFILES.contains(&name.as_str()) {
+        return Some(Generated::Lockfile);
+    }
+    if GENERATED_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+        return Some(Generated::GeneratedName);
+    }
+    None
+}
+
+/// `prose` files (notes, docs) skip the marker check: a `# ... generated by ...` line there is a
+/// heading, not a comment.
+pub fn detect_by_content(head: &str, prose: bool) -> Option<Generated> {
+    let has_marker = !prose
+        && head
+            .lines()
+            .take(MARKER_LINES)
+            .filter(|l| is_comment(l))
+            .any(|l| {
+                let l = l.to_ascii_lowercase();
+                MARKERS.iter().any(|m| l.contains(m))
+            });
+    if has_marker {
+        return Some(Generated::Marker);
+    }
+    is_minified(head).then_some(Generated::Minified)
+}
+
+fn is_comment(line: &str) -> bool {
+    let l = line.trim_start();
+    ["//", "#", "/*", "*", "<!--", "--", ";", "\"\"\""].iter().any(|p| l.starts_with(p))
+}
+
+fn is_minified(head: &str) -> bool {
+    if head.len() < MINIFIED_MIN_BYTES {
+        return false;
+    }
+    let lines = head.lines().filter(|l| !l.trim().is_empty()).count().max(1);
+    let whitespace = head.bytes().filter(u8::is_ascii_whitespace).count();
+    head.len() / lines >= MINIFIED_MIN_AVG_LINE && (whitespace as f32 / head.len() as f32) < MINIFIED_MAX_WHITESPACE
+}
+
+/// The first `HEAD_BYTES` as (lossy) UTF-8; `None` when unreadable.
+async fn read_head(path: &Path) -> Option<String> {
+    let file = tokio::fs::File::open(path).await.ok()?;
+    let mut buf = Vec::with_capacity(HEAD_BYTES);
+    file.take(HEAD_BYTES as u64).read_to_end(&mut buf).await.ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
@@ -0,0 +1,450 @@
+//! Lightweight knowledge graph over indexed documents.
+//!
+//! Nodes are entities (from the entities table) and documents; edges are
+//! - `mentions`: document -> entity, weighted by the number of chunks mentioning it
+//! - `co_occurs`: entity <-> entity, weighted by the number of chunks mentioning both
+//!
+//! The graph is derived data: it is rebuilt from the entities table on the first query after
+//! the index changed, and persisted as `graph.json` under the data dir so restarts don't pay
+//! for a rebuild.
+
+use crate::entities::entity_key;
+use crate::errors::ToolError;
+use crate::predicate::Predicate;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+const GRAPH_VERSION: u32 = 1;
+/// Upper bound on entity rows read when building.
+const MAX_GRAPH_ROWS: usize = 500_000;
+/// Chunks naming more entities than this only contribute their first ones to co-occurrence
+/// (keeps list-like chunks from producing quadratic edge counts).
+const MAX_ENTITIES_PER_CHUNK: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Entity,
+    Document,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    Mentions,
+    CoOccurs,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// `entity:<kind>:<key>` or `doc:<path>`.
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+    /// Entity kind (`person`, `org`, ...) for entity nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_kind: Option<String>,
+    /// Mentions (entities) or mentioned entities (documents).
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub a: usize,
+    pub b: usize,
+    pub kind: EdgeKind,
+    pub weight: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GraphFile {
+    version: u32,
+    built_at_epoch_secs: i64,
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+pub struct KnowledgeGraph {
+    pub built_at_epoch_secs: i64,
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    by_id: HashMap<String, usize>,
+    /// node -> (neighbor, edge index)
+    adjacency: Vec<Vec<(usize, usize)>>,
+}
+
+impl KnowledgeGraph {
+    fn from_parts(built_at_epoch_secs: i64, nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> Self {
+        let by_id = nodes.iter().enumerate().map(|(i, n)| (n.id.clone(), i)).collect();
+        let mut adjacency = vec![vec![]; nodes.len()];
+        for (i, e) in edges.iter().enumerate() {
+            if e.a < nodes.len() && e.b < nodes.len() {
+                adjacency[e.a].push((e.b, i));
+                adjacency[e.b].push((e.a, i));
+            }
+        }
+        for list in &mut adjacency {
+            list.sort_by_key(|(_, e)| std::cmp::Reverse(edges[*e].weight));
+        }
+        Self {
+            built_at_epoch_secs,
+            nodes,
+            edges,
+            by_id,
+            adjacency,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    async fn load(file: &Path) -> Option<Self> {
+        let s = tokio::fs::read_to_string(file).await.ok()?;
+        match serde_json::from_str::<GraphFile>(&s) {
+            Ok(g) if g.version == GRAPH_VERSION => Some(Self::from_parts(g.built_at_epoch_secs, g.nodes, g.edges)),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Ignoring invalid graph {}: {e}", file.display());
+                None
+            }
+        }
+    }
+
+    async fn save(&self, file: &Path) -> Result<(), String> {
+        if let Some(parent) = file.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create data dir {}: {e}", parent.display()))?;
+        }
+        let out = GraphFile {
+            version: GRAPH_VERSION,
+            built_at_epoch_secs: self.built_at_epoch_secs,
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+        };
+        let s = serde_json::to_string(&out).map_err(|e| format!("Failed to serialize graph: {e}"))?;
+        tokio::fs::write(file, s)
+            .await
+            .map_err(|e| format!("Failed to write graph {}: {e}", file.display()))
+    }
+
+    /// Resolve a user-supplied node: exact id, document path, exact entity name, then the most
+    /// mentioned entity whose name contains the query.
+    pub fn resolve(&self, query: &str) -> Option<usize> {
+        if let Some(&i) = self.by_id.get(query) {
+            return Some(i);
+        }
+        if let Some(&i) = self.by_id.get(&format!("doc:{query}")) {
+            return Some(i);
+        }
+        let key = entity_key(query);
+        if key.is_empty() {
+            return None;
+        }
+        let entities = || {
+            self.nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| n.kind == NodeKind::Entity)
+        };
+        entities()
+            .filter(|(_, n)| entity_key(&n.label) == key)
+            .max_by_key(|(_, n)| n.weight)
+            .or_else(|| {
+                entities()
+                    .filter(|(_, n)| entity_key(&n.label).contains(&key))
+                    .max_by_key(|(_, n)| n.weight)
+            })
+            .map(|(i, _)| i)
+    }
+
+    /// Neighbors within `depth` hops, strongest edges first.
+    pub fn neighbors(&self, start: usize, depth: usize, kind: Option<NodeKind>, limit: usize) -> Vec<Neighbor> {
+        let mut seen: HashSet<usize> = HashSet::from([start]);
+        let mut frontier = vec![start];
+        let mut out = vec![];
+        for hop in 1..=depth.max(1) {
+            let mut next = vec![];
+            for &n in &frontier {
+                for &(m, e) in &self.adjacency[n] {
+                    if !seen.insert(m) {
+                        continue;
+                    }
+                    next.push(m);
+                    if kind.is_none_or(|k| self.nodes[m].kind == k) {
+                        out.push(Neighbor {
+                            node: self.nodes[m].clone(),
+                            hops: hop,
+                            via: (hop > 1).then(|| self.nodes[n].label.clone()),
+                            edge: self.edges[e].kind,
+                            weight: self.edges[e].weight,
+                        });
+                    }
+                }
+            }
+            frontier = next;
+        }
+        out.sort_by(|a, b| a.hops.cmp(&b.hops).then_with(|| b.weight.cmp(&a.weight)));
+        out.truncate(limit);
+        out
+    }
+
+    /// Shortest path (fewest hops) between two nodes, at most `max_hops` edges long.
+    pub fn path(&self, from: usize, to: usize, max_hops: usize) -> Option<Vec<PathStep>> {
+        let mut prev: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut depth: HashMap<usize, usize> = HashMap::from([(from, 0)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(n) = queue.pop_front() {
+            if n == to {
+                break;
+            }
+            let d = depth[&n];
+            if d >= max_hops {
+                continue;
+            }
+            for &(m, e) in &self.adjacency[n] {
+                if depth.contains_key(&m) {
+                    continue;
+                }
+                depth.insert(m, d + 1);
+                prev.insert(m, (n, e));
+                queue.push_back(m);
+            }
+        }
+        if !depth.contains_key(&to) {
+            return None;
+        }
+
+        // Walk back from `to`; each node carries the edge it was reached by.
+        let mut steps = vec![];
+        let mut cur = to;
+        loop {
+            let via = prev.get(&cur).copied();
+            steps.push(PathStep {
+                node: self.nodes[cur].clone(),
+                edge: via.map(|(_, e)| self.edges[e].kind),
+                weight: via.map(|(_, e)| self.edges[e].weight),
+            });
+            match via {
+                Some((p, _)) => cur = p,
+                None => break,
+            }
+        }
+        steps.reverse();
+        Some(steps)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbor {
+    pub node: GraphNode,
+    pub hops: usize,
+    /// Intermediate node for 2+ hop neighbors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+    pub edge: EdgeKind,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PathStep {
+    pub node: GraphNode,
+    /// Edge from the previous step (absent on the first node).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge: Option<EdgeKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+}
+
+fn graph_file(data_dir: &Path) -> PathBuf {
+    data_dir.join("graph.json")
+}
+
+/// Build the graph from all entity mention rows.
+async fn build(state: &SharedState) -> Result<KnowledgeGraph, ToolError> {
+    let rows = state
+        .db
+        .find_entities(&Predicate::always(), MAX_GRAPH_ROWS)
+        .await
+        .map_err(ToolError::db_query_failed)?;
+
+    let mut nodes: Vec<GraphNode> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut node = |id: String, kind: NodeKind, label: &str, entity_kind: Option<&str>| -> usize {
+        *index.entry(id.clone()).or_insert_with(|| {
+            nodes.push(GraphNode {
+                id,
+                kind,
+                label: label.to_string(),
+                entity_kind: entity_kind.map(str::to_string),
+                weight: 0,
+            });
+            nodes.len() - 1
+        })
+    };
+
+    let mut mentions: BTreeMap<(usize, usize), u32> = BTreeMap::new();
+    let mut chunks: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for r in &rows {
+        let e = node(format!("entity:{}:{}", r.kind, r.key), NodeKind::Entity, &r.name, Some(&r.kind));
+        let d = node(format!("doc:{}", r.path), NodeKind::Document, &r.path, None);
+        *mentions.entry((d, e)).or_default() += 1;
+        chunks.entry(r.chunk_id.clone()).or_default().push(e);
+    }
+
+    let mut co: BTreeMap<(usize, usize), u32> = BTreeMap::new();
+    for mut ents in chunks.into_values() {
+        ents.sort_unstable();
+        ents.dedup();
+        ents.truncate(MAX_ENTITIES_PER_CHUNK);
+        for i in 0..ents.len() {
+            for j in i + 1..ents.len() {
+                *co.entry((ents[i], ents[j])).or_default() += 1;
+            }
+        }
+    }
+
+    let mut edges = vec![];
+    for ((d, e), w) in mentions {
+        nodes[e].weight += w;
+        nodes[d].weight += 1;
+        edges.push(GraphEdge {
+            a: d,
+            b: e,
+            kind: EdgeKind::Mentions,
+            weight: w,
+        });
+    }
+    for ((a, b), w) in co {
+        edges.push(GraphEdge {
+            a,
+            b,
+            kind: EdgeKind::CoOccurs,
+            weight: w,
+        });
+    }
+
+    Ok(KnowledgeGraph::from_parts(crate::catalog::now_epoch_secs(), nodes, edges))
+}
+
+/// Rebuild from the entities table and persist.
+pub async fn rebuild(state: &SharedState) -> Result<Value, ToolError> {
+    ensure_db(state)?;
+    let graph = build(state).await?;
+    graph.save(&graph_file(&state.data_dir)).await?;
+    let summary = summary(&graph);
+    state.graph_stale.store(false, Ordering::Relaxed);
+    *state.graph.lock().await = Some(graph);
+    Ok(summary)
+}
+
+fn summary(graph: &KnowledgeGraph) -> Value {
+    serde_json::json!({
+        "nodes": graph.node_count(),
+        "edges": graph.edge_count(),
+        "built_at_epoch_secs": graph.built_at_epoch_secs,
+    })
+}
+
+/// Run `f` against a current graph: load from disk on first use, rebuild when stale.
+async fn with_graph<T>(state: &SharedState, f: impl FnOnce(&KnowledgeGraph) -> T) -> Result<T, ToolError> {
+    ensure_db(state)?;
+    let mut cache = state.graph.lock().await;
+    let stale = state.graph_stale.swap(false, Ordering::Relaxed);
+    if cache.is_none() && !stale {
+        *cache = KnowledgeGraph::load(&graph_file(&state.data_dir)).await;
+    }
+    if cache.is_none() || stale {
+        let graph = match build(state).await {
+            Ok(g) => g,
+            Err(e) => {
+                state.graph_stale.store(stale, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        if let Err(e) = graph.save(&graph_file(&state.data_dir)).await {
+            tracing::warn!("{e}");
+        }
+        *cache = Some(graph);
+    }
+    match cache.as_ref() {
+        Some(g) => Ok(f(g)),
+        None => Err("Knowledge graph is unavailable".into()),
+    }
+}
+
+fn ensure_db(state: &SharedState) -> Result<(), ToolError> {
+    if state.db.is_enabled() {
+        return Ok(());
+    }
+    let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+    Err(ToolError::kb_disabled(reason))
+}
+
+#[derive(Debug, Deserialize)]
+struct NeighborsArgs {
+    node: String,
+    #[serde(default)]
+    depth: Option<usize>,
+    #[serde(default)]
+    kind: Option<NodeKind>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PathArgs {
+    from: String,
+    to: String,
+    #[serde(default)]
+    max_hops: Option<usize>,
+}
+
+/// MCP tool entrypoint (`silo_graph_neighbors`).
+pub async fn neighbors_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: NeighborsArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let depth = args.depth.unwrap_or(1).clamp(1, 3);
+    let limit = args.limit.unwrap_or(25).clamp(1, 500);
+    Ok(with_graph(state, |g| {
+        let Some(start) = g.resolve(&args.node) else {
+            return Err(format!("No graph node matches {:?}", args.node));
+        };
+        Ok(serde_json::json!({
+            "node": g.nodes[start],
+            "neighbors": g.neighbors(start, depth, args.kind, limit),
+        }))
+    })
+    .await??)
+}
+
+/// MCP tool entrypoint (`silo_graph_path`).
+pub async fn path_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: PathArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let max_hops = args.max_hops.unwrap_or(4).clamp(1, 8);
+    with_graph(state, |g| {
+        let from = g
+            .resolve(&args.from)
+            .ok_or_else(|| format!("No graph node matches {:?}", args.from))?;
+        let to = g
+            .resolve(&args.to)
+            .ok_or_else(|| format!("No graph node matches {:?}", args.to))?;
+        let path = g.path(from, to, max_hops);
+        Ok(serde_json::json!({
+            "from": g.nodes[from],
+            "to": g.nodes[to],
+            "connected": path.is_some(),
+            "hops": path.as_ref().map(|p| p.len().saturating_sub(1)),
+            "path": path.unwrap_or_default(),
+        }))
+    })
+    .await?
+}
@@ -0,0 +1,331 @@
+//! Exact-string / regex search (`silo_grep`), for when the words are known and vector search is
+//! the wrong tool.
+//!
+//! Two sources:
+//! - `index` (default): the stored chunk text of indexed files, including PDFs and other
+//!   extracted formats. Chunks are whitespace-joined, so there are no lines: each match comes
+//!   with a character window around it. Matches in the overlap of two chunks are reported once.
+//! - `files`: the files under the configured roots (extension allow-list and exclude globs
+//!   apply), re-read now, with line numbers and context lines.
+//!
+//! Locked collections are skipped in both.
+
+use crate::database::preview;
+use crate::errors::ToolError;
+use crate::filesystem::walk_files;
+use crate::state::SharedState;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MAX_MATCHES: usize = 100;
+const MAX_MATCHES: usize = 1000;
+const DEFAULT_CONTEXT: usize = 2;
+const MAX_CONTEXT: usize = 10;
+/// Characters on each side of an index match.
+const INDEX_WINDOW_CHARS: usize = 120;
+/// Longer lines (minified files) are cut in the output.
+const MAX_LINE_CHARS: usize = 400;
+/// Files read per call with `source: "files"`.
+const MAX_SCAN_FILES: u64 = 5000;
+/// Indexed files whose chunks are fetched per store query.
+const PATH_BATCH: usize = 100;
+/// Compiled-pattern size cap, so a pathological regex fails fast instead of eating memory.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrepSource {
+    #[default]
+    Index,
+    Files,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrepArgs {
+    pub pattern: String,
+    /// Match `pattern` as a literal string instead of a regex.
+    #[serde(default)]
+    pub literal: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub source: GrepSource,
+    /// Only paths matching one of these globs (e.g. `**/*.rs`).
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Skip paths matching any of these globs.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Context lines before and after each match (`files` source; default 2, max 10).
+    #[serde(default)]
+    pub context: Option<usize>,
+    #[serde(default)]
+    pub max_matches: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrepMatch {
+    pub path: String,
+    /// 1-based line (`files` source).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Chunk the match was found in (`index` source).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    /// The matching line, or the window around the match.
+    pub text: String,
+    /// The matched text itself.
+    pub matched: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GrepResult {
+    pub matches: Vec<GrepMatch>,
+    pub files_scanned: usize,
+    pub files_matched: usize,
+    /// Stopped at `max_matches` (or the file scan limit).
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// MCP tool entrypoint (`silo_grep`).
+pub async fn grep_tool(state: &SharedState, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+    let args: GrepArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let result = grep(state, args).await?;
+    serde_json::to_value(result).map_err(|e| format!("failed to serialize grep result: {e}").into())
+}
+
+pub async fn grep(state: &SharedState, args: GrepArgs) -> Result<GrepResult, ToolError> {
+    if args.pattern.is_empty() {
+        return Err("`pattern` must not be empty".into());
+    }
+    let pattern = if args.literal {
+        regex::escape(&args.pattern)
+    } else {
+        args.pattern.clone()
+    };
+    let re = RegexBuilder::new(&pattern)
+        .case_insensitive(args.case_insensitive)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {e}"))?;
+    let include = glob_set(&args.include)?;
+    let exclude = glob_set(&args.exclude)?;
+    let filter = PathFilter { include, exclude };
+    let max_matches = args.max_matches.unwrap_or(DEFAULT_MAX_MATCHES).clamp(1, MAX_MATCHES);
+    let context = args.context.unwrap_or(DEFAULT_CONTEXT).min(MAX_CONTEXT);
+
+    match args.source {
+        GrepSource::Index => Ok(grep_index(state, &re, &filter, max_matches).await?),
+        GrepSource::Files => grep_files(state, &re, &filter, max_matches, context).await,
+    }
+}
+
+struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    fn allows(&self, path: &str) -> bool {
+        self.include.as_ref().is_none_or(|g| g.is_match(path)) && !self.exclude.as_ref().is_some_and(|g| g.is_match(path))
+    }
+}
+
+pub(crate) fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pat in patterns {
+        let pat = crate::state::expand_tilde(pat).to_string_lossy().to_string();
+        builder.add(Glob::new(&pat).map_err(|e| format!("Invalid glob `{pat}`: {e}"))?);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Failed to build globset: {e}"))
+}
+
+async fn grep_index(
+    state: &SharedState,
+    re: &Regex,
+    filter: &PathFilter,
+    max_matches: usize,
+) -> Result<GrepResult, String> {
+    if !state.db.is_enabled() {
+        return Err(state.db.disabled_reason().unwrap_or("DB disabled").to_string());
+    }
+    let locked = crate::access::locked_paths(state).await;
+    let paths: Vec<String> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| !e.is_evicted() && e.chunks > 0)
+        .map(|e| e.path.clone())
+        .filter(|p| filter.allows(p) && locked.collection_of(p).is_none())
+        .collect();
+
+    let mut result = GrepResult::default();
+    for batch in paths.chunks(PATH_BATCH) {
+        let mut rows = state.db.chunks_for_paths(batch).await.map_err(|e| e.to_string())?;
+        rows.sort_by(|a, b| (&a.path, a.chunk_index).cmp(&(&b.path, b.chunk_index)));
+        result.files_scanned += batch.len();
+
+        let mut current: Option<&str> = None;
+        let mut seen_tokens: HashSet<usize> = HashSet::new();
+        let mut counted = false;
+        for row in &rows {
+            if current != Some(row.path.as_str()) {
+                current = Some(row.path.as_str());
+                seen_tokens.clear();
+                counted = false;
+            }
+            for m in re.find_iter(&row.content) {
+                // Chunks overlap: identify a match by its absolute token position in the file.
+                let token = row.start_token + row.content[..m.start()].matches(' ').count();
+                if !seen_tokens.insert(token) {
+                    continue;
+                }
+                if result.matches.len() >= max_matches {
+                    result.truncated = true;
+                    return Ok(result);
+                }
+                if !counted {
+                    result.files_matched += 1;
+                    counted = true;
+                }
+                result.matches.push(GrepMatch {
+                    path: row.path.clone(),
+                    line: None,
+                    chunk_index: Some(row.chunk_index),
+                    page: row.page,
+                    text: window(&row.content, m.start(), m.end()),
+                    matched: m.as_str().to_string(),
+                    before: vec![],
+                    after: vec![],
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// `INDEX_WINDOW_CHARS` characters on each side of `start..end`, with `…` where cut.
+fn window(text: &str, start: usize, end: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(INDEX_WINDOW_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(INDEX_WINDOW_CHARS)
+        .map_or(text.len(), |(i, _)| end + i);
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    out.push_str(&text[from..to]);
+    if to < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+async fn grep_files(
+    state: &SharedState,
+    re: &Regex,
+    filter: &PathFilter,
+    max_matches: usize,
+    context: usize,
+) -> Result<GrepResult, ToolError> {
+    let policy = state
+        .filesystem_policy()
+        .await
+        .ok_or_else(ToolError::no_filesystem_policy)?;
+    let roots = state.filesystem_roots().await;
+    let locked = crate::access::locked_paths(state).await;
+
+    let mut files: Vec<PathBuf> = vec![];
+    let walk = walk_files(&roots, &policy, false, MAX_SCAN_FILES, |_, path, meta| {
+        let path_str = path.to_string_lossy();
+        let wanted = policy.extension_allowed(path)
+            && meta.len() <= policy.max_file_size_bytes
+            && filter.allows(&path_str)
+            && locked.collection_of(&path_str).is_none();
+        if wanted {
+            files.push(path.to_path_buf());
+        }
+    })
+    .await;
+    files.sort();
+
+    let mut result = GrepResult {
+        truncated: walk.truncated,
+        ..GrepResult::default()
+    };
+    for path in files {
+        result.files_scanned += 1;
+        let text = match crate::extract::extract_text(&path, policy.max_text_bytes).await {
+            Ok(extracted) => extracted.text,
+            Err(e) => {
+                if result.errors.len() < 20 {
+                    result.errors.push(format!("{}: {e}", path.display()));
+                }
+                continue;
+            }
+        };
+        let before = result.matches.len();
+        let full = grep_lines(&path, &text, re, context, max_matches, &mut result.matches);
+        if result.matches.len() > before {
+            result.files_matched += 1;
+        }
+        if full {
+            result.truncated = true;
+            break;
+        }
+    }
+    Ok(result)
+}
+
+/// Append line matches of `text` to `out`; `true` once `max_matches` is reached.
+fn grep_lines(path: &Path, text: &str, re: &Regex, context: usize, max_matches: usize, out: &mut Vec<GrepMatch>) -> bool {
+    let lines: Vec<&str> = text.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(m) = re.find(line) else {
+            continue;
+        };
+        if out.len() >= max_matches {
+            return true;
+        }
+        out.push(GrepMatch {
+            path: path.to_string_lossy().to_string(),
+            line: Some(i + 1),
+            chunk_index: None,
+            page: None,
+            text: preview(line, MAX_LINE_CHARS),
+            matched: m.as_str().to_string(),
+            before: lines[i.saturating_sub(context)..i]
+                .iter()
+                .map(|l| preview(l, MAX_LINE_CHARS))
+                .collect(),
+            after: lines[i + 1..(i + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| preview(l, MAX_LINE_CHARS))
+                .collect(),
+        });
+    }
+    false
+}
@@ -0,0 +1,150 @@
+//! Localized tool descriptions, tool errors, notifications and result labels (`locale` config).
+//!
+//! English is the source language and lives in the code. A catalog maps message keys to
+//! translations: `tool.<name>` for tool descriptions, `error.<code>` for tool errors, `notify.*`
+//! for notifications and `format.*` for results rendered as text (`crate::render`). Catalogs for
+//! a few languages are built in (`locales/`) and may be partial; `<data_dir>/locales/<lang>.json`
+//! adds or overrides keys (e.g. for another language). Missing keys fall back to English.
+//!
+//! Tool errors are translated from their code (`crate::errors`); errors without one stay English.
+
+use crate::errors::ToolError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Built-in catalogs: language -> JSON object of key -> translation.
+const BUILT_IN: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("fr", include_str!("../locales/fr.json")),
+];
+
+/// Translations for one locale.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// Normalized locale (`de`, `pt-BR`); `en` when nothing is translated.
+    pub locale: String,
+    strings: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn english() -> Self {
+        Self {
+            locale: "en".to_string(),
+            strings: HashMap::new(),
+        }
+    }
+
+    /// Built-in and user translations for `locale` (language with an optional region, e.g.
+    /// `pt-BR` falls back to `pt`).
+    pub fn load(locale: &str, data_dir: &Path) -> Self {
+        let locale = normalize(locale);
+        let language = locale.split('-').next().unwrap_or_default().to_string();
+        let mut strings = HashMap::new();
+        let mut candidates = vec![language.clone()];
+        if locale != language {
+            candidates.push(locale.clone());
+        }
+        // Language first, so region-specific keys win.
+        for name in &candidates {
+            if let Some((_, json)) = BUILT_IN.iter().find(|(lang, _)| lang == name) {
+                merge_json(&mut strings, json, &format!("built-in {name}"));
+            }
+            let user = data_dir.join("locales").join(format!("{name}.json"));
+            if let Ok(json) = std::fs::read_to_string(&user) {
+                merge_json(&mut strings, &json, &user.display().to_string());
+            }
+        }
+        Self { locale, strings }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+
+    /// Translated description of a tool, if the catalog has one.
+    pub fn tool_description(&self, tool: &str) -> Option<&str> {
+        self.get(&format!("tool.{tool}"))
+    }
+
+    /// The translation of `key` (else `english`), with `{name}` placeholders filled from `args`.
+    pub fn text(&self, key: &str, english: &str, args: &[(&str, &str)]) -> String {
+        fill(self.get(key).unwrap_or(english), args)
+    }
+
+    /// A tool error in this locale (unchanged when it has no code or no translation).
+    pub fn localize_error(&self, error: &ToolError) -> String {
+        let Some(code) = error.code else {
+            return error.message.clone();
+        };
+        let args: Vec<(&str, &str)> = error.args.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.text(&format!("error.{}", code.as_str()), &error.message, &args)
+    }
+}
+
+/// The catalog of the configured locale, reloaded when the locale changes.
+#[derive(Default)]
+pub struct Translations {
+    current: Mutex<Option<Arc<Catalog>>>,
+}
+
+impl Translations {
+    pub fn catalog(&self, locale: &str, data_dir: &Path) -> Arc<Catalog> {
+        let locale = resolve(locale);
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        match current.as_ref() {
+            Some(c) if c.locale == normalize(&locale) => c.clone(),
+            _ => {
+                let catalog = Arc::new(Catalog::load(&locale, data_dir));
+                *current = Some(catalog.clone());
+                catalog
+            }
+        }
+    }
+}
+
+/// `system` means the locale of the environment (`LC_ALL`, `LC_MESSAGES`, `LANG`).
+fn resolve(locale: &str) -> String {
+    if locale != "system" {
+        return locale.to_string();
+    }
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty() && v != "C" && v != "POSIX")
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// `de_DE.UTF-8` -> `de-DE`, `FR` -> `fr`.
+fn normalize(locale: &str) -> String {
+    let base = locale.split(['.', '@']).next().unwrap_or_default().trim();
+    let mut parts = base.split(['_', '-']).filter(|p| !p.is_empty());
+    let language = parts.next().unwrap_or("en").to_ascii_lowercase();
+    match parts.next() {
+        Some(region) => format!("{language}-{}", region.to_ascii_uppercase()),
+        None => language,
+    }
+}
+
+fn merge_json(strings: &mut HashMap<String, String>, json: &str, source: &str) {
+    match serde_json::from_str::<HashMap<String, Value>>(json) {
+        Ok(map) => {
+            for (key, value) in map {
+                if let Value::String(s) = value {
+                    strings.insert(key, s);
+                }
+            }
+        }
+        Err(e) => tracing::warn!("Ignoring translations in {source}: {e}"),
+    }
+}
+
+pub(crate) fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    let mut out = template.to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), value);
+    }
+    out
+}
@@ -0,0 +1,125 @@
+//! Dry-run "git status" for the knowledge base (`silo_index_diff`).
+//!
+//! Scans the roots with the indexing policy and compares every candidate with the catalog by
+//! size and mtime. Nothing is read or written beyond file metadata.
+
+use crate::errors::ToolError;
+use crate::filesystem::{modified_epoch_secs, scan_candidates, ScanOptions};
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexDiffArgs {
+    /// Paths listed per category.
+    #[serde(default)]
+    pub max_samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    pub path: String,
+    pub size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_epoch_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexDiff {
+    /// Candidates not in the catalog.
+    pub new: u64,
+    /// Candidates whose size or mtime differs from the catalog.
+    pub changed: u64,
+    pub unchanged: u64,
+    /// Catalog entries whose file no longer exists.
+    pub deleted: u64,
+    /// Catalog entries whose file exists but the policy no longer indexes (excluded, too large, ...).
+    pub ineligible: u64,
+    pub new_bytes: u64,
+    pub changed_bytes: u64,
+    pub sample_new: Vec<DiffEntry>,
+    pub sample_changed: Vec<DiffEntry>,
+    pub sample_deleted: Vec<String>,
+    pub sample_ineligible: Vec<String>,
+}
+
+/// MCP tool entrypoint (`silo_index_diff`).
+pub async fn index_diff_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: IndexDiffArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let diff = index_diff(state, &args).await?;
+    serde_json::to_value(diff).map_err(|e| format!("failed to serialize index diff: {e}").into())
+}
+
+pub async fn index_diff(state: &SharedState, args: &IndexDiffArgs) -> Result<IndexDiff, ToolError> {
+    let Some(policy) = state.filesystem_policy().await else {
+        return Err(ToolError::no_filesystem_policy());
+    };
+    let roots = state.filesystem_roots().await;
+    let max_samples = args.max_samples.unwrap_or(50).min(5000);
+
+    let mut candidates: Vec<DiffEntry> = vec![];
+    let scan_opts = ScanOptions {
+        max_sample_candidates: 0,
+        max_sample_skipped: 0,
+    };
+    scan_candidates(roots, &policy, scan_opts, |path, meta| {
+        candidates.push(DiffEntry {
+            path: path.to_string_lossy().to_string(),
+            size_bytes: meta.len(),
+            modified_epoch_secs: modified_epoch_secs(meta),
+        });
+    })
+    .await;
+
+    let mut diff = IndexDiff::default();
+    let missing: Vec<String> = {
+        let catalog = state.catalog.read().await;
+        let mut seen: HashSet<&str> = HashSet::new();
+        for c in &candidates {
+            seen.insert(c.path.as_str());
+            match catalog.get(&c.path) {
+                None => {
+                    diff.new += 1;
+                    diff.new_bytes += c.size_bytes;
+                    diff.sample_new.push(c.clone());
+                }
+                Some(e)
+                    if e.size_bytes != Some(c.size_bytes as i64)
+                        || e.modified_epoch_secs != c.modified_epoch_secs =>
+                {
+                    diff.changed += 1;
+                    diff.changed_bytes += c.size_bytes;
+                    diff.sample_changed.push(c.clone());
+                }
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+        catalog
+            .entries()
+            .filter(|e| e.is_file() && !seen.contains(e.path.as_str()))
+            .map(|e| e.path.clone())
+            .collect()
+    };
+
+    for path in missing {
+        if tokio::fs::symlink_metadata(&path).await.is_ok() {
+            diff.ineligible += 1;
+            diff.sample_ineligible.push(path);
+        } else {
+            diff.deleted += 1;
+            diff.sample_deleted.push(path);
+        }
+    }
+
+    // Deterministic samples, independent of traversal order.
+    diff.sample_new.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.sample_new.truncate(max_samples);
+    diff.sample_changed.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.sample_changed.truncate(max_samples);
+    diff.sample_deleted.sort();
+    diff.sample_deleted.truncate(max_samples);
+    diff.sample_ineligible.sort();
+    diff.sample_ineligible.truncate(max_samples);
+    Ok(diff)
+}
@@ -1,9 +1,21 @@
-use crate::config::CompiledFileSystemPolicy;
+use crate::boilerplate::Boilerplate;
+use crate::catalog::{CatalogHandle, FileCatalog};
+use crate::config::{CompiledFileSystemPolicy, IndexOrder, NetworkMountMode};
 use crate::database::DatabaseHandle;
 use crate::embed::EmbedderHandle;
-use crate::ingest::process_file;
+use crate::entities::EntityExtractor;
+use crate::filesystem::{
+    entry_metadata, has_skip_marker, inode, is_cloud_placeholder, modified_epoch_secs, read_dir_limited,
+    VisitedDirs,
+};
+use crate::ingest::{process_file, IngestStats};
+use crate::metrics::{Metrics, SlowFile, SLOW_FILE_MS};
+use crate::mounts::NetworkMounts;
+use crate::sysindex::initial_stack;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 
@@ -14,9 +26,27 @@ pub struct IndexSummary {
     pub scanned_dirs: u64,
     pub ingested: u64,
     pub skipped: u64,
+    /// Directories cut short by `max_depth` or `max_files_per_directory`.
+    pub truncated_dirs: u64,
     pub errors: u64,
     pub stored: u64,
+    /// Files found under a new path with the same content as a vanished indexed file: their
+    /// chunks were moved to the new path instead of being re-embedded.
+    pub moved: u64,
     pub sample_errors: Vec<String>,
+    /// Network mounts met during the walk, with how they were handled.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub network_mounts: Vec<String>,
+    /// Roots whose files were listed by the OS index instead of walked.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system_index_roots: Vec<String>,
+    /// Documents evicted afterwards to stay within `storage.max_index_bytes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eviction: Option<crate::budget::EvictionReport>,
+    /// Files that took `metrics::SLOW_FILE_MS` or longer to ingest (huge or pathological files,
+    /// e.g. minified bundles), slowest first, with the stage that took the time.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub slow_files: Vec<SlowFile>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +54,13 @@ pub struct IndexOptions {
     pub max_files: Option<u64>,
     pub concurrency: usize,
     pub max_sample_errors: usize,
+    /// Updated as the run goes (`AppState::index_progress`, reported by `silo_search`).
+    pub progress: Option<Arc<IndexProgress>>,
+    /// Per-file ingest timings go here (`AppState::metrics`, reported by `silo_metrics`).
+    pub metrics: Option<Arc<Metrics>>,
+    /// Boilerplate chunks are dropped against this table (`AppState::boilerplate`) when the
+    /// policy's `drop_boilerplate` is on.
+    pub boilerplate: Option<Arc<Boilerplate>>,
 }
 
 impl Default for IndexOptions {
@@ -32,39 +69,150 @@ impl Default for IndexOptions {
             max_files: None,
             concurrency: 2,
             max_sample_errors: 20,
+            progress: None,
+            metrics: None,
+            boilerplate: None,
         }
     }
 }
 
+/// Progress of the bulk index runs in flight, so searches can say their results are partial.
+#[derive(Debug, Default)]
+pub struct IndexProgress {
+    runs: AtomicUsize,
+    walking: AtomicBool,
+    queued: AtomicU64,
+    done: AtomicU64,
+    started_epoch_secs: AtomicI64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexProgressSnapshot {
+    /// `walking` (listing files; totals still growing) or `ingesting`.
+    pub phase: &'static str,
+    pub files_done: u64,
+    pub files_pending: u64,
+    /// Of the files queued for ingestion (`None` while walking).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f32>,
+    pub started_epoch_secs: i64,
+}
+
+impl IndexProgress {
+    /// `None` when no index run is in progress.
+    pub fn snapshot(&self) -> Option<IndexProgressSnapshot> {
+        if self.runs.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let walking = self.walking.load(Ordering::Relaxed);
+        let queued = self.queued.load(Ordering::Relaxed);
+        let done = self.done.load(Ordering::Relaxed).min(queued);
+        let percent = match queued {
+            0 => 100.0,
+            n => (done as f32 * 1000.0 / n as f32).round() / 10.0,
+        };
+        Some(IndexProgressSnapshot {
+            phase: if walking { "walking" } else { "ingesting" },
+            files_done: done,
+            files_pending: queued - done,
+            percent: (!walking).then_some(percent),
+            started_epoch_secs: self.started_epoch_secs.load(Ordering::Relaxed),
+        })
+    }
+
+    fn start(self: &Arc<Self>) -> ProgressRun {
+        if self.runs.fetch_add(1, Ordering::Relaxed) == 0 {
+            self.queued.store(0, Ordering::Relaxed);
+            self.done.store(0, Ordering::Relaxed);
+            self.started_epoch_secs.store(crate::catalog::now_epoch_secs(), Ordering::Relaxed);
+        }
+        self.walking.store(true, Ordering::Relaxed);
+        ProgressRun(self.clone())
+    }
+}
+
+/// One run's hold on `IndexProgress`; released on drop, so a cancelled run does not look busy.
+struct ProgressRun(Arc<IndexProgress>);
+
+impl ProgressRun {
+    /// `n` more files to ingest while the walk goes on (directory order).
+    fn found(&self, n: u64) {
+        self.0.queued.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// The walk is over; `n` more files to ingest.
+    fn queued(&self, n: u64) {
+        self.0.queued.fetch_add(n, Ordering::Relaxed);
+        self.0.walking.store(false, Ordering::Relaxed);
+    }
+
+    fn done(&self) {
+        self.0.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ProgressRun {
+    fn drop(&mut self) {
+        self.0.runs.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 /// MVP bulk indexer: traverse roots and ingest eligible files.
 ///
 /// Notes:
 /// - Uses the same policy as preview scan
 /// - Limits concurrency to avoid oversubscribing CPU (embedding runs in blocking threads)
+/// - Files on network mounts get their own (lower) concurrency and size cap, or are skipped
 pub async fn index_roots(
     roots: Vec<PathBuf>,
     policy: Arc<CompiledFileSystemPolicy>,
     db: DatabaseHandle,
     embedder: EmbedderHandle,
+    entities: Option<Arc<EntityExtractor>>,
+    catalog: CatalogHandle,
     opts: IndexOptions,
 ) -> IndexSummary {
-    let sem = Arc::new(Semaphore::new(opts.concurrency.max(1)));
+    let progress = opts.progress.as_ref().map(IndexProgress::start);
+    let net = policy.network_mounts.clone();
+    let mounts = NetworkMounts::for_mode(net.mode).await;
+    let mut network_mounts: Vec<String> = vec![];
 
     let mut scanned_files = 0u64;
     let mut scanned_dirs = 0u64;
-    let mut ingested = 0u64;
     let mut skipped = 0u64;
-    let mut errors = 0u64;
-    let mut stored = 0u64;
-    let mut sample_errors: Vec<String> = vec![];
+    let mut truncated_dirs = 0u64;
+    let mut moved = 0u64;
+    let mut moves = MoveCandidates::new(&*catalog.read().await);
 
-    let mut stack: Vec<PathBuf> = roots.clone();
-    let mut tasks = tokio::task::JoinSet::new();
+    let mut visited = VisitedDirs::default();
+    let (mut stack, system_index_roots) = initial_stack(&roots, &policy).await;
+    let mut run = Ingestion {
+        db: &db,
+        embedder: &embedder,
+        entities: entities.as_ref(),
+        policy: &policy,
+        catalog: &catalog,
+        opts: &opts,
+        progress: progress.as_ref(),
+        sem: Arc::new(Semaphore::new(opts.concurrency.max(1))),
+        net_sem: Arc::new(Semaphore::new(net.concurrency.max(1))),
+        tasks: tokio::task::JoinSet::new(),
+        ingested: 0,
+        stored: 0,
+        errors: 0,
+        sample_errors: vec![],
+        slow_files: vec![],
+    };
+    let note_error = |run: &mut Ingestion, msg: String| push_err(&mut run.sample_errors, opts.max_sample_errors, msg);
 
-    let mut ingested_target = opts.max_files.unwrap_or(u64::MAX);
-
-    while let Some(current) = stack.pop() {
-        if ingested >= ingested_target {
+    let ingested_target = opts.max_files.unwrap_or(u64::MAX);
+    // In directory order, files are ingested as the walk finds them; other orders need the whole
+    // list first.
+    let streaming = policy.index_order == IndexOrder::Walk;
+    let mut found = 0u64;
+    let mut queue: Vec<QueuedFile> = vec![];
+    while let Some((current, depth)) = stack.pop() {
+        if streaming && found >= ingested_target {
             break;
         }
 
@@ -73,33 +221,54 @@ pub async fn index_roots(
             continue;
         }
 
-        let meta = match tokio::fs::symlink_metadata(&current).await {
-            Ok(m) => m,
-            Err(e) => {
+        let meta = match entry_metadata(&current, policy.follow_symlinks).await {
+            Ok(Some(m)) => m,
+            Ok(None) => {
                 skipped += 1;
-                push_err(&mut sample_errors, opts.max_sample_errors, format!("metadata {}: {e}", current.display()));
+                continue;
+            }
+            Err(reason) => {
+                skipped += 1;
+                note_error(&mut run, format!("{}: {reason}", current.display()));
                 continue;
             }
         };
 
         let ft = meta.file_type();
-        if ft.is_symlink() && !policy.follow_symlinks {
-            skipped += 1;
-            continue;
-        }
-
         if ft.is_dir() {
+            if !visited.first_visit(&meta) {
+                skipped += 1;
+                continue;
+            }
+            if depth > policy.max_depth {
+                skipped += 1;
+                truncated_dirs += 1;
+                continue;
+            }
             scanned_dirs += 1;
-            let mut rd = match tokio::fs::read_dir(&current).await {
+            if let Some(m) = mounts.find(&current) {
+                m.note(net.mode, &mut network_mounts);
+                if net.mode == NetworkMountMode::Skip {
+                    skipped += 1;
+                    continue;
+                }
+            }
+            if policy.honor_skip_markers && has_skip_marker(&current).await {
+                skipped += 1;
+                continue;
+            }
+            let (children, dropped) = match read_dir_limited(&current, policy.max_files_per_directory).await {
                 Ok(r) => r,
                 Err(e) => {
                     skipped += 1;
-                    push_err(&mut sample_errors, opts.max_sample_errors, format!("read_dir {}: {e}", current.display()));
+                    note_error(&mut run, format!("read_dir {}: {e}", current.display()));
                     continue;
                 }
             };
-            while let Ok(Some(entry)) = rd.next_entry().await {
-                stack.push(entry.path());
+            stack.extend(children.into_iter().map(|c| (c, depth + 1)));
+            if dropped > 0 {
+                skipped += dropped as u64;
+                truncated_dirs += 1;
             }
             continue;
         }
@@ -116,87 +285,311 @@ pub async fn index_roots(
             continue;
         }
 
+        let remote = mounts.find(&current).is_some();
+        let max_size = if remote {
+            policy.max_file_size_for(&current).min(net.max_file_size_bytes)
+        } else {
+            policy.max_file_size_for(&current)
+        };
         let size = meta.len();
-        if size > policy.max_file_size_bytes {
+        if size > max_size {
+            skipped += 1;
+            continue;
+        }
+
+        if policy.skip_placeholder(&current) && is_cloud_placeholder(&current, &meta) {
             skipped += 1;
             continue;
         }
 
-        // Spawn ingestion task (bounded by semaphore)
-        let permit = match sem.clone().acquire_owned().await {
+        // Catalog entry of this very version of the file (same size and mtime), if any.
+        let (unchanged, evicted) = catalog
+            .read()
+            .await
+            .get(&current.to_string_lossy())
+            .filter(|e| e.size_bytes == Some(size as i64) && e.modified_epoch_secs == modified_epoch_secs(&meta))
+            .map_or((false, false), |e| (true, e.is_evicted()));
+        // Evicted by the index size budget (`crate::budget`): stays out until the file changes.
+        if evicted {
+            skipped += 1;
+            continue;
+        }
+
+        // The content detectors read the file; an unchanged indexed file already passed them.
+        if !unchanged && policy.skip_generated && crate::generated::detect(&current).await.is_some() {
+            skipped += 1;
+            continue;
+        }
+
+        if !unchanged && crate::encrypted::detect_locked(&current).await.is_some() {
+            skipped += 1;
+            continue;
+        }
+
+        // A path new to the catalog may be a renamed or moved file: move its rows instead.
+        let path_str = current.to_string_lossy().to_string();
+        let moved_from = if !moves.is_empty() && catalog.read().await.get(&path_str).is_none() {
+            moves.take_match(&current, &meta, policy.max_text_bytes).await
+        } else {
+            None
+        };
+        if let Some(from) = moved_from {
+            match db.rename_file(&from, &path_str).await {
+                Ok(n) if n > 0 => {
+                    catalog.write().await.rename(&from, &path_str, inode(&meta));
+                    tracing::info!("Indexed file moved: {from} -> {path_str}");
+                    moved += 1;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to move index rows {from} -> {path_str}: {e}"),
+            }
+        }
+
+        let file = QueuedFile {
+            path_str,
+            size,
+            modified_epoch_secs: modified_epoch_secs(&meta),
+            remote,
+        };
+        found += 1;
+        if streaming {
+            if let Some(p) = &progress {
+                p.found(1);
+            }
+            if !run.ingest(file).await {
+                break;
+            }
+        } else {
+            queue.push(file);
+        }
+    }
+
+    // Other orders decide what is searchable soonest from the whole list.
+    order_queue(&mut queue, policy.index_order);
+    let take = usize::try_from(ingested_target).unwrap_or(usize::MAX);
+    queue.truncate(take);
+    if let Some(p) = &progress {
+        p.queued(queue.len() as u64);
+    }
+    for file in queue {
+        if !run.ingest(file).await {
+            break;
+        }
+    }
+    run.finish().await;
+
+    IndexSummary {
+        roots: roots.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        scanned_files,
+        scanned_dirs,
+        ingested: run.ingested,
+        skipped,
+        truncated_dirs,
+        errors: run.errors,
+        stored: run.stored,
+        moved,
+        sample_errors: run.sample_errors,
+        network_mounts,
+        system_index_roots,
+        eviction: None,
+        slow_files: run.slow_files,
+    }
+}
+
+/// The ingestion side of a run: `process_file` tasks, bounded by the semaphores (network files
+/// also by the network one), and the tally of their results.
+struct Ingestion<'a> {
+    db: &'a DatabaseHandle,
+    embedder: &'a EmbedderHandle,
+    entities: Option<&'a Arc<EntityExtractor>>,
+    policy: &'a Arc<CompiledFileSystemPolicy>,
+    catalog: &'a CatalogHandle,
+    opts: &'a IndexOptions,
+    progress: Option<&'a ProgressRun>,
+    sem: Arc<Semaphore>,
+    net_sem: Arc<Semaphore>,
+    tasks: tokio::task::JoinSet<(String, Result<IngestStats, String>)>,
+    ingested: u64,
+    stored: u64,
+    errors: u64,
+    sample_errors: Vec<String>,
+    slow_files: Vec<SlowFile>,
+}
+
+impl Ingestion<'_> {
+    /// Start ingesting `file`; `false` when the run cannot take more (semaphore closed).
+    async fn ingest(&mut self, file: QueuedFile) -> bool {
+        let QueuedFile { path_str, remote, .. } = file;
+        let net_permit = if remote {
+            match self.net_sem.clone().acquire_owned().await {
+                Ok(p) => Some(p),
+                Err(_) => return false,
+            }
+        } else {
+            None
+        };
+        let permit = match self.sem.clone().acquire_owned().await {
             Ok(p) => p,
-            Err(_) => break,
+            Err(_) => return false,
         };
 
-        let db = db.clone();
-        let embedder = embedder.clone();
-        let policy = policy.clone();
-        let path_str = current.to_string_lossy().to_string();
-        let max_text_bytes = policy.max_text_bytes;
-        // NOTE: This is an MVP default. In the next small patch we'll thread per-config values here.
-        let chunk_tokens = 500usize;
-        let chunk_overlap = 50usize;
-
-        tasks.spawn(async move {
-            let _permit = permit;
-            let res = process_file(&db, &embedder, &path_str, max_text_bytes, chunk_tokens, chunk_overlap).await;
+        let db = self.db.clone();
+        let embedder = self.embedder.clone();
+        let entities = self.entities.cloned();
+        let max_text_bytes = self.policy.max_text_bytes_for(Path::new(&path_str));
+        let chunking = self.policy.chunking.clone();
+        let boilerplate = self.opts.boilerplate.clone().filter(|_| self.policy.drop_boilerplate);
+
+        self.tasks.spawn(async move {
+            let _permits = (permit, net_permit);
+            let res = process_file(
+                &db,
+                &embedder,
+                entities.as_deref(),
+                boilerplate.as_deref(),
+                &path_str,
+                max_text_bytes,
+                &chunking,
+            )
+            .await;
             (path_str, res)
         });
 
         // Drain finished tasks opportunistically
-        while tasks.len() >= opts.concurrency * 2 {
-            if let Some(joined) = tasks.join_next().await {
-                match joined {
-                    Ok((_path, Ok(stats))) => {
-                        ingested += 1;
-                        if stats.stored {
-                            stored += 1;
-                        }
-                    }
-                    Ok((path, Err(e))) => {
-                        errors += 1;
-                        push_err(&mut sample_errors, opts.max_sample_errors, format!("ingest {path}: {e}"));
-                    }
-                    Err(e) => {
-                        errors += 1;
-                        push_err(&mut sample_errors, opts.max_sample_errors, format!("task join error: {e}"));
-                    }
-                }
-            } else {
+        while self.tasks.len() >= self.opts.concurrency * 2 {
+            let Some(joined) = self.tasks.join_next().await else {
                 break;
-            }
+            };
+            self.record(joined).await;
+        }
+        true
+    }
+
+    /// Wait for the remaining tasks.
+    async fn finish(&mut self) {
+        while let Some(joined) = self.tasks.join_next().await {
+            self.record(joined).await;
         }
     }
 
-    // Finish remaining tasks
-    while let Some(joined) = tasks.join_next().await {
+    async fn record(&mut self, joined: Result<(String, Result<IngestStats, String>), tokio::task::JoinError>) {
+        if let Some(p) = self.progress {
+            p.done();
+        }
+        let max_errors = self.opts.max_sample_errors;
         match joined {
             Ok((_path, Ok(stats))) => {
-                ingested += 1;
+                self.ingested += 1;
+                record_timing(self.opts.metrics.as_deref(), &mut self.slow_files, &stats);
                 if stats.stored {
-                    stored += 1;
+                    self.stored += 1;
+                    self.catalog.write().await.record_ingest(&stats);
                 }
             }
             Ok((path, Err(e))) => {
-                errors += 1;
-                push_err(&mut sample_errors, opts.max_sample_errors, format!("ingest {path}: {e}"));
+                self.errors += 1;
+                push_err(&mut self.sample_errors, max_errors, format!("ingest {path}: {e}"));
+                self.catalog.write().await.record_failure(&path, &e);
             }
             Err(e) => {
-                errors += 1;
-                push_err(&mut sample_errors, opts.max_sample_errors, format!("task join error: {e}"));
+                self.errors += 1;
+                push_err(&mut self.sample_errors, max_errors, format!("task join error: {e}"));
             }
         }
     }
+}
 
-    IndexSummary {
-        roots: roots.iter().map(|p| p.to_string_lossy().to_string()).collect(),
-        scanned_files,
-        scanned_dirs,
-        ingested,
-        skipped,
-        errors,
-        stored,
-        sample_errors,
+/// An eligible file found by the walk, waiting to be ingested.
+struct QueuedFile {
+    path_str: String,
+    size: u64,
+    modified_epoch_secs: Option<i64>,
+    remote: bool,
+}
+
+/// Sort the walk's files into ingestion order. `Recent` goes by modification day (newest
+/// first), smaller files first within a day; files without a time go last.
+fn order_queue(queue: &mut [QueuedFile], order: IndexOrder) {
+    const SECS_PER_DAY: i64 = 24 * 60 * 60;
+    match order {
+        IndexOrder::Walk => {}
+        IndexOrder::Small => queue.sort_by_key(|f| f.size),
+        IndexOrder::Recent => queue.sort_by_key(|f| {
+            let day = f.modified_epoch_secs.map(|t| t.div_euclid(SECS_PER_DAY));
+            (std::cmp::Reverse(day), f.size)
+        }),
+    }
+}
+
+/// An indexed file that may have been renamed.
+struct MoveCandidate {
+    path: String,
+    inode: Option<u64>,
+    modified_epoch_secs: Option<i64>,
+    file_hash: Option<String>,
+}
+
+/// Indexed files by size, for spotting renames during a walk. A file found under a new path
+/// takes over an entry whose file is gone and that matches it by inode (plus size and mtime)
+/// or by extracted-text hash (a move across filesystems gets a new inode).
+struct MoveCandidates {
+    by_size: HashMap<i64, Vec<MoveCandidate>>,
+}
+
+impl MoveCandidates {
+    fn new(catalog: &FileCatalog) -> Self {
+        let mut by_size: HashMap<i64, Vec<MoveCandidate>> = HashMap::new();
+        let indexed = catalog
+            .entries()
+            .filter(|e| e.is_file() && !e.is_evicted() && e.chunks > 0);
+        for e in indexed {
+            let Some(size) = e.size_bytes else {
+                continue;
+            };
+            by_size.entry(size).or_default().push(MoveCandidate {
+                path: e.path.clone(),
+                inode: e.inode,
+                modified_epoch_secs: e.modified_epoch_secs,
+                file_hash: e.file_hash.clone(),
+            });
+        }
+        Self { by_size }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.by_size.is_empty()
+    }
+
+    /// The indexed path `path` was moved from, if any (claimed: it is not offered again).
+    async fn take_match(&mut self, path: &Path, meta: &std::fs::Metadata, max_text_bytes: u64) -> Option<String> {
+        let candidates = self.by_size.get_mut(&(meta.len() as i64))?;
+        let ino = inode(meta);
+        let mtime = modified_epoch_secs(meta);
+        let mut hash: Option<Option<String>> = None;
+        for i in 0..candidates.len() {
+            let c = &candidates[i];
+            // Still there: a copy, not a move.
+            if tokio::fs::try_exists(&c.path).await.unwrap_or(true) {
+                continue;
+            }
+            let same_inode = ino.is_some() && c.inode == ino && c.modified_epoch_secs == mtime;
+            let same_content = same_inode || {
+                if hash.is_none() {
+                    hash = Some(
+                        crate::extract::extract_text(path, max_text_bytes)
+                            .await
+                            .ok()
+                            .map(|x| blake3::hash(x.text.as_bytes()).to_hex().to_string()),
+                    );
+                }
+                hash.as_ref().is_some_and(|h| h.is_some() && *h == c.file_hash)
+            };
+            if same_content {
+                return Some(candidates.swap_remove(i).path);
+            }
+        }
+        None
     }
 }
 
@@ -207,3 +600,18 @@ fn push_err(out: &mut Vec<String>, max: usize, msg: String) {
 }
 
 
+
+/// Files kept in `IndexSummary::slow_files`.
+const MAX_SLOW_FILES: usize = 20;
+
+fn record_timing(metrics: Option<&Metrics>, slow_files: &mut Vec<SlowFile>, stats: &IngestStats) {
+    if let Some(m) = metrics {
+        m.record_ingest(stats);
+    }
+    if stats.timings.total_ms < SLOW_FILE_MS {
+        return;
+    }
+    let at = slow_files.partition_point(|f| f.elapsed_ms >= stats.timings.total_ms);
+    slow_files.insert(at, SlowFile::from_stats(stats));
+    slow_files.truncate(MAX_SLOW_FILES);
+}
@@ -1,10 +1,18 @@
-use crate::chunk::chunk_by_whitespace_tokens;
-use crate::database::DatabaseHandle;
+use crate::boilerplate::Boilerplate;
+use crate::chunk::{cap_chunks, chunk_records, chunk_text, ChunkRules};
+use crate::database::{chunk_row_id, ChunkRow, DatabaseHandle, EntityRow};
 use crate::embed::EmbedderHandle;
-use crate::extract::extract_text;
+use crate::entities::{entity_rows, EntityExtractor};
+use crate::extract::{extract_text, ExtractKind, ExtractResult};
+use crate::logs::LogEntries;
+use crate::predicate::Predicate;
 use crate::state::expand_tilde;
+use crate::xattrs::{read_file_attributes, FileAttributes};
 use blake3::Hash;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct IngestStats {
@@ -14,58 +22,250 @@ pub struct IngestStats {
     pub chunk_tokens: usize,
     pub chunk_overlap_tokens: usize,
     pub chunks: usize,
+    /// Chunks the text split into when `max_chunks_per_file` dropped some (`chunks` were kept).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks_before_cap: Option<usize>,
+    /// Chunks dropped as boilerplate (`filesystem.drop_boilerplate`).
+    #[serde(skip_serializing_if = "is_zero")]
+    pub boilerplate_chunks: usize,
     pub stored: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_mtime_epoch_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_created_epoch_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_hash: Option<String>,
+    /// Inode at ingest time (Unix), for spotting renames (`crate::indexer`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_inode: Option<u64>,
+    /// Chunks whose text was already stored for this path, so they were not embedded again.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub reused_chunks: usize,
+    /// Entity mentions stored (only when the entity stage is enabled).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<usize>,
+    /// User tags from extended attributes (Finder tags, `user.xdg.tags`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub file_tags: Vec<String>,
+    /// Finder comment or `user.xdg.comment`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_comment: Option<String>,
+    pub timings: IngestTimings,
+    /// Definitions found in a source file (`crate::symbols`), for the catalog.
+    #[serde(skip)]
+    pub symbols: Vec<crate::symbols::Symbol>,
+}
+
+/// Wall time of each stage of one `process_file` call, in milliseconds (`crate::metrics`).
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct IngestTimings {
+    pub extract_ms: u64,
+    pub chunk_ms: u64,
+    /// Includes looking up reusable vectors of the previous version.
+    pub embed_ms: u64,
+    pub store_ms: u64,
+    #[serde(skip_serializing_if = "is_zero_ms")]
+    pub entities_ms: u64,
+    pub total_ms: u64,
+}
+
+impl IngestTimings {
+    /// The stage that took longest (the earliest one on a tie).
+    pub fn slowest_stage(&self) -> &'static str {
+        [
+            ("extract", self.extract_ms),
+            ("chunk", self.chunk_ms),
+            ("embed", self.embed_ms),
+            ("store", self.store_ms),
+            ("entities", self.entities_ms),
+        ]
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, ms)| *ms)
+        .map_or("extract", |(stage, _)| stage)
+    }
+}
+
+fn is_zero_ms(ms: &u64) -> bool {
+    *ms == 0
+}
+
+fn elapsed_ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
+}
+
+/// Text extracted from a document, and the file facts stored with its chunks.
+pub struct ExtractedDocument {
+    /// Path the chunks are stored under (a file path, or a connector URL).
+    pub path: String,
+    pub extracted: ExtractResult,
+    pub size_bytes: Option<i64>,
+    pub mtime_epoch_secs: Option<i64>,
+    pub created_epoch_secs: Option<i64>,
+    pub inode: Option<u64>,
+    pub attrs: FileAttributes,
 }
 
 /// Process a single file:
 /// 1) extract text
-/// 2) chunk into ~token windows (whitespace tokens)
-/// 3) embed (placeholder zeros for now)
+/// 2) chunk into ~token windows (whitespace tokens; logs along time windows, JSON by records),
+///    capped per file, minus boilerplate chunks
+/// 3) embed (placeholder zeros for now); chunks already stored with the same text keep their vectors
 /// 4) store chunks into LanceDB when enabled
+/// 5) optionally extract entity mentions per chunk
 pub async fn process_file(
     db: &DatabaseHandle,
     embedder: &EmbedderHandle,
+    entities: Option<&EntityExtractor>,
+    boilerplate: Option<&Boilerplate>,
     path: &str,
     max_text_bytes: u64,
-    chunk_tokens: usize,
-    chunk_overlap_tokens: usize,
+    chunking: &ChunkRules,
 ) -> Result<IngestStats, String> {
+    let started = Instant::now();
     let path = expand_tilde(path);
-    let path_str = path.to_string_lossy().to_string();
 
     let file_meta = tokio::fs::metadata(&path)
         .await
         .ok();
-    let file_size_bytes = file_meta.as_ref().map(|m| m.len() as i64);
-    let file_mtime_epoch_secs = file_meta
+    let size_bytes = file_meta.as_ref().map(|m| m.len() as i64);
+    let mtime_epoch_secs = file_meta
         .as_ref()
         .and_then(|m| m.modified().ok())
         .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
         .map(|d| d.as_secs() as i64);
+    // Not available on every filesystem (e.g. some Linux mounts).
+    let created_epoch_secs = file_meta
+        .as_ref()
+        .and_then(|m| m.created().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+    let inode = file_meta.as_ref().and_then(crate::filesystem::inode);
+
+    let attrs = read_file_attributes(&path);
+
+    // Encrypted documents fail with the reason the index walk skips them for.
+    if let Some(found) = crate::encrypted::detect_locked(&path).await {
+        return Err(found.describe().to_string());
+    }
 
+    let t = Instant::now();
     let extracted = extract_text(&path, max_text_bytes).await?;
+    let extract_ms = elapsed_ms(t);
+
+    let doc = ExtractedDocument {
+        path: path.to_string_lossy().to_string(),
+        extracted,
+        size_bytes,
+        mtime_epoch_secs,
+        created_epoch_secs,
+        inode,
+        attrs,
+    };
+    let mut stats = process_extracted(db, embedder, entities, boilerplate, doc, chunking).await?;
+    stats.timings.extract_ms = extract_ms;
+    stats.timings.total_ms = elapsed_ms(started);
+    Ok(stats)
+}
+
+/// Steps 2-5 of `process_file`, for text that is already extracted (e.g. content uploaded over
+/// MCP, see `crate::upload`).
+pub async fn process_extracted(
+    db: &DatabaseHandle,
+    embedder: &EmbedderHandle,
+    entities: Option<&EntityExtractor>,
+    boilerplate: Option<&Boilerplate>,
+    doc: ExtractedDocument,
+    chunking: &ChunkRules,
+) -> Result<IngestStats, String> {
+    let started = Instant::now();
+    let mut timings = IngestTimings::default();
+    let ExtractedDocument {
+        path: path_str,
+        extracted,
+        size_bytes: file_size_bytes,
+        mtime_epoch_secs: file_mtime_epoch_secs,
+        created_epoch_secs: file_created_epoch_secs,
+        inode: file_inode,
+        attrs,
+    } = doc;
+    let (cap, log_window_minutes) = (chunking.cap, chunking.log_window_minutes);
+    let chunking = chunking.for_path(Path::new(&path_str));
     let extracted_chars = extracted.text.chars().count();
     let file_hash = Some(blake3::hash(extracted.text.as_bytes()).to_hex().to_string());
+    let symbols = match extracted.kind {
+        ExtractKind::Text => crate::symbols::extract(&path_str, &extracted.text),
+        _ => vec![],
+    };
 
-    let chunks = chunk_by_whitespace_tokens(&extracted.text, chunk_tokens, chunk_overlap_tokens);
+    let t = Instant::now();
+    let log = match extracted.kind {
+        ExtractKind::Log => Some(LogEntries::parse(&extracted.text, file_mtime_epoch_secs)).filter(|l| !l.is_untimed()),
+        _ => None,
+    };
+    let mut chunks = match (&log, &extracted.kind) {
+        (Some(log), _) => log.chunks(&extracted.text, &chunking, log_window_minutes),
+        (None, ExtractKind::Json) => chunk_records(&extracted.text, &chunking),
+        _ => chunk_text(&extracted.text, &chunking),
+    };
+    let chunks_before_cap = match cap {
+        Some(cap) if chunks.len() > cap.max_chunks => {
+            let before = chunks.len();
+            chunks = cap_chunks(chunks, cap);
+            Some(before)
+        }
+        _ => None,
+    };
+    let boilerplate_chunks = boilerplate.map_or(0, |b| b.filter(&path_str, &mut chunks));
+    timings.chunk_ms = elapsed_ms(t);
 
-    let embeddings = embedder
-        .embed_texts(chunks.iter().map(|c| c.text.clone()).collect())
-        .await?;
-    if embeddings.len() != chunks.len() {
+    // An edited file keeps the vectors (and entity mentions) of chunks whose text is unchanged.
+    let t = Instant::now();
+    let previous = previous_version(db, &path_str, entities.is_some()).await;
+    let reusable: HashMap<&str, &[f32]> = previous
+        .chunks
+        .iter()
+        .filter(|r| r.embedding.len() == embedder.dim())
+        .map(|r| (r.content.as_str(), r.embedding.as_slice()))
+        .collect();
+    let mut embeddings: Vec<Vec<f32>> = chunks
+        .iter()
+        .map(|c| reusable.get(c.text.as_str()).map(|e| e.to_vec()).unwrap_or_default())
+        .collect();
+    let changed: Vec<usize> = (0..chunks.len()).filter(|&i| embeddings[i].is_empty()).collect();
+    let fresh = if changed.is_empty() {
+        vec![]
+    } else {
+        embedder
+            .embed_texts(changed.iter().map(|&i| chunks[i].text.clone()).collect())
+            .await?
+    };
+    if fresh.len() != changed.len() {
         return Err(format!(
             "embedder returned {} vectors for {} chunks",
-            embeddings.len(),
-            chunks.len()
+            fresh.len(),
+            changed.len()
         ));
     }
+    for (i, v) in changed.iter().zip(fresh) {
+        embeddings[*i] = v;
+    }
+    let reused_chunks = chunks.len() - changed.len();
+    timings.embed_ms = elapsed_ms(t);
 
     // Store only if DB is enabled (feature `lancedb` and initialization succeeded).
+    let t = Instant::now();
     let stored = if db.is_enabled() {
         let rows = chunks
             .iter()
             .zip(embeddings.iter())
-            .map(|(ch, emb)| (ch.index, ch.start_token, ch.end_token, ch.text.clone(), emb.clone()))
+            .map(|(ch, emb)| {
+                let page = extracted.page_of(ch.start_token);
+                let time = log.as_ref().and_then(|l| l.time_span(ch.start_token, ch.end_token));
+                (ch.index, ch.start_token, ch.end_token, page, time, ch.text.clone(), emb.clone())
+            })
             .collect::<Vec<_>>();
 
         db.replace_file_chunks(
@@ -73,6 +273,7 @@ pub async fn process_file(
             file_mtime_epoch_secs,
             file_size_bytes,
             file_hash.clone(),
+            &attrs.tags,
             rows,
         )
         .await
@@ -81,18 +282,96 @@ pub async fn process_file(
     } else {
         false
     };
+    timings.store_ms = elapsed_ms(t);
+
+    let t = Instant::now();
+    let entity_count = match entities {
+        Some(extractor) if stored => {
+            let mut rows = vec![];
+            for ch in &chunks {
+                let chunk_id = chunk_row_id(&path_str, ch.index, &ch.text);
+                // Same id: same path, position and text, so the same mentions.
+                if let Some(kept) = previous.entities.get(&chunk_id) {
+                    rows.extend(kept.iter().cloned());
+                    continue;
+                }
+                let mentions = extractor.extract(&ch.text).await;
+                rows.extend(entity_rows(&chunk_id, &path_str, ch.index, mentions));
+            }
+            let n = rows.len();
+            db.add_entities(rows)
+                .await
+                .map_err(|e| format!("DB write failed (entities): {e}"))?;
+            Some(n)
+        }
+        _ => None,
+    };
+    if entity_count.is_some() {
+        timings.entities_ms = elapsed_ms(t);
+    }
+    timings.total_ms = elapsed_ms(started);
 
     Ok(IngestStats {
         path: path_str,
         extracted_kind: format!("{:?}", extracted.kind).to_lowercase(),
         extracted_chars,
-        chunk_tokens,
-        chunk_overlap_tokens,
+        chunk_tokens: chunking.tokens,
+        chunk_overlap_tokens: chunking.overlap_tokens,
         chunks: chunks.len(),
+        chunks_before_cap,
+        boilerplate_chunks,
         stored,
+        file_size_bytes,
+        file_mtime_epoch_secs,
+        file_created_epoch_secs,
+        file_hash,
+        file_inode,
+        reused_chunks,
+        entities: entity_count,
+        file_tags: attrs.tags,
+        file_comment: attrs.comment,
+        timings,
+        symbols,
     })
 }
 
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// What is stored for a path before it is re-ingested.
+#[derive(Default)]
+struct PreviousVersion {
+    chunks: Vec<ChunkRow>,
+    /// Entity mentions by chunk id. Empty when the file was stored without the entity stage, so
+    /// turning the stage on still extracts every chunk.
+    entities: HashMap<String, Vec<EntityRow>>,
+}
+
+async fn previous_version(db: &DatabaseHandle, path: &str, with_entities: bool) -> PreviousVersion {
+    if !db.is_enabled() {
+        return PreviousVersion::default();
+    }
+    let chunks = match db.chunks_for_paths(&[path.to_string()]).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("Re-embedding all of {path}: reading its stored chunks failed: {e}");
+            return PreviousVersion::default();
+        }
+    };
+    let mut entities: HashMap<String, Vec<EntityRow>> = HashMap::new();
+    if with_entities && !chunks.is_empty() {
+        let rows = db
+            .find_entities(&Predicate::eq("path", path), u32::MAX as usize)
+            .await
+            .unwrap_or_default();
+        for row in rows {
+            entities.entry(row.chunk_id.clone()).or_default().push(row);
+        }
+    }
+    PreviousVersion { chunks, entities }
+}
+
 fn chunk_id(path: &str, chunk_index: usize, text: &str) -> String {
     let mut hasher = blake3::Hasher::new();
     hasher.update(path.as_bytes());
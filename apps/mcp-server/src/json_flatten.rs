@@ -0,0 +1,151 @@
+//! JSON and JSONL documents as `key.path: value` lines, so config files and data dumps are found
+//! by field names and values instead of by fragments of raw syntax.
+//!
+//! ```text
+//! {"server": {"port": 8080, "tags": ["a", "b"]}, "users": [{"name": "Ann"}, {"name": "Bo"}]}
+//!
+//! server.port: 8080
+//! server.tags: a, b
+//!
+//! users[0].name: Ann
+//!
+//! users[1].name: Bo
+//! ```
+//!
+//! A record is a JSONL line, an element of a top-level array, or an element of an array of
+//! objects that is not itself inside a record (arrays nested in a record stay in it). Records are
+//! separated by blank lines, which chunking keeps whole where it can (`crate::chunk::chunk_records`).
+//! Arrays of scalars become one comma-separated line. Strings are written without quotes, on one
+//! line. A `.json` file that does not parse (comments, trailing
+//! commas, cut at `max_text_bytes`) is indexed as plain text, as is a `.jsonl` file where most
+//! lines fail; single bad lines are kept as they are.
+
+use serde_json::Value;
+use std::path::Path;
+
+/// Extensions read as JSON Lines (one value per line); `json` is one value.
+const JSONL_EXTENSIONS: &[&str] = &["jsonl", "ndjson"];
+pub const EXTENSIONS: &[&str] = &["json", "jsonl", "ndjson"];
+
+/// The flattened text of a JSON or JSONL document (`None`: not valid JSON).
+pub fn flatten_document(path: &Path, text: &str) -> Option<String> {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    let mut out = Flattener::default();
+    if JSONL_EXTENSIONS.contains(&ext.as_str()) {
+        let (mut parsed, mut failed) = (0usize, 0usize);
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            out.start_record();
+            match serde_json::from_str::<Value>(line) {
+                Ok(value) => {
+                    parsed += 1;
+                    out.record("", &value);
+                }
+                Err(_) => {
+                    failed += 1;
+                    out.lines.push(line.to_string());
+                }
+            }
+        }
+        return (parsed > failed).then(|| out.finish());
+    }
+    let value: Value = serde_json::from_str(text).ok()?;
+    match &value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                out.start_record();
+                out.record(&format!("[{i}]"), item);
+            }
+        }
+        _ => out.value("", &value),
+    }
+    Some(out.finish())
+}
+
+#[derive(Default)]
+struct Flattener {
+    lines: Vec<String>,
+    /// Inside a record: arrays of objects no longer start records.
+    in_record: bool,
+}
+
+impl Flattener {
+    /// Blank line before a new record (none at the top).
+    fn start_record(&mut self) {
+        if self.lines.last().is_some_and(|l| !l.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn record(&mut self, path: &str, value: &Value) {
+        let outer = std::mem::replace(&mut self.in_record, true);
+        self.value(path, value);
+        self.in_record = outer;
+    }
+
+    fn value(&mut self, path: &str, value: &Value) {
+        match value {
+            Value::Object(map) if map.is_empty() => self.line(path, "{}"),
+            Value::Object(map) => {
+                for (key, v) in map {
+                    self.value(&join_key(path, key), v);
+                }
+            }
+            Value::Array(items) if items.is_empty() => self.line(path, "[]"),
+            Value::Array(items) if items.iter().all(is_scalar) => {
+                let joined = items.iter().map(scalar_text).collect::<Vec<_>>().join(", ");
+                self.line(path, &joined);
+            }
+            Value::Array(items) if self.in_record => {
+                for (i, item) in items.iter().enumerate() {
+                    self.value(&format!("{path}[{i}]"), item);
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.start_record();
+                    self.record(&format!("{path}[{i}]"), item);
+                }
+                // Whatever follows the array is not part of its last record.
+                self.start_record();
+            }
+            scalar => self.line(path, &scalar_text(scalar)),
+        }
+    }
+
+    fn line(&mut self, path: &str, value: &str) {
+        self.lines.push(if path.is_empty() {
+            value.to_string()
+        } else {
+            format!("{path}: {value}")
+        });
+    }
+
+    fn finish(mut self) -> String {
+        while self.lines.last().is_some_and(|l| l.is_empty()) {
+            self.lines.pop();
+        }
+        self.lines.join("\n")
+    }
+}
+
+/// `path.key`, or `path["key"]` when the key would not read back as one segment.
+fn join_key(path: &str, key: &str) -> String {
+    let plain = !key.is_empty() && !key.contains(['.', '[', ']', '"', ':']) && !key.contains(char::is_whitespace);
+    match (path.is_empty(), plain) {
+        (true, true) => key.to_string(),
+        (false, true) => format!("{path}.{key}"),
+        (_, false) => format!("{path}[{}]", Value::String(key.to_string())),
+    }
+}
+
+fn is_scalar(v: &Value) -> bool {
+    !v.is_object() && !v.is_array()
+}
+
+/// Strings without quotes and on one line; other scalars as JSON.
+fn scalar_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.split_whitespace().collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
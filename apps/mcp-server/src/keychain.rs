@@ -0,0 +1,49 @@
+//! The OS keychain (macOS Keychain, Windows Credential Manager, the Secret Service on Linux),
+//! through the `keyring` crate. Entries are stored under the service name `silo`; the caller picks
+//! the account name. Keychain calls block (and may show an unlock prompt), so they run on the
+//! blocking thread pool.
+//!
+//! Account names are namespaced by their user: `secret:<name>` (`crate::secrets`) and
+//! `document:<path>` (`crate::passwords`).
+
+const SERVICE: &str = "silo";
+
+/// The secret stored for `account` (`None`: no entry).
+pub async fn get(account: &str) -> Result<Option<String>, String> {
+    run(account, |entry| match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    })
+    .await
+}
+
+/// Store `secret` for `account`, replacing what was there.
+pub async fn set(account: &str, secret: &str) -> Result<(), String> {
+    let secret = secret.to_string();
+    run(account, move |entry| entry.set_password(&secret)).await
+}
+
+/// Remove the entry for `account`; `false` when there was none.
+pub async fn delete(account: &str) -> Result<bool, String> {
+    run(account, |entry| match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e),
+    })
+    .await
+}
+
+async fn run<T, F>(account: &str, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&keyring::Entry) -> keyring::Result<T> + Send + 'static,
+{
+    let account = account.to_string();
+    tokio::task::spawn_blocking(move || {
+        let entry = keyring::Entry::new(SERVICE, &account).map_err(|e| format!("OS keychain: {e}"))?;
+        f(&entry).map_err(|e| format!("OS keychain ({account}): {e}"))
+    })
+    .await
+    .map_err(|e| format!("OS keychain task failed: {e}"))?
+}
@@ -0,0 +1,107 @@
+//! Open or reveal files with the OS (the desktop app's actions on search hits).
+//!
+//! Paths get the same validation as the read tools (`validate_safe_path`), must exist, and are
+//! handed to the platform launcher as a single argument (never through a shell: on Windows that
+//! means Explorer rather than `cmd /C start`, since `cmd` re-parses `&`, `|`, `^` and `%VAR%`,
+//! which are legal in file names).
+
+use crate::state::expand_tilde;
+use crate::tools::validate_safe_path;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Open `path` in its default application.
+pub async fn open_path(path: &str) -> Result<(), String> {
+    let path = checked_path(path).await?;
+    if cfg!(windows) {
+        // Explorer opens a file in its default application; it exits with 1 even when it worked.
+        return Command::new("explorer")
+            .arg(&path)
+            .status()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to run explorer: {e}"));
+    }
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(&path);
+    run(cmd, "open").await
+}
+
+/// Show `path` selected in Finder / Explorer / the file manager.
+pub async fn reveal_path(path: &str) -> Result<(), String> {
+    let path = checked_path(path).await?;
+    if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("open");
+        cmd.arg("-R").arg(&path);
+        return run(cmd, "open").await;
+    }
+    if cfg!(windows) {
+        // Explorer exits with 1 even when it worked.
+        return Command::new("explorer")
+            .arg(format!("/select,{}", path.display()))
+            .status()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to run explorer: {e}"));
+    }
+    // freedesktop file managers (Nautilus, Dolphin, ...); otherwise open the parent folder.
+    let mut cmd = Command::new("dbus-send");
+    cmd.args([
+        "--session",
+        "--dest=org.freedesktop.FileManager1",
+        "--type=method_call",
+        "/org/freedesktop/FileManager1",
+        "org.freedesktop.FileManager1.ShowItems",
+    ])
+    .arg(format!("array:string:{}", file_uri(&path)))
+    .arg("string:");
+    if run(cmd, "dbus-send").await.is_ok() {
+        return Ok(());
+    }
+    let parent = path.parent().unwrap_or(&path);
+    let mut cmd = Command::new("xdg-open");
+    cmd.arg(parent);
+    run(cmd, "xdg-open").await
+}
+
+/// Validated, existing local path (shared with `crate::preview`).
+pub(crate) async fn checked_path(path: &str) -> Result<PathBuf, String> {
+    if path.contains("://") {
+        return Err(format!("{path} is not a file (indexed from a non-file source)"));
+    }
+    let path = expand_tilde(path);
+    validate_safe_path(&path)?;
+    tokio::fs::symlink_metadata(&path)
+        .await
+        .map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+async fn run(mut cmd: Command, name: &str) -> Result<(), String> {
+    let status = cmd
+        .status()
+        .await
+        .map_err(|e| format!("Failed to run {name}: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{name} failed (exit={status})"))
+    }
+}
+
+/// `file://` URI with everything but unreserved characters and `/` percent-encoded.
+fn file_uri(path: &Path) -> String {
+    let mut out = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
@@ -1,14 +1,92 @@
+pub mod access;
 pub mod agent;
+pub mod ask;
+pub mod backup;
+pub mod bench;
+pub mod boilerplate;
+pub mod budget;
+pub mod cache;
+pub mod catalog;
+pub mod chat;
+pub mod chat_export;
+pub mod cleanup;
 pub mod chunk;
+pub mod chunk_lookup;
 pub mod config;
+pub mod config_update;
+pub mod consent;
+pub mod coverage;
+pub mod daemon;
 pub mod database;
+pub mod datalock;
+pub mod disk;
 pub mod embed;
+pub mod encrypted;
+pub mod entities;
+pub mod errors;
+pub mod eval;
 pub mod extract;
+pub mod federation;
+pub mod fields;
 pub mod filesystem;
+pub mod generated;
+pub mod graph;
+pub mod grep;
+pub mod i18n;
+pub mod index_diff;
 pub mod indexer;
 pub mod ingest;
+pub mod json_flatten;
+pub mod keychain;
+pub mod launch;
 pub mod llm;
+pub mod logfile;
+pub mod library;
+pub mod logging;
+pub mod logs;
+pub mod maintenance;
+pub mod messages;
+pub mod metrics;
+pub mod models;
+pub mod memstore;
+pub mod mounts;
+pub mod newsletters;
+pub mod notes;
+pub mod notify;
+pub mod passwords;
+pub mod policy_explain;
+pub mod predicate;
+pub mod preview;
+pub mod prompt;
+pub mod query;
+pub mod quick_search;
+pub mod ratelimit;
+pub mod repo_context;
+pub mod render;
+pub mod responses;
+pub mod retrieval_debug;
+pub mod rewrite;
+pub mod sandbox;
+pub mod screenshots;
+pub mod secrets;
+pub mod store;
+pub mod structured;
+pub mod sysindex;
+pub mod tagging;
+pub mod timeline;
+pub mod traffic;
+pub mod trash;
+pub mod upload;
+pub mod verify;
+pub mod web;
+pub mod writes;
+pub mod xattrs;
 pub mod api;
 pub mod server;
+pub mod search_paths;
+pub mod session_index;
+#[cfg(feature = "sqlite")]
+pub mod sqlitestore;
 pub mod state;
+pub mod symbols;
 pub mod tools;
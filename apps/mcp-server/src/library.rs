@@ -0,0 +1,671 @@
+//! Reference library source (`library` source, `silo_index_library`): a Zotero library or a
+//! BibTeX file, and the documents attached to its entries.
+//!
+//! Zotero is read from a private copy of `zotero.sqlite` with the `sqlite3` tool (like
+//! `crate::messages`), so the running app's database is never opened; attachments come from its
+//! `storage` folder (linked files from their path, `attachments:` paths from `attachments_dir`).
+//! A `.bib` file is parsed here; entries name their documents in the `file` field (Zotero,
+//! Better BibTeX and JabRef exports), relative to the `.bib` file.
+//!
+//! Each attachment is stored under its own path, headed with the entry's title, authors, year,
+//! venue, DOI and citation key, and tagged `library` plus the entry's Zotero tags. The entry is
+//! also kept as the file's `reference` in the catalog, so search hits and answer citations carry
+//! it and markdown/plain results cite the work (`crate::render`) instead of the file name.
+//! Attachments that are unchanged, with the same reference, are skipped on the next run.
+
+use crate::config::LibrarySourceConfig;
+use crate::errors::ToolError;
+use crate::extract::{extract_html, extract_text, ExtractResult};
+use crate::ingest::ExtractedDocument;
+use crate::messages::{snapshot_db, sqlite_query};
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_ZOTERO_DB: &str = "~/Zotero/zotero.sqlite";
+/// Authors named in a citation before `et al.`.
+const CITED_AUTHORS: usize = 3;
+
+/// Bibliographic data of a library entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    /// BibTeX key (Zotero: the citation key field, else one made from author, year and title).
+    pub key: String,
+    pub title: String,
+    /// `Last, First` (or a single name for organizations).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    /// Journal, proceedings or book.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The entry formatted for display (APA-like), e.g.
+    /// `Vaswani, A., Shazeer, N., & Parmar, N. (2017). Attention is all you need. NeurIPS.`
+    pub citation: String,
+}
+
+impl Reference {
+    fn new(
+        key: Option<String>,
+        title: String,
+        authors: Vec<String>,
+        year: Option<i32>,
+        venue: Option<String>,
+        doi: Option<String>,
+        url: Option<String>,
+    ) -> Self {
+        let key = key.filter(|k| !k.is_empty()).unwrap_or_else(|| make_key(&authors, year, &title));
+        let mut r = Self {
+            key,
+            title,
+            authors,
+            year,
+            venue,
+            doi,
+            url,
+            citation: String::new(),
+        };
+        r.citation = r.format();
+        r
+    }
+
+    fn format(&self) -> String {
+        let mut names: Vec<String> = self.authors.iter().take(CITED_AUTHORS).map(|a| apa_name(a)).collect();
+        let mut out = match names.len() {
+            0 => String::new(),
+            _ if self.authors.len() > CITED_AUTHORS => format!("{}, et al. ", names.join(", ")),
+            1 => format!("{} ", names[0]),
+            _ => {
+                let last = names.pop().unwrap_or_default();
+                format!("{}, & {last} ", names.join(", "))
+            }
+        };
+        match self.year {
+            Some(year) => out.push_str(&format!("({year}). ")),
+            None => out.push_str("(n.d.). "),
+        }
+        out.push_str(self.title.trim_end_matches('.'));
+        out.push('.');
+        if let Some(venue) = &self.venue {
+            out.push_str(&format!(" {}.", venue.trim_end_matches('.')));
+        }
+        if let Some(doi) = &self.doi {
+            out.push_str(&format!(" https://doi.org/{doi}"));
+        }
+        out
+    }
+
+    /// `Title:`/`Authors:`/... lines heading the attachment's text.
+    fn header(&self) -> String {
+        let year = self.year.map(|y| y.to_string());
+        let authors = self.authors.join("; ");
+        let fields = [
+            ("Title", Some(self.title.as_str())),
+            ("Authors", Some(authors.as_str())),
+            ("Year", year.as_deref()),
+            ("Published in", self.venue.as_deref()),
+            ("DOI", self.doi.as_deref()),
+            ("URL", self.url.as_deref()),
+            ("Citation key", Some(self.key.as_str())),
+        ];
+        let mut out = String::new();
+        for (name, value) in fields {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                out.push_str(&format!("{name}: {value}\n"));
+            }
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// An entry and its documents.
+struct Entry {
+    reference: Reference,
+    tags: Vec<String>,
+    attachments: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryPass {
+    pub library: String,
+    /// Entries read from the library.
+    pub references: usize,
+    /// Attachment files found on disk.
+    pub attachments: usize,
+    /// Ingested in this run.
+    pub indexed: usize,
+    pub unchanged: usize,
+    /// Attachments the library names but that are not on disk.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// MCP tool entrypoint (`silo_index_library`).
+pub async fn index_library_tool(state: &SharedState, _args: Value) -> Result<Value, ToolError> {
+    let pass = index_library(state).await?;
+    serde_json::to_value(pass).map_err(|e| format!("failed to serialize library pass: {e}").into())
+}
+
+/// Read the library and ingest attachments that are new or changed since the last run.
+pub async fn index_library(state: &SharedState) -> Result<LibraryPass, ToolError> {
+    let Some(cfg) = state.library_config().await else {
+        return Err("Library source not configured. Add {\"type\": \"library\"} to `sources` in the config".into());
+    };
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let _pass = state.library_pass.lock().await;
+
+    let library = expand_tilde(&cfg.path.as_deref().unwrap_or(Path::new(DEFAULT_ZOTERO_DB)).to_string_lossy());
+    let is_bibtex = library.extension().is_some_and(|e| e.eq_ignore_ascii_case("bib"));
+    let entries = if is_bibtex {
+        read_bibtex(&library).await?
+    } else {
+        read_zotero(&library, &state.data_dir.join("library"), &cfg).await?
+    };
+    let fs_cfg = state.filesystem_config().await.unwrap_or_default();
+    let mut pass = LibraryPass {
+        library: library.to_string_lossy().to_string(),
+        references: entries.len(),
+        ..LibraryPass::default()
+    };
+
+    let mut references = HashMap::new();
+    for entry in &entries {
+        for path in &entry.attachments {
+            let path_str = path.to_string_lossy().to_string();
+            let Ok(meta) = tokio::fs::metadata(path).await else {
+                pass.missing.push(path_str);
+                continue;
+            };
+            if !meta.is_file() || references.contains_key(&path_str) {
+                continue;
+            }
+            pass.attachments += 1;
+            references.insert(path_str.clone(), entry.reference.clone());
+            let unchanged = state.catalog.read().await.get(&path_str).is_some_and(|e| {
+                e.size_bytes == Some(meta.len() as i64)
+                    && e.modified_epoch_secs == crate::filesystem::modified_epoch_secs(&meta)
+                    && e.reference.as_ref() == Some(&entry.reference)
+            });
+            if unchanged {
+                pass.unchanged += 1;
+                continue;
+            }
+            match store_attachment(state, &fs_cfg, entry, path).await {
+                Ok(()) => pass.indexed += 1,
+                Err(e) => pass.errors.push(format!("{path_str}: {e}")),
+            }
+        }
+    }
+
+    state.catalog.write().await.set_references(references);
+    if pass.indexed > 0 {
+        state.index_changed();
+    }
+    state.save_catalog().await;
+    Ok(pass)
+}
+
+async fn store_attachment(
+    state: &SharedState,
+    fs_cfg: &crate::config::FileSystemSourceConfig,
+    entry: &Entry,
+    path: &Path,
+) -> Result<(), String> {
+    let is_html = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "html" | "htm"));
+    let body = if is_html {
+        extract_html(path, fs_cfg.max_text_bytes).await?.1
+    } else {
+        extract_text(path, fs_cfg.max_text_bytes).await?
+    };
+    let header = entry.reference.header();
+    // Page starts count whitespace tokens, which the header shifts.
+    let shift = header.split_whitespace().count();
+    let extracted = ExtractResult {
+        text: format!("{header}{}", body.text),
+        page_starts: body.page_starts.iter().map(|s| s + shift).collect(),
+        ..body
+    };
+
+    let meta = tokio::fs::metadata(path).await.ok();
+    let mut attrs = crate::xattrs::read_file_attributes(path);
+    for tag in ["library"].into_iter().chain(entry.tags.iter().map(String::as_str)) {
+        let tag = crate::tagging::normalize_tag(tag);
+        if !tag.is_empty() && !attrs.tags.contains(&tag) {
+            attrs.tags.push(tag);
+        }
+    }
+    let doc = ExtractedDocument {
+        path: path.to_string_lossy().to_string(),
+        extracted,
+        size_bytes: meta.as_ref().map(|m| m.len() as i64),
+        mtime_epoch_secs: meta.as_ref().and_then(crate::filesystem::modified_epoch_secs),
+        created_epoch_secs: meta
+            .as_ref()
+            .and_then(|m| m.created().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64),
+        inode: meta.as_ref().and_then(crate::filesystem::inode),
+        attrs,
+    };
+    let stats = crate::ingest::process_extracted(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
+        doc,
+        &fs_cfg.chunking(),
+    )
+    .await?;
+    state.metrics.record_ingest(&stats);
+    state.catalog.write().await.record_ingest(&stats);
+    Ok(())
+}
+
+/// Add each hit's `reference` (from the catalog) to the serialized `hits`.
+pub async fn attach_references(state: &SharedState, hits: &mut Value) {
+    let Some(values) = hits.as_array_mut() else {
+        return;
+    };
+    let catalog = state.catalog.read().await;
+    for value in values {
+        let reference = value
+            .get("path")
+            .and_then(Value::as_str)
+            .and_then(|p| catalog.get(p))
+            .and_then(|e| e.reference.clone());
+        if let Some(reference) = reference {
+            value["reference"] = serde_json::json!(reference);
+        }
+    }
+}
+
+/// The catalog `reference` of `path`, if it is a library attachment.
+pub async fn reference_of(state: &SharedState, path: &str) -> Option<Reference> {
+    state.catalog.read().await.get(path).and_then(|e| e.reference.clone())
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoteroItem {
+    id: i64,
+    title: Option<String>,
+    date: Option<String>,
+    venue: Option<String>,
+    doi: Option<String>,
+    url: Option<String>,
+    citation_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoteroCreator {
+    id: i64,
+    first: Option<String>,
+    last: Option<String>,
+    kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoteroTag {
+    id: i64,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoteroAttachment {
+    parent: i64,
+    key: String,
+    path: String,
+}
+
+const ZOTERO_ITEMS_SQL: &str = "SELECT i.itemID AS id,
+    MAX(CASE WHEN f.fieldName = 'title' THEN v.value END) AS title,
+    MAX(CASE WHEN f.fieldName = 'date' THEN v.value END) AS date,
+    MAX(CASE WHEN f.fieldName IN ('publicationTitle', 'proceedingsTitle', 'bookTitle', 'conferenceName') THEN v.value END) AS venue,
+    MAX(CASE WHEN f.fieldName = 'DOI' THEN v.value END) AS doi,
+    MAX(CASE WHEN f.fieldName = 'url' THEN v.value END) AS url,
+    MAX(CASE WHEN f.fieldName = 'citationKey' THEN v.value END) AS citation_key
+  FROM items i
+  JOIN itemTypes t ON t.itemTypeID = i.itemTypeID
+  LEFT JOIN itemData d ON d.itemID = i.itemID
+  LEFT JOIN fields f ON f.fieldID = d.fieldID
+  LEFT JOIN itemDataValues v ON v.valueID = d.valueID
+  WHERE t.typeName NOT IN ('attachment', 'note', 'annotation')
+    AND i.itemID NOT IN (SELECT itemID FROM deletedItems)
+  GROUP BY i.itemID";
+
+const ZOTERO_CREATORS_SQL: &str = "SELECT ic.itemID AS id, c.firstName AS first, c.lastName AS last, ct.creatorType AS kind
+  FROM itemCreators ic
+  JOIN creators c ON c.creatorID = ic.creatorID
+  JOIN creatorTypes ct ON ct.creatorTypeID = ic.creatorTypeID
+  ORDER BY ic.itemID, ic.orderIndex";
+
+const ZOTERO_TAGS_SQL: &str = "SELECT it.itemID AS id, tg.name AS name FROM itemTags it JOIN tags tg ON tg.tagID = it.tagID";
+
+const ZOTERO_ATTACHMENTS_SQL: &str = "SELECT a.parentItemID AS parent, i.key AS key, a.path AS path
+  FROM itemAttachments a
+  JOIN items i ON i.itemID = a.itemID
+  WHERE a.parentItemID IS NOT NULL AND a.path IS NOT NULL
+    AND a.itemID NOT IN (SELECT itemID FROM deletedItems)";
+
+/// Entries of a Zotero library (regular items; notes and attachments are not entries), read from
+/// a copy in `dir`.
+async fn read_zotero(db: &Path, dir: &Path, cfg: &LibrarySourceConfig) -> Result<Vec<Entry>, String> {
+    if !db.is_file() {
+        return Err(format!(
+            "Zotero database not found at {} (set `path` of the library source)",
+            db.display()
+        ));
+    }
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let copy = snapshot_db(db, dir, "zotero.sqlite").await?;
+    let items: Vec<ZoteroItem> = sqlite_query("sqlite3", &copy, None, ZOTERO_ITEMS_SQL).await?;
+    let creators: Vec<ZoteroCreator> = sqlite_query("sqlite3", &copy, None, ZOTERO_CREATORS_SQL).await?;
+    let tags: Vec<ZoteroTag> = sqlite_query("sqlite3", &copy, None, ZOTERO_TAGS_SQL).await?;
+    let attachments: Vec<ZoteroAttachment> = sqlite_query("sqlite3", &copy, None, ZOTERO_ATTACHMENTS_SQL).await?;
+
+    let storage = db.parent().unwrap_or(Path::new(".")).join("storage");
+    let base = cfg.attachments_dir.as_ref().map(|d| expand_tilde(&d.to_string_lossy()));
+    let mut authors: HashMap<i64, Vec<&ZoteroCreator>> = HashMap::new();
+    for c in &creators {
+        authors.entry(c.id).or_default().push(c);
+    }
+    let mut item_tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for t in tags {
+        item_tags.entry(t.id).or_default().push(t.name);
+    }
+    let mut files: HashMap<i64, Vec<PathBuf>> = HashMap::new();
+    for a in &attachments {
+        let path = if let Some(name) = a.path.strip_prefix("storage:") {
+            Some(storage.join(&a.key).join(name))
+        } else if let Some(rel) = a.path.strip_prefix("attachments:") {
+            base.as_ref().map(|b| b.join(rel))
+        } else {
+            Some(expand_tilde(&a.path))
+        };
+        files.entry(a.parent).or_default().extend(path);
+    }
+
+    let out = items
+        .into_iter()
+        .map(|item| {
+            let creators = authors.remove(&item.id).unwrap_or_default();
+            // Editors only count when nobody is listed as author.
+            let has_authors = creators.iter().any(|c| c.kind.as_deref() == Some("author"));
+            let names = creators
+                .into_iter()
+                .filter(|c| !has_authors || c.kind.as_deref() == Some("author"))
+                .map(|c| match (c.last.as_deref(), c.first.as_deref()) {
+                    (Some(last), Some(first)) if !first.is_empty() => format!("{last}, {first}"),
+                    (Some(last), _) => last.to_string(),
+                    (None, first) => first.unwrap_or_default().to_string(),
+                })
+                .collect();
+            let reference = Reference::new(
+                item.citation_key,
+                item.title.unwrap_or_default(),
+                names,
+                item.date.as_deref().and_then(year_of),
+                item.venue,
+                item.doi,
+                item.url,
+            );
+            Entry {
+                reference,
+                tags: item_tags.remove(&item.id).unwrap_or_default(),
+                attachments: files.remove(&item.id).unwrap_or_default(),
+            }
+        })
+        .collect();
+    Ok(out)
+}
+
+/// Entries of a BibTeX file (`@string` macros are expanded; `@preamble` and `@comment` are
+/// skipped).
+async fn read_bibtex(path: &Path) -> Result<Vec<Entry>, String> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read file {}: {e}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let out = parse_bibtex(&text)
+        .into_iter()
+        .map(|(key, fields)| {
+            let field = |name: &str| fields.get(name).map(|v| clean_tex(v)).filter(|v| !v.is_empty());
+            let authors = field("author")
+                .or_else(|| field("editor"))
+                .map(|a| a.split(" and ").map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect())
+                .unwrap_or_default();
+            let reference = Reference::new(
+                Some(key),
+                field("title").unwrap_or_default(),
+                authors,
+                field("year").or_else(|| field("date")).as_deref().and_then(year_of),
+                field("journal").or_else(|| field("journaltitle")).or_else(|| field("booktitle")),
+                field("doi"),
+                field("url"),
+            );
+            let tags = field("keywords")
+                .map(|k| k.split([',', ';']).map(|t| t.trim().to_string()).collect())
+                .unwrap_or_default();
+            let attachments = fields.get("file").map(|f| bibtex_files(f, dir)).unwrap_or_default();
+            Entry {
+                reference,
+                tags,
+                attachments,
+            }
+        })
+        .collect();
+    Ok(out)
+}
+
+/// `(key, fields)` of each entry; field names lowercased, values without their outer braces or
+/// quotes.
+fn parse_bibtex(text: &str) -> Vec<(String, BTreeMap<String, String>)> {
+    let mut out = vec![];
+    let mut strings = HashMap::new();
+    let mut rest = text;
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let Some(open) = rest.find(['{', '(']) else {
+            break;
+        };
+        let kind = rest[..open].trim().to_ascii_lowercase();
+        let close = if rest.as_bytes()[open] == b'{' { '}' } else { ')' };
+        let body_end = matching(rest, open, close).unwrap_or(rest.len());
+        let body = &rest[open + 1..body_end.min(rest.len())];
+        rest = &rest[(body_end + 1).min(rest.len())..];
+        if kind == "string" {
+            strings.extend(bibtex_fields(body, &strings));
+            continue;
+        }
+        if matches!(kind.as_str(), "preamble" | "comment") || kind.contains(char::is_whitespace) {
+            continue;
+        }
+        let Some((key, fields_text)) = body.split_once(',') else {
+            continue;
+        };
+        out.push((key.trim().to_string(), bibtex_fields(fields_text, &strings)));
+    }
+    out
+}
+
+/// `name = value` pairs, comma-separated.
+fn bibtex_fields(mut text: &str, strings: &HashMap<String, String>) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    while let Some(eq) = text.find('=') {
+        let name = text[..eq].trim().trim_start_matches(',').trim().to_ascii_lowercase();
+        let value_text = text[eq + 1..].trim_start();
+        let (value, used) = bibtex_value(value_text, strings);
+        fields.insert(name, value);
+        text = &value_text[used..];
+    }
+    fields
+}
+
+/// Index of the `close` matching the opener at `open` (nested braces counted).
+fn matching(s: &str, open: usize, close: char) -> Option<usize> {
+    let mut depth = 0i32;
+    for (i, c) in s[open..].char_indices() {
+        match c {
+            '{' | '(' if c == '{' || close == ')' => depth += 1,
+            '}' | ')' if c == close || (c == '}' && close == ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A field value (`{...}`, `"..."`, a number or an `@string` name, `#`-joined parts
+/// concatenated) and the bytes it used, up to the next field.
+fn bibtex_value(s: &str, strings: &HashMap<String, String>) -> (String, usize) {
+    let mut value = String::new();
+    let mut i = 0;
+    loop {
+        let rest = &s[i..];
+        let trimmed = rest.trim_start();
+        i += rest.len() - trimmed.len();
+        match trimmed.chars().next() {
+            Some('{') => {
+                let end = matching(trimmed, 0, '}').unwrap_or(trimmed.len() - 1);
+                value.push_str(&trimmed[1..end]);
+                i += end + 1;
+            }
+            Some('"') => {
+                let end = trimmed[1..].find('"').map_or(trimmed.len() - 1, |p| p + 1);
+                value.push_str(&trimmed[1..end]);
+                i += end + 1;
+            }
+            _ => {
+                let end = trimmed.find([',', '#', '}', '\n']).unwrap_or(trimmed.len());
+                let word = trimmed[..end].trim();
+                value.push_str(strings.get(&word.to_ascii_lowercase()).map_or(word, String::as_str));
+                i += end;
+            }
+        }
+        let rest = s[i..].trim_start();
+        if let Some(after) = rest.strip_prefix('#') {
+            i = s.len() - after.len();
+            continue;
+        }
+        return (value, i);
+    }
+}
+
+/// Paths in a `file` field: `a.pdf;b.pdf`, or `Description:path:type` entries (Zotero, JabRef).
+fn bibtex_files(field: &str, dir: &Path) -> Vec<PathBuf> {
+    field
+        .split(';')
+        .filter_map(|f| {
+            // `\:` escapes a colon (Windows drive letters); none of ours are meaningful.
+            let f = f.replace("\\:", ":").replace("\\_", "_");
+            let parts: Vec<&str> = f.split(':').collect();
+            let path = match parts.len() {
+                0 | 1 => f.trim().to_string(),
+                2 => parts.join(":").trim().to_string(),
+                n => parts[1..n - 1].join(":").trim().to_string(),
+            };
+            (!path.is_empty()).then(|| {
+                let path = expand_tilde(&path);
+                if path.is_absolute() { path } else { dir.join(path) }
+            })
+        })
+        .collect()
+}
+
+/// TeX markup dropped from a field: braces, `\&`-style escapes, and runs of whitespace.
+fn clean_tex(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => {}
+            '\\' => match chars.peek() {
+                Some(&next) if "&%$#_{}".contains(next) => {
+                    out.push(next);
+                    chars.next();
+                }
+                // Accent commands (`\"o`, `\'e`) keep the letter.
+                Some(&next) if "\"'`^~=.".contains(next) => {
+                    chars.next();
+                }
+                _ => {
+                    while chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                        chars.next();
+                    }
+                }
+            },
+            '~' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The first four-digit year in a date (`2017`, `2017-06-12`, Zotero's `2017-00-00 2017`).
+fn year_of(date: &str) -> Option<i32> {
+    date.as_bytes()
+        .windows(4)
+        .enumerate()
+        .find(|&(i, w)| {
+            let digit_at = |j: usize| date.as_bytes().get(j).is_some_and(u8::is_ascii_digit);
+            w.iter().all(u8::is_ascii_digit) && !digit_at(i + 4) && (i == 0 || !digit_at(i - 1))
+        })
+        .and_then(|(i, _)| date[i..i + 4].parse().ok())
+}
+
+/// `Vaswani, A.` for `Vaswani, Ashish` (or `Ashish Vaswani`); single names stay whole.
+fn apa_name(name: &str) -> String {
+    let (last, first) = match name.split_once(',') {
+        Some((last, first)) => (last.trim(), first.trim()),
+        None => match name.trim().rsplit_once(' ') {
+            Some((first, last)) => (last, first),
+            None => (name.trim(), ""),
+        },
+    };
+    let initials: Vec<String> = first
+        .split([' ', '-'])
+        .filter_map(|p| p.chars().next())
+        .map(|c| format!("{c}."))
+        .collect();
+    if initials.is_empty() {
+        last.to_string()
+    } else {
+        format!("{last}, {}", initials.join(" "))
+    }
+}
+
+/// `vaswani2017attention`: first author's last name, year, first long title word.
+fn make_key(authors: &[String], year: Option<i32>, title: &str) -> String {
+    let last = authors
+        .first()
+        .map(|a| a.split(',').next().unwrap_or(a).split_whitespace().last().unwrap_or_default().to_string())
+        .unwrap_or_default();
+    let word = title.split_whitespace().find(|w| w.chars().count() > 3).unwrap_or_default();
+    let year = year.map(|y| y.to_string()).unwrap_or_default();
+    format!("{last}{year}{word}")
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
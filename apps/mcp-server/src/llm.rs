@@ -1,11 +1,35 @@
 use async_trait::async_trait;
 use std::path::PathBuf;
 use std::process::Stdio;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 
 #[async_trait]
 pub trait Llm: Send + Sync {
     async fn generate(&self, prompt: String) -> Result<String, String>;
+
+    /// Like `generate`, but sends text fragments to `tokens` as they are produced.
+    /// Returns the full completion. Default: one fragment with the whole completion.
+    async fn generate_stream(
+        &self,
+        prompt: String,
+        tokens: UnboundedSender<String>,
+    ) -> Result<String, String> {
+        let out = self.generate(prompt).await?;
+        let _ = tokens.send(out.clone());
+        Ok(out)
+    }
+
+    /// Stable identifier of backend + model (part of the answer cache key).
+    fn model_id(&self) -> String {
+        "none".to_string()
+    }
+
+    /// Context window of the underlying model in tokens, when the backend can tell.
+    async fn context_window_tokens(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub type LlmHandle = std::sync::Arc<dyn Llm>;
@@ -22,6 +46,59 @@ impl Llm for NoopLlm {
     }
 }
 
+/// Used when neither config nor the backend knows the model's context window.
+/// Small local models (and Ollama's default `num_ctx`) are in this range.
+pub const DEFAULT_CONTEXT_WINDOW_TOKENS: usize = 4096;
+
+/// Token budget for assembling prompts.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ContextBudget {
+    pub window_tokens: usize,
+    /// Tokens kept free for the model's answer.
+    pub reserve_tokens: usize,
+    /// Where `window_tokens` came from: `config`, `model`, or `default`.
+    pub source: &'static str,
+}
+
+impl ContextBudget {
+    /// Tokens available for the prompt itself.
+    pub fn prompt_tokens(&self) -> usize {
+        self.window_tokens.saturating_sub(self.reserve_tokens).max(256)
+    }
+}
+
+/// Conservative LLM token estimate. Real tokenizers produce ~1.3 tokens per English word and
+/// more for code/CJK, so take the larger of words*4/3 and chars/4.
+pub fn estimate_llm_tokens(text: &str) -> usize {
+    let words = text.split_whitespace().count();
+    let chars = text.chars().count();
+    (words * 4).div_ceil(3).max(chars.div_ceil(4))
+}
+
+/// Truncate `text` to roughly `max_tokens` (by `estimate_llm_tokens`), cutting at a word boundary.
+pub fn truncate_to_llm_tokens(text: &str, max_tokens: usize) -> String {
+    if estimate_llm_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let (mut words, mut chars) = (0usize, 0usize);
+    for word in text.split_whitespace() {
+        let add_chars = word.chars().count() + usize::from(!out.is_empty());
+        let est = ((words + 1) * 4).div_ceil(3).max((chars + add_chars).div_ceil(4));
+        if est > max_tokens {
+            break;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+        words += 1;
+        chars += add_chars;
+    }
+    out.push_str(" …");
+    out
+}
+
 /// Local LLM via the `ollama` CLI (no network required).
 ///
 /// Env vars (recommended for GUI apps with limited PATH):
@@ -30,10 +107,26 @@ impl Llm for NoopLlm {
 pub struct OllamaCliLlm {
     pub ollama_path: PathBuf,
     pub model: String,
+    /// Window from `ollama show`, looked up once.
+    context_window: tokio::sync::OnceCell<Option<usize>>,
+}
+
+impl OllamaCliLlm {
+    pub fn new(ollama_path: PathBuf, model: String) -> Self {
+        Self {
+            ollama_path,
+            model,
+            context_window: tokio::sync::OnceCell::new(),
+        }
+    }
 }
 
 #[async_trait]
 impl Llm for OllamaCliLlm {
+    fn model_id(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
     async fn generate(&self, prompt: String) -> Result<String, String> {
         // `ollama run <model> "<prompt>"` prints completion to stdout.
         // Keep this dependency-light; we can switch to the HTTP API later if desired.
@@ -49,23 +142,143 @@ impl Llm for OllamaCliLlm {
             .map_err(|e| format!("Failed to spawn ollama CLI at {}: {e}", self.ollama_path.display()))?;
 
         if !out.status.success() {
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            let hint = if stderr.to_ascii_lowercase().contains("could not connect to a running ollama instance")
-                || stderr.to_ascii_lowercase().contains("could not connect")
-            {
-                " (hint: start the Ollama daemon: `ollama serve` or open the Ollama app)"
-            } else {
-                ""
-            };
-            return Err(if stderr.is_empty() {
-                format!("ollama exited with status {}{hint}", out.status)
-            } else {
-                format!("ollama error: {stderr}{hint}")
-            });
+            return Err(ollama_error(out.status, &out.stderr));
         }
 
         Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
     }
+
+    async fn generate_stream(
+        &self,
+        prompt: String,
+        tokens: UnboundedSender<String>,
+    ) -> Result<String, String> {
+        let mut child = Command::new(&self.ollama_path)
+            .arg("run")
+            .arg(&self.model)
+            .arg(prompt)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to spawn ollama CLI at {}: {e}", self.ollama_path.display()))?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "ollama stdout not captured".to_string())?;
+        let mut stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| "ollama stderr not captured".to_string())?;
+        let stderr_task = tokio::spawn(async move {
+            let mut buf = vec![];
+            let _ = stderr.read_to_end(&mut buf).await;
+            buf
+        });
+
+        // Forward stdout as it arrives. Hold back incomplete UTF-8 sequences between reads.
+        let mut full = String::new();
+        let mut pending: Vec<u8> = vec![];
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stdout
+                .read(&mut buf)
+                .await
+                .map_err(|e| format!("Failed to read ollama output: {e}"))?;
+            if n == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buf[..n]);
+            let valid_up_to = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                // Invalid (not just incomplete) bytes: pass through lossily.
+                Err(_) => pending.len(),
+            };
+            let text = String::from_utf8_lossy(&pending[..valid_up_to]).to_string();
+            pending.drain(..valid_up_to);
+            if !text.is_empty() {
+                full.push_str(&text);
+                let _ = tokens.send(text);
+            }
+        }
+        if !pending.is_empty() {
+            let text = String::from_utf8_lossy(&pending).to_string();
+            full.push_str(&text);
+            let _ = tokens.send(text);
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| format!("Failed to wait for ollama: {e}"))?;
+        let stderr = stderr_task.await.unwrap_or_default();
+        if !status.success() {
+            return Err(ollama_error(status, &stderr));
+        }
+
+        Ok(full.trim().to_string())
+    }
+
+    /// The window `ollama run` actually uses: the model's `num_ctx` parameter, else Ollama's
+    /// default `num_ctx`, capped at the model's maximum context length. The maximum alone is not
+    /// it: Ollama cuts prompts longer than `num_ctx` without an error.
+    async fn context_window_tokens(&self) -> Option<usize> {
+        *self
+            .context_window
+            .get_or_init(|| async {
+                // `ollama show <model>` prints a "context length  131072" line under "Model" and,
+                // when the Modelfile sets it, a "num_ctx  8192" line under "Parameters".
+                let out = Command::new(&self.ollama_path)
+                    .arg("show")
+                    .arg(&self.model)
+                    .stdin(Stdio::null())
+                    .output()
+                    .await
+                    .ok()?;
+                if !out.status.success() {
+                    return None;
+                }
+                let show = String::from_utf8_lossy(&out.stdout);
+                let num_ctx = parse_num_ctx(&show).unwrap_or(DEFAULT_CONTEXT_WINDOW_TOKENS);
+                Some(parse_context_length(&show).map_or(num_ctx, |max| num_ctx.min(max)))
+            })
+            .await
+    }
+}
+
+fn parse_context_length(show_output: &str) -> Option<usize> {
+    show_output
+        .lines()
+        .find(|l| l.trim_start().to_ascii_lowercase().starts_with("context length"))
+        .and_then(|l| l.split_whitespace().last())
+        .and_then(|n| n.parse::<usize>().ok())
+}
+
+fn parse_num_ctx(show_output: &str) -> Option<usize> {
+    show_output
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>())
+        .find(|words| words.first() == Some(&"num_ctx"))
+        .and_then(|words| words.get(1)?.parse::<usize>().ok())
+}
+
+fn ollama_error(status: std::process::ExitStatus, stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr).trim().to_string();
+    let hint = if stderr.to_ascii_lowercase().contains("could not connect to a running ollama instance")
+        || stderr.to_ascii_lowercase().contains("could not connect")
+    {
+        " (hint: start the Ollama daemon: `ollama serve` or open the Ollama app)"
+    } else {
+        ""
+    };
+    if stderr.is_empty() {
+        format!("ollama exited with status {status}{hint}")
+    } else {
+        format!("ollama error: {stderr}{hint}")
+    }
 }
 
 pub fn llm_from_env() -> LlmHandle {
@@ -75,10 +288,33 @@ pub fn llm_from_env() -> LlmHandle {
         let ollama_path = std::env::var_os("SILO_OLLAMA_PATH")
             .map(PathBuf::from)
             .unwrap_or_else(|| PathBuf::from("ollama"));
-        return std::sync::Arc::new(OllamaCliLlm { ollama_path, model });
+        return std::sync::Arc::new(OllamaCliLlm::new(ollama_path, model));
     }
 
     std::sync::Arc::new(NoopLlm)
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOW: &str = "  Model
+    architecture        llama
+    parameters          3.2B
+    context length      131072
+    embedding length    3072
+
+  Parameters
+    num_ctx    8192
+    stop       \"<|eot_id|>\"
+";
+
+    #[test]
+    fn show_output_parsing() {
+        assert_eq!(parse_context_length(SHOW), Some(131072));
+        assert_eq!(parse_num_ctx(SHOW), Some(8192));
+        assert_eq!(parse_num_ctx("  Model\n    context length      131072\n"), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! Append-only log file with size-based rotation (`silo.log`, `traffic.jsonl`, ...).
+//!
+//! `name.ext` rotates to `name.1.ext`, `name.1.ext` to `name.2.ext` and so on; files past
+//! `keep_files` are deleted. The file is opened lazily, so nothing is created until the first write.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Smallest rotation size accepted, so a typo cannot rotate on every line.
+const MIN_MAX_BYTES: u64 = 4096;
+
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep_files: usize,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn new(path: PathBuf, max_bytes: u64, keep_files: usize) -> Self {
+        Self {
+            path,
+            max_bytes: max_bytes.max(MIN_MAX_BYTES),
+            keep_files,
+            file: None,
+            size: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(&mut self) -> std::io::Result<&mut File> {
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("log file just opened"))
+    }
+
+    /// `name.ext` -> `name.1.ext` -> ... -> `name.<keep_files>.ext` (dropped after).
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        self.size = 0;
+        if self.keep_files == 0 {
+            return ignore_not_found(std::fs::remove_file(&self.path));
+        }
+        let ext = self
+            .path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let rotated = |n: usize| match ext.as_str() {
+            "" => self.path.with_extension(n.to_string()),
+            ext => self.path.with_extension(format!("{n}.{ext}")),
+        };
+        let _ = std::fs::remove_file(rotated(self.keep_files));
+        for n in (1..self.keep_files).rev() {
+            let _ = std::fs::rename(rotated(n), rotated(n + 1));
+        }
+        ignore_not_found(std::fs::rename(&self.path, rotated(1)))
+    }
+}
+
+impl Write for RotatingFile {
+    /// Writes whole buffers: a line is never split across two files.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.open()?;
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.open()?.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(f) => f.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn ignore_not_found(result: std::io::Result<()>) -> std::io::Result<()> {
+    match result {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,93 @@
+//! Log output (`logging` in the config).
+//!
+//! Logs always go to stderr (stdout carries JSON-RPC), as human-readable text or JSON lines, and
+//! optionally also to `logs/silo.log` in the data dir, rotated by size like the traffic log.
+//! Levels come from `logging.level` and `logging.levels`, with `RUST_LOG` directives applied on
+//! top, so `RUST_LOG=mcp_server::indexer=trace` still works for a one-off run.
+
+use crate::config::{default_config_path, default_data_dir, load_or_init_config, LogFormat, LoggingConfig};
+use crate::logfile::RotatingFile;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE: &str = "silo.log";
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Install the global subscriber from the config file at the default location. Call once, first.
+pub async fn init_default() {
+    // Nothing is logged yet, so a broken config is reported on stderr by hand.
+    let cfg = match load_or_init_config(&default_config_path()).await {
+        Ok(cfg) => cfg.logging,
+        Err(e) => {
+            eprintln!("Failed to load config for logging (using defaults): {e}");
+            LoggingConfig::default()
+        }
+    };
+    init(&cfg, &default_data_dir());
+}
+
+/// Install the global subscriber. Does nothing if one is already installed.
+pub fn init(cfg: &LoggingConfig, data_dir: &Path) {
+    let (filter, filter_error) = build_filter(cfg);
+    let mut layers: Vec<BoxedLayer> = vec![fmt_layer(cfg.format, std::io::stderr, false)];
+    let file = log_file(cfg, data_dir);
+    if let Some(path) = &file {
+        let writer = Mutex::new(RotatingFile::new(path.clone(), cfg.max_file_bytes, cfg.keep_files));
+        layers.push(fmt_layer(cfg.format, writer, true));
+    }
+    if tracing_subscriber::registry().with(layers).with(filter).try_init().is_err() {
+        return;
+    }
+    if let Some(e) = filter_error {
+        tracing::warn!("Invalid log levels ({e}); using \"info\"");
+    }
+    if let Some(path) = file {
+        tracing::info!("Logging to {}", path.display());
+    }
+}
+
+/// `logging.file`, or `logs/silo.log` in the data dir with `logging.to_file`.
+pub fn log_file(cfg: &LoggingConfig, data_dir: &Path) -> Option<PathBuf> {
+    match &cfg.file {
+        Some(path) => Some(crate::state::expand_tilde(&path.to_string_lossy())),
+        None if cfg.to_file => Some(data_dir.join(LOG_DIR).join(LOG_FILE)),
+        None => None,
+    }
+}
+
+/// Config levels first, then `RUST_LOG` (a later directive for the same target wins).
+fn build_filter(cfg: &LoggingConfig) -> (EnvFilter, Option<String>) {
+    let mut directives = vec![cfg.level.trim().to_string()];
+    directives.extend(cfg.levels.iter().map(|(target, level)| format!("{}={}", target.trim(), level.trim())));
+    if let Ok(env) = std::env::var(EnvFilter::DEFAULT_ENV) {
+        directives.push(env);
+    }
+    let directives = directives
+        .into_iter()
+        .filter(|d| !d.is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+    match EnvFilter::try_new(&directives) {
+        Ok(filter) => (filter, None),
+        Err(e) => (EnvFilter::new("info"), Some(format!("`{directives}`: {e}"))),
+    }
+}
+
+/// Stderr keeps the old timestamp-free text (MCP clients stamp it themselves); files get times.
+fn fmt_layer<W>(format: LogFormat, writer: W, to_file: bool) -> BoxedLayer
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+        LogFormat::Text if to_file => layer.with_ansi(false).boxed(),
+        LogFormat::Text => layer.without_time().boxed(),
+    }
+}
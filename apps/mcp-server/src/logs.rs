@@ -0,0 +1,228 @@
+//! Log files: `.log` files and their rotated copies (`app.log.1`, `app.log.2024-05-14`).
+//!
+//! Each line's timestamp is parsed: ISO 8601 / RFC 3339 (`2024-05-14T15:02:03Z`,
+//! `2024-05-14 15:02:03,123`, `2024/05/14 15:02:03`), common log format
+//! (`[14/May/2024:15:02:03 +0000]`) and syslog (`May 14 15:02:03`). Times without a zone are
+//! local time; syslog lines carry no year and get the file's modification year (the year before
+//! for dates after the modification time). Lines without a timestamp (stack traces, wrapped
+//! messages) belong to the entry above.
+//!
+//! Chunks follow time windows: entries of one `log_window_minutes` window are packed into chunks
+//! of up to `chunk_tokens`, cut between entries, and a chunk never spans two windows. Each chunk
+//! stores the time span of its entries, which `time:` queries filter on (`crate::query`).
+
+use crate::chunk::{chunk_by_whitespace_tokens, ChunkOptions, TextChunk};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const DEFAULT_WINDOW_MINUTES: u64 = 15;
+/// Timestamps are looked for in this many bytes at the start of a line.
+const TIMESTAMP_PREFIX_BYTES: usize = 80;
+/// A syslog date this far past the file's modification time is from the year before.
+const SYSLOG_YEAR_SLACK_SECS: i64 = 24 * 3600;
+
+/// `.log`, or a rotated log: `.log.` followed by a number or a date (`app.log.1`,
+/// `app.log.20240514`, `app.log.2024-05-14`). Compressed rotations (`.log.2.gz`) are not text.
+pub fn is_log_path(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_ascii_lowercase()) else {
+        return false;
+    };
+    if name.ends_with(".log") {
+        return true;
+    }
+    name.rsplit_once(".log.").is_some_and(|(stem, suffix)| {
+        !stem.is_empty() && !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '_')
+    })
+}
+
+/// Extension used for `allow_extensions` and `extension_overrides`: `log` for rotated logs.
+pub fn effective_extension(path: &Path) -> Option<String> {
+    if is_log_path(path) {
+        return Some("log".to_string());
+    }
+    path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase())
+}
+
+/// One log entry: a line with a timestamp and the untimed lines after it.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// Whitespace-token index where the entry starts.
+    start_token: usize,
+    /// `None` only for untimed lines at the top of the file.
+    time: Option<i64>,
+}
+
+/// Entries of a log file's text, in file order.
+#[derive(Debug, Clone, Default)]
+pub struct LogEntries {
+    entries: Vec<Entry>,
+}
+
+impl LogEntries {
+    /// Entries of `text`; `mtime_epoch_secs` dates syslog lines.
+    pub fn parse(text: &str, mtime_epoch_secs: Option<i64>) -> Self {
+        let mtime = mtime_epoch_secs.unwrap_or_else(|| Local::now().timestamp());
+        let mut entries: Vec<Entry> = vec![];
+        let mut token = 0usize;
+        for line in text.lines() {
+            let n = line.split_whitespace().count();
+            if n == 0 {
+                continue;
+            }
+            match parse_timestamp(line, mtime) {
+                Some(time) => entries.push(Entry {
+                    start_token: token,
+                    time: Some(time),
+                }),
+                None if entries.is_empty() => entries.push(Entry {
+                    start_token: token,
+                    time: None,
+                }),
+                None => {}
+            }
+            token += n;
+        }
+        Self { entries }
+    }
+
+    /// No line had a timestamp (not a log after all).
+    pub fn is_untimed(&self) -> bool {
+        self.entries.iter().all(|e| e.time.is_none())
+    }
+
+    /// Chunks of `text` (the text given to `parse`) along time windows of `window_minutes`.
+    pub fn chunks(&self, text: &str, opts: &ChunkOptions, window_minutes: u64) -> Vec<TextChunk> {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        if tokens.is_empty() || opts.tokens == 0 {
+            return vec![];
+        }
+        let window_secs = (window_minutes.max(1) * 60) as i64;
+        let window_of = |e: &Entry| e.time.map(|t| t.div_euclid(window_secs));
+
+        let mut spans: Vec<(usize, usize)> = vec![];
+        let mut chunk_start = 0usize;
+        let mut chunk_window = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            let end = self.entries.get(i + 1).map_or(tokens.len(), |e| e.start_token);
+            let window = window_of(entry).or(chunk_window);
+            let new_window = chunk_window.is_some() && window != chunk_window;
+            if entry.start_token > chunk_start && (new_window || end - chunk_start > opts.tokens) {
+                spans.push((chunk_start, entry.start_token));
+                chunk_start = entry.start_token;
+            }
+            chunk_window = window;
+            // An entry longer than a chunk is split into windows of its own.
+            if end - chunk_start > opts.tokens {
+                let sub = chunk_by_whitespace_tokens(&tokens[chunk_start..end].join(" "), opts.tokens, opts.overlap_tokens);
+                spans.extend(sub.iter().map(|c| (chunk_start + c.start_token, chunk_start + c.end_token)));
+                chunk_start = end;
+            }
+        }
+        if chunk_start < tokens.len() {
+            spans.push((chunk_start, tokens.len()));
+        }
+        spans
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end))| TextChunk {
+                index,
+                text: tokens[start..end].join(" "),
+                start_token: start,
+                end_token: end,
+            })
+            .collect()
+    }
+
+    /// Earliest and latest timestamp of the entries in tokens `start..end`.
+    pub fn time_span(&self, start: usize, end: usize) -> Option<(i64, i64)> {
+        // The entry containing `start`, then those starting before `end`.
+        let first = self.entries.partition_point(|e| e.start_token <= start).saturating_sub(1);
+        let times = self.entries[first..]
+            .iter()
+            .take_while(|e| e.start_token < end.max(start + 1))
+            .filter_map(|e| e.time);
+        times.fold(None, |span, t| match span {
+            None => Some((t, t)),
+            Some((lo, hi)) => Some((lo.min(t), hi.max(t))),
+        })
+    }
+}
+
+struct Patterns {
+    iso: Regex,
+    clf: Regex,
+    syslog: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        iso: Regex::new(
+            r"(\d{4})[-/](\d{2})[-/](\d{2})[T ](\d{2}):(\d{2}):(\d{2})(?:[.,]\d+)?(?:(Z)|\s?([+-]\d{2}:?\d{2})\b)?",
+        )
+        .expect("valid ISO timestamp regex"),
+        clf: Regex::new(r"(\d{2})/([A-Za-z]{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2})\s([+-]\d{4})")
+            .expect("valid common log format regex"),
+        syslog: Regex::new(r"^(?:<\d+>)?([A-Z][a-z]{2})\s+(\d{1,2})\s(\d{2}):(\d{2}):(\d{2})\b")
+            .expect("valid syslog timestamp regex"),
+    })
+}
+
+/// Epoch seconds of the timestamp near the start of `line`.
+fn parse_timestamp(line: &str, mtime: i64) -> Option<i64> {
+    let mut end = line.len().min(TIMESTAMP_PREFIX_BYTES);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &line[..end];
+    let p = patterns();
+    let num = |c: &regex::Captures, i: usize| c.get(i).and_then(|m| m.as_str().parse::<u32>().ok());
+
+    if let Some(c) = p.iso.captures(head) {
+        let date = NaiveDate::from_ymd_opt(c[1].parse().ok()?, num(&c, 2)?, num(&c, 3)?)?;
+        let at = date.and_hms_opt(num(&c, 4)?, num(&c, 5)?, num(&c, 6)?)?;
+        let offset = match (c.get(7), c.get(8)) {
+            (Some(_), _) => Some(FixedOffset::east_opt(0)?),
+            (None, Some(o)) => Some(parse_offset(o.as_str())?),
+            (None, None) => None,
+        };
+        return local_or_offset(at, offset);
+    }
+    if let Some(c) = p.clf.captures(head) {
+        let date = NaiveDate::from_ymd_opt(c[3].parse().ok()?, month(&c[2])?, num(&c, 1)?)?;
+        let at = date.and_hms_opt(num(&c, 4)?, num(&c, 5)?, num(&c, 6)?)?;
+        return local_or_offset(at, Some(parse_offset(&c[7])?));
+    }
+    if let Some(c) = p.syslog.captures(head) {
+        let (m, d) = (month(&c[1])?, num(&c, 2)?);
+        let (h, min, s) = (num(&c, 3)?, num(&c, 4)?, num(&c, 5)?);
+        let year = Local.timestamp_opt(mtime, 0).single()?.year();
+        let at = |year: i32| local_or_offset(NaiveDate::from_ymd_opt(year, m, d)?.and_hms_opt(h, min, s)?, None);
+        let t = at(year)?;
+        return if t > mtime + SYSLOG_YEAR_SLACK_SECS { at(year - 1) } else { Some(t) };
+    }
+    None
+}
+
+fn local_or_offset(at: NaiveDateTime, offset: Option<FixedOffset>) -> Option<i64> {
+    match offset {
+        Some(offset) => offset.from_local_datetime(&at).single().map(|t: DateTime<FixedOffset>| t.timestamp()),
+        None => Local.from_local_datetime(&at).earliest().map(|t| t.timestamp()),
+    }
+}
+
+/// `+0200`, `-05:00`.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let sign = if s.starts_with('-') { -1 } else { 1 };
+    let digits: String = s.chars().filter(char::is_ascii_digit).collect();
+    let (h, m) = (digits.get(..2)?.parse::<i32>().ok()?, digits.get(2..4)?.parse::<i32>().ok()?);
+    FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+}
+
+fn month(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let name = name.to_ascii_lowercase();
+    MONTHS.iter().position(|m| *m == name).map(|i| i as u32 + 1)
+}
@@ -1,13 +1,28 @@
+use mcp_server::config::default_data_dir;
 use mcp_server::database::Database;
-use mcp_server::state::AppState;
+use mcp_server::state::{read_only_from_env, AppState};
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
-    init_tracing();
+    mcp_server::logging::init_default().await;
+
+    // `--daemon`: be the shared core other clients connect to (see `mcp_server::daemon`).
+    let daemon = std::env::args().skip(1).any(|a| a == "--daemon");
+    if !daemon {
+        // A running core (daemon or desktop app) already has the DB and models: just proxy.
+        match mcp_server::daemon::proxy_stdio(&default_data_dir(), read_only_from_env()).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+        }
+    }
 
     // "Zero-panic" entrypoint: any error becomes a JSON-RPC error response from the server loop.
-    let db = match Database::new("./data").await {
+    let db = match Database::open_configured(default_data_dir()).await {
         Ok(db) => Arc::new(db),
         Err(e) => {
             eprintln!("Failed to initialize database: {e}");
@@ -16,6 +31,14 @@ async fn main() {
         }
     };
 
+    if daemon && !db.is_enabled() {
+        eprintln!(
+            "--daemon needs the knowledge base: {}",
+            db.disabled_reason().unwrap_or("unknown reason")
+        );
+        return;
+    }
+
     let state = match AppState::new(db).await {
         Ok(s) => s,
         Err(e) => {
@@ -24,20 +47,19 @@ async fn main() {
         }
     };
 
+    mcp_server::screenshots::spawn_watcher(&state);
+    mcp_server::newsletters::spawn_watcher(&state);
+    mcp_server::maintenance::spawn_scheduler(&state);
+
+    if daemon {
+        if let Err(e) = mcp_server::daemon::listen(state).await {
+            eprintln!("Daemon stopped with error: {e}");
+        }
+        return;
+    }
+
     if let Err(e) = mcp_server::server::run_stdio_server(state).await {
         eprintln!("Server stopped with error: {e}");
     }
 }
 
-fn init_tracing() {
-    use tracing_subscriber::EnvFilter;
-    // Logs go to stderr by default. Keep stdout clean for JSON-RPC.
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_writer(std::io::stderr)
-        .without_time()
-        .init();
-}
-
-
@@ -0,0 +1,167 @@
+//! Disk-space maintenance (`silo_maintenance`, `storage.maintenance_interval_hours`).
+//!
+//! Every re-index deletes and re-adds a file's rows, and LanceDB only marks deleted rows and keeps
+//! every table version, so the tables grow with churn. Maintenance compacts both tables (rewriting
+//! fragments without the deleted rows) and deletes versions older than `storage.keep_versions_hours`,
+//! which is when the space actually comes back. The last report is kept in `maintenance_report.json`
+//! in the data dir; the scheduler uses it to pick up the interval across restarts.
+
+use crate::catalog::now_epoch_secs;
+use crate::database::CompactionStats;
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const REPORT_FILE: &str = "maintenance_report.json";
+const DEFAULT_KEEP_VERSIONS_HOURS: u64 = 1;
+/// How often the scheduler re-reads the config when maintenance is off.
+const IDLE_CHECK_SECS: u64 = 600;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MaintenanceArgs {
+    /// Override `storage.keep_versions_hours` for this run (0 keeps only the latest version).
+    #[serde(default)]
+    pub keep_versions_hours: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub at_epoch_secs: i64,
+    pub elapsed_ms: u64,
+    pub keep_versions_hours: u64,
+    /// LanceDB tables on disk before and after.
+    pub disk_bytes_before: u64,
+    pub disk_bytes_after: u64,
+    pub stats: CompactionStats,
+    /// Run by the background schedule rather than on request.
+    #[serde(default)]
+    pub scheduled: bool,
+}
+
+/// MCP tool entrypoint (`silo_maintenance`).
+pub async fn maintenance_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: MaintenanceArgs =
+        serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = run_maintenance(state, args.keep_versions_hours, false).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize maintenance report: {e}").into())
+}
+
+/// Start the background schedule (idle while `storage.maintenance_interval_hours` is unset).
+pub fn spawn_scheduler(state: &SharedState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            let interval = state.config.read().await.storage.maintenance_interval_hours;
+            let Some(hours) = interval.filter(|h| *h > 0) else {
+                tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+                continue;
+            };
+            let due = last_run_epoch_secs(&state.data_dir)
+                .await
+                .map_or(0, |at| at.saturating_add(hours.saturating_mul(3600) as i64));
+            let wait = due.saturating_sub(now_epoch_secs());
+            if wait > 0 {
+                tokio::time::sleep(Duration::from_secs((wait as u64).min(IDLE_CHECK_SECS))).await;
+                continue;
+            }
+            if state.db.is_enabled() {
+                match run_maintenance(&state, None, true).await {
+                    Ok(r) => tracing::info!(
+                        "Maintenance: {} -> {} bytes on disk",
+                        r.disk_bytes_before,
+                        r.disk_bytes_after
+                    ),
+                    Err(e) => tracing::warn!("Scheduled maintenance failed: {e}"),
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+        }
+    });
+}
+
+/// Compact the tables and prune old versions, measuring disk usage around it.
+pub async fn run_maintenance(
+    state: &SharedState,
+    keep_versions_hours: Option<u64>,
+    scheduled: bool,
+) -> Result<MaintenanceReport, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let keep_versions_hours = match keep_versions_hours {
+        Some(h) => h,
+        None => state
+            .config
+            .read()
+            .await
+            .storage
+            .keep_versions_hours
+            .unwrap_or(DEFAULT_KEEP_VERSIONS_HOURS),
+    };
+    let started = Instant::now();
+    let disk_bytes_before = tables_disk_bytes(&state.data_dir).await;
+    let stats = state
+        .db
+        .compact_and_prune(Duration::from_secs(keep_versions_hours.saturating_mul(3600)))
+        .await
+        .map_err(|e| format!("Maintenance failed: {e}"))?;
+
+    let report = MaintenanceReport {
+        at_epoch_secs: now_epoch_secs(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        keep_versions_hours,
+        disk_bytes_before,
+        disk_bytes_after: tables_disk_bytes(&state.data_dir).await,
+        stats,
+        scheduled,
+    };
+    let path = state.data_dir.join(REPORT_FILE);
+    match serde_json::to_string_pretty(&report) {
+        Ok(s) => {
+            if let Err(e) = tokio::fs::write(&path, s).await {
+                tracing::warn!("Failed to write {}: {e}", path.display());
+            }
+        }
+        Err(e) => tracing::warn!("Failed to serialize maintenance report: {e}"),
+    }
+    Ok(report)
+}
+
+async fn last_run_epoch_secs(data_dir: &Path) -> Option<i64> {
+    let raw = tokio::fs::read_to_string(data_dir.join(REPORT_FILE)).await.ok()?;
+    serde_json::from_str::<MaintenanceReport>(&raw).ok().map(|r| r.at_epoch_secs)
+}
+
+/// Bytes under the `*.lance` table directories in `data_dir`.
+pub(crate) async fn tables_disk_bytes(data_dir: &Path) -> u64 {
+    let Ok(mut rd) = tokio::fs::read_dir(data_dir).await else {
+        return 0;
+    };
+    let mut stack = vec![];
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        if entry.file_name().to_string_lossy().ends_with(".lance") {
+            stack.push(entry.path());
+        }
+    }
+    let mut total = 0u64;
+    while let Some(dir) = stack.pop() {
+        let Ok(mut rd) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                total += meta.len();
+            }
+        }
+    }
+    total
+}
@@ -0,0 +1,420 @@
+//! In-memory `VectorStore`: brute-force cosine search over rows kept in a `Vec`.
+//!
+//! Used when LanceDB is not compiled in. Fine for a few thousand chunks; nothing is persisted.
+
+use crate::database::{preview, ChunkRow, DbError, EntityRow, SearchHit, SearchOptions};
+use crate::predicate::{Columns, FieldValue, Predicate};
+use crate::store::{StoreBackend, StoreStats, VectorStore};
+use std::sync::RwLock;
+
+#[derive(Default)]
+pub struct MemoryStore {
+    chunks: RwLock<Vec<ChunkRow>>,
+    entities: RwLock<Vec<EntityRow>>,
+}
+
+fn write<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[async_trait::async_trait]
+impl VectorStore for MemoryStore {
+    fn backend(&self) -> StoreBackend {
+        StoreBackend::Memory
+    }
+
+    async fn add_chunks(&self, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        write(&self.chunks).extend(rows);
+        Ok(())
+    }
+
+    async fn replace_file_chunks(&self, path: &str, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        {
+            let mut chunks = write(&self.chunks);
+            chunks.retain(|r| r.path != path);
+            chunks.extend(rows);
+        }
+        write(&self.entities).retain(|r| r.path != path);
+        Ok(())
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), DbError> {
+        write(&self.chunks).retain(|r| r.path != path);
+        write(&self.entities).retain(|r| r.path != path);
+        Ok(())
+    }
+
+    async fn search(&self, query: &[f32], opts: &SearchOptions) -> Result<Vec<SearchHit>, DbError> {
+        let chunks = read(&self.chunks);
+        let mut scored: Vec<(f32, &ChunkRow)> = chunks
+            .iter()
+            .filter(|r| opts.filter.as_ref().is_none_or(|f| f.matches(*r)))
+            .map(|r| (cosine_similarity(query, &r.embedding), r))
+            .filter(|(score, _)| opts.min_score.is_none_or(|min| *score >= min))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(opts.top_k);
+        Ok(scored
+            .into_iter()
+            .map(|(score, r)| SearchHit {
+                id: Some(r.id.clone()),
+                path: r.path.clone(),
+                chunk_index: Some(r.chunk_index as i64),
+                start_token: Some(r.start_token as i64),
+                end_token: Some(r.end_token as i64),
+                page: r.page,
+                score: Some(score),
+                distance: Some(1.0 - score),
+                content_preview: Some(preview(&r.content, 240)),
+                content: Some(r.content.clone()),
+                source: r.source.clone(),
+                time_start_epoch_secs: r.time_start_epoch_secs,
+                time_end_epoch_secs: r.time_end_epoch_secs,
+                duplicate_paths: vec![],
+                collection: None,
+            })
+            .collect())
+    }
+
+    async fn chunks_for_paths(&self, paths: &[String]) -> Result<Vec<ChunkRow>, DbError> {
+        Ok(read(&self.chunks)
+            .iter()
+            .filter(|r| paths.contains(&r.path))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_chunks(&self, filter: &Predicate, limit: usize) -> Result<Vec<ChunkRow>, DbError> {
+        Ok(read(&self.chunks)
+            .iter()
+            .filter(|r| filter.matches(*r))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn set_file_tags(&self, path: &str, tags_column: &str) -> Result<(), DbError> {
+        for r in write(&self.chunks).iter_mut().filter(|r| r.path == path) {
+            r.tags = Some(tags_column.to_string());
+        }
+        Ok(())
+    }
+
+    async fn add_entities(&self, rows: Vec<EntityRow>) -> Result<(), DbError> {
+        write(&self.entities).extend(rows);
+        Ok(())
+    }
+
+    async fn find_entities(&self, filter: &Predicate, limit: usize) -> Result<Vec<EntityRow>, DbError> {
+        Ok(read(&self.entities)
+            .iter()
+            .filter(|r| filter.matches(*r))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn stats(&self) -> Result<StoreStats, DbError> {
+        Ok(StoreStats {
+            backend: StoreBackend::Memory,
+            persistent: false,
+            chunks: read(&self.chunks).len() as u64,
+            entity_mentions: read(&self.entities).len() as u64,
+        })
+    }
+}
+
+/// Same convention as the LanceDB store: degenerate (zero or mismatched) vectors score 0.0.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut na, mut nb) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        na += x * x;
+        nb += y * y;
+    }
+    let sim = dot / (na.sqrt() * nb.sqrt());
+    if sim.is_finite() {
+        sim.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+impl Columns for ChunkRow {
+    fn column(&self, column: &str) -> FieldValue<'_> {
+        match column {
+            "id" => FieldValue::Str(&self.id),
+            "path" => FieldValue::Str(&self.path),
+            "chunk_index" => FieldValue::Int(self.chunk_index as i64),
+            "start_token" => FieldValue::Int(self.start_token as i64),
+            "end_token" => FieldValue::Int(self.end_token as i64),
+            "file_mtime_epoch_secs" => opt_int(self.file_mtime_epoch_secs),
+            "file_size_bytes" => opt_int(self.file_size_bytes),
+            "file_hash" => opt_str(&self.file_hash),
+            "page" => opt_int(self.page),
+            "tags" => opt_str(&self.tags),
+            "source" => opt_str(&self.source),
+            "time_start_epoch_secs" => opt_int(self.time_start_epoch_secs),
+            "time_end_epoch_secs" => opt_int(self.time_end_epoch_secs),
+            "content" => FieldValue::Str(&self.content),
+            _ => FieldValue::Null,
+        }
+    }
+}
+
+fn opt_str(v: &Option<String>) -> FieldValue<'_> {
+    v.as_deref().map_or(FieldValue::Null, FieldValue::Str)
+}
+
+fn opt_int(v: Option<i64>) -> FieldValue<'static> {
+    v.map_or(FieldValue::Null, FieldValue::Int)
+}
+
+impl Columns for EntityRow {
+    fn column(&self, column: &str) -> FieldValue<'_> {
+        match column {
+            "chunk_id" => FieldValue::Str(&self.chunk_id),
+            "path" => FieldValue::Str(&self.path),
+            "chunk_index" => FieldValue::Int(self.chunk_index as i64),
+            "name" => FieldValue::Str(&self.name),
+            "key" => FieldValue::Str(&self.key),
+            "kind" => FieldValue::Str(&self.kind),
+            _ => FieldValue::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(path: &str, chunk_index: usize, embedding: Vec<f32>) -> ChunkRow {
+        ChunkRow {
+            id: format!("{path}#{chunk_index}"),
+            path: path.to_string(),
+            chunk_index,
+            start_token: chunk_index * 100,
+            end_token: chunk_index * 100 + 100,
+            file_mtime_epoch_secs: Some(1_700_000_000),
+            file_size_bytes: Some(1024),
+            file_hash: None,
+            page: None,
+            tags: None,
+            source: Some("file".to_string()),
+            time_start_epoch_secs: None,
+            time_end_epoch_secs: None,
+            content: format!("chunk {chunk_index} of {path}"),
+            embedding,
+        }
+    }
+
+    fn entity(path: &str, name: &str, kind: &str) -> EntityRow {
+        EntityRow {
+            chunk_id: format!("{path}#0"),
+            path: path.to_string(),
+            chunk_index: 0,
+            name: name.to_string(),
+            key: name.to_lowercase(),
+            kind: kind.to_string(),
+        }
+    }
+
+    fn paths(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.path.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn search_ranks_by_cosine_similarity() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![
+                chunk("/a.md", 0, vec![1.0, 0.0]),
+                chunk("/b.md", 0, vec![0.6, 0.8]),
+                chunk("/c.md", 0, vec![0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+
+        let hits = store.search(&[1.0, 0.0], &SearchOptions::default()).await.unwrap();
+        assert_eq!(paths(&hits), ["/a.md", "/b.md", "/c.md"]);
+        assert!((hits[0].score.unwrap() - 1.0).abs() < 1e-6);
+        assert!((hits[1].score.unwrap() - 0.6).abs() < 1e-6);
+        assert!(hits[2].distance.unwrap() > 0.99);
+
+        let opts = SearchOptions {
+            top_k: 2,
+            ..Default::default()
+        };
+        assert_eq!(paths(&store.search(&[0.0, 1.0], &opts).await.unwrap()), ["/c.md", "/b.md"]);
+
+        let opts = SearchOptions {
+            min_score: Some(0.5),
+            ..Default::default()
+        };
+        assert_eq!(paths(&store.search(&[1.0, 0.0], &opts).await.unwrap()), ["/a.md", "/b.md"]);
+    }
+
+    #[tokio::test]
+    async fn degenerate_vectors_score_zero() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![chunk("/zero.md", 0, vec![0.0, 0.0]), chunk("/short.md", 0, vec![1.0])])
+            .await
+            .unwrap();
+        let hits = store.search(&[1.0, 0.0], &SearchOptions::default()).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.score == Some(0.0)));
+    }
+
+    #[tokio::test]
+    async fn search_applies_the_filter() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![
+                chunk("/notes/a.md", 0, vec![1.0, 0.0]),
+                chunk("/work/b.md", 0, vec![1.0, 0.0]),
+                chunk("/work/b.md", 1, vec![0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+
+        let opts = SearchOptions {
+            filter: Some(Predicate::starts_with("path", "/work/")),
+            ..Default::default()
+        };
+        let hits = store.search(&[1.0, 0.0], &opts).await.unwrap();
+        assert_eq!(paths(&hits), ["/work/b.md", "/work/b.md"]);
+        assert_eq!(hits[0].chunk_index, Some(0));
+
+        let opts = SearchOptions {
+            filter: Some(Predicate::starts_with("path", "/work/").and(Predicate::eq("chunk_index", 1i64))),
+            ..Default::default()
+        };
+        let hits = store.search(&[1.0, 0.0], &opts).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_index, Some(1));
+
+        let opts = SearchOptions {
+            filter: Some(Predicate::never()),
+            ..Default::default()
+        };
+        assert!(store.search(&[1.0, 0.0], &opts).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replace_file_chunks_upserts_one_file() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![
+                chunk("/a.md", 0, vec![1.0, 0.0]),
+                chunk("/a.md", 1, vec![1.0, 0.0]),
+                chunk("/b.md", 0, vec![1.0, 0.0]),
+            ])
+            .await
+            .unwrap();
+        store
+            .add_entities(vec![entity("/a.md", "Ada Lovelace", "person"), entity("/b.md", "Acme", "org")])
+            .await
+            .unwrap();
+
+        let mut updated = chunk("/a.md", 0, vec![0.0, 1.0]);
+        updated.content = "rewritten".to_string();
+        store.replace_file_chunks("/a.md", vec![updated]).await.unwrap();
+
+        let rows = store.chunks_for_paths(&["/a.md".to_string()]).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].content, "rewritten");
+        assert_eq!(store.chunks_for_paths(&["/b.md".to_string()]).await.unwrap().len(), 1);
+
+        // The file's entity mentions are dropped with its old chunks; the caller adds new ones.
+        let entities = store.find_entities(&Predicate::always(), 10).await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].path, "/b.md");
+
+        // Inserting into a file that was not indexed yet.
+        store
+            .replace_file_chunks("/c.md", vec![chunk("/c.md", 0, vec![1.0, 0.0])])
+            .await
+            .unwrap();
+        let stats = store.stats().await.unwrap();
+        assert_eq!((stats.chunks, stats.entity_mentions), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn delete_file_removes_chunks_and_entities() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![chunk("/a.md", 0, vec![1.0, 0.0]), chunk("/b.md", 0, vec![1.0, 0.0])])
+            .await
+            .unwrap();
+        store
+            .add_entities(vec![entity("/a.md", "Ada Lovelace", "person"), entity("/b.md", "Acme", "org")])
+            .await
+            .unwrap();
+
+        store.delete_file("/a.md").await.unwrap();
+        store.delete_file("/missing.md").await.unwrap();
+
+        let hits = store.search(&[1.0, 0.0], &SearchOptions::default()).await.unwrap();
+        assert_eq!(paths(&hits), ["/b.md"]);
+        let entities = store.find_entities(&Predicate::always(), 10).await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "Acme");
+    }
+
+    #[tokio::test]
+    async fn find_chunks_and_entities_filter_and_limit() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks((0..5).map(|i| chunk("/a.md", i, vec![1.0, 0.0])).collect())
+            .await
+            .unwrap();
+        store
+            .add_entities(vec![
+                entity("/a.md", "Ada Lovelace", "person"),
+                entity("/a.md", "Acme", "org"),
+                entity("/a.md", "Alan Turing", "person"),
+            ])
+            .await
+            .unwrap();
+
+        let rows = store.find_chunks(&Predicate::ge("chunk_index", 2i64), 10).await.unwrap();
+        assert_eq!(rows.iter().map(|r| r.chunk_index).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(store.find_chunks(&Predicate::always(), 2).await.unwrap().len(), 2);
+        assert!(store.find_chunks(&Predicate::is_not_null("page"), 10).await.unwrap().is_empty());
+
+        let people = store.find_entities(&Predicate::eq("kind", "person"), 10).await.unwrap();
+        assert_eq!(people.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), ["Ada Lovelace", "Alan Turing"]);
+    }
+
+    #[tokio::test]
+    async fn set_file_tags_updates_only_that_file() {
+        let store = MemoryStore::default();
+        store
+            .add_chunks(vec![
+                chunk("/a.md", 0, vec![1.0, 0.0]),
+                chunk("/a.md", 1, vec![1.0, 0.0]),
+                chunk("/b.md", 0, vec![1.0, 0.0]),
+            ])
+            .await
+            .unwrap();
+        store
+            .set_file_tags("/a.md", &crate::database::tags_column(&["work".to_string()]))
+            .await
+            .unwrap();
+
+        let opts = SearchOptions {
+            filter: Some(crate::database::tags_filter(&["work".to_string()])),
+            ..Default::default()
+        };
+        let tagged = store.search(&[1.0, 0.0], &opts).await.unwrap();
+        assert_eq!(paths(&tagged), ["/a.md", "/a.md"]);
+    }
+}
@@ -0,0 +1,565 @@
+//! macOS Messages source (`silo_index_messages`).
+//!
+//! Reads a private copy of `chat.db` (plus its WAL) with the `sqlite3` command line tool, so the
+//! live database is never opened. Messages are grouped per chat into conversation chunks: a new
+//! chunk starts after a pause longer than `window_minutes` or every `max_messages_per_chunk`
+//! messages. Each chunk is one document at `messages://<chat ROWID>/<first message ROWID>`,
+//! with the chat name and participants in its header so "what did Alex say about the lease"
+//! finds it.
+//!
+//! Runs are incremental by message ROWID: only newer messages are read, and the last chunk of
+//! each chat they extend is rebuilt. `full` drops every Messages document and starts over
+//! (needed to forget deleted messages). Messages whose text only exists as `attributedBody`
+//! (no plain `text`) are skipped.
+
+use crate::config::MessagesSourceConfig;
+use crate::errors::ToolError;
+use crate::ingest::{IngestStats, IngestTimings};
+use crate::state::{expand_tilde, SharedState};
+use chrono::{Local, TimeZone};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const PATH_PREFIX: &str = "messages://";
+const DEFAULT_DB_PATH: &str = "~/Library/Messages/chat.db";
+const ADDRESS_BOOK_DIR: &str = "~/Library/Application Support/AddressBook";
+const ADDRESS_BOOK_FILE: &str = "AddressBook-v22.abcddb";
+
+/// Seconds between the Unix epoch and Apple's reference date (2001-01-01).
+const APPLE_EPOCH_OFFSET: i64 = 978_307_200;
+const SQLITE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexMessagesArgs {
+    /// Drop all indexed messages and re-read the whole history.
+    #[serde(default)]
+    pub full: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MessagesIndexSummary {
+    pub full: bool,
+    /// Messages read in this run (new ones plus those of rebuilt chunks).
+    pub messages: usize,
+    pub conversations: usize,
+    pub chunks: usize,
+    pub last_rowid: i64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// One message of a conversation (shared by the chat sources).
+#[derive(Debug, Clone)]
+pub struct ConversationMessage {
+    /// Source-specific ordering key (ROWID for Messages).
+    pub id: i64,
+    pub sent_at_epoch_secs: i64,
+    pub sender: String,
+    pub text: String,
+}
+
+/// A conversation chunk ready to be stored as one document.
+#[derive(Debug, Clone)]
+pub struct ConversationChunk {
+    pub path: String,
+    pub title: String,
+    pub participants: Vec<String>,
+    pub messages: Vec<ConversationMessage>,
+}
+
+/// Per-database progress, persisted as `messages_state.json` under the data dir.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    db_path: String,
+    last_rowid: i64,
+    /// Chat ROWID -> ROWID of the first message of its last chunk (rebuilt when extended).
+    open_chunks: HashMap<i64, i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageRow {
+    rowid: i64,
+    chat_id: i64,
+    date: i64,
+    is_from_me: i64,
+    handle: Option<String>,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatRow {
+    chat_id: i64,
+    identifier: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemberRow {
+    chat_id: i64,
+    handle: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaxRow {
+    max_rowid: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContactRow {
+    first: Option<String>,
+    last: Option<String>,
+    org: Option<String>,
+    value: Option<String>,
+}
+
+/// MCP tool entrypoint (`silo_index_messages`).
+pub async fn index_messages_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: IndexMessagesArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let summary = index_messages(state, &args).await?;
+    serde_json::to_value(summary).map_err(|e| format!("failed to serialize messages summary: {e}").into())
+}
+
+pub async fn index_messages(state: &SharedState, args: &IndexMessagesArgs) -> Result<MessagesIndexSummary, ToolError> {
+    let Some(cfg) = state.messages_config().await else {
+        return Err("Messages source not configured. Add {\"type\": \"messages\"} to `sources` in the config".into());
+    };
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let db_path = cfg
+        .db_path
+        .clone()
+        .unwrap_or_else(|| expand_tilde(DEFAULT_DB_PATH));
+
+    let snapshot_dir = state.data_dir.join("messages_snapshot");
+    let res = index_snapshot(state, &cfg, &db_path, &snapshot_dir, args.full).await;
+    let _ = tokio::fs::remove_dir_all(&snapshot_dir).await;
+    Ok(res?)
+}
+
+async fn index_snapshot(
+    state: &SharedState,
+    cfg: &MessagesSourceConfig,
+    db_path: &Path,
+    snapshot_dir: &Path,
+    full: bool,
+) -> Result<MessagesIndexSummary, String> {
+    let snapshot = snapshot_db(db_path, snapshot_dir, "chat.db").await.map_err(|e| {
+        format!("{e}. On macOS, grant Full Disk Access to the app running Silo to read Messages")
+    })?;
+
+    let state_file = state.data_dir.join("messages_state.json");
+    let mut sync = load_sync_state(&state_file).await;
+    let db_key = db_path.to_string_lossy().to_string();
+    let full = full || sync.db_path != db_key;
+    if full {
+        remove_chunks(state, PATH_PREFIX).await?;
+        sync = SyncState {
+            db_path: db_key,
+            ..SyncState::default()
+        };
+    }
+
+    let max_rowid = query::<MaxRow>(&snapshot, "SELECT COALESCE(MAX(ROWID), 0) AS max_rowid FROM message")
+        .await?
+        .first()
+        .map(|r| r.max_rowid)
+        .unwrap_or(0);
+    let mut summary = MessagesIndexSummary {
+        full,
+        last_rowid: sync.last_rowid,
+        ..MessagesIndexSummary::default()
+    };
+    if max_rowid <= sync.last_rowid {
+        return Ok(summary);
+    }
+
+    let mut rows = query::<MessageRow>(&snapshot, &messages_sql(&format!("m.ROWID > {}", sync.last_rowid))).await?;
+    // Re-read the open last chunk of every chat that got new messages.
+    let mut reopen: Vec<String> = vec![];
+    let mut seen_chats: Vec<i64> = vec![];
+    for r in &rows {
+        if seen_chats.contains(&r.chat_id) {
+            continue;
+        }
+        seen_chats.push(r.chat_id);
+        if let Some(first) = sync.open_chunks.get(&r.chat_id) {
+            reopen.push(format!(
+                "(cmj.chat_id = {} AND m.ROWID >= {first} AND m.ROWID <= {})",
+                r.chat_id, sync.last_rowid
+            ));
+        }
+    }
+    if !reopen.is_empty() {
+        rows.extend(query::<MessageRow>(&snapshot, &messages_sql(&reopen.join(" OR "))).await?);
+    }
+
+    let chats: HashMap<i64, ChatRow> = query::<ChatRow>(
+        &snapshot,
+        "SELECT ROWID AS chat_id, chat_identifier AS identifier, display_name FROM chat",
+    )
+    .await?
+    .into_iter()
+    .map(|c| (c.chat_id, c))
+    .collect();
+    let mut members: HashMap<i64, Vec<String>> = HashMap::new();
+    for m in query::<MemberRow>(
+        &snapshot,
+        "SELECT chj.chat_id AS chat_id, h.id AS handle FROM chat_handle_join chj JOIN handle h ON h.ROWID = chj.handle_id",
+    )
+    .await?
+    {
+        members.entry(m.chat_id).or_default().push(m.handle);
+    }
+    let contacts = if cfg.resolve_contacts {
+        load_contacts(snapshot_dir).await
+    } else {
+        HashMap::new()
+    };
+    let name_of = |handle: &str| contacts.get(&contact_key(handle)).cloned().unwrap_or_else(|| handle.to_string());
+
+    let mut by_chat: BTreeMap<i64, Vec<ConversationMessage>> = BTreeMap::new();
+    for r in rows {
+        let sender = if r.is_from_me != 0 {
+            "Me".to_string()
+        } else {
+            r.handle.as_deref().map(name_of).unwrap_or_else(|| "Unknown".to_string())
+        };
+        by_chat.entry(r.chat_id).or_default().push(ConversationMessage {
+            id: r.rowid,
+            sent_at_epoch_secs: apple_time_to_epoch(r.date),
+            sender,
+            text: r.text,
+        });
+    }
+
+    let gap_secs = (cfg.window_minutes.max(1) * 60) as i64;
+    for (chat_id, mut msgs) in by_chat {
+        let chat = chats.get(&chat_id);
+        let identifier = chat.and_then(|c| c.identifier.clone()).unwrap_or_default();
+        let display_name = chat.and_then(|c| c.display_name.clone()).unwrap_or_default();
+        if is_excluded(cfg, &identifier, &display_name) {
+            continue;
+        }
+        msgs.sort_by_key(|m| (m.sent_at_epoch_secs, m.id));
+        msgs.dedup_by_key(|m| m.id);
+
+        let mut participants: Vec<String> = members
+            .get(&chat_id)
+            .map(|hs| hs.iter().map(|h| name_of(h)).collect())
+            .unwrap_or_default();
+        participants.sort();
+        participants.dedup();
+        let title = if !display_name.trim().is_empty() {
+            display_name
+        } else if !participants.is_empty() {
+            participants.join(", ")
+        } else {
+            name_of(&identifier)
+        };
+
+        let windows = conversation_windows(&msgs, gap_secs, cfg.max_messages_per_chunk);
+        if let Some(last) = windows.last().and_then(|w| w.first()) {
+            sync.open_chunks.insert(chat_id, last.id);
+        }
+        let chunks: Vec<ConversationChunk> = windows
+            .into_iter()
+            .map(|w| ConversationChunk {
+                path: format!("{PATH_PREFIX}{chat_id}/{}", w[0].id),
+                title: title.clone(),
+                participants: participants.clone(),
+                messages: w.to_vec(),
+            })
+            .collect();
+        summary.messages += msgs.len();
+        summary.conversations += 1;
+        summary.chunks += chunks.len();
+        summary.errors.extend(store_conversation_chunks(state, "messages", chunks).await);
+    }
+
+    state.index_changed();
+    state.save_catalog().await;
+    // Chunk paths are deterministic, so a failed run is simply retried from the same ROWID.
+    if summary.errors.is_empty() {
+        sync.last_rowid = max_rowid;
+        save_sync_state(&state_file, &sync).await?;
+    }
+    summary.last_rowid = sync.last_rowid;
+    Ok(summary)
+}
+
+/// Split time-ordered messages into chunks at long pauses and every `max_messages`.
+pub fn conversation_windows(
+    msgs: &[ConversationMessage],
+    gap_secs: i64,
+    max_messages: usize,
+) -> Vec<&[ConversationMessage]> {
+    let max_messages = max_messages.max(1);
+    let mut out = vec![];
+    let mut start = 0;
+    for i in 1..=msgs.len() {
+        let split = i == msgs.len()
+            || i - start >= max_messages
+            || msgs[i].sent_at_epoch_secs - msgs[i - 1].sent_at_epoch_secs > gap_secs;
+        if split {
+            out.push(&msgs[start..i]);
+            start = i;
+        }
+    }
+    out
+}
+
+/// Render, embed and store conversation chunks (one document each). Returns per-chunk errors.
+pub async fn store_conversation_chunks(
+    state: &SharedState,
+    kind: &str,
+    chunks: Vec<ConversationChunk>,
+) -> Vec<String> {
+    if chunks.is_empty() {
+        return vec![];
+    }
+    let texts: Vec<String> = chunks.iter().map(render_chunk).collect();
+    let embeddings = match state.embedder.embed_texts(texts.clone()).await {
+        Ok(v) if v.len() == texts.len() => v,
+        Ok(v) => return vec![format!("embedder returned {} vectors for {} chunks", v.len(), texts.len())],
+        Err(e) => return vec![e],
+    };
+
+    let mut errors = vec![];
+    for ((chunk, text), embedding) in chunks.iter().zip(texts).zip(embeddings) {
+        let first = chunk.messages.first().map(|m| m.sent_at_epoch_secs);
+        let last = chunk.messages.last().map(|m| m.sent_at_epoch_secs);
+        let tokens = text.split_whitespace().count();
+        let file_hash = Some(blake3::hash(text.as_bytes()).to_hex().to_string());
+        let stats = IngestStats {
+            path: chunk.path.clone(),
+            extracted_kind: kind.to_string(),
+            extracted_chars: text.chars().count(),
+            chunk_tokens: tokens,
+            chunk_overlap_tokens: 0,
+            chunks: 1,
+            chunks_before_cap: None,
+            boilerplate_chunks: 0,
+            stored: true,
+            file_size_bytes: Some(text.len() as i64),
+            file_mtime_epoch_secs: last,
+            file_created_epoch_secs: first,
+            file_hash: file_hash.clone(),
+            file_inode: None,
+            reused_chunks: 0,
+            entities: None,
+            file_tags: vec![],
+            file_comment: None,
+            timings: IngestTimings::default(),
+            symbols: vec![],
+        };
+        let res = state
+            .db
+            .replace_file_chunks(
+                &chunk.path,
+                last,
+                stats.file_size_bytes,
+                file_hash,
+                &[],
+                vec![(0, 0, tokens, None, first.zip(last), text, embedding)],
+            )
+            .await;
+        match res {
+            Ok(()) => state.catalog.write().await.record_ingest(&stats),
+            Err(e) => errors.push(format!("{}: DB write failed: {e}", chunk.path)),
+        }
+    }
+    errors
+}
+
+fn render_chunk(chunk: &ConversationChunk) -> String {
+    let mut out = format!("Conversation: {}\n", chunk.title);
+    if !chunk.participants.is_empty() {
+        out.push_str(&format!("Participants: {}\n", chunk.participants.join(", ")));
+    }
+    out.push('\n');
+    for m in &chunk.messages {
+        let when = Local
+            .timestamp_opt(m.sent_at_epoch_secs, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!("[{when}] {}: {}\n", m.sender, m.text.trim()));
+    }
+    out
+}
+
+/// Remove every document whose path starts with `prefix` from the index and the catalog.
+pub(crate) async fn remove_chunks(state: &SharedState, prefix: &str) -> Result<(), String> {
+    let paths: Vec<String> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| e.path.starts_with(prefix))
+        .map(|e| e.path.clone())
+        .collect();
+    for path in paths {
+        state
+            .db
+            .delete_file(&path)
+            .await
+            .map_err(|e| format!("DB delete failed for {path}: {e}"))?;
+        state.catalog.write().await.remove(&path);
+    }
+    Ok(())
+}
+
+fn messages_sql(condition: &str) -> String {
+    format!(
+        "SELECT m.ROWID AS rowid, cmj.chat_id AS chat_id, m.date AS date, m.is_from_me AS is_from_me, \
+         h.id AS handle, m.text AS text \
+         FROM message m \
+         JOIN chat_message_join cmj ON cmj.message_id = m.ROWID \
+         LEFT JOIN handle h ON h.ROWID = m.handle_id \
+         WHERE ({condition}) AND m.text IS NOT NULL AND trim(m.text) != ''"
+    )
+}
+
+fn is_excluded(cfg: &MessagesSourceConfig, identifier: &str, display_name: &str) -> bool {
+    cfg.exclude_chats
+        .iter()
+        .any(|x| x.eq_ignore_ascii_case(identifier) || (!display_name.is_empty() && x.eq_ignore_ascii_case(display_name)))
+}
+
+/// Message dates are seconds (older macOS) or nanoseconds since 2001-01-01.
+fn apple_time_to_epoch(date: i64) -> i64 {
+    let secs = if date.abs() > 1_000_000_000_000 { date / 1_000_000_000 } else { date };
+    secs + APPLE_EPOCH_OFFSET
+}
+
+/// Handle -> contact name, from every local address book (best effort).
+async fn load_contacts(snapshot_dir: &Path) -> HashMap<String, String> {
+    let root = expand_tilde(ADDRESS_BOOK_DIR);
+    let mut books = vec![root.join(ADDRESS_BOOK_FILE)];
+    if let Ok(mut rd) = tokio::fs::read_dir(root.join("Sources")).await {
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            books.push(entry.path().join(ADDRESS_BOOK_FILE));
+        }
+    }
+
+    let sql = "SELECT r.ZFIRSTNAME AS first, r.ZLASTNAME AS last, r.ZORGANIZATION AS org, p.ZFULLNUMBER AS value \
+               FROM ZABCDRECORD r JOIN ZABCDPHONENUMBER p ON p.ZOWNER = r.Z_PK \
+               UNION ALL \
+               SELECT r.ZFIRSTNAME, r.ZLASTNAME, r.ZORGANIZATION, e.ZADDRESS \
+               FROM ZABCDRECORD r JOIN ZABCDEMAILADDRESS e ON e.ZOWNER = r.Z_PK";
+    let mut out = HashMap::new();
+    for (i, book) in books.iter().enumerate() {
+        if tokio::fs::metadata(book).await.is_err() {
+            continue;
+        }
+        let Ok(copy) = snapshot_db(book, snapshot_dir, &format!("contacts-{i}.abcddb")).await else {
+            continue;
+        };
+        let rows = match query::<ContactRow>(&copy, sql).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("Skipping address book {}: {e}", book.display());
+                continue;
+            }
+        };
+        for r in rows {
+            let name = [r.first, r.last]
+                .into_iter()
+                .flatten()
+                .filter(|s| !s.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let name = if name.is_empty() { r.org.unwrap_or_default() } else { name };
+            if let Some(value) = r.value.filter(|_| !name.trim().is_empty()) {
+                out.insert(contact_key(&value), name);
+            }
+        }
+    }
+    out
+}
+
+/// Emails compare case-insensitively, phone numbers by their last 10 digits.
+fn contact_key(handle: &str) -> String {
+    if handle.contains('@') {
+        return handle.trim().to_lowercase();
+    }
+    let digits: String = handle.chars().filter(char::is_ascii_digit).collect();
+    digits[digits.len().saturating_sub(10)..].to_string()
+}
+
+/// Copy a SQLite database with its `-wal`/`-shm` files into `dir`.
+pub(crate) async fn snapshot_db(src: &Path, dir: &Path, name: &str) -> Result<PathBuf, String> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let dst = dir.join(name);
+    tokio::fs::copy(src, &dst)
+        .await
+        .map_err(|e| format!("Cannot read {}: {e}", src.display()))?;
+    for suffix in ["-wal", "-shm"] {
+        let from = PathBuf::from(format!("{}{suffix}", src.display()));
+        let to = PathBuf::from(format!("{}{suffix}", dst.display()));
+        if tokio::fs::copy(&from, &to).await.is_err() {
+            let _ = tokio::fs::remove_file(&to).await;
+        }
+    }
+    Ok(dst)
+}
+
+async fn query<T: DeserializeOwned>(db: &Path, sql: &str) -> Result<Vec<T>, String> {
+    sqlite_query("sqlite3", db, None, sql).await
+}
+
+/// Run a read query with a SQLite CLI (`sqlite3`, `sqlcipher`) in JSON mode and decode its rows.
+/// `init` runs first with its output discarded (e.g. `PRAGMA key`, which prints `ok`).
+pub(crate) async fn sqlite_query<T: DeserializeOwned>(
+    program: &str,
+    db: &Path,
+    init: Option<&str>,
+    sql: &str,
+) -> Result<Vec<T>, String> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.arg("-json");
+    if let Some(init) = init {
+        let discard = db.with_extension("init-output");
+        cmd.arg("-cmd")
+            .arg(format!(".output '{}'", discard.display()))
+            .arg("-cmd")
+            .arg(init)
+            .arg("-cmd")
+            .arg(".output");
+    }
+    cmd.arg(db).arg(sql).kill_on_drop(true);
+    let out = tokio::time::timeout(SQLITE_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("{program} timed out"))?
+        .map_err(|e| format!("Failed to run {program} (is it installed?): {e}"))?;
+    if !out.status.success() {
+        return Err(format!("{program} failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&stdout).map_err(|e| format!("Unexpected {program} output: {e}"))
+}
+
+async fn load_sync_state(file: &Path) -> SyncState {
+    match tokio::fs::read_to_string(file).await {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+        Err(_) => SyncState::default(),
+    }
+}
+
+async fn save_sync_state(file: &Path, sync: &SyncState) -> Result<(), String> {
+    let s = serde_json::to_string(sync).map_err(|e| format!("Failed to serialize messages state: {e}"))?;
+    tokio::fs::write(file, s)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", file.display()))
+}
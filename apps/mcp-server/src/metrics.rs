@@ -0,0 +1,220 @@
+//! In-process timing metrics (`silo_metrics`): per-file ingest durations (by stage) and search
+//! latencies since the server started, with the slowest files and queries kept for inspection.
+//!
+//! Only memory is used: counts and totals, the last `RECENT` latencies for percentiles, and the
+//! `SLOWEST` entries of each kind. Ingests are recorded by index runs and `silo_ingest_file`;
+//! queries by `silo_search` (cached answers are counted, not timed). Files in locked collections
+//! are left out of the report.
+
+use crate::catalog::now_epoch_secs;
+use crate::database::preview;
+use crate::errors::ToolError;
+use crate::ingest::{IngestStats, IngestTimings};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Latencies kept for percentiles.
+const RECENT: usize = 1000;
+/// Slowest files / queries kept.
+const SLOWEST: usize = 20;
+/// Query text kept per slow query.
+const QUERY_PREVIEW_CHARS: usize = 200;
+/// An ingest this slow is logged and listed in the index run summary (`slow_files`).
+pub const SLOW_FILE_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowFile {
+    pub path: String,
+    pub elapsed_ms: u64,
+    /// The stage that took longest (`extract`, `chunk`, `embed`, `store`, `entities`).
+    pub slowest_stage: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size_bytes: Option<i64>,
+    pub extracted_chars: usize,
+    pub chunks: usize,
+    pub timings: IngestTimings,
+    pub at_epoch_secs: i64,
+}
+
+impl SlowFile {
+    pub fn from_stats(stats: &IngestStats) -> Self {
+        Self {
+            path: stats.path.clone(),
+            elapsed_ms: stats.timings.total_ms,
+            slowest_stage: stats.timings.slowest_stage(),
+            file_size_bytes: stats.file_size_bytes,
+            extracted_chars: stats.extracted_chars,
+            chunks: stats.chunks,
+            timings: stats.timings,
+            at_epoch_secs: now_epoch_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowQuery {
+    pub query: String,
+    pub elapsed_ms: u64,
+    pub embed_ms: u64,
+    pub search_ms: u64,
+    pub hits: usize,
+    pub at_epoch_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub mean_ms: f64,
+    /// Over the last `RECENT` (1000) samples.
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsReport {
+    pub ingest: LatencySummary,
+    pub queries: LatencySummary,
+    pub cached_queries: u64,
+    pub slowest_files: Vec<SlowFile>,
+    pub slowest_queries: Vec<SlowQuery>,
+}
+
+/// Count, total and recent samples of one kind of operation, plus its slowest entries.
+#[derive(Debug)]
+struct Series<T> {
+    count: u64,
+    total_ms: u64,
+    max_ms: u64,
+    recent: VecDeque<u64>,
+    /// Slowest first.
+    slowest: Vec<(u64, T)>,
+}
+
+impl<T: Clone> Series<T> {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            total_ms: 0,
+            max_ms: 0,
+            recent: VecDeque::with_capacity(RECENT),
+            slowest: vec![],
+        }
+    }
+
+    fn record(&mut self, ms: u64, entry: T) {
+        self.count += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+        if self.recent.len() == RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(ms);
+        if self.slowest.len() < SLOWEST || self.slowest.last().is_some_and(|(slow, _)| ms > *slow) {
+            let at = self.slowest.partition_point(|(slow, _)| *slow >= ms);
+            self.slowest.insert(at, (ms, entry));
+            self.slowest.truncate(SLOWEST);
+        }
+    }
+
+    fn summary(&self) -> LatencySummary {
+        let mut recent: Vec<u64> = self.recent.iter().copied().collect();
+        recent.sort_unstable();
+        let pct = |p: usize| recent.get(recent.len().saturating_sub(1) * p / 100).copied().unwrap_or(0);
+        LatencySummary {
+            count: self.count,
+            mean_ms: if self.count == 0 { 0.0 } else { self.total_ms as f64 / self.count as f64 },
+            p50_ms: pct(50),
+            p95_ms: pct(95),
+            max_ms: self.max_ms,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    ingest: Series<SlowFile>,
+    queries: Series<SlowQuery>,
+    cached_queries: u64,
+}
+
+/// Shared by the server (`AppState::metrics`) and passed to index runs.
+#[derive(Debug)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                ingest: Series::new(),
+                queries: Series::new(),
+                cached_queries: 0,
+            }),
+        }
+    }
+}
+
+impl Metrics {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn record_ingest(&self, stats: &IngestStats) {
+        let ms = stats.timings.total_ms;
+        if ms >= SLOW_FILE_MS {
+            tracing::warn!(
+                "Slow ingest: {} took {ms} ms (mostly {}; {} chars, {} chunks)",
+                stats.path,
+                stats.timings.slowest_stage(),
+                stats.extracted_chars,
+                stats.chunks
+            );
+        }
+        self.lock().ingest.record(ms, SlowFile::from_stats(stats));
+    }
+
+    pub fn record_query(&self, query: &str, embed_ms: u64, search_ms: u64, hits: usize) {
+        let elapsed_ms = embed_ms + search_ms;
+        let entry = SlowQuery {
+            query: preview(query, QUERY_PREVIEW_CHARS),
+            elapsed_ms,
+            embed_ms,
+            search_ms,
+            hits,
+            at_epoch_secs: now_epoch_secs(),
+        };
+        self.lock().queries.record(elapsed_ms, entry);
+    }
+
+    pub fn record_cached_query(&self) {
+        self.lock().cached_queries += 1;
+    }
+
+    /// Current numbers; slow files under `hidden` paths (locked collections) are left out.
+    pub fn report(&self, hidden: impl Fn(&str) -> bool) -> MetricsReport {
+        let inner = self.lock();
+        MetricsReport {
+            ingest: inner.ingest.summary(),
+            queries: inner.queries.summary(),
+            cached_queries: inner.cached_queries,
+            slowest_files: inner
+                .ingest
+                .slowest
+                .iter()
+                .filter(|(_, f)| !hidden(&f.path))
+                .map(|(_, f)| f.clone())
+                .collect(),
+            slowest_queries: inner.queries.slowest.iter().map(|(_, q)| q.clone()).collect(),
+        }
+    }
+}
+
+/// MCP tool entrypoint (`silo_metrics`).
+pub async fn metrics_tool(state: &crate::state::SharedState) -> Result<serde_json::Value, ToolError> {
+    let locked = crate::access::locked_paths(state).await;
+    let report = state.metrics.report(|p| locked.collection_of(p).is_some());
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize metrics: {e}").into())
+}
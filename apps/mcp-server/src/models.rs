@@ -0,0 +1,266 @@
+//! Embedding model management (`silo_models_list`, `silo_models_pull`, `silo_models_remove`).
+//!
+//! Models live in `models.cache_dir` (default `models/` in the data dir) in the Hugging Face hub
+//! layout fastembed uses (`models--<org>--<name>/`). The active model is downloaded and loaded on
+//! first use and unloaded after `models.idle_unload_secs` without use; `pull` does that ahead of
+//! time (e.g. right after install), `remove` frees the disk space.
+
+use crate::embed::ModelStatus;
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const HUB_DIR_PREFIX: &str = "models--";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelArgs {
+    /// Hugging Face repo, e.g. "Xenova/bge-small-en-v1.5" (default: the active model).
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelEntry {
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dim: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub downloaded: bool,
+    pub size_bytes: u64,
+    /// Used for indexing and search (other models are listed for download only).
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelList {
+    pub cache_dir: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<ModelStatus>,
+    pub models: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullResult {
+    pub model: String,
+    pub size_bytes: u64,
+    pub elapsed_ms: u64,
+    /// The active model was also loaded into memory.
+    pub loaded: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoveResult {
+    pub model: String,
+    pub removed_bytes: u64,
+    /// The model was in memory and was unloaded (it downloads again on next use).
+    pub unloaded: bool,
+}
+
+/// MCP tool entrypoint (`silo_models_list`).
+pub async fn models_list_tool(state: &SharedState, _args: Value) -> Result<Value, ToolError> {
+    let list = list_models(state).await;
+    serde_json::to_value(list).map_err(|e| format!("failed to serialize models: {e}").into())
+}
+
+/// MCP tool entrypoint (`silo_models_pull`).
+pub async fn models_pull_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ModelArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let pulled = pull_model(state, args.model.as_deref()).await?;
+    serde_json::to_value(pulled).map_err(|e| format!("failed to serialize pull result: {e}").into())
+}
+
+/// MCP tool entrypoint (`silo_models_remove`).
+pub async fn models_remove_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ModelArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let removed = remove_model(state, args.model.as_deref()).await?;
+    serde_json::to_value(removed).map_err(|e| format!("failed to serialize remove result: {e}").into())
+}
+
+pub async fn list_models(state: &SharedState) -> ModelList {
+    let active = state.embedder.model_status();
+    let active_code = active.as_ref().map(|s| s.model.clone());
+    let downloaded = downloaded_models(&state.models_dir).await;
+
+    let mut models: Vec<ModelEntry> = supported_models()
+        .into_iter()
+        .map(|(model, dim, description)| ModelEntry {
+            active: active_code.as_deref() == Some(model.as_str()),
+            downloaded: false,
+            size_bytes: 0,
+            dim: Some(dim),
+            description: Some(description),
+            model,
+        })
+        .collect();
+    for (model, dir) in &downloaded {
+        let size_bytes = dir_size(dir).await;
+        match models.iter_mut().find(|m| &m.model == model) {
+            Some(m) => {
+                m.downloaded = true;
+                m.size_bytes = size_bytes;
+            }
+            None => models.push(ModelEntry {
+                active: active_code.as_deref() == Some(model.as_str()),
+                model: model.clone(),
+                dim: None,
+                description: None,
+                downloaded: true,
+                size_bytes,
+            }),
+        }
+    }
+    // Active first, then downloaded, then the rest by name.
+    models.sort_by(|a, b| {
+        b.active
+            .cmp(&a.active)
+            .then(b.downloaded.cmp(&a.downloaded))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+
+    ModelList {
+        cache_dir: state.models_dir.to_string_lossy().to_string(),
+        active,
+        models,
+    }
+}
+
+/// Download `model` (default: the active one, which is also loaded).
+pub async fn pull_model(state: &SharedState, model: Option<&str>) -> Result<PullResult, String> {
+    let Some(active) = state.embedder.model_status() else {
+        return Err("Embeddings are disabled (build with `--features embeddings`)".to_string());
+    };
+    let model = model.map(str::trim).filter(|m| !m.is_empty()).unwrap_or(&active.model).to_string();
+    let started = Instant::now();
+
+    let loaded = if model == active.model {
+        state.embedder.load().await?;
+        true
+    } else {
+        download(&state.models_dir, &model).await?;
+        false
+    };
+
+    let size_bytes = match hub_dir(&state.models_dir, &model) {
+        Some(dir) => dir_size(&dir).await,
+        None => 0,
+    };
+    Ok(PullResult {
+        model,
+        size_bytes,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        loaded,
+    })
+}
+
+/// Delete a downloaded model (unloading it first when it is the active one).
+pub async fn remove_model(state: &SharedState, model: Option<&str>) -> Result<RemoveResult, String> {
+    let active = state.embedder.model_status().map(|s| s.model);
+    let Some(model) = model.map(str::trim).filter(|m| !m.is_empty()).map(str::to_string).or(active.clone()) else {
+        return Err("Missing `model`".to_string());
+    };
+    // Only directories found in the cache are ever deleted.
+    let Some((_, dir)) = downloaded_models(&state.models_dir)
+        .await
+        .into_iter()
+        .find(|(m, _)| *m == model)
+    else {
+        return Err(format!("{model} is not downloaded in {}", state.models_dir.display()));
+    };
+
+    let unloaded = active.as_deref() == Some(model.as_str()) && state.embedder.unload();
+    let removed_bytes = dir_size(&dir).await;
+    tokio::fs::remove_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to remove {}: {e}", dir.display()))?;
+    Ok(RemoveResult {
+        model,
+        removed_bytes,
+        unloaded,
+    })
+}
+
+/// (model code, dim, description) of every model fastembed can download.
+fn supported_models() -> Vec<(String, usize, String)> {
+    #[cfg(feature = "embeddings")]
+    {
+        fastembed::TextEmbedding::list_supported_models()
+            .into_iter()
+            .map(|m| (m.model_code, m.dim, m.description))
+            .collect()
+    }
+    #[cfg(not(feature = "embeddings"))]
+    {
+        vec![]
+    }
+}
+
+#[cfg(feature = "embeddings")]
+async fn download(cache_dir: &Path, model: &str) -> Result<(), String> {
+    let Some(info) = fastembed::TextEmbedding::list_supported_models()
+        .into_iter()
+        .find(|m| m.model_code == model)
+    else {
+        return Err(format!("Unknown model {model}. See `silo_models_list` for available models"));
+    };
+    let opts = fastembed::InitOptions::new(info.model)
+        .with_cache_dir(cache_dir.to_path_buf())
+        .with_show_download_progress(false);
+    tokio::task::spawn_blocking(move || fastembed::TextEmbedding::try_new(opts).map(|_| ()))
+        .await
+        .map_err(|e| format!("download task failed: {e}"))?
+        .map_err(|e| format!("Failed to download {model}: {e}"))
+}
+
+#[cfg(not(feature = "embeddings"))]
+async fn download(_cache_dir: &Path, model: &str) -> Result<(), String> {
+    Err(format!("Cannot download {model}: embeddings are disabled"))
+}
+
+/// Downloaded models in `cache_dir` as (model code, directory).
+async fn downloaded_models(cache_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut out = vec![];
+    let Ok(mut rd) = tokio::fs::read_dir(cache_dir).await else {
+        return out;
+    };
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(repo) = name.strip_prefix(HUB_DIR_PREFIX) else {
+            continue;
+        };
+        if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+            out.push((repo.replacen("--", "/", 1), entry.path()));
+        }
+    }
+    out
+}
+
+fn hub_dir(cache_dir: &Path, model: &str) -> Option<PathBuf> {
+    let dir = cache_dir.join(format!("{HUB_DIR_PREFIX}{}", model.replace('/', "--")));
+    dir.is_dir().then_some(dir)
+}
+
+/// Bytes in regular files under `dir` (hub snapshots are symlinks into `blobs/`, counted once).
+async fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0u64;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(d) = stack.pop() {
+        let Ok(mut rd) = tokio::fs::read_dir(&d).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            let Ok(meta) = tokio::fs::symlink_metadata(entry.path()).await else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else if meta.is_file() {
+                total += meta.len();
+            }
+        }
+    }
+    total
+}
@@ -0,0 +1,167 @@
+//! Network filesystem detection for the scanners.
+//!
+//! Reads the mount table (`/proc/self/mounts` on Linux, `mount` output on macOS) once per run
+//! and reports which paths live on NFS/SMB/other remote mounts, so indexing can throttle or
+//! skip them (`network_mounts` in the filesystem source config).
+
+use crate::config::NetworkMountMode;
+use std::path::{Path, PathBuf};
+
+/// Filesystem types that go over the network.
+const REMOTE_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "davfs",
+    "fuse.davfs",
+    "fuse.sshfs",
+    "sshfs",
+    "fuse.rclone",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "afs",
+    "lustre",
+];
+
+#[derive(Debug, Clone)]
+pub struct NetworkMount {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+}
+
+impl NetworkMount {
+    /// Record this mount in a scan summary list (once).
+    pub fn note(&self, mode: NetworkMountMode, out: &mut Vec<String>) {
+        let mode = match mode {
+            NetworkMountMode::Throttle => "throttled",
+            NetworkMountMode::Skip => "skipped",
+            NetworkMountMode::Local => "local",
+        };
+        let label = format!("{} ({}, {mode})", self.mount_point.display(), self.fs_type);
+        if !out.contains(&label) {
+            out.push(label);
+        }
+    }
+}
+
+/// Remote mounts present when the run started.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkMounts {
+    mounts: Vec<NetworkMount>,
+}
+
+impl NetworkMounts {
+    /// Mounts to treat specially under `mode` (none for `local`).
+    pub async fn for_mode(mode: NetworkMountMode) -> Self {
+        if mode == NetworkMountMode::Local {
+            return Self::default();
+        }
+        Self::detect().await
+    }
+
+    /// Best effort: an unreadable mount table means "no network mounts".
+    pub async fn detect() -> Self {
+        let mut mounts: Vec<NetworkMount> = read_mount_table()
+            .await
+            .into_iter()
+            .filter(|m| is_remote_fs_type(&m.fs_type))
+            .collect();
+        // Longest mount point first so nested mounts win in `find`.
+        mounts.sort_by_key(|m| std::cmp::Reverse(m.mount_point.as_os_str().len()));
+        Self { mounts }
+    }
+
+    /// The remote mount containing `path`, if any.
+    pub fn find(&self, path: &Path) -> Option<&NetworkMount> {
+        self.mounts.iter().find(|m| path.starts_with(&m.mount_point))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+}
+
+fn is_remote_fs_type(fs_type: &str) -> bool {
+    let t = fs_type.to_ascii_lowercase();
+    REMOTE_FS_TYPES.contains(&t.as_str())
+}
+
+async fn read_mount_table() -> Vec<NetworkMount> {
+    #[cfg(target_os = "linux")]
+    {
+        match tokio::fs::read_to_string("/proc/self/mounts").await {
+            Ok(s) => parse_proc_mounts(&s),
+            Err(_) => vec![],
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        match tokio::process::Command::new("/sbin/mount").output().await {
+            Ok(out) => parse_bsd_mount_output(&String::from_utf8_lossy(&out.stdout)),
+            Err(_) => vec![],
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        vec![]
+    }
+}
+
+/// `/proc/self/mounts`: `source mount_point fs_type options 0 0`, spaces escaped as `\040`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_mounts(s: &str) -> Vec<NetworkMount> {
+    s.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _source = fields.next()?;
+            let mount_point = fields.next()?;
+            let fs_type = fields.next()?;
+            Some(NetworkMount {
+                mount_point: PathBuf::from(unescape_octal(mount_point)),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// BSD/macOS `mount`: `//user@nas/share on /Volumes/share (smbfs, nodev, nosuid)`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_bsd_mount_output(s: &str) -> Vec<NetworkMount> {
+    s.lines()
+        .filter_map(|line| {
+            let (_source, rest) = line.split_once(" on ")?;
+            let (mount_point, opts) = rest.rsplit_once(" (")?;
+            let fs_type = opts.split([',', ')']).next()?.trim();
+            Some(NetworkMount {
+                mount_point: PathBuf::from(mount_point),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn unescape_octal(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|d| bytes[i] == b'\\' && d.iter().all(|b| (b'0'..=b'7').contains(b)))
+            .map(|d| d.iter().fold(0u32, |acc, b| acc * 8 + u32::from(b - b'0')));
+        if let Some(v) = octal.and_then(|v| u8::try_from(v).ok()) {
+            out.push(v);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
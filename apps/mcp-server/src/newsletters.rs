@@ -0,0 +1,594 @@
+//! Newsletter / RSS archive folder (`newsletters` source, `silo_index_newsletters`).
+//!
+//! A background task checks the folder (and its subfolders) every `poll_secs` and ingests new or
+//! changed items: saved web pages (`.html`/`.htm`, through the HTML extractor) and e-mails
+//! (`.eml`, the HTML or plain-text part). Each item is stored under its own path, headed with its
+//! feed, title, author, date and link, tagged `newsletter` plus its feed, and dated by its
+//! publication date (catalog `created_epoch_secs`, used by `silo_timeline`).
+//!
+//! The feed is the item's first subfolder (one folder per feed, as most readers export), else the
+//! sender's list or display name for e-mails and the page's site name for HTML. The filesystem
+//! indexer leaves the folder to this connector (see `crate::state::compile_from_config`).
+
+use crate::config::{FileSystemSourceConfig, NewslettersSourceConfig};
+use crate::errors::ToolError;
+use crate::extract::{decode_entities, html_to_text, truncate_text, ExtractKind, ExtractResult};
+use crate::filesystem::modified_epoch_secs;
+use crate::ingest::ExtractedDocument;
+use crate::state::{expand_tilde, SharedState};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const ITEM_EXTENSIONS: &[&str] = &["html", "htm", "eml"];
+/// Larger files are not newsletters (or carry attachments not worth reading).
+const MAX_ITEM_BYTES: u64 = 20 * 1024 * 1024;
+const MIN_POLL_SECS: u64 = 60;
+/// Nested multiparts followed at most.
+const MAX_MIME_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NewsletterPass {
+    pub folder: String,
+    /// Items found in the folder.
+    pub items: usize,
+    /// Ingested in this pass.
+    pub indexed: usize,
+    pub unchanged: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Metadata and text of one item.
+#[derive(Debug, Default)]
+struct Item {
+    feed: Option<String>,
+    title: Option<String>,
+    author: Option<String>,
+    published_epoch_secs: Option<i64>,
+    link: Option<String>,
+    html: bool,
+    text: String,
+}
+
+/// Start the background watcher (returns at once when no newsletters source is configured; the
+/// task stops when the source is removed from the config).
+pub fn spawn_watcher(state: &SharedState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        while let Some(cfg) = state.newsletters_config().await {
+            if state.db.is_enabled() {
+                let res = index_newsletters(&state).await;
+                match &res {
+                    Ok(pass) if pass.indexed > 0 => {
+                        tracing::info!("Indexed {} new newsletter items from {}", pass.indexed, pass.folder)
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Newsletter indexing failed: {e}"),
+                }
+                let outcome = res.as_ref().map(|_| ()).map_err(|e| e.message.as_str());
+                crate::notify::source_pass(&state, "newsletters", outcome).await;
+            }
+            tokio::time::sleep(Duration::from_secs(cfg.poll_secs.max(MIN_POLL_SECS))).await;
+        }
+    });
+}
+
+/// MCP tool entrypoint (`silo_index_newsletters`).
+pub async fn index_newsletters_tool(state: &SharedState, _args: Value) -> Result<Value, ToolError> {
+    let pass = index_newsletters(state).await?;
+    serde_json::to_value(pass).map_err(|e| format!("failed to serialize newsletter pass: {e}").into())
+}
+
+/// Ingest items that are new or changed since the last pass.
+pub async fn index_newsletters(state: &SharedState) -> Result<NewsletterPass, ToolError> {
+    let Some(cfg) = state.newsletters_config().await else {
+        return Err(
+            "Newsletters source not configured. Add {\"type\": \"newsletters\", \"folder\": \"...\"} to `sources` in the config"
+                .to_string().into(),
+        );
+    };
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let _pass = state.newsletter_pass.lock().await;
+
+    let folder = newsletters_folder(&cfg);
+    let fs_cfg = state.filesystem_config().await.unwrap_or_default();
+    let mut pass = NewsletterPass {
+        folder: folder.to_string_lossy().to_string(),
+        ..NewsletterPass::default()
+    };
+
+    for (path, meta) in list_items(&folder).await? {
+        pass.items += 1;
+        let path_str = path.to_string_lossy().to_string();
+        let unchanged = state.catalog.read().await.get(&path_str).is_some_and(|e| {
+            e.size_bytes == Some(meta.len() as i64) && e.modified_epoch_secs == modified_epoch_secs(&meta)
+        });
+        if unchanged {
+            pass.unchanged += 1;
+            continue;
+        }
+        match store_item(state, &fs_cfg, &folder, &path, &meta).await {
+            Ok(()) => pass.indexed += 1,
+            Err(e) => pass.errors.push(format!("{path_str}: {e}")),
+        }
+    }
+
+    if pass.indexed > 0 {
+        state.index_changed();
+        state.save_catalog().await;
+    }
+    Ok(pass)
+}
+
+/// The configured folder, `~` expanded.
+pub fn newsletters_folder(cfg: &NewslettersSourceConfig) -> PathBuf {
+    expand_tilde(&cfg.folder.to_string_lossy())
+}
+
+async fn store_item(
+    state: &SharedState,
+    fs_cfg: &FileSystemSourceConfig,
+    folder: &Path,
+    path: &Path,
+    meta: &std::fs::Metadata,
+) -> Result<(), String> {
+    let raw = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read file {}: {e}", path.display()))?;
+    let is_eml = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("eml"));
+    let mut item = if is_eml { parse_eml(&raw) } else { parse_html(&String::from_utf8_lossy(&raw)) };
+    if let Some(feed) = subfolder_feed(folder, path) {
+        item.feed = Some(feed);
+    }
+
+    let mut text = header(&item);
+    text.push_str(&item.text);
+    let truncated = truncate_text(&mut text, fs_cfg.max_text_bytes);
+    let mut attrs = crate::xattrs::read_file_attributes(path);
+    let feed_tag = item.feed.as_deref().map(crate::tagging::normalize_tag);
+    for tag in ["newsletter".to_string()].into_iter().chain(feed_tag) {
+        if !tag.is_empty() && !attrs.tags.contains(&tag) {
+            attrs.tags.push(tag);
+        }
+    }
+    let mtime = modified_epoch_secs(meta);
+    let doc = ExtractedDocument {
+        path: path.to_string_lossy().to_string(),
+        extracted: ExtractResult {
+            kind: if item.html { ExtractKind::Html } else { ExtractKind::Text },
+            text,
+            truncated,
+            page_starts: vec![],
+        },
+        size_bytes: Some(meta.len() as i64),
+        mtime_epoch_secs: mtime,
+        created_epoch_secs: item.published_epoch_secs.or(mtime),
+        inode: crate::filesystem::inode(meta),
+        attrs,
+    };
+    let stats = crate::ingest::process_extracted(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
+        doc,
+        &fs_cfg.chunking(),
+    )
+    .await?;
+    state.metrics.record_ingest(&stats);
+    // Recorded even without text, so empty items are not read again every pass.
+    state.catalog.write().await.record_ingest(&stats);
+    Ok(())
+}
+
+/// `Feed:`/`Title:`/`Author:`/`Date:`/`Link:` lines for the metadata that is known, then a blank
+/// line.
+fn header(item: &Item) -> String {
+    let date = item
+        .published_epoch_secs
+        .and_then(|s| chrono::DateTime::from_timestamp(s, 0))
+        .map(|d| d.format("%Y-%m-%d").to_string());
+    let fields = [
+        ("Feed", item.feed.as_deref()),
+        ("Title", item.title.as_deref()),
+        ("Author", item.author.as_deref()),
+        ("Date", date.as_deref()),
+        ("Link", item.link.as_deref()),
+    ];
+    let mut out = String::new();
+    for (name, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            out.push_str(&format!("{name}: {value}\n"));
+        }
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Name of the first folder between `folder` and `path`, if any.
+fn subfolder_feed(folder: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(folder).ok()?;
+    let mut components = rel.components();
+    let first = components.next()?;
+    components.next()?;
+    Some(first.as_os_str().to_string_lossy().to_string())
+}
+
+/// Items in `folder` and its subfolders (hidden entries and symlinked folders skipped), oldest
+/// first.
+async fn list_items(folder: &Path) -> Result<Vec<(PathBuf, std::fs::Metadata)>, String> {
+    let mut rd = tokio::fs::read_dir(folder)
+        .await
+        .map_err(|e| format!("Cannot read newsletters folder {}: {e}", folder.display()))?;
+    let mut out = vec![];
+    let mut pending = vec![];
+    loop {
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type().await else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let is_item = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| ITEM_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+            if !is_item {
+                continue;
+            }
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            if meta.is_file() && meta.len() <= MAX_ITEM_BYTES {
+                out.push((path, meta));
+            }
+        }
+        let Some(next) = pending.pop() else {
+            break;
+        };
+        // An unreadable subfolder only loses its own items.
+        match tokio::fs::read_dir(&next).await {
+            Ok(next) => rd = next,
+            Err(e) => tracing::debug!("Skipping {}: {e}", next.display()),
+        }
+    }
+    out.sort_by_key(|(_, meta)| modified_epoch_secs(meta));
+    Ok(out)
+}
+
+/// A saved web page: text, `<title>` (or `og:title`), site name, author, date and URL from its
+/// meta tags.
+fn parse_html(html: &str) -> Item {
+    let (title, text) = html_to_text(html);
+    let metas = meta_tags(html);
+    let meta = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| metas.iter().find(|(name, _)| name == k).map(|(_, v)| v.clone()))
+            .filter(|v| !v.is_empty())
+    };
+    let link = meta(&["og:url"]).or_else(|| canonical_link(html)).or_else(|| saved_from(html));
+    let published = meta(&["article:published_time", "date", "dc.date", "pubdate", "og:updated_time"])
+        .and_then(|d| parse_date(&d));
+    Item {
+        feed: meta(&["og:site_name", "application-name"]),
+        title: title.or_else(|| meta(&["og:title"])),
+        author: meta(&["author", "article:author"]),
+        published_epoch_secs: published,
+        link,
+        html: true,
+        text,
+    }
+}
+
+/// `(name or property, content)` of every `<meta>` tag, names lowercased.
+fn meta_tags(html: &str) -> Vec<(String, String)> {
+    let lower = html.to_ascii_lowercase();
+    lower
+        .match_indices("<meta")
+        .filter_map(|(i, _)| {
+            let tag = &html[i..i + lower[i..].find('>')?];
+            let name = attr(tag, "property").or_else(|| attr(tag, "name"))?;
+            Some((name.to_ascii_lowercase(), attr(tag, "content")?))
+        })
+        .collect()
+}
+
+/// `href` of `<link rel="canonical">`.
+fn canonical_link(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    lower.match_indices("<link").find_map(|(i, _)| {
+        let tag = &html[i..i + lower[i..].find('>')?];
+        attr(tag, "rel")
+            .filter(|r| r.eq_ignore_ascii_case("canonical"))
+            .and_then(|_| attr(tag, "href"))
+    })
+}
+
+/// The URL browsers note when saving a page (`<!-- saved from url=(0042)https://... -->`).
+fn saved_from(html: &str) -> Option<String> {
+    let head = &html[..html.len().min(4096)];
+    let start = head.find("saved from url=(")?;
+    let rest = &head[start..];
+    let url = &rest[rest.find(')')? + 1..];
+    let end = url.find([' ', '-', '\n']).unwrap_or(url.len());
+    Some(url[..end].to_string()).filter(|u| !u.is_empty())
+}
+
+/// Value of attribute `name` in `tag` (the text of one start tag), entities decoded.
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(p) = lower[from..].find(name).map(|p| from + p) {
+        from = p + name.len();
+        let rest = lower[from..].trim_start();
+        if p == 0 || !lower.as_bytes()[p - 1].is_ascii_whitespace() || !rest.starts_with('=') {
+            continue;
+        }
+        let value = tag[tag.len() - rest.len() + 1..].trim_start();
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>').next().unwrap_or_default(),
+        };
+        return Some(decode_entities(value).trim().to_string());
+    }
+    None
+}
+
+/// RFC 3339 or a plain `YYYY-MM-DD` date.
+fn parse_date(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if let Ok(d) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(d.timestamp());
+    }
+    let day = chrono::NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()?;
+    Some(day.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+}
+
+/// An e-mail: subject, sender, date and list from its headers; text from the HTML part (else the
+/// plain-text part).
+fn parse_eml(raw: &[u8]) -> Item {
+    let (headers, body) = split_message(raw);
+    let get = |name: &str| header_value(&headers, name).map(decode_words).filter(|v| !v.is_empty());
+    let from = get("from").map(|f| display_name(&f));
+    let feed = get("list-id").map(|l| display_name(&l)).or_else(|| from.clone());
+    let published = header_value(&headers, "date").and_then(|d| {
+        // Drop a trailing comment such as `(UTC)`.
+        let d = d.split('(').next().unwrap_or(d).trim();
+        chrono::DateTime::parse_from_rfc2822(d).ok().map(|d| d.timestamp())
+    });
+    let (html, text) = match text_part(&headers, body, 0) {
+        Some((mime, text)) if mime == "text/html" => (true, html_to_text(&text).1),
+        Some((_, text)) => (false, text.trim().to_string()),
+        None => (false, String::new()),
+    };
+    Item {
+        feed,
+        title: get("subject"),
+        author: from,
+        published_epoch_secs: published,
+        link: get("list-post")
+            .or_else(|| get("archived-at"))
+            .map(|l| l.trim_matches(['<', '>']).to_string()),
+        html,
+        text,
+    }
+}
+
+/// Headers (names lowercased, folded lines joined) and the body after the blank line.
+fn split_message(raw: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let (head_end, body_start) = (0..raw.len())
+        .find_map(|i| {
+            if raw[i..].starts_with(b"\r\n\r\n") {
+                Some((i, i + 4))
+            } else if raw[i..].starts_with(b"\n\n") {
+                Some((i, i + 2))
+            } else {
+                None
+            }
+        })
+        .unwrap_or((raw.len(), raw.len()));
+    let mut headers: Vec<(String, String)> = vec![];
+    for line in String::from_utf8_lossy(&raw[..head_end]).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, &raw[body_start..])
+}
+
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// Parameter `name` of a header value like `text/html; charset="utf-8"`.
+fn header_param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (key, v) = p.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| v.trim().trim_matches('"').to_string())
+    })
+}
+
+/// MIME type and decoded text of the best text part: HTML over plain text, attachments skipped.
+fn text_part(headers: &[(String, String)], body: &[u8], depth: usize) -> Option<(String, String)> {
+    let ctype = header_value(headers, "content-type").unwrap_or("text/plain");
+    let mime = ctype.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    if mime.starts_with("multipart/") {
+        let boundary = header_param(ctype, "boundary")?;
+        if depth >= MAX_MIME_DEPTH {
+            return None;
+        }
+        let parts: Vec<(String, String)> = split_multipart(body, &boundary)
+            .into_iter()
+            .filter_map(|part| {
+                let (headers, body) = split_message(part);
+                text_part(&headers, body, depth + 1)
+            })
+            .collect();
+        let html = parts.iter().position(|(m, _)| m == "text/html").unwrap_or(0);
+        return parts.into_iter().nth(html);
+    }
+    let attachment = header_value(headers, "content-disposition")
+        .is_some_and(|d| d.trim_start().to_ascii_lowercase().starts_with("attachment"));
+    if !mime.starts_with("text/") || attachment {
+        return None;
+    }
+    let bytes = match header_value(headers, "content-transfer-encoding").map(|e| e.trim().to_ascii_lowercase()) {
+        Some(e) if e == "base64" => {
+            let compact: Vec<u8> = body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .unwrap_or_else(|_| body.to_vec())
+        }
+        Some(e) if e == "quoted-printable" => decode_quoted_printable(body, false),
+        _ => body.to_vec(),
+    };
+    Some((mime, decode_charset(&bytes, header_param(ctype, "charset").as_deref())))
+}
+
+/// The parts between `--boundary` lines (preamble and epilogue dropped).
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delim = format!("--{boundary}");
+    let delim = delim.as_bytes();
+    let find = |hay: &[u8]| hay.windows(delim.len()).position(|w| w == delim);
+    let mut parts = vec![];
+    let Some(start) = find(body) else {
+        return parts;
+    };
+    let mut rest = &body[start + delim.len()..];
+    while !rest.starts_with(b"--") {
+        let line_end = rest.iter().position(|&b| b == b'\n').map_or(rest.len(), |p| p + 1);
+        rest = &rest[line_end..];
+        let Some(end) = find(rest) else {
+            parts.push(rest);
+            break;
+        };
+        parts.push(&rest[..end]);
+        rest = &rest[end + delim.len()..];
+    }
+    parts
+}
+
+/// Quoted-printable bytes decoded (`=XX` escapes, `=` soft line breaks; `_` is a space in
+/// encoded header words).
+fn decode_quoted_printable(input: &[u8], header: bool) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'=' if input[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if input[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' if i + 2 < input.len() => match (hex(input[i + 1]), hex(input[i + 2])) {
+                (Some(h), Some(l)) => {
+                    out.push(h << 4 | l);
+                    i += 3;
+                }
+                _ => {
+                    out.push(b'=');
+                    i += 1;
+                }
+            },
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Text in `charset`: UTF-8 (the default), or Latin-1 / Windows-1252 byte for byte.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.map(str::to_ascii_lowercase).as_deref() {
+        Some("iso-8859-1" | "latin1" | "windows-1252" | "cp1252") => bytes.iter().map(|&b| b as char).collect(),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// A header value with its RFC 2047 encoded words (`=?utf-8?B?...?=`) decoded.
+fn decode_words(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let before = &rest[..start];
+        match encoded_word(&rest[start..]) {
+            Some((text, len)) => {
+                // Whitespace between two encoded words is not part of the text.
+                if !(after_word && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&text);
+                rest = &rest[start + len..];
+                after_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The text of the encoded word at the start of `s`, and its length.
+fn encoded_word(s: &str) -> Option<(String, usize)> {
+    let inner = s.strip_prefix("=?")?;
+    let (charset, rest) = inner.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let encoded = &rest[..end];
+    let bytes = match encoding {
+        "B" | "b" => base64::engine::general_purpose::STANDARD.decode(encoded).ok()?,
+        "Q" | "q" => decode_quoted_printable(encoded.as_bytes(), true),
+        _ => return None,
+    };
+    let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+    // A language suffix (`utf-8*en`) is not part of the charset.
+    let charset = charset.split('*').next().unwrap_or(charset);
+    Some((decode_charset(&bytes, Some(charset)), len))
+}
+
+/// `Name` of `"Name" <address>` (the address when there is no name).
+fn display_name(value: &str) -> String {
+    match value.split_once('<') {
+        Some((name, addr)) => {
+            let name = name.trim().trim_matches('"').trim();
+            if name.is_empty() {
+                addr.trim_end_matches('>').trim().to_string()
+            } else {
+                name.to_string()
+            }
+        }
+        None => value.trim().to_string(),
+    }
+}
@@ -0,0 +1,280 @@
+//! Append-only notes (`silo_append_note`): "remember that X" without general write access.
+//!
+//! Entries go into one folder (`notes.root`): today's daily note by default, or a named note
+//! file inside the folder. Nothing is ever replaced or removed; each call appends one
+//! timestamped list item and re-indexes the note so it is searchable right away.
+//!
+//! The note must resolve inside the root (symlinked subfolders cannot point it elsewhere), be a
+//! `.md` / `.markdown` / `.txt` file, and stay outside Silo's own folders. Collection locks apply to
+//! the resolved note, and every attempt goes to the shared write audit log (`crate::writes`).
+
+use crate::config::NotesConfig;
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use crate::writes::{audit_entry, refuse_own_folders};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+const NOTE_EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
+
+#[derive(Debug, Deserialize)]
+pub struct AppendNoteArgs {
+    pub text: String,
+    /// Note file relative to the notes folder (e.g. `ideas.md`). Default: `notes.default_file`,
+    /// else today's daily note.
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppendNoteResult {
+    pub path: String,
+    /// The appended entry, as written.
+    pub entry: String,
+    /// The note file did not exist before this call.
+    pub created: bool,
+    pub indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_error: Option<String>,
+}
+
+/// The configured notes folder, read at startup.
+pub struct Notes {
+    root: PathBuf,
+    default_file: Option<String>,
+    daily_format: String,
+    max_entry_bytes: u64,
+    /// Serializes appends, so two entries never interleave.
+    append: tokio::sync::Mutex<()>,
+}
+
+impl Notes {
+    pub fn from_config(cfg: &NotesConfig) -> Option<Self> {
+        let root = expand_tilde(&cfg.root.as_ref()?.to_string_lossy());
+        tracing::info!("Notes enabled (silo_append_note) in {}", root.display());
+        Some(Self {
+            root,
+            default_file: cfg.default_file.clone(),
+            daily_format: cfg.daily_format.clone(),
+            max_entry_bytes: cfg.max_entry_bytes,
+            append: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Note name relative to the root: `file`, the default file, or today's daily note.
+    fn note_name(&self, file: Option<&str>, now: &DateTime<Local>) -> Result<(String, bool), String> {
+        if let Some(name) = file.or(self.default_file.as_deref()).filter(|f| !f.trim().is_empty()) {
+            return Ok((name.trim().to_string(), false));
+        }
+        let mut name = String::new();
+        write!(name, "{}", now.format(&self.daily_format))
+            .map_err(|_| format!("Invalid notes.daily_format `{}`", self.daily_format))?;
+        Ok((name, true))
+    }
+}
+
+/// MCP tool entrypoint (`silo_append_note`).
+pub async fn append_note_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: AppendNoteArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let result = append_note(state, &args).await?;
+    serde_json::to_value(result).map_err(|e| format!("failed to serialize note result: {e}").into())
+}
+
+pub async fn append_note(state: &SharedState, args: &AppendNoteArgs) -> Result<AppendNoteResult, ToolError> {
+    let Some(notes) = &state.notes else {
+        return Err("silo_append_note is disabled (set notes.root in the config and restart)".into());
+    };
+    let result = append_checked(state, notes, args).await;
+    let requested = args.file.clone().unwrap_or_else(|| notes.root.to_string_lossy().to_string());
+    let mut entry = audit_entry("silo_append_note", &requested, &args.text);
+    entry["ok"] = json!(result.is_ok());
+    match &result {
+        Ok(r) => {
+            entry["resolved_path"] = json!(r.path);
+            entry["created"] = json!(r.created);
+        }
+        Err(e) => entry["error"] = json!(e.message),
+    }
+    state.write_audit.record(entry);
+    let mut result = result?;
+
+    index_note(state, &mut result).await;
+    Ok(result)
+}
+
+async fn append_checked(state: &SharedState, notes: &Notes, args: &AppendNoteArgs) -> Result<AppendNoteResult, ToolError> {
+    let text = args.text.trim();
+    if text.is_empty() {
+        return Err("`text` must not be empty".into());
+    }
+    if text.len() as u64 > notes.max_entry_bytes {
+        return Err(format!(
+            "text is {} bytes; notes.max_entry_bytes is {}",
+            text.len(),
+            notes.max_entry_bytes
+        )
+        .into());
+    }
+    let now = Local::now();
+    let (name, daily) = notes.note_name(args.file.as_deref(), &now)?;
+    let path = note_path(state, notes, &name).await?;
+
+    let _guard = notes.append.lock().await;
+    let existing = tokio::fs::symlink_metadata(&path).await.ok();
+    if let Some(meta) = &existing {
+        if meta.file_type().is_symlink() {
+            return Err("refusing to append through a symlink".into());
+        }
+        if !meta.is_file() {
+            return Err(format!("{} exists and is not a regular file", path.display()).into());
+        }
+    }
+
+    let stamp = if daily {
+        now.format("%H:%M").to_string()
+    } else {
+        now.format("%Y-%m-%d %H:%M").to_string()
+    };
+    let entry = format_entry(&stamp, text);
+    let mut out = String::new();
+    if existing.is_none() && daily {
+        out.push_str(&format!("# {}\n\n", now.format("%Y-%m-%d")));
+    } else if existing.is_some() && !ends_with_newline(&path).await? {
+        out.push('\n');
+    }
+    out.push_str(&entry);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    file.write_all(out.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to append to {}: {e}", path.display()))?;
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to append to {}: {e}", path.display()))?;
+    tracing::info!("silo_append_note appended {} bytes to {}", out.len(), path.display());
+
+    Ok(AppendNoteResult {
+        path: path.to_string_lossy().to_string(),
+        entry,
+        created: existing.is_none(),
+        indexed: false,
+        chunks: None,
+        index_error: None,
+    })
+}
+
+/// Resolve `name` inside the notes root, creating the root and subfolders as needed.
+async fn note_path(state: &SharedState, notes: &Notes, name: &str) -> Result<PathBuf, ToolError> {
+    let rel = Path::new(name);
+    if rel.is_absolute() || name.starts_with('~') {
+        return Err("`file` must be relative to the notes folder".into());
+    }
+    crate::tools::validate_safe_path(rel)?;
+    let ext_ok = rel
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| NOTE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+    if !ext_ok {
+        return Err(format!("notes must be {} files", NOTE_EXTENSIONS.join(" / ")).into());
+    }
+    let file_name = rel.file_name().ok_or_else(|| "`file` must name a file".to_string())?;
+
+    tokio::fs::create_dir_all(&notes.root)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", notes.root.display()))?;
+    let root = tokio::fs::canonicalize(&notes.root)
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {e}", notes.root.display()))?;
+    let parent = root.join(rel).parent().map(Path::to_path_buf).unwrap_or_else(|| root.clone());
+    tokio::fs::create_dir_all(&parent)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    let parent = tokio::fs::canonicalize(&parent)
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {e}", parent.display()))?;
+    if !parent.starts_with(&root) {
+        return Err(format!("{} is outside the notes folder", parent.display()).into());
+    }
+    let path = parent.join(file_name);
+    refuse_own_folders(state, &path).await?;
+    crate::access::locked_paths(state)
+        .await
+        .check(&path.to_string_lossy())?;
+    Ok(path)
+}
+
+/// `- <stamp> <text>`, with continuation lines indented under the list item.
+fn format_entry(stamp: &str, text: &str) -> String {
+    let mut out = format!("- {stamp}");
+    for (i, line) in text.lines().enumerate() {
+        if i == 0 {
+            out.push(' ');
+        } else {
+            out.push_str("\n  ");
+        }
+        out.push_str(line.trim_end());
+    }
+    out.push('\n');
+    out
+}
+
+async fn ends_with_newline(path: &Path) -> Result<bool, ToolError> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let len = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+    if len == 0 {
+        return Ok(true);
+    }
+    let mut last = [0u8; 1];
+    file.seek(std::io::SeekFrom::Start(len - 1))
+        .await
+        .map_err(|e| ToolError::read_failed(path.display(), e))?;
+    file.read_exact(&mut last)
+        .await
+        .map_err(|e| ToolError::read_failed(path.display(), e))?;
+    Ok(last[0] == b'\n')
+}
+
+/// Re-ingest the note so the new entry is searchable. Failures are reported, not raised: the
+/// entry is already on disk.
+async fn index_note(state: &SharedState, result: &mut AppendNoteResult) {
+    let Some(fs_cfg) = state.filesystem_config().await else {
+        result.index_error = Some("No filesystem source configured".to_string());
+        return;
+    };
+    let res = crate::ingest::process_file(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        None,
+        &result.path,
+        fs_cfg.max_text_bytes,
+        &fs_cfg.chunking(),
+    )
+    .await;
+    state.index_changed();
+    match res {
+        Ok(stats) => {
+            result.indexed = stats.stored;
+            result.chunks = Some(stats.chunks);
+            if stats.stored {
+                state.catalog.write().await.record_ingest(&stats);
+                state.save_catalog().await;
+            }
+        }
+        Err(e) => result.index_error = Some(e),
+    }
+}
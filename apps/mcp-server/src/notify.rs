@@ -0,0 +1,221 @@
+//! Notifications about background work: an index run finished, or a source keeps failing.
+//!
+//! Events go to every subscriber of `AppState::notifier`: each MCP session forwards them as
+//! `notifications/message` log messages (see `crate::server`), and the desktop app shows them as
+//! system notifications. Event types are switched on and off in the `notifications` config,
+//! which is read when an event fires. Titles and messages are in the configured locale
+//! (`crate::i18n`); `event` and `data` are not translated.
+
+use crate::catalog::now_epoch_secs;
+use crate::indexer::IndexSummary;
+use crate::state::SharedState;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Events kept for a subscriber that is behind; older ones are dropped.
+const CHANNEL_CAPACITY: usize = 64;
+/// Failing files named in a `source_failing` notification.
+const MAX_NAMED_FILES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    IndexFinished,
+    SourceFailing,
+}
+
+/// MCP log levels used by notifications (ordered by severity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+}
+
+impl NotifyLevel {
+    /// MCP `logging/setLevel` name; levels above `error` count as `error`.
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "debug" => Self::Debug,
+            "info" => Self::Info,
+            "notice" => Self::Notice,
+            "warning" => Self::Warning,
+            "error" | "critical" | "alert" | "emergency" => Self::Error,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub event: NotifyEvent,
+    pub level: NotifyLevel,
+    pub title: String,
+    pub message: String,
+    /// Event details (the index summary counts, the failing source and its error).
+    pub data: Value,
+}
+
+/// Fan-out of notifications, plus the failures-in-a-row counts of background sources.
+pub struct Notifier {
+    tx: broadcast::Sender<Notification>,
+    failing: Mutex<HashMap<String, u32>>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            failing: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Notifier {
+    pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
+        self.tx.subscribe()
+    }
+}
+
+/// Send `n` unless its event type is switched off.
+pub async fn notify(state: &SharedState, n: Notification) {
+    let cfg = state.config.read().await.notifications.clone();
+    let enabled = match n.event {
+        NotifyEvent::IndexFinished => cfg.index_finished,
+        NotifyEvent::SourceFailing => cfg.source_failing,
+    };
+    if enabled {
+        tracing::debug!("Notification: {}: {}", n.title, n.message);
+        // No subscribers is fine: nobody is listening.
+        let _ = state.notifier.tx.send(n);
+    }
+}
+
+/// After an index run that started at `started_epoch_secs`: report the run, and the files whose
+/// ingest has now failed `failure_threshold` times in a row.
+pub async fn index_finished(state: &SharedState, summary: &IndexSummary, started_epoch_secs: i64) {
+    let messages = state.messages().await;
+    let stored = summary.stored.to_string();
+    let skipped = summary.skipped.to_string();
+    let errors = summary.errors.to_string();
+    let counts = [("stored", stored.as_str()), ("skipped", skipped.as_str()), ("errors", errors.as_str())];
+    let message = if summary.errors > 0 {
+        messages.text(
+            "notify.index_finished.message_errors",
+            "Indexed {stored} files ({skipped} skipped, {errors} errors)",
+            &counts,
+        )
+    } else {
+        messages.text("notify.index_finished.message", "Indexed {stored} files ({skipped} skipped)", &counts)
+    };
+    notify(
+        state,
+        Notification {
+            event: NotifyEvent::IndexFinished,
+            level: if summary.errors > 0 { NotifyLevel::Notice } else { NotifyLevel::Info },
+            title: messages.text("notify.index_finished.title", "Indexing finished", &[]),
+            message,
+            data: json!({
+                "roots": summary.roots,
+                "scanned_files": summary.scanned_files,
+                "stored": summary.stored,
+                "skipped": summary.skipped,
+                "errors": summary.errors,
+                "elapsed_secs": now_epoch_secs() - started_epoch_secs,
+            }),
+        },
+    )
+    .await;
+
+    let threshold = state.config.read().await.notifications.failure_threshold.max(1);
+    let mut failing: Vec<(String, String)> = state
+        .catalog
+        .read()
+        .await
+        .failures()
+        .filter(|f| f.attempts == threshold && f.failed_at_epoch_secs >= started_epoch_secs)
+        .map(|f| (f.path.clone(), f.error.clone()))
+        .collect();
+    if failing.is_empty() {
+        return;
+    }
+    failing.sort();
+    let count = failing.len();
+    let named: Vec<&str> = failing.iter().take(MAX_NAMED_FILES).map(|(p, _)| p.as_str()).collect();
+    let more = if count > named.len() {
+        let rest = (count - named.len()).to_string();
+        messages.text("notify.files_failing.more", " and {count} more", &[("count", &rest)])
+    } else {
+        String::new()
+    };
+    let attempts = threshold.to_string();
+    notify(
+        state,
+        Notification {
+            event: NotifyEvent::SourceFailing,
+            level: NotifyLevel::Warning,
+            title: messages.text("notify.files_failing.title", "Files keep failing to index", &[]),
+            message: messages.text(
+                "notify.files_failing.message",
+                "Failed {attempts} times in a row: {files}{more}",
+                &[("attempts", &attempts), ("files", &named.join(", ")), ("more", &more)],
+            ),
+            data: json!({
+                "source": "filesystem",
+                "attempts": threshold,
+                "files": failing
+                    .iter()
+                    .map(|(path, error)| json!({ "path": path, "error": error }))
+                    .collect::<Vec<_>>(),
+            }),
+        },
+    )
+    .await;
+}
+
+/// Outcome of a background pass of `source` (e.g. the screenshots watcher): notifies once when
+/// it has failed `failure_threshold` times in a row; a success starts the count over.
+pub async fn source_pass(state: &SharedState, source: &str, result: Result<(), &str>) {
+    let threshold = state.config.read().await.notifications.failure_threshold.max(1);
+    let failures = {
+        let mut failing = state.notifier.failing.lock().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Ok(()) => {
+                failing.remove(source);
+                return;
+            }
+            Err(_) => {
+                let n = failing.entry(source.to_string()).or_insert(0);
+                *n += 1;
+                *n
+            }
+        }
+    };
+    if failures != threshold {
+        return;
+    }
+    let error = result.err().unwrap_or_default();
+    let messages = state.messages().await;
+    let attempts = threshold.to_string();
+    notify(
+        state,
+        Notification {
+            event: NotifyEvent::SourceFailing,
+            level: NotifyLevel::Warning,
+            title: messages.text("notify.source_failing.title", "The {source} source keeps failing", &[("source", source)]),
+            message: messages.text(
+                "notify.source_failing.message",
+                "Failed {attempts} times in a row: {error}",
+                &[("attempts", &attempts), ("error", error)],
+            ),
+            data: json!({ "source": source, "attempts": threshold, "error": error }),
+        },
+    )
+    .await;
+}
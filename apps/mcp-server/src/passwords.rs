@@ -0,0 +1,184 @@
+//! Passwords for encrypted documents (`silo_document_password_set`), so files the index walk skips
+//! as encrypted (`crate::encrypted`) can be indexed once the user opts in.
+//!
+//! A password is stored for a file or a folder; a folder's password applies to the files below it
+//! unless one of them (or a deeper folder) has its own. Passwords live in the OS keychain
+//! (`crate::keychain`), one entry per path, never in the config. Only the list of paths is kept,
+//! in `document_passwords.json` under `data_dir`, so the walk knows which encrypted files to let
+//! through without asking the keychain.
+//!
+//! Passwords open PDFs (`pdftotext -upw`; poppler only takes it as an argument, so it shows in the
+//! process list while `pdftotext` runs). Office files are not extracted, so encrypted ones stay
+//! skipped either way.
+
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+const FILE_NAME: &str = "document_passwords.json";
+const FILE_VERSION: u32 = 1;
+
+/// Paths with a stored password. Process-wide, like the extractor limits, so extraction finds
+/// them without threading state through.
+static PATHS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+#[derive(Serialize, Deserialize)]
+struct PasswordsFile {
+    version: u32,
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SetPasswordArgs {
+    path: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeletePasswordArgs {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtectedPath {
+    pub path: String,
+    /// `file` or `folder` (`missing` when the path no longer exists).
+    pub kind: &'static str,
+}
+
+/// Read the list of paths from `data_dir` (missing or unreadable file -> none).
+pub async fn load(data_dir: &Path) {
+    let file = data_dir.join(FILE_NAME);
+    let paths = match tokio::fs::read_to_string(&file).await {
+        Ok(s) => match serde_json::from_str::<PasswordsFile>(&s) {
+            Ok(f) if f.version == FILE_VERSION => f.paths,
+            Ok(_) => vec![],
+            Err(e) => {
+                tracing::warn!("Ignoring invalid document password list {}: {e}", file.display());
+                vec![]
+            }
+        },
+        Err(_) => vec![],
+    };
+    *PATHS.write().unwrap_or_else(|e| e.into_inner()) = paths;
+}
+
+async fn save(data_dir: &Path, paths: Vec<PathBuf>) -> Result<(), String> {
+    tokio::fs::create_dir_all(data_dir)
+        .await
+        .map_err(|e| format!("Failed to create data dir {}: {e}", data_dir.display()))?;
+    let file = data_dir.join(FILE_NAME);
+    let s = serde_json::to_string_pretty(&PasswordsFile {
+        version: FILE_VERSION,
+        paths,
+    })
+    .map_err(|e| format!("Failed to serialize document password list: {e}"))?;
+    tokio::fs::write(&file, s)
+        .await
+        .map_err(|e| format!("Failed to write document password list {}: {e}", file.display()))
+}
+
+/// The stored path whose password applies to `path`: itself, or its nearest folder with one.
+pub fn covering(path: &Path) -> Option<PathBuf> {
+    let paths = PATHS.read().unwrap_or_else(|e| e.into_inner());
+    paths
+        .iter()
+        .filter(|p| path.starts_with(p))
+        .max_by_key(|p| p.components().count())
+        .cloned()
+}
+
+/// The password for `path` from the keychain, if one is stored for it or a folder above it.
+pub async fn password_for(path: &Path) -> Result<Option<String>, String> {
+    match covering(path) {
+        Some(p) => crate::keychain::get(&account(&p)).await,
+        None => Ok(None),
+    }
+}
+
+fn account(path: &Path) -> String {
+    format!("document:{}", path.display())
+}
+
+fn validated_path(path: &str) -> Result<PathBuf, ToolError> {
+    let path = expand_tilde(path);
+    crate::tools::validate_safe_path(&path)?;
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path.display()).into());
+    }
+    Ok(path)
+}
+
+/// MCP tool entrypoint (`silo_document_password_set`).
+pub async fn set_password_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: SetPasswordArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let path = validated_path(&args.path)?;
+    if args.password.is_empty() {
+        return Err("password must not be empty".into());
+    }
+    let kind = path_kind(&path).await;
+    if kind == "missing" {
+        return Err(format!("No such file or folder: {}", path.display()).into());
+    }
+    crate::keychain::set(&account(&path), &args.password).await?;
+    let paths = {
+        let mut paths = PATHS.write().unwrap_or_else(|e| e.into_inner());
+        if !paths.contains(&path) {
+            paths.push(path.clone());
+            paths.sort();
+        }
+        paths.clone()
+    };
+    save(&state.data_dir, paths).await?;
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "kind": kind,
+        "stored": true,
+        "note": "Stored in the OS keychain. Encrypted PDFs under this path are indexed on the next index run (or with silo_ingest_file).",
+    }))
+}
+
+/// MCP tool entrypoint (`silo_document_password_delete`). Text already indexed with the password
+/// stays in the index.
+pub async fn delete_password_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: DeletePasswordArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let path = validated_path(&args.path)?;
+    let in_keychain = crate::keychain::delete(&account(&path)).await?;
+    let (listed, paths) = {
+        let mut paths = PATHS.write().unwrap_or_else(|e| e.into_inner());
+        let before = paths.len();
+        paths.retain(|p| p != &path);
+        (paths.len() < before, paths.clone())
+    };
+    if listed {
+        save(&state.data_dir, paths).await?;
+    }
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "deleted": in_keychain || listed,
+    }))
+}
+
+/// MCP tool entrypoint (`silo_document_password_list`): the paths, never the passwords.
+pub async fn list_passwords_tool() -> Result<Value, ToolError> {
+    let paths = PATHS.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut out = Vec::with_capacity(paths.len());
+    for path in paths {
+        out.push(ProtectedPath {
+            kind: path_kind(&path).await,
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+    Ok(json!({ "paths": out }))
+}
+
+async fn path_kind(path: &Path) -> &'static str {
+    match tokio::fs::metadata(path).await {
+        Ok(m) if m.is_dir() => "folder",
+        Ok(_) => "file",
+        Err(_) => "missing",
+    }
+}
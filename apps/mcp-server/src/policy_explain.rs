@@ -0,0 +1,310 @@
+//! `silo_explain_policy`: how the compiled filesystem policy treats one path, check by check, in
+//! the order the index walk applies them (see `crate::filesystem::scan_candidates`).
+//!
+//! Every check is reported, not just the first failing one, so a file blocked by several rules
+//! shows all of them; `decision` follows the first failure, which is what the walk records.
+
+use crate::config::{CompiledFileSystemPolicy, NetworkMountMode};
+use crate::encrypted::Encrypted;
+use crate::errors::ToolError;
+use crate::filesystem::{entry_metadata, has_skip_marker, is_cloud_placeholder, SkipReason};
+use crate::mounts::{NetworkMount, NetworkMounts};
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct ExplainPolicyArgs {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyCheck {
+    pub check: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    /// How the walk reports a failure of this check (`silo_preview_index` skip reasons).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<SkipReason>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyExplanation {
+    pub path: String,
+    /// `index` when every check passes, else `skip`.
+    pub decision: &'static str,
+    /// The first failing check, as the walk would record it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<SkipReason>,
+    /// Root the path is under (the walk never reaches paths outside the roots).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub checks: Vec<PolicyCheck>,
+    /// The file is in the catalog (indexed at some point, e.g. by `silo_ingest_file`).
+    pub in_catalog: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_chunks: Option<usize>,
+}
+
+/// MCP tool entrypoint (`silo_explain_policy`).
+pub async fn explain_policy_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ExplainPolicyArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let path = expand_tilde(&args.path);
+    crate::tools::validate_safe_path(&path)?;
+    crate::access::locked_paths(state).await.check(&path.to_string_lossy())?;
+    let explanation = explain(state, &path).await?;
+    serde_json::to_value(explanation).map_err(|e| format!("failed to serialize explanation: {e}").into())
+}
+
+pub async fn explain(state: &SharedState, path: &Path) -> Result<PolicyExplanation, ToolError> {
+    let roots: Vec<PathBuf> = state
+        .filesystem_roots()
+        .await
+        .iter()
+        .map(|r| expand_tilde(&r.to_string_lossy()))
+        .collect();
+    let policy_guard = state.fs_policy.read().await;
+    let Some(policy) = policy_guard.as_ref() else {
+        return Err(ToolError::no_filesystem_policy());
+    };
+
+    let mut checks = vec![];
+    let root = roots.iter().filter(|r| path.starts_with(r)).max_by_key(|r| r.components().count());
+    checks.push(PolicyCheck {
+        check: "roots",
+        passed: root.is_some(),
+        detail: match root {
+            Some(r) => format!("under root {}", r.display()),
+            None => "not under any filesystem root (only silo_ingest_file indexes it)".to_string(),
+        },
+        skip_reason: None,
+    });
+    let mounts = NetworkMounts::for_mode(policy.network_mounts.mode).await;
+    let mount = mounts.find(path);
+    if let Some(root) = root {
+        directory_checks(policy, root, path, mount, &mut checks).await;
+    }
+    file_checks(policy, path, mount, &mut checks).await;
+
+    let first_failure = checks.iter().find(|c| !c.passed);
+    let (in_catalog, catalog_chunks) = match state.catalog.read().await.get(&path.to_string_lossy()) {
+        Some(e) => (true, Some(e.chunks)),
+        None => (false, None),
+    };
+    Ok(PolicyExplanation {
+        path: path.to_string_lossy().to_string(),
+        decision: if first_failure.is_some() { "skip" } else { "index" },
+        skip_reason: first_failure.and_then(|c| c.skip_reason),
+        root: root.map(|r| r.to_string_lossy().to_string()),
+        in_catalog,
+        catalog_chunks,
+        checks,
+    })
+}
+
+/// Checks on the directories the walk passes through from `root` down to `path`'s parent.
+async fn directory_checks(
+    policy: &CompiledFileSystemPolicy,
+    root: &Path,
+    path: &Path,
+    mount: Option<&NetworkMount>,
+    checks: &mut Vec<PolicyCheck>,
+) {
+    let dirs: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|d| d.starts_with(root))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    let excluded: Vec<String> = dirs
+        .iter()
+        .flat_map(|d| {
+            policy
+                .matching_excludes(d)
+                .into_iter()
+                .map(move |g| format!("{} (`{g}`)", d.display()))
+        })
+        .collect();
+    checks.push(PolicyCheck {
+        check: "excluded_directory",
+        passed: excluded.is_empty(),
+        detail: if excluded.is_empty() {
+            "no enclosing directory matches an exclude glob".to_string()
+        } else {
+            format!("enclosing directory excluded: {}", excluded.join(", "))
+        },
+        skip_reason: Some(SkipReason::Excluded),
+    });
+
+    let depth = dirs.len().saturating_sub(1);
+    checks.push(PolicyCheck {
+        check: "depth",
+        passed: depth <= policy.max_depth,
+        detail: format!("parent directory at depth {depth} (max_depth {})", policy.max_depth),
+        skip_reason: Some(SkipReason::Depth),
+    });
+
+    let mut symlinked = vec![];
+    let mut marked = vec![];
+    for d in &dirs {
+        if !policy.follow_symlinks && matches!(entry_metadata(d, false).await, Ok(None)) {
+            symlinked.push(d.display().to_string());
+        }
+        if policy.honor_skip_markers && has_skip_marker(d).await {
+            marked.push(d.display().to_string());
+        }
+    }
+    checks.push(PolicyCheck {
+        check: "symlinked_directory",
+        passed: symlinked.is_empty(),
+        detail: if symlinked.is_empty() {
+            "no symlinked directory on the way".to_string()
+        } else {
+            format!("symlinked directory not followed (follow_symlinks is off): {}", symlinked.join(", "))
+        },
+        skip_reason: Some(SkipReason::Symlink),
+    });
+    checks.push(PolicyCheck {
+        check: "skip_marker",
+        passed: marked.is_empty(),
+        detail: if !policy.honor_skip_markers {
+            "skip markers are ignored (honor_skip_markers is off)".to_string()
+        } else if marked.is_empty() {
+            "no CACHEDIR.TAG/.nobackup marker on the way".to_string()
+        } else {
+            format!("directory has a CACHEDIR.TAG/.nobackup marker: {}", marked.join(", "))
+        },
+        skip_reason: Some(SkipReason::Marker),
+    });
+
+    let net = &policy.network_mounts;
+    checks.push(PolicyCheck {
+        check: "network_mount",
+        passed: !(mount.is_some() && net.mode == NetworkMountMode::Skip),
+        detail: match mount {
+            Some(m) => format!("on network mount {} ({}), mode {:?}", m.mount_point.display(), m.fs_type, net.mode),
+            None => "not on a network mount that is treated specially".to_string(),
+        },
+        skip_reason: Some(SkipReason::NetworkMount),
+    });
+}
+
+/// Checks on the file itself.
+async fn file_checks(
+    policy: &CompiledFileSystemPolicy,
+    path: &Path,
+    mount: Option<&NetworkMount>,
+    checks: &mut Vec<PolicyCheck>,
+) {
+    let globs = policy.matching_excludes(path);
+    checks.push(PolicyCheck {
+        check: "exclude_globs",
+        passed: globs.is_empty(),
+        detail: if globs.is_empty() {
+            "matches no exclude glob".to_string()
+        } else {
+            format!("matches exclude glob {}", globs.iter().map(|g| format!("`{g}`")).collect::<Vec<_>>().join(", "))
+        },
+        skip_reason: Some(SkipReason::Excluded),
+    });
+
+    let meta = match entry_metadata(path, policy.follow_symlinks).await {
+        Ok(Some(m)) => m,
+        Ok(None) => {
+            checks.push(failed("file", "symlink, not followed (follow_symlinks is off)", SkipReason::Symlink));
+            return;
+        }
+        Err(reason) => {
+            checks.push(failed("file", &reason, SkipReason::Error));
+            return;
+        }
+    };
+    if !meta.is_file() {
+        let kind = if meta.is_dir() { "a directory" } else { "not a regular file" };
+        checks.push(failed("file", kind, SkipReason::NotRegular));
+        return;
+    }
+
+    let ext = crate::logs::effective_extension(path);
+    let extension_ok = policy.extension_allowed(path);
+    checks.push(PolicyCheck {
+        check: "extension",
+        passed: extension_ok,
+        detail: match &ext {
+            None => "no extension (only allowlisted extensions are indexed)".to_string(),
+            Some(e) if extension_ok => format!("`{e}` is allowlisted"),
+            Some(e) if policy.allow_extensions.contains(e) => format!("`{e}` is skipped by extension_overrides"),
+            Some(e) => format!("`{e}` is not in allow_extensions"),
+        },
+        skip_reason: Some(SkipReason::Extension),
+    });
+
+    let size = meta.len();
+    let max = policy.max_file_size_for(path);
+    let (max, limit) = match mount {
+        Some(_) if policy.network_mounts.max_file_size_bytes < max => {
+            (policy.network_mounts.max_file_size_bytes, "network_mounts.max_file_size_bytes")
+        }
+        _ => (max, "max_file_size_bytes"),
+    };
+    checks.push(PolicyCheck {
+        check: "size",
+        passed: size <= max,
+        detail: format!("{size} bytes ({limit} {max})"),
+        skip_reason: Some(SkipReason::TooLarge),
+    });
+
+    let placeholder = policy.skip_placeholder(path) && is_cloud_placeholder(path, &meta);
+    checks.push(PolicyCheck {
+        check: "cloud_placeholder",
+        passed: !placeholder,
+        detail: if placeholder {
+            "online-only cloud file (list its folder in hydrate_roots to index it)".to_string()
+        } else {
+            "content is on disk (or placeholders are not skipped here)".to_string()
+        },
+        skip_reason: Some(SkipReason::Placeholder),
+    });
+
+    let generated = if policy.skip_generated {
+        crate::generated::detect(path).await
+    } else {
+        None
+    };
+    checks.push(PolicyCheck {
+        check: "generated",
+        passed: generated.is_none(),
+        detail: match generated {
+            Some(g) => g.describe().to_string(),
+            None if policy.skip_generated => "not detected as generated".to_string(),
+            None => "generated files are not skipped (skip_generated is off)".to_string(),
+        },
+        skip_reason: Some(SkipReason::Generated),
+    });
+
+    let encrypted = crate::encrypted::detect(path).await;
+    let password = crate::passwords::covering(path);
+    checks.push(PolicyCheck {
+        check: "encrypted",
+        passed: encrypted.is_none() || (encrypted == Some(Encrypted::Pdf) && password.is_some()),
+        detail: match (encrypted, password) {
+            (None, _) => "not encrypted (or opens without a password)".to_string(),
+            (Some(Encrypted::Pdf), Some(p)) => format!("encrypted PDF, password stored for {}", p.display()),
+            (Some(e), _) => e.describe().to_string(),
+        },
+        skip_reason: Some(SkipReason::Encrypted),
+    });
+}
+
+fn failed(check: &'static str, detail: &str, skip_reason: SkipReason) -> PolicyCheck {
+    PolicyCheck {
+        check,
+        passed: false,
+        detail: detail.to_string(),
+        skip_reason: Some(skip_reason),
+    }
+}
@@ -0,0 +1,538 @@
+use std::fmt;
+
+/// SQL filter predicates for LanceDB (`delete`, `only_if`, ...).
+///
+/// LanceDB takes filters as SQL strings, so every value that ends up in one must be quoted.
+/// Rules:
+/// - Column names are `&'static str`: they come from our schema, never from user input.
+/// - Values are always rendered as escaped literals (strings single-quoted, `'` doubled).
+/// - Prefix matching uses `starts_with(...)` instead of `LIKE`, so `%`/`_` in paths need no escaping.
+///
+/// Next to the SQL, a predicate keeps its expression tree so stores without SQL (the in-memory
+/// store, see `crate::memstore`) can evaluate it with `matches`.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    sql: String,
+    expr: Expr,
+}
+
+impl PartialEq for Predicate {
+    fn eq(&self, other: &Self) -> bool {
+        self.sql == other.sql
+    }
+}
+
+impl Eq for Predicate {}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Const(bool),
+    Cmp(&'static str, CmpOp, Literal),
+    In(&'static str, Vec<Literal>),
+    StartsWith(&'static str, String),
+    EndsWith(&'static str, String),
+    Contains(&'static str, String),
+    /// Needle stored lowercased.
+    ContainsCi(&'static str, String),
+    IsNull(&'static str),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn sql(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+}
+
+/// A column value of a row being matched (`Predicate::matches`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue<'a> {
+    Null,
+    Str(&'a str),
+    Int(i64),
+    Float(f64),
+}
+
+/// A row that predicates can be evaluated against.
+pub trait Columns {
+    /// Value of `column` (`Null` for unknown columns).
+    fn column(&self, column: &str) -> FieldValue<'_>;
+}
+
+/// A literal value inside a predicate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<&str> for Literal {
+    fn from(v: &str) -> Self {
+        Literal::Str(v.to_string())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(v: String) -> Self {
+        Literal::Str(v)
+    }
+}
+
+impl From<&String> for Literal {
+    fn from(v: &String) -> Self {
+        Literal::Str(v.clone())
+    }
+}
+
+impl From<i64> for Literal {
+    fn from(v: i64) -> Self {
+        Literal::Int(v)
+    }
+}
+
+impl From<usize> for Literal {
+    fn from(v: usize) -> Self {
+        Literal::Int(v as i64)
+    }
+}
+
+impl From<f64> for Literal {
+    fn from(v: f64) -> Self {
+        Literal::Float(v)
+    }
+}
+
+impl From<bool> for Literal {
+    fn from(v: bool) -> Self {
+        Literal::Bool(v)
+    }
+}
+
+impl Literal {
+    fn to_sql(&self) -> String {
+        match self {
+            Literal::Str(s) => quote_str(s),
+            Literal::Int(i) => i.to_string(),
+            // Non-finite floats have no SQL literal; compare against NULL (never true) instead.
+            Literal::Float(f) if f.is_finite() => format!("{f:?}"),
+            Literal::Float(_) => "NULL".to_string(),
+            Literal::Bool(b) => b.to_string(),
+        }
+    }
+}
+
+/// Quote a string as a SQL literal: wrap in single quotes and double embedded quotes.
+///
+/// NUL bytes are dropped (they cannot appear in a valid path and some parsers stop at them).
+pub fn quote_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('\'');
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("''"),
+            '\0' => {}
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+impl Predicate {
+    /// Always-true predicate (useful as the identity for `and`).
+    pub fn always() -> Self {
+        Self::new("true".to_string(), Expr::Const(true))
+    }
+
+    /// Always-false predicate (useful as the identity for `or`).
+    pub fn never() -> Self {
+        Self::new("false".to_string(), Expr::Const(false))
+    }
+
+    pub fn eq(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Eq, value.into())
+    }
+
+    pub fn ne(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Ne, value.into())
+    }
+
+    pub fn lt(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Lt, value.into())
+    }
+
+    pub fn le(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Le, value.into())
+    }
+
+    pub fn gt(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Gt, value.into())
+    }
+
+    pub fn ge(column: &'static str, value: impl Into<Literal>) -> Self {
+        Self::cmp(column, CmpOp::Ge, value.into())
+    }
+
+    /// `column IN (...)`. An empty list never matches.
+    pub fn in_list<I, V>(column: &'static str, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Literal>,
+    {
+        let values = values.into_iter().map(Into::into).collect::<Vec<Literal>>();
+        if values.is_empty() {
+            return Self::never();
+        }
+        let items = values.iter().map(Literal::to_sql).collect::<Vec<_>>();
+        Self::new(
+            format!("{} IN ({})", ident(column), items.join(", ")),
+            Expr::In(column, values),
+        )
+    }
+
+    /// `starts_with(column, 'prefix')` — no wildcard semantics, so any prefix is safe.
+    pub fn starts_with(column: &'static str, prefix: &str) -> Self {
+        Self::new(
+            format!("starts_with({}, {})", ident(column), quote_str(prefix)),
+            Expr::StartsWith(column, prefix.to_string()),
+        )
+    }
+
+    /// `ends_with(column, 'suffix')`.
+    pub fn ends_with(column: &'static str, suffix: &str) -> Self {
+        Self::new(
+            format!("ends_with({}, {})", ident(column), quote_str(suffix)),
+            Expr::EndsWith(column, suffix.to_string()),
+        )
+    }
+
+    /// Like `contains`, ignoring case: `lower(column) LIKE '%needle%'` with `needle` lowercased.
+    pub fn contains_ci(column: &'static str, needle: &str) -> Self {
+        let needle = needle.to_lowercase();
+        Self::new(
+            format!("lower({}) LIKE {}", ident(column), quote_str(&like_pattern(&needle))),
+            Expr::ContainsCi(column, needle),
+        )
+    }
+
+    /// `column LIKE '%needle%'`, with `%`, `_` and `\` in `needle` escaped (matched literally).
+    pub fn contains(column: &'static str, needle: &str) -> Self {
+        Self::new(
+            format!("{} LIKE {}", ident(column), quote_str(&like_pattern(needle))),
+            Expr::Contains(column, needle.to_string()),
+        )
+    }
+
+    pub fn is_null(column: &'static str) -> Self {
+        Self::new(format!("{} IS NULL", ident(column)), Expr::IsNull(column))
+    }
+
+    pub fn is_not_null(column: &'static str) -> Self {
+        Self::new(
+            format!("{} IS NOT NULL", ident(column)),
+            Expr::Not(Box::new(Expr::IsNull(column))),
+        )
+    }
+
+    pub fn and(self, other: Predicate) -> Self {
+        Self::new(
+            format!("({}) AND ({})", self.sql, other.sql),
+            Expr::And(Box::new(self.expr), Box::new(other.expr)),
+        )
+    }
+
+    pub fn or(self, other: Predicate) -> Self {
+        Self::new(
+            format!("({}) OR ({})", self.sql, other.sql),
+            Expr::Or(Box::new(self.expr), Box::new(other.expr)),
+        )
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self::new(format!("NOT ({})", self.sql), Expr::Not(Box::new(self.expr)))
+    }
+
+    /// AND of all predicates (`true` when empty).
+    pub fn all(preds: impl IntoIterator<Item = Predicate>) -> Self {
+        preds
+            .into_iter()
+            .reduce(Predicate::and)
+            .unwrap_or_else(Self::always)
+    }
+
+    /// OR of all predicates (`false` when empty).
+    pub fn any(preds: impl IntoIterator<Item = Predicate>) -> Self {
+        preds
+            .into_iter()
+            .reduce(Predicate::or)
+            .unwrap_or_else(Self::never)
+    }
+
+    pub fn as_sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Evaluate against `row` the way SQL would: comparisons with NULL are unknown, and a row
+    /// matches only when the whole predicate is true.
+    pub fn matches(&self, row: &impl Columns) -> bool {
+        self.expr.eval(row) == Some(true)
+    }
+
+    /// The same filter in SQLite's dialect (`crate::sqlitestore`), which has no `starts_with`
+    /// and no default `LIKE` escape character.
+    pub fn as_sqlite_sql(&self) -> String {
+        sqlite_sql(&self.expr)
+    }
+
+    fn new(sql: String, expr: Expr) -> Self {
+        Predicate { sql, expr }
+    }
+
+    fn cmp(column: &'static str, op: CmpOp, value: Literal) -> Self {
+        Self::new(
+            format!("{} {} {}", ident(column), op.sql(), value.to_sql()),
+            Expr::Cmp(column, op, value),
+        )
+    }
+}
+
+impl Expr {
+    /// Three-valued logic: `None` is SQL's unknown.
+    fn eval(&self, row: &impl Columns) -> Option<bool> {
+        match self {
+            Expr::Const(b) => Some(*b),
+            Expr::Cmp(column, op, lit) => {
+                let ord = compare(row.column(column), lit)?;
+                Some(match op {
+                    CmpOp::Eq => ord.is_eq(),
+                    CmpOp::Ne => ord.is_ne(),
+                    CmpOp::Lt => ord.is_lt(),
+                    CmpOp::Le => ord.is_le(),
+                    CmpOp::Gt => ord.is_gt(),
+                    CmpOp::Ge => ord.is_ge(),
+                })
+            }
+            Expr::In(column, values) => {
+                let value = row.column(column);
+                if value == FieldValue::Null {
+                    return None;
+                }
+                Some(values.iter().any(|lit| compare(value, lit).is_some_and(|o| o.is_eq())))
+            }
+            Expr::StartsWith(column, prefix) => match row.column(column) {
+                FieldValue::Str(s) => Some(s.starts_with(prefix.as_str())),
+                _ => None,
+            },
+            Expr::EndsWith(column, suffix) => match row.column(column) {
+                FieldValue::Str(s) => Some(s.ends_with(suffix.as_str())),
+                _ => None,
+            },
+            Expr::Contains(column, needle) => match row.column(column) {
+                FieldValue::Str(s) => Some(s.contains(needle.as_str())),
+                _ => None,
+            },
+            Expr::ContainsCi(column, needle) => match row.column(column) {
+                FieldValue::Str(s) => Some(s.to_lowercase().contains(needle.as_str())),
+                _ => None,
+            },
+            Expr::IsNull(column) => Some(row.column(column) == FieldValue::Null),
+            Expr::And(a, b) => match (a.eval(row), b.eval(row)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Expr::Or(a, b) => match (a.eval(row), b.eval(row)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Expr::Not(a) => a.eval(row).map(|b| !b),
+        }
+    }
+}
+
+/// `value` compared to `lit` (`None` for NULL or mismatched types).
+fn compare(value: FieldValue<'_>, lit: &Literal) -> Option<std::cmp::Ordering> {
+    match (value, lit) {
+        (FieldValue::Str(a), Literal::Str(b)) => Some(a.cmp(b.as_str())),
+        (FieldValue::Int(a), Literal::Int(b)) => Some(a.cmp(b)),
+        (FieldValue::Int(a), Literal::Float(b)) => (a as f64).partial_cmp(b),
+        (FieldValue::Float(a), Literal::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (FieldValue::Float(a), Literal::Float(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sql)
+    }
+}
+
+fn sqlite_sql(expr: &Expr) -> String {
+    match expr {
+        Expr::Const(b) => b.to_string(),
+        Expr::Cmp(column, op, value) => format!("{} {} {}", ident(column), op.sql(), value.to_sql()),
+        Expr::In(column, values) => {
+            let items = values.iter().map(Literal::to_sql).collect::<Vec<_>>();
+            format!("{} IN ({})", ident(column), items.join(", "))
+        }
+        Expr::StartsWith(column, prefix) => format!(
+            "substr({}, 1, {}) = {}",
+            ident(column),
+            prefix.chars().count(),
+            quote_str(prefix)
+        ),
+        Expr::EndsWith(column, suffix) => format!(
+            "substr({}, -{}) = {}",
+            ident(column),
+            suffix.chars().count(),
+            quote_str(suffix)
+        ),
+        Expr::Contains(column, needle) => format!("instr({}, {}) > 0", ident(column), quote_str(needle)),
+        Expr::ContainsCi(column, needle) => format!("instr(lower({}), {}) > 0", ident(column), quote_str(needle)),
+        Expr::IsNull(column) => format!("{} IS NULL", ident(column)),
+        Expr::And(a, b) => format!("({}) AND ({})", sqlite_sql(a), sqlite_sql(b)),
+        Expr::Or(a, b) => format!("({}) OR ({})", sqlite_sql(a), sqlite_sql(b)),
+        Expr::Not(e) => format!("NOT ({})", sqlite_sql(e)),
+    }
+}
+
+/// `%needle%` for `LIKE`, with `%`, `_` and `\` in `needle` escaped (matched literally).
+fn like_pattern(needle: &str) -> String {
+    let mut pattern = String::with_capacity(needle.len() + 2);
+    pattern.push('%');
+    for c in needle.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Column identifiers are schema constants; anything outside `[A-Za-z_][A-Za-z0-9_]*`
+/// gets backtick-quoted so a typo can never turn into SQL.
+fn ident(column: &'static str) -> String {
+    let simple = column
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    if simple && !column.is_empty() {
+        column.to_string()
+    } else {
+        format!("`{}`", column.replace('`', "``"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_values_are_quoted_and_escaped() {
+        assert_eq!(Predicate::eq("path", "/notes/it's.md").as_sql(), "path = '/notes/it''s.md'");
+        assert_eq!(Predicate::ne("path", "'; DROP TABLE chunks; --").as_sql(), "path != '''; DROP TABLE chunks; --'");
+        assert_eq!(quote_str("a\0b"), "'ab'");
+        assert_eq!(quote_str(""), "''");
+    }
+
+    #[test]
+    fn numbers_and_booleans_render_as_literals() {
+        assert_eq!(Predicate::ge("file_mtime_epoch_secs", 1_700_000_000i64).as_sql(), "file_mtime_epoch_secs >= 1700000000");
+        assert_eq!(Predicate::lt("score", 0.5f64).as_sql(), "score < 0.5");
+        assert_eq!(Predicate::eq("score", f64::NAN).as_sql(), "score = NULL");
+        assert_eq!(Predicate::eq("deleted", true).as_sql(), "deleted = true");
+    }
+
+    #[test]
+    fn in_lists() {
+        assert_eq!(Predicate::in_list("path", ["/a", "/b'c"]).as_sql(), "path IN ('/a', '/b''c')");
+        assert_eq!(Predicate::in_list("chunk_index", [1i64, 2, 3]).as_sql(), "chunk_index IN (1, 2, 3)");
+        assert_eq!(Predicate::in_list("path", Vec::<String>::new()).as_sql(), "false");
+    }
+
+    #[test]
+    fn and_or_not_nest_with_parentheses() {
+        let p = Predicate::eq("a", 1i64).and(Predicate::eq("b", "x").or(Predicate::is_null("c")));
+        assert_eq!(p.as_sql(), "(a = 1) AND ((b = 'x') OR (c IS NULL))");
+        assert_eq!(p.not().as_sql(), "NOT ((a = 1) AND ((b = 'x') OR (c IS NULL)))");
+        assert_eq!(Predicate::is_not_null("c").as_sql(), "c IS NOT NULL");
+
+        let all = Predicate::all([Predicate::eq("a", 1i64), Predicate::eq("b", 2i64), Predicate::eq("c", 3i64)]);
+        assert_eq!(all.as_sql(), "((a = 1) AND (b = 2)) AND (c = 3)");
+        let any = Predicate::any([Predicate::eq("a", 1i64), Predicate::eq("b", 2i64)]);
+        assert_eq!(any.as_sql(), "(a = 1) OR (b = 2)");
+        assert_eq!(Predicate::all([]).as_sql(), "true");
+        assert_eq!(Predicate::any([]).as_sql(), "false");
+    }
+
+    #[test]
+    fn prefix_and_substring_matching() {
+        // `starts_with` has no wildcards: only the quote needs escaping.
+        assert_eq!(Predicate::starts_with("path", "/tmp/50%_'x").as_sql(), "starts_with(path, '/tmp/50%_''x')");
+        assert_eq!(Predicate::ends_with("path", ".md").as_sql(), "ends_with(path, '.md')");
+        assert_eq!(Predicate::contains("text", r"a%b_c\").as_sql(), r"text LIKE '%a\%b\_c\\%'");
+        assert_eq!(Predicate::contains_ci("text", "It's").as_sql(), "lower(text) LIKE '%it''s%'");
+    }
+
+    #[test]
+    fn odd_column_names_are_quoted() {
+        assert_eq!(Predicate::eq("weird col", 1i64).as_sql(), "`weird col` = 1");
+        assert_eq!(Predicate::eq("a`b", 1i64).as_sql(), "`a``b` = 1");
+        assert_eq!(Predicate::eq("9lives", 1i64).as_sql(), "`9lives` = 1");
+    }
+
+    #[test]
+    fn sqlite_dialect() {
+        let p = Predicate::starts_with("path", "/a'b").and(Predicate::ends_with("path", ".md"));
+        assert_eq!(p.as_sqlite_sql(), "(substr(path, 1, 4) = '/a''b') AND (substr(path, -3) = '.md')");
+        assert_eq!(Predicate::contains("text", "50%").as_sqlite_sql(), "instr(text, '50%') > 0");
+        assert_eq!(Predicate::in_list("path", ["x"]).not().as_sqlite_sql(), "NOT (path IN ('x'))");
+    }
+
+    struct Row(&'static [(&'static str, FieldValue<'static>)]);
+
+    impl Columns for Row {
+        fn column(&self, column: &str) -> FieldValue<'_> {
+            self.0
+                .iter()
+                .find(|(c, _)| *c == column)
+                .map_or(FieldValue::Null, |(_, v)| *v)
+        }
+    }
+
+    #[test]
+    fn matches_follows_sql_null_semantics() {
+        let row = Row(&[("path", FieldValue::Str("/notes/a.md")), ("chunk_index", FieldValue::Int(2))]);
+        assert!(Predicate::starts_with("path", "/notes/").matches(&row));
+        assert!(Predicate::in_list("chunk_index", [1i64, 2]).matches(&row));
+        assert!(!Predicate::eq("page", 1i64).matches(&row));
+        assert!(!Predicate::eq("page", 1i64).not().matches(&row));
+        assert!(Predicate::eq("page", 1i64).or(Predicate::eq("chunk_index", 2i64)).matches(&row));
+        assert!(Predicate::is_null("page").matches(&row));
+    }
+}
@@ -0,0 +1,167 @@
+//! Small previews of search hits for the desktop results list.
+//!
+//! - PDFs: the hit's page (first page by default) rendered to PNG with poppler's `pdftoppm`
+//! - Images: a thumbnail (`sips` on macOS, ImageMagick `convert` elsewhere; small originals are
+//!   returned as-is when neither is available)
+//! - Everything else: the first lines of text
+//!
+//! Rendered previews are cached under `data_dir/previews`, keyed by path, size, mtime and page,
+//! so an edited file gets a fresh preview.
+
+use crate::extract::IMAGE_EXTENSIONS;
+use crate::filesystem::modified_epoch_secs;
+use crate::state::SharedState;
+use base64::Engine;
+use serde::Serialize;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Longest side of rendered previews, in pixels.
+const PREVIEW_PX: u32 = 320;
+const TEXT_PREVIEW_LINES: usize = 30;
+const TEXT_PREVIEW_BYTES: u64 = 16 * 1024;
+/// Originals up to this size are shown as-is when no thumbnailer is installed.
+const MAX_INLINE_IMAGE_BYTES: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Preview {
+    /// `data:` URL, usable directly as an `<img src>`.
+    Image { path: String, data_url: String },
+    Text { path: String, text: String, truncated: bool },
+}
+
+/// Preview of `path` (for PDFs, of `page`, 1-based).
+pub async fn preview(state: &SharedState, path: &str, page: Option<i64>) -> Result<Preview, String> {
+    let file = crate::launch::checked_path(path).await?;
+    let meta = tokio::fs::metadata(&file)
+        .await
+        .map_err(|e| format!("Cannot read {}: {e}", file.display()))?;
+    if !meta.is_file() {
+        return Err(format!("{} is not a file", file.display()));
+    }
+    let ext = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    let path = file.to_string_lossy().to_string();
+
+    let is_pdf = ext == "pdf";
+    if !is_pdf && !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        let extracted = crate::extract::extract_text(&file, TEXT_PREVIEW_BYTES).await?;
+        let mut lines = extracted.text.lines();
+        let text = lines.by_ref().take(TEXT_PREVIEW_LINES).collect::<Vec<_>>().join("\n");
+        let truncated = extracted.truncated || lines.next().is_some();
+        return Ok(Preview::Text { path, text, truncated });
+    }
+
+    let page = if is_pdf { page.unwrap_or(1).max(1) } else { 0 };
+    let key = blake3::hash(
+        format!("{path}\n{}\n{:?}\n{page}", meta.len(), modified_epoch_secs(&meta)).as_bytes(),
+    )
+    .to_hex()[..32]
+        .to_string();
+    let dir = state.data_dir.join("previews");
+    let cached = dir.join(format!("{key}.png"));
+
+    if tokio::fs::metadata(&cached).await.is_err() {
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let rendered = if is_pdf {
+            render_pdf_page(&file, page, &dir.join(&key)).await
+        } else {
+            thumbnail(&file, &cached).await
+        };
+        if let Err(e) = rendered {
+            if !is_pdf && meta.len() <= MAX_INLINE_IMAGE_BYTES {
+                let bytes = tokio::fs::read(&file)
+                    .await
+                    .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+                let data_url = data_url(image_mime(&ext), &bytes);
+                return Ok(Preview::Image { path, data_url });
+            }
+            return Err(e);
+        }
+    }
+
+    let bytes = tokio::fs::read(&cached)
+        .await
+        .map_err(|e| format!("Failed to read preview {}: {e}", cached.display()))?;
+    let data_url = data_url("image/png", &bytes);
+    Ok(Preview::Image { path, data_url })
+}
+
+/// Renders `page` to `<out_prefix>.png`.
+async fn render_pdf_page(pdf: &Path, page: i64, out_prefix: &Path) -> Result<(), String> {
+    let page = page.to_string();
+    let size = PREVIEW_PX.to_string();
+    let output = Command::new("pdftoppm")
+        .args(["-png", "-singlefile", "-f", &page, "-l", &page, "-scale-to", &size])
+        .arg(pdf)
+        .arg(out_prefix)
+        .output()
+        .await
+        .map_err(|e| {
+            format!("Failed to run pdftoppm (is poppler installed?). Try `brew install poppler`. Details: {e}")
+        })?;
+    if !output.status.success() {
+        return Err(format!(
+            "pdftoppm failed for {} (exit={}): {}",
+            pdf.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+async fn thumbnail(image: &Path, out: &Path) -> Result<(), String> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut c = Command::new("sips");
+        c.args(["-s", "format", "png", "-Z", &PREVIEW_PX.to_string()])
+            .arg(image)
+            .arg("--out")
+            .arg(out);
+        c
+    } else {
+        // `[0]`: first frame of animated GIFs / multi-page TIFFs.
+        let mut input = image.as_os_str().to_owned();
+        input.push("[0]");
+        let mut c = Command::new("convert");
+        c.arg(input)
+            .args(["-thumbnail", &format!("{PREVIEW_PX}x{PREVIEW_PX}")])
+            .arg(format!("png:{}", out.display()));
+        c
+    };
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to create thumbnail for {}: {e}", image.display()))?;
+    if !output.status.success() {
+        let _ = tokio::fs::remove_file(out).await;
+        return Err(format!(
+            "Thumbnail failed for {} (exit={}): {}",
+            image.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn image_mime(ext: &str) -> &'static str {
+    match ext {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        _ => "image/png",
+    }
+}
+
+fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{mime};base64,{}", base64::engine::general_purpose::STANDARD.encode(bytes))
+}
@@ -0,0 +1,237 @@
+//! Prompt templates for `silo_ask`, `silo_chat`, `silo_agent` and `silo_extract_structured`.
+//!
+//! Syntax: `{name}` is a placeholder, `{{` / `}}` are literal braces. Templates are parsed and
+//! validated when the config is loaded (unknown or missing placeholders are errors), and
+//! rendered in a single pass, so retrieved text containing `{question}` is never re-expanded.
+
+use crate::config::PromptConfig;
+use crate::llm::estimate_llm_tokens;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Var(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    /// Parse and validate a template.
+    ///
+    /// - `allowed`: placeholders the caller knows how to fill
+    /// - `required`: placeholders that must appear (e.g. `{question}`)
+    pub fn parse(name: &str, text: &str, allowed: &[&str], required: &[&str]) -> Result<Self, String> {
+        let mut segments = vec![];
+        let mut lit = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    lit.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    lit.push('}');
+                }
+                '{' => {
+                    let mut var = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        var.push(c);
+                    }
+                    if !closed {
+                        return Err(format!("prompt template `{name}`: unclosed `{{` (use `{{{{` for a literal brace)"));
+                    }
+                    let var = var.trim().to_string();
+                    if !allowed.contains(&var.as_str()) {
+                        return Err(format!(
+                            "prompt template `{name}`: unknown placeholder `{{{var}}}` (allowed: {})",
+                            allowed.iter().map(|a| format!("{{{a}}}")).collect::<Vec<_>>().join(", ")
+                        ));
+                    }
+                    if !lit.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut lit)));
+                    }
+                    segments.push(Segment::Var(var));
+                }
+                '}' => {
+                    return Err(format!("prompt template `{name}`: stray `}}` (use `}}}}` for a literal brace)"));
+                }
+                c => lit.push(c),
+            }
+        }
+        if !lit.is_empty() {
+            segments.push(Segment::Literal(lit));
+        }
+
+        for r in required {
+            let present = segments
+                .iter()
+                .any(|s| matches!(s, Segment::Var(v) if v == r));
+            if !present {
+                return Err(format!("prompt template `{name}`: missing required placeholder `{{{r}}}`"));
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// Fill placeholders. Placeholders without a value render as empty strings.
+    pub fn render(&self, vars: &[(&str, &str)]) -> String {
+        let mut out = String::new();
+        for s in &self.segments {
+            match s {
+                Segment::Literal(l) => out.push_str(l),
+                Segment::Var(v) => {
+                    if let Some((_, val)) = vars.iter().find(|(k, _)| k == v) {
+                        out.push_str(val);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Approximate tokens used by the fixed (non-placeholder) text.
+    pub fn literal_tokens(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|s| match s {
+                Segment::Literal(l) => estimate_llm_tokens(l),
+                Segment::Var(_) => 0,
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    /// `silo_ask`: `{context}`, `{question}`
+    pub ask: PromptTemplate,
+    /// `silo_chat`: `{context}`, `{history}`, `{question}`
+    pub chat: PromptTemplate,
+    /// `silo_agent`: `{tools}`, `{task}`
+    pub agent: PromptTemplate,
+    /// `silo_extract_structured`: `{schema}`, `{document}`, `{instructions}`
+    pub extract: PromptTemplate,
+}
+
+pub const DEFAULT_ASK_TEMPLATE: &str = r#"You are Silo, a local-first assistant answering questions about the user's own files.
+
+Answer the question using ONLY the sources below. Cite sources inline as [1], [2], ...
+If the sources do not contain the answer, say that you could not find it in the indexed files.
+
+Sources:
+{context}
+Question: {question}
+Answer:"#;
+
+pub const DEFAULT_CHAT_TEMPLATE: &str = r#"You are Silo, a local-first assistant answering questions about the user's own files.
+Answer using ONLY the numbered sources and the conversation so far. Cite sources inline as [n].
+If the sources do not contain the answer, say that you could not find it in the indexed files.
+
+Sources:
+{context}
+{history}
+User: {question}
+Assistant:"#;
+
+pub const DEFAULT_AGENT_TEMPLATE: &str = r#"You are a local-first desktop assistant for the Silo app.
+
+You MUST respond with a SINGLE LINE of JSON only (no markdown, no explanation).
+The JSON must have this exact shape:
+{{"tool":"<tool_name>","arguments":{{...}}}}
+
+Pick the ONE best tool to accomplish the user task. If the task cannot be done with the tools,
+return: {{"tool":"none","arguments":{{"reason":"..."}}}}
+
+Available tools (name + description + JSON schema):
+{tools}
+
+User task: {task}
+"#;
+
+pub const DEFAULT_EXTRACT_TEMPLATE: &str = r#"You extract structured data from a document for the Silo app.
+
+Return ONLY a single JSON object (no markdown, no explanation) that matches this JSON schema:
+{schema}
+
+Use null for fields that are not present in the document. Do not invent values.
+{instructions}
+Document:
+{document}
+
+JSON:"#;
+
+const ASK_PLACEHOLDERS: &[&str] = &["context", "question"];
+const CHAT_PLACEHOLDERS: &[&str] = &["context", "history", "question"];
+const AGENT_PLACEHOLDERS: &[&str] = &["tools", "task"];
+const EXTRACT_PLACEHOLDERS: &[&str] = &["schema", "document", "instructions"];
+
+/// Compile configured templates (inline text or a file path), falling back to built-ins.
+pub async fn compile_prompt_templates(cfg: &PromptConfig) -> Result<PromptTemplates, String> {
+    let ask = load_template(
+        "ask",
+        cfg.ask_template.as_deref(),
+        cfg.ask_template_path.as_deref(),
+        DEFAULT_ASK_TEMPLATE,
+    )
+    .await?;
+    let chat = load_template(
+        "chat",
+        cfg.chat_template.as_deref(),
+        cfg.chat_template_path.as_deref(),
+        DEFAULT_CHAT_TEMPLATE,
+    )
+    .await?;
+    let agent = load_template(
+        "agent",
+        cfg.agent_template.as_deref(),
+        cfg.agent_template_path.as_deref(),
+        DEFAULT_AGENT_TEMPLATE,
+    )
+    .await?;
+    let extract = load_template(
+        "extract",
+        cfg.extract_template.as_deref(),
+        cfg.extract_template_path.as_deref(),
+        DEFAULT_EXTRACT_TEMPLATE,
+    )
+    .await?;
+
+    Ok(PromptTemplates {
+        ask: PromptTemplate::parse("ask", &ask, ASK_PLACEHOLDERS, &["context", "question"])?,
+        chat: PromptTemplate::parse("chat", &chat, CHAT_PLACEHOLDERS, &["context", "question"])?,
+        agent: PromptTemplate::parse("agent", &agent, AGENT_PLACEHOLDERS, &["tools", "task"])?,
+        extract: PromptTemplate::parse("extract", &extract, EXTRACT_PLACEHOLDERS, &["schema", "document"])?,
+    })
+}
+
+async fn load_template(
+    name: &str,
+    inline: Option<&str>,
+    path: Option<&Path>,
+    default: &str,
+) -> Result<String, String> {
+    match (inline, path) {
+        (Some(_), Some(_)) => Err(format!(
+            "prompts: set either `{name}_template` or `{name}_template_path`, not both"
+        )),
+        (Some(t), None) => Ok(t.to_string()),
+        (None, Some(p)) => {
+            let p = crate::state::expand_tilde(&p.to_string_lossy());
+            tokio::fs::read_to_string(&p)
+                .await
+                .map_err(|e| format!("Failed to read {name} prompt template {}: {e}", p.display()))
+        }
+        (None, None) => Ok(default.to_string()),
+    }
+}
@@ -0,0 +1,408 @@
+//! Search query language (`silo_search` `query`): one string carrying both the text to search for
+//! and filters, compiled into a `QueryPlan` (the text to embed plus a chunk `Predicate`).
+//!
+//! ```text
+//! "quarterly estimate" tax ext:pdf date:>2024-01-01 -draft
+//! (path:~/work OR tag:invoices) lang:rust NOT "generated code"
+//! ```
+//!
+//! - words are searched semantically; `"quoted phrases"` are searched too and must also appear
+//!   in the chunk (case-insensitive)
+//! - `path:` (a prefix for `/...` or `~/...`, else a substring), `ext:` (`ext:pdf,docx`),
+//!   `lang:` (`rust`, `python`, ... or an extension), `tag:`, `date:` (file modified;
+//!   `date:2024`, `date:>=2024-03`, `date:<2024-01-15`) and `time:` (when logged or sent, for log
+//!   lines and chat messages; `time:2024-05-14T15`, `time:2024-05-14T14:30..2024-05-14T15:30`);
+//!   values may be quoted (`path:"My Docs"`)
+//! - `AND` (implied between terms), `OR`, `NOT` / `-term`, and parentheses
+//!
+//! Negated words and phrases exclude chunks containing them. A group under `OR` only filters when
+//! every side of it does (`tax OR ext:pdf` matches everything). Unknown `field:` prefixes are
+//! plain words.
+
+use crate::predicate::Predicate;
+use crate::state::expand_tilde;
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    /// Text to embed: the words and phrases not under `NOT`.
+    pub text: String,
+    pub filter: Option<Predicate>,
+    /// The query used any syntax (phrases, fields, operators), so `text` differs from it.
+    pub advanced: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Path,
+    Ext,
+    Lang,
+    Tag,
+    Date,
+    Time,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "path" => Field::Path,
+            "ext" => Field::Ext,
+            "lang" => Field::Lang,
+            "tag" => Field::Tag,
+            "date" => Field::Date,
+            "time" => Field::Time,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    Field(Field, String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Word(String),
+    Phrase(String),
+    Field(Field, String),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+}
+
+/// Language names for `lang:` and their extensions.
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("rust", &["rs"]),
+    ("python", &["py", "pyi"]),
+    ("javascript", &["js", "mjs", "cjs", "jsx"]),
+    ("typescript", &["ts", "tsx"]),
+    ("go", &["go"]),
+    ("java", &["java"]),
+    ("kotlin", &["kt", "kts"]),
+    ("swift", &["swift"]),
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "cc", "cxx", "hpp", "hh"]),
+    ("csharp", &["cs"]),
+    ("ruby", &["rb"]),
+    ("php", &["php"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("sql", &["sql"]),
+    ("html", &["html", "htm"]),
+    ("css", &["css", "scss"]),
+    ("markdown", &["md", "markdown"]),
+];
+
+pub fn parse(query: &str) -> Result<QueryPlan, String> {
+    let tokens = tokenize(query);
+    let advanced = tokens.iter().any(|t| !matches!(t, Token::Word(_)));
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut nodes = vec![parser.or()];
+    // `or` stops early only at an unmatched `)`: skip it and go on.
+    while parser.pos < parser.tokens.len() {
+        parser.pos += 1;
+        nodes.push(parser.or());
+    }
+    let node = if nodes.len() == 1 { nodes.remove(0) } else { Node::And(nodes) };
+
+    let mut words = vec![];
+    collect_text(&node, &mut words);
+    let text = if advanced { words.join(" ") } else { query.trim().to_string() };
+    Ok(QueryPlan {
+        text,
+        filter: filter(&node, false)?,
+        advanced,
+    })
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    let quoted = |i: &mut usize| {
+        // At an opening quote: the text up to the closing one (or the end).
+        let start = *i + 1;
+        let end = chars[start..].iter().position(|&c| c == '"').map_or(chars.len(), |p| start + p);
+        *i = end + 1;
+        chars[start..end].iter().collect::<String>()
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == '"' {
+            let phrase = quoted(&mut i);
+            if !phrase.trim().is_empty() {
+                tokens.push(Token::Phrase(phrase.trim().to_string()));
+            }
+        } else if c == '-' && chars.get(i + 1).is_some_and(|n| !n.is_whitespace() && *n != '-') {
+            tokens.push(Token::Not);
+            i += 1;
+        } else {
+            let start = i;
+            while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')' | '"') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let field = word.strip_suffix(':').and_then(Field::parse);
+            let token = match field {
+                // `field:"quoted value"`
+                Some(field) if chars.get(i) == Some(&'"') => Token::Field(field, quoted(&mut i)),
+                _ => match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => match word.split_once(':') {
+                        Some((name, value)) if !value.is_empty() => match Field::parse(name) {
+                            Some(field) => Token::Field(field, value.to_string()),
+                            None => Token::Word(word),
+                        },
+                        _ => Token::Word(word),
+                    },
+                },
+            };
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// Recursive descent over the tokens: `or := and (OR and)*`, `and := unary (AND? unary)*`,
+/// `unary := NOT unary | ( or ) | term`. Stray operators and parentheses are skipped.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Node {
+        let mut sides = vec![self.and()];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            sides.push(self.and());
+        }
+        if sides.len() == 1 { sides.remove(0) } else { Node::Or(sides) }
+    }
+
+    fn and(&mut self) -> Node {
+        let mut terms = vec![];
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::And) => self.pos += 1,
+                _ => {
+                    if let Some(node) = self.unary() {
+                        terms.push(node);
+                    }
+                }
+            }
+        }
+        if terms.len() == 1 { terms.remove(0) } else { Node::And(terms) }
+    }
+
+    fn unary(&mut self) -> Option<Node> {
+        let token = self.peek()?.clone();
+        self.pos += 1;
+        match token {
+            Token::Not => match self.peek() {
+                None | Some(Token::And | Token::Or | Token::Close) => None,
+                _ => self.unary().map(|n| Node::Not(Box::new(n))),
+            },
+            Token::Open => {
+                let inner = self.or();
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                }
+                Some(inner)
+            }
+            Token::Word(w) => Some(Node::Word(w)),
+            Token::Phrase(p) => Some(Node::Phrase(p)),
+            Token::Field(f, v) => Some(Node::Field(f, v)),
+            Token::And | Token::Or | Token::Close => None,
+        }
+    }
+}
+
+fn collect_text(node: &Node, out: &mut Vec<String>) {
+    match node {
+        Node::Word(w) | Node::Phrase(w) => out.push(w.clone()),
+        Node::And(nodes) | Node::Or(nodes) => nodes.iter().for_each(|n| collect_text(n, out)),
+        Node::Field(..) | Node::Not(_) => {}
+    }
+}
+
+/// The filter `node` puts on chunks (`None`: no constraint). Plain words only constrain when
+/// `strict` (under `NOT`, where they must be absent).
+fn filter(node: &Node, strict: bool) -> Result<Option<Predicate>, String> {
+    Ok(match node {
+        Node::Word(w) => strict.then(|| Predicate::contains_ci("content", w)),
+        Node::Phrase(p) => Some(Predicate::contains_ci("content", p)),
+        Node::Field(field, value) => Some(field_filter(*field, value)?),
+        Node::And(nodes) => {
+            let mut preds = vec![];
+            for n in nodes {
+                preds.extend(filter(n, strict)?);
+            }
+            (!preds.is_empty()).then(|| Predicate::all(preds))
+        }
+        Node::Or(nodes) => {
+            let mut preds = vec![];
+            for n in nodes {
+                match filter(n, strict)? {
+                    Some(p) => preds.push(p),
+                    None => return Ok(None),
+                }
+            }
+            Some(Predicate::any(preds))
+        }
+        Node::Not(inner) => filter(inner, true)?.map(Predicate::not),
+    })
+}
+
+fn field_filter(field: Field, value: &str) -> Result<Predicate, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("empty value after a field prefix".to_string());
+    }
+    match field {
+        Field::Path if value.starts_with('/') || value.starts_with('~') => {
+            Ok(Predicate::starts_with("path", &expand_tilde(value).to_string_lossy()))
+        }
+        Field::Path => Ok(Predicate::contains("path", value)),
+        Field::Ext => Ok(extensions_filter(value.split(',').map(str::to_string))),
+        Field::Lang => Ok(extensions_filter(value.split(',').flat_map(|lang| {
+            let lang = lang.trim().to_ascii_lowercase();
+            match LANGUAGES.iter().find(|(name, _)| *name == lang) {
+                Some((_, exts)) => exts.iter().map(|e| e.to_string()).collect(),
+                None => vec![lang],
+            }
+        }))),
+        Field::Tag => crate::tagging::search_filter(&[value.to_string()])
+            .ok_or_else(|| format!("invalid tag {value:?}")),
+        Field::Date => date_filter(value),
+        Field::Time => time_filter(value),
+    }
+}
+
+/// Paths ending in any of `exts` (lowercase or uppercase).
+fn extensions_filter(exts: impl Iterator<Item = String>) -> Predicate {
+    Predicate::any(exts.map(|e| e.trim().trim_start_matches('.').to_ascii_lowercase()).filter(|e| !e.is_empty()).flat_map(|e| {
+        [
+            Predicate::ends_with("path", &format!(".{e}")),
+            Predicate::ends_with("path", &format!(".{}", e.to_ascii_uppercase())),
+        ]
+    }))
+}
+
+/// `date:[op]YYYY[-MM[-DD]]` on the file's modification time (local time); `op` is one of
+/// `>`, `>=`, `<`, `<=`, `=` (default: within that year, month or day).
+fn date_filter(value: &str) -> Result<Predicate, String> {
+    let (op, date) = split_op(value);
+    let (start, end) = date_range(date.trim()).ok_or_else(|| {
+        format!("invalid date {value:?} (expected [>|>=|<|<=]YYYY, YYYY-MM or YYYY-MM-DD)")
+    })?;
+    let column = "file_mtime_epoch_secs";
+    Ok(match op {
+        ">" => Predicate::ge(column, end),
+        ">=" => Predicate::ge(column, start),
+        "<" => Predicate::lt(column, start),
+        "<=" => Predicate::lt(column, end),
+        _ => Predicate::ge(column, start).and(Predicate::lt(column, end)),
+    })
+}
+
+/// `time:[op]YYYY-MM-DD[THH[:MM[:SS]]]` (or a year or month, as for `date:`) and
+/// `time:FROM..TO`, in local time, on the time span of a chunk's log lines or chat messages. A
+/// chunk matches when its span overlaps the period (`=`, `..`), ends in or after it (`>=`, `>`)
+/// or starts before or in it (`<`, `<=`). Chunks without timestamps never match.
+fn time_filter(value: &str) -> Result<Predicate, String> {
+    let invalid = || {
+        format!("invalid time {value:?} (expected [>|>=|<|<=]YYYY-MM-DD[THH[:MM[:SS]]] or FROM..TO)")
+    };
+    let (start_column, end_column) = ("time_start_epoch_secs", "time_end_epoch_secs");
+    if let Some((from, to)) = value.split_once("..") {
+        let (start, _) = time_range(from.trim()).ok_or_else(invalid)?;
+        let (_, end) = time_range(to.trim()).ok_or_else(invalid)?;
+        return Ok(Predicate::ge(end_column, start).and(Predicate::lt(start_column, end)));
+    }
+    let (op, time) = split_op(value);
+    let (start, end) = time_range(time.trim()).ok_or_else(invalid)?;
+    Ok(match op {
+        ">" => Predicate::ge(end_column, end),
+        ">=" => Predicate::ge(end_column, start),
+        "<" => Predicate::lt(start_column, start),
+        "<=" => Predicate::lt(start_column, end),
+        _ => Predicate::ge(end_column, start).and(Predicate::lt(start_column, end)),
+    })
+}
+
+/// `>=2024` -> (`>=`, `2024`); no operator is `=`.
+fn split_op(value: &str) -> (&'static str, &str) {
+    ["<=", ">=", "<", ">", "="]
+        .iter()
+        .find_map(|op| value.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("=", value))
+}
+
+/// Start and end (exclusive) of a date (`date_range`) or of an hour, minute or second of a day
+/// (`YYYY-MM-DDTHH[:MM[:SS]]`, `T` or a space), in epoch seconds.
+fn time_range(time: &str) -> Option<(i64, i64)> {
+    let Some((date, clock)) = time.split_once(['T', 't', ' ']) else {
+        return date_range(time);
+    };
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let parts: Vec<u32> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let (at, length) = match parts[..] {
+        [h] => (NaiveTime::from_hms_opt(h, 0, 0)?, 3600),
+        [h, m] => (NaiveTime::from_hms_opt(h, m, 0)?, 60),
+        [h, m, s] => (NaiveTime::from_hms_opt(h, m, s)?, 1),
+        _ => return None,
+    };
+    let start = Local.from_local_datetime(&day.and_time(at)).earliest()?.timestamp();
+    Some((start, start + length))
+}
+
+/// Start and end (exclusive) of a year, month or day, in epoch seconds.
+fn date_range(date: &str) -> Option<(i64, i64)> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let num = |i: usize| parts.get(i).and_then(|p| p.parse::<u32>().ok());
+    let year = parts.first()?.parse::<i32>().ok()?;
+    let (first, next) = match parts.len() {
+        1 => (NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year + 1, 1, 1)?),
+        2 => {
+            let month = num(1)?;
+            let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+            let next = if month == 12 {
+                NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(year, month + 1, 1)?
+            };
+            (first, next)
+        }
+        3 => {
+            let day = NaiveDate::from_ymd_opt(year, num(1)?, num(2)?)?;
+            (day, day.succ_opt()?)
+        }
+        _ => return None,
+    };
+    let start = crate::timeline::local_midnight(first)?;
+    let end = crate::timeline::local_midnight(next)?;
+    Some((start, end))
+}
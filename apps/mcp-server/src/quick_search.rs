@@ -0,0 +1,146 @@
+//! Spotlight-style quick search for the desktop hotkey popup.
+//!
+//! Latency first: file-name matches come from the catalog's trigram index (no embedding), and
+//! semantic hits are merged in only when they arrive within a short budget. The semantic search
+//! keeps running past the budget, so its query embedding and results are cached for the next
+//! keystroke. One row per file.
+
+use crate::database::SearchHit;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 30;
+const DEFAULT_BUDGET_MS: u64 = 150;
+/// Shorter queries only match names (embedding one or two characters is noise).
+const MIN_SEMANTIC_CHARS: usize = 3;
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct QuickSearchArgs {
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// How long to wait for semantic hits before answering with name matches only.
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickMatch {
+    Name,
+    Content,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickResult {
+    pub path: String,
+    /// File name (display title).
+    pub name: String,
+    #[serde(rename = "match")]
+    pub matched: QuickMatch,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickSearch {
+    pub query: String,
+    pub results: Vec<QuickResult>,
+    /// False when semantic hits were skipped (short query, disabled DB) or missed the budget.
+    pub semantic: bool,
+    pub elapsed_ms: f64,
+}
+
+pub async fn quick_search(state: &SharedState, args: &QuickSearchArgs) -> Result<QuickSearch, String> {
+    let started = Instant::now();
+    let query = args.query.trim().to_string();
+    let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    if query.is_empty() {
+        return Ok(QuickSearch {
+            query,
+            results: vec![],
+            semantic: false,
+            elapsed_ms: 0.0,
+        });
+    }
+
+    // Start the semantic search first so it runs while names are matched.
+    let semantic = (query.chars().count() >= MIN_SEMANTIC_CHARS && state.db.is_enabled()).then(|| {
+        let state = state.clone();
+        let query = query.clone();
+        tokio::spawn(async move {
+            let args = crate::tools::SearchKnowledgeBaseArgs {
+                query,
+                top_k: Some(limit),
+                // An LLM-drafted passage per keystroke is too slow for the popup.
+                rewrite: Some(crate::rewrite::RewriteMode::None),
+                ..Default::default()
+            };
+            crate::tools::silo_search(&state, args).await
+        })
+    });
+
+    let locked = crate::access::locked_paths(state).await;
+    let mut names = state.catalog.write().await.search_names(&query, limit);
+    names.retain(|m| locked.collection_of(&m.path).is_none());
+
+    let mut hits: Vec<SearchHit> = vec![];
+    let mut semantic_ok = false;
+    if let Some(task) = semantic {
+        let budget = Duration::from_millis(args.budget_ms.unwrap_or(DEFAULT_BUDGET_MS));
+        let remaining = budget.saturating_sub(started.elapsed());
+        if let Ok(Ok(Ok(mut out))) = tokio::time::timeout(remaining, task).await {
+            hits = serde_json::from_value(out["hits"].take()).unwrap_or_default();
+            semantic_ok = true;
+        }
+    }
+
+    // Best content hit per file first, then name matches not already listed.
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut results: Vec<QuickResult> = vec![];
+    for h in hits {
+        if !seen.insert(h.path.clone()) {
+            continue;
+        }
+        let snippet = h
+            .content_preview
+            .as_deref()
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(SNIPPET_CHARS).collect());
+        results.push(QuickResult {
+            name: file_name(&h.path),
+            path: h.path,
+            matched: QuickMatch::Content,
+            page: h.page,
+            snippet,
+        });
+    }
+    for m in names {
+        if seen.insert(m.path.clone()) {
+            results.push(QuickResult {
+                path: m.path,
+                name: m.name,
+                matched: QuickMatch::Name,
+                page: None,
+                snippet: None,
+            });
+        }
+    }
+    results.truncate(limit);
+
+    Ok(QuickSearch {
+        query,
+        results,
+        semantic: semantic_ok,
+        elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+fn file_name(path: &str) -> String {
+    path.rsplit(['/', '\\']).next().unwrap_or(path).to_string()
+}
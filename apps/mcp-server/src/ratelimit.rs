@@ -0,0 +1,97 @@
+//! Per-session rate limits on expensive tools (`rate_limits` in the config).
+//!
+//! Each client session (a stdio server, or one daemon socket connection) gets its own sliding
+//! one-minute window per tool class, so an agent stuck in a retry loop is told to back off
+//! instead of pinning the CPU with embeddings and LLM calls. Limits are read from the config on
+//! every call; 0 disables a class.
+
+use crate::config::RateLimitConfig;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolClass {
+    /// Query embedding + vector search.
+    Search,
+    /// LLM generation.
+    Ask,
+    /// Extraction, embedding and writes.
+    Ingest,
+    /// Whole-index or whole-disk work: walks and hashing, repairs, maintenance, model downloads.
+    Heavy,
+}
+
+impl ToolClass {
+    pub fn of(tool: &str) -> Option<Self> {
+        match tool {
+            "silo_search" | "silo_search_knowledge_base" | "search_knowledge_base" | "silo_search_entities"
+            | "silo_debug_retrieval" | "silo_repo_context" | "silo_search_paths" | "silo_session_index" | "silo_grep"
+            | "silo_find_symbol" => Some(Self::Search),
+            "silo_ask" | "silo_chat" | "silo_agent" | "silo_extract_structured" => Some(Self::Ask),
+            "silo_ingest_file" | "silo_ingest_content" | "silo_ingest_url" | "silo_index_home" | "silo_index_messages"
+            | "silo_index_screenshots" | "silo_index_newsletters" | "silo_index_library" | "silo_import_chat_export"
+            | "silo_tag_documents" | "silo_eval_run" | "silo_bench_ingest" => Some(Self::Ingest),
+            "silo_verify" | "silo_disk_report" | "silo_cleanup_suggestions" | "silo_index_diff" | "silo_maintenance"
+            | "silo_models_pull" | "silo_graph_rebuild" => Some(Self::Heavy),
+            _ => None,
+        }
+    }
+
+    fn limit(self, cfg: &RateLimitConfig) -> u32 {
+        match self {
+            Self::Search => cfg.search_per_minute,
+            Self::Ask => cfg.ask_per_minute,
+            Self::Ingest => cfg.ingest_per_minute,
+            Self::Heavy => cfg.heavy_per_minute,
+        }
+    }
+}
+
+/// Why a call was refused (sent as the JSON-RPC error `data`).
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimited {
+    pub tool: String,
+    pub class: ToolClass,
+    pub limit_per_minute: u32,
+    /// When the oldest call leaves the window.
+    pub retry_after_ms: u64,
+}
+
+/// Call times of one client session.
+#[derive(Debug, Default)]
+pub struct SessionLimiter {
+    calls: HashMap<ToolClass, VecDeque<Instant>>,
+}
+
+impl SessionLimiter {
+    /// Count a call to `tool`, or refuse it when its class is at the limit.
+    pub fn check(&mut self, tool: &str, cfg: &RateLimitConfig) -> Result<(), RateLimited> {
+        let Some(class) = ToolClass::of(tool) else {
+            return Ok(());
+        };
+        let limit = class.limit(cfg);
+        if limit == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let calls = self.calls.entry(class).or_default();
+        while calls.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+            calls.pop_front();
+        }
+        if calls.len() >= limit as usize {
+            let oldest = calls.front().copied().unwrap_or(now);
+            return Err(RateLimited {
+                tool: tool.to_string(),
+                class,
+                limit_per_minute: limit,
+                retry_after_ms: WINDOW.saturating_sub(now.duration_since(oldest)).as_millis() as u64,
+            });
+        }
+        calls.push_back(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,411 @@
+//! Readable text for search and answer results (`format` argument, `result_format` config).
+//!
+//! Tool results are JSON by default, which chat clients show as one long string. With `markdown`,
+//! `silo_search` hits become a table (rank, file, score, page, preview with the query words in
+//! bold) and `silo_ask`/`silo_chat` answers get their `[n]` references as footnotes naming the
+//! cited files. `plain` uses no markup: one block per hit with the preview on one line, and the
+//! answer followed by a numbered source list, which reads better in screen readers. Rendering
+//! happens after locked paths are scrubbed and the result is cut to the size limit (a note then
+//! names the `silo_continue` cursor); results of other shapes (e.g. `silo_chat` with `history`)
+//! stay JSON. Labels follow the locale (`crate::i18n`). Files from the reference library
+//! (`crate::library`) are shown by their citation, with the path after it.
+
+use crate::errors::ToolError;
+use crate::i18n::Catalog;
+use crate::state::SharedState;
+use crate::tools::ToolCallParams;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultFormat {
+    #[default]
+    Json,
+    Markdown,
+    Plain,
+}
+
+/// Preview length in the markdown hits table when hits carry only their full text.
+const TABLE_PREVIEW_CHARS: usize = 240;
+/// Shorter query words are not highlighted.
+const MIN_HIGHLIGHT_CHARS: usize = 2;
+
+/// Tools that take a `format` argument.
+pub const FORMATTED_TOOLS: &[&str] = &[
+    "silo_search",
+    "silo_search_knowledge_base",
+    "search_knowledge_base",
+    "silo_ask",
+    "silo_chat",
+    "silo_continue",
+];
+
+/// Format for `call`: its `format` argument, else the `result_format` config (`Json` for tools
+/// without formatting).
+pub async fn requested(state: &SharedState, call: &ToolCallParams) -> Result<ResultFormat, ToolError> {
+    if !FORMATTED_TOOLS.contains(&call.name.as_str()) {
+        return Ok(ResultFormat::Json);
+    }
+    match call.arguments.get("format") {
+        Some(v) if !v.is_null() => {
+            serde_json::from_value(v.clone()).map_err(|e| ToolError::invalid_arguments(format!("`format`: {e}")))
+        }
+        _ => Ok(state.config.read().await.result_format),
+    }
+}
+
+/// `result` as text in `format`; `None` keeps the JSON (`Json`, or a result of another shape).
+/// Words of `query` are bolded in markdown previews.
+pub fn render(result: &Value, format: ResultFormat, query: Option<&str>, messages: &Catalog) -> Option<String> {
+    if let Some(hits) = result.get("hits").and_then(Value::as_array) {
+        return match format {
+            ResultFormat::Json => None,
+            ResultFormat::Markdown => Some(search_markdown(result, hits, &query_terms(query), messages)),
+            ResultFormat::Plain => Some(search_plain(result, hits, messages)),
+        };
+    }
+    if result.get("answer").and_then(Value::as_str).is_some() {
+        return match format {
+            ResultFormat::Json => None,
+            ResultFormat::Markdown => Some(answer_markdown(result, messages)),
+            ResultFormat::Plain => Some(answer_plain(result, messages)),
+        };
+    }
+    None
+}
+
+/// A table of the hits (rank, file, score, page, preview with the query words bolded), then the
+/// duplicates and, with `include_full_content`, each hit's text.
+fn search_markdown(result: &Value, hits: &[Value], terms: &[String], messages: &Catalog) -> String {
+    let mut blocks = notes_before(result, hits, messages);
+    let first = first_rank(result);
+    if !hits.is_empty() {
+        let mut table = format!(
+            "| # | {} | {} | {} | {} |\n|--:|---|--:|--:|---|",
+            messages.text("format.col.file", "File", &[]),
+            messages.text("format.col.score", "Score", &[]),
+            messages.text("format.col.page", "Page", &[]),
+            messages.text("format.col.match", "Match", &[]),
+        );
+        for (i, hit) in hits.iter().enumerate() {
+            let preview = str_field(hit, "content_preview")
+                .map(str::to_string)
+                .or_else(|| str_field(hit, "content").map(|c| crate::database::preview(c, TABLE_PREVIEW_CHARS)))
+                .unwrap_or_default();
+            let path = format!("`{}`", cell(str_field(hit, "path").unwrap_or_default()));
+            table.push_str(&format!(
+                "\n| {} | {} | {} | {} | {} |",
+                first + i,
+                match citation(hit) {
+                    Some(c) => format!("{}<br>{path}", cell(c)),
+                    None => path,
+                },
+                hit.get("score").and_then(Value::as_f64).map_or(String::new(), |s| format!("{s:.3}")),
+                hit.get("page").and_then(Value::as_i64).map_or(String::new(), |p| p.to_string()),
+                bold_terms(&cell(&preview), terms),
+            ));
+        }
+        blocks.push(table);
+    }
+
+    let duplicates: Vec<String> = hits
+        .iter()
+        .enumerate()
+        .filter_map(|(i, hit)| {
+            let paths = duplicate_paths(hit);
+            if paths.is_empty() {
+                return None;
+            }
+            let paths = paths.iter().map(|p| format!("`{p}`")).collect::<Vec<_>>().join(", ");
+            Some(format!("- {}: {}", first + i, messages.text("format.also_in", "Also in: {paths}", &[("paths", &paths)])))
+        })
+        .collect();
+    if !duplicates.is_empty() {
+        blocks.push(duplicates.join("\n"));
+    }
+
+    for (i, hit) in hits.iter().enumerate() {
+        let Some(content) = str_field(hit, "content").filter(|c| !c.trim().is_empty()) else {
+            continue;
+        };
+        let quoted: Vec<String> = content
+            .trim()
+            .lines()
+            .map(|l| format!("> {}", bold_terms(l, terms)).trim_end().to_string())
+            .collect();
+        blocks.push(format!(
+            "#### {}. `{}`\n\n{}",
+            first + i,
+            str_field(hit, "path").unwrap_or_default(),
+            quoted.join("\n")
+        ));
+    }
+    blocks.extend(notes_after(result, messages));
+    blocks.join("\n\n")
+}
+
+/// One block per hit: number and path, score and page, the text on one line, duplicates.
+fn search_plain(result: &Value, hits: &[Value], messages: &Catalog) -> String {
+    let mut blocks = notes_before(result, hits, messages);
+    let first = first_rank(result);
+    for (i, hit) in hits.iter().enumerate() {
+        let path = str_field(hit, "path").unwrap_or_default();
+        let mut block = match citation(hit) {
+            Some(c) => format!("{}. {c}\n   {path}", first + i),
+            None => format!("{}. {path}", first + i),
+        };
+        let mut details = vec![];
+        if let Some(score) = hit.get("score").and_then(Value::as_f64) {
+            let score = format!("{score:.3}");
+            details.push(messages.text("format.score", "score {score}", &[("score", &score)]));
+        }
+        if let Some(page) = hit.get("page").and_then(Value::as_i64) {
+            let page = page.to_string();
+            details.push(messages.text("format.page", "page {page}", &[("page", &page)]));
+        }
+        if !details.is_empty() {
+            block.push_str(&format!("\n   {}", details.join(", ")));
+        }
+        let text = str_field(hit, "content").or_else(|| str_field(hit, "content_preview"));
+        if let Some(text) = text.filter(|t| !t.trim().is_empty()) {
+            block.push_str(&format!("\n   {}", one_line(text)));
+        }
+        let also_in = duplicate_paths(hit);
+        if !also_in.is_empty() {
+            let paths = also_in.join(", ");
+            block.push_str(&format!("\n   {}", messages.text("format.also_in", "Also in: {paths}", &[("paths", &paths)])));
+        }
+        blocks.push(block);
+    }
+    blocks.extend(notes_after(result, messages));
+    blocks.join("\n\n")
+}
+
+/// Rank of the first hit: 1, or later in a part continued with `silo_continue`.
+fn first_rank(result: &Value) -> usize {
+    let truncation = result.get("truncation").filter(|t| str_field(t, "field") == Some("hits"));
+    1 + truncation.and_then(|t| t.get("offset")).and_then(Value::as_u64).unwrap_or_default() as usize
+}
+
+fn notes_before(result: &Value, hits: &[Value], messages: &Catalog) -> Vec<String> {
+    let mut notes = vec![];
+    if result.get("index_progress").is_some_and(|p| !p.is_null()) {
+        notes.push(messages.text(
+            "format.index_in_progress",
+            "Indexing is in progress; results may be incomplete.",
+            &[],
+        ));
+    }
+    if hits.is_empty() {
+        notes.push(messages.text("format.no_results", "No results.", &[]));
+    }
+    notes
+}
+
+fn notes_after(result: &Value, messages: &Catalog) -> Vec<String> {
+    let mut notes = vec![];
+    if result.get("content_truncated").and_then(Value::as_bool) == Some(true) {
+        notes.push(messages.text(
+            "format.content_truncated",
+            "Some chunk texts were shortened to stay within the size limit.",
+            &[],
+        ));
+    }
+    notes.extend(continuation_note(result, messages));
+    notes
+}
+
+/// For a result cut to the size limit (`crate::responses`): how to get the rest.
+fn continuation_note(result: &Value, messages: &Catalog) -> Option<String> {
+    let cursor = str_field(result, "next_cursor")?;
+    let truncation = result.get("truncation")?;
+    let count = |key: &str| truncation.get(key).and_then(Value::as_u64).unwrap_or_default();
+    let (from, to) = (count("offset") + 1, count("offset") + count("returned"));
+    Some(messages.text(
+        "format.truncated",
+        "Showing {from}-{to} of {total} ({field}); call silo_continue with cursor \"{cursor}\" for more.",
+        &[
+            ("from", &from.to_string()),
+            ("to", &to.to_string()),
+            ("total", &count("total").to_string()),
+            ("field", str_field(truncation, "field").unwrap_or_default()),
+            ("cursor", cursor),
+        ],
+    ))
+}
+
+/// The answer with its `[n]` references as footnotes, which list the cited files.
+fn answer_markdown(result: &Value, messages: &Catalog) -> String {
+    let citations = citations(result);
+    let cited: Vec<u64> = citations.iter().map(|c| c.index).collect();
+    let mut out = footnote_refs(str_field(result, "answer").unwrap_or_default().trim(), &cited);
+    if !citations.is_empty() {
+        out.push('\n');
+        for c in &citations {
+            let cited = match c.citation {
+                Some(citation) => format!("{citation} — `{}`", c.path),
+                None => format!("`{}`", c.path),
+            };
+            out.push_str(&format!("\n[^{}]: {cited}{}", c.index, page_suffix(c.page, messages)));
+        }
+    }
+    out.push_str(&session_note(result, messages));
+    if let Some(note) = continuation_note(result, messages) {
+        out.push_str(&format!("\n\n{note}"));
+    }
+    out
+}
+
+/// The answer, then `Sources:` with one `[n] path, page p` line per citation.
+fn answer_plain(result: &Value, messages: &Catalog) -> String {
+    let mut out = str_field(result, "answer").unwrap_or_default().trim().to_string();
+    let citations = citations(result);
+    if !citations.is_empty() {
+        out.push_str(&format!("\n\n{}:", messages.text("format.sources", "Sources", &[])));
+        for c in &citations {
+            let cited = match c.citation {
+                Some(citation) => format!("{citation} — {}", c.path),
+                None => c.path.to_string(),
+            };
+            out.push_str(&format!("\n[{}] {cited}{}", c.index, page_suffix(c.page, messages)));
+        }
+    }
+    out.push_str(&session_note(result, messages));
+    if let Some(note) = continuation_note(result, messages) {
+        out.push_str(&format!("\n\n{note}"));
+    }
+    out
+}
+
+struct Cited<'v> {
+    index: u64,
+    path: &'v str,
+    page: Option<i64>,
+    /// Formatted reference of a library attachment.
+    citation: Option<&'v str>,
+}
+
+fn citations(result: &Value) -> Vec<Cited<'_>> {
+    result
+        .get("citations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|c| Cited {
+            index: c.get("index").and_then(Value::as_u64).unwrap_or_default(),
+            path: str_field(c, "path").unwrap_or_default(),
+            page: c.get("page").and_then(Value::as_i64),
+            citation: citation(c),
+        })
+        .collect()
+}
+
+/// `reference.citation` of a hit or citation from a library attachment (`crate::library`).
+fn citation(v: &Value) -> Option<&str> {
+    v.get("reference").and_then(|r| str_field(r, "citation"))
+}
+
+fn page_suffix(page: Option<i64>, messages: &Catalog) -> String {
+    match page {
+        Some(page) => format!(", {}", messages.text("format.page", "page {page}", &[("page", &page.to_string())])),
+        None => String::new(),
+    }
+}
+
+/// `silo_chat` replies name their session, for the next message.
+fn session_note(result: &Value, messages: &Catalog) -> String {
+    match str_field(result, "session_id") {
+        Some(id) => format!("\n\n{}", messages.text("format.session", "Session: {id}", &[("id", id)])),
+        None => String::new(),
+    }
+}
+
+/// `[n]` -> `[^n]` for the cited numbers `n`.
+fn footnote_refs(text: &str, cited: &[u64]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let number = rest[1..].split(']').next().filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        match number.and_then(|n| n.parse::<u64>().ok().map(|parsed| (n, parsed))) {
+            Some((n, parsed)) if cited.contains(&parsed) && rest[1 + n.len()..].starts_with(']') => {
+                out.push_str(&format!("[^{parsed}]"));
+                rest = &rest[n.len() + 2..];
+            }
+            _ => {
+                out.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Words of the query text (filters and operators removed), lowercased, for highlighting.
+fn query_terms(query: Option<&str>) -> Vec<String> {
+    let Some(plan) = query.and_then(|q| crate::query::parse(q).ok()) else {
+        return vec![];
+    };
+    let mut terms: Vec<String> = plan
+        .text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_HIGHLIGHT_CHARS)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// `text` with words that start with a query term in bold.
+fn bold_terms(text: &str, terms: &[String]) -> String {
+    if terms.is_empty() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                let word = &text[start..i];
+                let lower = word.to_lowercase();
+                if terms.iter().any(|t| lower.starts_with(t.as_str())) {
+                    out.push_str(&format!("**{word}**"));
+                } else {
+                    out.push_str(word);
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+        if !c.is_alphanumeric() && i < text.len() {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Text for a one-line markdown table cell.
+fn cell(text: &str) -> String {
+    one_line(text).replace('|', "\\|")
+}
+
+fn duplicate_paths(hit: &Value) -> Vec<&str> {
+    hit.get("duplicate_paths")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn str_field<'v>(v: &'v Value, key: &str) -> Option<&'v str> {
+    v.get(key).and_then(Value::as_str)
+}
+
+/// Whitespace runs (including line breaks) as single spaces.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
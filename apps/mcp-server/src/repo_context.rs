@@ -0,0 +1,418 @@
+//! Context bundle for coding agents (`silo_repo_context`): the parts of a repository that matter
+//! for a task, packed into a token budget.
+//!
+//! Candidates come from a vector search for the task over the indexed files under the repository
+//! root, plus the definitions (`crate::symbols`) of identifiers the task names (`parse_config`,
+//! `IndexOptions`, anything in backticks). Files are ranked by their best match; in each file the
+//! matched chunks and definitions become line ranges of the file as it is on disk (merged when
+//! they touch), so code keeps its layout. The bundle lists every candidate file first, then
+//! quotes the ranges file by file, best first, until `max_tokens` (estimated like the `silo_ask`
+//! prompt) is used; the range that crosses it is cut around its definition. Locked collections are skipped.
+
+use crate::database::SearchOptions;
+use crate::errors::ToolError;
+use crate::extract::ExtractKind;
+use crate::llm::{estimate_llm_tokens, truncate_to_llm_tokens};
+use crate::predicate::Predicate;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const DEFAULT_MAX_TOKENS: usize = 8000;
+const MAX_TOKENS: usize = 100_000;
+const DEFAULT_MAX_FILES: usize = 10;
+const MAX_FILES: usize = 50;
+/// Chunks fetched from the vector search.
+const CANDIDATE_CHUNKS: usize = 60;
+/// Score of a file that defines an identifier named in the task (above any cosine score).
+const SYMBOL_SCORE: f32 = 1.0;
+/// Each further matching chunk of a file adds this much to its score.
+const EXTRA_HIT_BONUS: f32 = 0.02;
+/// Ranges at most this many lines apart are quoted as one.
+const MERGE_GAP_LINES: usize = 3;
+/// A range is cut to the remaining budget only when at least this much is left.
+const MIN_CUT_TOKENS: usize = 100;
+/// Larger files are quoted from their stored chunks instead of re-read.
+const MAX_READ_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct RepoContextArgs {
+    /// Repository root.
+    pub path: String,
+    /// What the agent is about to do, e.g. "add a retry limit to the web fetcher".
+    pub task: String,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoContext {
+    pub root: String,
+    /// The bundle: the file list, then the quoted ranges (markdown).
+    pub context: String,
+    /// Estimated tokens of `context`.
+    pub tokens: usize,
+    pub max_tokens: usize,
+    /// Candidate files, most relevant first.
+    pub files: Vec<ContextFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextFile {
+    pub path: String,
+    pub score: f32,
+    /// Identifiers from the task defined in this file.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
+    /// 1-based line ranges quoted (or that would be, for files left out).
+    pub ranges: Vec<LineRange>,
+    /// Quoted in `context` (fully or partly).
+    pub included: bool,
+    /// The budget ran out inside this file.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// A file's matches before they are turned into line ranges.
+#[derive(Default)]
+struct Candidate {
+    score: f32,
+    chunks: Vec<ChunkHit>,
+    symbols: Vec<String>,
+    symbol_lines: Vec<LineRange>,
+}
+
+struct ChunkHit {
+    index: i64,
+    start_token: Option<usize>,
+    end_token: Option<usize>,
+    score: f32,
+    text: String,
+}
+
+/// A part of a file to quote.
+struct Quote {
+    /// Line of `lines[0]`; `None` for stored chunk text.
+    first_line: Option<usize>,
+    lines: Vec<String>,
+    /// Index in `lines` of the first definition named in the task, which a cut quote keeps.
+    focus: usize,
+}
+
+/// MCP tool entrypoint (`silo_repo_context`).
+pub async fn repo_context_tool(state: &SharedState, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+    let args: RepoContextArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let result = repo_context(state, args).await?;
+    serde_json::to_value(result).map_err(|e| format!("failed to serialize repo context: {e}").into())
+}
+
+pub async fn repo_context(state: &SharedState, args: RepoContextArgs) -> Result<RepoContext, ToolError> {
+    let task = args.task.trim();
+    if task.is_empty() {
+        return Err("`task` must not be empty".into());
+    }
+    let root = expand_tilde(args.path.trim());
+    crate::tools::validate_safe_path(&root)?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a folder", root.display()).into());
+    }
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let max_tokens = args.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS).clamp(1, MAX_TOKENS);
+    let max_files = args.max_files.unwrap_or(DEFAULT_MAX_FILES).clamp(1, MAX_FILES);
+    let root_str = root.to_string_lossy().to_string();
+    let locked = crate::access::locked_paths(state).await;
+
+    let qvec = state
+        .embed_query_cached(task)
+        .await
+        .map_err(ToolError::embedding_failed)?;
+    let in_root = crate::access::path_prefix_filter(&root);
+    let filter = match locked.search_filter() {
+        Some(unlocked) => Predicate::all([in_root, unlocked]),
+        None => in_root,
+    };
+    let hits = state
+        .db
+        .search_chunks_by_vector(
+            &qvec,
+            &SearchOptions {
+                top_k: CANDIDATE_CHUNKS,
+                min_score: None,
+                filter: Some(filter),
+            },
+        )
+        .await
+        .map_err(ToolError::db_search_failed)?;
+
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+    for h in hits {
+        let c = candidates.entry(h.path.clone()).or_default();
+        let score = h.score.unwrap_or_default();
+        c.score = if c.chunks.is_empty() { score } else { c.score.max(score) + EXTRA_HIT_BONUS };
+        c.chunks.push(ChunkHit {
+            index: h.chunk_index.unwrap_or_default(),
+            start_token: h.start_token.map(|t| t as usize),
+            end_token: h.end_token.map(|t| t as usize),
+            score,
+            text: h.content.or(h.content_preview).unwrap_or_default(),
+        });
+    }
+    let identifiers = identifiers(task);
+    if !identifiers.is_empty() {
+        let catalog = state.catalog.read().await;
+        for entry in catalog.entries() {
+            if !Path::new(&entry.path).starts_with(&root) || locked.collection_of(&entry.path).is_some() {
+                continue;
+            }
+            for symbol in entry.symbols.iter().filter(|s| identifiers.contains(&s.name)) {
+                let c = candidates.entry(entry.path.clone()).or_default();
+                c.score = c.score.max(SYMBOL_SCORE);
+                if !c.symbols.contains(&symbol.name) {
+                    c.symbols.push(symbol.name.clone());
+                }
+                c.symbol_lines.push(LineRange {
+                    start_line: symbol.line,
+                    end_line: symbol.end_line,
+                });
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, Candidate)> = candidates.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(max_files);
+
+    // Ranges per file, and the text to quote for each.
+    let mut files = vec![];
+    let mut quotes = vec![];
+    for (path, c) in ranked {
+        let quoted = quote_file(Path::new(&path), c.chunks, c.symbol_lines).await;
+        let mut ranges: Vec<LineRange> = quoted.iter().filter_map(Quote::range).collect();
+        ranges.sort_by_key(|r| r.start_line);
+        files.push(ContextFile {
+            path,
+            score: c.score,
+            symbols: c.symbols,
+            ranges,
+            included: false,
+            truncated: false,
+        });
+        quotes.push(quoted);
+    }
+
+    let mut context = format!("# Repository context\n\nTask: {task}\nRoot: {root_str}\n\nFiles, most relevant first:\n");
+    for f in &files {
+        context.push_str(&format!("- {}{}\n", relative(&f.path, &root), ranges_note(&f.ranges)));
+    }
+    let mut available = max_tokens.saturating_sub(estimate_llm_tokens(&context));
+    'files: for (f, quoted) in files.iter_mut().zip(quotes) {
+        let name = relative(&f.path, &root);
+        let fence = Path::new(&f.path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        for mut quote in quoted {
+            let label = |q: &Quote| match q.range() {
+                Some(r) => format!("{name}:{}-{}", r.start_line, r.end_line),
+                None => name.clone(),
+            };
+            let fixed = estimate_llm_tokens(&format!("\n## {}\n\n```{fence}\n```\n", label(&quote)));
+            let cost = fixed + quote.lines.iter().map(|l| estimate_llm_tokens(l) + 1).sum::<usize>();
+            if cost > available {
+                if available < fixed + MIN_CUT_TOKENS {
+                    break 'files;
+                }
+                quote.cut(available - fixed);
+                f.truncated = true;
+            }
+            let section = format!("\n## {}\n\n```{fence}\n{}\n```\n", label(&quote), quote.lines.join("\n"));
+            available = available.saturating_sub(estimate_llm_tokens(&section));
+            context.push_str(&section);
+            f.included = true;
+            if f.truncated {
+                break 'files;
+            }
+        }
+    }
+    state
+        .catalog
+        .write()
+        .await
+        .record_access(files.iter().filter(|f| f.included).map(|f| f.path.as_str()));
+
+    Ok(RepoContext {
+        root: root_str,
+        tokens: estimate_llm_tokens(&context),
+        context,
+        max_tokens,
+        files,
+    })
+}
+
+impl Quote {
+    fn range(&self) -> Option<LineRange> {
+        let first = self.first_line?;
+        Some(LineRange {
+            start_line: first,
+            end_line: first + self.lines.len().max(1) - 1,
+        })
+    }
+
+    /// Keep what fits in `max_tokens`: whole lines from the focus on, or the start of chunk text.
+    fn cut(&mut self, max_tokens: usize) {
+        if self.first_line.is_none() {
+            let text = truncate_to_llm_tokens(&self.lines.join(" "), max_tokens);
+            self.lines = vec![text];
+            return;
+        }
+        // Lines from the focus on, then whatever still fits just above it.
+        let cost = |l: &String| estimate_llm_tokens(l) + 1;
+        let (mut from, mut to, mut used) = (self.focus, self.focus, 0);
+        while to < self.lines.len() && used + cost(&self.lines[to]) <= max_tokens {
+            used += cost(&self.lines[to]);
+            to += 1;
+        }
+        while from > 0 && used + cost(&self.lines[from - 1]) <= max_tokens {
+            used += cost(&self.lines[from - 1]);
+            from -= 1;
+        }
+        self.first_line = self.first_line.map(|f| f + from);
+        self.lines = self.lines.drain(from..to).collect();
+        self.focus -= from;
+    }
+}
+
+/// The parts of a file to quote, most important first: line ranges of its matched chunks and
+/// definitions (merged when they touch; definitions first, then by chunk score) read from disk.
+/// Files that can't be read as text (PDFs, removed files) and chunks stored without token offsets
+/// are quoted from the stored chunk text instead.
+async fn quote_file(path: &Path, mut chunks: Vec<ChunkHit>, symbol_lines: Vec<LineRange>) -> Vec<Quote> {
+    let text = match tokio::fs::metadata(path).await {
+        Ok(meta) if meta.len() <= MAX_READ_BYTES && !is_extracted(path) => tokio::fs::read(path)
+            .await
+            .ok()
+            .map(|b| String::from_utf8_lossy(&b).to_string()),
+        _ => None,
+    };
+    let has_offsets = !symbol_lines.is_empty() || chunks.iter().any(|c| c.start_token.is_some() && c.end_token.is_some());
+    let Some(text) = text.filter(|_| has_offsets) else {
+        chunks.sort_by_key(|c| c.index);
+        chunks.dedup_by_key(|c| c.index);
+        chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+        return chunks
+            .into_iter()
+            .map(|c| Quote {
+                first_line: None,
+                lines: vec![c.text],
+                focus: 0,
+            })
+            .collect();
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let token_lines = token_lines(&text);
+    let line_of = |token: usize| token_lines.get(token).or(token_lines.last()).copied().unwrap_or(1);
+    // (range, priority, first definition line)
+    let mut ranges: Vec<(LineRange, f32, Option<usize>)> = chunks
+        .iter()
+        .filter_map(|c| {
+            let (start, end) = (c.start_token?, c.end_token?);
+            let range = LineRange {
+                start_line: line_of(start),
+                end_line: line_of(end.saturating_sub(1).max(start)),
+            };
+            Some((range, c.score, None))
+        })
+        .chain(symbol_lines.into_iter().map(|r| (r, SYMBOL_SCORE, Some(r.start_line))))
+        .collect();
+    ranges.sort_by_key(|(r, _, _)| (r.start_line, r.end_line));
+    let mut merged: Vec<(LineRange, f32, Option<usize>)> = vec![];
+    for (r, priority, focus) in ranges {
+        match merged.last_mut() {
+            Some((last, p, f)) if r.start_line <= last.end_line + MERGE_GAP_LINES + 1 => {
+                last.end_line = last.end_line.max(r.end_line);
+                *p = p.max(priority);
+                *f = f.or(focus);
+            }
+            _ => merged.push((r, priority, focus)),
+        }
+    }
+    merged.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.start_line.cmp(&b.0.start_line)));
+    merged
+        .into_iter()
+        .map(|(r, _, focus)| {
+            let (from, to) = (r.start_line.max(1) - 1, r.end_line.min(lines.len()));
+            Quote {
+                first_line: Some(from + 1),
+                lines: lines.get(from..to).unwrap_or_default().iter().map(|l| l.to_string()).collect(),
+                focus: focus.map_or(0, |f| f.saturating_sub(from + 1)),
+            }
+        })
+        .collect()
+}
+
+/// Formats whose stored text is extracted (or flattened), not the file's bytes.
+fn is_extracted(path: &Path) -> bool {
+    matches!(
+        crate::extract::detect_kind(path),
+        ExtractKind::Pdf | ExtractKind::Image | ExtractKind::Json
+    )
+}
+
+/// 1-based line of each whitespace-separated token (the tokens chunk offsets count).
+fn token_lines(text: &str) -> Vec<usize> {
+    let mut out = vec![];
+    for (i, line) in text.lines().enumerate() {
+        out.extend(std::iter::repeat_n(i + 1, line.split_whitespace().count()));
+    }
+    out
+}
+
+/// Words of the task that look like code identifiers: in backticks, or with `_`, `::`, `.` or
+/// an inner capital (`parse_config`, `IndexOptions`, `Config::load`: `load`).
+fn identifiers(task: &str) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    let quoted: Vec<&str> = task.split('`').skip(1).step_by(2).collect();
+    let words = task
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':' || c == '.'))
+        .map(|w| (w, false))
+        .chain(quoted.into_iter().map(|w| (w, true)));
+    for (word, backticked) in words {
+        let word = word.trim_matches(['.', ':', '(', ')']);
+        let name = word.rsplit([':', '.']).next().unwrap_or(word).trim_end_matches("()");
+        let looks_like_code = backticked
+            || word.contains("::")
+            || name.contains('_')
+            || (name.chars().skip(1).any(char::is_uppercase) && name.chars().any(char::is_lowercase));
+        let valid = name.len() >= 3 && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if looks_like_code && valid && !out.iter().any(|o| o == name) {
+            out.push(name.to_string());
+        }
+    }
+    out
+}
+
+fn relative(path: &str, root: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// ` (lines 10-40, 88-120)`
+fn ranges_note(ranges: &[LineRange]) -> String {
+    if ranges.is_empty() {
+        return String::new();
+    }
+    let list: Vec<String> = ranges.iter().map(|r| format!("{}-{}", r.start_line, r.end_line)).collect();
+    format!(" (lines {})", list.join(", "))
+}
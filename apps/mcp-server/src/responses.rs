@@ -0,0 +1,242 @@
+//! Size limits on tool results (`response_limits` config) and `silo_continue`.
+//!
+//! A JSON result larger than the tool's limit is cut rather than sent whole: its largest list
+//! (e.g. `hits`) keeps the items that fit, or its largest text (e.g. the `content` of a file) the
+//! characters that fit. The result then says `truncated: true`, carries a `next_cursor`, and a
+//! `truncation` object naming the field, the offset and count returned, and the total.
+//! `silo_continue` with the cursor returns the next part in the same shape; the last part has
+//! `truncated: false` and no cursor. A result that cannot be cut (no list or text, or one item
+//! alone is over the limit) is refused with an error suggesting a narrower request.
+//!
+//! The remaining parts are kept in memory for a while (`CONTINUATION_TTL`), after locked paths
+//! were scrubbed; a cursor is single-use.
+
+use crate::cache::TtlLru;
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Unfinished results kept for `silo_continue`; older ones are dropped.
+const CONTINUATION_CAPACITY: usize = 64;
+const CONTINUATION_TTL: Duration = Duration::from_secs(10 * 60);
+/// Room left in each part for `truncated`, `next_cursor` and `truncation`.
+const METADATA_BYTES: usize = 256;
+
+/// The rest of a result that was cut.
+#[derive(Clone)]
+struct Continuation {
+    tool: String,
+    /// The result without the cut field.
+    base: Map<String, Value>,
+    field: String,
+    /// Items (or text) of `field` not returned yet.
+    rest: Value,
+    /// Position of `rest` in the full field.
+    offset: usize,
+    total: usize,
+}
+
+pub struct Continuations {
+    pages: Mutex<TtlLru<String, Continuation>>,
+}
+
+impl Default for Continuations {
+    fn default() -> Self {
+        Self {
+            pages: Mutex::new(TtlLru::new(CONTINUATION_CAPACITY, CONTINUATION_TTL)),
+        }
+    }
+}
+
+impl Continuations {
+    fn store(&self, c: Continuation) -> String {
+        let cursor = crate::consent::random_id(&format!("{}\n{}\n{}", c.tool, c.field, c.offset));
+        let mut pages = self.pages.lock().unwrap_or_else(|e| e.into_inner());
+        pages.insert(cursor.clone(), c);
+        cursor
+    }
+
+    fn take(&self, cursor: &str) -> Option<Continuation> {
+        let mut pages = self.pages.lock().unwrap_or_else(|e| e.into_inner());
+        let c = pages.get(&cursor.to_string());
+        pages.remove(&cursor.to_string());
+        c
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ContinueArgs {
+    cursor: String,
+}
+
+/// Cut the JSON result `text` of `tool` to the configured limit; unchanged when it fits or is
+/// not a JSON object.
+pub async fn limit(state: &SharedState, tool: &str, text: &mut String) -> Result<(), String> {
+    let max_bytes = state.config.read().await.response_limits.max_bytes_for(tool);
+    if text.len() <= max_bytes {
+        return Ok(());
+    }
+    let Ok(Value::Object(mut result)) = serde_json::from_str::<Value>(text) else {
+        return Ok(());
+    };
+    let size = text.len();
+    let too_large = || {
+        format!(
+            "The {tool} result is {size} bytes, over the {max_bytes}-byte limit (response_limits), and cannot be split; \
+             narrow the request (e.g. a lower limit, fewer paths or a page range)"
+        )
+    };
+    let Some(field) = largest_field(&result) else {
+        return Err(too_large());
+    };
+    let Some(rest) = result.remove(&field) else {
+        return Err(too_large());
+    };
+    let total = len(&rest);
+    let page = next_page(state, tool.to_string(), result, field, rest, 0, total, max_bytes);
+    match page {
+        Some(page) => {
+            *text = page.to_string();
+            Ok(())
+        }
+        None => Err(too_large()),
+    }
+}
+
+/// MCP tool entrypoint (`silo_continue`).
+pub async fn continue_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ContinueArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let Some(c) = state.continuations.take(&args.cursor) else {
+        return Err(format!(
+            "Unknown or expired cursor {}; repeat the original call to start over",
+            args.cursor
+        )
+        .into());
+    };
+    let max_bytes = state.config.read().await.response_limits.max_bytes_for(&c.tool);
+    let Continuation { tool, base, field, rest, offset, total } = c;
+    next_page(state, tool.clone(), base, field.clone(), rest, offset, total, max_bytes).ok_or_else(|| {
+        format!("The next part of the {tool} result does not fit in {max_bytes} bytes (`{field}` item {offset})").into()
+    })
+}
+
+/// `base` with as much of `rest` as fits in `max_bytes` under `field` (at least one item or
+/// character), and the truncation metadata; the remainder is stored for `silo_continue`. `None`
+/// when not even one item fits.
+#[allow(clippy::too_many_arguments)]
+fn next_page(
+    state: &SharedState,
+    tool: String,
+    base: Map<String, Value>,
+    field: String,
+    rest: Value,
+    offset: usize,
+    total: usize,
+    max_bytes: usize,
+) -> Option<Value> {
+    let budget = max_bytes
+        .checked_sub(Value::Object(base.clone()).to_string().len() + field.len() + METADATA_BYTES)
+        .filter(|b| *b > 0)?;
+    let (taken, remainder, unit) = match rest {
+        Value::Array(items) => {
+            let (taken, remainder) = split_items(items, budget)?;
+            (Value::Array(taken), remainder.map(Value::Array), "items")
+        }
+        Value::String(s) => {
+            let (taken, remainder) = split_text(&s, budget);
+            (Value::String(taken), remainder.map(Value::String), "chars")
+        }
+        _ => return None,
+    };
+    let returned = len(&taken);
+    // The tool's own `truncated` (e.g. a result limit it hit) still holds on the last part.
+    let was_truncated = base.get("truncated").and_then(Value::as_bool).unwrap_or(false);
+    let mut page = base.clone();
+    page.insert(field.clone(), taken);
+    page.insert(
+        "truncation".to_string(),
+        json!({ "field": field, "offset": offset, "returned": returned, "total": total, "unit": unit }),
+    );
+    match remainder {
+        Some(remainder) => {
+            let cursor = state.continuations.store(Continuation {
+                tool,
+                base,
+                field,
+                rest: remainder,
+                offset: offset + returned,
+                total,
+            });
+            page.insert("truncated".to_string(), Value::Bool(true));
+            page.insert("next_cursor".to_string(), Value::String(cursor));
+        }
+        None => {
+            page.insert("truncated".to_string(), Value::Bool(was_truncated));
+        }
+    }
+    Some(Value::Object(page))
+}
+
+/// The top-level list or text taking the most space.
+fn largest_field(result: &Map<String, Value>) -> Option<String> {
+    result
+        .iter()
+        .filter(|(_, v)| matches!(v, Value::Array(a) if !a.is_empty()) || matches!(v, Value::String(s) if !s.is_empty()))
+        .max_by_key(|(_, v)| v.to_string().len())
+        .map(|(k, _)| k.clone())
+}
+
+/// The leading items that fit in `budget` bytes of JSON, and the rest if any.
+fn split_items(mut items: Vec<Value>, budget: usize) -> Option<(Vec<Value>, Option<Vec<Value>>)> {
+    let mut used = 2;
+    let mut fits = 0;
+    for item in &items {
+        used += item.to_string().len() + 1;
+        if used > budget {
+            break;
+        }
+        fits += 1;
+    }
+    if fits == 0 {
+        return None;
+    }
+    let rest = items.split_off(fits);
+    Some((items, (!rest.is_empty()).then_some(rest)))
+}
+
+/// The leading characters of `text` that fit in `budget` bytes as a JSON string (at least one),
+/// and the rest if any.
+fn split_text(text: &str, budget: usize) -> (String, Option<String>) {
+    let mut used = 2;
+    let mut end = text.len();
+    for (i, c) in text.char_indices() {
+        used += escaped_len(c);
+        if used > budget && i > 0 {
+            end = i;
+            break;
+        }
+    }
+    let rest = &text[end..];
+    (text[..end].to_string(), (!rest.is_empty()).then(|| rest.to_string()))
+}
+
+/// Bytes of `c` inside a JSON string.
+fn escaped_len(c: char) -> usize {
+    match c {
+        '"' | '\\' | '\n' | '\r' | '\t' | '\u{8}' | '\u{c}' => 2,
+        c if (c as u32) < 0x20 => 6,
+        c => c.len_utf8(),
+    }
+}
+
+/// Items of a list, or characters of a text.
+fn len(v: &Value) -> usize {
+    match v {
+        Value::Array(a) => a.len(),
+        Value::String(s) => s.chars().count(),
+        _ => 0,
+    }
+}
@@ -0,0 +1,219 @@
+//! `silo_debug_retrieval`: why an expected file is (not) among the results for a query.
+//!
+//! Walks the same steps as `silo_search` for one file: is it indexed (else why not, via
+//! `crate::policy_explain`), do its chunks pass each filter of the call, how similar is its best
+//! chunk, and where does it rank among all chunks passing the filters (after collapsing duplicate
+//! text, like the search). Ranks use plain similarity: the `prefer_recent`/`title_weight` rerank
+//! and query rewriting are not applied.
+
+use crate::database::{ChunkSource, SearchOptions};
+use crate::errors::ToolError;
+use crate::predicate::Predicate;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Ranks are searched this deep; a file below it is reported as not ranked.
+const MAX_RANK_SCAN: usize = 200;
+const DEFAULT_TOP_K: usize = 10;
+
+#[derive(Debug, Deserialize)]
+struct DebugRetrievalArgs {
+    query: String,
+    path: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+    #[serde(default)]
+    min_score: Option<f32>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    sources: Vec<ChunkSource>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterCheck {
+    pub filter: String,
+    /// Some chunk of the file passes the filter.
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BestChunk {
+    pub id: Option<String>,
+    pub chunk_index: Option<i64>,
+    pub score: Option<f32>,
+    pub content_preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetrievalDebug {
+    pub query: String,
+    pub path: String,
+    /// One-line answer: `returned at rank N`, or the first reason the file is missing.
+    pub verdict: String,
+    pub indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_chunks: Option<usize>,
+    /// For files that are not indexed: how the indexing policy treats the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<FilterCheck>,
+    /// The file's most similar chunk, ignoring filters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_chunk: Option<BestChunk>,
+    /// 1-based rank of the file's best hit among hits passing the filters (`None`: filtered out,
+    /// or below `MAX_RANK_SCAN`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<usize>,
+    /// Another file whose identical chunk text the file's hit was collapsed into.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapsed_into: Option<String>,
+    pub top_k: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+}
+
+/// MCP tool entrypoint (`silo_debug_retrieval`).
+pub async fn debug_retrieval_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: DebugRetrievalArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let debug = debug_retrieval(state, args).await?;
+    serde_json::to_value(debug).map_err(|e| format!("failed to serialize retrieval debug: {e}").into())
+}
+
+async fn debug_retrieval(state: &SharedState, args: DebugRetrievalArgs) -> Result<RetrievalDebug, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let path = expand_tilde(&args.path).to_string_lossy().to_string();
+    let locked = crate::access::locked_paths(state).await;
+    locked.check(&path)?;
+    let top_k = args.top_k.unwrap_or(DEFAULT_TOP_K).clamp(1, 50);
+    let mut out = RetrievalDebug {
+        query: args.query.clone(),
+        path: path.clone(),
+        top_k,
+        min_score: args.min_score,
+        ..Default::default()
+    };
+
+    let file = Predicate::eq("path", path.as_str());
+    out.catalog_chunks = state.catalog.read().await.get(&path).map(|e| e.chunks);
+    out.indexed = !state.db.find_chunks(&file, 1).await.map_err(ToolError::db_query_failed)?.is_empty();
+    if !out.indexed {
+        let policy = crate::policy_explain::explain(state, std::path::Path::new(&path)).await?;
+        out.verdict = match policy.skip_reason {
+            Some(reason) => format!("not indexed: the indexing policy skips it ({})", json_name(&reason)),
+            None if policy.decision == "skip" => "not indexed: it is outside the indexing roots".to_string(),
+            None => "not indexed yet (the policy allows it; index or ingest it)".to_string(),
+        };
+        out.policy = Some(serde_json::to_value(&policy).map_err(|e| e.to_string())?);
+        return Ok(out);
+    }
+
+    let plan = crate::query::parse(&args.query)?;
+    if plan.text.trim().is_empty() {
+        return Err(ToolError::filters_only_query());
+    }
+    let qvec = state
+        .embed_query_cached(&plan.text)
+        .await
+        .map_err(ToolError::embedding_failed)?;
+
+    let best = state
+        .db
+        .search_chunks_by_vector(
+            &qvec,
+            &SearchOptions {
+                top_k: 1,
+                min_score: None,
+                filter: Some(file.clone()),
+            },
+        )
+        .await
+        .map_err(ToolError::db_query_failed)?
+        .into_iter()
+        .next();
+    let best_score = best.as_ref().and_then(|h| h.score);
+    out.best_chunk = best.map(|h| BestChunk {
+        id: h.id,
+        chunk_index: h.chunk_index,
+        score: h.score,
+        content_preview: h.content_preview,
+    });
+
+    let mut named: Vec<(String, Predicate)> = vec![];
+    if let Some(f) = plan.filter.clone() {
+        named.push((format!("query syntax ({f})"), f));
+    }
+    if let Some(f) = crate::tagging::search_filter(&args.tags) {
+        named.push((format!("tags {:?}", args.tags), f));
+    }
+    if !args.sources.is_empty() {
+        let names: Vec<&str> = args.sources.iter().map(|s| s.as_str()).collect();
+        named.push((format!("sources {names:?}"), crate::database::sources_filter(&args.sources)));
+    }
+    for (name, f) in &named {
+        let passed = !state
+            .db
+            .find_chunks(&file.clone().and(f.clone()), 1)
+            .await
+            .map_err(ToolError::db_query_failed)?
+            .is_empty();
+        out.filters.push(FilterCheck {
+            filter: name.clone(),
+            passed,
+        });
+    }
+    let filter = named
+        .into_iter()
+        .map(|(_, f)| f)
+        .chain(locked.search_filter())
+        .reduce(|a, b| a.and(b));
+
+    let hits = state
+        .db
+        .search_chunks_by_vector(
+            &qvec,
+            &SearchOptions {
+                top_k: MAX_RANK_SCAN,
+                min_score: None,
+                filter,
+            },
+        )
+        .await
+        .map_err(ToolError::db_query_failed)?;
+    let hits = crate::tools::collapse_duplicate_chunks(hits);
+    if let Some(i) = hits.iter().position(|h| h.path == path || h.duplicate_paths.contains(&path)) {
+        out.rank = Some(i + 1);
+        if hits[i].path != path {
+            out.collapsed_into = Some(hits[i].path.clone());
+        }
+    }
+
+    out.verdict = if let Some(f) = out.filters.iter().find(|f| !f.passed) {
+        format!("excluded by filter: {}", f.filter)
+    } else if let Some(min) = args.min_score.filter(|min| best_score.is_none_or(|s| s < *min)) {
+        format!("best chunk scores {} (below min_score {min})", best_score.map_or("n/a".to_string(), |s| format!("{s:.3}")))
+    } else {
+        match out.rank {
+            None => format!("ranks below the top {MAX_RANK_SCAN} chunks"),
+            Some(r) if r > top_k => format!("ranks {r}, beyond top_k {top_k}"),
+            Some(r) => match &out.collapsed_into {
+                Some(other) => format!("returned at rank {r} as a duplicate of {other} (see duplicate_paths)"),
+                None => format!("returned at rank {r}"),
+            },
+        }
+    };
+    Ok(out)
+}
+
+/// Serialized name of a unit enum value (e.g. `too_large`).
+fn json_name(v: &impl Serialize) -> String {
+    match serde_json::to_value(v) {
+        Ok(Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,383 @@
+//! Query preprocessing for `silo_search` (`query_rewrite` config): typo correction against the
+//! corpus vocabulary, synonym expansion by the local LLM, and HyDE. All are off by default.
+//!
+//! The vocabulary (word -> occurrences in indexed chunks and file names) is built from the
+//! knowledge base on first use and kept in memory. Index changes mark it stale; a stale vocabulary
+//! is rebuilt on use once it is `REBUILD_AFTER` old, so a bulk index run does not rebuild it for
+//! every search. A query word is corrected only when the vocabulary lacks it and has a frequent
+//! word within one edit (two for words of `LONG_WORD_CHARS` or more).
+//!
+//! Synonyms are appended to the text that is embedded; they do not change filters or title
+//! matching. With `rewrite: "hyde"` the LLM drafts a passage that would answer the query, and the
+//! passage is embedded instead (a short question lands closer to answer-like chunks than the
+//! question itself does). The LLM's answers are cached per prompt.
+
+use crate::cache::TtlLru;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const REBUILD_AFTER: Duration = Duration::from_secs(10 * 60);
+/// Paths read from the knowledge base per query while building.
+const BUILD_BATCH_PATHS: usize = 64;
+/// Distinct words kept at most; once full, only words already counted are counted.
+const MAX_WORDS: usize = 300_000;
+const MIN_WORD_CHARS: usize = 3;
+const MAX_WORD_CHARS: usize = 32;
+/// Shorter query words are never corrected.
+const MIN_CORRECT_CHARS: usize = 4;
+const LONG_WORD_CHARS: usize = 8;
+/// A correction must occur at least this often in the corpus.
+const MIN_CORRECTION_COUNT: u32 = 3;
+
+/// `query_rewrite.max_synonyms` is capped at this.
+const MAX_SYNONYMS: usize = 10;
+/// HyDE passages are cut to this many LLM tokens before embedding.
+const MAX_PASSAGE_TOKENS: usize = 256;
+const LLM_CACHE_CAPACITY: usize = 256;
+const LLM_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+const SYNONYM_PROMPT: &str = "List up to {max} synonyms or closely related search terms for the search query below, \
+most useful first. Answer with a comma-separated list only, no explanations.\n\nQuery: {query}";
+
+const HYDE_PROMPT: &str = "Write a short passage (3 to 5 sentences) from a personal document, note or email that \
+answers or matches the search query below. Make up plausible details if needed. Answer with the passage only.\n\n\
+Query: {query}";
+
+/// How the query text is turned into what is embedded (`rewrite` search option).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewriteMode {
+    /// The query itself (after typo correction and synonyms).
+    #[default]
+    None,
+    /// A hypothetical answer passage drafted by the local LLM.
+    Hyde,
+}
+
+/// What to apply to a query (from the call, falling back to the `query_rewrite` config).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RewriteOptions {
+    pub correct_typos: bool,
+    pub expand_synonyms: bool,
+    pub mode: RewriteMode,
+}
+
+impl RewriteOptions {
+    pub fn any(&self) -> bool {
+        self.correct_typos || self.expand_synonyms || self.mode != RewriteMode::None
+    }
+}
+
+/// A word of the query replaced by a vocabulary word.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Correction {
+    pub from: String,
+    pub to: String,
+}
+
+/// `text` as corrected (used for embedding with `synonyms`, and for title matching).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Rewrite {
+    pub text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub corrections: Vec<Correction>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub synonyms: Vec<String>,
+    /// HyDE passage embedded instead of the query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passage: Option<String>,
+}
+
+impl Rewrite {
+    pub fn changed(&self) -> bool {
+        !self.corrections.is_empty() || !self.synonyms.is_empty() || self.passage.is_some()
+    }
+
+    /// Text to embed: the HyDE passage, or the corrected query followed by its synonyms.
+    pub fn embed_text(&self) -> String {
+        if let Some(p) = &self.passage {
+            return p.clone();
+        }
+        if self.synonyms.is_empty() {
+            return self.text.clone();
+        }
+        format!("{} {}", self.text, self.synonyms.join(" "))
+    }
+}
+
+struct Vocabulary {
+    words: HashMap<String, u32>,
+    built_at: Instant,
+}
+
+/// Vocabulary and synonym cache (`AppState::query_rewriter`).
+pub struct QueryRewriter {
+    vocabulary: tokio::sync::Mutex<Option<Arc<Vocabulary>>>,
+    stale: AtomicBool,
+    /// prompt -> LLM answer
+    llm_answers: Mutex<TtlLru<String, String>>,
+}
+
+impl Default for QueryRewriter {
+    fn default() -> Self {
+        Self {
+            vocabulary: tokio::sync::Mutex::new(None),
+            stale: AtomicBool::new(false),
+            llm_answers: Mutex::new(TtlLru::new(LLM_CACHE_CAPACITY, LLM_CACHE_TTL)),
+        }
+    }
+}
+
+impl QueryRewriter {
+    /// The index changed: rebuild the vocabulary on a later use.
+    pub fn mark_stale(&self) {
+        self.stale.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Apply `opts` to `text`. Failures (no LLM, DB errors) leave that step out.
+pub async fn rewrite(state: &SharedState, text: &str, opts: RewriteOptions) -> Rewrite {
+    let mut out = Rewrite {
+        text: text.to_string(),
+        ..Rewrite::default()
+    };
+    if opts.correct_typos {
+        match vocabulary(state).await {
+            Ok(vocab) => {
+                let (text, corrections) = correct_words(&vocab.words, text);
+                out.text = text;
+                out.corrections = corrections;
+            }
+            Err(e) => tracing::warn!("Typo correction skipped: {e}"),
+        }
+    }
+    if opts.expand_synonyms {
+        let max = state.config.read().await.query_rewrite.max_synonyms.min(MAX_SYNONYMS);
+        match synonyms(state, &out.text, max).await {
+            Ok(s) => out.synonyms = s,
+            Err(e) => tracing::warn!("Query expansion skipped: {e}"),
+        }
+    }
+    if opts.mode == RewriteMode::Hyde {
+        match hyde_passage(state, &out.text).await {
+            Ok(p) => out.passage = Some(p),
+            Err(e) => tracing::warn!("HyDE rewrite skipped: {e}"),
+        }
+    }
+    out
+}
+
+/// The current vocabulary: built on first use, rebuilt when stale and old enough.
+async fn vocabulary(state: &SharedState) -> Result<Arc<Vocabulary>, String> {
+    let rewriter = &state.query_rewriter;
+    let mut cache = rewriter.vocabulary.lock().await;
+    let stale = rewriter.stale.load(Ordering::Relaxed);
+    if let Some(v) = cache.as_ref().filter(|v| !stale || v.built_at.elapsed() < REBUILD_AFTER) {
+        return Ok(v.clone());
+    }
+    rewriter.stale.store(false, Ordering::Relaxed);
+    let built = match build(state).await {
+        Ok(v) => Arc::new(v),
+        Err(e) => {
+            rewriter.stale.store(true, Ordering::Relaxed);
+            return Err(e);
+        }
+    };
+    *cache = Some(built.clone());
+    Ok(built)
+}
+
+/// Count words in every indexed chunk and file name.
+async fn build(state: &SharedState) -> Result<Vocabulary, String> {
+    let started = Instant::now();
+    let paths: Vec<String> = state.catalog.read().await.entries().map(|e| e.path.clone()).collect();
+    let mut words: HashMap<String, u32> = HashMap::new();
+    for path in &paths {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        count_words(&mut words, name);
+    }
+    for batch in paths.chunks(BUILD_BATCH_PATHS) {
+        let rows = state
+            .db
+            .chunks_for_paths(batch)
+            .await
+            .map_err(|e| format!("DB query failed: {e}"))?;
+        for row in &rows {
+            count_words(&mut words, &row.content);
+        }
+    }
+    tracing::info!(
+        "Built query vocabulary: {} words from {} files in {} ms",
+        words.len(),
+        paths.len(),
+        started.elapsed().as_millis()
+    );
+    Ok(Vocabulary {
+        words,
+        built_at: Instant::now(),
+    })
+}
+
+fn count_words(words: &mut HashMap<String, u32>, text: &str) {
+    for w in text.split(|c: char| !c.is_alphabetic()) {
+        let n = w.chars().count();
+        if !(MIN_WORD_CHARS..=MAX_WORD_CHARS).contains(&n) {
+            continue;
+        }
+        let w = w.to_lowercase();
+        if let Some(c) = words.get_mut(&w) {
+            *c = c.saturating_add(1);
+        } else if words.len() < MAX_WORDS {
+            words.insert(w, 1);
+        }
+    }
+}
+
+/// Replace unknown words of `text` with their closest frequent vocabulary word. Words with
+/// digits or inner symbols (`v2beta`, `foo_bar`) are kept as typed; whitespace is normalized.
+fn correct_words(words: &HashMap<String, u32>, text: &str) -> (String, Vec<Correction>) {
+    let mut corrections: Vec<Correction> = vec![];
+    let tokens: Vec<String> = text
+        .split_whitespace()
+        .map(|token| {
+            let core = token.trim_matches(|c: char| !c.is_alphanumeric());
+            if core.is_empty() || !core.chars().all(char::is_alphabetic) {
+                return token.to_string();
+            }
+            let lower = core.to_lowercase();
+            let Some(to) = closest_word(words, &lower) else {
+                return token.to_string();
+            };
+            let fixed = token.replacen(core, &to, 1);
+            if !corrections.iter().any(|c| c.from == lower) {
+                corrections.push(Correction { from: lower, to });
+            }
+            fixed
+        })
+        .collect();
+    (tokens.join(" "), corrections)
+}
+
+/// The most frequent vocabulary word within the allowed edit distance of `word`, when `word`
+/// itself is not in the vocabulary.
+fn closest_word(words: &HashMap<String, u32>, word: &str) -> Option<String> {
+    let len = word.chars().count();
+    if len < MIN_CORRECT_CHARS || words.contains_key(word) {
+        return None;
+    }
+    let max_edits = if len >= LONG_WORD_CHARS { 2 } else { 1 };
+    let target: Vec<char> = word.chars().collect();
+    let mut best: Option<(usize, u32, &str)> = None;
+    for (candidate, &count) in words {
+        if count < MIN_CORRECTION_COUNT || candidate.len().abs_diff(word.len()) > max_edits * 4 {
+            continue;
+        }
+        let chars: Vec<char> = candidate.chars().collect();
+        if chars.len().abs_diff(len) > max_edits {
+            continue;
+        }
+        let Some(d) = edit_distance(&target, &chars, max_edits) else {
+            continue;
+        };
+        let better = match best {
+            None => true,
+            Some((bd, bc, bw)) => (d, std::cmp::Reverse(count), candidate.as_str()) < (bd, std::cmp::Reverse(bc), bw),
+        };
+        if better {
+            best = Some((d, count, candidate));
+        }
+    }
+    best.map(|(_, _, w)| w.to_string())
+}
+
+/// Optimal string alignment distance (edits plus adjacent transpositions) between `a` and `b`,
+/// or `None` when it exceeds `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        let mut row_min = cur[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(prev2[j - 2] + 1);
+            }
+            cur[j] = d;
+            row_min = row_min.min(d);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    let d = prev[b.len()];
+    (d <= max).then_some(d)
+}
+
+/// Up to `max` LLM-suggested synonyms for `query`.
+async fn synonyms(state: &SharedState, query: &str, max: usize) -> Result<Vec<String>, String> {
+    if max == 0 {
+        return Ok(vec![]);
+    }
+    let prompt = SYNONYM_PROMPT
+        .replace("{max}", &max.to_string())
+        .replace("{query}", query);
+    let raw = generate_cached(state, prompt).await?;
+    Ok(parse_synonyms(&raw, query, max))
+}
+
+/// A hypothetical passage answering `query`, cut to `MAX_PASSAGE_TOKENS`.
+async fn hyde_passage(state: &SharedState, query: &str) -> Result<String, String> {
+    let raw = generate_cached(state, HYDE_PROMPT.replace("{query}", query)).await?;
+    let passage = crate::llm::truncate_to_llm_tokens(raw.trim(), MAX_PASSAGE_TOKENS);
+    if passage.is_empty() {
+        return Err("LLM returned an empty passage".to_string());
+    }
+    Ok(passage)
+}
+
+async fn generate_cached(state: &SharedState, prompt: String) -> Result<String, String> {
+    if state.llm.model_id() == "none" {
+        return Err("no local LLM is configured (SILO_LLM_BACKEND, SILO_LLM_MODEL)".to_string());
+    }
+    if let Some(a) = lock(&state.query_rewriter.llm_answers).get(&prompt) {
+        return Ok(a);
+    }
+    let answer = state.llm.generate(prompt.clone()).await?;
+    lock(&state.query_rewriter.llm_answers).insert(prompt, answer.clone());
+    Ok(answer)
+}
+
+/// Comma- or line-separated terms from an LLM answer, minus list markers, duplicates and terms
+/// already in `query`.
+fn parse_synonyms(raw: &str, query: &str, max: usize) -> Vec<String> {
+    let query = query.to_lowercase();
+    let mut out: Vec<String> = vec![];
+    for item in raw.split([',', '\n', ';']) {
+        let term = item
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | '•'))
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if term.is_empty() || term.chars().count() > 40 || query.contains(&term) || out.contains(&term) {
+            continue;
+        }
+        out.push(term);
+        if out.len() >= max {
+            break;
+        }
+    }
+    out
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,215 @@
+//! Sandbox mode (`sandbox` in the config) and session-scoped temporary roots.
+//!
+//! With `sandbox.enabled`, a tool call from an MCP client whose `path`/`directory`/`paths`/`roots`
+//! argument lies outside the filesystem roots is refused, unless the folder was granted to the
+//! session with `silo_grant_temp_root`. A grant lasts `sandbox.grant_secs` (or less, if the call
+//! asks), is never written to the config, and ends with the session: it lives in the connection's
+//! `server::Session` and is made visible to its tool calls through the `SESSION_ROOTS` task-local.
+//! Calls made outside an MCP session (the desktop app) are not confined.
+//!
+//! Like unlocking, granting is for the client only: the built-in agent's dispatcher does not offer
+//! the grant tool, but the agent's own calls are confined to the session's roots.
+
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use crate::tools::ToolCallParams;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    /// Temporary roots of the MCP session running the current tool call.
+    pub static SESSION_ROOTS: SessionRoots;
+}
+
+/// Temporary roots of one MCP session (shared by its tool calls).
+#[derive(Debug, Clone, Default)]
+pub struct SessionRoots {
+    grants: Arc<Mutex<Vec<Grant>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Grant {
+    path: PathBuf,
+    until: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrantStatus {
+    pub path: String,
+    pub expires_in_secs: u64,
+}
+
+impl SessionRoots {
+    /// Grants that have not expired yet.
+    fn active(&self) -> Vec<Grant> {
+        let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        grants.retain(|g| g.until > now);
+        grants.clone()
+    }
+
+    /// Grant `path` until `until` (replacing an earlier grant of the same folder).
+    fn grant(&self, path: PathBuf, until: Instant) {
+        let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        grants.retain(|g| g.path != path);
+        grants.push(Grant { path, until });
+    }
+
+    fn revoke(&self, path: &Path) -> bool {
+        let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        let before = grants.len();
+        grants.retain(|g| g.path != path);
+        grants.len() < before
+    }
+
+    pub fn status(&self) -> Vec<GrantStatus> {
+        let now = Instant::now();
+        self.active()
+            .into_iter()
+            .map(|g| GrantStatus {
+                path: g.path.to_string_lossy().to_string(),
+                expires_in_secs: g.until.saturating_duration_since(now).as_secs(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantArgs {
+    /// Folder to grant (`~` is expanded).
+    pub path: String,
+    /// Grant for this long instead of `sandbox.grant_secs` (at most `sandbox.max_grant_secs`).
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// End an earlier grant of `path` instead.
+    #[serde(default)]
+    pub revoke: bool,
+}
+
+/// Refuse `call` when the sandbox is on, it runs in an MCP session, and one of its paths is
+/// outside the filesystem roots and the session's temporary roots.
+pub async fn check(state: &SharedState, call: &ToolCallParams) -> Result<(), ToolError> {
+    if !state.config.read().await.sandbox.enabled {
+        return Ok(());
+    }
+    let Ok(session) = SESSION_ROOTS.try_with(SessionRoots::clone) else {
+        return Ok(());
+    };
+    let paths = crate::consent::target_paths(&call.arguments);
+    if paths.is_empty() {
+        return Ok(());
+    }
+    check_within_roots(state, &session, &paths).await
+}
+
+/// `check` for a path a tool reads that is not one of its path arguments (an eval suite file).
+pub async fn check_path(state: &SharedState, path: &str) -> Result<(), ToolError> {
+    if !state.config.read().await.sandbox.enabled {
+        return Ok(());
+    }
+    let Ok(session) = SESSION_ROOTS.try_with(SessionRoots::clone) else {
+        return Ok(());
+    };
+    check_within_roots(state, &session, &[path.to_string()]).await
+}
+
+async fn check_within_roots(state: &SharedState, session: &SessionRoots, paths: &[String]) -> Result<(), ToolError> {
+    let mut allowed: Vec<PathBuf> = state
+        .filesystem_roots()
+        .await
+        .iter()
+        .map(|r| expand_tilde(&r.to_string_lossy()))
+        .collect();
+    allowed.extend(session.active().into_iter().map(|g| g.path));
+    // Compare with symlinks resolved on both sides: a link inside a root may point outside it,
+    // and a root may itself be reached through a link (e.g. `/tmp` on macOS).
+    let allowed: Vec<PathBuf> = allowed.iter().map(|root| resolve(root)).collect();
+    for path in paths {
+        let target = expand_tilde(path);
+        let inside = !target.components().any(|c| matches!(c, Component::ParentDir))
+            && target.is_absolute()
+            && allowed.iter().any(|root| resolve(&target).starts_with(root));
+        if !inside {
+            return Err(ToolError::outside_sandbox(path));
+        }
+    }
+    Ok(())
+}
+
+/// `path` with symlinks resolved. A path that does not exist (yet) is resolved through its
+/// nearest existing ancestor.
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = vec![];
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            return rest.iter().rev().fold(canonical, |p, name| p.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// MCP tool entrypoint (`silo_grant_temp_root`).
+pub async fn grant_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: GrantArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let Ok(session) = SESSION_ROOTS.try_with(SessionRoots::clone) else {
+        return Err("silo_grant_temp_root only works in an MCP session".into());
+    };
+    let path = expand_tilde(&args.path);
+    crate::tools::validate_safe_path(&path)?;
+    if args.revoke {
+        let revoked = session.revoke(&path);
+        return Ok(json!({ "revoked": revoked, "temp_roots": session.status() }));
+    }
+    if !path.is_absolute() || !path.is_dir() {
+        return Err(format!("{} is not an existing folder (use an absolute path)", path.display()).into());
+    }
+    if path.parent().is_none() {
+        return Err("Granting the whole filesystem is not allowed".into());
+    }
+    crate::access::locked_paths(state).await.check(&path.to_string_lossy())?;
+    let cfg = state.config.read().await.sandbox.clone();
+    let secs = args.ttl_secs.unwrap_or(cfg.grant_secs).min(cfg.max_grant_secs).max(1);
+    session.grant(path.clone(), Instant::now() + Duration::from_secs(secs));
+    tracing::info!("Granted {} to an MCP session for {secs}s", path.display());
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "expires_in_secs": secs,
+        "sandbox_enabled": cfg.enabled,
+        "temp_roots": session.status(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_follows_links_out_of_a_root() {
+        let base = std::env::temp_dir().join(format!("silo-sandbox-{}", std::process::id()));
+        let root = base.join("root");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        let root = resolve(&root);
+        assert!(resolve(&root.join("notes.md")).starts_with(&root));
+        assert!(resolve(&root.join("new/dir/file.md")).starts_with(&root));
+        let escaped = resolve(&root.join("link/secret.txt"));
+        assert!(!escaped.starts_with(&root));
+        assert!(escaped.starts_with(resolve(&outside)));
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
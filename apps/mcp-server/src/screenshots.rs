@@ -0,0 +1,173 @@
+//! Screenshot OCR watch folder (`screenshots` source, `silo_index_screenshots`).
+//!
+//! A background task checks the screenshots folder every `poll_secs` and OCRs new or changed
+//! images (`tesseract`, see `crate::extract`), so "that error dialog I screenshotted last week"
+//! is found by its text. Screenshots are indexed under their own path; the capture time is the
+//! file's creation time (catalog `created_epoch_secs`, used by `silo_timeline`).
+
+use crate::config::{FileSystemSourceConfig, ScreenshotsSourceConfig};
+use crate::errors::ToolError;
+use crate::extract::IMAGE_EXTENSIONS;
+use crate::filesystem::modified_epoch_secs;
+use crate::ingest::process_file;
+use crate::state::{expand_tilde, SharedState};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Larger images are not screenshots.
+const MAX_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
+const MIN_POLL_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScreenshotPass {
+    pub folder: String,
+    /// Screenshots found in the folder.
+    pub screenshots: usize,
+    /// OCR'd and stored in this pass.
+    pub indexed: usize,
+    pub unchanged: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Start the background watcher (returns at once when no screenshots source is configured; the
+/// task stops when the source is removed from the config).
+pub fn spawn_watcher(state: &SharedState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        while let Some(cfg) = state.screenshots_config().await {
+            if state.db.is_enabled() {
+                let res = index_screenshots(&state).await;
+                match &res {
+                    Ok(pass) if pass.indexed > 0 => {
+                        tracing::info!("Indexed {} new screenshots from {}", pass.indexed, pass.folder)
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Screenshot indexing failed: {e}"),
+                }
+                let outcome = res.as_ref().map(|_| ()).map_err(|e| e.message.as_str());
+                crate::notify::source_pass(&state, "screenshots", outcome).await;
+            }
+            tokio::time::sleep(Duration::from_secs(cfg.poll_secs.max(MIN_POLL_SECS))).await;
+        }
+    });
+}
+
+/// MCP tool entrypoint (`silo_index_screenshots`).
+pub async fn index_screenshots_tool(state: &SharedState, _args: Value) -> Result<Value, ToolError> {
+    let pass = index_screenshots(state).await?;
+    serde_json::to_value(pass).map_err(|e| format!("failed to serialize screenshot pass: {e}").into())
+}
+
+/// OCR and index screenshots that are new or changed since the last pass.
+pub async fn index_screenshots(state: &SharedState) -> Result<ScreenshotPass, ToolError> {
+    let Some(cfg) = state.screenshots_config().await else {
+        return Err("Screenshots source not configured. Add {\"type\": \"screenshots\"} to `sources` in the config".into());
+    };
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    let _pass = state.screenshot_pass.lock().await;
+
+    let folder = screenshot_folder(&cfg).await;
+    let fs_cfg = state.filesystem_config().await.unwrap_or_default();
+    let mut pass = ScreenshotPass {
+        folder: folder.to_string_lossy().to_string(),
+        ..ScreenshotPass::default()
+    };
+
+    for (path, meta) in list_screenshots(&folder, &cfg).await? {
+        pass.screenshots += 1;
+        let path_str = path.to_string_lossy().to_string();
+        let unchanged = state.catalog.read().await.get(&path_str).is_some_and(|e| {
+            e.size_bytes == Some(meta.len() as i64) && e.modified_epoch_secs == modified_epoch_secs(&meta)
+        });
+        if unchanged {
+            pass.unchanged += 1;
+            continue;
+        }
+        match ocr_and_store(state, &fs_cfg, &path_str).await {
+            Ok(()) => pass.indexed += 1,
+            // Without tesseract every image fails the same way.
+            Err(e) if e.starts_with("Failed to run tesseract") => return Err(e.into()),
+            Err(e) => pass.errors.push(format!("{path_str}: {e}")),
+        }
+    }
+
+    if pass.indexed > 0 {
+        state.index_changed();
+        state.save_catalog().await;
+    }
+    Ok(pass)
+}
+
+async fn ocr_and_store(state: &SharedState, fs_cfg: &FileSystemSourceConfig, path: &str) -> Result<(), String> {
+    let stats = process_file(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        None,
+        path,
+        fs_cfg.max_text_bytes,
+        &fs_cfg.chunking(),
+    )
+    .await?;
+    // Recorded even without text, so blank screenshots are not OCR'd again every pass.
+    state.catalog.write().await.record_ingest(&stats);
+    Ok(())
+}
+
+/// Screenshot images directly in `folder`, oldest first.
+async fn list_screenshots(
+    folder: &Path,
+    cfg: &ScreenshotsSourceConfig,
+) -> Result<Vec<(PathBuf, std::fs::Metadata)>, String> {
+    let mut rd = tokio::fs::read_dir(folder)
+        .await
+        .map_err(|e| format!("Cannot read screenshots folder {}: {e}", folder.display()))?;
+    let prefixes: Vec<String> = cfg.name_prefixes.iter().map(|p| p.to_lowercase()).collect();
+    let mut out = vec![];
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        let is_image = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if !is_image || name.starts_with('.') {
+            continue;
+        }
+        if !prefixes.is_empty() && !prefixes.iter().any(|p| name.starts_with(p.as_str())) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata().await else {
+            continue;
+        };
+        if meta.is_file() && meta.len() <= MAX_IMAGE_BYTES {
+            out.push((path, meta));
+        }
+    }
+    out.sort_by_key(|(_, meta)| modified_epoch_secs(meta));
+    Ok(out)
+}
+
+async fn screenshot_folder(cfg: &ScreenshotsSourceConfig) -> PathBuf {
+    if let Some(folder) = &cfg.folder {
+        return expand_tilde(&folder.to_string_lossy());
+    }
+    if cfg!(target_os = "macos") {
+        let configured = tokio::process::Command::new("defaults")
+            .args(["read", "com.apple.screencapture", "location"])
+            .output()
+            .await
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|s| !s.is_empty());
+        return expand_tilde(configured.as_deref().unwrap_or("~/Desktop"));
+    }
+    expand_tilde("~/Pictures/Screenshots")
+}
@@ -0,0 +1,235 @@
+//! Ad-hoc semantic search over files that need not be indexed (`silo_search_paths`).
+//!
+//! The given files, folders and globs are expanded, extracted, chunked and embedded into a
+//! throwaway in-memory store for this one call, then searched. Nothing touches the index or the
+//! catalog. Sizes are bounded (file count, per-file size, total extracted text) so a broad glob
+//! cannot turn one call into a full index run.
+
+use crate::chunk::ChunkRules;
+use crate::config::CompiledFileSystemPolicy;
+use crate::database::{Database, DatabaseHandle, SearchOptions, SEARCH_METRIC};
+use crate::errors::ToolError;
+use crate::filesystem::walk_files;
+use crate::state::{expand_tilde, SharedState};
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_MAX_FILES: usize = 100;
+pub(crate) const MAX_FILES: usize = 500;
+/// Extracted text embedded per call, over all files.
+const MAX_TOTAL_TEXT_BYTES: u64 = 32 * 1024 * 1024;
+/// Files looked at while expanding one folder or glob.
+const MAX_WALK_FILES: u64 = 100_000;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchPathsArgs {
+    /// Files, folders (searched recursively) or globs (`~/notes/**/*.md`).
+    pub paths: Vec<String>,
+    pub query: String,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Files to read at most (default 100, max 500).
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedPath {
+    pub path: String,
+    pub reason: String,
+}
+
+/// MCP tool entrypoint (`silo_search_paths`).
+pub async fn search_paths_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: SearchPathsArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    search_paths(state, args).await
+}
+
+pub async fn search_paths(state: &SharedState, args: SearchPathsArgs) -> Result<Value, ToolError> {
+    if args.query.trim().is_empty() {
+        return Err("`query` must not be empty".into());
+    }
+    if args.paths.is_empty() {
+        return Err("`paths` must not be empty".into());
+    }
+    let policy = state
+        .filesystem_policy()
+        .await
+        .ok_or_else(ToolError::no_filesystem_policy)?;
+    let fs_cfg = state
+        .filesystem_config()
+        .await
+        .ok_or_else(|| "No filesystem source configured".to_string())?;
+    let max_files = args.max_files.unwrap_or(DEFAULT_MAX_FILES).clamp(1, MAX_FILES);
+
+    let mut skipped = vec![];
+    let (files, truncated) = expand(&args.paths, &policy, max_files, &mut skipped).await;
+    let db = Arc::new(Database::memory());
+    let tally = ingest_files(state, &db, &files, &policy, &fs_cfg.chunking(), &mut skipped).await;
+
+    let qvec = state
+        .embed_query_cached(&args.query)
+        .await
+        .map_err(ToolError::embedding_failed)?;
+    let opts = SearchOptions {
+        top_k: args.top_k.unwrap_or(10).clamp(1, 50),
+        min_score: args.min_score,
+        filter: None,
+    };
+    let hits = db
+        .search_chunks_by_vector(&qvec, &opts)
+        .await
+        .map_err(|e| format!("Search failed: {e}"))?;
+
+    Ok(json!({
+        "metric": SEARCH_METRIC,
+        "hits": hits,
+        "files_searched": tally.files,
+        "chunks": tally.chunks,
+        "truncated": truncated || tally.budget_hit,
+        "skipped": skipped
+    }))
+}
+
+pub(crate) struct IngestTally {
+    pub files: usize,
+    pub chunks: usize,
+    /// Stopped at `MAX_TOTAL_TEXT_BYTES`.
+    pub budget_hit: bool,
+}
+
+/// Extract, chunk and embed `files` into the throwaway store `db`. Locked and oversized files
+/// are skipped, and the call stops once `MAX_TOTAL_TEXT_BYTES` of text went in.
+pub(crate) async fn ingest_files(
+    state: &SharedState,
+    db: &DatabaseHandle,
+    files: &[PathBuf],
+    policy: &CompiledFileSystemPolicy,
+    chunking: &ChunkRules,
+    skipped: &mut Vec<SkippedPath>,
+) -> IngestTally {
+    let locked = crate::access::locked_paths(state).await;
+    let mut tally = IngestTally {
+        files: 0,
+        chunks: 0,
+        budget_hit: false,
+    };
+    let mut text_bytes = 0u64;
+    for path in files {
+        let path_str = path.to_string_lossy().to_string();
+        if let Err(e) = locked.check(&path_str) {
+            skipped.push(SkippedPath { path: path_str, reason: e.message });
+            continue;
+        }
+        let size = tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+        let max_size = policy.max_file_size_for(path);
+        if size > max_size {
+            skipped.push(SkippedPath {
+                path: path_str,
+                reason: format!("larger than max_file_size_bytes ({max_size})"),
+            });
+            continue;
+        }
+        if text_bytes >= MAX_TOTAL_TEXT_BYTES {
+            tally.budget_hit = true;
+            break;
+        }
+        let max_text_bytes = policy.max_text_bytes_for(path).min(MAX_TOTAL_TEXT_BYTES - text_bytes);
+        let res = crate::ingest::process_file(db, &state.embedder, None, None, &path_str, max_text_bytes, chunking).await;
+        match res {
+            Ok(stats) => {
+                tally.files += 1;
+                tally.chunks += stats.chunks;
+                text_bytes += stats.extracted_chars as u64;
+            }
+            Err(e) => skipped.push(SkippedPath { path: path_str, reason: e }),
+        }
+    }
+    tally
+}
+
+/// Files named by `patterns`, deduplicated and sorted, plus whether `max_files` cut the list.
+/// Folders and globs only yield files with an allowed extension, outside the exclude globs.
+pub(crate) async fn expand(
+    patterns: &[String],
+    policy: &CompiledFileSystemPolicy,
+    max_files: usize,
+    skipped: &mut Vec<SkippedPath>,
+) -> (Vec<PathBuf>, bool) {
+    let mut files = BTreeSet::new();
+    let mut truncated = false;
+    for raw in patterns {
+        let expanded = expand_tilde(raw);
+        if let Err(e) = crate::tools::validate_safe_path(&expanded) {
+            skipped.push(SkippedPath { path: raw.clone(), reason: e.message });
+            continue;
+        }
+        if files.len() >= max_files {
+            truncated = true;
+            break;
+        }
+        let (base, matcher) = match glob_base(&expanded) {
+            Some(base) if base.as_os_str().is_empty() => {
+                skipped.push(SkippedPath {
+                    path: raw.clone(),
+                    reason: "globs need an absolute or ~/ path".to_string(),
+                });
+                continue;
+            }
+            Some(base) => {
+                let glob = GlobBuilder::new(&expanded.to_string_lossy())
+                    .literal_separator(true)
+                    .build();
+                match glob {
+                    Ok(g) => (base, Some(g.compile_matcher())),
+                    Err(e) => {
+                        skipped.push(SkippedPath { path: raw.clone(), reason: format!("invalid glob: {e}") });
+                        continue;
+                    }
+                }
+            }
+            None if expanded.is_file() => {
+                files.insert(expanded);
+                continue;
+            }
+            None if expanded.is_dir() => (expanded, None),
+            None => {
+                skipped.push(SkippedPath { path: raw.clone(), reason: "not found".to_string() });
+                continue;
+            }
+        };
+        let mut found = vec![];
+        let walk = walk_files(&[base], policy, false, MAX_WALK_FILES, |_, path, _| {
+            let wanted = matcher.as_ref().is_none_or(|m| m.is_match(path)) && policy.extension_allowed(path);
+            if wanted {
+                found.push(path.to_path_buf());
+            }
+        })
+        .await;
+        truncated |= walk.truncated;
+        for path in found {
+            if files.len() >= max_files {
+                truncated = true;
+                break;
+            }
+            files.insert(path);
+        }
+    }
+    (files.into_iter().collect(), truncated)
+}
+
+/// For a glob, the longest leading directory without glob syntax (where the walk starts);
+/// `None` for a plain path.
+fn glob_base(path: &Path) -> Option<PathBuf> {
+    let is_glob = |c: &Component| c.as_os_str().to_string_lossy().contains(['*', '?', '[', '{']);
+    if !path.components().any(|c| is_glob(&c)) {
+        return None;
+    }
+    Some(path.components().take_while(|c| !is_glob(c)).collect())
+}
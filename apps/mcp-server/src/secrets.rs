@@ -0,0 +1,166 @@
+//! Named secrets for connectors (IMAP passwords, API tokens of opt-in cloud sources), set with
+//! `silo_secret_set` and kept in the OS keychain (`crate::keychain`), never in `config.json`.
+//!
+//! A connector's config names the secret it needs (e.g. `"password_secret": "imap.work"`) and
+//! reads it with `resolve` when it runs. Only the names, descriptions and update times are kept in
+//! `secrets.json` under `data_dir`, for `silo_secret_list`; values never leave the keychain except
+//! to the connector using them, and the traffic log never records them (`crate::traffic`).
+//! Document passwords (`crate::passwords`) live in the same keychain under their own entries.
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+const FILE_NAME: &str = "secrets.json";
+const FILE_VERSION: u32 = 1;
+const MAX_NAME_CHARS: usize = 64;
+
+/// Serializes read-modify-write of `secrets.json`.
+static FILE_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub updated_epoch_secs: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SecretsFile {
+    version: u32,
+    secrets: Vec<SecretInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SetSecretArgs {
+    name: String,
+    secret: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteSecretArgs {
+    name: String,
+}
+
+/// The value of secret `name`, for a connector; an error names the secret when it is not set.
+pub async fn resolve(name: &str) -> Result<String, String> {
+    validate_name(name)?;
+    crate::keychain::get(&account(name))
+        .await?
+        .ok_or_else(|| format!("Secret `{name}` is not set (store it with silo_secret_set)"))
+}
+
+/// The secrets set so far (never their values).
+pub async fn list(data_dir: &Path) -> Vec<SecretInfo> {
+    let file = data_dir.join(FILE_NAME);
+    match tokio::fs::read_to_string(&file).await {
+        Ok(s) => match serde_json::from_str::<SecretsFile>(&s) {
+            Ok(f) if f.version == FILE_VERSION => f.secrets,
+            Ok(_) => vec![],
+            Err(e) => {
+                tracing::warn!("Ignoring invalid secret list {}: {e}", file.display());
+                vec![]
+            }
+        },
+        Err(_) => vec![],
+    }
+}
+
+async fn save(data_dir: &Path, secrets: Vec<SecretInfo>) -> Result<(), String> {
+    tokio::fs::create_dir_all(data_dir)
+        .await
+        .map_err(|e| format!("Failed to create data dir {}: {e}", data_dir.display()))?;
+    let file = data_dir.join(FILE_NAME);
+    let s = serde_json::to_string_pretty(&SecretsFile {
+        version: FILE_VERSION,
+        secrets,
+    })
+    .map_err(|e| format!("Failed to serialize secret list: {e}"))?;
+    tokio::fs::write(&file, s)
+        .await
+        .map_err(|e| format!("Failed to write secret list {}: {e}", file.display()))
+}
+
+fn account(name: &str) -> String {
+    format!("secret:{name}")
+}
+
+/// Names are letters, digits, `.`, `_` and `-` (`imap.work`, `github-token`).
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_CHARS
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid secret name `{name}`: use up to {MAX_NAME_CHARS} letters, digits, '.', '_' or '-'"
+        ))
+    }
+}
+
+/// MCP tool entrypoint (`silo_secret_set`).
+pub async fn set_secret_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: SetSecretArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    validate_name(&args.name)?;
+    if args.secret.is_empty() {
+        return Err("secret must not be empty".into());
+    }
+    crate::keychain::set(&account(&args.name), &args.secret).await?;
+
+    let _lock = FILE_LOCK.lock().await;
+    let mut secrets = list(&state.data_dir).await;
+    let description = args
+        .description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+    let replaced = match secrets.iter_mut().find(|s| s.name == args.name) {
+        Some(existing) => {
+            existing.updated_epoch_secs = now_epoch_secs();
+            if description.is_some() {
+                existing.description = description;
+            }
+            true
+        }
+        None => {
+            secrets.push(SecretInfo {
+                name: args.name.clone(),
+                description,
+                updated_epoch_secs: now_epoch_secs(),
+            });
+            secrets.sort_by(|a, b| a.name.cmp(&b.name));
+            false
+        }
+    };
+    save(&state.data_dir, secrets).await?;
+    Ok(json!({ "name": args.name, "stored": true, "replaced": replaced }))
+}
+
+/// MCP tool entrypoint (`silo_secret_list`).
+pub async fn list_secrets_tool(state: &SharedState) -> Result<Value, ToolError> {
+    Ok(json!({ "secrets": list(&state.data_dir).await }))
+}
+
+/// MCP tool entrypoint (`silo_secret_delete`).
+pub async fn delete_secret_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: DeleteSecretArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    validate_name(&args.name)?;
+    let in_keychain = crate::keychain::delete(&account(&args.name)).await?;
+
+    let _lock = FILE_LOCK.lock().await;
+    let mut secrets = list(&state.data_dir).await;
+    let before = secrets.len();
+    secrets.retain(|s| s.name != args.name);
+    let listed = secrets.len() < before;
+    if listed {
+        save(&state.data_dir, secrets).await?;
+    }
+    Ok(json!({ "name": args.name, "deleted": in_keychain || listed }))
+}
@@ -1,9 +1,13 @@
+use crate::notify::{Notification, NotifyLevel};
+use crate::ratelimit::{RateLimited, SessionLimiter};
+use crate::sandbox::{SessionRoots, SESSION_ROOTS};
 use crate::state::SharedState;
-use crate::tools::{self, ToolCallParams, ToolResult};
+use crate::tools::{self, ToolCallParams, ToolResult, SESSION_READ_ONLY};
+use crate::traffic::Direction;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::borrow::Cow;
-use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{self, AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 
 /// Runs a JSON-RPC 2.0 server over stdio, compatible with MCP-style calls.
 ///
@@ -11,27 +15,62 @@ use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
 /// - `initialize`: MCP handshake
 /// - `tools/list`: returns tool definitions
 /// - `tools/call`: executes a tool call
+/// - `logging/setLevel`: minimum level of the `notifications/message` sent to the session
+///   (index runs finished, sources failing; see `crate::notify`)
+/// - `silo/readOnly`: make the session read-only (sent by a read-only proxy, see `crate::daemon`)
 ///
 /// We also support `mcp.list_tools` / `mcp.call_tool` as aliases for convenience.
 pub async fn run_stdio_server(state: SharedState) -> Result<(), ServerFatalError> {
-    let stdin = io::stdin();
-    let stdout = io::stdout();
+    serve(BufReader::new(io::stdin()), io::stdout(), &state).await
+}
 
-    let mut reader = BufReader::new(stdin).lines();
-    let mut writer = io::BufWriter::new(stdout);
+/// The JSON-RPC loop over any line-delimited transport (stdio, or a daemon socket connection).
+/// One call is one client session (for rate limits).
+pub async fn serve<R, W>(reader: R, writer: W, state: &SharedState) -> Result<(), ServerFatalError>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = reader.lines();
+    let mut writer = io::BufWriter::new(writer);
+    let mut session = Session::default();
+    let mut notifications = state.notifier.subscribe();
+    let traffic = state.traffic.as_ref().map(|log| (log, log.new_session()));
 
-    while let Some(line) = reader.next_line().await? {
+    loop {
+        let line = tokio::select! {
+            line = reader.next_line() => match line? {
+                Some(line) => line,
+                None => break,
+            },
+            // A session that is behind skips the notifications it missed.
+            n = notifications.recv() => {
+                match n {
+                    Ok(n) if session.wants(&n) => {
+                        let sent = write_json(&mut writer, &log_message(&n)).await?;
+                        if let Some((log, session)) = traffic {
+                            log.record(session, Direction::Out, &sent);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+        };
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
+        if let Some((log, session)) = traffic {
+            log.record(session, Direction::In, line);
+        }
 
         let parsed: Result<JsonRpcRequest, _> = serde_json::from_str(line);
         let req = match parsed {
             Ok(r) => r,
             Err(e) => {
                 // Can't extract an id reliably if JSON is invalid -> treat as a notification.
-                write_json(
+                let sent = write_json(
                     &mut writer,
                     &JsonRpcResponse::<Value>::error(
                         None,
@@ -39,28 +78,71 @@ pub async fn run_stdio_server(state: SharedState) -> Result<(), ServerFatalError
                     ),
                 )
                 .await?;
+                if let Some((log, session)) = traffic {
+                    log.record(session, Direction::Out, &sent);
+                }
                 continue;
             }
         };
 
         // Notifications (no id) are allowed; we do not respond per JSON-RPC.
         let Some(id) = req.id.clone() else {
-            let _ = handle_request(req, &state).await;
+            let _ = handle_request(req, state, &mut session).await;
             continue;
         };
 
-        let resp = match handle_request(req, &state).await {
+        let resp = match handle_request(req, state, &mut session).await {
             Ok(result) => JsonRpcResponse::result(Some(id), result),
             Err(err) => JsonRpcResponse::<Value>::error(Some(id), err),
         };
 
-        write_json(&mut writer, &resp).await?;
+        let sent = write_json(&mut writer, &resp).await?;
+        if let Some((log, session)) = traffic {
+            log.record(session, Direction::Out, &sent);
+        }
     }
 
     Ok(())
 }
 
-async fn handle_request(req: JsonRpcRequest, state: &SharedState) -> Result<Value, JsonRpcError> {
+/// Per-connection state.
+#[derive(Default)]
+struct Session {
+    limiter: SessionLimiter,
+    /// Notifications are sent once the client has initialized.
+    initialized: bool,
+    /// Set by `logging/setLevel`; `None`: every notification.
+    min_level: Option<NotifyLevel>,
+    /// Folders granted with `silo_grant_temp_root` (`crate::sandbox`).
+    temp_roots: SessionRoots,
+    /// Set by `silo/readOnly`: write tools are hidden and refused for this session only.
+    read_only: bool,
+}
+
+impl Session {
+    fn wants(&self, n: &Notification) -> bool {
+        self.initialized && self.min_level.is_none_or(|min| n.level >= min)
+    }
+}
+
+/// A notification as an MCP log message.
+fn log_message(n: &Notification) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": {
+            "level": n.level,
+            "logger": "silo",
+            "data": n,
+        }
+    })
+}
+
+async fn handle_request(
+    req: JsonRpcRequest,
+    state: &SharedState,
+    session: &mut Session,
+) -> Result<Value, JsonRpcError> {
     if req.jsonrpc != "2.0" {
         return Err(JsonRpcError::invalid_request(
             "Only JSON-RPC 2.0 is supported".to_string(),
@@ -84,10 +166,12 @@ async fn handle_request(req: JsonRpcRequest, state: &SharedState) -> Result<Valu
                 init.protocol_version
             };
 
+            session.initialized = true;
             Ok(json!({
                 "protocolVersion": protocol_version,
                 "capabilities": {
-                    "tools": { "listChanged": false }
+                    "tools": { "listChanged": false },
+                    "logging": {}
                 },
                 "serverInfo": {
                     "name": "silo-mcp-server",
@@ -95,8 +179,25 @@ async fn handle_request(req: JsonRpcRequest, state: &SharedState) -> Result<Valu
                 }
             }))
         }
+        "logging/setLevel" => {
+            let level = req
+                .params
+                .as_ref()
+                .and_then(|p| p.get("level"))
+                .and_then(Value::as_str)
+                .and_then(NotifyLevel::parse)
+                .ok_or_else(|| JsonRpcError::invalid_params("Missing or unknown `level`".to_string()))?;
+            session.min_level = Some(level);
+            Ok(json!({}))
+        }
+        crate::daemon::READ_ONLY_METHOD => {
+            session.read_only = true;
+            Ok(json!({}))
+        }
         "tools/list" | "mcp.list_tools" => {
-            let tools = tools::tool_definitions();
+            let tools = SESSION_READ_ONLY
+                .scope(session.read_only, tools::localized_tool_definitions(state))
+                .await;
             Ok(json!({ "tools": tools }))
         }
         "tools/call" | "mcp.call_tool" => {
@@ -108,8 +209,29 @@ async fn handle_request(req: JsonRpcRequest, state: &SharedState) -> Result<Valu
                 JsonRpcError::invalid_params(format!("Invalid mcp.call_tool params: {e}"))
             })?;
 
-            let ToolResult { content, is_error } = tools::call_tool(state, call).await;
-            Ok(json!({ "content": content, "isError": is_error }))
+            let limits = state.config.read().await.rate_limits.clone();
+            session
+                .limiter
+                .check(&call.name, &limits)
+                .map_err(JsonRpcError::rate_limited)?;
+
+            let call = SESSION_READ_ONLY.scope(session.read_only, tools::call_tool(state, call));
+            let ToolResult { content, is_error, error } = SESSION_ROOTS.scope(session.temp_roots.clone(), call).await;
+            if !is_error {
+                return Ok(json!({ "content": content, "isError": false }));
+            }
+            // Errors in the configured locale; the code stays the same in every locale.
+            if let Some(error) = error
+                && let Some(code) = error.code
+            {
+                let text = state.messages().await.localize_error(&error);
+                return Ok(json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": true,
+                    "_meta": { "silo/errorCode": code.as_str() },
+                }));
+            }
+            Ok(json!({ "content": content, "isError": true }))
         }
         other => Err(JsonRpcError::method_not_found(format!(
             "Unknown method: {other}"
@@ -117,15 +239,16 @@ async fn handle_request(req: JsonRpcRequest, state: &SharedState) -> Result<Valu
     }
 }
 
+/// Write one message line; returns it (for the traffic log).
 async fn write_json<W: AsyncWriteExt + Unpin, T: Serialize>(
     writer: &mut W,
     value: &T,
-) -> Result<(), ServerFatalError> {
+) -> Result<String, ServerFatalError> {
     let s = serde_json::to_string(value)?;
     writer.write_all(s.as_bytes()).await?;
     writer.write_all(b"\n").await?;
     writer.flush().await?;
-    Ok(())
+    Ok(s)
 }
 
 #[derive(Debug, Deserialize)]
@@ -221,11 +344,23 @@ impl JsonRpcError {
             data: Some(json!({ "detail": message })),
         }
     }
+
+    /// Implementation-defined server error (429, like HTTP "Too Many Requests").
+    fn rate_limited(limited: RateLimited) -> Self {
+        Self {
+            code: -32029,
+            message: Cow::Owned(format!(
+                "Rate limited: {} allows {} calls per minute; retry in {} ms",
+                limited.tool, limited.limit_per_minute, limited.retry_after_ms
+            )),
+            data: serde_json::to_value(&limited).ok(),
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum ServerFatalError {
-    #[error("stdio I/O error: {0}")]
+    #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
     #[error("json serialization error: {0}")]
     Json(#[from] serde_json::Error),
@@ -0,0 +1,216 @@
+//! Session index (`silo_session_index`): files ingested into a RAM-only store for this server
+//! process, so a client can ask about a file without adding it to the permanent index.
+//!
+//! Nothing is written to disk: chunks live in an in-memory `VectorStore` (`crate::memstore`),
+//! the catalog is not touched, and everything is dropped on `clear` or when the process exits.
+
+use crate::catalog::now_epoch_secs;
+use crate::database::{Database, DatabaseHandle, SearchOptions, SEARCH_METRIC};
+use crate::errors::ToolError;
+use crate::ingest::IngestStats;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Files held at once; a session index is for a handful of documents, not a second corpus.
+const MAX_FILES: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAction {
+    Add,
+    #[default]
+    Search,
+    List,
+    Remove,
+    Clear,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionIndexArgs {
+    #[serde(default)]
+    pub action: SessionAction,
+    /// Files to add or remove.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionFile {
+    pub path: String,
+    pub extracted_kind: String,
+    pub chunks: usize,
+    pub added_epoch_secs: i64,
+}
+
+/// The per-process session store (see the module docs).
+pub struct SessionIndex {
+    db: DatabaseHandle,
+    files: tokio::sync::Mutex<BTreeMap<String, SessionFile>>,
+}
+
+impl Default for SessionIndex {
+    fn default() -> Self {
+        Self {
+            db: std::sync::Arc::new(Database::memory()),
+            files: tokio::sync::Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+/// MCP tool entrypoint (`silo_session_index`).
+pub async fn session_index_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: SessionIndexArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    match args.action {
+        SessionAction::Add => Ok(add(state, &args.paths).await?),
+        SessionAction::Search => {
+            let query = args
+                .query
+                .filter(|q| !q.trim().is_empty())
+                .ok_or_else(|| "`query` is required for action \"search\"".to_string())?;
+            search(state, &query, args.top_k, args.min_score).await
+        }
+        SessionAction::List => Ok(list(state).await),
+        SessionAction::Remove => Ok(remove(state, &args.paths).await?),
+        SessionAction::Clear => Ok(clear(state).await?),
+    }
+}
+
+/// Extract, chunk and embed `paths` into the session store (re-adding a file replaces it).
+pub async fn add(state: &SharedState, paths: &[String]) -> Result<Value, String> {
+    if paths.is_empty() {
+        return Err("`paths` is required for action \"add\"".to_string());
+    }
+    let fs_cfg = state
+        .filesystem_config()
+        .await
+        .ok_or_else(|| "No filesystem source configured".to_string())?;
+    let max_text_bytes = state
+        .fs_policy
+        .read()
+        .await
+        .as_ref()
+        .map(|p| p.max_text_bytes)
+        .unwrap_or(2 * 1024 * 1024);
+    let locked = crate::access::locked_paths(state).await;
+    let session = &state.session_index;
+
+    let mut added = vec![];
+    let mut errors = vec![];
+    for raw in paths {
+        let path = expand_tilde(raw).to_string_lossy().to_string();
+        let checked = crate::tools::validate_safe_path(std::path::Path::new(&path)).and_then(|()| locked.check(&path));
+        if let Err(e) = checked {
+            errors.push(json!({ "path": path, "error": e.message }));
+            continue;
+        }
+        let full = {
+            let files = session.files.lock().await;
+            files.len() >= MAX_FILES && !files.contains_key(&path)
+        };
+        if full {
+            errors.push(json!({ "path": path, "error": format!("session index is full ({MAX_FILES} files)") }));
+            continue;
+        }
+        let res = crate::ingest::process_file(
+            &session.db,
+            &state.embedder,
+            None,
+            None,
+            &path,
+            max_text_bytes,
+            &fs_cfg.chunking(),
+        )
+        .await;
+        match res {
+            Ok(stats) => added.push(record(session, stats).await),
+            Err(e) => errors.push(json!({ "path": path, "error": e })),
+        }
+    }
+    Ok(json!({
+        "added": added,
+        "errors": errors,
+        "files": session.files.lock().await.len()
+    }))
+}
+
+async fn record(session: &SessionIndex, stats: IngestStats) -> SessionFile {
+    let file = SessionFile {
+        path: stats.path.clone(),
+        extracted_kind: stats.extracted_kind,
+        chunks: stats.chunks,
+        added_epoch_secs: now_epoch_secs(),
+    };
+    session.files.lock().await.insert(stats.path, file.clone());
+    file
+}
+
+/// Semantic search over the session files only (same hit format as `silo_search`).
+pub async fn search(
+    state: &SharedState,
+    query: &str,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<Value, ToolError> {
+    let session = &state.session_index;
+    if session.files.lock().await.is_empty() {
+        return Ok(json!({ "metric": SEARCH_METRIC, "hits": [], "files": 0 }));
+    }
+    let locked = crate::access::locked_paths(state).await;
+    let qvec = state
+        .embed_query_cached(query)
+        .await
+        .map_err(ToolError::embedding_failed)?;
+    let opts = SearchOptions {
+        top_k: top_k.unwrap_or(10).clamp(1, 50),
+        min_score,
+        filter: locked.search_filter(),
+    };
+    let hits = session
+        .db
+        .search_chunks_by_vector(&qvec, &opts)
+        .await
+        .map_err(|e| format!("Session search failed: {e}"))?;
+    Ok(json!({
+        "metric": SEARCH_METRIC,
+        "hits": hits,
+        "files": session.files.lock().await.len()
+    }))
+}
+
+pub async fn list(state: &SharedState) -> Value {
+    let files = state.session_index.files.lock().await;
+    json!({
+        "files": files.values().collect::<Vec<_>>(),
+        "max_files": MAX_FILES
+    })
+}
+
+pub async fn remove(state: &SharedState, paths: &[String]) -> Result<Value, String> {
+    let session = &state.session_index;
+    let mut removed = vec![];
+    for raw in paths {
+        let path = expand_tilde(raw).to_string_lossy().to_string();
+        if session.files.lock().await.remove(&path).is_some() {
+            session.db.delete_file(&path).await.map_err(|e| e.to_string())?;
+            removed.push(path);
+        }
+    }
+    Ok(json!({ "removed": removed, "files": session.files.lock().await.len() }))
+}
+
+pub async fn clear(state: &SharedState) -> Result<Value, String> {
+    let session = &state.session_index;
+    let paths: Vec<String> = std::mem::take(&mut *session.files.lock().await).into_keys().collect();
+    for path in &paths {
+        session.db.delete_file(path).await.map_err(|e| e.to_string())?;
+    }
+    Ok(json!({ "removed": paths.len() }))
+}
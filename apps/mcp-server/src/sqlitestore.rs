@@ -0,0 +1,419 @@
+//! SQLite `VectorStore` (`--features sqlite`, `storage.backend = "sqlite"`).
+//!
+//! One file, `silo.sqlite3` in the data dir, with the same chunk and entity columns as the LanceDB
+//! tables. Vectors are little-endian f32 blobs scored with sqlite-vec's `vec_distance_cosine`,
+//! so a search is an exact scan over the rows that pass the filter (no ANN index). Filters are
+//! the same `Predicate`s, rendered in SQLite's dialect.
+//!
+//! rusqlite is synchronous: every call runs on the blocking pool behind one connection.
+
+use crate::config::VectorType;
+use crate::database::{
+    cosine_similarity_from_distance, preview, ChunkRow, ChunkSource, CompactionStats, DbError, EntityRow, SearchHit,
+    SearchOptions,
+};
+use crate::predicate::Predicate;
+use crate::store::{StoreBackend, StoreStats, VectorStore};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex, Once};
+
+pub const DB_FILE: &str = "silo.sqlite3";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS chunks (
+    id TEXT PRIMARY KEY,
+    path TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    start_token INTEGER NOT NULL,
+    end_token INTEGER NOT NULL,
+    file_mtime_epoch_secs INTEGER,
+    file_size_bytes INTEGER,
+    file_hash TEXT,
+    page INTEGER,
+    tags TEXT,
+    source TEXT,
+    time_start_epoch_secs INTEGER,
+    time_end_epoch_secs INTEGER,
+    content TEXT NOT NULL,
+    embedding BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS chunks_path ON chunks(path);
+CREATE TABLE IF NOT EXISTS entities (
+    chunk_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    key TEXT NOT NULL,
+    kind TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS entities_path ON entities(path);
+CREATE INDEX IF NOT EXISTS entities_key ON entities(key);
+";
+
+const CHUNK_COLUMNS: &str = "id, path, chunk_index, start_token, end_token, file_mtime_epoch_secs, \
+                             file_size_bytes, file_hash, page, tags, content, embedding, source, \
+                             time_start_epoch_secs, time_end_epoch_secs";
+
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+    /// Held for the life of the store (see `crate::datalock`).
+    _lock: crate::datalock::DataDirLock,
+}
+
+impl SqliteStore {
+    pub async fn open(data_dir: &Path, vector_type: VectorType) -> Result<Self, DbError> {
+        if vector_type != VectorType::F32 {
+            tracing::warn!("storage.vector_type is ignored by the SQLite backend (vectors are stored as f32)");
+        }
+        tokio::fs::create_dir_all(data_dir).await?;
+        let lock = crate::datalock::acquire(data_dir).map_err(DbError::Locked)?;
+        let file = data_dir.join(DB_FILE);
+        let conn = tokio::task::spawn_blocking(move || -> rusqlite::Result<Connection> {
+            register_sqlite_vec();
+            let conn = Connection::open(&file)?;
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            conn.pragma_update(None, "synchronous", "NORMAL")?;
+            conn.execute_batch(SCHEMA)?;
+            add_source_column(&conn)?;
+            add_time_columns(&conn)?;
+            Ok(conn)
+        })
+        .await
+        .map_err(|e| DbError::Io(std::io::Error::other(e)))??;
+        Ok(SqliteStore {
+            conn: Arc::new(Mutex::new(conn)),
+            _lock: lock,
+        })
+    }
+
+    /// Run `f` on the connection on the blocking pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, DbError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| DbError::Io(std::io::Error::other(e)))?
+        .map_err(DbError::from)
+    }
+}
+
+/// Load sqlite-vec into every connection opened afterwards (once per process).
+fn register_sqlite_vec() {
+    type EntryPoint = unsafe extern "C" fn(
+        *mut rusqlite::ffi::sqlite3,
+        *mut *const std::os::raw::c_char,
+        *const rusqlite::ffi::sqlite3_api_routines,
+    ) -> std::os::raw::c_int;
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        // SAFETY: `sqlite3_vec_init` is the extension's C entry point, which has exactly the
+        // signature SQLite calls auto-extensions with; the crate declares it without arguments.
+        unsafe {
+            let init = std::mem::transmute::<*const (), EntryPoint>(sqlite_vec::sqlite3_vec_init as *const ());
+            rusqlite::ffi::sqlite3_auto_extension(Some(init));
+        }
+    });
+}
+
+/// Databases created before the `source` column get it, filled in from the paths.
+fn add_source_column(conn: &Connection) -> rusqlite::Result<()> {
+    let has_source = conn
+        .prepare("SELECT 1 FROM pragma_table_info('chunks') WHERE name = 'source'")?
+        .exists([])?;
+    if has_source {
+        return Ok(());
+    }
+    conn.execute_batch("ALTER TABLE chunks ADD COLUMN source TEXT")?;
+    let mut update =
+        conn.prepare("UPDATE chunks SET source = ?1 WHERE source IS NULL AND substr(path, 1, ?2) = ?3")?;
+    for source in ChunkSource::ALL {
+        for prefix in source.path_prefixes() {
+            update.execute(params![source.as_str(), prefix.chars().count() as i64, prefix])?;
+        }
+    }
+    conn.execute(
+        "UPDATE chunks SET source = ?1 WHERE source IS NULL",
+        params![ChunkSource::Filesystem.as_str()],
+    )?;
+    Ok(())
+}
+
+/// Databases created before the time span columns get them (empty: re-index logs to fill them).
+fn add_time_columns(conn: &Connection) -> rusqlite::Result<()> {
+    let has_time = conn
+        .prepare("SELECT 1 FROM pragma_table_info('chunks') WHERE name = 'time_start_epoch_secs'")?
+        .exists([])?;
+    if has_time {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "ALTER TABLE chunks ADD COLUMN time_start_epoch_secs INTEGER;
+         ALTER TABLE chunks ADD COLUMN time_end_epoch_secs INTEGER;",
+    )
+}
+
+fn vector_blob(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn blob_vector(b: &[u8]) -> Vec<f32> {
+    b.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn insert_chunks(conn: &Connection, rows: &[ChunkRow]) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "INSERT OR REPLACE INTO chunks ({CHUNK_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"
+    ))?;
+    for r in rows {
+        stmt.execute(params![
+            r.id,
+            r.path,
+            r.chunk_index as i64,
+            r.start_token as i64,
+            r.end_token as i64,
+            r.file_mtime_epoch_secs,
+            r.file_size_bytes,
+            r.file_hash,
+            r.page,
+            r.tags,
+            r.content,
+            vector_blob(&r.embedding),
+            r.source,
+            r.time_start_epoch_secs,
+            r.time_end_epoch_secs,
+        ])?;
+    }
+    Ok(())
+}
+
+fn chunk_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ChunkRow> {
+    let embedding: Vec<u8> = row.get(11)?;
+    Ok(ChunkRow {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        chunk_index: row.get::<_, i64>(2)?.max(0) as usize,
+        start_token: row.get::<_, i64>(3)?.max(0) as usize,
+        end_token: row.get::<_, i64>(4)?.max(0) as usize,
+        file_mtime_epoch_secs: row.get(5)?,
+        file_size_bytes: row.get(6)?,
+        file_hash: row.get(7)?,
+        page: row.get(8)?,
+        tags: row.get(9)?,
+        source: row.get(12)?,
+        time_start_epoch_secs: row.get(13)?,
+        time_end_epoch_secs: row.get(14)?,
+        content: row.get(10)?,
+        embedding: blob_vector(&embedding),
+    })
+}
+
+/// Bytes in use by the database file (`page_count * page_size`).
+fn used_bytes(conn: &Connection) -> rusqlite::Result<u64> {
+    let pages: i64 = conn.query_row("PRAGMA page_count", [], |r| r.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |r| r.get(0))?;
+    Ok((pages.max(0) as u64) * (page_size.max(0) as u64))
+}
+
+#[async_trait::async_trait]
+impl VectorStore for SqliteStore {
+    fn backend(&self) -> StoreBackend {
+        StoreBackend::Sqlite
+    }
+
+    async fn add_chunks(&self, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            insert_chunks(&tx, &rows)?;
+            tx.commit()
+        })
+        .await
+    }
+
+    /// One transaction, so an interrupted replace keeps the old rows.
+    async fn replace_file_chunks(&self, path: &str, rows: Vec<ChunkRow>) -> Result<(), DbError> {
+        let path = path.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM chunks WHERE path = ?1", params![path])?;
+            insert_chunks(&tx, &rows)?;
+            tx.execute("DELETE FROM entities WHERE path = ?1", params![path])?;
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), DbError> {
+        let path = path.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM chunks WHERE path = ?1", params![path])?;
+            tx.execute("DELETE FROM entities WHERE path = ?1", params![path])?;
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn search(&self, query: &[f32], opts: &SearchOptions) -> Result<Vec<SearchHit>, DbError> {
+        let query = vector_blob(query);
+        let filter = opts
+            .filter
+            .as_ref()
+            .map_or_else(|| "1".to_string(), Predicate::as_sqlite_sql);
+        let top_k = opts.top_k.min(i64::MAX as usize) as i64;
+        let min_score = opts.min_score;
+        self.with_conn(move |conn| {
+            // Degenerate vectors have no cosine distance (NULL); sort them last like LanceDB.
+            let mut stmt = conn.prepare(&format!(
+                "SELECT path, chunk_index, start_token, end_token, page, content, \
+                 vec_distance_cosine(embedding, ?1) AS distance, source, id, \
+                 time_start_epoch_secs, time_end_epoch_secs \
+                 FROM chunks WHERE {filter} ORDER BY distance IS NULL, distance LIMIT ?2"
+            ))?;
+            let hits = stmt.query_map(params![query, top_k], |row| {
+                let content: String = row.get(5)?;
+                let distance = row.get::<_, Option<f64>>(6)?.map_or(f32::NAN, |d| d as f32);
+                Ok(SearchHit {
+                    id: row.get(8)?,
+                    path: row.get(0)?,
+                    chunk_index: row.get(1)?,
+                    start_token: row.get(2)?,
+                    end_token: row.get(3)?,
+                    page: row.get(4)?,
+                    score: Some(cosine_similarity_from_distance(distance)),
+                    distance: Some(distance),
+                    content_preview: Some(preview(&content, 240)),
+                    content: Some(content),
+                    source: row.get(7)?,
+                    time_start_epoch_secs: row.get(9)?,
+                    time_end_epoch_secs: row.get(10)?,
+                    duplicate_paths: vec![],
+                    collection: None,
+                })
+            })?;
+            let mut out = vec![];
+            for hit in hits {
+                let hit = hit?;
+                if min_score.is_none_or(|min| hit.score.is_some_and(|s| s >= min)) {
+                    out.push(hit);
+                }
+            }
+            Ok(out)
+        })
+        .await
+    }
+
+    async fn chunks_for_paths(&self, paths: &[String]) -> Result<Vec<ChunkRow>, DbError> {
+        let filter = Predicate::in_list("path", paths).as_sqlite_sql();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(&format!("SELECT {CHUNK_COLUMNS} FROM chunks WHERE {filter}"))?;
+            let rows = stmt.query_map([], chunk_from_row)?;
+            rows.collect()
+        })
+        .await
+    }
+
+    async fn find_chunks(&self, filter: &Predicate, limit: usize) -> Result<Vec<ChunkRow>, DbError> {
+        let filter = filter.as_sqlite_sql();
+        let limit = limit.min(i64::MAX as usize) as i64;
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(&format!("SELECT {CHUNK_COLUMNS} FROM chunks WHERE {filter} LIMIT ?1"))?;
+            let rows = stmt.query_map(params![limit], chunk_from_row)?;
+            rows.collect()
+        })
+        .await
+    }
+
+    async fn set_file_tags(&self, path: &str, tags_column: &str) -> Result<(), DbError> {
+        let (path, tags) = (path.to_string(), tags_column.to_string());
+        self.with_conn(move |conn| {
+            conn.execute("UPDATE chunks SET tags = ?1 WHERE path = ?2", params![tags, path])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn add_entities(&self, rows: Vec<EntityRow>) -> Result<(), DbError> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO entities (chunk_id, path, chunk_index, name, key, kind) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for r in &rows {
+                    stmt.execute(params![r.chunk_id, r.path, r.chunk_index as i64, r.name, r.key, r.kind])?;
+                }
+            }
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn find_entities(&self, filter: &Predicate, limit: usize) -> Result<Vec<EntityRow>, DbError> {
+        let filter = filter.as_sqlite_sql();
+        let limit = limit.min(i64::MAX as usize) as i64;
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT chunk_id, path, chunk_index, name, key, kind FROM entities WHERE {filter} LIMIT ?1"
+            ))?;
+            let rows = stmt.query_map(params![limit], |row| {
+                Ok(EntityRow {
+                    chunk_id: row.get(0)?,
+                    path: row.get(1)?,
+                    chunk_index: row.get::<_, i64>(2)?.max(0) as usize,
+                    name: row.get(3)?,
+                    key: row.get(4)?,
+                    kind: row.get(5)?,
+                })
+            })?;
+            rows.collect()
+        })
+        .await
+    }
+
+    async fn stats(&self) -> Result<StoreStats, DbError> {
+        self.with_conn(|conn| {
+            let chunks: i64 = conn.query_row("SELECT COUNT(*) FROM chunks", [], |r| r.get(0))?;
+            let entity_mentions: i64 = conn.query_row("SELECT COUNT(*) FROM entities", [], |r| r.get(0))?;
+            Ok(StoreStats {
+                backend: StoreBackend::Sqlite,
+                persistent: true,
+                chunks: chunks.max(0) as u64,
+                entity_mentions: entity_mentions.max(0) as u64,
+            })
+        })
+        .await
+    }
+
+    async fn optimize(&self) -> Result<(), DbError> {
+        self.with_conn(|conn| {
+            conn.query_row("PRAGMA optimize", [], |_| Ok(())).optional()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// `VACUUM` rewrites the file without free pages; SQLite keeps no old versions to prune.
+    async fn compact_and_prune(&self, prune_older_than: std::time::Duration) -> Result<CompactionStats, DbError> {
+        let _ = prune_older_than;
+        self.with_conn(|conn| {
+            let before = used_bytes(conn)?;
+            conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+            let after = used_bytes(conn)?;
+            Ok(CompactionStats {
+                bytes_removed: before.saturating_sub(after),
+                ..CompactionStats::default()
+            })
+        })
+        .await
+    }
+}
@@ -1,96 +1,383 @@
+use crate::ask::CachedAnswer;
+use crate::boilerplate::Boilerplate;
+use crate::cache::TtlLru;
+use crate::catalog::{CatalogHandle, FileCatalog};
+use crate::chat::ChatStore;
+use crate::consent::ConsentStore;
 use crate::config::{
-    compile_filesystem_policy, default_config_path, load_or_init_config, CompiledFileSystemPolicy,
-    FileSystemSourceConfig, SiloConfig, SourceConfig,
+    compile_filesystem_policy, config_revision, default_config_path, default_data_dir,
+    load_or_init_config, read_config, CompiledFileSystemPolicy,
+    FileSystemSourceConfig, LibrarySourceConfig, MessagesSourceConfig, NewslettersSourceConfig, ScreenshotsSourceConfig, SiloConfig,
+    SourceConfig,
 };
 use crate::database::DatabaseHandle;
-use crate::embed::{EmbedderHandle, NoopEmbedder};
-use crate::llm::{llm_from_env, LlmHandle};
+use crate::embed::EmbedderHandle;
+use crate::entities::EntityExtractor;
+use crate::errors::ToolError;
+use crate::graph::KnowledgeGraph;
+use crate::llm::{llm_from_env, ContextBudget, LlmHandle, DEFAULT_CONTEXT_WINDOW_TOKENS};
+use crate::prompt::{compile_prompt_templates, PromptTemplates};
+use crate::tagging::TagJobStatus;
+use crate::traffic::TrafficLog;
 use serde_json::json;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Query embeddings are pure functions of the query text, so they can live for a while.
+const QUERY_EMBEDDING_CACHE_CAPACITY: usize = 256;
+const QUERY_EMBEDDING_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Search results depend on the index, so only keep them long enough to absorb
+/// search-as-you-type debounces and agent retries.
+const SEARCH_RESULT_CACHE_CAPACITY: usize = 32;
+const SEARCH_RESULT_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Answers are checked against the catalog before reuse, so they can live much longer.
+const ANSWER_CACHE_CAPACITY: usize = 64;
+const ANSWER_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
 /// Shared server state.
 ///
 /// Scalable design: keep "sources" in config, and compile per-source policies for fast checks.
 pub struct AppState {
     pub db: DatabaseHandle,
+    pub data_dir: PathBuf,
+    /// Embedding model downloads (`models.cache_dir`, resolved at startup).
+    pub models_dir: PathBuf,
     pub config_path: PathBuf,
     pub config: RwLock<SiloConfig>,
+    /// Revision of the config file `config` was loaded from or last saved as (see
+    /// `update_config`). Guarded by the `config` lock.
+    config_revision: Mutex<String>,
     pub fs_policy: RwLock<Option<CompiledFileSystemPolicy>>,
+    /// Validated prompt templates compiled from `config.prompts`.
+    pub prompts: RwLock<PromptTemplates>,
     pub embedder: EmbedderHandle,
     pub llm: LlmHandle,
+    /// Entity extraction stage for ingest (`None` unless `entities.enabled`).
+    pub entities: Option<Arc<EntityExtractor>>,
+    /// File-level view of what has been indexed (persisted under `data_dir`).
+    pub catalog: CatalogHandle,
+    /// Multi-turn chat sessions (`silo_chat`), in memory only.
+    pub chats: Mutex<ChatStore>,
+    /// Files added with `silo_session_index`, in memory only.
+    pub session_index: crate::session_index::SessionIndex,
+    /// query text -> embedding
+    pub query_embeddings: Mutex<TtlLru<String, Vec<f32>>>,
+    /// search key (query + options) -> serialized result
+    pub search_results: Mutex<TtlLru<String, serde_json::Value>>,
+    /// answer key (model + question + retrieved chunks) -> `silo_ask` answer
+    pub answers: Mutex<TtlLru<String, CachedAnswer>>,
+    /// Knowledge graph, loaded/built on first use (`crate::graph`).
+    pub graph: tokio::sync::Mutex<Option<KnowledgeGraph>>,
+    /// Set when the index changes; the next graph query rebuilds.
+    pub graph_stale: AtomicBool,
+    /// Progress of the background tagging job (`crate::tagging`).
+    pub tag_job: Mutex<TagJobStatus>,
+    /// Held while a screenshots pass runs (watcher or `silo_index_screenshots`).
+    pub screenshot_pass: tokio::sync::Mutex<()>,
+    /// Held while a newsletters pass runs (watcher or `silo_index_newsletters`).
+    pub newsletter_pass: tokio::sync::Mutex<()>,
+    /// Held while `silo_index_library` runs.
+    pub library_pass: tokio::sync::Mutex<()>,
+    /// Locked collections opened by `silo_unlock_collection`: name -> expiry.
+    pub unlocks: Mutex<HashMap<String, Instant>>,
+    /// Tool calls waiting for (or granted) user consent (`crate::consent`).
+    pub consents: Mutex<ConsentStore>,
+    /// Write-capable tools are hidden and refused (`read_only` config or `SILO_READ_ONLY`).
+    pub read_only: bool,
+    /// JSON-RPC traffic log, when enabled (`crate::traffic`).
+    pub traffic: Option<TrafficLog>,
+    /// `silo_write_file`, when enabled (`crate::writes`).
+    pub file_writes: Option<crate::writes::FileWrites>,
+    /// Bulk index runs in flight (`crate::indexer`), reported with search results.
+    pub index_progress: Arc<crate::indexer::IndexProgress>,
+    /// Ingest and search timings (`crate::metrics`, `silo_metrics`).
+    pub metrics: Arc<crate::metrics::Metrics>,
+    /// Shingle counts for boilerplate filtering (`crate::boilerplate`), persisted under `data_dir`.
+    pub boilerplate: Arc<crate::boilerplate::Boilerplate>,
+    /// Corpus vocabulary and synonym cache for query preprocessing (`crate::rewrite`).
+    pub query_rewriter: crate::rewrite::QueryRewriter,
+    /// `silo_append_note`, when a notes folder is configured (`crate::notes`).
+    pub notes: Option<crate::notes::Notes>,
+    /// `silo_ingest_url`, when enabled (`crate::web`).
+    pub web: Option<crate::web::WebIngest>,
+    /// Audit log of tool writes to user files (`logs/writes.jsonl`).
+    pub write_audit: crate::writes::WriteAudit,
+    /// Index and source failure notifications for MCP sessions and the desktop app (`crate::notify`).
+    pub notifier: crate::notify::Notifier,
+    /// Translations for the `locale` config (`crate::i18n`).
+    pub i18n: crate::i18n::Translations,
+    /// Rest of tool results cut to `response_limits`, for `silo_continue` (`crate::responses`).
+    pub continuations: crate::responses::Continuations,
 }
 
 impl AppState {
     pub async fn new(db: DatabaseHandle) -> Result<Arc<Self>, String> {
         let config_path = default_config_path();
         let cfg = load_or_init_config(&config_path).await?;
+        let revision = tokio::fs::read_to_string(&config_path)
+            .await
+            .map(|s| config_revision(&s))
+            .unwrap_or_default();
+        let data_dir = default_data_dir();
+        crate::backup::apply_backup_exclusion(&data_dir, cfg.storage.include_in_backups).await;
 
+        let read_only = cfg.read_only || read_only_from_env();
+        if read_only {
+            tracing::info!("Read-only mode: write-capable tools are disabled");
+        }
+        let traffic = TrafficLog::from_config(&cfg.debug, &data_dir);
+        let file_writes = crate::writes::FileWrites::from_config(&cfg.writes);
+        let notes = crate::notes::Notes::from_config(&cfg.notes);
+        let web = crate::web::WebIngest::from_config(&cfg.web);
+        let write_audit = crate::writes::WriteAudit::new(&data_dir);
+        crate::passwords::load(&data_dir).await;
         let fs_policy = compile_from_config(&cfg)?;
+        apply_extract_limits(fs_policy.as_ref());
+        let prompts = compile_prompt_templates(&cfg.prompts).await?;
 
+        let models_dir = cfg
+            .models
+            .cache_dir
+            .as_ref()
+            .map(|p| expand_tilde(&p.to_string_lossy()))
+            .unwrap_or_else(|| data_dir.join("models"));
         let embedder: EmbedderHandle = {
             #[cfg(feature = "embeddings")]
             {
-                match crate::embed::FastEmbedder::try_new_default() {
-                    Ok(e) => {
-                        tracing::info!("Embedder initialized: fastembed (bge-small-en-v1.5)");
-                        Arc::new(e)
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to init fastembed embedder, falling back to noop: {e}");
-                        Arc::new(NoopEmbedder)
-                    }
-                }
+                // Downloaded/loaded on first use (see `crate::models`).
+                let idle = (cfg.models.idle_unload_secs > 0)
+                    .then(|| std::time::Duration::from_secs(cfg.models.idle_unload_secs));
+                tracing::info!("Embedder: fastembed (bge-small-en-v1.5), models in {}", models_dir.display());
+                crate::embed::FastEmbedder::new(models_dir.clone(), idle)
             }
             #[cfg(not(feature = "embeddings"))]
             {
                 tracing::info!("Embedder disabled (build without --features embeddings); using noop embedder");
-                Arc::new(NoopEmbedder)
+                Arc::new(crate::embed::NoopEmbedder)
             }
         };
 
         let llm = llm_from_env();
+        let entities = EntityExtractor::from_config(&cfg.entities, &llm).map(Arc::new);
+        // An in-memory store starts empty, so a saved catalog would claim files it does not hold.
+        let (catalog, boilerplate) = if db.backend() == Some(crate::store::StoreBackend::Memory) {
+            (FileCatalog::empty(&data_dir), Boilerplate::empty(&data_dir))
+        } else {
+            (FileCatalog::load(&data_dir).await, Boilerplate::load(&data_dir).await)
+        };
+        let catalog = Arc::new(RwLock::new(catalog));
 
         Ok(Arc::new(Self {
             db,
+            data_dir,
+            models_dir,
             config_path,
             config: RwLock::new(cfg),
+            config_revision: Mutex::new(revision),
             fs_policy: RwLock::new(fs_policy),
+            prompts: RwLock::new(prompts),
             embedder,
             llm,
+            entities,
+            catalog,
+            chats: Mutex::new(ChatStore::default()),
+            session_index: Default::default(),
+            query_embeddings: Mutex::new(TtlLru::new(
+                QUERY_EMBEDDING_CACHE_CAPACITY,
+                QUERY_EMBEDDING_CACHE_TTL,
+            )),
+            search_results: Mutex::new(TtlLru::new(
+                SEARCH_RESULT_CACHE_CAPACITY,
+                SEARCH_RESULT_CACHE_TTL,
+            )),
+            answers: Mutex::new(TtlLru::new(ANSWER_CACHE_CAPACITY, ANSWER_CACHE_TTL)),
+            graph: tokio::sync::Mutex::new(None),
+            graph_stale: AtomicBool::new(false),
+            tag_job: Mutex::new(TagJobStatus::default()),
+            screenshot_pass: tokio::sync::Mutex::new(()),
+            newsletter_pass: tokio::sync::Mutex::new(()),
+            library_pass: tokio::sync::Mutex::new(()),
+            unlocks: Mutex::new(HashMap::new()),
+            consents: Mutex::new(ConsentStore::default()),
+            read_only,
+            traffic,
+            file_writes,
+            notes,
+            web,
+            write_audit,
+            index_progress: Default::default(),
+            metrics: Default::default(),
+            boilerplate: Arc::new(boilerplate),
+            query_rewriter: Default::default(),
+            notifier: Default::default(),
+            i18n: Default::default(),
+            continuations: Default::default(),
         }))
     }
 
+    /// Translations for the configured locale.
+    pub async fn messages(&self) -> Arc<crate::i18n::Catalog> {
+        let locale = self.config.read().await.locale.clone();
+        self.i18n.catalog(&locale, &self.data_dir)
+    }
+
+    /// Embed a search query, reusing a cached vector for repeated identical queries.
+    pub async fn embed_query_cached(&self, query: &str) -> Result<Vec<f32>, String> {
+        if let Some(v) = lock_cache(&self.query_embeddings).get(&query.to_string()) {
+            return Ok(v);
+        }
+        let v = self.embedder.embed_query(query.to_string()).await?;
+        lock_cache(&self.query_embeddings).insert(query.to_string(), v.clone());
+        Ok(v)
+    }
+
+    pub fn cached_search_result(&self, key: &str) -> Option<serde_json::Value> {
+        lock_cache(&self.search_results).get(&key.to_string())
+    }
+
+    pub fn cache_search_result(&self, key: String, value: serde_json::Value) {
+        lock_cache(&self.search_results).insert(key, value);
+    }
+
+    pub fn cached_answer(&self, key: &str) -> Option<CachedAnswer> {
+        lock_cache(&self.answers).get(&key.to_string())
+    }
+
+    pub fn cache_answer(&self, key: String, value: CachedAnswer) {
+        lock_cache(&self.answers).insert(key, value);
+    }
+
+    pub fn forget_answer(&self, key: &str) {
+        lock_cache(&self.answers).remove(&key.to_string());
+    }
+
+    pub fn tag_job_status(&self) -> TagJobStatus {
+        lock_cache(&self.tag_job).clone()
+    }
+
+    pub fn update_tag_job<R>(&self, f: impl FnOnce(&mut TagJobStatus) -> R) -> R {
+        f(&mut lock_cache(&self.tag_job))
+    }
+
+    /// Prompt token budget: config override, else the model's reported window, else a default.
+    pub async fn context_budget(&self) -> ContextBudget {
+        let llm_cfg = self.config.read().await.llm.clone();
+        let (window_tokens, source) = match llm_cfg.context_window_tokens {
+            Some(n) => (n, "config"),
+            None => match self.llm.context_window_tokens().await {
+                Some(n) => (n, "model"),
+                None => (DEFAULT_CONTEXT_WINDOW_TOKENS, "default"),
+            },
+        };
+        ContextBudget {
+            window_tokens,
+            reserve_tokens: llm_cfg.answer_reserve_tokens,
+            source,
+        }
+    }
+
+    /// Best-effort persist of the file catalog and the boilerplate table (logged, never fatal).
+    pub async fn save_catalog(&self) {
+        if let Err(e) = self.catalog.read().await.save().await {
+            tracing::warn!("{e}");
+        }
+        if let Err(e) = self.boilerplate.save().await {
+            tracing::warn!("{e}");
+        }
+    }
+
+    /// Call after anything that changes the index (ingest, bulk index, deletes): drops cached
+    /// search results and marks derived data (knowledge graph, query vocabulary) for rebuild.
+    pub fn index_changed(&self) {
+        lock_cache(&self.search_results).clear();
+        self.graph_stale.store(true, Ordering::Relaxed);
+        self.query_rewriter.mark_stale();
+    }
+
     pub async fn get_config_json(&self) -> serde_json::Value {
+        self.reload_config_if_changed().await;
         let cfg = self.config.read().await;
         json!({
             "configPath": self.config_path.to_string_lossy(),
+            "revision": lock_cache(&self.config_revision).clone(),
             "config": &*cfg
         })
     }
 
-    pub async fn set_index_roots(&self, roots: Vec<PathBuf>) -> Result<serde_json::Value, String> {
+    /// Pick up manual edits of the config file (a file that fails to parse is ignored with a
+    /// warning). Like `update_config`, this refreshes the filesystem policy; settings read at
+    /// startup still need a restart.
+    async fn reload_config_if_changed(&self) {
         let mut cfg = self.config.write().await;
-
-        let mut updated = false;
-        for src in &mut cfg.sources {
-            let SourceConfig::FileSystem(fs) = src;
-            fs.roots = roots.clone();
-            updated = true;
-            break;
+        let (disk, revision) = match read_config(&self.config_path).await {
+            Ok(Some(found)) => found,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Keeping the loaded config: {e}");
+                return;
+            }
+        };
+        if revision == *lock_cache(&self.config_revision) {
+            return;
         }
-        if !updated {
-            cfg.sources.push(SourceConfig::FileSystem(FileSystemSourceConfig {
-                roots,
-                ..FileSystemSourceConfig::default()
-            }));
+        match compile_from_config(&disk) {
+            Ok(compiled) => {
+                tracing::info!("Config file changed on disk; reloaded (revision {revision})");
+                apply_extract_limits(compiled.as_ref());
+                *self.fs_policy.write().await = compiled;
+                *cfg = disk;
+                *lock_cache(&self.config_revision) = revision;
+            }
+            Err(e) => tracing::warn!("Keeping the loaded config: {e}"),
         }
+    }
 
-        crate::config::save_config(&self.config_path, &cfg).await?;
-        let compiled = compile_from_config(&cfg)?;
+    /// Read-modify-write of the config file. `edit` runs on the file's current contents, so
+    /// manual edits made since startup are kept rather than overwritten; the result is validated
+    /// and saved atomically, and becomes the loaded config. With `expected_revision` (from
+    /// `silo_get_config`), nothing is written if the file has changed since that revision, nor
+    /// when `edit` fails. Returns the new revision.
+    pub async fn update_config(
+        &self,
+        expected_revision: Option<&str>,
+        edit: impl FnOnce(&mut SiloConfig) -> Result<(), String>,
+    ) -> Result<String, ToolError> {
+        let mut cfg = self.config.write().await;
+        let (mut next, revision) = match read_config(&self.config_path).await? {
+            Some(found) => found,
+            None => (cfg.clone(), String::new()),
+        };
+        if let Some(expected) = expected_revision.filter(|e| *e != revision) {
+            return Err(ToolError::config_conflict(expected, &revision));
+        }
+        edit(&mut next)?;
+        let compiled = compile_from_config(&next)?;
+        let revision = crate::config::save_config(&self.config_path, &next).await?;
+        apply_extract_limits(compiled.as_ref());
         *self.fs_policy.write().await = compiled;
+        *cfg = next;
+        *lock_cache(&self.config_revision) = revision.clone();
+        Ok(revision)
+    }
 
+    pub async fn set_index_roots(
+        &self,
+        roots: Vec<PathBuf>,
+        expected_revision: Option<&str>,
+    ) -> Result<serde_json::Value, ToolError> {
+        self.update_config(expected_revision, |cfg| {
+            let mut fs = filesystem_source_owned(cfg).unwrap_or_default();
+            fs.roots = roots;
+            crate::config::set_filesystem_source(cfg, fs);
+            Ok(())
+        })
+        .await?;
         Ok(self.get_config_json().await)
     }
 
@@ -124,9 +411,28 @@ impl AppState {
             issues.push("No filesystem source configured".to_string());
         }
 
+        let store = match self.db.stats().await {
+            Ok(stats) => stats,
+            Err(e) => {
+                issues.push(format!("cannot read the index: {e}"));
+                None
+            }
+        };
+        match &store {
+            Some(s) if !s.persistent => {
+                issues.push("the index is kept in memory and lost on exit (build with --features lancedb)".to_string())
+            }
+            None => issues.push(format!(
+                "knowledge base disabled: {}",
+                self.db.disabled_reason().unwrap_or("unknown reason")
+            )),
+            Some(_) => {}
+        }
+
         json!({
             "ok": issues.is_empty(),
-            "issues": issues
+            "issues": issues,
+            "store": store
         })
     }
 
@@ -147,28 +453,107 @@ impl AppState {
     pub async fn filesystem_policy(&self) -> Option<CompiledFileSystemPolicy> {
         self.fs_policy.read().await.clone()
     }
+
+    /// The screenshots source, if configured.
+    pub async fn screenshots_config(&self) -> Option<ScreenshotsSourceConfig> {
+        screenshots_source(&*self.config.read().await)
+    }
+
+    /// The newsletters source, if configured.
+    pub async fn newsletters_config(&self) -> Option<NewslettersSourceConfig> {
+        newsletters_source(&*self.config.read().await)
+    }
+
+    /// The library source, if configured.
+    pub async fn library_config(&self) -> Option<LibrarySourceConfig> {
+        library_source(&*self.config.read().await)
+    }
+
+    /// The Messages source, if configured.
+    pub async fn messages_config(&self) -> Option<MessagesSourceConfig> {
+        messages_source(&*self.config.read().await)
+    }
+}
+
+/// `SILO_READ_ONLY` set to `1`, `true` or `yes`.
+pub fn read_only_from_env() -> bool {
+    std::env::var("SILO_READ_ONLY")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Cache locks are never held across `.await`; recover from poisoning instead of panicking.
+fn lock_cache<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
 }
 
 fn filesystem_source(cfg: &SiloConfig) -> Option<&FileSystemSourceConfig> {
     cfg.sources.iter().find_map(|s| match s {
         SourceConfig::FileSystem(fs) => Some(fs),
+        _ => None,
     })
 }
 
 fn filesystem_source_owned(cfg: &SiloConfig) -> Option<FileSystemSourceConfig> {
     cfg.sources.iter().find_map(|s| match s {
         SourceConfig::FileSystem(fs) => Some(fs.clone()),
+        _ => None,
+    })
+}
+
+fn screenshots_source(cfg: &SiloConfig) -> Option<ScreenshotsSourceConfig> {
+    cfg.sources.iter().find_map(|s| match s {
+        SourceConfig::Screenshots(s) => Some(s.clone()),
+        _ => None,
     })
 }
 
+fn newsletters_source(cfg: &SiloConfig) -> Option<NewslettersSourceConfig> {
+    cfg.sources.iter().find_map(|s| match s {
+        SourceConfig::Newsletters(n) => Some(n.clone()),
+        _ => None,
+    })
+}
+
+fn library_source(cfg: &SiloConfig) -> Option<LibrarySourceConfig> {
+    cfg.sources.iter().find_map(|s| match s {
+        SourceConfig::Library(l) => Some(l.clone()),
+        _ => None,
+    })
+}
+
+fn messages_source(cfg: &SiloConfig) -> Option<MessagesSourceConfig> {
+    cfg.sources.iter().find_map(|s| match s {
+        SourceConfig::Messages(m) => Some(m.clone()),
+        _ => None,
+    })
+}
+
+/// Also applies the policy's extractor limits (process-wide, see `crate::extract`). The
+/// newsletters folder is excluded: its connector stores the items with their feed metadata, and
+/// the file indexer would replace them with the raw pages.
 fn compile_from_config(cfg: &SiloConfig) -> Result<Option<CompiledFileSystemPolicy>, String> {
     if let Some(fs) = filesystem_source(cfg) {
-        Ok(Some(compile_filesystem_policy(fs)?))
+        let mut fs = fs.clone();
+        if let Some(n) = newsletters_source(cfg) {
+            let folder = crate::newsletters::newsletters_folder(&n);
+            fs.exclude_globs
+                .push(format!("{}/**", globset::escape(&folder.to_string_lossy())));
+        }
+        compile_filesystem_policy(&fs).map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// Hand the extractor limits of a policy being committed to `crate::extract` (kept as they are
+/// without a filesystem source).
+fn apply_extract_limits(policy: Option<&CompiledFileSystemPolicy>) {
+    if let Some(policy) = policy {
+        crate::extract::set_limits(policy.extract_limits);
+    }
+}
+
 pub type SharedState = Arc<AppState>;
 
 /// Utility: best-effort resolve `~` prefix if present.
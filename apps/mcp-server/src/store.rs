@@ -0,0 +1,107 @@
+//! Storage backend behind `Database`.
+//!
+//! `Database` is what the rest of the server talks to; a `VectorStore` is where chunk rows and
+//! entity mentions actually live. `storage.backend` picks LanceDB (`--features lancedb`, see
+//! `crate::database`) or SQLite with sqlite-vec (`--features sqlite`, see `crate::sqlitestore`).
+//! When LanceDB is selected but not compiled in, an in-memory store (`crate::memstore`) stands
+//! in, so a minimal build still indexes and searches, just not across restarts.
+
+use crate::database::{ChunkRow, CompactionStats, DbError, EntityRow, SearchHit, SearchOptions};
+use crate::database::chunk_row_id;
+use crate::predicate::Predicate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreBackend {
+    Lancedb,
+    Sqlite,
+    Memory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreStats {
+    pub backend: StoreBackend,
+    /// Survives a restart.
+    pub persistent: bool,
+    pub chunks: u64,
+    pub entity_mentions: u64,
+}
+
+#[async_trait::async_trait]
+pub trait VectorStore: Send + Sync {
+    fn backend(&self) -> StoreBackend;
+
+    /// Append chunk rows as given.
+    async fn add_chunks(&self, rows: Vec<ChunkRow>) -> Result<(), DbError>;
+
+    /// Make `rows` the chunks of `path` and drop the path's entity mentions. Must never leave
+    /// the file without rows if interrupted.
+    async fn replace_file_chunks(&self, path: &str, rows: Vec<ChunkRow>) -> Result<(), DbError>;
+
+    /// Remove every chunk and entity mention of `path`.
+    async fn delete_file(&self, path: &str) -> Result<(), DbError>;
+
+    /// Nearest chunks to `query` by cosine similarity (`SearchHit::score`, higher is better).
+    async fn search(&self, query: &[f32], opts: &SearchOptions) -> Result<Vec<SearchHit>, DbError>;
+
+    /// Every chunk row of `paths`, vectors included.
+    async fn chunks_for_paths(&self, paths: &[String]) -> Result<Vec<ChunkRow>, DbError>;
+
+    /// Chunk rows matching `filter` (at most `limit`), vectors included.
+    async fn find_chunks(&self, filter: &Predicate, limit: usize) -> Result<Vec<ChunkRow>, DbError>;
+
+    /// Set the `tags` column (in `tags_column` form) of every chunk of `path`.
+    async fn set_file_tags(&self, path: &str, tags_column: &str) -> Result<(), DbError>;
+
+    async fn add_entities(&self, rows: Vec<EntityRow>) -> Result<(), DbError>;
+
+    /// Entity mention rows matching `filter` (at most `limit`).
+    async fn find_entities(&self, filter: &Predicate, limit: usize) -> Result<Vec<EntityRow>, DbError>;
+
+    async fn stats(&self) -> Result<StoreStats, DbError>;
+
+    /// Move every chunk row and entity mention of `from` to `to`, vectors as they are (a rename
+    /// needs no re-embedding). Returns the chunks moved; 0 (and no change) for an unknown `from`.
+    async fn rename_file(&self, from: &str, to: &str) -> Result<usize, DbError> {
+        let mut rows = self.chunks_for_paths(&[from.to_string()]).await?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let mut entities = self
+            .find_entities(&Predicate::eq("path", from), u32::MAX as usize)
+            .await?;
+        // Row ids hash the path, and entity mentions point at row ids.
+        let mut ids = std::collections::HashMap::new();
+        for row in &mut rows {
+            let id = chunk_row_id(to, row.chunk_index, &row.content);
+            ids.insert(std::mem::replace(&mut row.id, id.clone()), id);
+            row.path = to.to_string();
+        }
+        for e in &mut entities {
+            if let Some(id) = ids.get(&e.chunk_id) {
+                e.chunk_id = id.clone();
+            }
+            e.path = to.to_string();
+        }
+        let moved = rows.len();
+        // Written under the new path first, so an interruption leaves a duplicate, never a gap.
+        self.replace_file_chunks(to, rows).await?;
+        if !entities.is_empty() {
+            self.add_entities(entities).await?;
+        }
+        self.delete_file(from).await?;
+        Ok(moved)
+    }
+
+    /// Reclaim space after deletes (quick, best effort).
+    async fn optimize(&self) -> Result<(), DbError> {
+        Ok(())
+    }
+
+    /// Compact and drop history older than `prune_older_than`, reporting what was reclaimed.
+    async fn compact_and_prune(&self, prune_older_than: std::time::Duration) -> Result<CompactionStats, DbError> {
+        let _ = prune_older_than;
+        Ok(CompactionStats::default())
+    }
+}
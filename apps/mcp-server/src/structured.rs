@@ -0,0 +1,258 @@
+//! Schema-driven field extraction from a single document (`silo_extract_structured`).
+//!
+//! Flow: extract text -> fit it to the context window -> LLM returns JSON -> validate against
+//! the caller's schema. On validation errors the model gets one retry with the errors listed.
+//!
+//! Only the JSON Schema subset that field extraction needs is supported: `type` (string or
+//! list), `properties`, `required`, `additionalProperties: false`, `items`, `enum`.
+
+use crate::errors::ToolError;
+use crate::llm::{estimate_llm_tokens, truncate_to_llm_tokens};
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Attempts per call: the first answer plus one repair round.
+const MAX_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Deserialize)]
+struct ExtractStructuredArgs {
+    path: String,
+    schema: Value,
+    #[serde(default)]
+    instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredExtraction {
+    pub path: String,
+    /// Extracted object (best attempt, even when invalid).
+    pub data: Value,
+    pub valid: bool,
+    /// Schema violations of `data` (empty when valid).
+    pub errors: Vec<String>,
+    pub attempts: usize,
+    /// The document was cut to fit the model's context window.
+    pub document_truncated: bool,
+}
+
+/// MCP tool entrypoint (`silo_extract_structured`).
+pub async fn extract_structured_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ExtractStructuredArgs =
+        serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let out = extract_structured(state, &args.path, &args.schema, args.instructions.as_deref()).await?;
+    serde_json::to_value(out).map_err(|e| format!("failed to serialize extraction: {e}").into())
+}
+
+pub async fn extract_structured(
+    state: &SharedState,
+    path: &str,
+    schema: &Value,
+    instructions: Option<&str>,
+) -> Result<StructuredExtraction, ToolError> {
+    check_schema(schema)?;
+    let path = expand_tilde(path);
+    if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err(ToolError::parent_dir_path());
+    }
+
+    let max_text_bytes = state
+        .fs_policy
+        .read()
+        .await
+        .as_ref()
+        .map(|p| p.max_text_bytes)
+        .unwrap_or(2 * 1024 * 1024);
+    let extracted = crate::extract::extract_text(&path, max_text_bytes).await?;
+    if extracted.text.trim().is_empty() {
+        return Err(format!("No text could be extracted from {}", path.display()).into());
+    }
+
+    let template = state.prompts.read().await.extract.clone();
+    let schema_text = serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string());
+    let instructions = instructions
+        .map(|i| format!("Additional instructions: {}\n", i.trim()))
+        .unwrap_or_default();
+
+    let fixed = template.literal_tokens() + estimate_llm_tokens(&schema_text) + estimate_llm_tokens(&instructions);
+    // Room for a repair round: the previous answer (about schema-sized) plus the error list.
+    let repair_room = estimate_llm_tokens(&schema_text) + 128;
+    let available = state
+        .context_budget()
+        .await
+        .prompt_tokens()
+        .saturating_sub(fixed + repair_room);
+    let document_truncated = extracted.truncated || estimate_llm_tokens(&extracted.text) > available;
+    let document = truncate_to_llm_tokens(&extracted.text, available);
+
+    let base_prompt = template.render(&[
+        ("schema", &schema_text),
+        ("document", &document),
+        ("instructions", &instructions),
+    ]);
+
+    let mut prompt = base_prompt.clone();
+    let mut data = Value::Null;
+    let mut errors = vec![];
+    let mut attempts = 0;
+    while attempts < MAX_ATTEMPTS {
+        attempts += 1;
+        let raw = state.llm.generate(prompt.clone()).await?;
+        match parse_json_object(&raw) {
+            Ok(v) => {
+                errors = validate(&v, schema);
+                data = v;
+            }
+            Err(e) => {
+                errors = vec![e];
+                data = Value::Null;
+            }
+        }
+        if errors.is_empty() {
+            break;
+        }
+        prompt = format!(
+            "{base_prompt}\n{}\n\nThat answer is invalid:\n- {}\n\nReturn the corrected JSON object only.\nJSON:",
+            raw.trim(),
+            errors.join("\n- ")
+        );
+    }
+
+    Ok(StructuredExtraction {
+        path: path.to_string_lossy().to_string(),
+        data,
+        valid: errors.is_empty(),
+        errors,
+        attempts,
+        document_truncated,
+    })
+}
+
+/// The top level must describe an object with named properties.
+fn check_schema(schema: &Value) -> Result<(), String> {
+    let Some(obj) = schema.as_object() else {
+        return Err("schema must be a JSON object".to_string());
+    };
+    if obj.get("type").is_some_and(|t| t != "object") {
+        return Err("schema.type must be \"object\"".to_string());
+    }
+    match obj.get("properties").and_then(Value::as_object) {
+        Some(props) if !props.is_empty() => Ok(()),
+        _ => Err("schema.properties must list at least one field".to_string()),
+    }
+}
+
+/// Models like to wrap JSON in prose or ``` fences; take the outermost `{...}`.
+fn parse_json_object(raw: &str) -> Result<Value, String> {
+    let start = raw.find('{');
+    let end = raw.rfind('}');
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err("LLM did not return a JSON object".to_string());
+    };
+    if end < start {
+        return Err("LLM did not return a JSON object".to_string());
+    }
+    let v: Value = serde_json::from_str(&raw[start..=end])
+        .map_err(|e| format!("LLM returned invalid JSON: {e}"))?;
+    if !v.is_object() {
+        return Err("LLM did not return a JSON object".to_string());
+    }
+    Ok(v)
+}
+
+/// Validate `value` against the supported schema subset; returns human-readable violations.
+fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = vec![];
+    validate_at("$", value, schema, &mut errors);
+    errors
+}
+
+fn validate_at(at: &str, value: &Value, schema: &Value, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let allowed: Vec<&str> = match ty {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(a) => a.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(t, value)) {
+            errors.push(format!("{at}: expected {}, got {}", allowed.join(" or "), type_name(value)));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        errors.push(format!("{at}: {value} is not one of {}", Value::Array(options.clone())));
+    }
+
+    match value {
+        Value::Object(obj) => validate_object(at, obj, schema, errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(&format!("{at}[{i}]"), item, item_schema, errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_object(at: &str, obj: &Map<String, Value>, schema: &Map<String, Value>, errors: &mut Vec<String>) {
+    let props = schema.get("properties").and_then(Value::as_object);
+
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    for name in &required {
+        if !obj.contains_key(*name) {
+            errors.push(format!("{at}: missing required field `{name}`"));
+        }
+    }
+
+    for (key, v) in obj {
+        // The prompt asks for null when a field isn't in the document; treat that as absent.
+        if v.is_null() && !required.contains(&key.as_str()) {
+            continue;
+        }
+        match props.and_then(|p| p.get(key)) {
+            Some(prop_schema) => validate_at(&format!("{at}.{key}"), v, prop_schema, errors),
+            None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                errors.push(format!("{at}: unexpected field `{key}`"));
+            }
+            None => {}
+        }
+    }
+}
+
+fn type_matches(ty: &str, value: &Value) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
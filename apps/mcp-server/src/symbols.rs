@@ -0,0 +1,398 @@
+//! Symbol index of source files (`silo_find_symbol`): where a function, method or type is defined.
+//!
+//! When a source file is ingested (`crate::ingest::process_extracted`) its text is parsed with
+//! tree-sitter, and the definitions matched by the grammar's tags query (the one editors and code
+//! hosts use for "go to definition") are kept on its catalog entry with their lines, enclosing
+//! type and first line. Rust, Python, JavaScript, TypeScript/TSX, Go and Java are parsed; other
+//! files have no symbols. Lookups are exact (case-sensitive name) or fuzzy: case-insensitive,
+//! prefix, substring, abbreviation (`gcfg` for `get_config`) and one-typo matches, best first.
+//! A `Type::name` or `Type.name` query also matches the enclosing type. Locked collections are
+//! skipped.
+
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, StreamingIterator};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+/// Larger sources (bundles, generated code) are not parsed.
+const MAX_SOURCE_BYTES: usize = 1024 * 1024;
+/// Characters of the definition's first line kept as its signature.
+const SIGNATURE_CHARS: usize = 200;
+
+/// A definition in a source file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    /// `function`, `method`, `class`, `struct`, `enum`, `interface`, `type`, `module`, `macro`,
+    /// `constant`, ...
+    pub kind: String,
+    /// 1-based first and last line of the definition.
+    pub line: usize,
+    pub end_line: usize,
+    /// Enclosing type or module (for Rust methods, the `impl` type).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// First line of the definition, trimmed.
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FindSymbolArgs {
+    /// Symbol name, optionally qualified (`Config::load`, `Parser.parse`).
+    pub name: String,
+    /// Only case-sensitive equal names (default: fuzzy).
+    #[serde(default)]
+    pub exact: bool,
+    /// Only symbols of this kind (`function`, `class`, ...; `function` includes methods).
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Only paths matching one of these globs (e.g. `**/src/**`).
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolMatch {
+    pub path: String,
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    /// 1.0 for an exact name; lower for looser matches.
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FindSymbolResult {
+    pub matches: Vec<SymbolMatch>,
+    /// Matches before `limit`.
+    pub total: usize,
+    /// Indexed files with symbols (files indexed before the symbol index get theirs when
+    /// re-indexed).
+    pub indexed_files: usize,
+}
+
+/// MCP tool entrypoint (`silo_find_symbol`).
+pub async fn find_symbol_tool(state: &SharedState, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+    let args: FindSymbolArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let result = find_symbol(state, args).await?;
+    serde_json::to_value(result).map_err(|e| format!("failed to serialize symbol matches: {e}").into())
+}
+
+pub async fn find_symbol(state: &SharedState, args: FindSymbolArgs) -> Result<FindSymbolResult, String> {
+    let query = args.name.trim();
+    if query.is_empty() {
+        return Err("`name` must not be empty".to_string());
+    }
+    let (container, name) = match query.rsplit_once("::").or_else(|| query.rsplit_once('.')) {
+        Some((container, name)) if !name.is_empty() => (Some(container.rsplit([':', '.']).next().unwrap_or(container)), name),
+        _ => (None, query),
+    };
+    let include = crate::grep::glob_set(&args.include)?;
+    let kind = args.kind.as_deref().map(str::to_ascii_lowercase);
+    let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let locked = crate::access::locked_paths(state).await;
+    let name_lower = name.to_lowercase();
+
+    let catalog = state.catalog.read().await;
+    let mut indexed_files = 0;
+    let mut matches = vec![];
+    for entry in catalog.entries() {
+        if entry.symbols.is_empty() {
+            continue;
+        }
+        indexed_files += 1;
+        if include.as_ref().is_some_and(|g| !g.is_match(&entry.path)) || locked.collection_of(&entry.path).is_some() {
+            continue;
+        }
+        for symbol in &entry.symbols {
+            if kind.as_deref().is_some_and(|k| !kind_matches(k, &symbol.kind)) {
+                continue;
+            }
+            let in_container = container.is_none_or(|c| {
+                symbol.container.as_deref().is_some_and(|s| {
+                    if args.exact { s == c } else { s.eq_ignore_ascii_case(c) }
+                })
+            });
+            if !in_container {
+                continue;
+            }
+            let score = if args.exact {
+                (symbol.name == name).then_some(1.0)
+            } else {
+                fuzzy_score(&symbol.name, name, &name_lower)
+            };
+            if let Some(score) = score {
+                matches.push(SymbolMatch {
+                    path: entry.path.clone(),
+                    symbol: symbol.clone(),
+                    score,
+                });
+            }
+        }
+    }
+    drop(catalog);
+
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.symbol.name.len().cmp(&b.symbol.name.len()))
+            .then_with(|| a.path.cmp(&b.path))
+            .then(a.symbol.line.cmp(&b.symbol.line))
+    });
+    let total = matches.len();
+    matches.truncate(limit);
+    Ok(FindSymbolResult {
+        matches,
+        total,
+        indexed_files,
+    })
+}
+
+/// `function` also finds methods; other kinds match exactly.
+fn kind_matches(wanted: &str, kind: &str) -> bool {
+    wanted == kind || (wanted == "function" && kind == "method")
+}
+
+/// How well `name` matches the query, best first: equal, equal ignoring case, prefix, substring,
+/// abbreviation (the query's characters in order), one edit away.
+fn fuzzy_score(name: &str, query: &str, query_lower: &str) -> Option<f32> {
+    if name == query {
+        return Some(1.0);
+    }
+    let lower = name.to_lowercase();
+    if lower == query_lower {
+        Some(0.9)
+    } else if lower.starts_with(query_lower) {
+        Some(0.8)
+    } else if lower.contains(query_lower) {
+        Some(0.7)
+    } else if is_subsequence(query_lower, &lower) {
+        Some(0.5)
+    } else if query_lower.chars().count() >= 4 && within_one_edit(query_lower, &lower) {
+        Some(0.4)
+    } else {
+        None
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+/// Equal after at most one inserted, deleted or replaced character.
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(long.iter()).take_while(|(x, y)| x == y).count();
+    let skip = if short.len() == long.len() { prefix + 1 } else { prefix };
+    short.get(skip..).unwrap_or_default() == long.get(prefix + 1..).unwrap_or_default()
+}
+
+#[derive(Clone, Copy)]
+enum Lang {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Java,
+}
+
+impl Lang {
+    fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match ext.as_str() {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+            "ts" | "mts" | "cts" => Self::TypeScript,
+            "tsx" => Self::Tsx,
+            "go" => Self::Go,
+            "java" => Self::Java,
+            _ => return None,
+        })
+    }
+}
+
+/// A grammar with its compiled tags query.
+struct Grammar {
+    language: Language,
+    query: Query,
+}
+
+/// Compiled once per language; `None` if the query does not compile for the grammar.
+fn grammar(lang: Lang) -> Option<&'static Grammar> {
+    static GRAMMARS: [OnceLock<Option<Grammar>>; 7] = [const { OnceLock::new() }; 7];
+    GRAMMARS[lang as usize]
+        .get_or_init(|| {
+            let (language, tags): (Language, String) = match lang {
+                Lang::Rust => (tree_sitter_rust::LANGUAGE.into(), tree_sitter_rust::TAGS_QUERY.into()),
+                Lang::Python => (tree_sitter_python::LANGUAGE.into(), tree_sitter_python::TAGS_QUERY.into()),
+                Lang::JavaScript => (tree_sitter_javascript::LANGUAGE.into(), tree_sitter_javascript::TAGS_QUERY.into()),
+                // TypeScript's tags add to JavaScript's.
+                Lang::TypeScript | Lang::Tsx => (
+                    match lang {
+                        Lang::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+                        _ => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+                    },
+                    format!("{}\n{}", tree_sitter_javascript::TAGS_QUERY, tree_sitter_typescript::TAGS_QUERY),
+                ),
+                Lang::Go => (tree_sitter_go::LANGUAGE.into(), tree_sitter_go::TAGS_QUERY.into()),
+                Lang::Java => (tree_sitter_java::LANGUAGE.into(), tree_sitter_java::TAGS_QUERY.into()),
+            };
+            match Query::new(&language, &tags) {
+                Ok(query) => Some(Grammar { language, query }),
+                Err(e) => {
+                    tracing::warn!("Symbol query does not compile, skipping the language: {e}");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// One definition matched by the tags query.
+struct Def<'t> {
+    node: Node<'t>,
+    name: String,
+    kind: &'static str,
+    pattern: usize,
+}
+
+/// Definitions in `text`, a file at `path`, in source order (empty for other languages).
+pub fn extract(path: &str, text: &str) -> Vec<Symbol> {
+    let Some(grammar) = Lang::of(Path::new(path)).and_then(grammar) else {
+        return vec![];
+    };
+    if text.len() > MAX_SOURCE_BYTES {
+        return vec![];
+    }
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar.language).is_err() {
+        return vec![];
+    }
+    let Some(tree) = parser.parse(text, None) else {
+        return vec![];
+    };
+
+    let names = grammar.query.capture_names();
+    let mut defs = vec![];
+    let mut cursor = QueryCursor::new();
+    let mut found = cursor.matches(&grammar.query, tree.root_node(), text.as_bytes());
+    while let Some(m) = found.next() {
+        let mut name = None;
+        let mut def = None;
+        for c in m.captures {
+            match names[c.index as usize] {
+                "name" => name = Some(c.node),
+                capture => {
+                    if let Some(kind) = capture.strip_prefix("definition.") {
+                        def = Some((c.node, kind));
+                    }
+                }
+            }
+        }
+        let (Some(name), Some((node, kind))) = (name, def) else {
+            continue;
+        };
+        defs.push(Def {
+            node,
+            name: text[name.byte_range()].to_string(),
+            kind: refine_kind(kind, node.kind()),
+            pattern: m.pattern_index,
+        });
+    }
+    // Several patterns can match one node (Rust methods are also functions): the first wins.
+    defs.sort_by_key(|d| (d.node.start_byte(), std::cmp::Reverse(d.node.end_byte()), d.pattern));
+    defs.dedup_by_key(|d| d.node.id());
+
+    let by_node: HashMap<usize, (&str, &str)> = defs.iter().map(|d| (d.node.id(), (d.name.as_str(), d.kind))).collect();
+    defs.iter()
+        .map(|d| {
+            let container = container_of(d.node, &by_node, text);
+            let in_type = container.as_ref().is_some_and(|(_, kind)| TYPE_KINDS.contains(kind));
+            // Python methods are plain functions, Rust functions in a `mod` match as methods.
+            let kind = match d.kind {
+                "function" if in_type => "method",
+                "method" if !in_type && d.node.kind() == "function_item" => "function",
+                kind => kind,
+            };
+            Symbol {
+                name: d.name.clone(),
+                kind: kind.to_string(),
+                line: d.node.start_position().row + 1,
+                end_line: d.node.end_position().row + 1,
+                container: container.map(|(name, _)| name),
+                signature: signature(&text[d.node.byte_range()]),
+            }
+        })
+        .collect()
+}
+
+/// Kinds whose functions are methods.
+const TYPE_KINDS: &[&str] = &["class", "struct", "enum", "union", "interface", "type"];
+
+/// Tags queries call all Rust types `class`; name them after the item.
+fn refine_kind(kind: &str, node_kind: &str) -> &'static str {
+    match node_kind {
+        "struct_item" => "struct",
+        "enum_item" => "enum",
+        "union_item" => "union",
+        "type_item" | "type_spec" => "type",
+        _ => match kind {
+            "function" => "function",
+            "method" => "method",
+            "class" => "class",
+            "interface" => "interface",
+            "module" => "module",
+            "macro" => "macro",
+            "constant" => "constant",
+            _ => "other",
+        },
+    }
+}
+
+/// Name and kind of the enclosing type or module: the nearest enclosing definition, the type of a
+/// Rust `impl`, or the receiver type of a Go method.
+fn container_of<'a>(node: Node, defs: &HashMap<usize, (&str, &'a str)>, text: &str) -> Option<(String, &'a str)> {
+    // `impl<T> Foo<T>`, `func (s *Foo[T])`: just `Foo`.
+    let type_name = |ty: &str| {
+        let ty = ty.trim_matches(['(', ')']).split_whitespace().last().unwrap_or_default();
+        ty.trim_start_matches(['*', '&']).split(['<', '[']).next().unwrap_or_default().to_string()
+    };
+    let receiver = node.child_by_field_name("receiver").filter(|_| node.kind() == "method_declaration");
+    if let Some(receiver) = receiver {
+        return Some((type_name(&text[receiver.byte_range()]), "type"));
+    }
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if let Some((name, kind)) = defs.get(&parent.id()) {
+            return Some((name.to_string(), *kind));
+        }
+        if parent.kind() == "impl_item" {
+            let ty = parent.child_by_field_name("type")?;
+            return Some((type_name(&text[ty.byte_range()]), "type"));
+        }
+        current = parent.parent();
+    }
+    None
+}
+
+fn signature(definition: &str) -> String {
+    let line = definition.lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(SIGNATURE_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
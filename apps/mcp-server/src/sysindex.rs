@@ -0,0 +1,98 @@
+//! OS file indexes as a candidate source (`use_system_index` in the filesystem source config).
+//!
+//! Spotlight (`mdfind`) on macOS and `plocate` on Linux can list every file with an allowed
+//! extension under a root in seconds, instead of walking the whole tree. The listed files still
+//! go through the normal per-file policy checks (excludes, size, placeholders). Directory-level
+//! rules (skip markers, depth and per-directory limits) do not apply to listed files, and a
+//! `plocate` database only knows files as of its last `updatedb` run.
+//!
+//! Any failure (tool missing, error, timeout, no results) falls back to walking that root.
+
+use crate::config::CompiledFileSystemPolicy;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const LIST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Initial scan stack: listed files for roots the OS index covers, the root itself otherwise.
+/// Also returns the roots that were listed.
+pub async fn initial_stack(roots: &[PathBuf], policy: &CompiledFileSystemPolicy) -> (Vec<(PathBuf, usize)>, Vec<String>) {
+    let mut stack = vec![];
+    let mut listed_roots = vec![];
+    for root in roots {
+        let listed = if policy.use_system_index {
+            list_files(root, &policy.allow_extensions).await
+        } else {
+            None
+        };
+        match listed {
+            Some(files) => {
+                tracing::info!("Listed {} files under {} from the OS index", files.len(), root.display());
+                listed_roots.push(root.to_string_lossy().to_string());
+                stack.extend(files.into_iter().map(|f| (f, 1)));
+            }
+            None => stack.push((root.clone(), 0)),
+        }
+    }
+    (stack, listed_roots)
+}
+
+/// Files under `root` with one of `extensions`, from the OS index (`None` = walk instead).
+pub async fn list_files(root: &Path, extensions: &[String]) -> Option<Vec<PathBuf>> {
+    if extensions.is_empty() {
+        return None;
+    }
+    let mut cmd = query_command(root, extensions)?;
+    cmd.kill_on_drop(true);
+    let out = tokio::time::timeout(LIST_TIMEOUT, cmd.output()).await.ok()?.ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let files: Vec<PathBuf> = out
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).to_string()))
+        .filter(|p| p.starts_with(root))
+        .collect();
+    (!files.is_empty()).then_some(files)
+}
+
+#[cfg(target_os = "macos")]
+fn query_command(root: &Path, extensions: &[String]) -> Option<tokio::process::Command> {
+    let query = extensions
+        .iter()
+        .map(|e| format!("kMDItemFSName == \"*.{e}\"c"))
+        .collect::<Vec<_>>()
+        .join(" || ");
+    let mut cmd = tokio::process::Command::new("mdfind");
+    cmd.arg("-0").arg("-onlyin").arg(root).arg(query);
+    Some(cmd)
+}
+
+#[cfg(target_os = "linux")]
+fn query_command(root: &Path, extensions: &[String]) -> Option<tokio::process::Command> {
+    let root = root.to_str()?.trim_end_matches('/');
+    let exts = extensions.iter().map(|e| regex_escape(e)).collect::<Vec<_>>().join("|");
+    let pattern = format!("^{}/.*\\.({exts})$", regex_escape(root));
+    let mut cmd = tokio::process::Command::new("plocate");
+    cmd.arg("-0").arg("-i").arg("--regex").arg(pattern);
+    Some(cmd)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn query_command(_root: &Path, _extensions: &[String]) -> Option<tokio::process::Command> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn regex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,354 @@
+//! Automatic document tagging (`silo_tag_documents`, `silo_list_tags`).
+//!
+//! A background job walks the catalog and gives each untagged (or re-indexed) document 3-5
+//! short tags. Embeddings keep the vocabulary consistent: the tags of the most similar,
+//! already-tagged documents are offered to the LLM as candidates, and used to top up answers
+//! with too few tags. Tags are written to the chunk table's `tags` column (for search filters)
+//! and to the catalog (for listing). User tags read from extended attributes at index time
+//! (`crate::xattrs`) share the column and the listing.
+
+use crate::catalog::now_epoch_secs;
+use crate::database::{tags_filter, SearchOptions};
+use crate::errors::ToolError;
+use crate::llm::truncate_to_llm_tokens;
+use crate::predicate::Predicate;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+pub const MIN_TAGS: usize = 3;
+pub const MAX_TAGS: usize = 5;
+const MAX_TAG_CHARS: usize = 32;
+
+/// Document excerpt shown to the LLM (and embedded to find similar documents).
+const EXCERPT_TOKENS: usize = 800;
+/// Nearest chunks from other tagged documents used for candidate tags.
+const NEIGHBOR_HITS: usize = 12;
+const MAX_CANDIDATES: usize = 15;
+/// Persist the catalog every this many documents so a crash does not lose a long run.
+const SAVE_EVERY: usize = 20;
+
+const TAG_PROMPT: &str = "You assign topic tags to documents.\n\
+Return ONLY a JSON array of 3 to 5 short lowercase tags (1-3 words each, e.g. [\"tax\", \"invoices\", \"2023\"]).\n\
+Prefer reusing these existing tags when they fit: {candidates}\n\n\
+Document: {name}\n\
+---\n\
+{excerpt}\n\
+---\n";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TagJobStatus {
+    pub running: bool,
+    /// Documents selected for this run.
+    pub total: usize,
+    pub processed: usize,
+    pub tagged: usize,
+    pub failed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at_epoch_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at_epoch_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub documents: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagList {
+    pub tags: Vec<TagCount>,
+    pub tagged_documents: usize,
+    pub untagged_documents: usize,
+    pub job: TagJobStatus,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TagDocumentsArgs {
+    /// Re-tag documents that already have tags.
+    #[serde(default)]
+    retag: bool,
+    #[serde(default)]
+    max_docs: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ListTagsArgs {
+    #[serde(default)]
+    prefix: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// MCP tool entrypoint (`silo_tag_documents`).
+pub async fn tag_documents_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: TagDocumentsArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let started = start_tagging(state, args.retag, args.max_docs)?;
+    Ok(json!({ "started": started, "job": state.tag_job_status() }))
+}
+
+/// MCP tool entrypoint (`silo_list_tags`).
+pub async fn list_tags_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: ListTagsArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let out = list_tags(state, args.prefix.as_deref(), args.limit.unwrap_or(100)).await;
+    serde_json::to_value(out).map_err(|e| format!("failed to serialize tags: {e}").into())
+}
+
+/// Start the background job. Returns `false` when a run is already in progress.
+pub fn start_tagging(state: &SharedState, retag: bool, max_docs: Option<usize>) -> Result<bool, ToolError> {
+    if !state.db.is_enabled() {
+        let reason = state.db.disabled_reason().unwrap_or("unknown reason");
+        return Err(ToolError::kb_disabled(reason));
+    }
+    if state.llm.model_id() == "none" {
+        return Err("Tagging needs a local LLM. Set SILO_LLM_BACKEND=ollama and SILO_LLM_MODEL".into());
+    }
+    let claimed = state.update_tag_job(|job| {
+        if job.running {
+            return false;
+        }
+        *job = TagJobStatus {
+            running: true,
+            started_at_epoch_secs: Some(now_epoch_secs()),
+            ..TagJobStatus::default()
+        };
+        true
+    });
+    if claimed {
+        let state = state.clone();
+        tokio::spawn(async move { run_job(&state, retag, max_docs).await });
+    }
+    Ok(claimed)
+}
+
+/// Kick off tagging after a bulk index when `tagging.auto` is set (best effort).
+pub async fn after_index(state: &SharedState) {
+    if !state.config.read().await.tagging.auto {
+        return;
+    }
+    if let Err(e) = start_tagging(state, false, None) {
+        tracing::warn!("Automatic tagging not started: {e}");
+    }
+}
+
+pub async fn list_tags(state: &SharedState, prefix: Option<&str>, limit: usize) -> TagList {
+    let prefix = prefix.map(|p| p.trim().to_lowercase()).unwrap_or_default();
+    let catalog = state.catalog.read().await;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let (mut tagged, mut untagged) = (0, 0);
+    for e in catalog.entries() {
+        let all = e.all_tags();
+        if all.is_empty() {
+            untagged += 1;
+            continue;
+        }
+        tagged += 1;
+        for t in all.into_iter().filter(|t| t.starts_with(&prefix)) {
+            *counts.entry(t).or_default() += 1;
+        }
+    }
+    let mut tags: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, documents)| TagCount { tag, documents })
+        .collect();
+    tags.sort_by(|a, b| b.documents.cmp(&a.documents).then_with(|| a.tag.cmp(&b.tag)));
+    tags.truncate(limit.clamp(1, 1000));
+    TagList {
+        tags,
+        tagged_documents: tagged,
+        untagged_documents: untagged,
+        job: state.tag_job_status(),
+    }
+}
+
+/// Search filter for documents carrying every tag (tags are normalized first).
+pub fn search_filter(tags: &[String]) -> Option<Predicate> {
+    let tags = normalize_tags(tags.iter().map(String::as_str));
+    (!tags.is_empty()).then(|| tags_filter(&tags))
+}
+
+async fn run_job(state: &SharedState, retag: bool, max_docs: Option<usize>) {
+    let paths: Vec<String> = {
+        let catalog = state.catalog.read().await;
+        catalog
+            .entries()
+            .filter(|e| e.is_file() && !e.is_evicted())
+            .filter(|e| retag || e.tagged_at_epoch_secs.is_none_or(|t| t < e.indexed_at_epoch_secs))
+            .map(|e| e.path.clone())
+            .take(max_docs.unwrap_or(usize::MAX))
+            .collect()
+    };
+    state.update_tag_job(|job| job.total = paths.len());
+
+    for (i, path) in paths.iter().enumerate() {
+        let res = match tag_document(state, path).await {
+            Ok(tags) => {
+                // The column also carries the file's own (extended attribute) tags.
+                let mut column = state
+                    .catalog
+                    .read()
+                    .await
+                    .get(path)
+                    .map(|e| e.file_tags.clone())
+                    .unwrap_or_default();
+                for t in &tags {
+                    if !column.contains(t) {
+                        column.push(t.clone());
+                    }
+                }
+                state
+                    .db
+                    .set_file_tags(path, &column)
+                    .await
+                    .map(|_| tags)
+                    .map_err(|e| format!("DB write failed (tags): {e}"))
+            }
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(tags) => {
+                state.catalog.write().await.set_tags(path, tags);
+                state.update_tag_job(|job| job.tagged += 1);
+            }
+            Err(e) => {
+                tracing::warn!("Tagging {path} failed: {e}");
+                state.update_tag_job(|job| {
+                    job.failed += 1;
+                    job.last_error = Some(format!("{path}: {e}"));
+                });
+            }
+        }
+        state.update_tag_job(|job| job.processed += 1);
+        if (i + 1) % SAVE_EVERY == 0 {
+            state.save_catalog().await;
+        }
+    }
+
+    state.save_catalog().await;
+    state.index_changed();
+    state.update_tag_job(|job| {
+        job.running = false;
+        job.finished_at_epoch_secs = Some(now_epoch_secs());
+    });
+}
+
+async fn tag_document(state: &SharedState, path: &str) -> Result<Vec<String>, String> {
+    let max_text_bytes = state
+        .fs_policy
+        .read()
+        .await
+        .as_ref()
+        .map(|p| p.max_text_bytes)
+        .unwrap_or(2 * 1024 * 1024);
+    let extracted = crate::extract::extract_text(std::path::Path::new(path), max_text_bytes).await?;
+    let excerpt = truncate_to_llm_tokens(extracted.text.trim(), EXCERPT_TOKENS);
+    if excerpt.is_empty() {
+        return Err("no text could be extracted".to_string());
+    }
+
+    let candidates = candidate_tags(state, path, &excerpt).await?;
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    let prompt = TAG_PROMPT
+        .replace("{candidates}", &if candidates.is_empty() { "(none yet)".to_string() } else { candidates.join(", ") })
+        .replace("{name}", &name)
+        .replace("{excerpt}", &excerpt);
+    let raw = state.llm.generate(prompt).await?;
+
+    let mut tags = normalize_tags(parse_tag_list(&raw).iter().map(String::as_str));
+    for c in &candidates {
+        if tags.len() >= MIN_TAGS {
+            break;
+        }
+        if !tags.contains(c) {
+            tags.push(c.clone());
+        }
+    }
+    if tags.is_empty() {
+        return Err("LLM returned no usable tags".to_string());
+    }
+    Ok(tags)
+}
+
+/// Tags of the documents most similar to `excerpt`, most frequent first.
+async fn candidate_tags(state: &SharedState, path: &str, excerpt: &str) -> Result<Vec<String>, String> {
+    let vecs = state.embedder.embed_texts(vec![excerpt.to_string()]).await?;
+    let Some(qvec) = vecs.into_iter().next() else {
+        return Ok(vec![]);
+    };
+    let opts = SearchOptions {
+        top_k: NEIGHBOR_HITS,
+        filter: Some(Predicate::ne("path", path).and(Predicate::is_not_null("tags"))),
+        ..SearchOptions::default()
+    };
+    let hits = state
+        .db
+        .search_chunks_by_vector(&qvec, &opts)
+        .await
+        .map_err(|e| format!("DB search failed: {e}"))?;
+
+    let catalog = state.catalog.read().await;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut seen_paths = vec![];
+    for h in &hits {
+        if seen_paths.contains(&h.path) {
+            continue;
+        }
+        seen_paths.push(h.path.clone());
+        for t in catalog.get(&h.path).map(|e| e.all_tags()).unwrap_or_default() {
+            *counts.entry(t).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(ranked.into_iter().take(MAX_CANDIDATES).map(|(t, _)| t).collect())
+}
+
+/// JSON array if the model returned one, else comma/newline separated text.
+fn parse_tag_list(raw: &str) -> Vec<String> {
+    if let (Some(start), Some(end)) = (raw.find('['), raw.rfind(']'))
+        && start < end
+        && let Ok(v) = serde_json::from_str::<Vec<String>>(&raw[start..=end])
+    {
+        return v;
+    }
+    raw.split([',', '\n'])
+        .map(|t| t.trim_start_matches(['-', '*', ' ']).to_string())
+        .collect()
+}
+
+/// Lowercase, kebab-case, deduplicated, at most `MAX_TAGS`.
+pub fn normalize_tags<'a>(raw: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    for t in raw {
+        let tag = normalize_tag(t);
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+        if out.len() == MAX_TAGS {
+            break;
+        }
+    }
+    out
+}
+
+pub(crate) fn normalize_tag(raw: &str) -> String {
+    let mut tag = String::new();
+    for c in raw.trim().trim_matches(['"', '\'', '#']).chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            tag.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !tag.is_empty() && !tag.ends_with('-') {
+            tag.push('-');
+        }
+        if tag.chars().count() >= MAX_TAG_CHARS {
+            break;
+        }
+    }
+    tag.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,196 @@
+//! Activity timeline: indexed documents bucketed by date (`silo_timeline`).
+//!
+//! Works purely off the file catalog (no DB scan), so it is cheap enough to call on every
+//! UI render. Buckets use the local time zone, since "what did I work on last Tuesday" is a
+//! local-time question.
+
+use crate::catalog::CatalogEntry;
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineField {
+    #[default]
+    Modified,
+    Created,
+    Indexed,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineArgs {
+    #[serde(default)]
+    pub granularity: Granularity,
+    #[serde(default)]
+    pub field: TimelineField,
+    /// Inclusive lower bound (`YYYY-MM-DD` or epoch seconds).
+    #[serde(default)]
+    pub from: Option<Value>,
+    /// Inclusive upper bound (`YYYY-MM-DD` or epoch seconds).
+    #[serde(default)]
+    pub to: Option<Value>,
+    /// Items listed per bucket (most recent first).
+    #[serde(default)]
+    pub top_n: Option<usize>,
+    /// Most recent buckets to return.
+    #[serde(default)]
+    pub max_buckets: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineItem {
+    pub path: String,
+    pub name: String,
+    pub epoch_secs: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineBucket {
+    /// `2024-05-03`, `2024-W18` or `2024-05`.
+    pub key: String,
+    pub start_epoch_secs: i64,
+    pub count: usize,
+    pub total_bytes: i64,
+    pub top: Vec<TimelineItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    pub granularity: Granularity,
+    pub field: TimelineField,
+    /// Oldest bucket first.
+    pub buckets: Vec<TimelineBucket>,
+    /// Catalog files without a date for `field` (e.g. no creation time on this filesystem).
+    pub undated: usize,
+}
+
+/// MCP tool entrypoint (`silo_timeline`).
+pub async fn timeline_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: TimelineArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let t = timeline(state, &args).await?;
+    serde_json::to_value(t).map_err(|e| format!("failed to serialize timeline: {e}").into())
+}
+
+pub async fn timeline(state: &SharedState, args: &TimelineArgs) -> Result<Timeline, String> {
+    let from = args.from.as_ref().map(|v| parse_bound(v, false)).transpose()?;
+    let to = args.to.as_ref().map(|v| parse_bound(v, true)).transpose()?;
+    let top_n = args.top_n.unwrap_or(5).min(50);
+    let max_buckets = args.max_buckets.unwrap_or(52).clamp(1, 1000);
+
+    let catalog = state.catalog.read().await;
+    let mut undated = 0;
+    let mut buckets: BTreeMap<i64, (String, Vec<&CatalogEntry>)> = BTreeMap::new();
+    for e in catalog.entries() {
+        let Some(ts) = entry_time(e, args.field) else {
+            undated += 1;
+            continue;
+        };
+        if from.is_some_and(|f| ts < f) || to.is_some_and(|t| ts > t) {
+            continue;
+        }
+        let Some((start, key)) = bucket_of(ts, args.granularity) else {
+            undated += 1;
+            continue;
+        };
+        buckets.entry(start).or_insert_with(|| (key, vec![])).1.push(e);
+    }
+
+    let skip = buckets.len().saturating_sub(max_buckets);
+    let buckets = buckets
+        .into_iter()
+        .skip(skip)
+        .map(|(start, (key, mut entries))| {
+            entries.sort_by_key(|e| std::cmp::Reverse(entry_time(e, args.field)));
+            TimelineBucket {
+                key,
+                start_epoch_secs: start,
+                count: entries.len(),
+                total_bytes: entries.iter().filter_map(|e| e.size_bytes).sum(),
+                top: entries
+                    .iter()
+                    .take(top_n)
+                    .map(|e| TimelineItem {
+                        path: e.path.clone(),
+                        name: Path::new(&e.path)
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| e.path.clone()),
+                        epoch_secs: entry_time(e, args.field).unwrap_or_default(),
+                        size_bytes: e.size_bytes,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(Timeline {
+        granularity: args.granularity,
+        field: args.field,
+        buckets,
+        undated,
+    })
+}
+
+fn entry_time(e: &CatalogEntry, field: TimelineField) -> Option<i64> {
+    match field {
+        TimelineField::Modified => e.modified_epoch_secs,
+        TimelineField::Created => e.created_epoch_secs,
+        TimelineField::Indexed => Some(e.indexed_at_epoch_secs),
+    }
+}
+
+/// Bucket start (local midnight, as epoch secs) and display key.
+fn bucket_of(ts: i64, granularity: Granularity) -> Option<(i64, String)> {
+    let date = DateTime::from_timestamp(ts, 0)?.with_timezone(&Local).date_naive();
+    let (start, key) = match granularity {
+        Granularity::Day => (date, date.format("%Y-%m-%d").to_string()),
+        Granularity::Week => {
+            let week = date.iso_week();
+            let monday = NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Mon)?;
+            (monday, format!("{}-W{:02}", week.year(), week.week()))
+        }
+        Granularity::Month => (date.with_day(1)?, date.format("%Y-%m").to_string()),
+    };
+    Some((local_midnight(start)?, key))
+}
+
+pub(crate) fn local_midnight(date: NaiveDate) -> Option<i64> {
+    let naive = date.and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+/// `YYYY-MM-DD` (start or end of that local day) or epoch seconds.
+fn parse_bound(v: &Value, end_of_day: bool) -> Result<i64, String> {
+    if let Some(n) = v.as_i64() {
+        return Ok(n);
+    }
+    let s = v
+        .as_str()
+        .ok_or_else(|| format!("Invalid date bound {v}: expected YYYY-MM-DD or epoch seconds"))?;
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {s:?}: {e} (expected YYYY-MM-DD)"))?;
+    let day = if end_of_day { date.succ_opt() } else { Some(date) };
+    let start = day
+        .and_then(local_midnight)
+        .ok_or_else(|| format!("Invalid date {s:?}"))?;
+    Ok(if end_of_day { start - 1 } else { start })
+}
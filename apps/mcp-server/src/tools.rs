@@ -1,7 +1,12 @@
-use crate::database::DatabaseHandle;
+use crate::database::{ChunkSource, DatabaseHandle, SearchHit, SearchOptions, SEARCH_METRIC};
+use crate::errors::ToolError;
+use crate::fields::SearchField;
+use crate::render::ResultFormat;
+use crate::rewrite::{RewriteMode, RewriteOptions};
 use crate::state::{expand_tilde, SharedState};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize)]
@@ -30,6 +35,82 @@ pub struct ToolResultContent {
 pub struct ToolResult {
     pub content: Vec<ToolResultContent>,
     pub is_error: bool,
+    /// Code and values of the error (its English text is the content), for translation.
+    pub error: Option<ToolError>,
+}
+
+tokio::task_local! {
+    /// Set for the calls of an MCP session that asked to be read-only (`crate::daemon`).
+    pub static SESSION_READ_ONLY: bool;
+}
+
+/// Tools that change the index, config, downloaded models or files on disk. Hidden from
+/// `tools/list` and refused in read-only mode (`read_only`).
+pub const WRITE_TOOLS: &[&str] = &[
+    "silo_index_home",
+    "silo_ingest_file",
+    "silo_ingest_content",
+    "silo_ingest_url",
+    "silo_set_index_roots",
+    "silo_update_config",
+    "silo_document_password_set",
+    "silo_document_password_delete",
+    "silo_secret_set",
+    "silo_secret_delete",
+    "silo_purge_deleted",
+    "silo_index_messages",
+    "silo_index_screenshots",
+    "silo_index_newsletters",
+    "silo_index_library",
+    "silo_import_chat_export",
+    "silo_tag_documents",
+    "silo_graph_rebuild",
+    "silo_index_budget",
+    "silo_maintenance",
+    "silo_backup_settings",
+    "silo_bench_ingest",
+    "silo_models_pull",
+    "silo_models_remove",
+    "silo_write_file",
+    "silo_append_note",
+];
+
+pub fn is_write_tool(name: &str) -> bool {
+    WRITE_TOOLS.contains(&name)
+}
+
+/// Read-only mode for the current call: the server's (`AppState::read_only`) or the session's.
+pub fn read_only(state: &SharedState) -> bool {
+    state.read_only || SESSION_READ_ONLY.try_with(|r| *r).unwrap_or(false)
+}
+
+/// The tools this server runs: all of them, or only the read/search ones in read-only mode.
+/// `silo_write_file` is only listed when writes are enabled, `silo_append_note` when a notes
+/// folder is configured, `silo_ingest_url` when web ingestion is enabled.
+pub fn available_tool_definitions(state: &SharedState) -> Vec<ToolDefinition> {
+    tool_definitions()
+        .into_iter()
+        .filter(|t| !(read_only(state) && is_write_tool(t.name)))
+        .filter(|t| t.name != "silo_write_file" || state.file_writes.is_some())
+        .filter(|t| t.name != "silo_append_note" || state.notes.is_some())
+        .filter(|t| t.name != "silo_ingest_url" || state.web.is_some())
+        .collect()
+}
+
+/// `available_tool_definitions` with descriptions in the configured locale (`crate::i18n`), for
+/// `tools/list`.
+pub async fn localized_tool_definitions(state: &SharedState) -> Vec<Value> {
+    let messages = state.messages().await;
+    available_tool_definitions(state)
+        .into_iter()
+        .map(|t| {
+            json!({
+                "name": t.name,
+                "description": messages.tool_description(t.name).unwrap_or(t.description),
+                "inputSchema": t.input_schema,
+            })
+        })
+        .collect()
 }
 
 pub fn tool_definitions() -> Vec<ToolDefinition> {
@@ -46,6 +127,51 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "silo_ask",
+            description: "Answers a question from your indexed files (retrieve top chunks -> local LLM), with numbered citations.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "question": { "type": "string", "description": "Natural language question." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 20, "default": 5, "description": "Number of chunks to retrieve as context." },
+                    "min_score": { "type": "number", "minimum": -1, "maximum": 1 },
+                    "format": { "type": "string", "enum": ["json", "markdown", "plain"], "description": "json: the answer object; markdown/plain: the answer text followed by its numbered sources. Default: result_format in the config." }
+                },
+                "required": ["question"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_chat",
+            description: "Multi-turn chat over your indexed files. Keeps history and previously retrieved sources per session_id, so follow-up questions work. Omit session_id to start a new session.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session_id": { "type": "string", "description": "Session to continue (returned by the first call)." },
+                    "message": { "type": "string", "description": "User message." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 20, "default": 5 },
+                    "history": { "type": "boolean", "default": false, "description": "Return the session history instead of sending a message." },
+                    "reset": { "type": "boolean", "default": false, "description": "Forget the session instead of sending a message." },
+                    "format": { "type": "string", "enum": ["json", "markdown", "plain"], "description": "json: the reply object; markdown/plain: the answer text, its numbered sources and the session_id. Default: result_format in the config." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_extract_structured",
+            description: "Extracts structured fields from one document (e.g. invoice number, total, due date) using the local LLM. Returns an object validated against the given JSON schema.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File to extract from (supports ~/ prefix and PDFs)." },
+                    "schema": { "type": "object", "description": "JSON schema of the fields to extract: {\"type\":\"object\",\"properties\":{...},\"required\":[...]}." },
+                    "instructions": { "type": "string", "description": "Optional extra guidance (date format, currency, ...)." }
+                },
+                "required": ["path", "schema"],
+                "additionalProperties": false
+            }),
+        },
         ToolDefinition {
             name: "silo_list_files",
             description: "Scans a local folder non-recursively.",
@@ -84,17 +210,195 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "silo_search",
-            description: "Semantic search over indexed chunks (embed query + vector search).",
+            description: "Semantic search over indexed chunks (embed query + vector search). Scores are cosine similarity (higher is better). Identical chunks in several files come back as one hit listing the other files in duplicate_paths. While an index run is in progress the result includes index_progress (results may be incomplete).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to search for. Also accepts \"quoted phrases\" (must appear verbatim), path:, ext:, lang:, tag: and date: filters (date:>2024-01-01, date:2024-03), time: for when log lines or chat messages were written (time:2024-05-14T15, time:2024-05-14T14:30..2024-05-14T15:30, local time), AND/OR/NOT, -word exclusions and parentheses." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 50, "default": 10 },
+                    "min_score": { "type": "number", "minimum": -1, "maximum": 1, "description": "Minimum cosine similarity (higher is better) for a hit to be returned." },
+                    "entity": { "type": "string", "description": "Only search chunks that mention this entity (exact name, case-insensitive; requires the entity stage)." },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "Only search documents carrying all of these tags (see silo_list_tags)." },
+                    "sources": { "type": "array", "items": { "type": "string", "enum": ["filesystem", "messages", "whatsapp", "signal", "upload", "web"] }, "description": "Only search chunks from these connectors. Each hit reports its source." },
+                    "prefer_recent": { "type": "boolean", "description": "Rank recently modified (and, if configured, often accessed) files higher among similar hits; defaults to the ranking config (useful while a first index run is still going: see index_progress in the result)." },
+                    "fields": { "type": "array", "items": { "type": "string", "enum": ["title", "body"] }, "description": "Where the query should match: title (the file name) and/or body (chunk text). Default both; [\"title\"] only searches files whose name contains a query term." },
+                    "correct_typos": { "type": "boolean", "description": "Replace query words missing from the indexed files with a close, frequent word. Default: query_rewrite.correct_typos in the config." },
+                    "expand_synonyms": { "type": "boolean", "description": "Add synonyms suggested by the local LLM to the query. Default: query_rewrite.expand_synonyms in the config." },
+                    "federated": { "type": "boolean", "description": "Search each collection, chat history (messages) and the remaining files (files) separately and merge them by per-partition normalized, weighted scores; hits carry their collection. Default: federation.enabled in the config." },
+                    "rewrite": { "type": "string", "enum": ["none", "hyde"], "description": "hyde: the local LLM drafts a hypothetical answer passage, which is embedded instead of the query (better recall for short questions, one LLM call). Default: query_rewrite.mode in the config." },
+                    "preview_chars": { "type": "integer", "minimum": 0, "description": "Length of each hit's content_preview (0: none). Default: hits.preview_chars (240); capped at hits.max_preview_chars." },
+                    "include_full_content": { "type": "boolean", "default": false, "description": "Add each hit's full chunk text as content, up to hits.max_content_chars over all hits (content_truncated is set when text was clipped)." },
+                    "format": { "type": "string", "enum": ["json", "markdown", "plain"], "description": "json: the result object; markdown/plain: readable text blocks per hit (path, score, page, preview). Default: result_format in the config." }
+                },
+                "required": ["query"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_search_entities",
+            description: "Finds people, organizations and projects mentioned in indexed files (e.g. everything involving \"Acme Corp\"), with the files and chunks that mention them. Requires `entities.enabled` in the config.",
             input_schema: json!({
                 "type": "object",
                 "properties": {
-                    "query": { "type": "string" },
-                    "top_k": { "type": "integer", "minimum": 1, "maximum": 50, "default": 10 }
+                    "query": { "type": "string", "description": "Entity name or part of it (case-insensitive)." },
+                    "kind": { "type": "string", "enum": ["person", "org", "project", "other"] },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 200, "default": 20 }
                 },
                 "required": ["query"],
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "silo_graph_neighbors",
+            description: "Knowledge graph: entities and documents connected to a node (entity name, document path, or node id), strongest links first. Built from extracted entities.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "node": { "type": "string", "description": "Entity name (e.g. \"Acme Corp\"), document path, or node id." },
+                    "depth": { "type": "integer", "minimum": 1, "maximum": 3, "default": 1 },
+                    "kind": { "type": "string", "enum": ["entity", "document"], "description": "Only return neighbors of this kind." },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 500, "default": 25 }
+                },
+                "required": ["node"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_graph_path",
+            description: "Knowledge graph: shortest chain of documents/entities connecting two nodes (\"how are these two projects connected?\").",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "from": { "type": "string" },
+                    "to": { "type": "string" },
+                    "max_hops": { "type": "integer", "minimum": 1, "maximum": 8, "default": 4 }
+                },
+                "required": ["from", "to"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_graph_rebuild",
+            description: "Rebuilds the knowledge graph from the entities table (normally automatic after indexing).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_debug_retrieval",
+            description: "Explains why a file is or isn't in the results for a query: whether it is indexed (else how the indexing policy treats it), which filters its chunks fail, its best chunk's similarity, and its rank among the hits passing the filters against top_k and min_score.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "The search query (same syntax as silo_search)." },
+                    "path": { "type": "string", "description": "File expected in the results (supports ~/ prefix)." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 50, "default": 10 },
+                    "min_score": { "type": "number" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "sources": { "type": "array", "items": { "type": "string", "enum": ["filesystem", "messages", "whatsapp", "signal", "upload", "web"] } }
+                },
+                "required": ["query", "path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_search_files",
+            description: "Fast keyword search over indexed file names/paths (no embeddings; suitable for search-as-you-type).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Space-separated keywords; each must appear in the path." },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 200, "default": 20 }
+                },
+                "required": ["query"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_get_chunk",
+            description: "Fetch one indexed chunk (by `id`, or `path` + `chunk_index` from a search hit) with its metadata and the neighboring chunks of the same file.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Chunk row id." },
+                    "path": { "type": "string", "description": "Indexed path (with `chunk_index`, instead of `id`)." },
+                    "chunk_index": { "type": "integer", "minimum": 0 },
+                    "neighbors": { "type": "integer", "minimum": 0, "maximum": 5, "default": 1, "description": "Chunks to include before and after." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_timeline",
+            description: "Activity timeline of indexed files: counts, total size and most recent files per day/week/month, by modified, created or indexed date.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "granularity": { "type": "string", "enum": ["day", "week", "month"], "default": "week" },
+                    "field": { "type": "string", "enum": ["modified", "created", "indexed"], "default": "modified" },
+                    "from": { "type": ["string", "integer"], "description": "Inclusive start: YYYY-MM-DD (local) or epoch seconds." },
+                    "to": { "type": ["string", "integer"], "description": "Inclusive end: YYYY-MM-DD (local) or epoch seconds." },
+                    "top_n": { "type": "integer", "minimum": 0, "maximum": 50, "default": 5 },
+                    "max_buckets": { "type": "integer", "minimum": 1, "maximum": 1000, "default": 52 }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_disk_report",
+            description: "Storage insights for the configured roots: largest folders and files, size per extension, and folder growth since the previous report. Metadata only (no extraction).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "max_depth": { "type": "integer", "minimum": 1, "maximum": 10, "default": 3, "description": "Folder levels below each root to aggregate." },
+                    "top_n": { "type": "integer", "minimum": 1, "maximum": 500, "default": 20 },
+                    "include_excluded": { "type": "boolean", "default": false, "description": "Also count paths matched by the exclude globs (node_modules, caches, ...)." },
+                    "max_files": { "type": "integer", "minimum": 1 }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_cleanup_suggestions",
+            description: "Read-only cleanup candidates under the configured roots: large files untouched for N months, duplicate groups among indexed files, and large cache directories (node_modules, .cache, ...). Nothing is deleted.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "min_size_mb": { "type": "integer", "minimum": 1, "default": 100, "description": "Minimum size for stale-file suggestions." },
+                    "untouched_months": { "type": "integer", "minimum": 1, "default": 12 },
+                    "min_cache_mb": { "type": "integer", "minimum": 1, "default": 500 },
+                    "include_excluded": { "type": "boolean", "default": true, "description": "Walk paths matched by the exclude globs (where caches usually are)." },
+                    "top_n": { "type": "integer", "minimum": 1, "maximum": 500, "default": 20 }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_tag_documents",
+            description: "Starts the background job that assigns 3-5 tags to each untagged or re-indexed document (local LLM, with tags of similar documents as candidates). Returns immediately; poll silo_list_tags for progress.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "retag": { "type": "boolean", "default": false, "description": "Also re-tag documents that already have tags." },
+                    "max_docs": { "type": "integer", "minimum": 1, "description": "Tag at most this many documents in this run." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_list_tags",
+            description: "Lists document tags with how many documents carry each, plus tagging job progress.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "prefix": { "type": "string", "description": "Only tags starting with this prefix." },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 1000, "default": 100 }
+                },
+                "additionalProperties": false
+            }),
+        },
         ToolDefinition {
             name: "silo_index_home",
             description: "Bulk index configured roots under ~ (extract -> chunk -> embed -> store). Use with --features mvp for real embeddings + DB.",
@@ -109,7 +413,7 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "silo_get_config",
-            description: "Returns the effective Silo configuration (including config file path).",
+            description: "Returns the effective Silo configuration (including config file path and its `revision`, for `expected_revision` checks).",
             input_schema: json!({
                 "type": "object",
                 "properties": {},
@@ -118,16 +422,112 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "silo_set_index_roots",
-            description: "Sets filesystem indexing roots (MVP default is your home directory).",
+            description: "Sets filesystem indexing roots (MVP default is your home directory). Edits the config file's current contents, so manual edits are kept.",
             input_schema: json!({
                 "type": "object",
                 "properties": {
-                    "roots": { "type": "array", "items": { "type": "string" }, "description": "Directories to index (supports ~/ prefix)." }
+                    "roots": { "type": "array", "items": { "type": "string" }, "description": "Directories to index (supports ~/ prefix)." },
+                    "expected_revision": { "type": "string", "description": "`revision` from silo_get_config; the call fails without changes if the config file changed since." }
                 },
                 "required": ["roots"],
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "silo_update_config",
+            description: "Changes settings with a JSON merge patch: `filesystem` (exclude_globs, allow_extensions, size limits, chunking, ...), ranking, query_rewrite, federation, hits, tagging, llm, notifications, locale, result_format and response_limits. Objects merge, null resets a setting to its default. Roots change only with silo_set_index_roots. The result is validated before saving; dry_run reports the changed settings and the resulting values without saving.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "patch": { "type": "object", "description": "e.g. {\"filesystem\": {\"max_file_size_bytes\": 20000000, \"chunk_tokens\": 400}}" },
+                    "dry_run": { "type": "boolean", "default": false },
+                    "expected_revision": { "type": "string", "description": "`revision` from silo_get_config; the call fails without changes if the config file changed since." }
+                },
+                "required": ["patch"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_explain_policy",
+            description: "Explains how the indexing policy treats a path, check by check (root, excluded directories and the matching glob, depth, symlinks, skip markers, network mounts, exclude globs, extension, size limit, cloud placeholder, generated file, encrypted document), and whether it is in the catalog. For \"why wasn't this file indexed?\".",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File path (supports ~/ prefix)." }
+                },
+                "required": ["path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_document_password_set",
+            description: "Stores the password of an encrypted PDF, or of every encrypted PDF in a folder, in the OS keychain so indexing can open it (encrypted documents are skipped otherwise). The password is never written to the config; only the path is remembered. Takes effect on the next index run or silo_ingest_file.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File or folder (supports ~/ prefix); a folder's password applies to the files below it." },
+                    "password": { "type": "string" }
+                },
+                "required": ["path", "password"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_document_password_delete",
+            description: "Removes a stored document password from the OS keychain (the path as given to silo_document_password_set). Text already indexed stays in the index.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File or folder (supports ~/ prefix)." }
+                },
+                "required": ["path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_document_password_list",
+            description: "Lists the files and folders with a stored document password (never the passwords).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_secret_set",
+            description: "Stores a named secret (IMAP password, API token) in the OS keychain for connectors, which refer to it by name in their config. Secrets are never written to config.json or returned by any tool. Setting an existing name replaces its value.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Letters, digits, '.', '_' or '-', up to 64 characters (e.g. imap.work)." },
+                    "secret": { "type": "string" },
+                    "description": { "type": "string", "description": "What the secret is for (shown by silo_secret_list)." }
+                },
+                "required": ["name", "secret"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_secret_list",
+            description: "Lists the stored secrets: names, descriptions and when they were last set (never their values).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_secret_delete",
+            description: "Removes a named secret from the OS keychain.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" }
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            }),
+        },
         ToolDefinition {
             name: "silo_validate_index_config",
             description: "Validates that configured indexing roots are accessible and sane.",
@@ -149,6 +549,236 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "silo_index_diff",
+            description: "Dry run of an incremental index (\"git status\" for the knowledge base): new, changed and deleted files versus the catalog, with samples. Metadata only.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "max_samples": { "type": "integer", "minimum": 0, "maximum": 5000, "default": 50 }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_coverage",
+            description: "Index coverage per filesystem root: files eligible under the indexing policy vs indexed, failed (last ingest errored, with the error), evicted and pending (new or changed), with percentages for progress bars. Metadata only.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "max_samples": { "type": "integer", "minimum": 0, "maximum": 1000, "default": 20, "description": "Failed and pending paths listed per root." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_continue",
+            description: "Next part of a tool result that was cut to the size limit (`truncated: true`): pass its `next_cursor`. Parts have the same shape as the original result; `truncation` tells which items (or characters) of which field they hold. Cursors are single-use and expire after 10 minutes.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "cursor": { "type": "string", "description": "`next_cursor` of the previous part." },
+                    "format": { "type": "string", "enum": ["json", "markdown", "plain"], "description": "Result format for search and answer results (default: the result_format config)." }
+                },
+                "required": ["cursor"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_purge_deleted",
+            description: "Removes index entries whose files were moved to the OS trash (so search stops returning deleted files). Optionally also entries whose files are simply gone.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "include_missing": { "type": "boolean", "default": false, "description": "Also purge entries whose file is missing but not in the trash." },
+                    "dry_run": { "type": "boolean", "default": false }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_messages",
+            description: "Indexes macOS Messages history (chat.db, read from a private copy) as conversation chunks with participant names. Incremental; requires a `messages` source in the config.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "full": { "type": "boolean", "default": false, "description": "Drop indexed messages and re-read the whole history." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_screenshots",
+            description: "OCRs and indexes new screenshots from the configured screenshots folder now (the background watcher does the same every poll interval).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_newsletters",
+            description: "Ingests new saved newsletters and RSS items (.html/.eml) from the configured newsletters folder now, each with its feed, title, author, date and link (the background watcher does the same every poll interval). Items are tagged newsletter and with their feed.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_library",
+            description: "Reads the configured Zotero library (or BibTeX file) and indexes the PDFs and snapshots attached to its entries, each headed with title, authors, year, venue and DOI and tagged library plus its Zotero tags. Search hits and answer citations from these files carry a `reference` (with an APA-style `citation`). Unchanged attachments are skipped.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_import_chat_export",
+            description: "Imports a WhatsApp chat export (.txt or .zip) or Signal Desktop history (needs the sqlcipher CLI) as conversation chunks. Re-importing a chat replaces it.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "WhatsApp export file, or the Signal Desktop data directory." },
+                    "format": { "type": "string", "enum": ["whatsapp", "signal"], "description": "Default: signal for directories, whatsapp for files." },
+                    "name": { "type": "string", "description": "WhatsApp chat name (default: from the file name)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_index_budget",
+            description: "Checks the estimated index size against storage.max_index_bytes and evicts the lowest-value documents (storage.evict order: least_accessed, oldest or largest) until it fits. Returns what was evicted. Evicted files are not re-indexed until they change.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "dry_run": { "type": "boolean", "default": false, "description": "Only report what would be evicted." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_collections",
+            description: "Lists configured collections (named folder groups) with their access policy: open collections are always searchable, locked ones are hidden from search, answers and file reads until unlocked.",
+            input_schema: json!({ "type": "object", "properties": {}, "additionalProperties": false }),
+        },
+        ToolDefinition {
+            name: "silo_unlock_collection",
+            description: "Unlocks a locked collection for a limited time (its unlock_secs), making its files searchable and readable. Only call this when the user explicitly asks to.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Collection name (see silo_collections)." },
+                    "secs": { "type": "integer", "minimum": 1, "description": "Unlock for this long (capped at the collection's unlock_secs)." }
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_grant_temp_root",
+            description: "Grants a folder to this session for reading and searching (silo_read_file, silo_list_files, silo_search_paths, silo_grep, silo_session_index, ...) for a limited time, without adding it to the config or the index. Needed in sandbox mode for folders outside the indexed roots, e.g. when the user asks for help with a folder. `revoke` ends a grant early; the result lists the session's temporary roots.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute folder path (`~` is expanded)." },
+                    "ttl_secs": { "type": "integer", "minimum": 1, "description": "Grant for this long (default sandbox.grant_secs, capped at sandbox.max_grant_secs)." },
+                    "revoke": { "type": "boolean", "default": false }
+                },
+                "required": ["path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_lock_collection",
+            description: "Locks an unlocked collection again before its unlock expires (all collections when name is omitted).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_consent_status",
+            description: "Checks a pending_consent request (returned instead of a result when a tool call touches a sensitive path). Once the user approved it in the Silo app, returns a single-use consent_token: repeat the same tool call with an extra `consent_token` argument.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "consent_id": { "type": "string" }
+                },
+                "required": ["consent_id"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_backup_settings",
+            description: "Exports the config and the generated tags into a small .tar.gz (no vectors or caches: the index is rebuilt by re-indexing, and the data dir is excluded from backups). Returns the archive path.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Archive path (default: backups/silo-settings-<time>.tar.gz next to the config file)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_maintenance",
+            description: "Reclaims disk space in the knowledge base: compacts the LanceDB tables (dropping rows left by deletes and re-indexing) and deletes old table versions. Returns on-disk size before and after. Can also run on a schedule (storage.maintenance_interval_hours).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "keep_versions_hours": { "type": "integer", "minimum": 0, "description": "Keep table versions younger than this (default storage.keep_versions_hours, or 1)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_verify",
+            description: "Checks index integrity on a random sample of indexed files (or the given paths): re-reads each source file and compares its hash, checks stored chunk rows (count, ids, embedding dimension and values). Reports drift (missing, modified, row_count, corrupted_row, bad_embedding, hash_mismatch) per file; with repair=true re-ingests the files that can be fixed.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "sample": { "type": "integer", "minimum": 1, "maximum": 500, "description": "Files to sample (default 50)." },
+                    "paths": { "type": "array", "items": { "type": "string" }, "description": "Check these indexed files instead of a sample." },
+                    "repair": { "type": "boolean", "description": "Re-ingest files with drift (default false)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_models_list",
+            description: "Lists embedding models: the active model's load state (unloaded/loading/loaded), downloadable models, and what is downloaded in the models cache dir with sizes.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_models_pull",
+            description: "Downloads an embedding model into the models cache dir. The active model (default) is also loaded, so the first search does not wait for it.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "model": { "type": "string", "description": "Model repo as listed by silo_models_list (default: the active model)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_models_remove",
+            description: "Deletes a downloaded embedding model from the models cache dir (the active model is unloaded and downloads again on next use).",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "model": { "type": "string", "description": "Model repo as listed by silo_models_list (default: the active model)." }
+                },
+                "additionalProperties": false
+            }),
+        },
         ToolDefinition {
             name: "silo_preview_extract",
             description: "Extracts text from a file (supports PDF via pdftotext) and returns a short preview (no embeddings).",
@@ -162,6 +792,151 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "silo_grep",
+            description: "Regex or literal search. source \"index\" (default) searches the stored text of indexed files (PDFs included) and returns a window around each match; source \"files\" re-reads files under the configured roots and returns line numbers with context lines.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "pattern": { "type": "string", "description": "Regex (Rust syntax), or a literal string with literal: true." },
+                    "literal": { "type": "boolean", "description": "Match pattern literally (default false)." },
+                    "case_insensitive": { "type": "boolean", "description": "Default false." },
+                    "source": { "type": "string", "enum": ["index", "files"], "description": "Default: index." },
+                    "include": { "type": "array", "items": { "type": "string" }, "description": "Only paths matching one of these globs (e.g. **/*.md)." },
+                    "exclude": { "type": "array", "items": { "type": "string" }, "description": "Skip paths matching any of these globs." },
+                    "context": { "type": "integer", "minimum": 0, "maximum": 10, "description": "Context lines before/after each match (files source; default 2)." },
+                    "max_matches": { "type": "integer", "minimum": 1, "maximum": 1000, "description": "Default 100." }
+                },
+                "required": ["pattern"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_find_symbol",
+            description: "Finds where functions, methods, classes and other types are defined in indexed source files (Rust, Python, JavaScript, TypeScript, Go, Java), with path and line numbers. Fuzzy by default (case-insensitive, prefix, substring, abbreviations, one typo); `Type::name` or `Type.name` also matches the enclosing type. Use it for code navigation where semantic search is too loose.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Symbol name, optionally qualified (Config::load, Parser.parse)." },
+                    "exact": { "type": "boolean", "description": "Only case-sensitive equal names (default false)." },
+                    "kind": { "type": "string", "description": "Only this kind: function (includes methods), method, class, struct, enum, interface, type, module, macro, constant." },
+                    "include": { "type": "array", "items": { "type": "string" }, "description": "Only paths matching one of these globs (e.g. **/src/**)." },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 200, "default": 20 }
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_repo_context",
+            description: "Context for a coding task: finds the indexed files of a repository most relevant to the task (semantic search plus the definitions of identifiers it names) and packs their relevant line ranges, quoted from disk with file and line labels, into one markdown bundle within max_tokens. Also lists the candidate files with scores and ranges. Call it before editing code in an unfamiliar repository.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Repository root (must be under an indexed root)." },
+                    "task": { "type": "string", "description": "What is about to be done, in words; name functions or types in backticks to pull in their definitions." },
+                    "max_tokens": { "type": "integer", "minimum": 1, "maximum": 100000, "default": 8000 },
+                    "max_files": { "type": "integer", "minimum": 1, "maximum": 50, "default": 10 }
+                },
+                "required": ["path", "task"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_search_paths",
+            description: "Semantic search over given files, folders or globs without indexing them first: extracts and embeds them on the fly (bounded: at most max_files files and 32 MB of text) and returns ranked passages.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "paths": { "type": "array", "items": { "type": "string" }, "description": "Files, folders (recursive) or globs such as ~/notes/**/*.md." },
+                    "query": { "type": "string", "description": "Search query." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 50, "description": "Max hits (default 10)." },
+                    "min_score": { "type": "number", "description": "Drop hits below this cosine similarity." },
+                    "max_files": { "type": "integer", "minimum": 1, "maximum": 500, "description": "Files to read at most (default 100)." }
+                },
+                "required": ["paths", "query"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_metrics",
+            description: "Timing metrics since the server started: ingest time per file (count, mean, p50/p95/max) and search latency, with the slowest files (time per stage: extract, chunk, embed, store) and slowest queries.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_eval_run",
+            description: "Measure retrieval quality: runs a YAML suite of queries with their expected files against one or more search variants (the live index, prefer_recent, min_score, or re-chunked copies of a corpus with other chunk settings) and reports recall@k and MRR per variant.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "suite": { "type": "string", "description": "Path to the suite YAML file (supports ~/ prefix)." },
+                    "yaml": { "type": "string", "description": "The suite YAML inline, instead of suite." },
+                    "variants": { "type": "array", "items": { "type": "string" }, "description": "Only run these variants (by name)." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_bench_ingest",
+            description: "Ingest benchmark: runs N synthetic or sampled indexed files through extract, chunk, embed and store, timing each stage separately, and reports per-stage totals, mean/p50/p95 per file and overall throughput. Benchmark rows are removed afterwards.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "files": { "type": "integer", "minimum": 1, "maximum": 500, "description": "Files to run (default 20)." },
+                    "source": { "type": "string", "enum": ["synthetic", "sample"], "description": "Generated text files (default) or a sample of indexed files." },
+                    "file_bytes": { "type": "integer", "minimum": 1, "maximum": 1048576, "description": "Size of each synthetic file (default 16384)." },
+                    "store": { "type": "string", "enum": ["index", "memory"], "description": "Time writes to the live store under temporary paths (default) or to a throwaway in-memory store." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_session_index",
+            description: "Temporary index for this session: add files (extracted, chunked and embedded into memory only; nothing is written to disk or the permanent index), then search them. Cleared on exit.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "action": { "type": "string", "enum": ["add", "search", "list", "remove", "clear"], "description": "Default: search." },
+                    "paths": { "type": "array", "items": { "type": "string" }, "description": "Files to add or remove (supports ~/ prefix)." },
+                    "query": { "type": "string", "description": "Search query (action search)." },
+                    "top_k": { "type": "integer", "minimum": 1, "maximum": 50, "description": "Max hits (default 10)." },
+                    "min_score": { "type": "number", "description": "Drop hits below this cosine similarity." }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_write_file",
+            description: "Writes a text file inside the folders allowed by the writes config (only offered when writes are enabled). Refuses to replace an existing file unless overwrite is true. Every call is audit-logged.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File to write (absolute or ~/)." },
+                    "content": { "type": "string", "description": "Full file content (UTF-8)." },
+                    "overwrite": { "type": "boolean", "description": "Replace the file if it exists (default false)." },
+                    "create_dirs": { "type": "boolean", "description": "Create missing folders (default false)." }
+                },
+                "required": ["path", "content"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_append_note",
+            description: "Appends a timestamped entry to today's daily note (or a named note) in the configured notes folder, then indexes it so it is searchable. Use for \"remember that ...\". Only offered when a notes folder is configured; never edits or removes existing text.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Entry to append (Markdown; multiple lines allowed)." },
+                    "file": { "type": "string", "description": "Note file relative to the notes folder, e.g. ideas.md (default: today's daily note)." }
+                },
+                "required": ["text"],
+                "additionalProperties": false
+            }),
+        },
         ToolDefinition {
             name: "silo_ingest_file",
             description: "Ingests a file: extract -> chunk (~500 tokens w/ overlap) -> (placeholder) embed -> store to LanceDB when enabled.",
@@ -170,7 +945,35 @@ pub fn tool_definitions() -> Vec<ToolDefinition> {
                 "properties": {
                     "path": { "type": "string", "description": "File path to ingest (supports ~/ prefix)." }
                 },
-                "required": ["path"],
+                "required": ["path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_ingest_content",
+            description: "Ingests content sent by the client instead of a file path: text, or a document's bytes in base64 (PDFs and images are extracted like files). It is chunked, embedded and stored like a file under upload://<virtual_path> with source \"upload\"; sending the same virtual_path again replaces it.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "virtual_path": { "type": "string", "description": "Name to store the content under, e.g. inbox/meeting.md. Its extension picks the extractor." },
+                    "text": { "type": "string", "description": "The text (pass this or base64)." },
+                    "base64": { "type": "string", "description": "Document bytes, base64-encoded." },
+                    "mime": { "type": "string", "description": "MIME type, used when virtual_path has no extension (e.g. application/pdf)." },
+                    "modified_epoch_secs": { "type": "integer", "description": "Modification time to record (default: now)." }
+                },
+                "required": ["virtual_path"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "silo_ingest_url",
+            description: "Fetches a web page (or a PDF or text file on the web) and ingests its text, headed with the page title and URL, so reading-list articles become searchable. Stored under the URL with source \"web\" and in the \"web\" partition of federated search; ingesting the same URL again replaces it. Only offered when web ingestion is enabled.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "http(s) URL of the page." }
+                },
+                "required": ["url"],
                 "additionalProperties": false
             }),
         },
@@ -185,15 +988,96 @@ pub async fn call_tool(state: &SharedState, call: ToolCallParams) -> ToolResult
             Err(e) => err_text(e),
         };
     }
+    // Unlocking is for the user (via their MCP client); the agent's dispatcher never sees it.
+    if call.name == "silo_unlock_collection" {
+        return match crate::access::unlock_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        };
+    }
+    // Grants belong to the client's session; the agent's calls only use them.
+    if call.name == "silo_grant_temp_root" {
+        return match crate::sandbox::grant_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        };
+    }
 
     call_tool_no_agent(state, call).await
 }
 
 /// Tool dispatcher used by the agent itself.
 ///
+/// Enforces collection locks (`crate::access`): `path`/`directory` arguments inside a locked
+/// collection are refused, and locked paths are dropped from the output. Calls on sensitive paths
+/// need a consent token (`crate::consent`). Write tools are refused in read-only mode, and paths
+/// outside the session's folders in sandbox mode (`crate::sandbox`). Results over the size limit
+/// are cut, with a cursor for the rest (`crate::responses`). Search and answer results are
+/// rendered as text when a `format` asks for it (`crate::render`).
+///
 /// IMPORTANT: This function MUST NOT reference `crate::agent::agent_tool` (even indirectly),
 /// otherwise Rust will treat the futures as potentially recursive.
-pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams) -> ToolResult {
+pub(crate) async fn call_tool_no_agent(state: &SharedState, mut call: ToolCallParams) -> ToolResult {
+    if read_only(state) && is_write_tool(&call.name) {
+        return err_text(ToolError::read_only(&call.name));
+    }
+    if let Err(e) = crate::sandbox::check(state, &call).await {
+        return err_text(e);
+    }
+    let locked = crate::access::locked_paths(state).await;
+    for key in ["path", "directory"] {
+        let checked = call
+            .arguments
+            .get(key)
+            .and_then(Value::as_str)
+            .map_or(Ok(()), |path| locked.check(&expand_tilde(path).to_string_lossy()));
+        if let Err(e) = checked {
+            return err_text(e);
+        }
+    }
+    match crate::consent::gate(state, &mut call).await {
+        Ok(None) => {}
+        Ok(Some(pending)) => return ok_json(pending),
+        Err(e) => return err_text(e),
+    }
+    let format = match crate::render::requested(state, &call).await {
+        Ok(format) => format,
+        Err(e) => return err_text(e),
+    };
+    let query = call.arguments.get("query").and_then(Value::as_str).map(str::to_string);
+    let tool = call.name.clone();
+    let mut result = dispatch(state, call).await;
+    if !locked.is_empty() && !result.is_error {
+        for content in &mut result.content {
+            if let Ok(mut value) = serde_json::from_str::<Value>(&content.text) {
+                locked.scrub(&mut value);
+                content.text = value.to_string();
+            }
+        }
+    }
+    // `silo_continue` parts are already cut to the original tool's limit.
+    if !result.is_error && tool != "silo_continue" {
+        for content in &mut result.content {
+            if let Err(e) = crate::responses::limit(state, &tool, &mut content.text).await {
+                return err_text(e);
+            }
+        }
+    }
+    if format != ResultFormat::Json && !result.is_error {
+        let messages = state.messages().await;
+        for content in &mut result.content {
+            let rendered = serde_json::from_str::<Value>(&content.text)
+                .ok()
+                .and_then(|value| crate::render::render(&value, format, query.as_deref(), &messages));
+            if let Some(text) = rendered {
+                content.text = text;
+            }
+        }
+    }
+    result
+}
+
+async fn dispatch(state: &SharedState, call: ToolCallParams) -> ToolResult {
     match call.name.as_str() {
         "silo_agent" => err_text("Agent recursion is not allowed".to_string()),
         // New canonical names:
@@ -206,7 +1090,7 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                     Ok(v) => ok_json(v),
                     Err(e) => err_text(e),
                 },
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
         "silo_read_file" | "read_file" => {
@@ -216,19 +1100,82 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                     Ok(v) => ok_json(v),
                     Err(e) => err_text(e),
                 },
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
         "silo_search" | "silo_search_knowledge_base" | "search_knowledge_base" => {
             let args: Result<SearchKnowledgeBaseArgs, _> = serde_json::from_value(call.arguments);
             match args {
-                Ok(args) => match silo_search(state, args.query, args.top_k).await {
+                Ok(args) => match silo_search(state, args).await {
                     Ok(v) => ok_json(v),
                     Err(e) => err_text(e),
                 },
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
+            }
+        }
+        "silo_search_entities" => match crate::entities::search_entities_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_graph_neighbors" => match crate::graph::neighbors_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_graph_path" => match crate::graph::path_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_graph_rebuild" => match crate::graph::rebuild(state).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_get_chunk" => match crate::chunk_lookup::get_chunk_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_debug_retrieval" => match crate::retrieval_debug::debug_retrieval_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_search_files" => {
+            let args: Result<SearchFilesArgs, _> = serde_json::from_value(call.arguments);
+            match args {
+                Ok(args) => ok_json(silo_search_files(state, &args.query, args.limit).await),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
+        "silo_ask" => match crate::ask::ask_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_chat" => match crate::chat::chat_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_extract_structured" => match crate::structured::extract_structured_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_timeline" => match crate::timeline::timeline_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_disk_report" => match crate::disk::disk_report_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_cleanup_suggestions" => match crate::cleanup::cleanup_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_tag_documents" => match crate::tagging::tag_documents_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_list_tags" => match crate::tagging::list_tags_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
         "silo_get_config" => match state.get_config_json().await {
             v => ok_json(v),
         },
@@ -237,14 +1184,22 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
             match args {
                 Ok(args) => {
                     let roots: Vec<PathBuf> = args.roots.into_iter().map(|s| expand_tilde(&s)).collect();
-                    match state.set_index_roots(roots).await {
+                    match state.set_index_roots(roots, args.expected_revision.as_deref()).await {
                         Ok(v) => ok_json(v),
                         Err(e) => err_text(e),
                     }
                 }
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
+        "silo_update_config" => match crate::config_update::update_config_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_explain_policy" => match crate::policy_explain::explain_policy_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
         "silo_validate_index_config" => ok_json(state.validate_index_config().await),
         "silo_preview_index" => {
             let args: Result<PreviewIndexArgs, _> = serde_json::from_value(call.arguments);
@@ -252,7 +1207,7 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                 Ok(args) => {
                     let policy_opt = state.fs_policy.read().await;
                     let Some(policy) = policy_opt.as_ref() else {
-                        return err_text("No filesystem policy configured".to_string());
+                        return err_text(ToolError::no_filesystem_policy());
                     };
 
                     let roots = state.filesystem_roots().await;
@@ -266,9 +1221,111 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                         json!({"error": format!("failed to serialize scan summary: {e}")})
                     }))
                 }
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
+        "silo_continue" => match crate::responses::continue_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_document_password_set" => match crate::passwords::set_password_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_document_password_delete" => match crate::passwords::delete_password_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_document_password_list" => match crate::passwords::list_passwords_tool().await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_secret_set" => match crate::secrets::set_secret_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_secret_list" => match crate::secrets::list_secrets_tool(state).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_secret_delete" => match crate::secrets::delete_secret_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_coverage" => match crate::coverage::coverage_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_diff" => match crate::index_diff::index_diff_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_purge_deleted" => match crate::trash::purge_deleted_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_library" => match crate::library::index_library_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_messages" => match crate::messages::index_messages_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_newsletters" => match crate::newsletters::index_newsletters_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_screenshots" => match crate::screenshots::index_screenshots_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_import_chat_export" => match crate::chat_export::import_chat_export_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_index_budget" => match crate::budget::index_budget_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_collections" => match crate::access::collections_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_unlock_collection" => err_text("Only the user can unlock a collection".to_string()),
+        "silo_grant_temp_root" => err_text("Only the MCP client can grant a folder".to_string()),
+        "silo_lock_collection" => match crate::access::lock_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_consent_status" => match crate::consent::consent_status_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_backup_settings" => match crate::backup::backup_settings_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_maintenance" => match crate::maintenance::maintenance_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_verify" => match crate::verify::verify_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_models_list" => match crate::models::models_list_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_models_pull" => match crate::models::models_pull_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_models_remove" => match crate::models::models_remove_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
         "silo_preview_extract" => {
             let args: Result<PreviewExtractArgs, _> = serde_json::from_value(call.arguments);
             match args {
@@ -305,9 +1362,57 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                         "preview": preview
                     }))
                 }
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
+        "silo_find_symbol" => match crate::symbols::find_symbol_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_repo_context" => match crate::repo_context::repo_context_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_grep" => match crate::grep::grep_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_metrics" => match crate::metrics::metrics_tool(state).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_eval_run" => match crate::eval::eval_run_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_bench_ingest" => match crate::bench::bench_ingest_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_search_paths" => match crate::search_paths::search_paths_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_session_index" => match crate::session_index::session_index_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_write_file" => match crate::writes::write_file_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_append_note" => match crate::notes::append_note_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_ingest_url" => match crate::web::ingest_url_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
+        "silo_ingest_content" => match crate::upload::ingest_content_tool(state, call.arguments).await {
+            Ok(v) => ok_json(v),
+            Err(e) => err_text(e),
+        },
         "silo_ingest_file" => {
             let args: Result<IngestFileArgs, _> = serde_json::from_value(call.arguments);
             match args {
@@ -322,19 +1427,28 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                         .read()
                         .await
                         .as_ref()
-                        .map(|p| p.max_text_bytes)
+                        .map(|p| p.max_text_bytes_for(&expand_tilde(&args.path)))
                         .unwrap_or(2 * 1024 * 1024);
 
                     let res = crate::ingest::process_file(
                         &state.db,
                         &state.embedder,
+                        state.entities.as_deref(),
+                        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
                         &args.path,
                         max_text_bytes,
-                        fs_cfg.chunk_tokens,
-                        fs_cfg.chunk_overlap_tokens,
+                        &fs_cfg.chunking(),
                     )
                     .await;
 
+                    state.index_changed();
+                    if let Ok(stats) = &res {
+                        state.metrics.record_ingest(stats);
+                        if stats.stored {
+                            state.catalog.write().await.record_ingest(stats);
+                            state.save_catalog().await;
+                        }
+                    }
                     match res {
                         Ok(stats) => ok_json(serde_json::to_value(stats).unwrap_or_else(|e| {
                             json!({"error": format!("failed to serialize ingest stats: {e}")})
@@ -342,7 +1456,7 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
                         Err(e) => err_text(e),
                     }
                 }
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
         "silo_index_home" => {
@@ -350,32 +1464,43 @@ pub(crate) async fn call_tool_no_agent(state: &SharedState, call: ToolCallParams
             match args {
                 Ok(args) => {
                     let Some(policy) = state.filesystem_policy().await else {
-                        return err_text("No filesystem policy configured".to_string());
+                        return err_text(ToolError::no_filesystem_policy());
                     };
                     let roots = state.filesystem_roots().await;
                     let opts = crate::indexer::IndexOptions {
                         max_files: args.max_files,
                         concurrency: args.concurrency.unwrap_or(2),
                         max_sample_errors: 20,
+                        progress: Some(state.index_progress.clone()),
+                        metrics: Some(state.metrics.clone()),
+                        boilerplate: Some(state.boilerplate.clone()),
                     };
 
-                    let summary = crate::indexer::index_roots(
+                    let started = crate::catalog::now_epoch_secs();
+                    let mut summary = crate::indexer::index_roots(
                         roots,
                         std::sync::Arc::new(policy),
                         state.db.clone(),
                         state.embedder.clone(),
+                        state.entities.clone(),
+                        state.catalog.clone(),
                         opts,
                     )
                     .await;
+                    state.index_changed();
+                    state.save_catalog().await;
+                    summary.eviction = crate::budget::after_index(state).await;
+                    crate::tagging::after_index(state).await;
+                    crate::notify::index_finished(state, &summary, started).await;
 
                     ok_json(serde_json::to_value(summary).unwrap_or_else(|e| {
                         json!({"error": format!("failed to serialize index summary: {e}")})
                     }))
                 }
-                Err(e) => err_text(format!("Invalid arguments: {e}")),
+                Err(e) => err_text(ToolError::invalid_arguments(e)),
             }
         }
-        other => err_text(format!("Unknown tool: {other}")),
+        other => err_text(ToolError::unknown_tool(other)),
     }
 }
 
@@ -386,16 +1511,19 @@ fn ok_json(value: Value) -> ToolResult {
             text: value.to_string(),
         }],
         is_error: false,
+        error: None,
     }
 }
 
-fn err_text(msg: String) -> ToolResult {
+fn err_text(error: impl Into<ToolError>) -> ToolResult {
+    let error = error.into();
     ToolResult {
         content: vec![ToolResultContent {
             kind: "text",
-            text: msg,
+            text: error.message.clone(),
         }],
         is_error: true,
+        error: Some(error),
     }
 }
 
@@ -409,16 +1537,55 @@ struct ReadFileArgs {
     path: String,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SearchKnowledgeBaseArgs {
+    pub query: String,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    #[serde(default)]
+    pub entity: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Connectors to search (`ChunkSource`); empty: all.
+    #[serde(default)]
+    pub sources: Vec<ChunkSource>,
+    #[serde(default)]
+    pub prefer_recent: Option<bool>,
+    /// Fields to match in (`crate::fields`); empty: all.
+    #[serde(default)]
+    pub fields: Vec<SearchField>,
+    /// Query preprocessing (`crate::rewrite`); `None`: the `query_rewrite` config.
+    #[serde(default)]
+    pub correct_typos: Option<bool>,
+    #[serde(default)]
+    pub expand_synonyms: Option<bool>,
+    #[serde(default)]
+    pub rewrite: Option<RewriteMode>,
+    /// Federated search (`crate::federation`); `None`: the `federation` config.
+    #[serde(default)]
+    pub federated: Option<bool>,
+    /// Preview length per hit; `None`: `hits.preview_chars`. Capped at `hits.max_preview_chars`.
+    #[serde(default)]
+    pub preview_chars: Option<usize>,
+    /// Add each hit's chunk text as `content` (within `hits.max_content_chars` in total).
+    #[serde(default)]
+    pub include_full_content: bool,
+}
+
 #[derive(Debug, Deserialize)]
-struct SearchKnowledgeBaseArgs {
+struct SearchFilesArgs {
     query: String,
     #[serde(default)]
-    top_k: Option<usize>,
+    limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
 struct SetIndexRootsArgs {
     roots: Vec<String>,
+    #[serde(default)]
+    expected_revision: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -477,7 +1644,7 @@ async fn list_files(args: ListFilesArgs) -> Result<Value, String> {
     Ok(json!({ "entries": out }))
 }
 
-async fn read_file(args: ReadFileArgs) -> Result<Value, String> {
+async fn read_file(args: ReadFileArgs) -> Result<Value, ToolError> {
     let path = expand_tilde(&args.path);
     validate_safe_path(&path)?;
 
@@ -488,39 +1655,334 @@ async fn read_file(args: ReadFileArgs) -> Result<Value, String> {
     Ok(json!({ "path": path.to_string_lossy(), "content": content }))
 }
 
-async fn silo_search(state: &SharedState, query: String, top_k: Option<usize>) -> Result<Value, String> {
+pub(crate) async fn silo_search(
+    state: &SharedState,
+    args: SearchKnowledgeBaseArgs,
+) -> Result<Value, ToolError> {
+    let SearchKnowledgeBaseArgs {
+        query,
+        top_k,
+        min_score,
+        entity,
+        tags,
+        mut sources,
+        prefer_recent,
+        mut fields,
+        correct_typos,
+        expand_synonyms,
+        rewrite,
+        federated,
+        preview_chars,
+        include_full_content,
+    } = args;
     if !state.db.is_enabled() {
         let reason = state
             .db
             .disabled_reason()
             .unwrap_or("unknown reason")
             .to_string();
-        return Err(format!("Knowledge base is disabled: {reason}"));
+        return Err(ToolError::kb_disabled(reason));
     }
 
     let k = top_k.unwrap_or(10).clamp(1, 50);
+    let (prefer_recent, rewrite_opts, federated, hits_cfg) = {
+        let cfg = state.config.read().await;
+        let opts = RewriteOptions {
+            correct_typos: correct_typos.unwrap_or(cfg.query_rewrite.correct_typos),
+            expand_synonyms: expand_synonyms.unwrap_or(cfg.query_rewrite.expand_synonyms),
+            mode: rewrite.unwrap_or(cfg.query_rewrite.mode),
+        };
+        (
+            prefer_recent.unwrap_or(cfg.ranking.prefer_recent),
+            opts,
+            federated.unwrap_or(cfg.federation.enabled),
+            cfg.hits.clone(),
+        )
+    };
+    let preview_chars = preview_chars.unwrap_or(hits_cfg.preview_chars).min(hits_cfg.max_preview_chars);
+    fields.sort();
+    fields.dedup();
+    sources.sort_by_key(|s| s.as_str());
+    sources.dedup();
+    if fields.is_empty() {
+        fields = vec![SearchField::Title, SearchField::Body];
+    }
+    let locked = crate::access::locked_paths(state).await;
+
+    // Fast path: identical query + options within the short result TTL (UI debounce, agent retries).
+    let cache_key = format!(
+        "{}\n{}\n{rewrite_opts:?} {federated} {sources:?} {preview_chars} {include_full_content}",
+        locked.cache_key(),
+        search_cache_key(&query, k, min_score, entity.as_deref(), &tags, prefer_recent, &fields)
+    );
+    let progress = state.index_progress.snapshot();
+    if let Some(mut cached) = state.cached_search_result(&cache_key) {
+        state.metrics.record_cached_query();
+        cached["cached"] = json!(true);
+        if let Some(p) = progress {
+            cached["index_progress"] = json!(p);
+        }
+        return Ok(cached);
+    }
+
+    let entity_filter = match entity.as_deref() {
+        Some(e) => match crate::entities::entity_search_filter(state, e).await? {
+            Some(f) => Some(f),
+            // Nothing mentions the entity: no need to embed or search.
+            None => return Ok(json!({ "metric": SEARCH_METRIC, "entity": e, "hits": [] })),
+        },
+        None => None,
+    };
+    let tag_filter = crate::tagging::search_filter(&tags);
+    let source_filter = (!sources.is_empty()).then(|| crate::database::sources_filter(&sources));
+    let plan = crate::query::parse(&query)?;
+    if plan.text.trim().is_empty() {
+        return Err(ToolError::filters_only_query());
+    }
+    let rewrite = if rewrite_opts.any() {
+        crate::rewrite::rewrite(state, &plan.text, rewrite_opts).await
+    } else {
+        crate::rewrite::Rewrite {
+            text: plan.text.clone(),
+            ..Default::default()
+        }
+    };
+    // Title only: search the chunks of files whose name matches.
+    let terms = crate::fields::query_terms(&rewrite.text);
+    let title_only = !fields.contains(&SearchField::Body);
+    let title_filter = if title_only {
+        Some(crate::fields::title_filter(&*state.catalog.read().await, &terms))
+    } else {
+        None
+    };
+    let filter = [entity_filter, tag_filter, source_filter, title_filter, plan.filter.clone(), locked.search_filter()]
+        .into_iter()
+        .flatten()
+        .reduce(|a, b| a.and(b));
+
+    let embed_started = std::time::Instant::now();
     let qvec = state
-        .embedder
-        .embed_query(query)
+        .embed_query_cached(&rewrite.embed_text())
         .await
-        .map_err(|e| format!("Embedding failed: {e}"))?;
+        .map_err(ToolError::embedding_failed)?;
+    let embed_ms = embed_started.elapsed().as_millis() as u64;
 
-    let hits = state
-        .db
-        .search_chunks_by_vector(&qvec, k)
-        .await
-        .map_err(|e| format!("DB search failed: {e}"))?;
+    let search_started = std::time::Instant::now();
+    let title_terms: &[String] = if fields.contains(&SearchField::Title) { &terms } else { &[] };
+    // Federated hits are ordered by normalized partition scores, not by (boosted) similarity.
+    let rerank = !federated
+        && (prefer_recent || (!title_terms.is_empty() && state.config.read().await.ranking.title_weight != 0.0));
+    let opts = SearchOptions {
+        // Reranking and collapsing duplicates need candidates beyond the top k.
+        top_k: if rerank {
+            (k * RERANK_CANDIDATES_FACTOR).min(MAX_RERANK_CANDIDATES)
+        } else {
+            k * DUPLICATE_CANDIDATES_FACTOR
+        },
+        min_score,
+        filter,
+    };
+    let (hits, mut partitions) = if federated {
+        let partitions = crate::federation::partitions(state).await;
+        let (hits, summaries) = crate::federation::search(state, &qvec, &opts, partitions).await?;
+        (hits, Some(summaries))
+    } else {
+        let hits = state
+            .db
+            .search_chunks_by_vector(&qvec, &opts)
+            .await
+            .map_err(ToolError::db_search_failed)?;
+        (hits, None)
+    };
+    let mut hits = collapse_duplicate_chunks(hits);
+    if rerank {
+        rerank_hits(state, &mut hits, title_terms, prefer_recent).await;
+    }
+    hits.truncate(k);
+    if let Some(p) = partitions.as_mut() {
+        crate::federation::count_hits(p, &hits);
+    }
+    let search_ms = search_started.elapsed().as_millis() as u64;
+    state.metrics.record_query(&query, embed_ms, search_ms, hits.len());
+    state.catalog.write().await.record_access(hits.iter().map(|h| h.path.as_str()));
+
+    for h in &mut hits {
+        h.content_preview = h
+            .content
+            .as_deref()
+            .filter(|_| preview_chars > 0)
+            .map(|c| crate::database::preview(c, preview_chars));
+    }
+    let mut hits_json = json!(hits);
+    crate::library::attach_references(state, &mut hits_json).await;
+    let content_truncated = include_full_content && attach_content(&hits, &mut hits_json, hits_cfg.max_content_chars);
+
+    let mut out = json!({ "metric": SEARCH_METRIC, "hits": hits_json });
+    if content_truncated {
+        out["content_truncated"] = json!(true);
+    }
+    if let Some(e) = entity {
+        out["entity"] = json!(e);
+    }
+    if !tags.is_empty() {
+        out["tags"] = json!(tags);
+    }
+    if !sources.is_empty() {
+        out["sources"] = json!(sources);
+    }
+    if title_only {
+        out["fields"] = json!(fields);
+    }
+    if plan.advanced {
+        out["query_plan"] = json!({
+            "text": plan.text,
+            "filter": plan.filter.map(|f| f.to_string()),
+        });
+    }
+    if rewrite.changed() {
+        out["query_rewrite"] = json!(rewrite);
+    }
+    if let Some(p) = partitions {
+        out["federation"] = json!(p);
+    }
+    state.cache_search_result(cache_key, out.clone());
+    if let Some(p) = progress {
+        out["index_progress"] = json!(p);
+    }
+    Ok(out)
+}
+
+/// Add each hit's chunk text to its JSON as `content`, in order, until `budget` characters are
+/// used: the hit that crosses it gets a clipped text, later hits none. Returns whether any text
+/// was clipped or left out.
+fn attach_content(hits: &[SearchHit], hits_json: &mut Value, mut budget: usize) -> bool {
+    let Some(values) = hits_json.as_array_mut() else {
+        return false;
+    };
+    let mut truncated = false;
+    for (hit, value) in hits.iter().zip(values) {
+        let Some(content) = hit.content.as_deref() else {
+            continue;
+        };
+        if budget == 0 {
+            truncated = true;
+            continue;
+        }
+        let chars = content.chars().count();
+        if chars > budget {
+            value["content"] = json!(crate::database::preview(content, budget));
+            truncated = true;
+            budget = 0;
+        } else {
+            value["content"] = json!(content);
+            budget -= chars;
+        }
+    }
+    truncated
+}
+
+/// Without `prefer_recent`, this many times `top_k` candidates are fetched, so collapsing
+/// duplicate chunks still leaves `top_k` hits in most cases.
+const DUPLICATE_CANDIDATES_FACTOR: usize = 2;
+/// Reranked searches fetch this many times `top_k` candidates (at most `MAX_RERANK_CANDIDATES`).
+const RERANK_CANDIDATES_FACTOR: usize = 3;
+const MAX_RERANK_CANDIDATES: usize = 150;
+/// Search-result count at which the access bonus reaches `ranking.access_weight`.
+const ACCESS_SATURATION: f32 = 100.0;
+
+/// Merge hits with the same chunk text (whitespace-normalized; copied license text, templates)
+/// into the best-scoring one, listing the other files in `duplicate_paths`. Order is kept.
+pub(crate) fn collapse_duplicate_chunks(hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    let mut out: Vec<SearchHit> = Vec::with_capacity(hits.len());
+    let mut by_text: HashMap<blake3::Hash, usize> = HashMap::new();
+    for hit in hits {
+        let key = hit.content.as_deref().map(|c| {
+            let mut hasher = blake3::Hasher::new();
+            for word in c.split_whitespace() {
+                hasher.update(word.as_bytes());
+                hasher.update(b" ");
+            }
+            hasher.finalize()
+        });
+        let Some(key) = key else {
+            out.push(hit);
+            continue;
+        };
+        match by_text.get(&key) {
+            Some(&i) => {
+                let kept = &mut out[i];
+                if kept.path != hit.path && !kept.duplicate_paths.contains(&hit.path) {
+                    kept.duplicate_paths.push(hit.path);
+                }
+            }
+            None => {
+                by_text.insert(key, out.len());
+                out.push(hit);
+            }
+        }
+    }
+    out
+}
+
+/// Order `hits` by similarity plus bonuses, weighted by the `ranking` config: for file names
+/// containing `title_terms`, and with `prefer_recent` for recently modified and often accessed
+/// files (from the catalog). `score` itself stays the cosine similarity.
+pub(crate) async fn rerank_hits(state: &SharedState, hits: &mut [SearchHit], title_terms: &[String], prefer_recent: bool) {
+    let mut ranking = state.config.read().await.ranking.clone();
+    if !prefer_recent {
+        ranking.recency_weight = 0.0;
+        ranking.access_weight = 0.0;
+    }
+    let half_life_days = ranking.recency_half_life_days.max(0.1);
+    let now = crate::catalog::now_epoch_secs();
+    let catalog = state.catalog.read().await;
+    let boosted = |h: &SearchHit| {
+        let entry = catalog.get(&h.path);
+        let age_days = entry
+            .and_then(|e| e.modified_epoch_secs)
+            .map_or(f32::INFINITY, |t| (now - t).max(0) as f32 / 86_400.0);
+        let accesses = entry.map_or(0.0, |e| e.access_count as f32);
+        h.score.unwrap_or(f32::MIN)
+            + ranking.title_weight * crate::fields::title_match(&h.path, title_terms)
+            + ranking.recency_weight * 0.5f32.powf(age_days / half_life_days)
+            + ranking.access_weight * (accesses.ln_1p() / ACCESS_SATURATION.ln_1p()).min(1.0)
+    };
+    hits.sort_by(|a, b| boosted(b).total_cmp(&boosted(a)));
+}
+
+fn search_cache_key(
+    query: &str,
+    top_k: usize,
+    min_score: Option<f32>,
+    entity: Option<&str>,
+    tags: &[String],
+    prefer_recent: bool,
+    fields: &[SearchField],
+) -> String {
+    format!("{top_k}\n{min_score:?}\n{entity:?}\n{tags:?}\n{prefer_recent}\n{fields:?}\n{query}")
+}
 
-    Ok(json!({ "hits": hits }))
+pub(crate) async fn silo_search_files(state: &SharedState, query: &str, limit: Option<usize>) -> Value {
+    let limit = limit.unwrap_or(20).clamp(1, 200);
+    let started = std::time::Instant::now();
+    let locked = crate::access::locked_paths(state).await;
+    let mut catalog = state.catalog.write().await;
+    let mut matches = catalog.search_names(query, limit);
+    matches.retain(|m| locked.collection_of(&m.path).is_none());
+    json!({
+        "matches": matches,
+        "catalog_files": catalog.len(),
+        "elapsed_ms": started.elapsed().as_secs_f64() * 1000.0
+    })
 }
 
-fn validate_safe_path(path: &Path) -> Result<(), String> {
+pub(crate) fn validate_safe_path(path: &Path) -> Result<(), ToolError> {
     // Light "safety" check: reject obviously weird inputs; you can tighten this later.
     if path.as_os_str().is_empty() {
-        return Err("Path must not be empty".to_string());
+        return Err(ToolError::empty_path());
     }
     if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
-        return Err("Path must not contain '..'".to_string());
+        return Err(ToolError::parent_dir_path());
     }
     Ok(())
 }
@@ -0,0 +1,125 @@
+//! Opt-in JSON-RPC traffic log for debugging clients (`debug.log_traffic` or `SILO_LOG_TRAFFIC=1`).
+//!
+//! Every request and response line is appended to `logs/traffic.jsonl` in the data dir, rotated at
+//! `debug.max_log_bytes` (keeping `debug.keep_files` old files). With `debug.redact` (the default)
+//! only protocol structure survives: method and tool names, ids, numbers, booleans and a few enum
+//! fields are kept, every other string becomes `"<redacted len=N #hash>"`. The hash is stable, so
+//! repeated queries can still be correlated without their text being in the log. Passwords and
+//! secrets (`silo_secret_set`) are never logged, redacted or not.
+
+use crate::config::DebugConfig;
+use crate::logfile::RotatingFile;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE: &str = "traffic.jsonl";
+
+/// Keys whose string values are protocol, not personal content.
+const KEEP_KEYS: &[&str] = &[
+    "jsonrpc",
+    "id",
+    "method",
+    "name",
+    "type",
+    "protocolVersion",
+    "version",
+    "kind",
+    "state",
+    "status",
+    "metric",
+    "class",
+    "tool",
+];
+
+/// Keys whose string values are replaced with `SECRET` even when redaction is off.
+const SECRET_KEYS: &[&str] = &["password", "secret"];
+const SECRET: &str = "<secret>";
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+pub struct TrafficLog {
+    redact: bool,
+    file: Mutex<RotatingFile>,
+    sessions: AtomicU64,
+}
+
+impl TrafficLog {
+    /// The log, when enabled in `cfg` or by `SILO_LOG_TRAFFIC`.
+    pub fn from_config(cfg: &DebugConfig, data_dir: &Path) -> Option<Self> {
+        let env = std::env::var("SILO_LOG_TRAFFIC")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        if !(cfg.log_traffic || env) {
+            return None;
+        }
+        let path = data_dir.join(LOG_DIR).join(LOG_FILE);
+        tracing::info!(
+            "Logging JSON-RPC traffic to {} ({})",
+            path.display(),
+            if cfg.redact { "redacted" } else { "NOT redacted" }
+        );
+        Some(Self {
+            redact: cfg.redact,
+            file: Mutex::new(RotatingFile::new(path, cfg.max_log_bytes, cfg.keep_files)),
+            sessions: AtomicU64::new(0),
+        })
+    }
+
+    /// Id for a new client session (one `server::serve` call).
+    pub fn new_session(&self) -> u64 {
+        self.sessions.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Append one JSON-RPC line. Best effort: I/O errors are logged and dropped.
+    pub fn record(&self, session: u64, direction: Direction, line: &str) {
+        let message = match serde_json::from_str::<Value>(line) {
+            Ok(mut v) => {
+                redact(&mut v, None, self.redact);
+                v
+            }
+            Err(_) if self.redact => json!({ "unparsed": redacted_str(line) }),
+            Err(_) => json!({ "unparsed": line }),
+        };
+        let entry = json!({
+            "ts_ms": std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            "session": session,
+            "dir": match direction {
+                Direction::In => "in",
+                Direction::Out => "out",
+            },
+            "message": message,
+        });
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.write_all(format!("{entry}\n").as_bytes()) {
+            tracing::warn!("Failed to write traffic log {}: {e}", file.path().display());
+        }
+    }
+}
+
+/// Replace secrets in `value`, and with `content` all content strings; `key` is the object key
+/// `value` sits under.
+fn redact(value: &mut Value, key: Option<&str>, content: bool) {
+    match value {
+        Value::String(s) if key.is_some_and(|k| SECRET_KEYS.contains(&k)) => *s = SECRET.to_string(),
+        Value::String(s) if content && !key.is_some_and(|k| KEEP_KEYS.contains(&k)) => *s = redacted_str(s),
+        Value::Array(items) => items.iter_mut().for_each(|v| redact(v, key, content)),
+        Value::Object(obj) => obj.iter_mut().for_each(|(k, v)| redact(v, Some(k.as_str()), content)),
+        _ => {}
+    }
+}
+
+fn redacted_str(s: &str) -> String {
+    let hash = blake3::hash(s.as_bytes()).to_hex();
+    format!("<redacted len={} #{}>", s.chars().count(), &hash[..8])
+}
@@ -0,0 +1,183 @@
+//! Recently-deleted awareness (`silo_purge_deleted`).
+//!
+//! Trash folders are excluded from indexing by default; this removes index entries whose file
+//! was moved to the trash since it was indexed, so search stops pointing at deleted files.
+//!
+//! - freedesktop (Linux): `Trash/info/*.trashinfo` records the original path exactly
+//! - macOS: `~/.Trash` only keeps the file name, so a missing file counts as trashed when a
+//!   file of the same name and size is in the trash
+
+use crate::errors::ToolError;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PurgeDeletedArgs {
+    /// Also purge entries whose file is gone but not found in the trash.
+    #[serde(default)]
+    pub include_missing: bool,
+    /// Report what would be purged without changing the index.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    /// Removed (or, on a dry run, removable) paths.
+    pub purged: Vec<String>,
+    pub trashed: usize,
+    /// Missing files not found in the trash (purged only with `include_missing`).
+    pub missing: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// What the trash folders contain.
+#[derive(Default)]
+struct TrashIndex {
+    /// Original paths (freedesktop trash info files).
+    originals: HashSet<PathBuf>,
+    /// File name -> sizes (macOS trash keeps names only).
+    names: HashMap<String, Vec<u64>>,
+}
+
+impl TrashIndex {
+    fn contains(&self, path: &str, size_bytes: Option<i64>) -> bool {
+        if self.originals.contains(Path::new(path)) {
+            return true;
+        }
+        let Some(name) = Path::new(path).file_name() else {
+            return false;
+        };
+        self.names
+            .get(name.to_string_lossy().as_ref())
+            .is_some_and(|sizes| size_bytes.is_none_or(|s| sizes.contains(&(s as u64))))
+    }
+}
+
+/// MCP tool entrypoint (`silo_purge_deleted`).
+pub async fn purge_deleted_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: PurgeDeletedArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = purge_deleted(state, &args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize purge report: {e}").into())
+}
+
+pub async fn purge_deleted(state: &SharedState, args: &PurgeDeletedArgs) -> Result<PurgeReport, String> {
+    let entries: Vec<(String, Option<i64>)> = state
+        .catalog
+        .read()
+        .await
+        .entries()
+        .filter(|e| e.is_file())
+        .map(|e| (e.path.clone(), e.size_bytes))
+        .collect();
+
+    let mut gone = vec![];
+    for (path, size) in entries {
+        if tokio::fs::symlink_metadata(&path).await.is_err() {
+            gone.push((path, size));
+        }
+    }
+
+    let trash = if gone.is_empty() { TrashIndex::default() } else { load_trash().await };
+    let mut report = PurgeReport {
+        dry_run: args.dry_run,
+        purged: vec![],
+        trashed: 0,
+        missing: 0,
+        errors: vec![],
+    };
+    for (path, size) in gone {
+        if trash.contains(&path, size) {
+            report.trashed += 1;
+        } else {
+            report.missing += 1;
+            if !args.include_missing {
+                continue;
+            }
+        }
+        if !args.dry_run {
+            if let Err(e) = state.db.delete_file(&path).await {
+                report.errors.push(format!("{path}: {e}"));
+                continue;
+            }
+            state.catalog.write().await.remove(&path);
+        }
+        report.purged.push(path);
+    }
+
+    if !args.dry_run && !report.purged.is_empty() {
+        state.index_changed();
+        state.save_catalog().await;
+    }
+    report.purged.sort();
+    Ok(report)
+}
+
+async fn load_trash() -> TrashIndex {
+    let mut trash = TrashIndex::default();
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| expand_tilde("~/.local/share"));
+    let info_dir = data_home.join("Trash").join("info");
+    if let Ok(mut rd) = tokio::fs::read_dir(&info_dir).await {
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            if entry.path().extension().is_some_and(|e| e == "trashinfo")
+                && let Ok(s) = tokio::fs::read_to_string(entry.path()).await
+                && let Some(original) = parse_trashinfo(&s)
+            {
+                trash.originals.insert(original);
+            }
+        }
+    }
+
+    if let Ok(mut rd) = tokio::fs::read_dir(expand_tilde("~/.Trash")).await {
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            trash
+                .names
+                .entry(entry.file_name().to_string_lossy().to_string())
+                .or_default()
+                .push(meta.len());
+        }
+    }
+    trash
+}
+
+/// `Path=` of a `.trashinfo` file (percent-encoded, absolute).
+fn parse_trashinfo(s: &str) -> Option<PathBuf> {
+    let raw = s.lines().find_map(|l| l.strip_prefix("Path="))?;
+    let path = PathBuf::from(percent_decode(raw.trim()));
+    path.is_absolute().then_some(path)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
@@ -0,0 +1,167 @@
+//! Content pushed by MCP clients (`silo_ingest_content`) instead of a path on disk.
+//!
+//! The client sends text, or the base64 bytes of a document, under a virtual path (and optionally
+//! a MIME type). Bytes are written to `<data_dir>/uploads/` with the document's extension,
+//! extracted like a file (PDF text, OCR for images, plain text) and removed again. The text then
+//! goes through the file pipeline (`crate::ingest::process_extracted`: chunking, embedding,
+//! storing, entities) and is stored as `upload://<virtual path>` with `source: "upload"`.
+//! Sending the same virtual path again replaces the earlier content.
+
+use crate::catalog::now_epoch_secs;
+use crate::errors::ToolError;
+use crate::extract::{extract_text, text_as, truncate_text, ExtractResult};
+use crate::ingest::ExtractedDocument;
+use crate::state::SharedState;
+use crate::xattrs::FileAttributes;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Component, Path};
+
+pub const PATH_PREFIX: &str = "upload://";
+
+/// Text and base64 bytes accepted when no filesystem policy sets the limits.
+const DEFAULT_MAX_TEXT_BYTES: u64 = 2 * 1024 * 1024;
+const DEFAULT_MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct IngestContentArgs {
+    /// Where the content is stored, e.g. `inbox/meeting.md` (becomes `upload://inbox/meeting.md`).
+    pub virtual_path: String,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Document bytes, base64-encoded (standard alphabet).
+    #[serde(default)]
+    pub base64: Option<String>,
+    /// MIME type; picks the extractor when `virtual_path` has no extension.
+    #[serde(default)]
+    pub mime: Option<String>,
+    /// Modification time to record (default: now).
+    #[serde(default)]
+    pub modified_epoch_secs: Option<i64>,
+}
+
+/// MCP tool entrypoint (`silo_ingest_content`).
+pub async fn ingest_content_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: IngestContentArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let Some(fs_cfg) = state.filesystem_config().await else {
+        return Err("No filesystem source configured".into());
+    };
+    let name = virtual_name(&args.virtual_path, args.mime.as_deref())?;
+    let path = format!("{PATH_PREFIX}{name}");
+    let (max_text_bytes, max_file_bytes) = match state.filesystem_policy().await {
+        Some(p) => (p.max_text_bytes_for(Path::new(&name)), p.max_file_size_bytes),
+        None => (DEFAULT_MAX_TEXT_BYTES, DEFAULT_MAX_FILE_BYTES),
+    };
+
+    let extracted = match (args.text, args.base64) {
+        (Some(text), None) => text_result(&name, text, max_text_bytes),
+        (None, Some(encoded)) => {
+            // Base64 is 4 characters per 3 bytes; refuse before decoding.
+            if encoded.len() as u64 / 4 * 3 > max_file_bytes {
+                return Err(format!("Content of {path} is larger than {max_file_bytes} bytes").into());
+            }
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| ToolError::invalid_arguments(format!("`base64`: {e}")))?;
+            extract_bytes(state, &name, &bytes, max_text_bytes).await?
+        }
+        _ => return Err(ToolError::invalid_arguments("pass exactly one of `text` and `base64`")),
+    };
+    if extracted.text.trim().is_empty() {
+        return Err(format!("No text could be extracted from {path}").into());
+    }
+
+    let size_bytes = extracted.text.len() as i64;
+    let modified = args.modified_epoch_secs.unwrap_or_else(now_epoch_secs);
+    let truncated = extracted.truncated;
+    let doc = ExtractedDocument {
+        path: path.clone(),
+        extracted,
+        size_bytes: Some(size_bytes),
+        mtime_epoch_secs: Some(modified),
+        created_epoch_secs: Some(modified),
+        inode: None,
+        attrs: FileAttributes::default(),
+    };
+    let res = crate::ingest::process_extracted(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
+        doc,
+        &fs_cfg.chunking(),
+    )
+    .await;
+
+    state.index_changed();
+    let stats = res?;
+    state.metrics.record_ingest(&stats);
+    if stats.stored {
+        state.catalog.write().await.record_ingest(&stats);
+        state.save_catalog().await;
+    }
+    let mut out = serde_json::to_value(&stats).map_err(|e| format!("failed to serialize ingest stats: {e}"))?;
+    out["source"] = json!(crate::database::ChunkSource::Upload.as_str());
+    out["truncated"] = json!(truncated);
+    Ok(out)
+}
+
+/// `virtual_path` without a scheme or leading slashes, with an extension for `mime` when it has
+/// none.
+fn virtual_name(virtual_path: &str, mime: Option<&str>) -> Result<String, ToolError> {
+    let name = virtual_path.trim();
+    let name = name.strip_prefix(PATH_PREFIX).unwrap_or(name).trim_start_matches('/');
+    if name.is_empty() {
+        return Err(ToolError::invalid_arguments("`virtual_path` must not be empty"));
+    }
+    if name.contains("://") || Path::new(name).components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(ToolError::invalid_arguments("`virtual_path` must be a relative path without '..'"));
+    }
+    match mime.and_then(extension_for_mime) {
+        Some(ext) if Path::new(name).extension().is_none() => Ok(format!("{name}.{ext}")),
+        _ => Ok(name.to_string()),
+    }
+}
+
+fn extension_for_mime(mime: &str) -> Option<&'static str> {
+    let mime = mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    Some(match mime.as_str() {
+        "application/pdf" => "pdf",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/tiff" => "tiff",
+        "image/bmp" => "bmp",
+        "text/markdown" => "md",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        "application/json" => "json",
+        m if m.starts_with("text/") => "txt",
+        _ => return None,
+    })
+}
+
+/// Text sent as is, cut to `max_text_bytes` (at a character boundary); JSON is flattened and
+/// logs chunked by time like files with `name`.
+fn text_result(name: &str, mut text: String, max_text_bytes: u64) -> ExtractResult {
+    let truncated = truncate_text(&mut text, max_text_bytes);
+    text_as(Path::new(name), text, truncated)
+}
+
+/// Extract `bytes` as a file named like `name`, through a temporary copy in `<data_dir>/uploads/`.
+async fn extract_bytes(state: &SharedState, name: &str, bytes: &[u8], max_text_bytes: u64) -> Result<ExtractResult, String> {
+    let dir = state.data_dir.join("uploads");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let ext = Path::new(name).extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let tmp = dir.join(format!("{}{ext}", crate::consent::random_id(name)));
+    tokio::fs::write(&tmp, bytes)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+    let res = extract_text(&tmp, max_text_bytes).await;
+    let _ = tokio::fs::remove_file(&tmp).await;
+    res
+}
@@ -0,0 +1,253 @@
+//! Index integrity check (`silo_verify`).
+//!
+//! Samples indexed files from the catalog, reads all their chunk rows back and re-extracts the
+//! source files, then reports drift:
+//! - `missing`: the source file is gone (use `silo_purge_deleted`)
+//! - `modified`: the extracted text no longer matches the stored `file_hash`
+//! - `row_count`: the table holds a different number of chunks than the catalog recorded
+//! - `corrupted_row`: a row's id does not match its path, chunk index and content
+//! - `bad_embedding`: wrong dimension, non-finite values, or all zeros with embeddings enabled
+//! - `hash_mismatch`: rows disagree with the catalog about the file hash
+//!
+//! With `repair`, every file with drift other than `missing` is re-ingested.
+
+use crate::catalog::now_epoch_secs;
+use crate::database::{chunk_row_id, ChunkRow};
+use crate::errors::ToolError;
+use crate::state::SharedState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+const DEFAULT_SAMPLE: usize = 50;
+const MAX_SAMPLE: usize = 500;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyArgs {
+    /// Files to sample (default 50, max 500).
+    #[serde(default)]
+    pub sample: Option<usize>,
+    /// Check these files instead of a random sample.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Re-ingest files with drift.
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    Missing,
+    Modified,
+    RowCount,
+    CorruptedRow,
+    BadEmbedding,
+    HashMismatch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDrift {
+    pub path: String,
+    pub kinds: BTreeSet<DriftKind>,
+    pub details: Vec<String>,
+    /// Re-ingesting would fix it (the file still exists).
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub at_epoch_secs: i64,
+    pub sampled_files: usize,
+    pub sampled_chunks: usize,
+    pub ok_files: usize,
+    pub drift: Vec<FileDrift>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub repaired: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// MCP tool entrypoint (`silo_verify`).
+pub async fn verify_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: VerifyArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let report = verify(state, args).await?;
+    serde_json::to_value(report).map_err(|e| format!("failed to serialize verify report: {e}").into())
+}
+
+pub async fn verify(state: &SharedState, args: VerifyArgs) -> Result<VerifyReport, String> {
+    if !state.db.is_enabled() {
+        return Err(state.db.disabled_reason().unwrap_or("DB disabled").to_string());
+    }
+    if args.repair && crate::tools::read_only(state) {
+        return Err("silo_verify cannot repair in read-only mode".to_string());
+    }
+    let locked = crate::access::locked_paths(state).await;
+    let max_text_bytes = state
+        .fs_policy
+        .read()
+        .await
+        .as_ref()
+        .map(|p| p.max_text_bytes)
+        .unwrap_or(2 * 1024 * 1024);
+
+    // (path, catalog file hash, catalog chunk count)
+    let entries: Vec<(String, Option<String>, usize)> = {
+        let catalog = state.catalog.read().await;
+        let mut entries: Vec<_> = if args.paths.is_empty() {
+            catalog
+                .entries()
+                .filter(|e| !e.is_evicted() && e.chunks > 0 && !e.path.contains("://"))
+                .map(|e| (e.path.clone(), e.file_hash.clone(), e.chunks))
+                .collect()
+        } else {
+            args.paths
+                .iter()
+                .map(|p| crate::state::expand_tilde(p).to_string_lossy().to_string())
+                .filter_map(|p| catalog.get(&p).map(|e| (e.path.clone(), e.file_hash.clone(), e.chunks)))
+                .collect()
+        };
+        entries.retain(|(path, _, _)| locked.collection_of(path).is_none());
+        let sample = args.sample.unwrap_or(DEFAULT_SAMPLE).clamp(1, MAX_SAMPLE);
+        if args.paths.is_empty() {
+            shuffle(&mut entries);
+        }
+        entries.truncate(sample);
+        entries
+    };
+
+    let paths: Vec<String> = entries.iter().map(|(p, _, _)| p.clone()).collect();
+    let mut rows: HashMap<String, Vec<ChunkRow>> = HashMap::new();
+    for row in state.db.chunks_for_paths(&paths).await.map_err(|e| e.to_string())? {
+        rows.entry(row.path.clone()).or_default().push(row);
+    }
+
+    let mut report = VerifyReport {
+        at_epoch_secs: now_epoch_secs(),
+        sampled_files: entries.len(),
+        ..VerifyReport::default()
+    };
+    for (path, catalog_hash, catalog_chunks) in entries {
+        let rows = rows.remove(&path).unwrap_or_default();
+        report.sampled_chunks += rows.len();
+        let drift = check_file(&path, catalog_hash.as_deref(), catalog_chunks, &rows, max_text_bytes).await;
+        match drift {
+            Some(d) => report.drift.push(d),
+            None => report.ok_files += 1,
+        }
+    }
+
+    if args.repair {
+        for d in report.drift.iter().filter(|d| d.repairable) {
+            match reingest(state, &d.path, max_text_bytes).await {
+                Ok(()) => report.repaired.push(d.path.clone()),
+                Err(e) => report.errors.push(format!("{}: {e}", d.path)),
+            }
+        }
+        if !report.repaired.is_empty() {
+            state.index_changed();
+            state.save_catalog().await;
+        }
+    }
+    Ok(report)
+}
+
+async fn check_file(
+    path: &str,
+    catalog_hash: Option<&str>,
+    catalog_chunks: usize,
+    rows: &[ChunkRow],
+    max_text_bytes: u64,
+) -> Option<FileDrift> {
+    let mut drift = FileDrift {
+        path: path.to_string(),
+        kinds: BTreeSet::new(),
+        details: vec![],
+        repairable: true,
+    };
+
+    if !Path::new(path).is_file() {
+        drift.kinds.insert(DriftKind::Missing);
+        drift.details.push("source file no longer exists".to_string());
+        drift.repairable = false;
+    } else {
+        match crate::extract::extract_text(Path::new(path), max_text_bytes).await {
+            Ok(extracted) => {
+                let hash = blake3::hash(extracted.text.as_bytes()).to_hex().to_string();
+                if catalog_hash.is_some_and(|h| h != hash) {
+                    drift.kinds.insert(DriftKind::Modified);
+                    drift.details.push("source changed since it was indexed".to_string());
+                }
+            }
+            Err(e) => drift.details.push(format!("could not re-read source: {e}")),
+        }
+    }
+
+    if rows.len() != catalog_chunks {
+        drift.kinds.insert(DriftKind::RowCount);
+        drift
+            .details
+            .push(format!("{} rows stored, catalog expects {catalog_chunks}", rows.len()));
+    }
+    let zero_is_bad = cfg!(feature = "embeddings");
+    for row in rows {
+        if row.id != chunk_row_id(&row.path, row.chunk_index, &row.content) {
+            drift.kinds.insert(DriftKind::CorruptedRow);
+            drift
+                .details
+                .push(format!("chunk {}: id does not match its content", row.chunk_index));
+        }
+        let bad_embedding = row.embedding.len() != crate::embed::EMBEDDING_DIM
+            || row.embedding.iter().any(|x| !x.is_finite())
+            || (zero_is_bad && row.embedding.iter().all(|x| *x == 0.0));
+        if bad_embedding {
+            drift.kinds.insert(DriftKind::BadEmbedding);
+            drift.details.push(format!(
+                "chunk {}: bad embedding ({} dims)",
+                row.chunk_index,
+                row.embedding.len()
+            ));
+        }
+        if row.file_hash.as_deref() != catalog_hash {
+            drift.kinds.insert(DriftKind::HashMismatch);
+            drift
+                .details
+                .push(format!("chunk {}: file_hash differs from the catalog", row.chunk_index));
+        }
+    }
+
+    (!drift.kinds.is_empty()).then_some(drift)
+}
+
+async fn reingest(state: &SharedState, path: &str, max_text_bytes: u64) -> Result<(), String> {
+    let fs_cfg = state
+        .filesystem_config()
+        .await
+        .ok_or_else(|| "No filesystem source configured".to_string())?;
+    let stats = crate::ingest::process_file(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
+        path,
+        max_text_bytes,
+        &fs_cfg.chunking(),
+    )
+    .await?;
+    if stats.stored {
+        state.catalog.write().await.record_ingest(&stats);
+    }
+    Ok(())
+}
+
+/// Order by a per-call keyed hash: a different sample on every run without an RNG dependency.
+fn shuffle<T>(items: &mut [(String, T, usize)]) {
+    use std::hash::{BuildHasher, Hasher};
+    let keys = std::collections::hash_map::RandomState::new();
+    items.sort_by_cached_key(|(path, _, _)| {
+        let mut h = keys.build_hasher();
+        h.write(path.as_bytes());
+        h.finish()
+    });
+}
@@ -0,0 +1,301 @@
+//! Web pages (`silo_ingest_url`, `web` config): reading-list articles in the knowledge base.
+//!
+//! Off unless `web.enabled` is set. The page is downloaded with `curl` into `<data_dir>/web/`
+//! (at most `web.max_bytes`, within `web.timeout_secs`), reduced to its text by the HTML
+//! extractor (`crate::extract::html_to_text`; PDFs and plain text are extracted like files),
+//! headed with its title and URL, and stored under the URL itself with `source: "web"` through the
+//! file pipeline (`crate::ingest::process_extracted`). Ingesting the same URL again replaces it.
+//! Federated search keeps web pages in their own `web` partition.
+//!
+//! Only `http`/`https` URLs are fetched. Unless `web.allow_private_hosts` is set, every host on
+//! the way (redirects are followed here, not by curl) must resolve to public addresses only, and
+//! curl is pinned to the address that was checked.
+
+use crate::catalog::now_epoch_secs;
+use crate::config::WebConfig;
+use crate::errors::ToolError;
+use crate::extract::{extract_html, extract_text, truncate_text, ExtractResult};
+use crate::ingest::ExtractedDocument;
+use crate::state::SharedState;
+use crate::xattrs::FileAttributes;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Web pages are stored under their URL.
+pub const PATH_PREFIXES: &[&str] = &["https://", "http://"];
+/// Redirects followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+/// Text kept when no filesystem policy sets `max_text_bytes`.
+const DEFAULT_MAX_TEXT_BYTES: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct IngestUrlArgs {
+    pub url: String,
+}
+
+/// URL ingestion settings, read at startup.
+pub struct WebIngest {
+    max_bytes: u64,
+    timeout_secs: u64,
+    allow_private_hosts: bool,
+}
+
+impl WebIngest {
+    pub fn from_config(cfg: &WebConfig) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        tracing::info!("Web page ingestion enabled (silo_ingest_url)");
+        Some(Self {
+            max_bytes: cfg.max_bytes,
+            timeout_secs: cfg.timeout_secs.max(1),
+            allow_private_hosts: cfg.allow_private_hosts,
+        })
+    }
+}
+
+/// A downloaded page.
+struct Fetched {
+    file: PathBuf,
+    content_type: String,
+    /// URL after redirects.
+    final_url: String,
+    size_bytes: u64,
+}
+
+/// MCP tool entrypoint (`silo_ingest_url`).
+pub async fn ingest_url_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: IngestUrlArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let Some(web) = &state.web else {
+        return Err("Web page ingestion is disabled; set `web.enabled` in the config".into());
+    };
+    let Some(fs_cfg) = state.filesystem_config().await else {
+        return Err("No filesystem source configured".into());
+    };
+    // The fragment only scrolls within the page.
+    let url = args.url.trim();
+    let url = url.split('#').next().unwrap_or(url).to_string();
+    host_of(&url)?;
+    let max_text_bytes = match state.filesystem_policy().await {
+        Some(p) => p.max_text_bytes,
+        None => DEFAULT_MAX_TEXT_BYTES,
+    };
+
+    let dir = state.data_dir.join("web");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let tmp = dir.join(crate::consent::random_id(&url));
+    let fetched = fetch(web, &url, &tmp).await;
+    let extracted = match &fetched {
+        Ok(f) => extract(f, web.max_bytes).await,
+        Err(e) => Err(e.clone()),
+    };
+    let _ = tokio::fs::remove_file(&tmp).await;
+    let fetched = fetched?;
+    let (title, body) = extracted?;
+    if body.text.trim().is_empty() {
+        return Err(format!("No text could be extracted from {url}").into());
+    }
+
+    let mut text = match &title {
+        Some(title) => format!("Title: {title}\nURL: {url}\n\n{}", body.text),
+        None => format!("URL: {url}\n\n{}", body.text),
+    };
+    let truncated = truncate_text(&mut text, max_text_bytes) || body.truncated;
+    let now = now_epoch_secs();
+    let doc = ExtractedDocument {
+        path: url.clone(),
+        extracted: ExtractResult { text, truncated, ..body },
+        size_bytes: Some(fetched.size_bytes as i64),
+        mtime_epoch_secs: Some(now),
+        created_epoch_secs: Some(now),
+        inode: None,
+        attrs: FileAttributes::default(),
+    };
+    let res = crate::ingest::process_extracted(
+        &state.db,
+        &state.embedder,
+        state.entities.as_deref(),
+        fs_cfg.drop_boilerplate.then_some(&*state.boilerplate),
+        doc,
+        &fs_cfg.chunking(),
+    )
+    .await;
+
+    state.index_changed();
+    let stats = res?;
+    state.metrics.record_ingest(&stats);
+    if stats.stored {
+        state.catalog.write().await.record_ingest(&stats);
+        state.save_catalog().await;
+    }
+    let mut out = serde_json::to_value(&stats).map_err(|e| format!("failed to serialize ingest stats: {e}"))?;
+    out["source"] = json!(crate::database::ChunkSource::Web.as_str());
+    out["collection"] = json!(crate::federation::WEB);
+    out["url"] = json!(url);
+    out["final_url"] = json!(fetched.final_url);
+    out["title"] = json!(title);
+    out["content_type"] = json!(fetched.content_type);
+    out["truncated"] = json!(truncated);
+    Ok(out)
+}
+
+/// The title and text of a download, by content type.
+async fn extract(f: &Fetched, max_bytes: u64) -> Result<(Option<String>, ExtractResult), String> {
+    let mime = f.content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    // Extractors for files go by extension.
+    let ext = match mime.as_str() {
+        "text/html" | "application/xhtml+xml" | "" => return extract_html(&f.file, max_bytes).await,
+        "application/pdf" => "pdf",
+        m if m.starts_with("text/") => "txt",
+        m => return Err(format!("Unsupported content type {m} at {}", f.final_url)),
+    };
+    let typed = f.file.with_extension(ext);
+    tokio::fs::rename(&f.file, &typed)
+        .await
+        .map_err(|e| format!("Failed to rename {}: {e}", f.file.display()))?;
+    let res = extract_text(&typed, max_bytes).await;
+    let _ = tokio::fs::rename(&typed, &f.file).await;
+    res.map(|r| (None, r))
+}
+
+/// Download `url` to `file`, following redirects (each target is checked like `url`).
+async fn fetch(web: &WebIngest, url: &str, file: &Path) -> Result<Fetched, String> {
+    let mut current = url.to_string();
+    for _ in 0..=MAX_REDIRECTS {
+        let (host, port) = host_of(&current)?;
+        let mut cmd = Command::new("curl");
+        cmd.args(["-sS", "--fail", "--proto", "=http,https"])
+            .args(["--max-time", &web.timeout_secs.to_string()])
+            .args(["--max-filesize", &web.max_bytes.to_string()])
+            .args(["-A", concat!("Silo/", env!("CARGO_PKG_VERSION"))])
+            .arg("-o")
+            .arg(file)
+            .args(["-w", "%{http_code}\t%{content_type}\t%{redirect_url}"]);
+        if !web.allow_private_hosts {
+            let addr = public_addr(&host, port).await?;
+            cmd.arg("--resolve").arg(format!("{host}:{port}:{}", resolve_ip(addr.ip())));
+        }
+        cmd.arg(&current).kill_on_drop(true);
+        let output = tokio::time::timeout(Duration::from_secs(web.timeout_secs + 5), cmd.output())
+            .await
+            .map_err(|_| format!("Fetching {current} timed out after {}s", web.timeout_secs))?
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => "curl was not found; install it to ingest web pages".to_string(),
+                _ => format!("Failed to run curl: {e}"),
+            })?;
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Fetching {current} failed: {}", err.trim()));
+        }
+        let written = String::from_utf8_lossy(&output.stdout).to_string();
+        let mut fields = written.splitn(3, '\t');
+        let code = fields.next().unwrap_or_default();
+        let content_type = fields.next().unwrap_or_default().to_string();
+        let redirect = fields.next().unwrap_or_default().trim().to_string();
+        if code.starts_with('3') && !redirect.is_empty() {
+            current = redirect;
+            continue;
+        }
+        let size = tokio::fs::metadata(file).await.map(|m| m.len()).unwrap_or(0);
+        if size > web.max_bytes {
+            return Err(format!("{current} is larger than {} bytes (web.max_bytes)", web.max_bytes));
+        }
+        return Ok(Fetched {
+            file: file.to_path_buf(),
+            content_type,
+            final_url: current,
+            size_bytes: size,
+        });
+    }
+    Err(format!("{url} redirected more than {MAX_REDIRECTS} times"))
+}
+
+/// Host and port of an `http`/`https` URL.
+fn host_of(url: &str) -> Result<(String, u16), ToolError> {
+    let lower = url.to_ascii_lowercase();
+    let (rest, default_port) = if lower.starts_with("https://") {
+        (&url[8..], 443)
+    } else if lower.starts_with("http://") {
+        (&url[7..], 80)
+    } else {
+        return Err(ToolError::invalid_arguments(format!("{url} is not an http(s) URL")));
+    };
+    if url.chars().any(char::is_whitespace) {
+        return Err(ToolError::invalid_arguments(format!("{url} contains whitespace")));
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority.rsplit('@').next().unwrap_or_default();
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6.split_once(']').ok_or_else(|| ToolError::invalid_arguments(format!("bad host in {url}")))?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(p) => p.parse().map_err(|_| ToolError::invalid_arguments(format!("bad port in {url}")))?,
+        None => default_port,
+    };
+    if host.is_empty() {
+        return Err(ToolError::invalid_arguments(format!("{url} has no host")));
+    }
+    Ok((host.to_ascii_lowercase(), port))
+}
+
+/// The first address of `host`, when all of them are public.
+async fn public_addr(host: &str, port: u16) -> Result<SocketAddr, String> {
+    let refused = || format!("{host} is a local or private address; set `web.allow_private_hosts` to fetch it");
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
+        return Err(refused());
+    }
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Could not resolve {host}: {e}"))?
+        .collect();
+    if addrs.iter().any(|a| is_private(a.ip())) {
+        return Err(refused());
+    }
+    addrs.into_iter().next().ok_or_else(|| format!("Could not resolve {host}"))
+}
+
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                // Carrier-grade NAT (100.64.0.0/10).
+                || (v4.octets()[0] == 100 && v4.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_private(IpAddr::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                v6.is_loopback()
+                    || v6.is_unspecified()
+                    // Unique local (fc00::/7) and link-local (fe80::/10).
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// `ip` as curl's `--resolve` wants it (IPv6 in brackets).
+fn resolve_ip(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{v6}]"),
+    }
+}
@@ -0,0 +1,266 @@
+//! File writes by tools (`silo_write_file`), off unless `writes.enabled`.
+//!
+//! Rails, in order:
+//! - the tool is hidden and refused unless enabled, and in read-only mode (it is a write tool)
+//! - the path must resolve (symlinks in existing folders followed) inside `writes.roots`, or the
+//!   filesystem roots when none are set, and outside Silo's own data and config folders
+//! - an existing file is only replaced with `overwrite: true`, and never through a symlink
+//! - content is capped at `writes.max_bytes`
+//!
+//! Every attempt, allowed or not, is appended to `logs/writes.jsonl` (`WriteAudit`) in the data dir with the
+//! content's size and hash (not the content). Collection locks and consent prompts apply to the
+//! `path` argument like for any other tool.
+
+use crate::catalog::now_epoch_secs;
+use crate::config::WritesConfig;
+use crate::errors::ToolError;
+use crate::logfile::RotatingFile;
+use crate::state::{expand_tilde, SharedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+
+const LOG_DIR: &str = "logs";
+const AUDIT_FILE: &str = "writes.jsonl";
+const AUDIT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+const AUDIT_KEEP_FILES: usize = 3;
+
+#[derive(Debug, Deserialize)]
+pub struct WriteFileArgs {
+    pub path: String,
+    pub content: String,
+    /// Replace an existing file.
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Create missing folders (inside the allowed roots).
+    #[serde(default)]
+    pub create_dirs: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteFileResult {
+    pub path: String,
+    pub bytes: u64,
+    /// `false` when an existing file was replaced.
+    pub created: bool,
+}
+
+/// Enabled writes: settings read at startup.
+pub struct FileWrites {
+    roots: Vec<PathBuf>,
+    max_bytes: u64,
+}
+
+impl FileWrites {
+    pub fn from_config(cfg: &WritesConfig) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        tracing::info!("File writes enabled (silo_write_file)");
+        Some(Self {
+            roots: cfg.roots.iter().map(|r| expand_tilde(&r.to_string_lossy())).collect(),
+            max_bytes: cfg.max_bytes,
+        })
+    }
+}
+
+/// `logs/writes.jsonl`, shared by every tool that writes user files (`silo_write_file`,
+/// `silo_append_note`). Opened on the first entry.
+pub struct WriteAudit {
+    file: Mutex<RotatingFile>,
+}
+
+impl WriteAudit {
+    pub fn new(data_dir: &Path) -> Self {
+        let path = data_dir.join(LOG_DIR).join(AUDIT_FILE);
+        Self {
+            file: Mutex::new(RotatingFile::new(path, AUDIT_MAX_BYTES, AUDIT_KEEP_FILES)),
+        }
+    }
+
+    /// Append one audit line. Best effort: I/O errors are logged and dropped.
+    pub fn record(&self, entry: Value) {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{entry}").and_then(|()| file.flush()) {
+            tracing::warn!("Failed to write {}: {e}", file.path().display());
+        }
+    }
+}
+
+/// Base audit entry for a write of `content` requested as `path`.
+pub(crate) fn audit_entry(tool: &str, path: &str, content: &str) -> Value {
+    json!({
+        "at_epoch_secs": now_epoch_secs(),
+        "tool": tool,
+        "path": path,
+        "bytes": content.len(),
+        "blake3": blake3::hash(content.as_bytes()).to_hex().to_string()
+    })
+}
+
+/// MCP tool entrypoint (`silo_write_file`).
+pub async fn write_file_tool(state: &SharedState, args: Value) -> Result<Value, ToolError> {
+    let args: WriteFileArgs = serde_json::from_value(args).map_err(ToolError::invalid_arguments)?;
+    let result = write_file(state, &args).await?;
+    serde_json::to_value(result).map_err(|e| format!("failed to serialize write result: {e}").into())
+}
+
+pub async fn write_file(state: &SharedState, args: &WriteFileArgs) -> Result<WriteFileResult, ToolError> {
+    let Some(writes) = &state.file_writes else {
+        return Err("silo_write_file is disabled (set writes.enabled in the config and restart)".into());
+    };
+    let result = write_checked(state, writes, args).await;
+    let mut entry = audit_entry("silo_write_file", &args.path, &args.content);
+    entry["overwrite"] = json!(args.overwrite);
+    entry["ok"] = json!(result.is_ok());
+    match &result {
+        Ok(r) => {
+            entry["resolved_path"] = json!(r.path);
+            entry["created"] = json!(r.created);
+        }
+        Err(e) => entry["error"] = json!(e.message),
+    }
+    state.write_audit.record(entry);
+    result
+}
+
+async fn write_checked(state: &SharedState, writes: &FileWrites, args: &WriteFileArgs) -> Result<WriteFileResult, ToolError> {
+    let path = expand_tilde(&args.path);
+    crate::tools::validate_safe_path(&path)?;
+    if !path.is_absolute() {
+        return Err("path must be absolute (or start with ~/)".into());
+    }
+    let bytes = args.content.len() as u64;
+    if bytes > writes.max_bytes {
+        return Err(format!("content is {bytes} bytes; writes.max_bytes is {}", writes.max_bytes).into());
+    }
+
+    let target = resolve(&path).await?;
+    let roots = if writes.roots.is_empty() {
+        state.filesystem_roots().await
+    } else {
+        writes.roots.clone()
+    };
+    let mut allowed = false;
+    for root in roots {
+        if let Ok(root) = tokio::fs::canonicalize(&root).await {
+            allowed |= target.path.starts_with(&root);
+        }
+    }
+    if !allowed {
+        return Err(format!("{} is outside the folders writes are allowed in", target.path.display()).into());
+    }
+    refuse_own_folders(state, &target.path).await?;
+    // The `path` argument was checked by the dispatcher; check where it really points too.
+    crate::access::locked_paths(state)
+        .await
+        .check(&target.path.to_string_lossy())?;
+    if target.missing_dirs && !args.create_dirs {
+        return Err("the folder does not exist (pass create_dirs: true to create it)".into());
+    }
+
+    let existing = tokio::fs::symlink_metadata(&target.path).await.ok();
+    if let Some(meta) = &existing {
+        if meta.file_type().is_symlink() {
+            return Err("refusing to write through a symlink".into());
+        }
+        if !meta.is_file() {
+            return Err(format!("{} exists and is not a regular file", target.path.display()).into());
+        }
+        if !args.overwrite {
+            return Err(format!("{} exists (pass overwrite: true to replace it)", target.path.display()).into());
+        }
+    }
+
+    let parent = target.path.parent().ok_or_else(|| "path has no parent folder".to_string())?;
+    if target.missing_dirs {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    if existing.is_some() {
+        replace(&target.path, args.content.as_bytes()).await?;
+    } else {
+        create_new(&target.path, args.content.as_bytes()).await?;
+    }
+    tracing::info!("silo_write_file wrote {bytes} bytes to {}", target.path.display());
+    Ok(WriteFileResult {
+        path: target.path.to_string_lossy().to_string(),
+        bytes,
+        created: existing.is_none(),
+    })
+}
+
+/// Refuse a resolved `path` inside Silo's data or config folder.
+pub(crate) async fn refuse_own_folders(state: &SharedState, path: &Path) -> Result<(), String> {
+    let own = [Some(state.data_dir.as_path()), state.config_path.parent()];
+    for dir in own.into_iter().flatten() {
+        let inside = tokio::fs::canonicalize(dir)
+            .await
+            .is_ok_and(|dir| path.starts_with(dir));
+        if inside {
+            return Err("writing into Silo's own data or config folder is not allowed".to_string());
+        }
+    }
+    Ok(())
+}
+
+struct Target {
+    /// The path with its existing folders canonicalized (the file name itself is not resolved).
+    path: PathBuf,
+    missing_dirs: bool,
+}
+
+/// Resolve `path` through its nearest existing ancestor, so symlinked folders cannot point a
+/// write outside the allowed roots.
+async fn resolve(path: &Path) -> Result<Target, String> {
+    let name = path.file_name().ok_or_else(|| "path must name a file".to_string())?;
+    let mut ancestor = path.parent().ok_or_else(|| "path has no parent folder".to_string())?;
+    let mut rest = vec![];
+    while tokio::fs::metadata(ancestor).await.is_err() {
+        rest.push(ancestor.file_name().ok_or_else(|| "no existing parent folder".to_string())?);
+        ancestor = ancestor.parent().ok_or_else(|| "no existing parent folder".to_string())?;
+    }
+    let mut resolved = tokio::fs::canonicalize(ancestor)
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {e}", ancestor.display()))?;
+    let missing_dirs = !rest.is_empty();
+    for part in rest.into_iter().rev() {
+        resolved.push(part);
+    }
+    resolved.push(name);
+    Ok(Target {
+        path: resolved,
+        missing_dirs,
+    })
+}
+
+async fn create_new(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    file.write_all(content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    file.flush().await.map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Write a sibling temp file, then rename it over `path`, so readers never see half a file.
+async fn replace(path: &Path, content: &[u8]) -> Result<(), String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{name}.silo-write.tmp"));
+    tokio::fs::write(&tmp, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+    if let Err(e) = tokio::fs::rename(&tmp, path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(format!("Failed to replace {}: {e}", path.display()));
+    }
+    Ok(())
+}
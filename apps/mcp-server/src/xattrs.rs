@@ -0,0 +1,68 @@
+//! User tags and comments stored in extended attributes.
+//!
+//! - macOS Finder: `com.apple.metadata:_kMDItemUserTags` (binary plist array of `"Name\n<color>"`)
+//!   and `com.apple.metadata:kMDItemFinderComment` (binary plist string)
+//! - Linux (freedesktop, used by Dolphin/Baloo and others): `user.xdg.tags` (comma separated)
+//!   and `user.xdg.comment`
+//!
+//! Read during ingest; tags are normalized like generated tags so the `tags` search filter
+//! matches both. Missing or unreadable attributes are simply absent.
+
+use std::path::Path;
+
+const FINDER_TAGS: &str = "com.apple.metadata:_kMDItemUserTags";
+const FINDER_COMMENT: &str = "com.apple.metadata:kMDItemFinderComment";
+const XDG_TAGS: &str = "user.xdg.tags";
+const XDG_COMMENT: &str = "user.xdg.comment";
+
+const MAX_COMMENT_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Default)]
+pub struct FileAttributes {
+    /// Normalized, deduplicated user tags.
+    pub tags: Vec<String>,
+    pub comment: Option<String>,
+}
+
+/// Tags and comment of `path` (best effort).
+pub fn read_file_attributes(path: &Path) -> FileAttributes {
+    let mut raw_tags: Vec<String> = vec![];
+    if let Some(v) = get(path, FINDER_TAGS) {
+        // Finder appends the label color index after a newline.
+        raw_tags.extend(
+            plist::from_bytes::<Vec<String>>(&v)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| t.split('\n').next().unwrap_or_default().to_string()),
+        );
+    }
+    if let Some(v) = get(path, XDG_TAGS) {
+        raw_tags.extend(String::from_utf8_lossy(&v).split(',').map(str::to_string));
+    }
+
+    let mut tags: Vec<String> = vec![];
+    for t in raw_tags {
+        let tag = crate::tagging::normalize_tag(&t);
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    let comment = get(path, FINDER_COMMENT)
+        .and_then(|v| plist::from_bytes::<String>(&v).ok())
+        .or_else(|| get(path, XDG_COMMENT).map(|v| String::from_utf8_lossy(&v).to_string()))
+        .map(|c| c.trim().chars().take(MAX_COMMENT_CHARS).collect::<String>())
+        .filter(|c| !c.is_empty());
+
+    FileAttributes { tags, comment }
+}
+
+#[cfg(unix)]
+fn get(path: &Path, name: &str) -> Option<Vec<u8>> {
+    xattr::get(path, name).ok().flatten()
+}
+
+#[cfg(not(unix))]
+fn get(_path: &Path, _name: &str) -> Option<Vec<u8>> {
+    None
+}